@@ -0,0 +1,920 @@
+use figment::Figment;
+use figment::providers::{Env, Format, Toml};
+use postgres::database::DatabaseConfig;
+use serde::{Deserialize, Deserializer};
+use sqlx::postgres::PgSslMode;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// JWT secret used when `JWT_SECRET` is not set. Only suitable for local development.
+const DEV_JWT_SECRET: &str = "your-secret-key-change-this-in-production";
+
+/// Token signing algorithms accepted in `JWT_ALGORITHM`.
+const JWT_ALGORITHMS: &[&str] = &["HS256", "RS256", "EdDSA"];
+
+/// Availability and notification retention (in days) must exceed this, since recent rows
+/// feed poll tuning and the monthly SMS budget.
+const MIN_RETENTION_DAYS: u32 = 31;
+
+/// Proxy URL schemes accepted in `RECREATION_GOV_PROXIES`.
+const PROXY_SCHEMES: &[&str] = &["http", "https", "socks5", "socks5h"];
+
+/// Pacing profiles accepted in `RECREATION_GOV_PACING`.
+const PACING_PROFILES: &[&str] = &["off", "steady", "human"];
+
+/// Fixture modes accepted in `RECREATION_GOV_FIXTURES`.
+const FIXTURE_MODES: &[&str] = &["off", "capture", "replay"];
+
+/// Geocoding providers accepted in `GEOCODING_PROVIDER`.
+const GEOCODING_PROVIDERS: &[&str] = &["none", "nominatim", "geonames"];
+
+/// Errors raised while loading or validating the configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// A source couldn't be read or a value has the wrong type.
+    #[error("Failed to load configuration: {0}")]
+    Load(Box<figment::Error>),
+
+    /// A value was read but isn't acceptable.
+    #[error("Invalid configuration: {0}")]
+    Invalid(String),
+}
+
+/// Application settings. Each field is read from the upper-case environment variable of
+/// the same name (e.g. `jwt_secret` from `JWT_SECRET`), which overrides the TOML file
+/// named by `CONFIG_FILE` (default `config.toml`, optional), which overrides the defaults.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// Public URL of the server, used in links sent to users
+    pub base_url: String,
+    /// Address the HTTP server listens on
+    pub bind_address: String,
+    /// Serve fixtures and log notifications instead of calling real providers
+    #[serde(deserialize_with = "deserialize_flag")]
+    pub sandbox_mode: bool,
+
+    /// Secret used to sign and verify JWTs with HS256
+    #[serde(deserialize_with = "deserialize_text")]
+    pub jwt_secret: String,
+    /// Token signing algorithm (`HS256` with `JWT_SECRET`, or `RS256`/`EdDSA` with the keys
+    /// in `JWT_KEYS_DIR`)
+    pub jwt_algorithm: String,
+    /// Directory of PEM private keys named `<key id>.pem`; every key verifies tokens and
+    /// is published in the JWKS
+    pub jwt_keys_dir: Option<String>,
+    /// ID of the key in `JWT_KEYS_DIR` that signs new tokens
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub jwt_active_key_id: Option<String>,
+    /// Key material that two-factor secrets are encrypted with; derived from the JWT
+    /// secret when unset. Changing it disables every enrolled authenticator.
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub two_factor_encryption_key: Option<String>,
+    /// Key material that links sent to users (unsubscribe links, calendar feeds) are
+    /// signed with; derived from the JWT secret when unset. Changing it breaks the links
+    /// already sent. `UNSUBSCRIBE_SIGNING_KEY` is still read as its old name.
+    #[serde(
+        alias = "unsubscribe_signing_key",
+        deserialize_with = "deserialize_optional_text"
+    )]
+    pub link_signing_key: Option<String>,
+
+    /// PostgreSQL connection URL
+    pub database_url: String,
+    /// Maximum number of connections kept by the pool
+    pub database_max_connections: u32,
+    /// Minimum number of idle connections kept open
+    pub database_min_connections: u32,
+    /// Seconds to wait for a free connection before failing
+    pub database_acquire_timeout_secs: u64,
+    /// Server-side timeout applied to every statement, in milliseconds
+    pub database_statement_timeout_ms: Option<u64>,
+    /// TLS mode (`disable`, `prefer`, `require`, `verify-ca`, `verify-full`)
+    pub database_ssl_mode: Option<String>,
+
+    /// Redis URL for shared verification tokens (requires the `redis` feature)
+    pub redis_url: Option<String>,
+
+    /// Sender address for outgoing email; `noreply@` the sending domain when unset
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub from_email: Option<String>,
+    /// Domain verified in SES that email is sent from; `FROM_EMAIL` must be on it
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub email_sending_domain: Option<String>,
+    /// Display name shown next to the sender address
+    #[serde(deserialize_with = "deserialize_text")]
+    pub from_name: String,
+    /// Address replies to outgoing email go to; replies go to the sender when unset
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub reply_to_email: Option<String>,
+    /// Whether the SES account is still in the SES sandbox
+    #[serde(deserialize_with = "deserialize_flag")]
+    pub ses_sandbox: bool,
+    /// Directory of email/SMS templates replacing the built-in ones with the same file
+    /// name (e.g. `layout.html`, `availability_alert.sms`)
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub notification_templates_dir: Option<String>,
+    /// Secret the SES/SNS delivery webhooks require in their `token` query parameter;
+    /// the webhooks are disabled when unset
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub delivery_webhook_token: Option<String>,
+    /// Comma-separated ARNs of the SNS topics the delivery webhooks accept messages from;
+    /// required with `DELIVERY_WEBHOOK_TOKEN`
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub delivery_webhook_topic_arns: Option<String>,
+
+    /// SMS messages that may be sent per calendar month (UTC) across all users; alerts
+    /// fall back to email once it's used up. 0 disables SMS.
+    pub sms_monthly_budget: u32,
+    /// SMS messages that may be sent to one user per calendar month (UTC)
+    pub sms_monthly_user_budget: u32,
+
+    /// Poll campgrounds of scans checking in soon at high frequency
+    #[serde(deserialize_with = "deserialize_flag")]
+    pub sniper_mode_enabled: bool,
+    /// Scans checking in within this many hours get sniper mode
+    pub sniper_window_hours: u32,
+    /// Poll interval of campgrounds in sniper mode, in minutes
+    pub sniper_poll_interval_minutes: u32,
+    /// Upstream requests per hour reserved for sniper mode; caps how many campgrounds
+    /// are in sniper mode at once
+    pub sniper_hourly_request_budget: u32,
+    /// Longest a campground whose polls keep failing waits between polls, in minutes:
+    /// each failure in a row doubles its poll interval up to this. 0 turns backoff off.
+    pub poll_error_backoff_max_minutes: u32,
+    /// Poll campgrounds with a release schedule every few seconds around their release
+    /// time
+    #[serde(deserialize_with = "deserialize_flag")]
+    pub release_burst_enabled: bool,
+    /// Seconds between polls during a release burst, from 15 to 30
+    pub release_burst_poll_interval_seconds: u32,
+    /// Minutes before a release time its burst starts
+    pub release_burst_lead_minutes: u32,
+    /// Minutes after a release time its burst lasts
+    pub release_burst_minutes: u32,
+
+    /// Days a scan runs before it expires unless extended; scans always end once their
+    /// check-in date passes. 0 lets scans run until check-in.
+    pub scan_max_age_days: u32,
+    /// Days before a scan expires that its owner is emailed so they can extend it
+    pub scan_expiry_warning_days: u32,
+
+    /// Address the scan watchdog emails when the scan system goes down or recovers
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub ops_alert_email: Option<String>,
+    /// URL the scan watchdog POSTs `{"text": ...}` to (e.g. a Slack incoming webhook)
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub ops_alert_webhook_url: Option<String>,
+    /// Minutes without a completed poll, while campgrounds are due, after which the scan
+    /// system counts as down; 0 disables the check
+    pub watchdog_poll_stale_minutes: u32,
+    /// Percentage of failed upstream API calls over the last 15 minutes above which the
+    /// scan system counts as down; 0 disables the check
+    pub watchdog_api_error_rate_percent: u32,
+    /// Calls recreation.gov blocked over the last 15 minutes, each starting a new session,
+    /// from which the scan system counts as down; 0 disables the check
+    pub watchdog_session_blocks: u32,
+
+    /// Days past nights of cached availability and availability history are kept; 0
+    /// keeps them forever
+    pub availability_retention_days: u32,
+    /// Days notification records are kept; 0 keeps them forever
+    pub notification_retention_days: u32,
+    /// Days scan timeline events are kept; 0 keeps them forever
+    pub scan_event_retention_days: u32,
+
+    /// API key for recreation.gov / RIDB, required outside sandbox mode
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub recreation_gov_api_key: Option<String>,
+    /// Comma-separated proxies (`http://`, `https://`, `socks5://` or `socks5h://`, with
+    /// optional `user:password@`) recreation.gov calls rotate through; direct when unset
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub recreation_gov_proxies: Option<String>,
+    /// Consecutive failed or blocked calls after which a proxy is taken out of rotation
+    pub recreation_gov_proxy_max_failures: u32,
+    /// How availability calls to recreation.gov are spaced out (`off`, `steady`, `human`)
+    pub recreation_gov_pacing: String,
+    /// Least time between two paced calls (availability and permit polls) of one
+    /// instance, in milliseconds; 0 leaves spacing to the pacing profile
+    pub recreation_gov_min_call_interval_ms: u64,
+    /// Seconds a month of campground availability fetched from recreation.gov is reused
+    /// before it's revalidated
+    pub recreation_gov_availability_cache_secs: u64,
+    /// Calls to recreation.gov and RIDB allowed in any rolling hour, across every
+    /// instance; 0 removes the cap
+    pub recreation_gov_hourly_request_budget: u32,
+    /// Availability and permit polls allowed per rolling hour; 0 leaves them capped only
+    /// by the overall budget
+    pub recreation_gov_hourly_availability_budget: u32,
+    /// Availability and permit lookups users make on demand (previews, live availability)
+    /// allowed per rolling hour; 0 leaves them capped only by the overall budget
+    pub recreation_gov_hourly_on_demand_budget: u32,
+    /// Facility, campsite and media lookups allowed per rolling hour; 0 leaves them
+    /// capped only by the overall budget
+    pub recreation_gov_hourly_details_budget: u32,
+    /// Recreation area and facility searches allowed per rolling hour; 0 leaves them
+    /// capped only by the overall budget
+    pub recreation_gov_hourly_search_budget: u32,
+    /// Whether raw recreation.gov responses are saved to (`capture`) or served from
+    /// (`replay`) `RECREATION_GOV_FIXTURES_DIR`, or neither (`off`)
+    pub recreation_gov_fixtures: String,
+    /// Directory recreation.gov response fixtures are kept in
+    pub recreation_gov_fixtures_dir: String,
+
+    /// Reverse geocoding provider (`none`, `nominatim`, `geonames`)
+    pub geocoding_provider: String,
+    /// Base URL override for the geocoding provider
+    pub geocoding_base_url: Option<String>,
+    /// GeoNames account name, required by the `geonames` provider
+    #[serde(deserialize_with = "deserialize_optional_text")]
+    pub geonames_username: Option<String>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        let database = DatabaseConfig::default();
+
+        Self {
+            base_url: "http://localhost:8080".to_string(),
+            bind_address: "0.0.0.0:8080".to_string(),
+            sandbox_mode: false,
+            jwt_secret: DEV_JWT_SECRET.to_string(),
+            jwt_algorithm: "HS256".to_string(),
+            jwt_keys_dir: None,
+            jwt_active_key_id: None,
+            two_factor_encryption_key: None,
+            link_signing_key: None,
+            database_url: database.url,
+            database_max_connections: database.max_connections,
+            database_min_connections: database.min_connections,
+            database_acquire_timeout_secs: database.acquire_timeout.as_secs(),
+            database_statement_timeout_ms: None,
+            database_ssl_mode: None,
+            redis_url: None,
+            from_email: None,
+            email_sending_domain: None,
+            from_name: "CampTracker".to_string(),
+            reply_to_email: None,
+            ses_sandbox: false,
+            notification_templates_dir: None,
+            delivery_webhook_token: None,
+            delivery_webhook_topic_arns: None,
+            sms_monthly_budget: 1000,
+            sms_monthly_user_budget: 50,
+            sniper_mode_enabled: true,
+            sniper_window_hours: 72,
+            sniper_poll_interval_minutes: 2,
+            sniper_hourly_request_budget: 300,
+            poll_error_backoff_max_minutes: 120,
+            release_burst_enabled: true,
+            release_burst_poll_interval_seconds: 20,
+            release_burst_lead_minutes: 1,
+            release_burst_minutes: 5,
+            scan_max_age_days: 180,
+            scan_expiry_warning_days: 3,
+            ops_alert_email: None,
+            ops_alert_webhook_url: None,
+            watchdog_poll_stale_minutes: 30,
+            watchdog_api_error_rate_percent: 50,
+            watchdog_session_blocks: 10,
+            availability_retention_days: 180,
+            notification_retention_days: 365,
+            scan_event_retention_days: 90,
+            recreation_gov_api_key: None,
+            recreation_gov_proxies: None,
+            recreation_gov_proxy_max_failures: 3,
+            recreation_gov_pacing: "off".to_string(),
+            recreation_gov_min_call_interval_ms: 0,
+            recreation_gov_availability_cache_secs: 300,
+            recreation_gov_hourly_request_budget: 1000,
+            recreation_gov_hourly_availability_budget: 700,
+            recreation_gov_hourly_on_demand_budget: 150,
+            recreation_gov_hourly_details_budget: 200,
+            recreation_gov_hourly_search_budget: 100,
+            recreation_gov_fixtures: "off".to_string(),
+            recreation_gov_fixtures_dir: "fixtures/recreation_gov".to_string(),
+            geocoding_provider: "none".to_string(),
+            geocoding_base_url: None,
+            geonames_username: None,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Loads and validates the configuration from the TOML file and environment.
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = std::env::var("CONFIG_FILE").unwrap_or_else(|_| "config.toml".to_string());
+
+        let figment = Figment::new().merge(Toml::file(path)).merge(Env::raw());
+
+        let mut config: Self = figment
+            .extract()
+            .map_err(|e| ConfigError::Load(Box::new(e)))?;
+
+        config.normalize();
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Returns the connection pool settings for the database.
+    pub fn database(&self) -> Result<DatabaseConfig, ConfigError> {
+        let ssl_mode = self
+            .database_ssl_mode
+            .as_deref()
+            .map(PgSslMode::from_str)
+            .transpose()
+            .map_err(|e| ConfigError::Invalid(format!("DATABASE_SSL_MODE: {}", e)))?;
+
+        Ok(DatabaseConfig {
+            url: self.database_url.clone(),
+            max_connections: self.database_max_connections,
+            min_connections: self.database_min_connections,
+            acquire_timeout: Duration::from_secs(self.database_acquire_timeout_secs),
+            statement_timeout: self
+                .database_statement_timeout_ms
+                .map(Duration::from_millis),
+            ssl_mode,
+        })
+    }
+
+    /// Key material two-factor secrets are encrypted with: `TWO_FACTOR_ENCRYPTION_KEY`,
+    /// or the JWT secret when it isn't set.
+    pub fn two_factor_key_material(&self) -> &str {
+        self.two_factor_encryption_key
+            .as_deref()
+            .unwrap_or(&self.jwt_secret)
+    }
+
+    /// Key material links sent to users are signed with: `LINK_SIGNING_KEY`, or the JWT
+    /// secret when it isn't set.
+    pub fn link_key_material(&self) -> &str {
+        self.link_signing_key.as_deref().unwrap_or(&self.jwt_secret)
+    }
+
+    /// Address outgoing email is sent from: `FROM_EMAIL`, or `noreply@` the sending
+    /// domain when it isn't set. `None` when neither is configured.
+    pub fn sender_address(&self) -> Option<String> {
+        self.from_email.clone().or_else(|| {
+            self.email_sending_domain
+                .as_ref()
+                .map(|domain| format!("noreply@{}", domain))
+        })
+    }
+
+    /// SNS topic ARNs listed in `DELIVERY_WEBHOOK_TOPIC_ARNS`
+    pub fn delivery_webhook_topic_arn_list(&self) -> Vec<&str> {
+        self.delivery_webhook_topic_arns
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|arn| !arn.is_empty())
+            .collect()
+    }
+
+    /// Proxy URLs listed in `RECREATION_GOV_PROXIES`
+    pub fn recreation_gov_proxy_urls(&self) -> Vec<&str> {
+        self.recreation_gov_proxies
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .collect()
+    }
+
+    /// Treats empty optional values as unset and trims trailing slashes from URLs.
+    fn normalize(&mut self) {
+        for value in [
+            &mut self.database_ssl_mode,
+            &mut self.redis_url,
+            &mut self.from_email,
+            &mut self.email_sending_domain,
+            &mut self.reply_to_email,
+            &mut self.jwt_keys_dir,
+            &mut self.jwt_active_key_id,
+            &mut self.two_factor_encryption_key,
+            &mut self.link_signing_key,
+            &mut self.delivery_webhook_token,
+            &mut self.delivery_webhook_topic_arns,
+            &mut self.ops_alert_email,
+            &mut self.ops_alert_webhook_url,
+            &mut self.recreation_gov_api_key,
+            &mut self.recreation_gov_proxies,
+            &mut self.geocoding_base_url,
+            &mut self.geonames_username,
+        ] {
+            if value.as_deref().is_some_and(|v| v.trim().is_empty()) {
+                *value = None;
+            }
+        }
+
+        self.base_url = self.base_url.trim_end_matches('/').to_string();
+        self.from_name = self.from_name.trim().to_string();
+        for address in [&mut self.from_email, &mut self.reply_to_email] {
+            *address = address.as_deref().map(|value| value.trim().to_lowercase());
+        }
+        self.email_sending_domain = self.email_sending_domain.as_deref().map(|domain| {
+            domain
+                .trim()
+                .trim_start_matches('@')
+                .trim_end_matches('.')
+                .to_lowercase()
+        });
+        self.geocoding_provider = self.geocoding_provider.to_lowercase();
+        self.recreation_gov_pacing = self.recreation_gov_pacing.to_lowercase();
+        self.recreation_gov_fixtures = self.recreation_gov_fixtures.to_lowercase();
+    }
+
+    /// Checks the sender of outgoing email. Outside sandbox mode a sender is required, and
+    /// it must be on the sending domain when one is configured, since SES only sends
+    /// from verified identities and receivers check the domain's SPF and DKIM records.
+    fn validate_email_sender(&self) -> Result<(), ConfigError> {
+        if let Some(domain) = &self.email_sending_domain
+            && (!domain.contains('.') || !domain.chars().all(is_domain_char))
+        {
+            return Err(ConfigError::Invalid(format!(
+                "EMAIL_SENDING_DOMAIN is not a domain name: '{}'",
+                domain
+            )));
+        }
+
+        for (name, address) in [
+            ("FROM_EMAIL", &self.from_email),
+            ("REPLY_TO_EMAIL", &self.reply_to_email),
+        ] {
+            if let Some(address) = address
+                && !is_email_address(address)
+            {
+                return Err(ConfigError::Invalid(format!(
+                    "{} is not an email address: '{}'",
+                    name, address
+                )));
+            }
+        }
+
+        if let (Some(from), Some(domain)) = (&self.from_email, &self.email_sending_domain) {
+            let from_domain = from.rsplit_once('@').map_or("", |(_, domain)| domain);
+            if from_domain != domain && !from_domain.ends_with(&format!(".{}", domain)) {
+                return Err(ConfigError::Invalid(format!(
+                    "FROM_EMAIL must be on EMAIL_SENDING_DOMAIN ({}), got '{}'",
+                    domain, from
+                )));
+            }
+        }
+
+        if self.from_name.is_empty() || self.from_name.contains(['<', '>', '"', '\\']) {
+            return Err(ConfigError::Invalid(format!(
+                "FROM_NAME must be non-empty and can't contain <, >, \\ or \", got '{}'",
+                self.from_name
+            )));
+        }
+        if self.from_name.chars().any(char::is_control) {
+            return Err(ConfigError::Invalid(
+                "FROM_NAME can't contain control characters".to_string(),
+            ));
+        }
+
+        if !self.sandbox_mode {
+            if self.sender_address().is_none() {
+                return Err(ConfigError::Invalid(
+                    "FROM_EMAIL or EMAIL_SENDING_DOMAIN is required unless SANDBOX_MODE is \
+                     enabled"
+                        .to_string(),
+                ));
+            }
+            if self.email_sending_domain.is_none() {
+                log::warn!(
+                    "⚠️ EMAIL_SENDING_DOMAIN is not set; make sure the domain of FROM_EMAIL is \
+                     verified in SES"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks values that can't be expressed in the types alone.
+    fn validate(&self) -> Result<(), ConfigError> {
+        if !self.base_url.starts_with("http://") && !self.base_url.starts_with("https://") {
+            return Err(ConfigError::Invalid(format!(
+                "BASE_URL must start with http:// or https://, got '{}'",
+                self.base_url
+            )));
+        }
+        if base_url_host(&self.base_url).is_empty() || self.base_url.contains(['?', '#', ' ']) {
+            return Err(ConfigError::Invalid(format!(
+                "BASE_URL must be a URL without a query or fragment, got '{}'",
+                self.base_url
+            )));
+        }
+        if !self.sandbox_mode && is_local_host(base_url_host(&self.base_url)) {
+            log::warn!(
+                "⚠️ BASE_URL is {}, so links in emails and texts only work on this machine",
+                self.base_url
+            );
+        }
+
+        if self.bind_address.trim().is_empty() {
+            return Err(ConfigError::Invalid("BIND_ADDRESS is empty".to_string()));
+        }
+
+        if self.jwt_secret.is_empty() {
+            return Err(ConfigError::Invalid("JWT_SECRET is empty".to_string()));
+        }
+        if !JWT_ALGORITHMS.contains(&self.jwt_algorithm.as_str()) {
+            return Err(ConfigError::Invalid(format!(
+                "JWT_ALGORITHM must be one of {}, got '{}'",
+                JWT_ALGORITHMS.join(", "),
+                self.jwt_algorithm
+            )));
+        }
+        if self.jwt_algorithm == "HS256" {
+            if self.jwt_secret == DEV_JWT_SECRET && !self.sandbox_mode {
+                return Err(ConfigError::Invalid(
+                    "JWT_SECRET is the development secret, which anyone can sign tokens \
+                     with; set JWT_SECRET unless SANDBOX_MODE is enabled"
+                        .to_string(),
+                ));
+            }
+        } else if self.jwt_keys_dir.is_none() || self.jwt_active_key_id.is_none() {
+            return Err(ConfigError::Invalid(format!(
+                "JWT_KEYS_DIR and JWT_ACTIVE_KEY_ID are required with JWT_ALGORITHM={}",
+                self.jwt_algorithm
+            )));
+        }
+
+        if self.jwt_secret == DEV_JWT_SECRET && !self.sandbox_mode {
+            let unset: Vec<&str> = [
+                ("TWO_FACTOR_ENCRYPTION_KEY", &self.two_factor_encryption_key),
+                ("LINK_SIGNING_KEY", &self.link_signing_key),
+            ]
+            .into_iter()
+            .filter(|(_, key)| key.is_none())
+            .map(|(name, _)| name)
+            .collect();
+
+            if !unset.is_empty() {
+                return Err(ConfigError::Invalid(format!(
+                    "{} would fall back to the development JWT secret; set {} or JWT_SECRET",
+                    unset.join(" and "),
+                    if unset.len() > 1 { "them" } else { "it" }
+                )));
+            }
+        }
+        if self
+            .two_factor_encryption_key
+            .as_ref()
+            .is_some_and(|key| key.len() < 32)
+        {
+            return Err(ConfigError::Invalid(
+                "TWO_FACTOR_ENCRYPTION_KEY must be at least 32 characters".to_string(),
+            ));
+        }
+        if self
+            .link_signing_key
+            .as_ref()
+            .is_some_and(|key| key.len() < 32)
+        {
+            return Err(ConfigError::Invalid(
+                "LINK_SIGNING_KEY must be at least 32 characters".to_string(),
+            ));
+        }
+        if self
+            .delivery_webhook_token
+            .as_ref()
+            .is_some_and(|token| token.len() < 32)
+        {
+            return Err(ConfigError::Invalid(
+                "DELIVERY_WEBHOOK_TOKEN must be at least 32 characters".to_string(),
+            ));
+        }
+        if self.delivery_webhook_token.is_some()
+            && self.delivery_webhook_topic_arn_list().is_empty()
+        {
+            return Err(ConfigError::Invalid(
+                "DELIVERY_WEBHOOK_TOPIC_ARNS is required with DELIVERY_WEBHOOK_TOKEN, since any \
+                 AWS account can sign SNS messages"
+                    .to_string(),
+            ));
+        }
+        if let Some(arn) = self
+            .delivery_webhook_topic_arn_list()
+            .into_iter()
+            .find(|arn| !arn.starts_with("arn:") || arn.split(':').nth(2) != Some("sns"))
+        {
+            return Err(ConfigError::Invalid(format!(
+                "DELIVERY_WEBHOOK_TOPIC_ARNS entries must be SNS topic ARNs, got '{}'",
+                arn
+            )));
+        }
+
+        if self
+            .ops_alert_webhook_url
+            .as_ref()
+            .is_some_and(|url| !url.starts_with("https://") && !url.starts_with("http://"))
+        {
+            return Err(ConfigError::Invalid(
+                "OPS_ALERT_WEBHOOK_URL must start with http:// or https://".to_string(),
+            ));
+        }
+        if self.watchdog_api_error_rate_percent > 100 {
+            return Err(ConfigError::Invalid(
+                "WATCHDOG_API_ERROR_RATE_PERCENT must be at most 100".to_string(),
+            ));
+        }
+        if !(15..=30).contains(&self.release_burst_poll_interval_seconds) {
+            return Err(ConfigError::Invalid(
+                "RELEASE_BURST_POLL_INTERVAL_SECONDS must be between 15 and 30".to_string(),
+            ));
+        }
+
+        if self.database_max_connections == 0 {
+            return Err(ConfigError::Invalid(
+                "DATABASE_MAX_CONNECTIONS must be at least 1".to_string(),
+            ));
+        }
+        if self.database_min_connections > self.database_max_connections {
+            return Err(ConfigError::Invalid(
+                "DATABASE_MIN_CONNECTIONS can't exceed DATABASE_MAX_CONNECTIONS".to_string(),
+            ));
+        }
+        self.database()?;
+
+        self.validate_email_sender()?;
+
+        if self.sniper_poll_interval_minutes == 0 || self.sniper_poll_interval_minutes > 60 {
+            return Err(ConfigError::Invalid(
+                "SNIPER_POLL_INTERVAL_MINUTES must be between 1 and 60".to_string(),
+            ));
+        }
+
+        if self.scan_max_age_days > 0 && self.scan_expiry_warning_days >= self.scan_max_age_days {
+            return Err(ConfigError::Invalid(
+                "SCAN_EXPIRY_WARNING_DAYS must be less than SCAN_MAX_AGE_DAYS".to_string(),
+            ));
+        }
+
+        // Time-to-book tuning reads the last 30 days of availability history, and the SMS
+        // budget counts this month's notifications
+        if (1..=MIN_RETENTION_DAYS).contains(&self.availability_retention_days) {
+            return Err(ConfigError::Invalid(format!(
+                "AVAILABILITY_RETENTION_DAYS must be 0 (keep forever) or more than {}",
+                MIN_RETENTION_DAYS
+            )));
+        }
+        if (1..=MIN_RETENTION_DAYS).contains(&self.notification_retention_days) {
+            return Err(ConfigError::Invalid(format!(
+                "NOTIFICATION_RETENTION_DAYS must be 0 (keep forever) or more than {}",
+                MIN_RETENTION_DAYS
+            )));
+        }
+
+        if self.recreation_gov_api_key.is_none()
+            && !self.sandbox_mode
+            && self.recreation_gov_fixtures != "replay"
+        {
+            return Err(ConfigError::Invalid(
+                "RECREATION_GOV_API_KEY is required unless SANDBOX_MODE is enabled or \
+                 RECREATION_GOV_FIXTURES=replay"
+                    .to_string(),
+            ));
+        }
+
+        if let Some(url) = self.recreation_gov_proxy_urls().into_iter().find(|url| {
+            !PROXY_SCHEMES
+                .iter()
+                .any(|scheme| url.starts_with(&format!("{}://", scheme)))
+        }) {
+            return Err(ConfigError::Invalid(format!(
+                "RECREATION_GOV_PROXIES entries must start with one of {}, got '{}'",
+                PROXY_SCHEMES
+                    .iter()
+                    .map(|scheme| format!("{}://", scheme))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                url.split_once('@').map_or(url, |(_, host)| host)
+            )));
+        }
+        if self.recreation_gov_proxy_max_failures == 0 {
+            return Err(ConfigError::Invalid(
+                "RECREATION_GOV_PROXY_MAX_FAILURES must be at least 1".to_string(),
+            ));
+        }
+
+        if !PACING_PROFILES.contains(&self.recreation_gov_pacing.as_str()) {
+            return Err(ConfigError::Invalid(format!(
+                "RECREATION_GOV_PACING must be one of {}, got '{}'",
+                PACING_PROFILES.join(", "),
+                self.recreation_gov_pacing
+            )));
+        }
+
+        if !FIXTURE_MODES.contains(&self.recreation_gov_fixtures.as_str()) {
+            return Err(ConfigError::Invalid(format!(
+                "RECREATION_GOV_FIXTURES must be one of {}, got '{}'",
+                FIXTURE_MODES.join(", "),
+                self.recreation_gov_fixtures
+            )));
+        }
+        if self.recreation_gov_fixtures_dir.trim().is_empty() {
+            return Err(ConfigError::Invalid(
+                "RECREATION_GOV_FIXTURES_DIR is empty".to_string(),
+            ));
+        }
+
+        if !GEOCODING_PROVIDERS.contains(&self.geocoding_provider.as_str()) {
+            return Err(ConfigError::Invalid(format!(
+                "GEOCODING_PROVIDER must be one of {}, got '{}'",
+                GEOCODING_PROVIDERS.join(", "),
+                self.geocoding_provider
+            )));
+        }
+        if self.geocoding_provider == "geonames" && self.geonames_username.is_none() {
+            return Err(ConfigError::Invalid(
+                "GEONAMES_USERNAME is required by the geonames provider".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Host of a URL, without its port.
+fn base_url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split('/').next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+
+    if host.starts_with('[') {
+        host.split_inclusive(']').next().unwrap_or(host)
+    } else {
+        host.split(':').next().unwrap_or(host)
+    }
+}
+
+/// Returns true for hosts only reachable from the machine itself.
+fn is_local_host(host: &str) -> bool {
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]" | "0.0.0.0") || host.ends_with(".localhost")
+}
+
+/// Characters allowed in a domain name.
+fn is_domain_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '.'
+}
+
+/// A single address such as `alerts@example.com`, with nothing that could spill into
+/// other email headers.
+fn is_email_address(address: &str) -> bool {
+    address.rsplit_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty()
+            && domain.contains('.')
+            && domain.chars().all(is_domain_char)
+            && !local
+                .chars()
+                .any(|c| c.is_whitespace() || c.is_control() || "<>,;:\"@()[]\\".contains(c))
+    })
+}
+
+/// Accepts `true`/`false`, `1`/`0`, and `yes`/`no` for boolean settings.
+fn deserialize_flag<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Int(i64),
+        Text(String),
+    }
+
+    match Flag::deserialize(deserializer)? {
+        Flag::Bool(value) => Ok(value),
+        Flag::Int(value) => Ok(value != 0),
+        Flag::Text(value) => match value.to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Ok(true),
+            "" | "0" | "false" | "no" | "off" => Ok(false),
+            other => Err(serde::de::Error::custom(format!(
+                "expected a boolean, got '{}'",
+                other
+            ))),
+        },
+    }
+}
+
+/// Scalar setting value as parsed by figment, which turns `123` into a number and
+/// `true` into a boolean even for settings that are free text.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Scalar {
+    Text(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl Scalar {
+    fn into_text(self) -> String {
+        match self {
+            Scalar::Text(value) => value,
+            Scalar::Int(value) => value.to_string(),
+            Scalar::Float(value) => value.to_string(),
+            Scalar::Bool(value) => value.to_string(),
+        }
+    }
+}
+
+/// Reads a free-text setting, keeping values like `123456` as text.
+fn deserialize_text<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Scalar::deserialize(deserializer)?.into_text())
+}
+
+/// Reads an optional free-text setting, keeping values like `123456` as text.
+fn deserialize_optional_text<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<Scalar>::deserialize(deserializer)?.map(Scalar::into_text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn production_config() -> AppConfig {
+        AppConfig {
+            base_url: "https://camptracker.example.com".to_string(),
+            jwt_secret: "a-production-secret-that-nobody-else-knows".to_string(),
+            from_email: Some("alerts@camptracker.example.com".to_string()),
+            email_sending_domain: Some("camptracker.example.com".to_string()),
+            recreation_gov_api_key: Some("ridb-key".to_string()),
+            ..AppConfig::default()
+        }
+    }
+
+    #[test]
+    fn accepts_a_production_jwt_secret() {
+        production_config().validate().unwrap();
+    }
+
+    #[test]
+    fn refuses_the_development_jwt_secret_outside_sandbox_mode() {
+        let config = AppConfig {
+            jwt_secret: DEV_JWT_SECRET.to_string(),
+            // Even with both derived keys set, tokens could be forged with the dev secret
+            two_factor_encryption_key: Some("k".repeat(32)),
+            link_signing_key: Some("l".repeat(32)),
+            ..production_config()
+        };
+
+        let error = config.validate().unwrap_err();
+        assert!(error.to_string().contains("JWT_SECRET"), "{}", error);
+    }
+
+    #[test]
+    fn allows_the_development_jwt_secret_in_sandbox_mode() {
+        let config = AppConfig {
+            sandbox_mode: true,
+            jwt_secret: DEV_JWT_SECRET.to_string(),
+            ..production_config()
+        };
+
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn delivery_webhooks_need_their_topics() {
+        let token = Some("t".repeat(32));
+        let without_topics = AppConfig {
+            delivery_webhook_token: token.clone(),
+            ..production_config()
+        };
+        assert!(without_topics.validate().is_err());
+
+        let not_an_arn = AppConfig {
+            delivery_webhook_token: token.clone(),
+            delivery_webhook_topic_arns: Some("ses-feedback".to_string()),
+            ..production_config()
+        };
+        assert!(not_an_arn.validate().is_err());
+
+        let with_topics = AppConfig {
+            delivery_webhook_token: token,
+            delivery_webhook_topic_arns: Some(
+                "arn:aws:sns:us-west-2:123456789012:ses-feedback, \
+                 arn:aws:sns:us-west-2:123456789012:sms-status"
+                    .to_string(),
+            ),
+            ..production_config()
+        };
+        with_topics.validate().unwrap();
+        assert_eq!(with_topics.delivery_webhook_topic_arn_list().len(), 2);
+    }
+}
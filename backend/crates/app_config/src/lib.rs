@@ -0,0 +1,9 @@
+//! # App Config
+//!
+//! This crate provides the typed application configuration. It is loaded once at
+//! startup from defaults, an optional TOML file, and environment variables, validated,
+//! and then handed to the services and handlers that need it.
+
+/// Application configuration and its loader.
+mod config;
+pub use config::*;
@@ -0,0 +1,61 @@
+use std::future::Future;
+use uuid::Uuid;
+
+use crate::service::AuthService;
+use crate::two_factor::TwoFactorService;
+use crate::types::{AuthError, SignUpRequest, User};
+
+/// The account operations signup and login make, implemented by [`AuthService`] and, in
+/// handler tests that mustn't touch the database, by test doubles.
+pub trait AuthApi: Send + Sync + 'static {
+    /// Creates a user, failing with [`AuthError::EmailExists`] for a taken email
+    fn create_user(
+        &self,
+        request: &SignUpRequest,
+    ) -> impl Future<Output = Result<User, AuthError>> + Send;
+
+    /// The user with this email and password, or [`AuthError::InvalidCredentials`]
+    fn verify_password(
+        &self,
+        email: &str,
+        password: &str,
+    ) -> impl Future<Output = Result<User, AuthError>> + Send;
+
+    /// Stores a session for a hashed refresh token and returns its ID
+    fn create_session(
+        &self,
+        user_id: &Uuid,
+        refresh_token_hash: &str,
+    ) -> impl Future<Output = Result<Uuid, AuthError>> + Send;
+}
+
+impl AuthApi for AuthService {
+    async fn create_user(&self, request: &SignUpRequest) -> Result<User, AuthError> {
+        AuthService::create_user(self, request).await
+    }
+
+    async fn verify_password(&self, email: &str, password: &str) -> Result<User, AuthError> {
+        AuthService::verify_password(self, email, password).await
+    }
+
+    async fn create_session(
+        &self,
+        user_id: &Uuid,
+        refresh_token_hash: &str,
+    ) -> Result<Uuid, AuthError> {
+        AuthService::create_session(self, user_id, refresh_token_hash).await
+    }
+}
+
+/// The two-factor check login makes before issuing tokens, implemented by
+/// [`TwoFactorService`] and by test doubles in handler tests.
+pub trait TwoFactorApi: Send + Sync + 'static {
+    /// Whether the user has enabled two-factor authentication
+    fn is_enabled(&self, user_id: &Uuid) -> impl Future<Output = Result<bool, AuthError>> + Send;
+}
+
+impl TwoFactorApi for TwoFactorService {
+    async fn is_enabled(&self, user_id: &Uuid) -> Result<bool, AuthError> {
+        TwoFactorService::is_enabled(self, user_id).await
+    }
+}
@@ -3,11 +3,17 @@
 //! This crate provides authentication services for the application.
 //! //! It includes JWT token handling, middleware for request authentication, and service definitions.
 
+/// The account operations handlers make, as a trait tests can mock.
+pub mod auth_api;
 /// JWT token handling and user authentication services.
 pub mod jwt;
 /// Middleware for request authentication and user session management.
 pub mod middleware;
+/// Organizations (family accounts) whose members share scans.
+pub mod organizations;
 /// Service definitions for user management and authentication operations.
 pub mod service;
+/// TOTP two-factor authentication with encrypted authenticator secrets.
+pub mod two_factor;
 /// Types and structures used in authentication services.
 pub mod types;
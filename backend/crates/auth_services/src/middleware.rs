@@ -4,6 +4,7 @@ use actix_web::{
     dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
 };
 use futures_util::future::LocalBoxFuture;
+use i18n::{Locale, current_locale, message, with_locale};
 use std::{
     future::{Ready, ready},
     rc::Rc,
@@ -13,8 +14,18 @@ use uuid::Uuid;
 use crate::jwt::JwtService;
 
 /// Middleware for handling authentication by verifying JWT tokens
-/// and extracting user information from the request.
-pub struct AuthMiddleware;
+/// and extracting user information from the request. The request is then handled in the
+/// user's preferred locale when the token carries one.
+pub struct AuthMiddleware {
+    jwt_service: JwtService,
+}
+
+impl AuthMiddleware {
+    /// Creates the middleware, verifying tokens with the given JWT service.
+    pub fn new(jwt_service: JwtService) -> Self {
+        Self { jwt_service }
+    }
+}
 
 impl<S, B> Transform<S, ServiceRequest> for AuthMiddleware
 where
@@ -31,7 +42,7 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(AuthMiddlewareService {
             service: Rc::new(service),
-            jwt_service: JwtService::new(),
+            jwt_service: self.jwt_service.clone(),
         }))
     }
 }
@@ -77,29 +88,40 @@ where
                 None => {
                     let response = HttpResponse::Unauthorized().json(serde_json::json!({
                         "error": "missing_token",
-                        "message": "Authorization token is required"
+                        "message": message(current_locale(), "missing-token")
                     }));
                     return Ok(req.into_response(response).map_into_right_body());
                 }
             };
 
-            // Verify token and extract user ID
-            let user_id = match jwt_service.extract_user_id_from_token(token) {
-                Ok(user_id) => user_id,
-                Err(_) => {
+            // Verify token and extract user ID, role and locale
+            let claims = jwt_service.verify_token(token).ok().and_then(|claims| {
+                let locale = claims
+                    .locale
+                    .and_then(|locale| locale.parse::<Locale>().ok());
+                Uuid::parse_str(&claims.sub)
+                    .ok()
+                    .map(|user_id| (user_id, claims.role, locale))
+            });
+
+            let (user_id, role, locale) = match claims {
+                Some(claims) => claims,
+                None => {
                     let response = HttpResponse::Unauthorized().json(serde_json::json!({
                         "error": "invalid_token",
-                        "message": "Invalid or expired token"
+                        "message": message(current_locale(), "invalid-token")
                     }));
                     return Ok(req.into_response(response).map_into_right_body());
                 }
             };
 
-            // Add user ID to request extensions
+            // Add user ID and role to request extensions
             req.extensions_mut().insert(user_id);
+            req.extensions_mut().insert(UserRole(role));
 
-            // Continue with the request
-            let res = service.call(req).await?;
+            // Continue with the request, in the user's locale
+            let locale = locale.unwrap_or_else(current_locale);
+            let res = with_locale(locale, service.call(req)).await?;
             Ok(res.map_into_left_body())
         })
     }
@@ -110,6 +132,10 @@ pub fn extract_user_id(req: &ServiceRequest) -> Option<Uuid> {
     req.extensions().get::<Uuid>().copied()
 }
 
+/// Role of the authenticated user, taken from the token claims
+#[derive(Debug, Clone)]
+pub struct UserRole(pub String);
+
 /// Custom extractor for authenticated user ID
 pub struct AuthenticatedUser(pub Uuid);
 
@@ -128,3 +154,27 @@ impl actix_web::FromRequest for AuthenticatedUser {
         })
     }
 }
+
+/// Custom extractor for an authenticated user with the admin role
+pub struct AdminUser(pub Uuid);
+
+impl actix_web::FromRequest for AdminUser {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let extensions = req.extensions();
+        let user_id = extensions.get::<Uuid>().copied();
+        let is_admin = extensions
+            .get::<UserRole>()
+            .is_some_and(|role| role.0 == "admin");
+
+        ready(match user_id {
+            Some(id) if is_admin => Ok(AdminUser(id)),
+            Some(_) => Err(actix_web::error::ErrorForbidden("Admin access required")),
+            None => Err(actix_web::error::ErrorUnauthorized(
+                "User not authenticated",
+            )),
+        })
+    }
+}
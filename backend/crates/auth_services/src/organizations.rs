@@ -0,0 +1,516 @@
+use actix_web::{Error, HttpMessage, web};
+use chrono::{DateTime, Duration, Utc};
+use futures_util::future::LocalBoxFuture;
+use i18n::{current_locale, message};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+use validator::Validate;
+
+/// How long an invitation can be accepted
+const INVITATION_TTL_DAYS: i64 = 7;
+
+/// Role of the member who created the organization
+pub const OWNER_ROLE: &str = "owner";
+
+/// Role of invited members
+pub const MEMBER_ROLE: &str = "member";
+
+/// An organization (e.g. a family) whose members share scans
+#[derive(Debug, Clone, Serialize)]
+pub struct Organization {
+    /// Unique identifier for the organization
+    pub id: Uuid,
+    /// Display name
+    pub name: String,
+    /// User who created the organization and manages its members
+    pub owner_id: Uuid,
+    /// Role of the requesting user in the organization
+    pub role: String,
+    /// When the organization was created
+    pub created_at: DateTime<Utc>,
+}
+
+/// A member of an organization
+#[derive(Debug, Clone, Serialize)]
+pub struct OrganizationMemberInfo {
+    /// ID of the member
+    pub user_id: Uuid,
+    /// Name of the member
+    pub name: String,
+    /// Email address of the member
+    pub email: String,
+    /// Role in the organization (`owner` or `member`)
+    pub role: String,
+    /// When the member joined
+    pub joined_at: DateTime<Utc>,
+}
+
+/// An invitation that hasn't been accepted yet
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingInvitation {
+    /// Unique identifier for the invitation
+    pub id: Uuid,
+    /// Invited email address
+    pub email: String,
+    /// When the invitation expires
+    pub expires_at: DateTime<Utc>,
+}
+
+/// An organization with its members and pending invitations
+#[derive(Debug, Serialize)]
+pub struct OrganizationDetails {
+    /// The organization
+    #[serde(flatten)]
+    pub organization: Organization,
+    /// Current members
+    pub members: Vec<OrganizationMemberInfo>,
+    /// Invitations waiting to be accepted
+    pub invitations: Vec<PendingInvitation>,
+}
+
+/// Request structure for creating an organization
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateOrganizationRequest {
+    /// Display name
+    #[validate(length(min = 1, max = 255, message = "Name is required"))]
+    pub name: String,
+}
+
+/// Request structure for inviting a member
+#[derive(Debug, Deserialize, Validate)]
+pub struct InviteMemberRequest {
+    /// Email address to send the invitation to
+    #[validate(email(message = "Please enter a valid email"))]
+    pub email: String,
+}
+
+/// Request structure for accepting an invitation
+#[derive(Debug, Deserialize)]
+pub struct AcceptInvitationRequest {
+    /// Token from the invitation email
+    pub token: String,
+}
+
+/// An invitation that was just created
+#[derive(Debug, Clone)]
+pub struct CreatedInvitation {
+    /// Organization the invitation is for
+    pub organization_name: String,
+    /// Invited email address
+    pub email: String,
+    /// When the invitation expires
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Custom error type for organization operations
+#[derive(Debug, thiserror::Error)]
+pub enum OrganizationError {
+    /// Database error
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    /// Validation error
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    /// The organization doesn't exist or the user isn't a member
+    #[error("Organization not found")]
+    NotFound,
+
+    /// Only the owner may do this
+    #[error("Only the organization owner can do this")]
+    OwnerRequired,
+
+    /// The invitation doesn't exist, has expired, or is for another email address
+    #[error("Invalid or expired invitation")]
+    InvalidInvitation,
+}
+
+impl actix_web::ResponseError for OrganizationError {
+    fn error_response(&self) -> actix_web::HttpResponse {
+        use actix_web::HttpResponse;
+
+        let locale = current_locale();
+
+        match self {
+            OrganizationError::Validation(msg) => {
+                HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "validation_error",
+                    "message": msg
+                }))
+            }
+            OrganizationError::NotFound => HttpResponse::NotFound().json(serde_json::json!({
+                "error": "organization_not_found",
+                "message": message(locale, "organization-not-found")
+            })),
+            OrganizationError::OwnerRequired => HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "owner_required",
+                "message": message(locale, "owner-required")
+            })),
+            OrganizationError::InvalidInvitation => {
+                HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "invalid_invitation",
+                    "message": message(locale, "invalid-invitation")
+                }))
+            }
+            OrganizationError::Database(_) => {
+                HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "internal_error",
+                    "message": message(locale, "internal-error")
+                }))
+            }
+        }
+    }
+}
+
+/// Service for organizations, their members, and invitations
+pub struct OrganizationService {
+    pool: PgPool,
+}
+
+impl OrganizationService {
+    /// Creates a new instance of `OrganizationService` with the provided database connection pool
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates an organization owned by the user
+    pub async fn create_organization(
+        &self,
+        owner_id: &Uuid,
+        name: &str,
+    ) -> Result<Organization, OrganizationError> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query(
+            "INSERT INTO organizations (name, owner_id) VALUES ($1, $2) RETURNING id, created_at",
+        )
+        .bind(name.trim())
+        .bind(owner_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let id: Uuid = row.get("id");
+
+        sqlx::query(
+            "INSERT INTO organization_members (organization_id, user_id, role) VALUES ($1, $2, $3)",
+        )
+        .bind(id)
+        .bind(owner_id)
+        .bind(OWNER_ROLE)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        log::info!("👪 User {} created organization {}", owner_id, id);
+
+        Ok(Organization {
+            id,
+            name: name.trim().to_string(),
+            owner_id: *owner_id,
+            role: OWNER_ROLE.to_string(),
+            created_at: row.get("created_at"),
+        })
+    }
+
+    /// Lists the organizations the user belongs to
+    pub async fn list_organizations(
+        &self,
+        user_id: &Uuid,
+    ) -> Result<Vec<Organization>, OrganizationError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT o.id, o.name, o.owner_id, o.created_at, m.role
+            FROM organizations o
+            JOIN organization_members m ON m.organization_id = o.id
+            WHERE m.user_id = $1
+            ORDER BY o.created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(organization_from_row).collect())
+    }
+
+    /// Returns an organization with its members and pending invitations, if the user is a member
+    pub async fn get_organization(
+        &self,
+        user_id: &Uuid,
+        organization_id: &Uuid,
+    ) -> Result<OrganizationDetails, OrganizationError> {
+        let row = sqlx::query(
+            r#"
+            SELECT o.id, o.name, o.owner_id, o.created_at, m.role
+            FROM organizations o
+            JOIN organization_members m ON m.organization_id = o.id
+            WHERE o.id = $1 AND m.user_id = $2
+            "#,
+        )
+        .bind(organization_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(OrganizationError::NotFound)?;
+
+        let members = sqlx::query(
+            r#"
+            SELECT m.user_id, u.name, u.email, m.role, m.joined_at
+            FROM organization_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.organization_id = $1
+            ORDER BY m.joined_at
+            "#,
+        )
+        .bind(organization_id)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| OrganizationMemberInfo {
+            user_id: row.get("user_id"),
+            name: row.get("name"),
+            email: row.get("email"),
+            role: row.get("role"),
+            joined_at: row.get("joined_at"),
+        })
+        .collect();
+
+        let invitations = sqlx::query(
+            r#"
+            SELECT id, email, expires_at
+            FROM organization_invitations
+            WHERE organization_id = $1 AND accepted_at IS NULL AND expires_at > NOW()
+            ORDER BY created_at
+            "#,
+        )
+        .bind(organization_id)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| PendingInvitation {
+            id: row.get("id"),
+            email: row.get("email"),
+            expires_at: row.get("expires_at"),
+        })
+        .collect();
+
+        Ok(OrganizationDetails {
+            organization: organization_from_row(&row),
+            members,
+            invitations,
+        })
+    }
+
+    /// Stores an invitation for the email address. Only the owner may invite.
+    pub async fn create_invitation(
+        &self,
+        member: &OrganizationMember,
+        email: &str,
+        token: &str,
+    ) -> Result<CreatedInvitation, OrganizationError> {
+        if !member.is_owner() {
+            return Err(OrganizationError::OwnerRequired);
+        }
+
+        let email = email.trim().to_lowercase();
+
+        let already_member = sqlx::query(
+            r#"
+            SELECT 1 FROM organization_members m
+            JOIN users u ON u.id = m.user_id
+            WHERE m.organization_id = $1 AND u.email = $2
+            "#,
+        )
+        .bind(member.organization_id)
+        .bind(&email)
+        .fetch_optional(&self.pool)
+        .await?
+        .is_some();
+
+        if already_member {
+            return Err(OrganizationError::Validation(format!(
+                "{} is already a member",
+                email
+            )));
+        }
+
+        let expires_at = Utc::now() + Duration::days(INVITATION_TTL_DAYS);
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO organization_invitations (organization_id, email, token, invited_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING (SELECT name FROM organizations WHERE id = $1) AS organization_name
+            "#,
+        )
+        .bind(member.organization_id)
+        .bind(&email)
+        .bind(token)
+        .bind(member.user_id)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(CreatedInvitation {
+            organization_name: row.get("organization_name"),
+            email,
+            expires_at,
+        })
+    }
+
+    /// Adds the user to the organization of a valid invitation sent to their email address
+    pub async fn accept_invitation(
+        &self,
+        user_id: &Uuid,
+        token: &str,
+    ) -> Result<Organization, OrganizationError> {
+        let mut tx = self.pool.begin().await?;
+
+        let organization_id: Uuid = sqlx::query(
+            r#"
+            UPDATE organization_invitations i
+            SET accepted_at = NOW()
+            FROM users u
+            WHERE i.token = $1 AND u.id = $2 AND u.email = i.email
+              AND i.accepted_at IS NULL AND i.expires_at > NOW()
+            RETURNING i.organization_id
+            "#,
+        )
+        .bind(token)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(OrganizationError::InvalidInvitation)?
+        .get("organization_id");
+
+        sqlx::query(
+            r#"
+            INSERT INTO organization_members (organization_id, user_id, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (organization_id, user_id) DO NOTHING
+            "#,
+        )
+        .bind(organization_id)
+        .bind(user_id)
+        .bind(MEMBER_ROLE)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        log::info!(
+            "👪 User {} joined organization {}",
+            user_id,
+            organization_id
+        );
+
+        self.get_organization(user_id, &organization_id)
+            .await
+            .map(|details| details.organization)
+    }
+
+    /// Removes a member. The owner may remove anyone but themselves; members may leave.
+    /// Scans the removed member shared with the organization stay with the organization.
+    pub async fn remove_member(
+        &self,
+        member: &OrganizationMember,
+        user_id: &Uuid,
+    ) -> Result<(), OrganizationError> {
+        if member.user_id != *user_id && !member.is_owner() {
+            return Err(OrganizationError::OwnerRequired);
+        }
+
+        let result = sqlx::query(
+            r#"
+            DELETE FROM organization_members
+            WHERE organization_id = $1 AND user_id = $2 AND role <> $3
+            "#,
+        )
+        .bind(member.organization_id)
+        .bind(user_id)
+        .bind(OWNER_ROLE)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(OrganizationError::Validation(
+                "Not a member, or the owner (who can't leave the organization)".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn organization_from_row(row: &sqlx::postgres::PgRow) -> Organization {
+    Organization {
+        id: row.get("id"),
+        name: row.get("name"),
+        owner_id: row.get("owner_id"),
+        role: row.get("role"),
+        created_at: row.get("created_at"),
+    }
+}
+
+/// Custom extractor for an authenticated user who is a member of the organization in the
+/// `{org_id}` path segment. Responds with 404 when they aren't, so organizations can't be
+/// probed.
+#[derive(Debug, Clone)]
+pub struct OrganizationMember {
+    /// ID of the authenticated user
+    pub user_id: Uuid,
+    /// ID of the organization
+    pub organization_id: Uuid,
+    /// Role of the user in the organization
+    pub role: String,
+}
+
+impl OrganizationMember {
+    /// Returns true if the user owns the organization.
+    pub fn is_owner(&self) -> bool {
+        self.role == OWNER_ROLE
+    }
+}
+
+impl actix_web::FromRequest for OrganizationMember {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let user_id = req.extensions().get::<Uuid>().copied();
+        let organization_id = req
+            .match_info()
+            .get("org_id")
+            .and_then(|id| Uuid::parse_str(id).ok());
+        let pool = req.app_data::<web::Data<PgPool>>().cloned();
+
+        Box::pin(async move {
+            let user_id = user_id
+                .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not authenticated"))?;
+            let organization_id = organization_id.ok_or(OrganizationError::NotFound)?;
+            let pool = pool.ok_or_else(|| {
+                actix_web::error::ErrorInternalServerError("Database pool not configured")
+            })?;
+
+            let role: String = sqlx::query(
+                "SELECT role FROM organization_members WHERE organization_id = $1 AND user_id = $2",
+            )
+            .bind(organization_id)
+            .bind(user_id)
+            .fetch_optional(pool.get_ref())
+            .await
+            .map_err(OrganizationError::from)?
+            .ok_or(OrganizationError::NotFound)?
+            .get("role");
+
+            Ok(OrganizationMember {
+                user_id,
+                organization_id,
+                role,
+            })
+        })
+    }
+}
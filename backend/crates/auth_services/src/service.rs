@@ -1,13 +1,32 @@
 use bcrypt::{DEFAULT_COST, hash, verify};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use i18n::{
+    current_locale, format_phone_number, parse_timezone, user_timezone, validate_phone_number,
+};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
+use postgres::pagination::{Page, Pagination};
+
 use crate::types::{
-    AuthError, NotificationPreferences, SignUpRequest, UpdateProfileRequest, User,
-    validate_phone_number,
+    AuthError, EmailChange, NotificationPreferences, OnboardingChecklist, PatchProfileRequest,
+    SignUpRequest, User, UserListQuery, UserSummary,
 };
 
+/// Filter shared by the admin user list and its count; binds email search, status, and
+/// signup date range as $1 to $4
+const USER_LIST_FILTER: &str = r#"
+    ($1::TEXT IS NULL OR strpos(lower(email), lower($1)) > 0)
+    AND (
+        $2::TEXT IS NULL
+        OR ($2 = 'active' AND COALESCE(is_active, true))
+        OR ($2 = 'inactive' AND NOT COALESCE(is_active, true))
+        OR ($2 = 'unverified' AND NOT COALESCE(email_verified, false))
+    )
+    AND ($3::DATE IS NULL OR created_at >= $3::DATE::TIMESTAMP AT TIME ZONE 'UTC')
+    AND ($4::DATE IS NULL OR created_at < ($4::DATE + 1)::TIMESTAMP AT TIME ZONE 'UTC')
+"#;
+
 /// A service for handling user authentication operations such as creating users,
 /// retrieving user information, verifying credentials, and managing sessions.
 pub struct AuthService {
@@ -21,6 +40,7 @@ impl AuthService {
     }
 
     /// Creates a new user in the database with the provided sign-up request.
+    #[tracing::instrument(skip_all)]
     pub async fn create_user(&self, request: &SignUpRequest) -> Result<User, AuthError> {
         // Validate phone number format
         if !validate_phone_number(&request.phone) {
@@ -41,7 +61,7 @@ impl AuthService {
         let password_hash = hash(&request.password, DEFAULT_COST)?;
 
         // Format phone number to E.164 format
-        let formatted_phone = self.format_phone_number(&request.phone);
+        let formatted_phone = format_phone_number(&request.phone);
 
         // Serialize notification preferences to JSON
         let notification_prefs =
@@ -53,12 +73,12 @@ impl AuthService {
         let row = sqlx::query(
             r#"
             INSERT INTO users (
-                email, name, phone, password_hash, notification_preferences
-            ) VALUES ($1, $2, $3, $4, $5)
+                email, name, phone, password_hash, notification_preferences, locale, timezone
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
             RETURNING 
                 id, email, name, phone, password_hash, role, 
                 email_verified, phone_verified, notification_preferences,
-                timezone, is_active, created_at, updated_at
+                timezone, locale, is_active, created_at, updated_at
             "#,
         )
         .bind(request.email.to_lowercase().trim())
@@ -66,6 +86,9 @@ impl AuthService {
         .bind(&formatted_phone)
         .bind(&password_hash)
         .bind(&notification_prefs)
+        // Without a choice, users get the language their browser asked for
+        .bind(request.locale.unwrap_or_else(current_locale).code())
+        .bind(user_timezone(request.timezone.as_deref()).name())
         .fetch_one(&self.pool)
         .await?;
 
@@ -80,6 +103,7 @@ impl AuthService {
             phone_verified: row.get("phone_verified"),
             notification_preferences: row.get("notification_preferences"),
             timezone: row.get("timezone"),
+            locale: row.get::<String, _>("locale").parse().unwrap_or_default(),
             is_active: row.get("is_active"),
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
@@ -89,13 +113,14 @@ impl AuthService {
     }
 
     /// Retrieves a user by their email address, returning `None` if not found or inactive.
+    #[tracing::instrument(skip_all)]
     pub async fn get_user_by_email(&self, email: &str) -> Result<Option<User>, AuthError> {
         let row = sqlx::query(
             r#"
             SELECT 
                 id, email, name, phone, password_hash, role,
                 email_verified, phone_verified, notification_preferences,
-                timezone, is_active, created_at, updated_at
+                timezone, locale, is_active, created_at, updated_at
             FROM users 
             WHERE email = $1 AND is_active = true
             "#,
@@ -116,6 +141,7 @@ impl AuthService {
                 phone_verified: row.get("phone_verified"),
                 notification_preferences: row.get("notification_preferences"),
                 timezone: row.get("timezone"),
+                locale: row.get::<String, _>("locale").parse().unwrap_or_default(),
                 is_active: row.get("is_active"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
@@ -128,13 +154,14 @@ impl AuthService {
 
     /// Retrieves a user by their ID, returning `None` if not found or inactive.
     /// This method is useful for fetching user details without exposing sensitive information.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id))]
     pub async fn get_user_by_id(&self, user_id: &Uuid) -> Result<Option<User>, AuthError> {
         let row = sqlx::query(
             r#"
             SELECT 
                 id, email, name, phone, password_hash, role,
                 email_verified, phone_verified, notification_preferences,
-                timezone, is_active, created_at, updated_at
+                timezone, locale, is_active, created_at, updated_at
             FROM users 
             WHERE id = $1 AND is_active = true
             "#,
@@ -155,6 +182,7 @@ impl AuthService {
                 phone_verified: row.get("phone_verified"),
                 notification_preferences: row.get("notification_preferences"),
                 timezone: row.get("timezone"),
+                locale: row.get::<String, _>("locale").parse().unwrap_or_default(),
                 is_active: row.get("is_active"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
@@ -166,6 +194,7 @@ impl AuthService {
     }
 
     /// Verifies the user's password against the stored hash.
+    #[tracing::instrument(skip_all)]
     pub async fn verify_password(&self, email: &str, password: &str) -> Result<User, AuthError> {
         let user = self
             .get_user_by_email(email)
@@ -182,6 +211,7 @@ impl AuthService {
     }
 
     /// Creates a new session for the user with a refresh token hash
+    #[tracing::instrument(skip_all, fields(user_id = %user_id))]
     pub async fn create_session(
         &self,
         user_id: &Uuid,
@@ -204,23 +234,24 @@ impl AuthService {
     }
 
     /// Updates the user's email and/or phone verification status
+    #[tracing::instrument(skip_all, fields(user_id = %user_id))]
     pub async fn update_user_verification(
         &self,
         user_id: &Uuid,
         email_verified: Option<bool>,
         phone_verified: Option<bool>,
     ) -> Result<(), AuthError> {
-        if email_verified.is_some() {
+        if let Some(email_verified) = email_verified {
             sqlx::query("UPDATE users SET email_verified = $1, updated_at = NOW() WHERE id = $2")
-                .bind(email_verified.unwrap())
+                .bind(email_verified)
                 .bind(user_id)
                 .execute(&self.pool)
                 .await?;
         }
 
-        if phone_verified.is_some() {
+        if let Some(phone_verified) = phone_verified {
             sqlx::query("UPDATE users SET phone_verified = $1, updated_at = NOW() WHERE id = $2")
-                .bind(phone_verified.unwrap())
+                .bind(phone_verified)
                 .bind(user_id)
                 .execute(&self.pool)
                 .await?;
@@ -229,67 +260,365 @@ impl AuthService {
         Ok(())
     }
 
-    /// Updates the user's profile information
-    pub async fn update_user_profile(
+    /// Records a pending change of the user's email to `new_email`, confirmed by `token`
+    /// until `expires_at`. The current email stays in use until the change is confirmed;
+    /// a new request replaces any pending one.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id))]
+    pub async fn request_email_change(
         &self,
         user_id: &Uuid,
-        request: &UpdateProfileRequest,
-    ) -> Result<User, AuthError> {
-        // Get current user to compare changes
-        let current_user = self
-            .get_user_by_id(user_id)
-            .await?
-            .ok_or(AuthError::UserNotFound)?;
+        new_email: &str,
+        token: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), AuthError> {
+        let existing_user = sqlx::query("SELECT id FROM users WHERE email = $1 AND id <> $2")
+            .bind(new_email)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
 
-        // Check if email or phone changed
-        let email_changed = current_user.email != request.email;
-        let phone_changed = current_user.phone.as_deref() != Some(&request.phone);
+        if existing_user.is_some() {
+            return Err(AuthError::EmailExists);
+        }
 
-        // Determine new verification status
-        let new_email_verified = if email_changed {
-            false
-        } else {
-            current_user.email_verified
+        let result = sqlx::query(
+            r#"
+            UPDATE users
+            SET pending_email = $1, email_change_token = $2, email_change_expires_at = $3,
+                updated_at = NOW()
+            WHERE id = $4 AND is_active = true
+            "#,
+        )
+        .bind(new_email)
+        .bind(token)
+        .bind(expires_at)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AuthError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the email address the user asked to switch to, if the change is still
+    /// waiting for confirmation.
+    pub async fn pending_email(&self, user_id: &Uuid) -> Result<Option<String>, AuthError> {
+        let row = sqlx::query(
+            r#"
+            SELECT pending_email FROM users
+            WHERE id = $1 AND email_change_expires_at > NOW()
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.and_then(|row| row.get("pending_email")))
+    }
+
+    /// Discards the user's pending email change.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id))]
+    pub async fn cancel_email_change(&self, user_id: &Uuid) -> Result<(), AuthError> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET pending_email = NULL, email_change_token = NULL, email_change_expires_at = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Switches the account to its pending email if `token` confirms it and hasn't expired.
+    /// The new address is verified by the confirmation itself. Returns `None` for unknown
+    /// or expired tokens, and [`AuthError::EmailExists`] if another account took the
+    /// address in the meantime.
+    #[tracing::instrument(skip_all)]
+    pub async fn confirm_email_change(
+        &self,
+        token: &str,
+    ) -> Result<Option<EmailChange>, AuthError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE users u
+            SET email = u.pending_email,
+                email_verified = true,
+                pending_email = NULL,
+                email_change_token = NULL,
+                email_change_expires_at = NULL,
+                updated_at = NOW()
+            FROM (
+                SELECT id, email FROM users
+                WHERE email_change_token = $1
+                  AND email_change_expires_at > NOW()
+                  AND is_active = true
+                FOR UPDATE
+            ) previous
+            WHERE u.id = previous.id
+            RETURNING u.id, u.name, u.locale, previous.email AS old_email, u.email AS new_email
+            "#,
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await;
+
+        let row = match result {
+            Ok(row) => row,
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                return Err(AuthError::EmailExists);
+            }
+            Err(e) => return Err(e.into()),
         };
-        let new_phone_verified = if phone_changed {
-            false
-        } else {
-            current_user.phone_verified
+
+        Ok(row.map(|row| EmailChange {
+            user_id: row.get("id"),
+            name: row.get("name"),
+            locale: row.get::<String, _>("locale").parse().unwrap_or_default(),
+            old_email: row.get("old_email"),
+            new_email: row.get("new_email"),
+        }))
+    }
+
+    /// Returns the cached notification channel deliverability for the user and when it
+    /// was checked, or `None` if it has never been checked.
+    pub async fn get_channel_deliverability(
+        &self,
+        user_id: &Uuid,
+    ) -> Result<Option<(serde_json::Value, DateTime<Utc>)>, AuthError> {
+        let row = sqlx::query(
+            r#"
+            SELECT channel_deliverability, deliverability_checked_at
+            FROM users
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AuthError::UserNotFound)?;
+
+        let deliverability: Option<serde_json::Value> = row.get("channel_deliverability");
+        let checked_at: Option<DateTime<Utc>> = row.get("deliverability_checked_at");
+
+        Ok(deliverability.zip(checked_at))
+    }
+
+    /// Stores the result of the latest notification channel deliverability check.
+    pub async fn update_channel_deliverability(
+        &self,
+        user_id: &Uuid,
+        deliverability: &serde_json::Value,
+    ) -> Result<(), AuthError> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET channel_deliverability = $1, deliverability_checked_at = NOW()
+            WHERE id = $2
+            "#,
+        )
+        .bind(deliverability)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Computes the user's progress through account setup. A channel counts as tested once
+    /// an enabled channel passed its pre-flight check or a notification was sent.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id))]
+    pub async fn onboarding_checklist(
+        &self,
+        user_id: &Uuid,
+    ) -> Result<OnboardingChecklist, AuthError> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                COALESCE(u.email_verified, false) AS email_verified,
+                COALESCE(u.phone_verified, false) AS phone_verified,
+                EXISTS (SELECT 1 FROM user_scans WHERE user_id = u.id)
+                    OR EXISTS (SELECT 1 FROM permit_scans WHERE user_id = u.id)
+                    AS first_scan_created,
+                EXISTS (
+                    SELECT 1
+                    FROM jsonb_each(COALESCE(u.channel_deliverability, '{}'::jsonb)) AS c(channel, result)
+                    WHERE c.result->>'deliverable' = 'true'
+                      AND u.notification_preferences->>c.channel = 'true'
+                )
+                    OR EXISTS (
+                        SELECT 1 FROM notifications
+                        WHERE user_id = u.id AND status IN ('sent', 'delivered')
+                    )
+                    AS notification_channel_tested
+            FROM users u
+            WHERE u.id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AuthError::UserNotFound)?;
+
+        Ok(OnboardingChecklist::new(
+            [
+                "email_verified",
+                "phone_verified",
+                "first_scan_created",
+                "notification_channel_tested",
+            ]
+            .map(|key| (key, row.get::<bool, _>(key))),
+        ))
+    }
+
+    /// Lists users for admins, newest first, filtered by email, status, and signup date.
+    #[tracing::instrument(skip_all)]
+    pub async fn list_users(&self, query: &UserListQuery) -> Result<Page<UserSummary>, AuthError> {
+        if let Some(status) = query.status.as_deref()
+            && !["active", "inactive", "unverified"].contains(&status)
+        {
+            return Err(AuthError::Validation(format!(
+                "Unknown status '{}'; use active, inactive, or unverified",
+                status
+            )));
+        }
+
+        let pagination = Pagination::new(query.page, query.per_page);
+        let email = query
+            .email
+            .as_deref()
+            .map(str::trim)
+            .filter(|e| !e.is_empty());
+
+        let total: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM users WHERE {}",
+            USER_LIST_FILTER
+        ))
+        .bind(email)
+        .bind(query.status.as_deref())
+        .bind(query.from)
+        .bind(query.to)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT id, name, email, phone, COALESCE(role, 'user') AS role, plan,
+                   COALESCE(email_verified, false) AS email_verified,
+                   COALESCE(phone_verified, false) AS phone_verified,
+                   COALESCE(is_active, true) AS is_active, created_at
+            FROM users
+            WHERE {}
+            ORDER BY created_at DESC, id
+            LIMIT $5 OFFSET $6
+            "#,
+            USER_LIST_FILTER
+        ))
+        .bind(email)
+        .bind(query.status.as_deref())
+        .bind(query.from)
+        .bind(query.to)
+        .bind(pagination.per_page)
+        .bind(pagination.offset())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let users = rows
+            .into_iter()
+            .map(|row| UserSummary {
+                id: row.get("id"),
+                name: row.get("name"),
+                email: row.get("email"),
+                phone: row.get("phone"),
+                role: row.get("role"),
+                plan: row.get("plan"),
+                email_verified: row.get("email_verified"),
+                phone_verified: row.get("phone_verified"),
+                is_active: row.get("is_active"),
+                created_at: row.get("created_at"),
+            })
+            .collect();
+
+        Ok(Page::new(users, pagination, total))
+    }
+
+    /// Updates the user's profile information. Only the fields set in the request are
+    /// changed; changing the phone resets its verification status. The email isn't
+    /// changed here, see [`AuthService::request_email_change`].
+    #[tracing::instrument(skip_all, fields(user_id = %user_id))]
+    pub async fn update_user_profile(
+        &self,
+        user_id: &Uuid,
+        request: &PatchProfileRequest,
+    ) -> Result<User, AuthError> {
+        if request.is_empty() {
+            return Err(AuthError::Validation(
+                "At least one profile field must be provided".to_string(),
+            ));
+        }
+
+        // Validate and format phone number to E.164 format
+        let phone = match &request.phone {
+            Some(phone) if !validate_phone_number(phone) => {
+                return Err(AuthError::InvalidPhoneNumber);
+            }
+            Some(phone) => Some(format_phone_number(phone)),
+            None => None,
         };
 
         // Serialize notification preferences to JSON and map error to AuthError
-        let notification_prefs =
-            serde_json::to_value(&request.notification_preferences).map_err(|e| {
+        let notification_prefs = request
+            .notification_preferences
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| {
                 AuthError::Validation(format!("Invalid notification preferences: {}", e))
             })?;
 
-        // Build the update query and manually construct the User
+        // Unset fields fall back to the stored values in a single statement, so
+        // concurrent updates to other fields aren't clobbered
         let row = sqlx::query(
             r#"
             UPDATE users 
-            SET name = $1, 
-                email = $2, 
-                phone = $3,
-                email_verified = $4,
-                phone_verified = $5,
-                notification_preferences = $6,
+            SET name = COALESCE($1, name), 
+                phone = COALESCE($2, phone),
+                phone_verified = CASE
+                    WHEN $2::VARCHAR IS NOT NULL AND $2::VARCHAR IS DISTINCT FROM phone THEN FALSE
+                    ELSE phone_verified
+                END,
+                notification_preferences = COALESCE($3, notification_preferences),
+                locale = COALESCE($4, locale),
+                timezone = COALESCE($5, timezone),
                 updated_at = NOW()
-            WHERE id = $7
+            WHERE id = $6 AND is_active = true
             RETURNING 
                 id, email, name, phone, password_hash, role, 
                 email_verified, phone_verified, notification_preferences,
-                timezone, is_active, created_at, updated_at
+                timezone, locale, is_active, created_at, updated_at
             "#,
         )
-        .bind(request.name.trim())
-        .bind(request.email.to_lowercase().trim())
-        .bind(&request.phone)
-        .bind(new_email_verified)
-        .bind(new_phone_verified)
+        .bind(request.name.as_deref().map(str::trim))
+        .bind(&phone)
         .bind(&notification_prefs)
+        .bind(request.locale.map(|locale| locale.code()))
+        .bind(
+            request
+                .timezone
+                .as_deref()
+                .and_then(parse_timezone)
+                .map(|timezone| timezone.name()),
+        )
         .bind(user_id)
-        .fetch_one(&self.pool)
-        .await?;
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(AuthError::UserNotFound)?;
 
         let updated_user = User {
             id: row.get("id"),
@@ -302,6 +631,7 @@ impl AuthService {
             phone_verified: row.get("phone_verified"),
             notification_preferences: row.get("notification_preferences"),
             timezone: row.get("timezone"),
+            locale: row.get::<String, _>("locale").parse().unwrap_or_default(),
             is_active: row.get("is_active"),
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
@@ -309,19 +639,6 @@ impl AuthService {
 
         Ok(updated_user)
     }
-
-    fn format_phone_number(&self, phone: &str) -> String {
-        // Remove all non-digit characters
-        let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
-
-        // Add +1 if it's a 10-digit US number
-        if digits.len() == 10 {
-            format!("+1{}", digits)
-        } else {
-            // For 11-digit numbers starting with 1, or any other format, just add +
-            format!("+{}", digits)
-        }
-    }
 }
 
 impl User {
@@ -0,0 +1,565 @@
+use chrono::Utc;
+use qrcode::QrCode;
+use qrcode::render::svg;
+use rand::Rng;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::digest::{SHA256, digest};
+use ring::hkdf;
+use ring::rand::{SecureRandom, SystemRandom};
+use sqlx::{PgPool, Row};
+use totp_rs::{Algorithm, TOTP};
+use uuid::Uuid;
+
+use crate::types::{AuthError, TwoFactorSetup};
+
+/// Issuer shown next to the account in authenticator apps
+const TOTP_ISSUER: &str = "Campsite Tracker";
+
+/// Seconds each authenticator code is valid for
+const TOTP_STEP_SECS: u64 = 30;
+
+/// Bytes of randomness in an authenticator secret (160 bits, as RFC 4226 recommends)
+const SECRET_LEN: usize = 20;
+
+/// Recovery codes issued when two-factor authentication is enabled
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Characters of recovery codes, leaving out ones easily confused (0/o, 1/l/i)
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// Wrong codes accepted in a row before verification is locked
+const MAX_FAILED_ATTEMPTS: i32 = 5;
+
+/// Minutes verification stays locked after too many wrong codes
+const LOCKOUT_MINUTES: i32 = 15;
+
+/// Encrypts authenticator secrets at rest with AES-256-GCM, binding each to its user
+#[derive(Clone)]
+pub struct TwoFactorCipher {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl TwoFactorCipher {
+    /// Derives the encryption key from the configured key material with HKDF-SHA256.
+    pub fn new(key_material: &str) -> Self {
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, b"campsite-tracker")
+            .extract(key_material.as_bytes());
+        let okm = prk
+            .expand(&[b"two-factor-secrets"], &AES_256_GCM)
+            .expect("AES-256 key length is a valid HKDF output length");
+
+        Self {
+            key: LessSafeKey::new(UnboundKey::from(okm)),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// Encrypts a user's secret, returning the ciphertext and the nonce used.
+    fn encrypt(&self, user_id: &Uuid, secret: &[u8]) -> Result<(Vec<u8>, Vec<u8>), AuthError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| AuthError::Internal("Failed to generate nonce".to_string()))?;
+
+        let mut ciphertext = secret.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(user_id.as_bytes()),
+                &mut ciphertext,
+            )
+            .map_err(|_| AuthError::Internal("Failed to encrypt secret".to_string()))?;
+
+        Ok((ciphertext, nonce.to_vec()))
+    }
+
+    /// Decrypts a user's secret.
+    fn decrypt(
+        &self,
+        user_id: &Uuid,
+        ciphertext: &[u8],
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, AuthError> {
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| AuthError::Internal("Stored nonce is malformed".to_string()))?;
+
+        let mut plaintext = ciphertext.to_vec();
+        let secret = self
+            .key
+            .open_in_place(nonce, Aad::from(user_id.as_bytes()), &mut plaintext)
+            .map_err(|_| AuthError::Internal("Failed to decrypt secret".to_string()))?;
+
+        Ok(secret.to_vec())
+    }
+
+    /// Generates a new random authenticator secret.
+    fn generate_secret(&self) -> Result<Vec<u8>, AuthError> {
+        let mut secret = vec![0u8; SECRET_LEN];
+        self.rng
+            .fill(&mut secret)
+            .map_err(|_| AuthError::Internal("Failed to generate secret".to_string()))?;
+        Ok(secret)
+    }
+}
+
+/// Enrollment state of a user, decrypted
+struct Enrollment {
+    totp: TOTP,
+    enabled: bool,
+    recovery_code_hashes: Vec<String>,
+    last_used_step: Option<i64>,
+    locked: bool,
+}
+
+/// Enrolls users in TOTP two-factor authentication and checks their codes
+pub struct TwoFactorService {
+    pool: PgPool,
+    cipher: TwoFactorCipher,
+}
+
+impl TwoFactorService {
+    /// Creates a service storing secrets encrypted with the given cipher
+    pub fn new(pool: PgPool, cipher: TwoFactorCipher) -> Self {
+        Self { pool, cipher }
+    }
+
+    /// Whether the user has enabled two-factor authentication
+    pub async fn is_enabled(&self, user_id: &Uuid) -> Result<bool, AuthError> {
+        let enabled: Option<bool> =
+            sqlx::query_scalar("SELECT enabled FROM user_two_factor WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(enabled.unwrap_or(false))
+    }
+
+    /// Starts enrolling an authenticator: generates a secret, replacing any enrollment
+    /// not yet confirmed, and returns it with its QR code. Two-factor authentication is
+    /// only enabled once a code from the authenticator is confirmed with `enable`.
+    pub async fn begin_setup(
+        &self,
+        user_id: &Uuid,
+        email: &str,
+    ) -> Result<TwoFactorSetup, AuthError> {
+        let secret = self.cipher.generate_secret()?;
+        let (ciphertext, nonce) = self.cipher.encrypt(user_id, &secret)?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO user_two_factor (user_id, secret_ciphertext, secret_nonce)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret_ciphertext = EXCLUDED.secret_ciphertext,
+                secret_nonce = EXCLUDED.secret_nonce,
+                recovery_code_hashes = '{}',
+                last_used_step = NULL,
+                failed_attempts = 0,
+                locked_until = NULL,
+                created_at = NOW()
+            WHERE NOT user_two_factor.enabled
+            "#,
+        )
+        .bind(user_id)
+        .bind(&ciphertext)
+        .bind(&nonce)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AuthError::TwoFactorAlreadyEnabled);
+        }
+
+        let totp = totp(secret, email)?;
+        let otpauth_url = totp.get_url();
+        let qr_svg = QrCode::new(otpauth_url.as_bytes())
+            .map_err(|e| AuthError::Internal(format!("Failed to render QR code: {}", e)))?
+            .render::<svg::Color>()
+            .min_dimensions(200, 200)
+            .build();
+
+        Ok(TwoFactorSetup {
+            secret: totp.get_secret_base32(),
+            otpauth_url,
+            qr_svg,
+        })
+    }
+
+    /// Confirms enrollment with a code from the authenticator and enables two-factor
+    /// authentication. Returns the recovery codes, which are only ever shown here.
+    pub async fn enable(&self, user_id: &Uuid, code: &str) -> Result<Vec<String>, AuthError> {
+        let mut tx = self.pool.begin().await?;
+        let enrollment = self
+            .load(&mut tx, user_id)
+            .await?
+            .ok_or(AuthError::TwoFactorNotSetUp)?;
+
+        if enrollment.enabled {
+            return Err(AuthError::TwoFactorAlreadyEnabled);
+        }
+        if enrollment.locked {
+            return Err(AuthError::TwoFactorLocked);
+        }
+
+        let Some(step) = matching_step(&enrollment, code) else {
+            record_failure(&mut tx, user_id).await?;
+            tx.commit().await?;
+            return Err(AuthError::InvalidTwoFactorCode);
+        };
+
+        let recovery_codes = generate_recovery_codes();
+        let hashes: Vec<String> = recovery_codes
+            .iter()
+            .map(|code| hash_recovery_code(code))
+            .collect();
+
+        sqlx::query(
+            r#"
+            UPDATE user_two_factor
+            SET enabled = TRUE, enabled_at = NOW(), recovery_code_hashes = $2,
+                last_used_step = $3, failed_attempts = 0, locked_until = NULL
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(&hashes)
+        .bind(step)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(recovery_codes)
+    }
+
+    /// Checks a code from the user's authenticator, or one of their recovery codes, which
+    /// is used up. Each authenticator code is accepted once; after too many wrong codes
+    /// verification is locked for a while.
+    pub async fn verify(&self, user_id: &Uuid, code: &str) -> Result<(), AuthError> {
+        let mut tx = self.pool.begin().await?;
+        let enrollment = self
+            .load(&mut tx, user_id)
+            .await?
+            .filter(|enrollment| enrollment.enabled)
+            .ok_or(AuthError::TwoFactorNotSetUp)?;
+
+        if enrollment.locked {
+            return Err(AuthError::TwoFactorLocked);
+        }
+
+        if let Some(step) = matching_step(&enrollment, code) {
+            sqlx::query(
+                r#"
+                UPDATE user_two_factor
+                SET last_used_step = $2, failed_attempts = 0
+                WHERE user_id = $1
+                "#,
+            )
+            .bind(user_id)
+            .bind(step)
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            let hash = hash_recovery_code(code);
+            if !enrollment.recovery_code_hashes.contains(&hash) {
+                record_failure(&mut tx, user_id).await?;
+                tx.commit().await?;
+                return Err(AuthError::InvalidTwoFactorCode);
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE user_two_factor
+                SET recovery_code_hashes = array_remove(recovery_code_hashes, $2),
+                    failed_attempts = 0
+                WHERE user_id = $1
+                "#,
+            )
+            .bind(user_id)
+            .bind(&hash)
+            .execute(&mut *tx)
+            .await?;
+
+            log::info!("🔑 User {} signed in with a recovery code", user_id);
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Turns two-factor authentication off after checking a code, removing the secret
+    /// and recovery codes.
+    pub async fn disable(&self, user_id: &Uuid, code: &str) -> Result<(), AuthError> {
+        self.verify(user_id, code).await?;
+
+        sqlx::query("DELETE FROM user_two_factor WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Loads and decrypts the user's enrollment, locking its row for the transaction.
+    async fn load(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        user_id: &Uuid,
+    ) -> Result<Option<Enrollment>, AuthError> {
+        let row = sqlx::query(
+            r#"
+            SELECT t.secret_ciphertext, t.secret_nonce, t.enabled, t.recovery_code_hashes,
+                   t.last_used_step, COALESCE(t.locked_until > NOW(), false) AS locked,
+                   u.email
+            FROM user_two_factor t
+            JOIN users u ON u.id = t.user_id
+            WHERE t.user_id = $1
+            FOR UPDATE OF t
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let secret = self.cipher.decrypt(
+            user_id,
+            row.get::<Vec<u8>, _>("secret_ciphertext").as_slice(),
+            row.get::<Vec<u8>, _>("secret_nonce").as_slice(),
+        )?;
+
+        Ok(Some(Enrollment {
+            totp: totp(secret, row.get("email"))?,
+            enabled: row.get("enabled"),
+            recovery_code_hashes: row.get("recovery_code_hashes"),
+            last_used_step: row.get("last_used_step"),
+            locked: row.get("locked"),
+        }))
+    }
+}
+
+/// Builds the TOTP generator of a secret (SHA-1, 6 digits, 30 seconds, as authenticator
+/// apps expect)
+fn totp(secret: Vec<u8>, email: &str) -> Result<TOTP, AuthError> {
+    TOTP::new(
+        Algorithm::SHA1,
+        6,
+        0,
+        TOTP_STEP_SECS,
+        secret,
+        Some(TOTP_ISSUER.to_string()),
+        email.to_string(),
+    )
+    .map_err(|e| AuthError::Internal(format!("Invalid TOTP parameters: {}", e)))
+}
+
+/// Time step of the authenticator code entered, allowing one step of clock drift either
+/// way, if it's valid and newer than the last code accepted
+fn matching_step(enrollment: &Enrollment, code: &str) -> Option<i64> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    if code.len() != 6 || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let current = Utc::now().timestamp() as u64 / TOTP_STEP_SECS;
+    [current.saturating_sub(1), current, current + 1]
+        .into_iter()
+        .find(|step| enrollment.totp.check(&code, step * TOTP_STEP_SECS))
+        .map(|step| step as i64)
+        .filter(|step| enrollment.last_used_step.is_none_or(|last| *step > last))
+}
+
+/// Counts a wrong code, locking verification once too many were entered in a row
+async fn record_failure(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: &Uuid,
+) -> Result<(), AuthError> {
+    sqlx::query(
+        r#"
+        UPDATE user_two_factor
+        SET failed_attempts = CASE WHEN failed_attempts + 1 >= $2 THEN 0 ELSE failed_attempts + 1 END,
+            locked_until = CASE
+                WHEN failed_attempts + 1 >= $2 THEN NOW() + make_interval(mins => $3)
+                ELSE locked_until
+            END
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(MAX_FAILED_ATTEMPTS)
+    .bind(LOCKOUT_MINUTES)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Generates single-use recovery codes like `k7fq2-9xbma`
+fn generate_recovery_codes() -> Vec<String> {
+    let mut rng = rand::rng();
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let chars: String = (0..10)
+                .map(|_| {
+                    RECOVERY_CODE_ALPHABET[rng.random_range(0..RECOVERY_CODE_ALPHABET.len())]
+                        as char
+                })
+                .collect();
+            format!("{}-{}", &chars[..5], &chars[5..])
+        })
+        .collect()
+}
+
+/// SHA-256 of a recovery code, ignoring case, spaces, and dashes. Codes are random
+/// enough that a fast hash is safe.
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    digest(&SHA256, normalized.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use postgres::test_fixtures::insert_user;
+
+    const KEY: &str = "a-test-key-of-at-least-32-characters";
+
+    fn enrollment(last_used_step: Option<i64>) -> Enrollment {
+        Enrollment {
+            totp: totp(vec![7; SECRET_LEN], "camper@example.com").unwrap(),
+            enabled: true,
+            recovery_code_hashes: Vec::new(),
+            last_used_step,
+            locked: false,
+        }
+    }
+
+    /// Code the authenticator shows `offset` seconds from now
+    fn code_at(enrollment: &Enrollment, offset: i64) -> String {
+        enrollment
+            .totp
+            .generate((Utc::now().timestamp() + offset) as u64)
+    }
+
+    #[test]
+    fn accepts_codes_within_one_step_of_drift() {
+        let enrollment = enrollment(None);
+
+        for offset in [-30, 0, 30] {
+            let code = code_at(&enrollment, offset);
+            let step = matching_step(&enrollment, &code);
+            assert!(step.is_some(), "code {}s off was rejected", offset);
+        }
+
+        let code = code_at(&enrollment, 0);
+        let spaced = format!("{} {}", &code[..3], &code[3..]);
+        assert!(matching_step(&enrollment, &spaced).is_some());
+    }
+
+    #[test]
+    fn rejects_codes_outside_the_drift() {
+        let enrollment = enrollment(None);
+
+        for offset in [-120, -90, 90, 120] {
+            let code = code_at(&enrollment, offset);
+            // A code from further out only matches when it happens to repeat
+            if code != code_at(&enrollment, -30)
+                && code != code_at(&enrollment, 0)
+                && code != code_at(&enrollment, 30)
+            {
+                assert_eq!(matching_step(&enrollment, &code), None, "{}s off", offset);
+            }
+        }
+
+        assert_eq!(matching_step(&enrollment, "12345"), None);
+        assert_eq!(matching_step(&enrollment, "abcdef"), None);
+    }
+
+    #[test]
+    fn rejects_replayed_codes() {
+        let code = code_at(&enrollment(None), 0);
+        let step = matching_step(&enrollment(None), &code).unwrap();
+
+        assert_eq!(matching_step(&enrollment(Some(step)), &code), None);
+        assert_eq!(matching_step(&enrollment(Some(step + 1)), &code), None);
+        assert_eq!(
+            matching_step(&enrollment(Some(step - 1)), &code),
+            Some(step)
+        );
+    }
+
+    #[test]
+    fn secrets_round_trip_through_encryption() {
+        let cipher = TwoFactorCipher::new(KEY);
+        let user_id = Uuid::new_v4();
+        let secret = cipher.generate_secret().unwrap();
+
+        let (ciphertext, nonce) = cipher.encrypt(&user_id, &secret).unwrap();
+
+        assert_ne!(ciphertext, secret);
+        assert_eq!(
+            cipher.decrypt(&user_id, &ciphertext, &nonce).unwrap(),
+            secret
+        );
+    }
+
+    #[test]
+    fn secrets_only_decrypt_with_their_key_and_user() {
+        let cipher = TwoFactorCipher::new(KEY);
+        let user_id = Uuid::new_v4();
+        let (ciphertext, nonce) = cipher.encrypt(&user_id, b"secret").unwrap();
+
+        assert!(
+            TwoFactorCipher::new("another-key-of-at-least-32-characters")
+                .decrypt(&user_id, &ciphertext, &nonce)
+                .is_err()
+        );
+        assert!(
+            cipher
+                .decrypt(&Uuid::new_v4(), &ciphertext, &nonce)
+                .is_err()
+        );
+        assert!(cipher.decrypt(&user_id, &ciphertext, &nonce[1..]).is_err());
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn a_code_signs_in_only_once(pool: PgPool) {
+        let user_id = insert_user(&pool, "camper@example.com").await;
+        let service = TwoFactorService::new(pool, TwoFactorCipher::new(KEY));
+
+        let setup = service
+            .begin_setup(&user_id, "camper@example.com")
+            .await
+            .unwrap();
+        let secret = totp_rs::Secret::Encoded(setup.secret).to_bytes().unwrap();
+        let authenticator = totp(secret, "camper@example.com").unwrap();
+        let now = Utc::now().timestamp() as u64;
+
+        service
+            .enable(&user_id, &authenticator.generate(now))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            service.verify(&user_id, &authenticator.generate(now)).await,
+            Err(AuthError::InvalidTwoFactorCode)
+        ));
+        service
+            .verify(&user_id, &authenticator.generate(now + TOTP_STEP_SECS))
+            .await
+            .unwrap();
+    }
+}
@@ -1,4 +1,5 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use i18n::{FluentArgs, Locale, current_locale, message, message_with, parse_timezone};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
@@ -28,6 +29,17 @@ pub struct SignUpRequest {
 
     /// Notification preferences for the user
     pub notification_preferences: NotificationPreferences,
+
+    /// Language of the user's emails, texts and error messages; the request's
+    /// `Accept-Language` when not given
+    #[serde(default)]
+    pub locale: Option<Locale>,
+
+    /// IANA time zone of the user, e.g. `America/Denver`; `America/Los_Angeles` when not
+    /// given
+    #[serde(default)]
+    #[validate(custom(function = "validate_timezone"))]
+    pub timezone: Option<String>,
 }
 
 /// Preferences for user notifications
@@ -109,6 +121,10 @@ pub struct UserInfo {
     pub phone_verified: bool,
     /// User's notification preferences
     pub notification_preferences: NotificationPreferences,
+    /// Language of the user's emails, texts and error messages
+    pub locale: Locale,
+    /// IANA time zone the user's alerts and scan dates are read in
+    pub timezone: String,
 }
 
 /// Request structure for updating user profile
@@ -132,6 +148,226 @@ pub struct UpdateProfileRequest {
 
     /// Notification preferences for the user
     pub notification_preferences: NotificationPreferences,
+
+    /// Language of the user's emails, texts and error messages; unchanged when not given
+    #[serde(default)]
+    pub locale: Option<Locale>,
+
+    /// IANA time zone of the user; unchanged when not given
+    #[serde(default)]
+    #[validate(custom(function = "validate_timezone"))]
+    pub timezone: Option<String>,
+}
+
+/// Request structure for partially updating the user profile.
+/// Fields that are not provided keep their current values.
+#[derive(Debug, Default, serde::Deserialize, validator::Validate)]
+pub struct PatchProfileRequest {
+    /// New name of the user
+    #[validate(length(min = 1, max = 255, message = "Name is required"))]
+    pub name: Option<String>,
+
+    /// New email address of the user
+    #[validate(email(message = "Please enter a valid email"))]
+    pub email: Option<String>,
+
+    /// New phone number of the user
+    #[validate(length(
+        min = 10,
+        max = 15,
+        message = "Phone number must be between 10-15 digits"
+    ))]
+    pub phone: Option<String>,
+
+    /// New notification preferences for the user
+    pub notification_preferences: Option<NotificationPreferences>,
+
+    /// New language of the user's emails, texts and error messages
+    pub locale: Option<Locale>,
+
+    /// New IANA time zone of the user
+    #[validate(custom(function = "validate_timezone"))]
+    pub timezone: Option<String>,
+}
+
+impl PatchProfileRequest {
+    /// Returns true if no field is set.
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.email.is_none()
+            && self.phone.is_none()
+            && self.notification_preferences.is_none()
+            && self.locale.is_none()
+            && self.timezone.is_none()
+    }
+}
+
+/// Custom validation function for IANA time zone names
+fn validate_timezone(timezone: &str) -> Result<(), validator::ValidationError> {
+    match parse_timezone(timezone) {
+        Some(_) => Ok(()),
+        None => Err(validator::ValidationError::new("invalid_timezone")
+            .with_message("Unknown time zone; use an IANA name such as America/Denver".into())),
+    }
+}
+
+impl From<UpdateProfileRequest> for PatchProfileRequest {
+    fn from(request: UpdateProfileRequest) -> Self {
+        Self {
+            name: Some(request.name),
+            email: Some(request.email),
+            phone: Some(request.phone),
+            notification_preferences: Some(request.notification_preferences),
+            locale: request.locale,
+            timezone: request.timezone,
+        }
+    }
+}
+
+/// A step of the guided account setup
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingStep {
+    /// Step key (`email_verified`, `phone_verified`, `first_scan_created`,
+    /// `notification_channel_tested`)
+    pub key: &'static str,
+    /// Whether the user has completed the step
+    pub completed: bool,
+}
+
+/// An email change confirmed through the link sent to the new address
+#[derive(Debug, Clone)]
+pub struct EmailChange {
+    /// User whose email changed
+    pub user_id: Uuid,
+    /// Name of the user
+    pub name: String,
+    /// Language of the user's emails
+    pub locale: Locale,
+    /// Address the account used before the change
+    pub old_email: String,
+    /// Address the account uses now
+    pub new_email: String,
+}
+
+/// Progress through the guided account setup, steps in the order they should be done
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingChecklist {
+    /// Setup steps
+    pub steps: Vec<OnboardingStep>,
+    /// Number of completed steps
+    pub completed_steps: usize,
+    /// Number of steps
+    pub total_steps: usize,
+    /// Whether every step is completed
+    pub complete: bool,
+}
+
+impl OnboardingChecklist {
+    /// Builds a checklist from each step's key and completion, in order.
+    pub fn new(steps: impl IntoIterator<Item = (&'static str, bool)>) -> Self {
+        let steps: Vec<OnboardingStep> = steps
+            .into_iter()
+            .map(|(key, completed)| OnboardingStep { key, completed })
+            .collect();
+        let completed_steps = steps.iter().filter(|step| step.completed).count();
+
+        Self {
+            total_steps: steps.len(),
+            complete: completed_steps == steps.len(),
+            completed_steps,
+            steps,
+        }
+    }
+}
+
+/// Query parameters of the admin user list
+#[derive(Debug, Default, Deserialize)]
+pub struct UserListQuery {
+    /// Page number, starting at 1
+    pub page: Option<i64>,
+    /// Users per page (default 50, at most 200)
+    pub per_page: Option<i64>,
+    /// Only users whose email contains this text, ignoring case
+    pub email: Option<String>,
+    /// Only `active`, `inactive`, or `unverified` (email not verified) users
+    pub status: Option<String>,
+    /// Only users who signed up on or after this day (UTC)
+    pub from: Option<NaiveDate>,
+    /// Only users who signed up on or before this day (UTC)
+    pub to: Option<NaiveDate>,
+}
+
+/// A user as listed to admins
+#[derive(Debug, Clone, Serialize)]
+pub struct UserSummary {
+    /// User ID
+    pub id: Uuid,
+    /// Name
+    pub name: String,
+    /// Email address
+    pub email: String,
+    /// Phone number
+    pub phone: Option<String>,
+    /// Role (`user`, `admin`)
+    pub role: String,
+    /// Plan key
+    pub plan: String,
+    /// Whether the email address is verified
+    pub email_verified: bool,
+    /// Whether the phone number is verified
+    pub phone_verified: bool,
+    /// Whether the account is active
+    pub is_active: bool,
+    /// When the user signed up
+    pub created_at: DateTime<Utc>,
+}
+
+/// Authenticator enrollment started by the user; shown once so it can be scanned
+#[derive(Debug, Serialize)]
+pub struct TwoFactorSetup {
+    /// Base32 secret, for authenticator apps that can't scan the QR code
+    pub secret: String,
+    /// `otpauth://` URL the QR code encodes
+    pub otpauth_url: String,
+    /// QR code of the URL as an SVG image
+    pub qr_svg: String,
+}
+
+/// Request carrying a code from the user's authenticator app or a recovery code
+#[derive(Debug, Deserialize, Validate)]
+pub struct TwoFactorCodeRequest {
+    /// Authenticator or recovery code
+    #[validate(length(
+        min = 6,
+        max = 20,
+        message = "Enter the code from your authenticator app"
+    ))]
+    pub code: String,
+}
+
+/// Second step of a login for users with two-factor authentication
+#[derive(Debug, Deserialize, Validate)]
+pub struct TwoFactorLoginRequest {
+    /// Challenge token returned by the login endpoint
+    pub challenge_token: String,
+    /// Authenticator or recovery code
+    #[validate(length(
+        min = 6,
+        max = 20,
+        message = "Enter the code from your authenticator app"
+    ))]
+    pub code: String,
+}
+
+/// Login response for users with two-factor authentication, in place of the tokens
+#[derive(Debug, Serialize)]
+pub struct TwoFactorChallenge {
+    /// Always true; tells clients to ask for a code
+    pub two_factor_required: bool,
+    /// Token to send with the code to `/api/auth/2fa`
+    pub challenge_token: String,
+    /// Seconds the challenge token is valid for
+    pub expires_in: i64,
 }
 
 /// Request structure for user login
@@ -169,6 +405,8 @@ pub struct User {
     pub notification_preferences: serde_json::Value,
     /// Timezone of the user
     pub timezone: String,
+    /// Language of the user's emails, texts and error messages
+    pub locale: Locale,
     /// Whether the user account is active
     pub is_active: bool,
     /// Timestamp when the user was created
@@ -190,6 +428,14 @@ pub struct Claims {
     pub exp: usize, // expiration timestamp
     /// Issued at timestamp of the token
     pub iat: usize, // issued at timestamp
+    /// What a restricted token may be used for (e.g. `two_factor` for login challenges);
+    /// unset on access and refresh tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    /// Language code of the user's preferred locale; set on access tokens, so error
+    /// messages follow the preference without a database lookup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
 }
 
 /// Custom error type for authentication-related errors
@@ -226,24 +472,54 @@ pub enum AuthError {
     /// An error occurred while validating input data
     #[error("Validation error: {0}")]
     Validation(String),
+
+    /// An unexpected error occurred in a backing service
+    #[error("Internal error: {0}")]
+    Internal(String),
+
+    /// Two-factor authentication is already enabled for the user
+    #[error("Two-factor authentication is already enabled")]
+    TwoFactorAlreadyEnabled,
+
+    /// Two-factor authentication hasn't been set up (or enabled) for the user
+    #[error("Two-factor authentication is not set up")]
+    TwoFactorNotSetUp,
+
+    /// The authenticator or recovery code is wrong, expired, or already used
+    #[error("Invalid two-factor code")]
+    InvalidTwoFactorCode,
+
+    /// Too many wrong two-factor codes were entered recently
+    #[error("Too many two-factor attempts")]
+    TwoFactorLocked,
+
+    /// The login challenge token is invalid or expired
+    #[error("Invalid or expired login challenge")]
+    InvalidChallenge,
+
+    /// The action was requested too often; it can be retried after the given seconds
+    #[error("Rate limited for {0} seconds")]
+    RateLimited(u64),
 }
 
 impl actix_web::ResponseError for AuthError {
     fn error_response(&self) -> actix_web::HttpResponse {
         use actix_web::HttpResponse;
 
+        let locale = current_locale();
+
         match self {
             AuthError::EmailExists => HttpResponse::Conflict().json(serde_json::json!({
                 "error": "email_exists",
-                "message": "An account with this email already exists"
+                "message": message(locale, "email-exists")
             })),
             AuthError::InvalidCredentials => HttpResponse::Unauthorized().json(serde_json::json!({
                 "error": "invalid_credentials",
-                "message": "Invalid email or password"
+                "message": message(locale, "invalid-credentials")
             })),
             AuthError::UserNotFound => HttpResponse::NotFound().json(serde_json::json!({
                 "error": "user_not_found",
-                "message": "User not found"
+                "message": message(locale, "user-not-found")
             })),
             AuthError::Validation(msg) => HttpResponse::BadRequest().json(serde_json::json!({
                 "error": "validation_error",
@@ -251,25 +527,47 @@ impl actix_web::ResponseError for AuthError {
             })),
             AuthError::InvalidPhoneNumber => HttpResponse::BadRequest().json(serde_json::json!({
                 "error": "invalid_phone_number",
-                "message": "Please enter a valid US phone number"
+                "message": message(locale, "invalid-phone-number")
+            })),
+            AuthError::TwoFactorAlreadyEnabled => {
+                HttpResponse::Conflict().json(serde_json::json!({
+                    "error": "two_factor_enabled",
+                    "message": message(locale, "two-factor-enabled")
+                }))
+            }
+            AuthError::TwoFactorNotSetUp => HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "two_factor_not_set_up",
+                "message": message(locale, "two-factor-not-set-up")
+            })),
+            AuthError::InvalidTwoFactorCode => {
+                HttpResponse::Unauthorized().json(serde_json::json!({
+                    "error": "invalid_two_factor_code",
+                    "message": message(locale, "invalid-two-factor-code")
+                }))
+            }
+            AuthError::TwoFactorLocked => HttpResponse::TooManyRequests().json(serde_json::json!({
+                "error": "too_many_attempts",
+                "message": message(locale, "too-many-attempts")
             })),
+            AuthError::InvalidChallenge => HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "invalid_challenge",
+                "message": message(locale, "invalid-challenge")
+            })),
+            AuthError::RateLimited(retry_after_secs) => {
+                let mut args = FluentArgs::new();
+                args.set("minutes", retry_after_secs.div_ceil(60));
+
+                HttpResponse::TooManyRequests()
+                    .insert_header(("Retry-After", retry_after_secs.to_string()))
+                    .json(serde_json::json!({
+                        "error": "rate_limited",
+                        "message": message_with(locale, "rate-limited", &args)
+                    }))
+            }
             _ => HttpResponse::InternalServerError().json(serde_json::json!({
                 "error": "internal_error",
-                "message": "An internal error occurred"
+                "message": message(locale, "internal-error")
             })),
         }
     }
 }
-
-/// Validates a US phone number format
-pub fn validate_phone_number(phone: &str) -> bool {
-    // Remove all non-digit characters
-    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
-
-    // US phone numbers should be 10 digits, or 11 if they include the country code (1)
-    match digits.len() {
-        10 => true,
-        11 => digits.starts_with('1'),
-        _ => false,
-    }
-}
@@ -0,0 +1,251 @@
+use chrono::{Duration, NaiveDate};
+use rec_gov::{CampsiteAvailability, is_available_status};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Sites available on each night polled. Nights polled with no site open map to an
+/// empty set; nights missing weren't polled, so nothing is known about them.
+pub type AvailableSites = BTreeMap<NaiveDate, BTreeSet<String>>;
+
+/// Sites bookable on each night of a poll, by campsite ID. Nights priced above
+/// `max_price` don't count as bookable; nights without a reported price do.
+pub fn available_sites(sites: &[CampsiteAvailability], max_price: Option<f64>) -> AvailableSites {
+    let mut available = AvailableSites::new();
+
+    for site in sites {
+        let prices: BTreeMap<NaiveDate, f64> = site.nightly_prices().collect();
+
+        for (date, status) in site.nights() {
+            let over_budget = max_price
+                .zip(prices.get(&date))
+                .is_some_and(|(max_price, price)| *price > max_price);
+
+            let night = available.entry(date).or_default();
+            if is_available_status(status) && !over_budget {
+                night.insert(site.campsite_id.clone());
+            }
+        }
+    }
+
+    available
+}
+
+/// Stays a scan accepts as of `today`, earliest first: its own dates shifted by up to
+/// its flexibility either way, without starting in the past.
+pub fn candidate_stays(
+    check_in_date: NaiveDate,
+    check_out_date: NaiveDate,
+    flexibility_days: i32,
+    today: NaiveDate,
+) -> Vec<(NaiveDate, NaiveDate)> {
+    let flexibility = i64::from(flexibility_days.max(0));
+
+    (-flexibility..=flexibility)
+        .map(|shift| {
+            (
+                check_in_date + Duration::days(shift),
+                check_out_date + Duration::days(shift),
+            )
+        })
+        .filter(|(check_in, _)| *check_in >= today)
+        .collect()
+}
+
+/// Sites bookable every night of at least one of `stays`. A night that wasn't polled
+/// counts as having no site open.
+pub fn matching_sites(
+    stays: &[(NaiveDate, NaiveDate)],
+    available: &AvailableSites,
+) -> BTreeSet<String> {
+    let mut matching = BTreeSet::new();
+
+    for (check_in, check_out) in stays {
+        let mut nights = check_in.iter_days().take_while(|date| date < check_out);
+        let Some(first) = nights.next() else {
+            continue;
+        };
+
+        let mut open = available.get(&first).cloned().unwrap_or_default();
+        for night in nights {
+            let Some(sites) = available.get(&night) else {
+                open.clear();
+                break;
+            };
+            open.retain(|site| sites.contains(site));
+        }
+
+        matching.extend(open);
+    }
+
+    matching
+}
+
+/// Sites bookable for one of `stays` now that weren't in the previous poll, so a scan is
+/// only alerted about a site once while it stays open. Sites stay new until a poll has
+/// covered every night of the stay, so a gap in the previous poll can't hide them.
+pub fn find_new_availability(
+    stays: &[(NaiveDate, NaiveDate)],
+    previous: &AvailableSites,
+    current: &AvailableSites,
+) -> BTreeSet<String> {
+    let before = matching_sites(stays, previous);
+
+    matching_sites(stays, current)
+        .into_iter()
+        .filter(|site| !before.contains(site))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 7, day).unwrap()
+    }
+
+    /// Availability from `(day, sites)` pairs
+    fn nights(nights: &[(u32, &[&str])]) -> AvailableSites {
+        nights
+            .iter()
+            .map(|(day, sites)| {
+                (
+                    date(*day),
+                    sites.iter().map(|site| site.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn sites(sites: &[&str]) -> BTreeSet<String> {
+        sites.iter().map(|site| site.to_string()).collect()
+    }
+
+    fn campsite(id: &str, statuses: &[(u32, &str)], price: Option<f64>) -> CampsiteAvailability {
+        let key = |day: &u32| format!("{}T00:00:00Z", date(*day).format("%Y-%m-%d"));
+
+        CampsiteAvailability {
+            campsite_id: id.to_string(),
+            site: id.to_string(),
+            loop_name: None,
+            campsite_type: Some("STANDARD NONELECTRIC".to_string()),
+            availabilities: statuses
+                .iter()
+                .map(|(day, status)| (key(day), status.to_string()))
+                .collect(),
+            quantities: BTreeMap::new(),
+            type_of_use: Some("Overnight".to_string()),
+            prices: price
+                .map(|price| statuses.iter().map(|(day, _)| (key(day), price)).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    #[test]
+    fn collects_bookable_sites_per_night() {
+        let polled = [
+            campsite("A", &[(1, "Available"), (2, "Reserved")], None),
+            campsite("B", &[(1, "Available"), (2, "Available")], Some(40.0)),
+            campsite("C", &[(1, "Available"), (2, "Available")], Some(80.0)),
+        ];
+
+        assert_eq!(
+            available_sites(&polled, None),
+            nights(&[(1, &["A", "B", "C"]), (2, &["B", "C"])])
+        );
+        // C is over budget; A has no price, so it still counts
+        assert_eq!(
+            available_sites(&polled, Some(50.0)),
+            nights(&[(1, &["A", "B"]), (2, &["B"])])
+        );
+        // Nights polled with nothing open are kept, empty
+        let booked = [campsite("A", &[(1, "Reserved")], None)];
+        assert_eq!(available_sites(&booked, None), nights(&[(1, &[])]));
+    }
+
+    #[test]
+    fn shifts_stays_by_flexibility_without_starting_in_the_past() {
+        assert_eq!(
+            candidate_stays(date(10), date(12), 0, date(1)),
+            vec![(date(10), date(12))]
+        );
+        assert_eq!(
+            candidate_stays(date(10), date(12), 2, date(9)),
+            vec![
+                (date(9), date(11)),
+                (date(10), date(12)),
+                (date(11), date(13)),
+                (date(12), date(14)),
+            ]
+        );
+        // Negative flexibility is treated as none
+        assert_eq!(candidate_stays(date(10), date(12), -3, date(1)).len(), 1);
+        assert!(candidate_stays(date(10), date(12), 1, date(12)).is_empty());
+    }
+
+    #[test]
+    fn matches_sites_open_every_night_of_a_stay() {
+        let stay = [(date(1), date(4))];
+
+        // Open for the whole stay
+        let available = nights(&[(1, &["A", "B"]), (2, &["A", "B"]), (3, &["A"])]);
+        assert_eq!(matching_sites(&stay, &available), sites(&["A"]));
+
+        // Partial ranges don't match, even when every night has some site open
+        let available = nights(&[(1, &["A"]), (2, &["A", "B"]), (3, &["B"])]);
+        assert!(matching_sites(&stay, &available).is_empty());
+
+        // A night that wasn't polled can't be assumed open
+        let available = nights(&[(1, &["A"]), (3, &["A"])]);
+        assert!(matching_sites(&stay, &available).is_empty());
+
+        // Stays without nights match nothing
+        let available = nights(&[(1, &["A"])]);
+        assert!(matching_sites(&[(date(1), date(1))], &available).is_empty());
+    }
+
+    #[test]
+    fn matches_any_flexible_stay() {
+        let stays = candidate_stays(date(2), date(4), 1, date(1));
+        let available = nights(&[(1, &["A"]), (2, &["A", "B"]), (3, &["B", "C"]), (4, &["C"])]);
+
+        // A fits July 1-3, B July 2-4, C July 3-5
+        assert_eq!(matching_sites(&stays, &available), sites(&["A", "B", "C"]));
+        assert_eq!(matching_sites(&stays[1..2], &available), sites(&["B"]));
+    }
+
+    #[test]
+    fn finds_sites_newly_open_for_a_stay() {
+        let stay = [(date(1), date(3))];
+        let previous = nights(&[(1, &["A", "B"]), (2, &["A"])]);
+
+        // A was already open; B completes the stay once night 2 opens up
+        let current = nights(&[(1, &["A", "B"]), (2, &["A", "B"])]);
+        assert_eq!(
+            find_new_availability(&stay, &previous, &current),
+            sites(&["B"])
+        );
+
+        // Openings outside the stay don't count
+        let current = nights(&[(1, &["A", "B"]), (2, &["A"]), (3, &["B"])]);
+        assert!(find_new_availability(&stay, &previous, &current).is_empty());
+
+        // A site that closed and reopened is new again
+        let closed = nights(&[(1, &["B"]), (2, &[])]);
+        let reopened = nights(&[(1, &["A", "B"]), (2, &["A"])]);
+        assert_eq!(
+            find_new_availability(&stay, &closed, &reopened),
+            sites(&["A"])
+        );
+
+        // Without a previous poll of every night, everything open is new
+        let partial = nights(&[(1, &["A"])]);
+        assert_eq!(
+            find_new_availability(&stay, &partial, &previous),
+            sites(&["A"])
+        );
+        assert_eq!(
+            find_new_availability(&stay, &AvailableSites::new(), &previous),
+            sites(&["A"])
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use chrono::NaiveDate;
+use rec_gov::{CampsiteAvailability, is_available_status, is_closed_status};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+
+/// Per-night snapshots of polled campgrounds in `campground_availability`: how many sites
+/// were open and listed, the nightly price of each site whose price was reported, and how
+/// many polls in a row found no site reservable at all
+#[derive(Debug, Clone)]
+pub struct AvailabilitySnapshots {
+    pool: PgPool,
+}
+
+impl AvailabilitySnapshots {
+    /// Creates a store writing to `pool`
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Records every night of `start..end` from the sites of one poll. A night polled
+    /// without prices keeps the prices recorded earlier. A night no site reports as
+    /// reservable (booked counts as reservable; closed doesn't) extends its closed
+    /// streak, and any other night ends it. Failures are logged, since a poll shouldn't
+    /// fail over its bookkeeping.
+    pub async fn record(
+        &self,
+        campground_id: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+        sites: &[CampsiteAvailability],
+    ) {
+        let nights: Vec<NaiveDate> = start.iter_days().take_while(|date| *date < end).collect();
+        let mut available: BTreeMap<NaiveDate, i32> = BTreeMap::new();
+        let mut reservable: BTreeMap<NaiveDate, i32> = BTreeMap::new();
+        let mut prices: BTreeMap<NaiveDate, serde_json::Map<String, serde_json::Value>> =
+            BTreeMap::new();
+
+        for site in sites {
+            for (date, status) in site.nights() {
+                if is_available_status(status) {
+                    *available.entry(date).or_default() += 1;
+                }
+                if !is_closed_status(status) {
+                    *reservable.entry(date).or_default() += 1;
+                }
+            }
+            for (date, price) in site.nightly_prices() {
+                prices
+                    .entry(date)
+                    .or_default()
+                    .insert(site.campsite_id.clone(), price.into());
+            }
+        }
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO campground_availability (
+                campground_id, date, available_sites, total_sites, site_prices,
+                last_checked, check_status, error_message, closed_polls, closed_since
+            )
+            SELECT $1, night.date, night.available_sites, $2,
+                   NULLIF(night.site_prices, '{}'::JSONB), NOW(), 'success', NULL,
+                   CASE WHEN night.closed THEN 1 ELSE 0 END,
+                   CASE WHEN night.closed THEN NOW() END
+            FROM UNNEST($3::DATE[], $4::INTEGER[], $5::JSONB[], $6::BOOLEAN[])
+                AS night(date, available_sites, site_prices, closed)
+            ON CONFLICT (campground_id, date) DO UPDATE SET
+                available_sites = EXCLUDED.available_sites,
+                total_sites = EXCLUDED.total_sites,
+                site_prices = COALESCE(EXCLUDED.site_prices, campground_availability.site_prices),
+                last_checked = NOW(),
+                check_status = 'success',
+                error_message = NULL,
+                closed_polls = CASE WHEN EXCLUDED.closed_polls > 0
+                                    THEN campground_availability.closed_polls + 1 ELSE 0 END,
+                closed_since = CASE WHEN EXCLUDED.closed_polls > 0
+                                    THEN COALESCE(campground_availability.closed_since, NOW()) END
+            "#,
+        )
+        .bind(campground_id)
+        .bind(sites.len().min(i32::MAX as usize) as i32)
+        .bind(&nights)
+        .bind(
+            nights
+                .iter()
+                .map(|date| available.get(date).copied().unwrap_or(0))
+                .collect::<Vec<i32>>(),
+        )
+        .bind(
+            nights
+                .iter()
+                .map(|date| serde_json::Value::Object(prices.remove(date).unwrap_or_default()))
+                .collect::<Vec<serde_json::Value>>(),
+        )
+        .bind(
+            nights
+                .iter()
+                .map(|date| !reservable.contains_key(date))
+                .collect::<Vec<bool>>(),
+        )
+        .execute(&self.pool)
+        .await;
+
+        if let Err(e) = result {
+            log::warn!(
+                "⚠️ Failed to record availability of campground {}: {}",
+                campground_id,
+                e
+            );
+        }
+    }
+}
@@ -0,0 +1,682 @@
+use chrono::{DateTime, NaiveDate, Timelike, Utc};
+use rec_gov::{CampsiteAvailability, is_available_status};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::BTreeMap;
+
+/// Minimum number of booked windows before a campground's median is trusted.
+const MIN_TIME_TO_BOOK_SAMPLES: i64 = 5;
+
+/// Number of hours of the day reported as the best times to scan
+const BEST_SCAN_WINDOWS: usize = 3;
+
+/// Sites opening up in a single poll that always count as a release
+const RELEASE_BURST_SITES: usize = 5;
+
+/// Releases go live on the hour; openings in the first minutes of an hour where a release
+/// was seen before are likely releases too
+const RELEASE_MINUTES_PAST_HOUR: u32 = 10;
+
+/// Days of history searched for earlier releases
+const RELEASE_HISTORY_DAYS: i32 = 90;
+
+/// Likely cause of sites opening up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeReason {
+    /// A booked site was cancelled and is available again
+    Cancellation,
+    /// The campground released new inventory (e.g. a new booking window opened)
+    InventoryRelease,
+}
+
+impl ChangeReason {
+    /// Value stored in `availability_history.change_reason`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeReason::Cancellation => "cancellation",
+            ChangeReason::InventoryRelease => "inventory_release",
+        }
+    }
+
+    /// ID of the message explaining the reason in availability alerts
+    pub fn message_id(&self) -> &'static str {
+        match self {
+            ChangeReason::Cancellation => "alert-reason-cancellation",
+            ChangeReason::InventoryRelease => "alert-reason-release",
+        }
+    }
+}
+
+/// Guesses why sites opened up in a poll. Many sites at once, or a few sites right after
+/// the hour at a time of day the campground released inventory before, look like a
+/// release; anything else looks like a cancellation. Returns `None` when nothing opened
+/// up or this is the first poll of the night (every site looks new).
+pub fn classify_change(
+    opened_sites: usize,
+    observed_at: DateTime<Utc>,
+    first_snapshot: bool,
+    released_at_this_hour_before: bool,
+) -> Option<ChangeReason> {
+    if opened_sites == 0 || first_snapshot {
+        return None;
+    }
+
+    let near_the_hour = observed_at.minute() < RELEASE_MINUTES_PAST_HOUR;
+
+    if opened_sites >= RELEASE_BURST_SITES
+        || (opened_sites > 1 && near_the_hour && released_at_this_hour_before)
+    {
+        Some(ChangeReason::InventoryRelease)
+    } else {
+        Some(ChangeReason::Cancellation)
+    }
+}
+
+/// Why the sites of an alert likely opened up, from the reasons recorded for each night
+/// polled: a release if any night the sites are open saw one, otherwise a cancellation
+/// if any did. Returns `None` when no night they're open was classified.
+pub fn alert_reason(
+    reasons: &BTreeMap<NaiveDate, ChangeReason>,
+    sites: &[CampsiteAvailability],
+) -> Option<ChangeReason> {
+    let open_reasons: Vec<ChangeReason> = sites
+        .iter()
+        .flat_map(|site| site.nights())
+        .filter(|(_, status)| is_available_status(status))
+        .filter_map(|(night, _)| reasons.get(&night).copied())
+        .collect();
+
+    [ChangeReason::InventoryRelease, ChangeReason::Cancellation]
+        .into_iter()
+        .find(|reason| open_reasons.contains(reason))
+}
+
+/// How quickly sites at a campground get booked once they open up
+#[derive(Debug, Serialize)]
+pub struct CampgroundTimeToBook {
+    /// ID of the campground
+    pub campground_id: String,
+    /// Name of the campground, if known
+    pub campground_name: Option<String>,
+    /// Median minutes between a site opening up and it being booked
+    pub median_minutes: f64,
+    /// Number of booked windows the median is computed from
+    pub samples: i64,
+}
+
+/// Query parameters for the time-to-book report
+#[derive(Debug, Deserialize)]
+pub struct TimeToBookQuery {
+    /// Number of days of booked windows to report on (defaults to 30)
+    pub days: Option<i32>,
+}
+
+/// How often alerts at a campground turned into bookings
+#[derive(Debug, Serialize)]
+pub struct CampgroundAlertConversion {
+    /// ID of the campground
+    pub campground_id: String,
+    /// Name of the campground, if known
+    pub campground_name: Option<String>,
+    /// How often the campground is polled, in minutes
+    pub poll_frequency_minutes: Option<i32>,
+    /// Scans alerted at least once
+    pub alerted_scans: i64,
+    /// Alerts sent for them
+    pub alerts: i64,
+    /// Alerted scans whose owner booked a site
+    pub booked: i64,
+    /// Alerted scans whose owner found the sites gone
+    pub missed: i64,
+    /// Alerted scans whose owner gave up
+    pub gave_up: i64,
+    /// Share of alerted scans that were booked, from 0 to 1
+    pub conversion_rate: f64,
+    /// Median minutes from a scan's first alert to its owner reporting the booking
+    pub median_minutes_to_book: Option<f64>,
+}
+
+/// Query parameters for the alert conversion report
+#[derive(Debug, Deserialize)]
+pub struct AlertConversionQuery {
+    /// Number of days of alerts to report on (defaults to 30)
+    pub days: Option<i32>,
+}
+
+/// Query parameters for campground statistics
+#[derive(Debug, Deserialize)]
+pub struct CampgroundStatsQuery {
+    /// Number of days of history to report on (defaults to 90)
+    pub days: Option<i32>,
+}
+
+/// Sites that opened up on one day of the week
+#[derive(Debug, Serialize)]
+pub struct WeekdayOpenings {
+    /// ISO day of the week (1 = Monday, 7 = Sunday), in UTC
+    pub weekday: i32,
+    /// Number of sites that opened up
+    pub openings: i64,
+    /// Number of those that were likely cancellations
+    pub cancellations: i64,
+}
+
+/// Sites that opened up during one hour of the day
+#[derive(Debug, Serialize)]
+pub struct HourlyOpenings {
+    /// Hour of the day (0-23), in UTC
+    pub hour: i32,
+    /// Number of sites that opened up
+    pub openings: i64,
+}
+
+/// Availability trends of a campground
+#[derive(Debug, Serialize)]
+pub struct CampgroundStats {
+    /// ID of the campground
+    pub campground_id: String,
+    /// Number of days of history covered
+    pub days: i32,
+    /// Number of polls recorded
+    pub snapshots: i64,
+    /// Sites that opened up per day of the week
+    pub openings_by_weekday: Vec<WeekdayOpenings>,
+    /// Sites that opened up, by likely cause (`cancellation`, `inventory_release`)
+    pub openings_by_reason: BTreeMap<String, i64>,
+    /// Average minutes an opened site stayed available before being booked again
+    pub average_minutes_to_rebook: Option<f64>,
+    /// Hours of the day with the most openings, most first
+    pub best_scan_windows: Vec<HourlyOpenings>,
+}
+
+/// Tracks when campsites open up and get booked, and derives per-campground
+/// time-to-book statistics from it.
+#[derive(Clone)]
+pub struct BookingAnalytics {
+    pool: PgPool,
+}
+
+impl BookingAnalytics {
+    /// Creates a new instance of `BookingAnalytics` with the provided database connection pool
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Records the result of polling a campground for one night: opens a window for
+    /// every newly available site, closes the windows of sites no longer available, and
+    /// appends the snapshot to the availability history. Returns the likely reason sites
+    /// opened up, for availability alerts.
+    pub async fn record_poll(
+        &self,
+        campground_id: &str,
+        date: NaiveDate,
+        available_site_ids: &[String],
+    ) -> Result<Option<ChangeReason>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let observed_at = Utc::now();
+
+        let history = sqlx::query(
+            r#"
+            SELECT
+                EXISTS (
+                    SELECT 1 FROM availability_history
+                    WHERE campground_id = $1 AND date = $2
+                ) AS seen_before,
+                EXISTS (
+                    SELECT 1 FROM availability_history
+                    WHERE campground_id = $1 AND change_reason = 'inventory_release'
+                      AND observed_at >= $3 - make_interval(days => $4)
+                      AND EXTRACT(HOUR FROM observed_at AT TIME ZONE 'UTC') = $5
+                ) AS released_at_this_hour
+            "#,
+        )
+        .bind(campground_id)
+        .bind(date)
+        .bind(observed_at)
+        .bind(RELEASE_HISTORY_DAYS)
+        .bind(observed_at.hour() as i32)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let opened_site_ids: Vec<String> = sqlx::query(
+            r#"
+            INSERT INTO site_availability_windows (campground_id, campsite_id, date)
+            SELECT $1, site_id, $2 FROM UNNEST($3::TEXT[]) AS site_id
+            ON CONFLICT (campground_id, campsite_id, date) WHERE closed_at IS NULL
+            DO NOTHING
+            RETURNING campsite_id
+            "#,
+        )
+        .bind(campground_id)
+        .bind(date)
+        .bind(available_site_ids)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|row| row.get("campsite_id"))
+        .collect();
+
+        let closed_site_ids: Vec<String> = sqlx::query(
+            r#"
+            UPDATE site_availability_windows
+            SET closed_at = NOW()
+            WHERE campground_id = $1 AND date = $2 AND closed_at IS NULL
+              AND NOT (campsite_id = ANY($3))
+            RETURNING campsite_id
+            "#,
+        )
+        .bind(campground_id)
+        .bind(date)
+        .bind(available_site_ids)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|row| row.get("campsite_id"))
+        .collect();
+
+        let change_reason = classify_change(
+            opened_site_ids.len(),
+            observed_at,
+            !history.get::<bool, _>("seen_before"),
+            history.get("released_at_this_hour"),
+        );
+
+        sqlx::query(
+            r#"
+            INSERT INTO availability_history (
+                campground_id, date, observed_at, available_site_ids, opened_site_ids,
+                closed_site_ids, change_reason
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(campground_id)
+        .bind(date)
+        .bind(observed_at)
+        .bind(available_site_ids)
+        .bind(&opened_site_ids)
+        .bind(&closed_site_ids)
+        .bind(change_reason.map(|reason| reason.as_str()))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(change_reason)
+    }
+
+    /// Returns the median time-to-book per campground over windows closed in the last
+    /// `days` days, fastest first. Windows that closed because the night passed are
+    /// not bookings and are ignored.
+    pub async fn time_to_book(&self, days: i32) -> Result<Vec<CampgroundTimeToBook>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                w.campground_id, c.name AS campground_name,
+                PERCENTILE_CONT(0.5) WITHIN GROUP (
+                    ORDER BY EXTRACT(EPOCH FROM (w.closed_at - w.opened_at))::FLOAT8 / 60
+                ) AS median_minutes,
+                COUNT(*) AS samples
+            FROM site_availability_windows w
+            LEFT JOIN campgrounds c ON c.id = w.campground_id
+            WHERE w.closed_at >= NOW() - make_interval(days => $1)
+              AND w.closed_at::DATE <= w.date
+            GROUP BY w.campground_id, c.name
+            HAVING COUNT(*) >= $2
+            ORDER BY median_minutes
+            "#,
+        )
+        .bind(days)
+        .bind(MIN_TIME_TO_BOOK_SAMPLES)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let stats = rows
+            .into_iter()
+            .map(|row| CampgroundTimeToBook {
+                campground_id: row.get("campground_id"),
+                campground_name: row.get("campground_name"),
+                median_minutes: row.get("median_minutes"),
+                samples: row.get("samples"),
+            })
+            .collect();
+
+        Ok(stats)
+    }
+
+    /// Returns, per campground, how many scans alerted in the last `days` days ended up
+    /// booked, missed, or abandoned according to their owners, with the campground's
+    /// poll frequency, most alerted first. Campgrounds with many misses are the ones
+    /// worth polling faster.
+    pub async fn alert_conversion(
+        &self,
+        days: i32,
+    ) -> Result<Vec<CampgroundAlertConversion>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            WITH alerted AS (
+                SELECT user_scan_id, MIN(created_at) AS first_alert_at, COUNT(*) AS alerts
+                FROM scan_events
+                WHERE event_type = 'notification_sent'
+                  AND created_at >= NOW() - make_interval(days => $1)
+                GROUP BY user_scan_id
+            )
+            SELECT
+                us.campground_id, c.name AS campground_name, pj.poll_frequency_minutes,
+                COUNT(*) AS alerted_scans,
+                SUM(a.alerts)::BIGINT AS alerts,
+                COUNT(*) FILTER (WHERE us.outcome = 'booked') AS booked,
+                COUNT(*) FILTER (WHERE us.outcome = 'missed') AS missed,
+                COUNT(*) FILTER (WHERE us.outcome = 'gave_up') AS gave_up,
+                PERCENTILE_CONT(0.5) WITHIN GROUP (
+                    ORDER BY EXTRACT(EPOCH FROM (us.outcome_at - a.first_alert_at))::FLOAT8 / 60
+                ) FILTER (
+                    WHERE us.outcome = 'booked' AND us.outcome_at >= a.first_alert_at
+                ) AS median_minutes_to_book
+            FROM alerted a
+            JOIN user_scans us ON us.id = a.user_scan_id
+            LEFT JOIN campgrounds c ON c.id = us.campground_id
+            LEFT JOIN polling_jobs pj ON pj.campground_id = us.campground_id
+            GROUP BY us.campground_id, c.name, pj.poll_frequency_minutes
+            ORDER BY alerted_scans DESC, us.campground_id
+            "#,
+        )
+        .bind(days)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let alerted_scans: i64 = row.get("alerted_scans");
+                let booked: i64 = row.get("booked");
+
+                CampgroundAlertConversion {
+                    campground_id: row.get("campground_id"),
+                    campground_name: row.get("campground_name"),
+                    poll_frequency_minutes: row.get("poll_frequency_minutes"),
+                    alerted_scans,
+                    alerts: row.get("alerts"),
+                    booked,
+                    missed: row.get("missed"),
+                    gave_up: row.get("gave_up"),
+                    conversion_rate: booked as f64 / alerted_scans.max(1) as f64,
+                    median_minutes_to_book: row.get("median_minutes_to_book"),
+                }
+            })
+            .collect())
+    }
+
+    /// Returns a campground's availability trends over the last `days` days: when sites
+    /// open up, and how long they stay available before being booked again.
+    pub async fn campground_stats(
+        &self,
+        campground_id: &str,
+        days: i32,
+    ) -> Result<CampgroundStats, sqlx::Error> {
+        let snapshots: i64 = sqlx::query(
+            r#"
+            SELECT COUNT(*) AS snapshots
+            FROM availability_history
+            WHERE campground_id = $1 AND observed_at >= NOW() - make_interval(days => $2)
+            "#,
+        )
+        .bind(campground_id)
+        .bind(days)
+        .fetch_one(&self.pool)
+        .await?
+        .get("snapshots");
+
+        let openings_by_weekday = sqlx::query(
+            r#"
+            SELECT
+                EXTRACT(ISODOW FROM observed_at AT TIME ZONE 'UTC')::INT4 AS weekday,
+                SUM(CARDINALITY(opened_site_ids))::INT8 AS openings,
+                COALESCE(
+                    SUM(CARDINALITY(opened_site_ids)) FILTER (WHERE change_reason = 'cancellation'),
+                    0
+                )::INT8 AS cancellations
+            FROM availability_history
+            WHERE campground_id = $1 AND observed_at >= NOW() - make_interval(days => $2)
+            GROUP BY weekday
+            ORDER BY weekday
+            "#,
+        )
+        .bind(campground_id)
+        .bind(days)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| WeekdayOpenings {
+            weekday: row.get("weekday"),
+            openings: row.get("openings"),
+            cancellations: row.get("cancellations"),
+        })
+        .collect();
+
+        let openings_by_reason = sqlx::query(
+            r#"
+            SELECT change_reason, SUM(CARDINALITY(opened_site_ids))::INT8 AS openings
+            FROM availability_history
+            WHERE campground_id = $1 AND observed_at >= NOW() - make_interval(days => $2)
+              AND change_reason IS NOT NULL
+            GROUP BY change_reason
+            "#,
+        )
+        .bind(campground_id)
+        .bind(days)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.get("change_reason"), row.get("openings")))
+        .collect();
+
+        let best_scan_windows = sqlx::query(
+            r#"
+            SELECT
+                EXTRACT(HOUR FROM observed_at AT TIME ZONE 'UTC')::INT4 AS hour,
+                SUM(CARDINALITY(opened_site_ids))::INT8 AS openings
+            FROM availability_history
+            WHERE campground_id = $1 AND observed_at >= NOW() - make_interval(days => $2)
+              AND CARDINALITY(opened_site_ids) > 0
+            GROUP BY hour
+            ORDER BY openings DESC, hour
+            LIMIT $3
+            "#,
+        )
+        .bind(campground_id)
+        .bind(days)
+        .bind(BEST_SCAN_WINDOWS as i64)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| HourlyOpenings {
+            hour: row.get("hour"),
+            openings: row.get("openings"),
+        })
+        .collect();
+
+        // Windows that closed because the night passed weren't booked
+        let average_minutes_to_rebook: Option<f64> = sqlx::query(
+            r#"
+            SELECT AVG(EXTRACT(EPOCH FROM (closed_at - opened_at))::FLOAT8 / 60) AS minutes
+            FROM site_availability_windows
+            WHERE campground_id = $1 AND closed_at >= NOW() - make_interval(days => $2)
+              AND closed_at::DATE <= date
+            "#,
+        )
+        .bind(campground_id)
+        .bind(days)
+        .fetch_one(&self.pool)
+        .await?
+        .get("minutes");
+
+        Ok(CampgroundStats {
+            campground_id: campground_id.to_string(),
+            days,
+            snapshots,
+            openings_by_weekday,
+            openings_by_reason,
+            average_minutes_to_rebook,
+            best_scan_windows,
+        })
+    }
+
+    /// Polls fast-turnover campgrounds more often: sets each polling job's frequency from
+    /// its campground's median time-to-book. Returns the number of jobs updated;
+    /// campgrounds without enough samples, in sniper mode, or pinned by an admin keep
+    /// their current frequency.
+    /// The order jobs are polled in comes from [`PriorityScoring`](crate::PriorityScoring).
+    pub async fn apply_poll_frequencies(&self, days: i32) -> Result<u64, sqlx::Error> {
+        let mut updated = 0;
+
+        for stats in self.time_to_book(days).await? {
+            let frequency_minutes = poll_frequency_for(stats.median_minutes);
+
+            updated += sqlx::query(
+                r#"
+                UPDATE polling_jobs
+                SET poll_frequency_minutes = $1, updated_at = NOW()
+                WHERE campground_id = $2
+                  AND sniper_until IS NULL
+                  AND NOT pinned
+                  AND poll_frequency_minutes IS DISTINCT FROM $1
+                "#,
+            )
+            .bind(frequency_minutes)
+            .bind(&stats.campground_id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        }
+
+        Ok(updated)
+    }
+}
+
+/// Poll frequency in minutes for a campground whose sites are typically booked within
+/// `median_minutes` of opening up.
+pub fn poll_frequency_for(median_minutes: f64) -> i32 {
+    if median_minutes < 30.0 {
+        5
+    } else if median_minutes < 120.0 {
+        10
+    } else {
+        15
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 7, day).unwrap()
+    }
+
+    fn site(name: &str, open_nights: &[NaiveDate]) -> CampsiteAvailability {
+        CampsiteAvailability {
+            campsite_id: format!("232447-{}", name),
+            site: name.to_string(),
+            loop_name: Some("Loop A".to_string()),
+            campsite_type: Some("STANDARD NONELECTRIC".to_string()),
+            availabilities: open_nights
+                .iter()
+                .map(|night| {
+                    (
+                        format!("{}T00:00:00Z", night.format("%Y-%m-%d")),
+                        "Available".to_string(),
+                    )
+                })
+                .collect(),
+            quantities: BTreeMap::new(),
+            type_of_use: Some("Overnight".to_string()),
+            prices: BTreeMap::new(),
+        }
+    }
+
+    /// A poll at `minute` past 7am
+    fn polled_at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, 1, 7, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn nothing_opening_up_has_no_reason() {
+        assert_eq!(classify_change(0, polled_at(0), false, true), None);
+        // Every site looks new on the first poll of a night
+        assert_eq!(classify_change(20, polled_at(0), true, true), None);
+    }
+
+    #[test]
+    fn bursts_of_sites_are_releases() {
+        for (opened, reason) in [
+            (1, ChangeReason::Cancellation),
+            (RELEASE_BURST_SITES - 1, ChangeReason::Cancellation),
+            (RELEASE_BURST_SITES, ChangeReason::InventoryRelease),
+            (RELEASE_BURST_SITES + 10, ChangeReason::InventoryRelease),
+        ] {
+            assert_eq!(
+                classify_change(opened, polled_at(30), false, false),
+                Some(reason),
+                "{} sites",
+                opened
+            );
+        }
+    }
+
+    #[test]
+    fn a_few_sites_right_after_a_past_release_hour_are_a_release() {
+        for (opened, minute, released_before, reason) in [
+            (2, 0, true, ChangeReason::InventoryRelease),
+            (
+                2,
+                RELEASE_MINUTES_PAST_HOUR - 1,
+                true,
+                ChangeReason::InventoryRelease,
+            ),
+            (
+                2,
+                RELEASE_MINUTES_PAST_HOUR,
+                true,
+                ChangeReason::Cancellation,
+            ),
+            (2, 0, false, ChangeReason::Cancellation),
+            // A single site is a cancellation even at release time
+            (1, 0, true, ChangeReason::Cancellation),
+        ] {
+            assert_eq!(
+                classify_change(opened, polled_at(minute), false, released_before),
+                Some(reason),
+                "{} sites at :{:02}, released before: {}",
+                opened,
+                minute,
+                released_before
+            );
+        }
+    }
+
+    #[test]
+    fn alerts_take_the_reason_of_the_nights_their_sites_are_open() {
+        let reasons = BTreeMap::from([
+            (date(1), ChangeReason::Cancellation),
+            (date(3), ChangeReason::InventoryRelease),
+        ]);
+
+        assert_eq!(
+            alert_reason(&reasons, &[site("A1", &[date(1), date(2)])]),
+            Some(ChangeReason::Cancellation)
+        );
+        // A release on any open night wins
+        assert_eq!(
+            alert_reason(&reasons, &[site("A1", &[date(1)]), site("B2", &[date(3)])]),
+            Some(ChangeReason::InventoryRelease)
+        );
+        assert_eq!(alert_reason(&reasons, &[site("A1", &[date(2)])]), None);
+        assert_eq!(
+            alert_reason(&BTreeMap::new(), &[site("A1", &[date(1)])]),
+            None
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use rec_gov::{Facility, Provider, RecGovApi};
+use sqlx::{PgPool, Row};
+
+/// Outcome of one metadata sync
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CampgroundSyncSummary {
+    /// Campgrounds with active or paused scans that were looked up
+    pub checked: usize,
+    /// Campgrounds whose row was refreshed
+    pub updated: usize,
+    /// Campgrounds RIDB no longer knows
+    pub missing: usize,
+    /// Campgrounds whose lookup or update failed
+    pub failed: usize,
+}
+
+/// Keeps the `campgrounds` table (names, coordinates, state, site counts, reservable
+/// flag) in line with RIDB for every campground users are scanning, so campgrounds
+/// first stored with just an ID get a proper name
+#[derive(Debug, Clone)]
+pub struct CampgroundSync {
+    pool: PgPool,
+}
+
+impl CampgroundSync {
+    /// Creates a sync writing to `pool`
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Refreshes the metadata of each recreation.gov campground with active or paused
+    /// scans. Campgrounds RIDB doesn't know are left as they are; lookup failures are
+    /// logged and counted so one bad campground doesn't stop the others.
+    pub async fn sync<R: RecGovApi>(
+        &self,
+        rec_gov: &R,
+    ) -> Result<CampgroundSyncSummary, sqlx::Error> {
+        let mut summary = CampgroundSyncSummary::default();
+
+        for campground_id in self.scanned_campgrounds().await? {
+            summary.checked += 1;
+
+            let facility = match rec_gov.facility(&campground_id).await {
+                Ok(Some(facility)) => facility,
+                Ok(None) => {
+                    log::warn!("⚠️ RIDB no longer knows campground {}", campground_id);
+                    summary.missing += 1;
+                    continue;
+                }
+                Err(e) => {
+                    log::warn!("⚠️ Failed to look up campground {}: {}", campground_id, e);
+                    summary.failed += 1;
+                    continue;
+                }
+            };
+
+            let total_sites = match rec_gov.facility_campsites(&campground_id).await {
+                Ok(campsites) => Some(campsites.len() as i32),
+                Err(e) => {
+                    log::warn!(
+                        "⚠️ Failed to count campsites of campground {}: {}",
+                        campground_id,
+                        e
+                    );
+                    None
+                }
+            };
+
+            match self.store(&campground_id, &facility, total_sites).await {
+                Ok(()) => summary.updated += 1,
+                Err(e) => {
+                    log::warn!("⚠️ Failed to update campground {}: {}", campground_id, e);
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Recreation.gov campgrounds with active or paused scans
+    async fn scanned_campgrounds(&self) -> Result<Vec<String>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT campground_id
+            FROM user_scans
+            WHERE status IN ('active', 'paused')
+              AND deleted_at IS NULL
+              AND provider = $1
+            ORDER BY campground_id
+            "#,
+        )
+        .bind(Provider::RecreationGov.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| row.get("campground_id"))
+            .collect())
+    }
+
+    /// Writes RIDB's view of a facility over its campgrounds row, keeping stored values
+    /// RIDB leaves empty
+    async fn store(
+        &self,
+        campground_id: &str,
+        facility: &Facility,
+        total_sites: Option<i32>,
+    ) -> Result<(), sqlx::Error> {
+        let (latitude, longitude) = facility.coordinates().unzip();
+
+        sqlx::query(
+            r#"
+            INSERT INTO campgrounds (
+                id, name, state, latitude, longitude, total_sites, is_reservable, description
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, true), $8)
+            ON CONFLICT (id) DO UPDATE SET
+                name = EXCLUDED.name,
+                state = COALESCE(EXCLUDED.state, campgrounds.state),
+                latitude = COALESCE(EXCLUDED.latitude, campgrounds.latitude),
+                longitude = COALESCE(EXCLUDED.longitude, campgrounds.longitude),
+                total_sites = COALESCE(EXCLUDED.total_sites, campgrounds.total_sites),
+                is_reservable = COALESCE($7, campgrounds.is_reservable),
+                description = COALESCE(NULLIF(EXCLUDED.description, ''), campgrounds.description),
+                last_updated = NOW()
+            "#,
+        )
+        .bind(campground_id)
+        .bind(&facility.name)
+        .bind(facility.state_code())
+        .bind(latitude)
+        .bind(longitude)
+        .bind(total_sites)
+        .bind(facility.reservable)
+        .bind(&facility.description)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use rec_gov::{RecGovApi, RecGovError, SiteAttributes};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+
+/// Days campsite attributes are trusted before RIDB is asked again
+const ATTRIBUTES_TTL_DAYS: i32 = 30;
+
+/// Campsite attributes (hookups, vehicle length, pet rules) kept in the `campsites` table,
+/// fetched from RIDB the first time a scan with site filters polls a campground
+#[derive(Debug, Clone)]
+pub struct CampsiteAttributeStore {
+    pool: PgPool,
+}
+
+impl CampsiteAttributeStore {
+    /// Creates a store reading and writing `pool`
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Attributes of each campsite of a campground, keyed by campsite ID. They come from
+    /// the `campsites` table when synced within the last month, and are otherwise
+    /// fetched from RIDB (one call per site) and stored. Database failures are logged,
+    /// since the attributes can still be fetched.
+    pub async fn for_campground<R: RecGovApi>(
+        &self,
+        rec_gov: &R,
+        campground_id: &str,
+    ) -> Result<HashMap<String, SiteAttributes>, RecGovError> {
+        match self.stored(campground_id).await {
+            Ok(stored) if !stored.is_empty() => return Ok(stored),
+            Ok(_) => {}
+            Err(e) => log::warn!(
+                "⚠️ Failed to read site attributes of campground {}: {}",
+                campground_id,
+                e
+            ),
+        }
+
+        self.sync(rec_gov, campground_id).await
+    }
+
+    /// Fetches the campsites of a campground and their attributes from RIDB, and stores
+    /// them in the `campsites` table
+    pub async fn sync<R: RecGovApi>(
+        &self,
+        rec_gov: &R,
+        campground_id: &str,
+    ) -> Result<HashMap<String, SiteAttributes>, RecGovError> {
+        let campsites = rec_gov.facility_campsites(campground_id).await?;
+        let mut attributes = HashMap::new();
+        let mut stored = 0;
+
+        for campsite in campsites {
+            let ridb_attributes = rec_gov.campsite_attributes(&campsite.id).await?;
+            let site = SiteAttributes::from_ridb(&ridb_attributes);
+            let amenities: serde_json::Map<String, serde_json::Value> = ridb_attributes
+                .into_iter()
+                .map(|attribute| (attribute.name, attribute.value.into()))
+                .collect();
+
+            let result = sqlx::query(
+                r#"
+                INSERT INTO campsites (
+                    id, campground_id, site_name, site_type, loop_name, is_accessible,
+                    amenities, electric_hookup, water_hookup, sewer_hookup,
+                    max_vehicle_length, pets_allowed, attributes_updated_at, last_updated
+                )
+                VALUES ($1, $2, $3, LEFT($4, 50), $5, $6, $7, $8, $9, $10, $11, $12, NOW(), NOW())
+                ON CONFLICT (id) DO UPDATE SET
+                    site_name = EXCLUDED.site_name,
+                    site_type = EXCLUDED.site_type,
+                    loop_name = EXCLUDED.loop_name,
+                    is_accessible = EXCLUDED.is_accessible,
+                    amenities = EXCLUDED.amenities,
+                    electric_hookup = EXCLUDED.electric_hookup,
+                    water_hookup = EXCLUDED.water_hookup,
+                    sewer_hookup = EXCLUDED.sewer_hookup,
+                    max_vehicle_length = EXCLUDED.max_vehicle_length,
+                    pets_allowed = EXCLUDED.pets_allowed,
+                    attributes_updated_at = NOW(),
+                    last_updated = NOW()
+                "#,
+            )
+            .bind(&campsite.id)
+            .bind(campground_id)
+            .bind(&campsite.name)
+            .bind(&campsite.site_type)
+            .bind(&campsite.loop_name)
+            .bind(campsite.accessible)
+            .bind(serde_json::Value::Object(amenities))
+            .bind(site.electric_hookup)
+            .bind(site.water_hookup)
+            .bind(site.sewer_hookup)
+            .bind(site.max_vehicle_length)
+            .bind(site.pets_allowed)
+            .execute(&self.pool)
+            .await;
+
+            match result {
+                Ok(_) => stored += 1,
+                Err(e) => log::warn!(
+                    "⚠️ Failed to store attributes of campsite {}: {}",
+                    campsite.id,
+                    e
+                ),
+            }
+
+            attributes.insert(campsite.id, site);
+        }
+
+        log::info!(
+            "🏕️ Synced attributes of {} campsites of campground {} ({} stored)",
+            attributes.len(),
+            campground_id,
+            stored
+        );
+
+        Ok(attributes)
+    }
+
+    /// Attributes synced within `ATTRIBUTES_TTL_DAYS`
+    async fn stored(
+        &self,
+        campground_id: &str,
+    ) -> Result<HashMap<String, SiteAttributes>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, electric_hookup, water_hookup, sewer_hookup, max_vehicle_length,
+                   pets_allowed
+            FROM campsites
+            WHERE campground_id = $1
+              AND attributes_updated_at > NOW() - make_interval(days => $2)
+            "#,
+        )
+        .bind(campground_id)
+        .bind(ATTRIBUTES_TTL_DAYS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                (
+                    row.get("id"),
+                    SiteAttributes {
+                        electric_hookup: row.get("electric_hookup"),
+                        water_hookup: row.get("water_hookup"),
+                        sewer_hookup: row.get("sewer_hookup"),
+                        max_vehicle_length: row.get("max_vehicle_length"),
+                        pets_allowed: row.get("pets_allowed"),
+                    },
+                )
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,251 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// Scarcity score from which a job is scheduled as high priority
+const HIGH_PRIORITY_SCORE: f64 = 60.0;
+
+/// Scarcity score from which a job is scheduled as normal priority
+const NORMAL_PRIORITY_SCORE: f64 = 25.0;
+
+/// Priority bucket a due polling job is scheduled from. Each round of scheduling takes
+/// up to its weight in jobs from every bucket, so busy buckets get more of a tick
+/// without starving the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityBucket {
+    /// Campgrounds in sniper mode
+    Sniper,
+    /// Scarcity score of 60 or more
+    High,
+    /// Scarcity score from 25 to 60
+    Normal,
+    /// Scarcity score under 25
+    Low,
+}
+
+impl PriorityBucket {
+    /// Every bucket, highest first
+    pub const ALL: [PriorityBucket; 4] = [
+        PriorityBucket::Sniper,
+        PriorityBucket::High,
+        PriorityBucket::Normal,
+        PriorityBucket::Low,
+    ];
+
+    /// Bucket of a job with the given priority and scarcity score
+    pub fn of(priority: i32, priority_score: f64) -> Self {
+        if priority > 1 {
+            PriorityBucket::Sniper
+        } else if priority_score >= HIGH_PRIORITY_SCORE {
+            PriorityBucket::High
+        } else if priority_score >= NORMAL_PRIORITY_SCORE {
+            PriorityBucket::Normal
+        } else {
+            PriorityBucket::Low
+        }
+    }
+
+    /// Jobs taken from the bucket each scheduling round
+    pub fn weight(self) -> usize {
+        match self {
+            PriorityBucket::Sniper => 8,
+            PriorityBucket::High => 4,
+            PriorityBucket::Normal => 2,
+            PriorityBucket::Low => 1,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            PriorityBucket::Sniper => 0,
+            PriorityBucket::High => 1,
+            PriorityBucket::Normal => 2,
+            PriorityBucket::Low => 3,
+        }
+    }
+}
+
+/// Polling job due for a poll and not claimed yet
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct DueJob {
+    /// Campground to poll
+    pub campground_id: String,
+    /// Higher for campgrounds in sniper mode
+    pub priority: i32,
+    /// Scarcity score of the campground
+    pub priority_score: f64,
+    /// When the poll was due; `None` for campgrounds never polled
+    pub next_poll_at: Option<DateTime<Utc>>,
+}
+
+impl DueJob {
+    /// Bucket the job is scheduled from
+    pub fn bucket(&self) -> PriorityBucket {
+        PriorityBucket::of(self.priority, self.priority_score)
+    }
+
+    /// How long the job has waited past its due time as of `now`
+    pub fn delay_secs(&self, now: DateTime<Utc>) -> i64 {
+        self.next_poll_at
+            .map_or(0, |due| (now - due).num_seconds().max(0))
+    }
+}
+
+/// Picks up to `limit` of the `due` jobs by weighted round-robin across priority
+/// buckets, taking the longest waiting first within each bucket. `due` is expected in
+/// that order (oldest due first). Buckets with nothing left give up their share to the
+/// others; jobs not picked spill over to the next tick, where having waited longer puts
+/// them at the front of their bucket.
+pub fn fair_schedule(due: Vec<DueJob>, limit: usize) -> Vec<DueJob> {
+    let mut buckets: [VecDeque<DueJob>; 4] = Default::default();
+    for job in due {
+        buckets[job.bucket().index()].push_back(job);
+    }
+
+    let mut picked = Vec::with_capacity(limit);
+    while picked.len() < limit && buckets.iter().any(|bucket| !bucket.is_empty()) {
+        for bucket in PriorityBucket::ALL {
+            let take = bucket.weight().min(limit - picked.len());
+            let queue = &mut buckets[bucket.index()];
+            picked.extend(queue.drain(..take.min(queue.len())));
+        }
+    }
+
+    picked
+}
+
+/// Due jobs waiting in one priority bucket
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BucketDepth {
+    /// The bucket
+    pub bucket: PriorityBucket,
+    /// Jobs taken from it each scheduling round
+    pub weight: usize,
+    /// Jobs due and waiting to be claimed
+    pub due: usize,
+    /// Longest a waiting job is past due, in seconds
+    pub max_delay_secs: i64,
+    /// Average time waiting jobs are past due, in seconds
+    pub avg_delay_secs: i64,
+}
+
+/// Queue depth and scheduling delay of each priority bucket as of `now`
+pub fn queue_depth(due: &[DueJob], now: DateTime<Utc>) -> Vec<BucketDepth> {
+    PriorityBucket::ALL
+        .into_iter()
+        .map(|bucket| {
+            let delays: Vec<i64> = due
+                .iter()
+                .filter(|job| job.bucket() == bucket)
+                .map(|job| job.delay_secs(now))
+                .collect();
+
+            BucketDepth {
+                bucket,
+                weight: bucket.weight(),
+                due: delays.len(),
+                max_delay_secs: delays.iter().copied().max().unwrap_or(0),
+                avg_delay_secs: delays.iter().sum::<i64>() / (delays.len().max(1) as i64),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn job(id: &str, priority: i32, score: f64, minutes_late: i64) -> DueJob {
+        DueJob {
+            campground_id: id.to_string(),
+            priority,
+            priority_score: score,
+            next_poll_at: Some(Utc::now() - Duration::minutes(minutes_late)),
+        }
+    }
+
+    fn ids(jobs: &[DueJob]) -> Vec<&str> {
+        jobs.iter().map(|job| job.campground_id.as_str()).collect()
+    }
+
+    #[test]
+    fn buckets_by_sniper_mode_then_score() {
+        assert_eq!(PriorityBucket::of(10, 0.0), PriorityBucket::Sniper);
+        assert_eq!(PriorityBucket::of(1, 75.0), PriorityBucket::High);
+        assert_eq!(PriorityBucket::of(1, 25.0), PriorityBucket::Normal);
+        assert_eq!(PriorityBucket::of(1, 3.5), PriorityBucket::Low);
+    }
+
+    #[test]
+    fn high_priority_jobs_dont_starve_the_rest() {
+        // 20 high-priority jobs and 2 low-priority ones all due at once
+        let mut due: Vec<DueJob> = (0..20)
+            .map(|i| job(&format!("high-{}", i), 1, 80.0, 5))
+            .collect();
+        due.push(job("low-0", 1, 0.0, 5));
+        due.push(job("low-1", 1, 0.0, 4));
+
+        let picked = fair_schedule(due, 10);
+        assert_eq!(picked.len(), 10);
+        // One round takes 4 high and 1 low, the next the same
+        assert_eq!(
+            ids(&picked),
+            [
+                "high-0", "high-1", "high-2", "high-3", "low-0", "high-4", "high-5", "high-6",
+                "high-7", "low-1"
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_buckets_give_up_their_share() {
+        let due = vec![
+            job("sniper", 5, 0.0, 1),
+            job("normal-0", 1, 30.0, 9),
+            job("normal-1", 1, 30.0, 8),
+            job("normal-2", 1, 30.0, 7),
+        ];
+
+        assert_eq!(
+            ids(&fair_schedule(due.clone(), 10)),
+            ["sniper", "normal-0", "normal-1", "normal-2"]
+        );
+        // What doesn't fit spills over, oldest kept for next tick
+        assert_eq!(ids(&fair_schedule(due, 2)), ["sniper", "normal-0"]);
+    }
+
+    #[test]
+    fn reports_depth_and_delay_per_bucket() {
+        let now = Utc::now();
+        let due = vec![
+            DueJob {
+                next_poll_at: Some(now - Duration::seconds(120)),
+                ..job("a", 1, 0.0, 0)
+            },
+            DueJob {
+                next_poll_at: Some(now - Duration::seconds(60)),
+                ..job("b", 1, 0.0, 0)
+            },
+            DueJob {
+                next_poll_at: None,
+                ..job("c", 3, 0.0, 0)
+            },
+        ];
+
+        let depth = queue_depth(&due, now);
+        assert_eq!(depth.len(), 4);
+        assert_eq!((depth[0].bucket, depth[0].due), (PriorityBucket::Sniper, 1));
+        assert_eq!(depth[0].max_delay_secs, 0);
+        assert_eq!(depth[1].due, 0);
+        assert_eq!(
+            (
+                depth[3].due,
+                depth[3].max_delay_secs,
+                depth[3].avg_delay_secs
+            ),
+            (2, 120, 90)
+        );
+    }
+}
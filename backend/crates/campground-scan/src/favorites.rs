@@ -0,0 +1,280 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rec_gov::{FacilityType, Provider, SiteFilters};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::scan_types::{CreateScanRequest, ScanError};
+
+/// Most campgrounds one user can bookmark
+pub const MAX_FAVORITES: i64 = 100;
+
+/// Days ahead summarized in each favorite's availability
+pub const FAVORITE_AVAILABILITY_DAYS: i32 = 60;
+
+/// Request body for `POST /api/user/favorites`
+#[derive(Debug, Deserialize, Validate)]
+pub struct AddFavoriteRequest {
+    /// ID of the campground (RIDB facility ID, or ReserveCalifornia facility ID)
+    #[validate(length(min = 1, max = 50, message = "Campground ID is required"))]
+    pub campground_id: String,
+
+    /// Name of the campground, used if we haven't seen it before
+    #[validate(length(min = 1, message = "Campground name is required"))]
+    pub campground_name: String,
+
+    /// Reservation system the campground is booked through (defaults to recreation.gov)
+    #[serde(default)]
+    pub provider: Provider,
+
+    /// Personal note about the campground
+    #[validate(length(max = 500, message = "Notes are limited to 500 characters"))]
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Request body for `POST /api/user/favorites/{campground_id}/scan`: the stay to scan
+/// the favorite campground for
+#[derive(Debug, Deserialize)]
+pub struct FavoriteScanRequest {
+    /// Check-in date
+    pub check_in_date: NaiveDate,
+
+    /// Check-out date
+    pub check_out_date: NaiveDate,
+
+    /// Kind of inventory to watch (defaults to campsites)
+    #[serde(default)]
+    pub facility_type: FacilityType,
+
+    /// Days the stay may shift earlier or later
+    #[serde(default)]
+    pub flexibility_days: i32,
+}
+
+/// What recent polls saw of a favorite campground over the next
+/// `FAVORITE_AVAILABILITY_DAYS`
+#[derive(Debug, Clone, Serialize)]
+pub struct FavoriteAvailability {
+    /// Nights with at least one open site
+    pub nights_available: i64,
+    /// First night with an open site
+    pub first_available_date: Option<NaiveDate>,
+    /// Most open sites seen on any night
+    pub max_available_sites: i32,
+    /// When the campground was last checked
+    pub last_checked: DateTime<Utc>,
+}
+
+/// A campground the user bookmarked
+#[derive(Debug, Clone, Serialize)]
+pub struct FavoriteCampground {
+    /// ID of the campground
+    pub campground_id: String,
+    /// Name of the campground
+    pub campground_name: String,
+    /// Reservation system the campground is booked through
+    pub provider: String,
+    /// Two-letter state code, if known
+    pub state: Option<String>,
+    /// Personal note about the campground
+    pub note: Option<String>,
+    /// Active or paused scans the user has on the campground
+    pub active_scans: i64,
+    /// Cached availability, or `None` if the campground was never polled
+    pub availability: Option<FavoriteAvailability>,
+    /// When the campground was bookmarked
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl FavoriteCampground {
+    /// Scan request for a stay at this campground
+    pub fn scan_request(&self, stay: &FavoriteScanRequest) -> CreateScanRequest {
+        CreateScanRequest {
+            campground_id: self.campground_id.clone(),
+            campground_name: self.campground_name.clone(),
+            check_in_date: stay.check_in_date,
+            check_out_date: stay.check_out_date,
+            organization_id: None,
+            facility_type: stay.facility_type,
+            provider: Provider::of_campground(&self.campground_id),
+            flexibility_days: stay.flexibility_days,
+            max_price: None,
+            site_filters: SiteFilters::default(),
+            quick_book: false,
+        }
+    }
+}
+
+/// Response to `GET /api/user/favorites`
+#[derive(Debug, Serialize)]
+pub struct FavoritesResponse {
+    /// Favorite campgrounds, most recently added first
+    pub favorites: Vec<FavoriteCampground>,
+}
+
+/// Campgrounds users bookmarked, with availability summaries from the polling cache
+pub struct FavoriteService {
+    pool: PgPool,
+}
+
+impl FavoriteService {
+    /// Creates a favorites service
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Lists the user's favorite campgrounds, most recently added first
+    pub async fn list(&self, user_id: &Uuid) -> Result<Vec<FavoriteCampground>, ScanError> {
+        let rows = sqlx::query(&favorites_query("f.user_id = $1"))
+            .bind(user_id)
+            .bind(FAVORITE_AVAILABILITY_DAYS)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter().map(favorite_from_row).collect())
+    }
+
+    /// Gets one of the user's favorite campgrounds
+    pub async fn get(
+        &self,
+        user_id: &Uuid,
+        campground_id: &str,
+    ) -> Result<FavoriteCampground, ScanError> {
+        let row = sqlx::query(&favorites_query("f.user_id = $1 AND f.campground_id = $3"))
+            .bind(user_id)
+            .bind(FAVORITE_AVAILABILITY_DAYS)
+            .bind(campground_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(ScanError::CampgroundNotFound)?;
+
+        Ok(favorite_from_row(&row))
+    }
+
+    /// Bookmarks a campground, or updates its note if it already is one
+    pub async fn add(
+        &self,
+        user_id: &Uuid,
+        request: &AddFavoriteRequest,
+    ) -> Result<FavoriteCampground, ScanError> {
+        let campground_id = request.provider.campground_id(&request.campground_id);
+        let mut tx = self.pool.begin().await?;
+
+        // Lock the user so concurrent requests can't both take the last slot
+        sqlx::query("SELECT id FROM users WHERE id = $1 FOR UPDATE")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let favorites: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM favorite_campgrounds WHERE user_id = $1 AND campground_id <> $2",
+        )
+        .bind(user_id)
+        .bind(&campground_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if favorites >= MAX_FAVORITES {
+            return Err(ScanError::Validation(format!(
+                "You can save up to {} favorite campgrounds",
+                MAX_FAVORITES
+            )));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO campgrounds (id, name, provider)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(&campground_id)
+        .bind(&request.campground_name)
+        .bind(request.provider.as_str())
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO favorite_campgrounds (user_id, campground_id, note)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, campground_id) DO UPDATE SET note = EXCLUDED.note
+            "#,
+        )
+        .bind(user_id)
+        .bind(&campground_id)
+        .bind(&request.note)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.get(user_id, &campground_id).await
+    }
+
+    /// Removes a campground from the user's favorites. Returns `false` if it wasn't one.
+    pub async fn remove(&self, user_id: &Uuid, campground_id: &str) -> Result<bool, ScanError> {
+        let removed = sqlx::query(
+            "DELETE FROM favorite_campgrounds WHERE user_id = $1 AND campground_id = $2",
+        )
+        .bind(user_id)
+        .bind(campground_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(removed > 0)
+    }
+}
+
+/// Favorites matching `condition`, with their cached availability over the next `$2` days
+fn favorites_query(condition: &str) -> String {
+    format!(
+        r#"
+        SELECT f.campground_id, COALESCE(c.name, f.campground_id) AS campground_name,
+               COALESCE(c.provider, 'recreation_gov') AS provider, c.state, f.note,
+               f.created_at,
+               (SELECT COUNT(*) FROM user_scans us
+                WHERE us.user_id = f.user_id AND us.campground_id = f.campground_id
+                  AND us.status IN ('active', 'paused') AND us.deleted_at IS NULL) AS active_scans,
+               a.nights_available, a.first_available_date, a.max_available_sites,
+               a.last_checked
+        FROM favorite_campgrounds f
+        LEFT JOIN campgrounds c ON c.id = f.campground_id
+        LEFT JOIN LATERAL (
+            SELECT COUNT(*) FILTER (WHERE ca.available_sites > 0) AS nights_available,
+                   MIN(ca.date) FILTER (WHERE ca.available_sites > 0) AS first_available_date,
+                   COALESCE(MAX(ca.available_sites), 0) AS max_available_sites,
+                   MAX(ca.last_checked) AS last_checked
+            FROM campground_availability ca
+            WHERE ca.campground_id = f.campground_id
+              AND ca.date >= CURRENT_DATE
+              AND ca.date < CURRENT_DATE + $2
+        ) a ON true
+        WHERE {condition}
+        ORDER BY f.created_at DESC
+        "#
+    )
+}
+
+/// Builds a favorite from a row selected by `favorites_query`
+fn favorite_from_row(row: &sqlx::postgres::PgRow) -> FavoriteCampground {
+    let last_checked: Option<DateTime<Utc>> = row.get("last_checked");
+
+    FavoriteCampground {
+        campground_id: row.get("campground_id"),
+        campground_name: row.get("campground_name"),
+        provider: row.get("provider"),
+        state: row.get("state"),
+        note: row.get("note"),
+        active_scans: row.get("active_scans"),
+        availability: last_checked.map(|last_checked| FavoriteAvailability {
+            nights_available: row.get("nights_available"),
+            first_available_date: row.get("first_available_date"),
+            max_available_sites: row.get("max_available_sites"),
+            last_checked,
+        }),
+        created_at: row.get("created_at"),
+    }
+}
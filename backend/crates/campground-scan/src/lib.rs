@@ -11,3 +11,140 @@ pub use scan_types::*;
 /// Service for handling campground scan database operations
 mod scan_service;
 pub use scan_service::*;
+
+/// The scan operations handlers make, as a trait tests can mock
+mod scan_api;
+pub use scan_api::*;
+
+/// Per-night availability and site prices of polled campgrounds
+mod availability_snapshots;
+pub use availability_snapshots::*;
+
+/// Campsite hookups, vehicle length and pet rules synced from RIDB for site filters
+mod campsite_attributes;
+pub use campsite_attributes::*;
+
+/// Nightly refresh of scanned campgrounds' names, locations and site counts from RIDB
+mod campground_sync;
+pub use campground_sync::*;
+
+/// Time-to-book analytics derived from site availability windows
+mod booking_analytics;
+pub use booking_analytics::*;
+
+/// Polling priority scored from sell-outs, active scans and imminent check-ins
+mod priority_scoring;
+pub use priority_scoring::*;
+
+/// Weighted round-robin scheduling of due polling jobs across priority buckets
+mod fair_scheduling;
+pub use fair_scheduling::*;
+
+/// Polling bursts around the daily inventory release of campgrounds with a release schedule
+mod release_bursts;
+pub use release_bursts::*;
+
+/// Coalescing of due campgrounds in one recreation area into poll batches
+mod poll_batching;
+pub use poll_batching::*;
+
+/// Service for scans watching permit quotas (e.g. Half Dome)
+mod permit_scan_service;
+pub use permit_scan_service::*;
+
+/// Polling of claimed permits' scans and permit alerts
+mod permit_polling;
+pub use permit_polling::*;
+
+/// Pure matching of polled availability against scan stays, and of what opened since the
+/// previous poll
+mod availability_diff;
+pub use availability_diff::*;
+
+/// Polling settings, sniper mode for same-week trips, and provider dispatch
+mod scan_executor;
+pub use scan_executor::*;
+
+/// Polling settings ops can change at runtime through the admin API
+mod scan_config;
+pub use scan_config::*;
+
+/// Scan health from polling errors, and scans whose campground can't be checked
+mod scan_health;
+pub use scan_health::*;
+
+/// Campgrounds closed on every night of a scan's stay, cross-checked with their season
+mod seasonal_closure;
+pub use seasonal_closure::*;
+
+/// Scan expiry policy and the expiry worker's queries
+mod scan_expiry;
+pub use scan_expiry::*;
+
+/// Activity timeline of each scan
+mod scan_events;
+pub use scan_events::*;
+
+/// Replays recorded availability history through the matching and notification pipeline
+mod scan_replay;
+pub use scan_replay::*;
+
+/// Live scan updates pushed to connected dashboards
+mod scan_updates;
+pub use scan_updates::*;
+
+/// Heartbeats of background workers for health checks
+mod worker_heartbeats;
+pub use worker_heartbeats::*;
+
+/// Stops background workers between rounds at shutdown
+mod worker_shutdown;
+pub use worker_shutdown::*;
+
+/// Polling jobs claimed per server instance, so replicas don't poll the same campground
+mod polling_jobs;
+pub use polling_jobs::*;
+
+/// Polling of claimed campgrounds' scans, alerts, and the poller's status
+mod scan_manager;
+pub use scan_manager::*;
+
+/// Rounds of background workers claimed across server instances
+mod worker_rounds;
+pub use worker_rounds::*;
+
+/// Retention windows for availability, notification, and scan event rows
+mod retention;
+pub use retention::*;
+
+/// Availability alerts grouping every open site of a poll into one message per scan
+mod scan_notifications;
+pub use scan_notifications::*;
+
+/// Public read-only share links to scans, and cloning shared scans
+mod scan_sharing;
+pub use scan_sharing::*;
+
+/// Friends added to a scan's alerts by email or phone, once they confirm
+mod scan_recipients;
+pub use scan_recipients::*;
+
+/// Campgrounds users bookmarked, with cached availability summaries
+mod favorites;
+pub use favorites::*;
+
+/// Trips grouping scans of alternative campgrounds for one getaway
+mod trips;
+pub use trips::*;
+
+/// iCalendar feed of each user's scan dates
+mod scan_calendar;
+pub use scan_calendar::*;
+
+/// CSV and JSON exports streamed a page at a time
+mod scan_export;
+pub use scan_export::*;
+
+/// Watchdog flagging stalled polling and upstream API failures
+mod scan_watchdog;
+pub use scan_watchdog::*;
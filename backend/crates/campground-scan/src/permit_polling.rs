@@ -0,0 +1,249 @@
+use chrono::NaiveDate;
+use notification_services::{NotificationError, NotificationService, SmsBudget};
+use rec_gov::{RecGovClient, RecGovError, Retry};
+use serde::Serialize;
+
+use crate::permit_scan_service::PermitScanService;
+use crate::polling_jobs::PollingJobQueue;
+use crate::scan_types::{PermitScan, ScanRecipient};
+
+/// Most permits one round claims; due permits beyond that wait for the next tick
+pub const MAX_PERMITS_PER_ROUND: i64 = 20;
+
+/// What polling a set of claimed permits did
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PermitRound {
+    /// Permits polled
+    pub permits: usize,
+    /// Permit scans checked
+    pub scans_checked: usize,
+    /// Permits whose poll failed
+    pub failed: usize,
+    /// Permits handed back unpolled because the hourly call budget ran out
+    pub deferred: usize,
+    /// Permits whose poll panicked, backed off like a failed poll
+    pub panicked: usize,
+    /// Alerts sent to permit scan owners
+    pub alerts_sent: usize,
+}
+
+/// What checking one permit's scans did
+#[derive(Default)]
+struct PermitPoll {
+    scans_checked: usize,
+    alerts_sent: usize,
+    /// How soon the first failure is worth retrying, if any
+    failure: Option<Retry>,
+    /// Whether the hourly call budget ran out
+    deferred: bool,
+}
+
+/// Polls the permits this instance claims from the [`PollingJobQueue`]: checks their
+/// permit scans against recreation.gov and alerts owners when enough permits open up.
+/// Each permit is polled in its own task, so a panic loses only that permit's poll,
+/// which then backs off like a failed one. Claims keep instances from polling the same
+/// permit, and lapse if this instance goes away.
+#[derive(Clone)]
+pub struct PermitPoller {
+    queue: PollingJobQueue,
+    scans: PermitScanService,
+    client: RecGovClient,
+    notification_service: NotificationService,
+    sms_budget: SmsBudget,
+}
+
+impl PermitPoller {
+    /// Creates a poller claiming permits from `queue` and checking them with `client`
+    pub fn new(
+        queue: PollingJobQueue,
+        scans: PermitScanService,
+        client: RecGovClient,
+        notification_service: NotificationService,
+        sms_budget: SmsBudget,
+    ) -> Self {
+        Self {
+            queue,
+            scans,
+            client,
+            notification_service,
+            sms_budget,
+        }
+    }
+
+    /// Claims up to [`MAX_PERMITS_PER_ROUND`] due permits and polls them. Once the hourly
+    /// call budget runs out, the permits left are released unpolled. Errors recording a
+    /// poll are logged, so the other claims are still handed back.
+    pub async fn run_round(&self) -> Result<PermitRound, sqlx::Error> {
+        let permit_ids = self
+            .queue
+            .get_permit_jobs_needing_poll(MAX_PERMITS_PER_ROUND)
+            .await?;
+
+        let mut round = PermitRound::default();
+        for permit_id in &permit_ids {
+            if round.deferred > 0 {
+                round.deferred += 1;
+                self.release(permit_id).await;
+                continue;
+            }
+
+            let failure = match tokio::spawn(self.clone().poll_permit(permit_id.clone())).await {
+                Ok(poll) => {
+                    round.scans_checked += poll.scans_checked;
+                    round.alerts_sent += poll.alerts_sent;
+                    if poll.deferred {
+                        round.deferred += 1;
+                        self.release(permit_id).await;
+                        continue;
+                    }
+                    round.permits += 1;
+                    if poll.failure.is_some() {
+                        round.failed += 1;
+                    }
+                    poll.failure
+                }
+                Err(e) => {
+                    log::error!("💥 Poll of permit {} panicked: {}", permit_id, e);
+                    round.panicked += 1;
+                    Some(Retry::Backoff)
+                }
+            };
+
+            match self.queue.finish_permit(permit_id, failure).await {
+                Ok(true) => {}
+                Ok(false) => log::warn!(
+                    "⚠️ Claim of permit {} lapsed before its poll was recorded",
+                    permit_id
+                ),
+                Err(e) => log::error!(
+                    "❌ Failed to record the poll of permit {}: {}",
+                    permit_id,
+                    e
+                ),
+            }
+        }
+
+        Ok(round)
+    }
+
+    /// Hands back the claim of a permit left unpolled
+    async fn release(&self, permit_id: &str) {
+        if let Err(e) = self.queue.release_permit(permit_id).await {
+            log::error!("❌ Failed to release permit {}: {}", permit_id, e);
+        }
+    }
+
+    /// Checks one permit's pending scans, alerting owners whose permits opened up. Runs
+    /// as a task of its own.
+    async fn poll_permit(self, permit_id: String) -> PermitPoll {
+        let mut poll = PermitPoll::default();
+
+        let scans = match self.scans.pending_permit_scans(&permit_id).await {
+            Ok(scans) => scans,
+            Err(e) => {
+                log::error!("❌ Failed to load scans of permit {}: {}", permit_id, e);
+                return poll;
+            }
+        };
+
+        for scan in &scans {
+            let openings = match self.scans.check_permit_scan(&self.client, scan).await {
+                Ok(openings) => openings,
+                Err(RecGovError::RateLimited) => {
+                    poll.deferred = true;
+                    break;
+                }
+                Err(e) => {
+                    log::error!("❌ Failed to check permit scan {}: {}", scan.id, e);
+                    poll.failure.get_or_insert(e.retry());
+                    continue;
+                }
+            };
+            poll.scans_checked += 1;
+
+            if openings.is_empty() {
+                continue;
+            }
+
+            let mut dates: Vec<_> = openings
+                .iter()
+                .flat_map(|opening| opening.dates.iter().copied())
+                .collect();
+            dates.sort();
+            dates.dedup();
+
+            log::info!(
+                "🎟️ Permits released for scan {} ({} dates)",
+                scan.id,
+                dates.len()
+            );
+
+            let recipient = match self.scans.permit_scan_recipient(scan).await {
+                Ok(recipient) => recipient,
+                Err(e) => {
+                    log::error!("❌ Failed to look up permit scan {} owner: {}", scan.id, e);
+                    continue;
+                }
+            };
+
+            if let Some(recipient) = recipient {
+                if let Err(e) = self.notify(&recipient, scan, &dates).await {
+                    log::error!("❌ Failed to send permit alert for {}: {}", scan.id, e);
+                    continue;
+                }
+                poll.alerts_sent += 1;
+            }
+
+            if let Err(e) = self.scans.mark_permit_scan_notified(&scan.id).await {
+                log::error!("❌ Failed to mark permit scan {} notified: {}", scan.id, e);
+            }
+        }
+
+        poll
+    }
+
+    /// Alerts a permit scan's owner by SMS and/or email. When the SMS budget is used up,
+    /// the alert falls back to email with a notice.
+    async fn notify(
+        &self,
+        recipient: &ScanRecipient,
+        scan: &PermitScan,
+        dates: &[NaiveDate],
+    ) -> Result<(), NotificationError> {
+        let mut notice = None;
+
+        if recipient.sms_enabled
+            && let Some(phone) = &recipient.phone
+        {
+            let allowance = self
+                .notification_service
+                .send_permit_sms(
+                    &self.sms_budget,
+                    &recipient.user_id,
+                    phone,
+                    &scan.permit_name,
+                    &scan.permit_id,
+                    dates,
+                    recipient.locale,
+                )
+                .await?;
+            notice = allowance.fallback_notice(recipient.locale);
+        }
+
+        if recipient.email_enabled || notice.is_some() {
+            self.notification_service
+                .send_permit_available(
+                    &recipient.user_id,
+                    &recipient.email,
+                    &scan.permit_name,
+                    &scan.permit_id,
+                    dates,
+                    notice.as_deref(),
+                    recipient.locale,
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
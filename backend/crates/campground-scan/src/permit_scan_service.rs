@@ -0,0 +1,275 @@
+use i18n::user_timezone;
+use rec_gov::{RecGovClient, RecGovError};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::scan_service::ScanService;
+use crate::scan_types::*;
+
+/// Columns selected for a permit scan
+const PERMIT_SCAN_COLUMNS: &str = r#"
+    id, user_id, permit_id, permit_name, division_id, start_date, end_date, group_size,
+    status, notification_sent, last_checked_at, created_at, updated_at
+"#;
+
+/// Longest entry date range a permit scan may watch
+const MAX_PERMIT_SCAN_DAYS: i64 = 93;
+
+/// Service for handling permit scan operations
+#[derive(Clone)]
+pub struct PermitScanService {
+    pool: PgPool,
+    scans: ScanService,
+}
+
+impl PermitScanService {
+    /// Creates a new instance of `PermitScanService` with the provided database connection pool
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            scans: ScanService::new(pool.clone()),
+            pool,
+        }
+    }
+
+    /// Creates a new permit scan for the specified user
+    pub async fn create_permit_scan(
+        &self,
+        user_id: &Uuid,
+        request: &CreatePermitScanRequest,
+    ) -> Result<PermitScan, ScanError> {
+        if request.group_size <= 0 {
+            return Err(ScanError::Validation(
+                "Group size must be at least 1".to_string(),
+            ));
+        }
+
+        let days = (request.end_date - request.start_date).num_days();
+        if days < 0 {
+            return Err(ScanError::Validation(
+                "End date must be on or after the start date".to_string(),
+            ));
+        }
+        if days > MAX_PERMIT_SCAN_DAYS {
+            return Err(ScanError::Validation(format!(
+                "Permit scans can watch at most {} days",
+                MAX_PERMIT_SCAN_DAYS
+            )));
+        }
+
+        // Don't create scans whose alerts could never reach the user
+        self.scans.ensure_deliverable_channel(user_id).await?;
+
+        let scan = sqlx::query_as::<_, PermitScan>(&format!(
+            r#"
+            INSERT INTO permit_scans (
+                user_id, permit_id, permit_name, division_id, start_date, end_date, group_size
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING {}
+            "#,
+            PERMIT_SCAN_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(&request.permit_id)
+        .bind(&request.permit_name)
+        .bind(&request.division_id)
+        .bind(request.start_date)
+        .bind(request.end_date)
+        .bind(request.group_size)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(scan)
+    }
+
+    /// Gets all permit scans of a user, newest first
+    pub async fn get_user_permit_scans(
+        &self,
+        user_id: &Uuid,
+    ) -> Result<Vec<PermitScan>, ScanError> {
+        let scans = sqlx::query_as::<_, PermitScan>(&format!(
+            "SELECT {} FROM permit_scans WHERE user_id = $1 ORDER BY created_at DESC",
+            PERMIT_SCAN_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(scans)
+    }
+
+    /// Deletes a permit scan owned by the user
+    pub async fn delete_permit_scan(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+    ) -> Result<(), ScanError> {
+        let result = sqlx::query("DELETE FROM permit_scans WHERE id = $1 AND user_id = $2")
+            .bind(scan_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ScanError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Gets the active permit scans of one permit that haven't alerted yet and whose
+    /// entry dates haven't all passed, for polling the permit
+    pub async fn pending_permit_scans(
+        &self,
+        permit_id: &str,
+    ) -> Result<Vec<PermitScan>, ScanError> {
+        let scans = sqlx::query_as::<_, PermitScan>(&format!(
+            r#"
+            SELECT {} FROM permit_scans
+            WHERE permit_id = $1
+              AND status = 'active'
+              AND NOT COALESCE(notification_sent, FALSE)
+              AND end_date >= CURRENT_DATE
+            ORDER BY last_checked_at ASC NULLS FIRST
+            "#,
+            PERMIT_SCAN_COLUMNS
+        ))
+        .bind(permit_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(scans)
+    }
+
+    /// Checks one permit scan against recreation.gov and records the check. Returns the
+    /// divisions with enough permits on at least one entry date; the caller notifies the
+    /// user and marks the scan notified.
+    pub async fn check_permit_scan(
+        &self,
+        client: &RecGovClient,
+        scan: &PermitScan,
+    ) -> Result<Vec<PermitOpening>, RecGovError> {
+        let divisions = client
+            .get_permit_availability(&scan.permit_id, scan.start_date, scan.end_date)
+            .await?;
+
+        let mut openings: Vec<PermitOpening> = divisions
+            .iter()
+            .filter(|division| {
+                scan.division_id
+                    .as_ref()
+                    .is_none_or(|id| *id == division.division_id)
+            })
+            .filter_map(|division| {
+                let dates: Vec<_> = division
+                    .open_days(scan.start_date, scan.end_date, scan.group_size)
+                    .collect();
+
+                (!dates.is_empty()).then(|| PermitOpening {
+                    scan_id: scan.id,
+                    division_id: division.division_id.clone(),
+                    dates,
+                })
+            })
+            .collect();
+        openings.sort_by(|a, b| a.division_id.cmp(&b.division_id));
+
+        let recorded = sqlx::query("UPDATE permit_scans SET last_checked_at = NOW() WHERE id = $1")
+            .bind(scan.id)
+            .execute(&self.pool)
+            .await;
+
+        if let Err(e) = recorded {
+            log::error!("❌ Failed to record permit scan {} check: {}", scan.id, e);
+        }
+
+        Ok(openings)
+    }
+
+    /// The owner of a permit scan with their notification settings, if still active
+    pub async fn permit_scan_recipient(
+        &self,
+        scan: &PermitScan,
+    ) -> Result<Option<ScanRecipient>, ScanError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, email, phone, phone_verified, notification_preferences, locale,
+                   timezone
+            FROM users
+            WHERE id = $1 AND is_active = true
+            "#,
+        )
+        .bind(scan.user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let recipient = row.map(|row| {
+            let preferences: Option<serde_json::Value> = row.get("notification_preferences");
+            let preferences = preferences.unwrap_or_default();
+            let phone_verified: Option<bool> = row.get("phone_verified");
+
+            ScanRecipient {
+                user_id: row.get("id"),
+                email: row.get("email"),
+                phone: row.get("phone"),
+                email_enabled: preferences["email"].as_bool().unwrap_or(false),
+                sms_enabled: preferences["sms"].as_bool().unwrap_or(false)
+                    && phone_verified.unwrap_or(false),
+                locale: row.get::<String, _>("locale").parse().unwrap_or_default(),
+                timezone: user_timezone(row.get::<Option<String>, _>("timezone").as_deref()),
+            }
+        });
+
+        Ok(recipient)
+    }
+
+    /// Marks a permit scan as notified so it doesn't alert again for the same opening
+    pub async fn mark_permit_scan_notified(&self, scan_id: &Uuid) -> Result<(), ScanError> {
+        sqlx::query(
+            "UPDATE permit_scans SET notification_sent = TRUE, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(scan_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use postgres::test_fixtures::insert_user;
+
+    fn request(group_size: i32) -> CreatePermitScanRequest {
+        let start_date = Utc::now().date_naive() + Duration::days(30);
+        CreatePermitScanRequest {
+            permit_id: "234652".to_string(),
+            permit_name: "Half Dome".to_string(),
+            division_id: None,
+            start_date,
+            end_date: start_date + Duration::days(7),
+            group_size,
+        }
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn scans_need_at_least_one_permit(pool: PgPool) {
+        let user_id = insert_user(&pool, "hiker@example.com").await;
+        let service = PermitScanService::new(pool);
+
+        for group_size in [0, -3] {
+            assert!(matches!(
+                service
+                    .create_permit_scan(&user_id, &request(group_size))
+                    .await,
+                Err(ScanError::Validation(_))
+            ));
+        }
+        service
+            .create_permit_scan(&user_id, &request(2))
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,105 @@
+use crate::polling_jobs::ClaimedPollingJob;
+
+/// Most campgrounds polled in one batch, so a large recreation area doesn't keep one
+/// session busy for a whole tick
+pub const MAX_POLL_BATCH_SIZE: usize = 10;
+
+/// Campgrounds polled in a row over one upstream session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PollBatch {
+    /// Recreation area the campgrounds are in, or `None` for a campground polled alone
+    pub recarea_id: Option<String>,
+    /// Campgrounds to poll, in the order they were scheduled
+    pub campground_ids: Vec<String>,
+}
+
+impl PollBatch {
+    /// Key the batch's calls share their session and pacing under
+    pub fn key(&self) -> String {
+        match &self.recarea_id {
+            Some(recarea_id) => format!("recarea:{}", recarea_id),
+            None => self.campground_ids.first().cloned().unwrap_or_default(),
+        }
+    }
+}
+
+/// Groups claimed jobs of campgrounds in the same recreation area into batches of at most
+/// [`MAX_POLL_BATCH_SIZE`], so they're polled back to back over one session instead of
+/// each warming up its own. Batches come in the order their first job was scheduled,
+/// and jobs keep their scheduled order within a batch; campgrounds without a known
+/// recreation area are batches of one.
+pub fn coalesce_by_recarea(jobs: &[ClaimedPollingJob]) -> Vec<PollBatch> {
+    let mut batches: Vec<PollBatch> = Vec::new();
+
+    for job in jobs {
+        let open_batch = job.recarea_id.as_ref().and_then(|recarea_id| {
+            batches.iter_mut().find(|batch| {
+                batch.recarea_id.as_ref() == Some(recarea_id)
+                    && batch.campground_ids.len() < MAX_POLL_BATCH_SIZE
+            })
+        });
+
+        match open_batch {
+            Some(batch) => batch.campground_ids.push(job.campground_id.clone()),
+            None => batches.push(PollBatch {
+                recarea_id: job.recarea_id.clone(),
+                campground_ids: vec![job.campground_id.clone()],
+            }),
+        }
+    }
+
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(campground_id: &str, recarea_id: Option<&str>) -> ClaimedPollingJob {
+        ClaimedPollingJob {
+            campground_id: campground_id.to_string(),
+            active_scan_count: 1,
+            poll_frequency_minutes: 15,
+            priority: 1,
+            priority_score: 0.0,
+            consecutive_errors: 0,
+            scheduling_delay_secs: 0,
+            recarea_id: recarea_id.map(str::to_string),
+        }
+    }
+
+    fn ids(batch: &PollBatch) -> Vec<&str> {
+        batch.campground_ids.iter().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn batches_campgrounds_of_one_recarea_in_scheduled_order() {
+        let jobs = [
+            job("232447", Some("2991")),
+            job("251869", None),
+            job("232445", Some("2994")),
+            job("232450", Some("2991")),
+            job("232449", Some("2991")),
+        ];
+
+        let batches = coalesce_by_recarea(&jobs);
+        assert_eq!(batches.len(), 3);
+        assert_eq!(ids(&batches[0]), ["232447", "232450", "232449"]);
+        assert_eq!(batches[0].key(), "recarea:2991");
+        assert_eq!(ids(&batches[1]), ["251869"]);
+        assert_eq!(batches[1].key(), "251869");
+        assert_eq!(ids(&batches[2]), ["232445"]);
+    }
+
+    #[test]
+    fn splits_large_recareas() {
+        let jobs: Vec<ClaimedPollingJob> = (0..MAX_POLL_BATCH_SIZE + 2)
+            .map(|i| job(&i.to_string(), Some("2991")))
+            .collect();
+
+        let batches = coalesce_by_recarea(&jobs);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].campground_ids.len(), MAX_POLL_BATCH_SIZE);
+        assert_eq!(ids(&batches[1]), ["10", "11"]);
+    }
+}
@@ -0,0 +1,1025 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use rec_gov::{RecGovError, Retry};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::fair_scheduling::{BucketDepth, DueJob, fair_schedule, queue_depth};
+use crate::release_bursts::ReleaseSchedule;
+use crate::scan_config::SharedScanConfig;
+use crate::seasonal_closure::{CLOSED_AFTER_POLLS, CLOSED_POLL_INTERVAL_MINUTES};
+
+/// How long a claimed polling job stays with the instance that claimed it. Claims left
+/// by an instance that died lapse after this and the campground is polled again.
+pub const POLL_CLAIM_LEASE_MINUTES: i32 = 10;
+
+/// Longest an admin can disable a polling job for: one week
+pub const MAX_POLLING_JOB_DISABLE_MINUTES: i64 = 7 * 24 * 60;
+
+/// Longest poll frequency an admin can set: once a day
+pub const MAX_POLL_FREQUENCY_MINUTES: i32 = 24 * 60;
+
+/// Minutes a campground waits after its `errors_in_row`th failed poll in a row, by how
+/// soon the failure is worth retrying: timeouts are retried after a minute, doubling
+/// with each failure; other upstream trouble after the poll frequency, doubling; and
+/// blocks after the longest backoff straight away. Backoff never exceeds
+/// `backoff_max_minutes` (beyond the poll frequency), and 0 turns it off.
+pub fn failure_backoff_minutes(
+    retry: Retry,
+    errors_in_row: i32,
+    poll_frequency_minutes: i32,
+    backoff_max_minutes: u32,
+) -> i32 {
+    let backoff_max = backoff_max_minutes.min(i32::MAX as u32) as i32;
+    if backoff_max == 0 {
+        return poll_frequency_minutes;
+    }
+    let doubling = 2i32.pow(errors_in_row.clamp(1, 10) as u32 - 1);
+
+    match retry {
+        Retry::Soon => doubling.min(backoff_max.max(1)),
+        Retry::Backoff => poll_frequency_minutes
+            .max(backoff_max.min(poll_frequency_minutes.saturating_mul(doubling * 2))),
+        Retry::Pause => poll_frequency_minutes.max(backoff_max),
+    }
+}
+
+/// Campground whose polling job was claimed by this instance
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ClaimedPollingJob {
+    /// Campground to poll
+    pub campground_id: String,
+    /// Active scans watching the campground
+    pub active_scan_count: i32,
+    /// Minutes between polls
+    pub poll_frequency_minutes: i32,
+    /// Higher polls first (sniper mode campgrounds are highest)
+    pub priority: i32,
+    /// Scarcity score ordering jobs of the same priority, highest first
+    pub priority_score: f64,
+    /// Failed polls in a row
+    pub consecutive_errors: i32,
+    /// Seconds the job waited past its due time before being claimed
+    pub scheduling_delay_secs: i64,
+    /// RIDB recreation area of the campground, when known
+    pub recarea_id: Option<String>,
+}
+
+/// Conditions of a polling job that is due and free to claim
+const DUE_JOB_FILTER: &str = r#"
+    active_scan_count > 0
+    AND NOT paused
+    AND COALESCE(next_poll_at, NOW()) <= NOW()
+    AND (disabled_until IS NULL OR disabled_until <= NOW())
+    AND (NOT COALESCE(is_being_polled, FALSE) OR claimed_until IS NULL
+         OR claimed_until <= NOW())
+"#;
+
+/// Columns of a [`ClaimedPollingJob`], returned from `polling_jobs pj` joined to its
+/// campground `c`
+const CLAIMED_JOB_COLUMNS: &str = r#"
+    pj.campground_id,
+    COALESCE(pj.active_scan_count, 0) AS active_scan_count,
+    COALESCE(pj.poll_frequency_minutes, 15) AS poll_frequency_minutes,
+    COALESCE(pj.priority, 1) AS priority,
+    pj.priority_score,
+    COALESCE(pj.consecutive_errors, 0) AS consecutive_errors,
+    GREATEST(EXTRACT(EPOCH FROM NOW() - COALESCE(pj.next_poll_at, NOW())), 0)::BIGINT
+        AS scheduling_delay_secs,
+    c.parent_recarea_id AS recarea_id
+"#;
+
+/// Most due jobs one scheduling pass looks at
+const MAX_DUE_JOBS: i64 = 5000;
+
+/// Polling jobs due and free to claim, longest waiting first
+async fn due_jobs(pool: &PgPool) -> Result<Vec<DueJob>, sqlx::Error> {
+    sqlx::query_as::<_, DueJob>(&format!(
+        r#"
+        SELECT campground_id, COALESCE(priority, 1) AS priority, priority_score, next_poll_at
+        FROM polling_jobs
+        WHERE {}
+        ORDER BY next_poll_at ASC NULLS FIRST, campground_id
+        LIMIT $1
+        "#,
+        DUE_JOB_FILTER
+    ))
+    .bind(MAX_DUE_JOBS)
+    .fetch_all(pool)
+    .await
+}
+
+/// Hands out due polling jobs so several server instances can poll side by side: each
+/// campground is claimed by one instance at a time (`FOR UPDATE SKIP LOCKED`), and the
+/// claim lapses after [`POLL_CLAIM_LEASE_MINUTES`] if that instance goes away.
+#[derive(Debug, Clone)]
+pub struct PollingJobQueue {
+    pool: PgPool,
+    instance_id: String,
+    config: SharedScanConfig,
+}
+
+impl PollingJobQueue {
+    /// Creates a queue claiming jobs under a new, unique instance ID, backing off failing
+    /// campgrounds as `config` says at the time
+    pub fn new(pool: PgPool, config: SharedScanConfig) -> Self {
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "web_server".to_string());
+        let instance_id = format!("{}-{}", host, Uuid::new_v4().simple());
+
+        Self {
+            pool,
+            instance_id,
+            config,
+        }
+    }
+
+    /// ID this instance claims jobs under
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Claims up to `limit` campgrounds with active scans that are due for a poll and not
+    /// claimed by another instance, picked by weighted round-robin across priority
+    /// buckets (see [`fair_schedule`]) so a burst of high-priority jobs can't starve the
+    /// rest. Due jobs that don't fit spill over to the next tick. Rows another instance
+    /// is claiming at the same moment are skipped rather than waited on.
+    pub async fn get_jobs_needing_poll(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<ClaimedPollingJob>, sqlx::Error> {
+        let due = due_jobs(&self.pool).await?;
+        let depth = due.len();
+        let picked: Vec<String> = fair_schedule(due, limit.max(0) as usize)
+            .into_iter()
+            .map(|job| job.campground_id)
+            .collect();
+        if picked.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let claimed = sqlx::query_as::<_, ClaimedPollingJob>(&format!(
+            r#"
+            WITH picked AS (
+                SELECT campground_id
+                FROM polling_jobs
+                WHERE campground_id = ANY($1) AND {}
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE polling_jobs pj
+            SET is_being_polled = TRUE,
+                claimed_by = $2,
+                claimed_until = NOW() + make_interval(mins => $3),
+                updated_at = NOW()
+            FROM picked
+            LEFT JOIN campgrounds c ON c.id = picked.campground_id
+            WHERE pj.campground_id = picked.campground_id
+            RETURNING {}
+            "#,
+            DUE_JOB_FILTER, CLAIMED_JOB_COLUMNS
+        ))
+        .bind(&picked)
+        .bind(&self.instance_id)
+        .bind(POLL_CLAIM_LEASE_MINUTES)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let max_delay = claimed
+            .iter()
+            .map(|job| job.scheduling_delay_secs)
+            .max()
+            .unwrap_or(0);
+        log::info!(
+            "🗓️ Claimed {} of {} due polling jobs ({} spill over to the next tick), longest wait {}s",
+            claimed.len(),
+            depth,
+            depth - claimed.len(),
+            max_delay
+        );
+
+        Ok(claimed)
+    }
+
+    /// Claims one campground's polling job whether or not it's due, for an admin forcing
+    /// a poll. Returns `None` when the campground has no active scans or another instance
+    /// holds its claim.
+    pub async fn claim(
+        &self,
+        campground_id: &str,
+    ) -> Result<Option<ClaimedPollingJob>, sqlx::Error> {
+        sqlx::query_as::<_, ClaimedPollingJob>(&format!(
+            r#"
+            WITH picked AS (
+                SELECT campground_id
+                FROM polling_jobs
+                WHERE campground_id = $1
+                  AND active_scan_count > 0
+                  AND (NOT COALESCE(is_being_polled, FALSE) OR claimed_until IS NULL
+                       OR claimed_until <= NOW())
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE polling_jobs pj
+            SET is_being_polled = TRUE,
+                claimed_by = $2,
+                claimed_until = NOW() + make_interval(mins => $3),
+                updated_at = NOW()
+            FROM picked
+            LEFT JOIN campgrounds c ON c.id = picked.campground_id
+            WHERE pj.campground_id = picked.campground_id
+            RETURNING {}
+            "#,
+            CLAIMED_JOB_COLUMNS
+        ))
+        .bind(campground_id)
+        .bind(&self.instance_id)
+        .bind(POLL_CLAIM_LEASE_MINUTES)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Releases this instance's claim of one campground without recording a poll, so
+    /// it's picked up again on the next tick, e.g. when the hourly call budget ran out
+    /// before it was polled
+    pub async fn release(&self, campground_id: &str) -> Result<bool, sqlx::Error> {
+        let released = sqlx::query(
+            r#"
+            UPDATE polling_jobs
+            SET is_being_polled = FALSE, claimed_by = NULL, claimed_until = NULL, updated_at = NOW()
+            WHERE campground_id = $1 AND claimed_by = $2
+            "#,
+        )
+        .bind(campground_id)
+        .bind(&self.instance_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(released > 0)
+    }
+
+    /// Releases this instance's claim of one campground without recording a poll and
+    /// keeps it from being polled for `minutes`, e.g. after its polls kept panicking.
+    /// Admins can resume it sooner by enabling it. Returns when polling resumes, or `None`
+    /// when the claim had already lapsed and gone to another instance.
+    pub async fn quarantine(
+        &self,
+        campground_id: &str,
+        minutes: i64,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let until = Utc::now() + Duration::minutes(minutes);
+
+        let quarantined = sqlx::query(
+            r#"
+            UPDATE polling_jobs
+            SET is_being_polled = FALSE, claimed_by = NULL, claimed_until = NULL,
+                disabled_until = $3, updated_at = NOW()
+            WHERE campground_id = $1 AND claimed_by = $2
+            "#,
+        )
+        .bind(campground_id)
+        .bind(&self.instance_id)
+        .bind(until)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok((quarantined > 0).then_some(until))
+    }
+
+    /// Records a finished poll of a campground this instance claimed and releases the
+    /// claim, scheduling the next poll at the job's frequency. A failed poll is retried
+    /// after [`failure_backoff_minutes`] for its kind of failure, and the failure's kind
+    /// and status are kept for admins until a poll succeeds.
+    /// When every night the poll checked has been closed for [`CLOSED_AFTER_POLLS`] polls,
+    /// the campground is likely closed for the season and waits at least
+    /// [`CLOSED_POLL_INTERVAL_MINUTES`]. During a release burst, successful polls are
+    /// followed by the next one after the configured burst interval instead.
+    /// Returns `false` when the claim had already lapsed and gone to another instance.
+    pub async fn finish(
+        &self,
+        campground_id: &str,
+        failure: Option<&RecGovError>,
+    ) -> Result<bool, sqlx::Error> {
+        let config = self.config.get();
+        let mut tx = self.pool.begin().await?;
+
+        let Some(job) = sqlx::query(
+            r#"
+            SELECT COALESCE(poll_frequency_minutes, 15) AS poll_frequency_minutes,
+                   COALESCE(consecutive_errors, 0) AS consecutive_errors
+            FROM polling_jobs
+            WHERE campground_id = $1 AND claimed_by = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(campground_id)
+        .bind(&self.instance_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(false);
+        };
+
+        let retry_minutes = failure.map(|failure| {
+            failure_backoff_minutes(
+                failure.retry(),
+                job.get::<i32, _>("consecutive_errors") + 1,
+                job.get("poll_frequency_minutes"),
+                config.error_backoff_max_minutes,
+            )
+        });
+
+        sqlx::query(
+            r#"
+            UPDATE polling_jobs
+            SET is_being_polled = FALSE,
+                claimed_by = NULL,
+                claimed_until = NULL,
+                last_polled = NOW(),
+                next_poll_at = CASE
+                    WHEN $2::INT IS NOT NULL THEN NOW() + make_interval(mins => $2)
+                    WHEN burst_until > NOW() THEN NOW() + make_interval(secs => $5)
+                    WHEN EXISTS (
+                        SELECT 1 FROM campground_availability ca
+                        WHERE ca.campground_id = polling_jobs.campground_id
+                          AND ca.last_checked > COALESCE(polling_jobs.last_polled, '-infinity')
+                    ) AND NOT EXISTS (
+                        SELECT 1 FROM campground_availability ca
+                        WHERE ca.campground_id = polling_jobs.campground_id
+                          AND ca.last_checked > COALESCE(polling_jobs.last_polled, '-infinity')
+                          AND ca.closed_polls < $3
+                    ) THEN NOW() + make_interval(
+                        mins => GREATEST(COALESCE(poll_frequency_minutes, 15), $4)
+                    )
+                    ELSE NOW() + make_interval(mins => COALESCE(poll_frequency_minutes, 15))
+                END,
+                consecutive_errors = CASE WHEN $2::INT IS NULL THEN 0
+                                          ELSE COALESCE(consecutive_errors, 0) + 1 END,
+                failing_since = CASE WHEN $2::INT IS NULL THEN NULL
+                                     ELSE COALESCE(failing_since, NOW()) END,
+                last_failure_kind = $6,
+                last_failure_status = $7,
+                updated_at = NOW()
+            WHERE campground_id = $1
+            "#,
+        )
+        .bind(campground_id)
+        .bind(retry_minutes)
+        .bind(CLOSED_AFTER_POLLS)
+        .bind(CLOSED_POLL_INTERVAL_MINUTES)
+        .bind(f64::from(config.release_burst_interval_seconds))
+        .bind(failure.map(|failure| failure.kind().as_str()))
+        .bind(failure.and_then(RecGovError::status).map(i32::from))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Claims up to `limit` permits with an active, unnotified permit scan that are due
+    /// for a poll and not claimed by another instance, longest waiting first. Claims
+    /// lapse after [`POLL_CLAIM_LEASE_MINUTES`] like campground claims.
+    pub async fn get_permit_jobs_needing_poll(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            WITH picked AS (
+                SELECT permit_id
+                FROM permit_polling_jobs pj
+                WHERE COALESCE(next_poll_at, NOW()) <= NOW()
+                  AND (claimed_until IS NULL OR claimed_until <= NOW())
+                  AND EXISTS (
+                      SELECT 1 FROM permit_scans ps
+                      WHERE ps.permit_id = pj.permit_id
+                        AND ps.status = 'active'
+                        AND NOT COALESCE(ps.notification_sent, FALSE)
+                        AND ps.end_date >= CURRENT_DATE
+                  )
+                ORDER BY next_poll_at ASC NULLS FIRST, permit_id
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE permit_polling_jobs pj
+            SET claimed_by = $2,
+                claimed_until = NOW() + make_interval(mins => $3),
+                updated_at = NOW()
+            FROM picked
+            WHERE pj.permit_id = picked.permit_id
+            RETURNING pj.permit_id
+            "#,
+        )
+        .bind(limit.max(0))
+        .bind(&self.instance_id)
+        .bind(POLL_CLAIM_LEASE_MINUTES)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Releases this instance's claim of one permit without recording a poll, so it's
+    /// picked up again on the next tick
+    pub async fn release_permit(&self, permit_id: &str) -> Result<bool, sqlx::Error> {
+        let released = sqlx::query(
+            r#"
+            UPDATE permit_polling_jobs
+            SET claimed_by = NULL, claimed_until = NULL, updated_at = NOW()
+            WHERE permit_id = $1 AND claimed_by = $2
+            "#,
+        )
+        .bind(permit_id)
+        .bind(&self.instance_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(released > 0)
+    }
+
+    /// Records a finished poll of a permit this instance claimed and releases the claim,
+    /// scheduling the next poll at the job's frequency. A failed poll is retried after
+    /// [`failure_backoff_minutes`] for `failure`, so a permit whose polls keep failing
+    /// (or panicking) is polled less and less often. Returns `false` when the claim had
+    /// already lapsed and gone to another instance.
+    pub async fn finish_permit(
+        &self,
+        permit_id: &str,
+        failure: Option<Retry>,
+    ) -> Result<bool, sqlx::Error> {
+        let config = self.config.get();
+        let mut tx = self.pool.begin().await?;
+
+        let Some(job) = sqlx::query(
+            r#"
+            SELECT poll_frequency_minutes, consecutive_errors
+            FROM permit_polling_jobs
+            WHERE permit_id = $1 AND claimed_by = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(permit_id)
+        .bind(&self.instance_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(false);
+        };
+
+        let poll_frequency_minutes: i32 = job.get("poll_frequency_minutes");
+        let wait_minutes = failure.map_or(poll_frequency_minutes, |retry| {
+            failure_backoff_minutes(
+                retry,
+                job.get::<i32, _>("consecutive_errors") + 1,
+                poll_frequency_minutes,
+                config.error_backoff_max_minutes,
+            )
+        });
+
+        sqlx::query(
+            r#"
+            UPDATE permit_polling_jobs
+            SET claimed_by = NULL,
+                claimed_until = NULL,
+                last_polled = NOW(),
+                next_poll_at = NOW() + make_interval(mins => $2),
+                consecutive_errors = CASE WHEN $3 THEN consecutive_errors + 1 ELSE 0 END,
+                updated_at = NOW()
+            WHERE permit_id = $1
+            "#,
+        )
+        .bind(permit_id)
+        .bind(wait_minutes)
+        .bind(failure.is_some())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Releases every claim this instance still holds, of campgrounds and of permits,
+    /// without recording a poll, so they're picked up again right away. Run at shutdown
+    /// once the workers have stopped; other instances' claims are left alone.
+    pub async fn release_claims(&self) -> Result<u64, sqlx::Error> {
+        let campgrounds = sqlx::query(
+            r#"
+            UPDATE polling_jobs
+            SET is_being_polled = FALSE, claimed_by = NULL, claimed_until = NULL, updated_at = NOW()
+            WHERE claimed_by = $1
+            "#,
+        )
+        .bind(&self.instance_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        let permits = sqlx::query(
+            r#"
+            UPDATE permit_polling_jobs
+            SET claimed_by = NULL, claimed_until = NULL, updated_at = NOW()
+            WHERE claimed_by = $1
+            "#,
+        )
+        .bind(&self.instance_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(campgrounds + permits)
+    }
+}
+
+/// Polling job of one campground, as shown to admins
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PollingJobState {
+    /// Campground polled
+    pub campground_id: String,
+    /// Name of the campground
+    pub campground_name: Option<String>,
+    /// Active scans watching the campground
+    pub active_scan_count: Option<i32>,
+    /// Minutes between polls
+    pub poll_frequency_minutes: Option<i32>,
+    /// Higher polls first; only sniper mode raises it
+    pub priority: Option<i32>,
+    /// Scarcity score ordering jobs of the same priority
+    pub priority_score: f64,
+    /// When the score was last recalculated
+    pub priority_scored_at: Option<DateTime<Utc>>,
+    /// Failed polls in a row
+    pub consecutive_errors: Option<i32>,
+    /// When the current streak of failed polls started
+    pub failing_since: Option<DateTime<Utc>>,
+    /// Kind of the latest failure while polls are failing (`timeout`, `http`, `decode`,
+    /// `blocked`, `config`)
+    pub last_failure_kind: Option<String>,
+    /// HTTP status of the latest failure, if recreation.gov answered
+    pub last_failure_status: Option<i32>,
+    /// When the campground was last polled
+    pub last_polled: Option<DateTime<Utc>>,
+    /// When the next poll is due
+    pub next_poll_at: Option<DateTime<Utc>>,
+    /// Instance polling the campground right now, if any
+    pub claimed_by: Option<String>,
+    /// When that instance's claim lapses
+    pub claimed_until: Option<DateTime<Utc>>,
+    /// End of sniper mode, while the campground is in it
+    pub sniper_until: Option<DateTime<Utc>>,
+    /// Polling is skipped until then, when an admin disabled the job
+    pub disabled_until: Option<DateTime<Utc>>,
+    /// Polling is skipped until an admin resumes the job
+    pub paused: bool,
+    /// Frequency and priority were set by an admin and aren't retuned automatically
+    pub pinned: bool,
+    /// Local time the campground releases inventory each day, if known
+    pub release_time: Option<NaiveTime>,
+    /// Time zone of the release time
+    pub release_time_zone: Option<String>,
+    /// How many months ahead the night released each day is
+    pub release_window_months: Option<i32>,
+    /// End of the release burst, while the campground is in one
+    pub burst_until: Option<DateTime<Utc>>,
+}
+
+/// Upstream call made while polling a campground
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PollResult {
+    /// Logical name of the upstream endpoint
+    pub endpoint: String,
+    /// HTTP status, or `None` when no response came back
+    pub status_code: Option<i32>,
+    /// How long the call took
+    pub duration_ms: i32,
+    /// When the call was made
+    pub called_at: Option<DateTime<Utc>>,
+}
+
+/// Date whose cached availability has a failed check
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AvailabilityCheckError {
+    /// Date the availability is for
+    pub date: NaiveDate,
+    /// Outcome of the check (`error`, `rate_limited`)
+    pub check_status: Option<String>,
+    /// Error message of the check
+    pub error_message: Option<String>,
+    /// When the date was last checked
+    pub last_checked: Option<DateTime<Utc>>,
+}
+
+/// What admins see when drilling into one campground's polling
+#[derive(Debug, Clone, Serialize)]
+pub struct PollingJobReport {
+    /// The polling job
+    pub job: PollingJobState,
+    /// Most recent upstream calls for the campground, newest first
+    pub recent_polls: Vec<PollResult>,
+    /// Dates whose last check failed, most recently checked first
+    pub availability_errors: Vec<AvailabilityCheckError>,
+}
+
+/// Query parameters of the polling job report
+#[derive(Debug, Deserialize)]
+pub struct PollingJobReportQuery {
+    /// Most recent polls and errors to list (default 20, at most 100)
+    pub limit: Option<i64>,
+}
+
+/// Request to stop polling a campground for a while
+#[derive(Debug, Deserialize, Validate)]
+pub struct DisablePollingJobRequest {
+    /// Minutes to skip the campground for, at most [`MAX_POLLING_JOB_DISABLE_MINUTES`]
+    #[validate(range(
+        min = 1,
+        max = MAX_POLLING_JOB_DISABLE_MINUTES,
+        message = "Minutes must be between 1 and 10080"
+    ))]
+    pub minutes: i64,
+}
+
+/// Admin changes to a polling job; fields left out are kept. Setting the frequency or
+/// priority pins them unless `pinned` says otherwise.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdatePollingJobRequest {
+    /// Minutes between polls, at most [`MAX_POLL_FREQUENCY_MINUTES`]
+    #[validate(range(
+        min = 1,
+        max = MAX_POLL_FREQUENCY_MINUTES,
+        message = "Poll frequency must be between 1 and 1440 minutes"
+    ))]
+    pub poll_frequency_minutes: Option<i32>,
+    /// Higher polls first; sniper mode campgrounds get [`SNIPER_PRIORITY`]
+    ///
+    /// [`SNIPER_PRIORITY`]: crate::SNIPER_PRIORITY
+    #[validate(range(min = 1, max = 10, message = "Priority must be between 1 and 10"))]
+    pub priority: Option<i32>,
+    /// `true` stops polling until the job is resumed with `false`
+    pub paused: Option<bool>,
+    /// `false` hands the frequency and priority back to automatic tuning
+    pub pinned: Option<bool>,
+}
+
+impl UpdatePollingJobRequest {
+    /// Whether the request changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.poll_frequency_minutes.is_none()
+            && self.priority.is_none()
+            && self.paused.is_none()
+            && self.pinned.is_none()
+    }
+
+    /// Pin the job ends up with, or `None` to keep its current one
+    fn pin(&self) -> Option<bool> {
+        self.pinned
+            .or((self.poll_frequency_minutes.is_some() || self.priority.is_some()).then_some(true))
+    }
+}
+
+/// Depth and scheduling delay of the polling queue
+#[derive(Debug, Clone, Serialize)]
+pub struct PollingQueueStats {
+    /// Jobs due and waiting to be claimed
+    pub due: usize,
+    /// Jobs claimed and being polled right now
+    pub in_flight: i64,
+    /// Waiting jobs of each priority bucket, with how long they're past due
+    pub buckets: Vec<BucketDepth>,
+}
+
+/// Admin view and controls of campgrounds' polling jobs
+#[derive(Debug, Clone)]
+pub struct PollingJobAdmin {
+    pool: PgPool,
+}
+
+impl PollingJobAdmin {
+    /// Creates the admin controls backed by the given pool
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Reports a campground's polling job with its latest upstream calls and the dates
+    /// whose availability check failed. `None` when the campground has no polling job.
+    pub async fn report(
+        &self,
+        campground_id: &str,
+        limit: i64,
+    ) -> Result<Option<PollingJobReport>, sqlx::Error> {
+        let Some(job) = self.state(campground_id).await? else {
+            return Ok(None);
+        };
+
+        let recent_polls = sqlx::query_as::<_, PollResult>(
+            r#"
+            SELECT endpoint, status_code, duration_ms, called_at
+            FROM api_call_ledger
+            WHERE campground_id = $1
+            ORDER BY called_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(campground_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let availability_errors = sqlx::query_as::<_, AvailabilityCheckError>(
+            r#"
+            SELECT date, check_status, error_message, last_checked
+            FROM campground_availability
+            WHERE campground_id = $1 AND check_status IS DISTINCT FROM 'success'
+            ORDER BY last_checked DESC NULLS LAST, date
+            LIMIT $2
+            "#,
+        )
+        .bind(campground_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Some(PollingJobReport {
+            job,
+            recent_polls,
+            availability_errors,
+        }))
+    }
+
+    /// A campground's polling job, or `None` when it has none
+    async fn state(&self, campground_id: &str) -> Result<Option<PollingJobState>, sqlx::Error> {
+        sqlx::query_as::<_, PollingJobState>(
+            r#"
+            SELECT pj.campground_id, c.name AS campground_name, pj.active_scan_count,
+                   pj.poll_frequency_minutes, pj.priority, pj.priority_score,
+                   pj.priority_scored_at, pj.consecutive_errors, pj.failing_since,
+                   pj.last_failure_kind, pj.last_failure_status,
+                   pj.last_polled, pj.next_poll_at, pj.claimed_by, pj.claimed_until,
+                   pj.sniper_until, pj.disabled_until, pj.paused, pj.pinned,
+                   pj.release_time, pj.release_time_zone, pj.release_window_months,
+                   pj.burst_until
+            FROM polling_jobs pj
+            LEFT JOIN campgrounds c ON c.id = pj.campground_id
+            WHERE pj.campground_id = $1
+            "#,
+        )
+        .bind(campground_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Changes a campground's poll frequency and priority, pauses or resumes it, and pins
+    /// or unpins its schedule. Pinned jobs leave sniper mode and aren't retuned; a shorter
+    /// frequency or a resume brings the next poll forward, and the next polling cycle
+    /// picks the rest up. Returns the updated job, or `None` when the campground has no
+    /// polling job.
+    pub async fn update(
+        &self,
+        campground_id: &str,
+        request: &UpdatePollingJobRequest,
+    ) -> Result<Option<PollingJobState>, sqlx::Error> {
+        let updated = sqlx::query(
+            r#"
+            UPDATE polling_jobs
+            SET poll_frequency_minutes = COALESCE($2, poll_frequency_minutes),
+                priority = COALESCE($3, priority),
+                paused = COALESCE($4, paused),
+                pinned = COALESCE($5, pinned),
+                sniper_until = CASE WHEN COALESCE($5, pinned) THEN NULL ELSE sniper_until END,
+                next_poll_at = CASE
+                    WHEN $4 IS FALSE THEN LEAST(COALESCE(next_poll_at, NOW()), NOW())
+                    WHEN $2 IS NOT NULL THEN LEAST(
+                        COALESCE(next_poll_at, NOW()),
+                        COALESCE(last_polled, NOW()) + make_interval(mins => $2)
+                    )
+                    ELSE next_poll_at
+                END,
+                updated_at = NOW()
+            WHERE campground_id = $1
+            "#,
+        )
+        .bind(campground_id)
+        .bind(request.poll_frequency_minutes)
+        .bind(request.priority)
+        .bind(request.paused)
+        .bind(request.pin())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if updated == 0 {
+            return Ok(None);
+        }
+        self.state(campground_id).await
+    }
+
+    /// Sets when a campground releases inventory, so it's polled in bursts around that
+    /// time, or clears it with `None` (ending any burst in progress). Returns the updated
+    /// job, or `None` when the campground has no polling job.
+    pub async fn set_release_schedule(
+        &self,
+        campground_id: &str,
+        schedule: Option<&ReleaseSchedule>,
+    ) -> Result<Option<PollingJobState>, sqlx::Error> {
+        let updated = sqlx::query(
+            r#"
+            UPDATE polling_jobs
+            SET release_time = $2,
+                release_time_zone = $3,
+                release_window_months = $4,
+                burst_until = CASE WHEN $2 IS NULL THEN NULL ELSE burst_until END,
+                updated_at = NOW()
+            WHERE campground_id = $1
+            "#,
+        )
+        .bind(campground_id)
+        .bind(schedule.map(|schedule| schedule.release_time))
+        .bind(schedule.map(|schedule| schedule.time_zone.name()))
+        .bind(schedule.and_then(|schedule| schedule.window_months.map(|months| months as i32)))
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if updated == 0 {
+            return Ok(None);
+        }
+        self.state(campground_id).await
+    }
+
+    /// Reports how many polling jobs are due in each priority bucket and how long they've
+    /// waited, along with the jobs being polled right now
+    pub async fn queue_stats(&self) -> Result<PollingQueueStats, sqlx::Error> {
+        let due = due_jobs(&self.pool).await?;
+        let in_flight: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM polling_jobs
+            WHERE COALESCE(is_being_polled, FALSE) AND claimed_until > NOW()
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(PollingQueueStats {
+            due: due.len(),
+            in_flight,
+            buckets: queue_depth(&due, Utc::now()),
+        })
+    }
+
+    /// Clears a campground's error streak. Returns `false` when it has no polling job.
+    pub async fn reset_errors(&self, campground_id: &str) -> Result<bool, sqlx::Error> {
+        let reset = sqlx::query(
+            "UPDATE polling_jobs SET consecutive_errors = 0, updated_at = NOW() WHERE campground_id = $1",
+        )
+        .bind(campground_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(reset > 0)
+    }
+
+    /// Stops polling a campground for `minutes`, after which it resumes on its own. A poll
+    /// already running finishes. Returns when polling resumes, or `None` when the
+    /// campground has no polling job.
+    pub async fn disable(
+        &self,
+        campground_id: &str,
+        minutes: i64,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let until = Utc::now() + Duration::minutes(minutes);
+
+        let disabled = sqlx::query(
+            "UPDATE polling_jobs SET disabled_until = $2, updated_at = NOW() WHERE campground_id = $1",
+        )
+        .bind(campground_id)
+        .bind(until)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok((disabled > 0).then_some(until))
+    }
+
+    /// Resumes polling a disabled campground right away. Returns `false` when it has no
+    /// polling job.
+    pub async fn enable(&self, campground_id: &str) -> Result<bool, sqlx::Error> {
+        let enabled = sqlx::query(
+            r#"
+            UPDATE polling_jobs
+            SET disabled_until = NULL,
+                next_poll_at = LEAST(COALESCE(next_poll_at, NOW()), NOW()),
+                updated_at = NOW()
+            WHERE campground_id = $1
+            "#,
+        )
+        .bind(campground_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(enabled > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan_executor::ScanExecutorConfig;
+    use app_config::AppConfig;
+    use postgres::test_fixtures::insert_user;
+
+    fn queue(pool: &PgPool) -> PollingJobQueue {
+        let config = ScanExecutorConfig::from_config(&AppConfig::default());
+        PollingJobQueue::new(pool.clone(), SharedScanConfig::new(config))
+    }
+
+    #[test]
+    fn backoff_follows_the_kind_of_failure() {
+        // Timeouts come back quickly
+        assert_eq!(failure_backoff_minutes(Retry::Soon, 1, 15, 120), 1);
+        assert_eq!(failure_backoff_minutes(Retry::Soon, 3, 15, 120), 4);
+        // Server errors double from the poll frequency
+        assert_eq!(failure_backoff_minutes(Retry::Backoff, 1, 15, 120), 30);
+        assert_eq!(failure_backoff_minutes(Retry::Backoff, 2, 15, 120), 60);
+        assert_eq!(failure_backoff_minutes(Retry::Backoff, 9, 15, 120), 120);
+        // Blocks pause for the longest backoff right away
+        assert_eq!(failure_backoff_minutes(Retry::Pause, 1, 15, 120), 120);
+        assert_eq!(failure_backoff_minutes(Retry::Pause, 1, 240, 120), 240);
+        // Backoff turned off
+        assert_eq!(failure_backoff_minutes(Retry::Soon, 4, 15, 0), 15);
+        assert_eq!(failure_backoff_minutes(Retry::Pause, 4, 15, 0), 15);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn permits_are_claimed_by_one_instance_until_polled(pool: PgPool) {
+        let user_id = insert_user(&pool, "hiker@example.com").await;
+        sqlx::query(
+            r#"
+            INSERT INTO permit_scans (user_id, permit_id, permit_name, start_date, end_date)
+            VALUES ($1, '234652', 'Half Dome', CURRENT_DATE + 30, CURRENT_DATE + 37)
+            "#,
+        )
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let (first, second) = (queue(&pool), queue(&pool));
+
+        assert_eq!(
+            first.get_permit_jobs_needing_poll(10).await.unwrap(),
+            vec!["234652".to_string()]
+        );
+        assert!(
+            second
+                .get_permit_jobs_needing_poll(10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!second.finish_permit("234652", None).await.unwrap());
+
+        // A failed poll backs off, and the permit isn't due again until then
+        assert!(
+            first
+                .finish_permit("234652", Some(Retry::Backoff))
+                .await
+                .unwrap()
+        );
+        let (errors, wait_minutes): (i32, f64) = sqlx::query_as(
+            r#"
+            SELECT consecutive_errors, EXTRACT(EPOCH FROM next_poll_at - NOW())::FLOAT8 / 60
+            FROM permit_polling_jobs WHERE permit_id = '234652'
+            "#,
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(errors, 1);
+        assert!(wait_minutes > 15.0);
+        assert!(
+            second
+                .get_permit_jobs_needing_poll(10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Released claims are picked up again right away
+        sqlx::query("UPDATE permit_polling_jobs SET next_poll_at = NOW()")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            second.get_permit_jobs_needing_poll(10).await.unwrap().len(),
+            1
+        );
+        assert!(second.release_permit("234652").await.unwrap());
+        assert_eq!(
+            first.get_permit_jobs_needing_poll(10).await.unwrap().len(),
+            1
+        );
+        assert_eq!(first.release_claims().await.unwrap(), 1);
+    }
+}
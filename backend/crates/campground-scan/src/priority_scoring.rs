@@ -0,0 +1,172 @@
+use sqlx::{PgPool, Row};
+
+/// Most points sell-outs add to a score
+const SELL_OUT_POINTS: f64 = 40.0;
+
+/// Most points active scans add to a score
+const DEMAND_POINTS: f64 = 25.0;
+
+/// Most points an imminent check-in adds to a score
+const URGENCY_POINTS: f64 = 35.0;
+
+/// Check-ins further out than this many days add nothing to a score
+const URGENCY_HORIZON_DAYS: i64 = 30;
+
+/// What a campground's polling priority is scored from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScarcitySignals {
+    /// Sites booked per day, counted from availability windows that closed before their
+    /// night passed
+    pub sell_outs_per_day: f64,
+    /// Active scans watching the campground
+    pub active_scans: i32,
+    /// Days until the soonest check-in of an active scan, if any check in ahead
+    pub days_to_check_in: Option<i64>,
+}
+
+/// Polling priority from 0 to 100: up to 40 points for sell-outs and 25 for active scans,
+/// both with diminishing returns, and up to 35 for a check-in within 30 days, rising as
+/// it nears.
+pub fn scarcity_score(signals: &ScarcitySignals) -> f64 {
+    let sell_outs = (signals.sell_outs_per_day.max(0.0).ln_1p() * 10.0).min(SELL_OUT_POINTS);
+    let demand = (f64::from(signals.active_scans.max(0)).ln_1p() * 8.0).min(DEMAND_POINTS);
+    let urgency = signals.days_to_check_in.map_or(0.0, |days| {
+        let days_left = (URGENCY_HORIZON_DAYS - days.max(0)).max(0);
+        URGENCY_POINTS * days_left as f64 / URGENCY_HORIZON_DAYS as f64
+    });
+
+    ((sell_outs + demand + urgency) * 100.0).round() / 100.0
+}
+
+/// Scores polling jobs by how scarce their campground's sites are, so the poller claims
+/// the campgrounds where an opening is most likely to matter first
+#[derive(Debug, Clone)]
+pub struct PriorityScoring {
+    pool: PgPool,
+}
+
+impl PriorityScoring {
+    /// Creates a scorer backed by the given pool
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Recalculates the priority score of every polling job with active scans from the
+    /// last `days` of sell-outs, its active scans and their soonest check-in. Jobs
+    /// without active scans drop to 0. Returns the number of jobs scored.
+    pub async fn apply(&self, days: i32) -> Result<u64, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT pj.campground_id,
+                   COALESCE(pj.active_scan_count, 0) AS active_scans,
+                   (SELECT COUNT(*)
+                    FROM site_availability_windows saw
+                    WHERE saw.campground_id = pj.campground_id
+                      AND saw.closed_at >= NOW() - make_interval(days => $1)
+                      AND saw.closed_at::DATE <= saw.date) AS sell_outs,
+                   (SELECT MIN(us.check_in_date) - CURRENT_DATE
+                    FROM user_scans us
+                    WHERE us.campground_id = pj.campground_id
+                      AND us.status = 'active'
+                      AND us.deleted_at IS NULL
+                      AND us.check_in_date >= CURRENT_DATE) AS days_to_check_in
+            FROM polling_jobs pj
+            WHERE COALESCE(pj.active_scan_count, 0) > 0
+            "#,
+        )
+        .bind(days)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut campground_ids = Vec::with_capacity(rows.len());
+        let mut scores = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            let sell_outs: i64 = row.get("sell_outs");
+            let days_to_check_in: Option<i32> = row.get("days_to_check_in");
+
+            scores.push(scarcity_score(&ScarcitySignals {
+                sell_outs_per_day: sell_outs as f64 / f64::from(days.max(1)),
+                active_scans: row.get("active_scans"),
+                days_to_check_in: days_to_check_in.map(i64::from),
+            }));
+            campground_ids.push(row.get::<String, _>("campground_id"));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let scored = sqlx::query(
+            r#"
+            UPDATE polling_jobs pj
+            SET priority_score = scored.score, priority_scored_at = NOW(), updated_at = NOW()
+            FROM UNNEST($1::VARCHAR[], $2::FLOAT8[]) AS scored(campground_id, score)
+            WHERE pj.campground_id = scored.campground_id
+            "#,
+        )
+        .bind(&campground_ids)
+        .bind(&scores)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query(
+            r#"
+            UPDATE polling_jobs
+            SET priority_score = 0, priority_scored_at = NOW(), updated_at = NOW()
+            WHERE COALESCE(active_scan_count, 0) = 0 AND priority_score <> 0
+            "#,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(scored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scarce_soon_and_watched_campgrounds_score_higher() {
+        let quiet = ScarcitySignals {
+            sell_outs_per_day: 0.0,
+            active_scans: 1,
+            days_to_check_in: None,
+        };
+        let busy = ScarcitySignals {
+            sell_outs_per_day: 12.0,
+            ..quiet.clone()
+        };
+        let imminent = ScarcitySignals {
+            days_to_check_in: Some(2),
+            ..quiet.clone()
+        };
+
+        assert!(scarcity_score(&busy) > scarcity_score(&quiet));
+        assert!(scarcity_score(&imminent) > scarcity_score(&quiet));
+        assert!(
+            scarcity_score(&ScarcitySignals {
+                active_scans: 10,
+                ..quiet.clone()
+            }) > scarcity_score(&quiet)
+        );
+
+        // Far-off check-ins add nothing, and no campground scores past 100
+        assert_eq!(
+            scarcity_score(&ScarcitySignals {
+                days_to_check_in: Some(90),
+                ..quiet.clone()
+            }),
+            scarcity_score(&quiet)
+        );
+        let maxed = ScarcitySignals {
+            sell_outs_per_day: 10_000.0,
+            active_scans: i32::MAX,
+            days_to_check_in: Some(0),
+        };
+        assert_eq!(scarcity_score(&maxed), 100.0);
+    }
+}
@@ -0,0 +1,268 @@
+use chrono::{DateTime, Duration, Months, NaiveDate, NaiveTime, TimeZone, Utc};
+use i18n::{Tz, local_date, parse_timezone};
+use serde::Deserialize;
+use sqlx::{PgPool, Row};
+use validator::Validate;
+
+use crate::scan_executor::ScanExecutorConfig;
+
+/// Longest rolling release window an admin can set, in months
+pub const MAX_RELEASE_WINDOW_MONTHS: i32 = 24;
+
+/// When a campground releases new inventory each day, e.g. 07:00 Pacific for the night
+/// six months out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReleaseSchedule {
+    /// Local time of day inventory is released
+    pub release_time: NaiveTime,
+    /// Time zone of the release time
+    pub time_zone: Tz,
+    /// How many months ahead the night released each day is, when known
+    pub window_months: Option<u32>,
+}
+
+impl ReleaseSchedule {
+    /// Instant of the release on local `date`; `None` when a daylight saving change
+    /// skips the release time that day
+    pub fn release_on(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        self.time_zone
+            .from_local_datetime(&date.and_time(self.release_time))
+            .earliest()
+            .map(|release| release.with_timezone(&Utc))
+    }
+
+    /// Night opened for booking by the release on local `date`, when the window is known
+    pub fn released_night(&self, date: NaiveDate) -> Option<NaiveDate> {
+        date.checked_add_months(Months::new(self.window_months?))
+    }
+
+    /// Burst `now` falls in, running from `lead` before a release until `length` after it
+    pub fn burst_at(
+        &self,
+        now: DateTime<Utc>,
+        lead: Duration,
+        length: Duration,
+    ) -> Option<ReleaseBurst> {
+        let today = local_date(now, self.time_zone);
+
+        [today.pred_opt(), Some(today), today.succ_opt()]
+            .into_iter()
+            .flatten()
+            .find_map(|date| {
+                let release = self.release_on(date)?;
+                (release - lead <= now && now < release + length).then(|| ReleaseBurst {
+                    until: release + length,
+                    released_night: self.released_night(date),
+                })
+            })
+    }
+}
+
+/// Polling burst around one release
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReleaseBurst {
+    /// When the burst ends and the campground goes back to its poll frequency
+    pub until: DateTime<Utc>,
+    /// Night the release opens for booking, when the release window is known
+    pub released_night: Option<NaiveDate>,
+}
+
+/// Admin request setting when a campground releases inventory
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReleaseScheduleRequest {
+    /// Local time inventory is released, e.g. `07:00`
+    pub release_time: NaiveTime,
+    /// IANA time zone of the release time, e.g. `America/Los_Angeles`
+    #[validate(custom(function = "validate_time_zone"))]
+    pub time_zone: String,
+    /// How many months ahead the night released each day is, when known
+    #[validate(range(
+        min = 1,
+        max = MAX_RELEASE_WINDOW_MONTHS,
+        message = "Release window must be between 1 and 24 months"
+    ))]
+    pub window_months: Option<i32>,
+}
+
+impl ReleaseScheduleRequest {
+    /// Schedule requested, or `None` when the time zone is unknown
+    pub fn schedule(&self) -> Option<ReleaseSchedule> {
+        Some(ReleaseSchedule {
+            release_time: self.release_time,
+            time_zone: parse_timezone(&self.time_zone)?,
+            window_months: self.window_months.map(|months| months as u32),
+        })
+    }
+}
+
+/// Custom validation function for IANA time zone names
+fn validate_time_zone(time_zone: &str) -> Result<(), validator::ValidationError> {
+    match parse_timezone(time_zone) {
+        Some(_) => Ok(()),
+        None => Err(validator::ValidationError::new("invalid_timezone")
+            .with_message("Unknown time zone; use an IANA name such as America/Denver".into())),
+    }
+}
+
+/// Outcome of one release burst round
+#[derive(Debug, Clone, Default)]
+pub struct ReleaseBurstUpdate {
+    /// Campgrounds whose burst started
+    pub bursting: u64,
+    /// Campgrounds whose burst ended
+    pub ended: u64,
+}
+
+/// Starts and ends the polling bursts of campgrounds with a release schedule, so they're
+/// polled every few seconds around their release time
+pub struct ReleaseBurstScheduler {
+    pool: PgPool,
+    config: ScanExecutorConfig,
+}
+
+impl ReleaseBurstScheduler {
+    /// Creates a scheduler with the given settings
+    pub fn new(pool: PgPool, config: ScanExecutorConfig) -> Self {
+        Self { pool, config }
+    }
+
+    /// Ends bursts that ran their course, then starts one for each campground whose
+    /// release is coming up (or just happened) and is due right away. With a known
+    /// release window, only campgrounds with an active scan wanting the released night
+    /// burst; otherwise any active scan will do. Paused jobs never burst.
+    pub async fn apply(&self) -> Result<ReleaseBurstUpdate, sqlx::Error> {
+        let ended = sqlx::query(
+            r#"
+            UPDATE polling_jobs
+            SET burst_until = NULL, updated_at = NOW()
+            WHERE burst_until IS NOT NULL AND (burst_until <= NOW() OR NOT $1)
+            "#,
+        )
+        .bind(self.config.release_burst_enabled)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if !self.config.release_burst_enabled {
+            return Ok(ReleaseBurstUpdate {
+                ended,
+                ..Default::default()
+            });
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT campground_id, release_time, release_time_zone, release_window_months
+            FROM polling_jobs
+            WHERE release_time IS NOT NULL AND burst_until IS NULL AND NOT paused
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let now = Utc::now();
+        let lead = Duration::minutes(self.config.release_burst_lead_minutes.into());
+        let length = Duration::minutes(self.config.release_burst_minutes.into());
+        let mut campground_ids = Vec::new();
+        let mut untils = Vec::new();
+        let mut released_nights = Vec::new();
+
+        for row in rows {
+            let Some(time_zone) = row
+                .get::<Option<String>, _>("release_time_zone")
+                .as_deref()
+                .and_then(parse_timezone)
+            else {
+                continue;
+            };
+            let schedule = ReleaseSchedule {
+                release_time: row.get("release_time"),
+                time_zone,
+                window_months: row
+                    .get::<Option<i32>, _>("release_window_months")
+                    .map(|months| months.max(0) as u32),
+            };
+
+            if let Some(burst) = schedule.burst_at(now, lead, length) {
+                campground_ids.push(row.get::<String, _>("campground_id"));
+                untils.push(burst.until);
+                released_nights.push(burst.released_night);
+            }
+        }
+
+        if campground_ids.is_empty() {
+            return Ok(ReleaseBurstUpdate {
+                ended,
+                ..Default::default()
+            });
+        }
+
+        let bursting = sqlx::query(
+            r#"
+            UPDATE polling_jobs pj
+            SET burst_until = b.until,
+                next_poll_at = LEAST(COALESCE(pj.next_poll_at, NOW()), NOW()),
+                updated_at = NOW()
+            FROM UNNEST($1::TEXT[], $2::TIMESTAMPTZ[], $3::DATE[]) AS b(campground_id, until, night)
+            WHERE pj.campground_id = b.campground_id
+              AND EXISTS (
+                SELECT 1 FROM user_scans us
+                WHERE us.campground_id = b.campground_id
+                  AND us.status = 'active'
+                  AND us.deleted_at IS NULL
+                  AND (b.night IS NULL
+                       OR (b.night >= us.check_in_date - us.flexibility_days
+                           AND b.night < us.check_out_date + us.flexibility_days))
+              )
+            "#,
+        )
+        .bind(&campground_ids)
+        .bind(&untils)
+        .bind(&released_nights)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(ReleaseBurstUpdate { bursting, ended })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bursts_surround_the_local_release_time() {
+        let schedule = ReleaseSchedule {
+            release_time: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            time_zone: parse_timezone("America/Los_Angeles").unwrap(),
+            window_months: Some(6),
+        };
+        let lead = Duration::minutes(1);
+        let length = Duration::minutes(5);
+        // 07:00 PDT
+        let release = Utc.with_ymd_and_hms(2026, 7, 15, 14, 0, 0).unwrap();
+
+        assert_eq!(
+            schedule.burst_at(release - Duration::seconds(30), lead, length),
+            Some(ReleaseBurst {
+                until: release + length,
+                released_night: NaiveDate::from_ymd_opt(2027, 1, 15),
+            })
+        );
+        assert!(
+            schedule
+                .burst_at(release + Duration::minutes(4), lead, length)
+                .is_some()
+        );
+        assert_eq!(
+            schedule.burst_at(release - Duration::minutes(2), lead, length),
+            None
+        );
+        assert_eq!(schedule.burst_at(release + length, lead, length), None);
+
+        // 07:00 PST in winter
+        let winter = Utc.with_ymd_and_hms(2026, 12, 1, 15, 0, 0).unwrap();
+        assert!(schedule.burst_at(winter, lead, length).is_some());
+    }
+}
@@ -0,0 +1,118 @@
+use serde::Serialize;
+use sqlx::PgPool;
+use std::time::Duration;
+
+use crate::scan_executor::ScanExecutorConfig;
+
+/// Rows deleted per statement, so each delete stays short and autovacuum can reclaim the
+/// space between batches instead of facing one huge dead-tuple backlog
+const RETENTION_BATCH_SIZE: i64 = 5000;
+
+/// Pause between batches, leaving room for regular traffic and vacuum
+const RETENTION_BATCH_PAUSE: Duration = Duration::from_millis(200);
+
+/// Rows deleted by a retention pass, per table
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PrunedRows {
+    /// Cached availability of nights past the retention window
+    pub campground_availability: u64,
+    /// Availability history observed before the retention window
+    pub availability_history: u64,
+    /// Notification records created before the retention window
+    pub notifications: u64,
+    /// Scan timeline events created before the retention window
+    pub scan_events: u64,
+}
+
+impl PrunedRows {
+    /// Rows deleted across all tables
+    pub fn total(&self) -> u64 {
+        self.campground_availability
+            + self.availability_history
+            + self.notifications
+            + self.scan_events
+    }
+}
+
+/// Deletes availability, notification, and scan event rows older than the retention
+/// windows in [`ScanExecutorConfig`], in small batches
+pub struct RetentionService {
+    pool: PgPool,
+    config: ScanExecutorConfig,
+}
+
+impl RetentionService {
+    /// Creates a retention service with the given settings
+    pub fn new(pool: PgPool, config: ScanExecutorConfig) -> Self {
+        Self { pool, config }
+    }
+
+    /// Deletes every row past its table's retention window. Tables whose window is 0 are
+    /// left alone.
+    pub async fn prune(&self) -> Result<PrunedRows, sqlx::Error> {
+        Ok(PrunedRows {
+            campground_availability: self
+                .delete_in_batches(
+                    "campground_availability",
+                    "date < CURRENT_DATE - $1",
+                    self.config.availability_retention_days,
+                )
+                .await?,
+            availability_history: self
+                .delete_in_batches(
+                    "availability_history",
+                    "observed_at < NOW() - make_interval(days => $1)",
+                    self.config.availability_retention_days,
+                )
+                .await?,
+            notifications: self
+                .delete_in_batches(
+                    "notifications",
+                    "created_at < NOW() - make_interval(days => $1)",
+                    self.config.notification_retention_days,
+                )
+                .await?,
+            scan_events: self
+                .delete_in_batches(
+                    "scan_events",
+                    "created_at < NOW() - make_interval(days => $1)",
+                    self.config.scan_event_retention_days,
+                )
+                .await?,
+        })
+    }
+
+    /// Deletes rows of `table` matching `condition` (with `$1` bound to the retention
+    /// days) [`RETENTION_BATCH_SIZE`] at a time until none are left
+    async fn delete_in_batches(
+        &self,
+        table: &'static str,
+        condition: &'static str,
+        retention_days: u32,
+    ) -> Result<u64, sqlx::Error> {
+        if retention_days == 0 {
+            return Ok(0);
+        }
+
+        let statement = format!(
+            "DELETE FROM {table} WHERE ctid = ANY(ARRAY(SELECT ctid FROM {table} WHERE {condition} LIMIT $2))"
+        );
+        let mut deleted = 0;
+
+        loop {
+            let batch = sqlx::query(&statement)
+                .bind(retention_days as i32)
+                .bind(RETENTION_BATCH_SIZE)
+                .execute(&self.pool)
+                .await?
+                .rows_affected();
+
+            deleted += batch;
+            if batch < RETENTION_BATCH_SIZE as u64 {
+                return Ok(deleted);
+            }
+
+            tokio::time::sleep(RETENTION_BATCH_PAUSE).await;
+        }
+    }
+}
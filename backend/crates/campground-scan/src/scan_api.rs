@@ -0,0 +1,60 @@
+use std::future::Future;
+use uuid::Uuid;
+
+use crate::scan_service::ScanService;
+use crate::scan_types::{CreateScanRequest, CreatedScan, ScanError, UserScanWithCampground};
+
+/// The scan operations creating and updating a scan make, implemented by
+/// [`ScanService`] and, in handler tests that mustn't touch the database, by test doubles.
+pub trait ScanApi: Send + Sync + 'static {
+    /// Creates a scan for the user, failing when the stay is invalid or the user's plan
+    /// has no scans left
+    fn create_scan(
+        &self,
+        user_id: &Uuid,
+        request: &CreateScanRequest,
+    ) -> impl Future<Output = Result<CreatedScan, ScanError>> + Send;
+
+    /// Sets the status of one of the user's scans
+    fn update_scan_status(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+        new_status: &str,
+    ) -> impl Future<Output = Result<UserScanWithCampground, ScanError>> + Send;
+
+    /// Remembers that a campground is first-come, first-served, so it's no longer
+    /// offered for scans
+    fn mark_not_reservable(
+        &self,
+        campground_id: &str,
+        campground_name: &str,
+    ) -> impl Future<Output = Result<(), ScanError>> + Send;
+}
+
+impl ScanApi for ScanService {
+    async fn create_scan(
+        &self,
+        user_id: &Uuid,
+        request: &CreateScanRequest,
+    ) -> Result<CreatedScan, ScanError> {
+        ScanService::create_scan(self, user_id, request).await
+    }
+
+    async fn update_scan_status(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+        new_status: &str,
+    ) -> Result<UserScanWithCampground, ScanError> {
+        ScanService::update_scan_status(self, user_id, scan_id, new_status).await
+    }
+
+    async fn mark_not_reservable(
+        &self,
+        campground_id: &str,
+        campground_name: &str,
+    ) -> Result<(), ScanError> {
+        ScanService::mark_not_reservable(self, campground_id, campground_name).await
+    }
+}
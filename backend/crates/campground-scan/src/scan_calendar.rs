@@ -0,0 +1,53 @@
+use notification_services::{CalendarEvent, ics_calendar};
+use serde::Deserialize;
+
+use crate::scan_types::UserScanWithCampground;
+
+/// Query of a scan calendar feed link
+#[derive(Debug, Deserialize)]
+pub struct CalendarFeedQuery {
+    /// Signed token naming the user whose scans the feed lists
+    pub token: String,
+}
+
+/// The user's scans as an iCalendar feed: one all-day event per stay of an active,
+/// paused or completed scan, titled by where the scan stands. Cancelled and expired
+/// scans drop out, so calendars remove their events on the next refresh.
+pub fn scan_calendar(scans: &[UserScanWithCampground], base_url: &str) -> String {
+    let mut scans: Vec<&UserScanWithCampground> = scans
+        .iter()
+        .filter(|scan| matches!(scan.status.as_str(), "active" | "paused" | "completed"))
+        .collect();
+    scans.sort_by_key(|scan| (scan.check_in_date, scan.id));
+
+    let events: Vec<CalendarEvent> = scans
+        .into_iter()
+        .map(|scan| {
+            let summary = match scan.status.as_str() {
+                "active" => format!("Watching {}", scan.campground_name),
+                "paused" => format!("Watching {} (paused)", scan.campground_name),
+                _ => format!("Camping at {}", scan.campground_name),
+            };
+            let scan_url = format!("{}/?scan={}", base_url, scan.id);
+
+            CalendarEvent {
+                uid: CalendarEvent::scan_uid(&scan.id),
+                start: scan.check_in_date,
+                end: scan.check_out_date,
+                summary,
+                description: Some(format!(
+                    "{} night{} at {}\nScan status: {}\n{}",
+                    scan.nights,
+                    if scan.nights == 1 { "" } else { "s" },
+                    scan.campground_name,
+                    scan.status,
+                    scan_url
+                )),
+                url: Some(scan_url),
+                stamp: scan.updated_at,
+            }
+        })
+        .collect();
+
+    ics_calendar("Campsite Tracker scans", None, &events)
+}
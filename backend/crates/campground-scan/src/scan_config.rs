@@ -0,0 +1,104 @@
+use serde::Deserialize;
+use std::sync::{Arc, RwLock};
+use validator::Validate;
+
+use crate::scan_executor::ScanExecutorConfig;
+
+/// Longest minimum interval between upstream calls an admin can set: one minute
+pub const MAX_MIN_API_INTERVAL_MS: u64 = 60_000;
+
+/// Longest error backoff an admin can set: one day
+pub const MAX_ERROR_BACKOFF_MINUTES: u32 = 24 * 60;
+
+/// Polling settings shared by the polling workers and the admin API, so ops can change
+/// them without restarting (e.g. to slow down during a recreation.gov incident). Clones
+/// share the settings. Changes hold on this instance until it restarts, which goes back
+/// to the environment.
+#[derive(Debug, Clone)]
+pub struct SharedScanConfig(Arc<RwLock<ScanExecutorConfig>>);
+
+impl SharedScanConfig {
+    /// Shares `config`
+    pub fn new(config: ScanExecutorConfig) -> Self {
+        Self(Arc::new(RwLock::new(config)))
+    }
+
+    /// Settings in effect right now
+    pub fn get(&self) -> ScanExecutorConfig {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Applies `update` and returns the settings now in effect
+    pub fn update(&self, update: &ScanConfigUpdate) -> ScanExecutorConfig {
+        let mut config = self.0.write().unwrap();
+        update.apply(&mut config);
+        config.clone()
+    }
+}
+
+/// Admin changes to the polling settings that can be made at runtime; fields left out
+/// are kept
+#[derive(Debug, Deserialize, Validate)]
+pub struct ScanConfigUpdate {
+    /// Least time between two paced recreation.gov calls, in milliseconds; 0 leaves
+    /// spacing to the pacing profile
+    #[validate(range(
+        max = MAX_MIN_API_INTERVAL_MS,
+        message = "Minimum API interval must be at most 60000 ms"
+    ))]
+    pub min_api_interval_ms: Option<u64>,
+    /// Calls to recreation.gov and RIDB allowed per rolling hour; 0 removes the cap
+    pub max_calls_per_hour: Option<u32>,
+    /// Longest a failing campground waits between polls, in minutes; 0 turns backoff off
+    #[validate(range(
+        max = MAX_ERROR_BACKOFF_MINUTES,
+        message = "Error backoff must be at most 1440 minutes"
+    ))]
+    pub error_backoff_max_minutes: Option<u32>,
+}
+
+impl ScanConfigUpdate {
+    /// Whether the update changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.min_api_interval_ms.is_none()
+            && self.max_calls_per_hour.is_none()
+            && self.error_backoff_max_minutes.is_none()
+    }
+
+    fn apply(&self, config: &mut ScanExecutorConfig) {
+        if let Some(min_api_interval_ms) = self.min_api_interval_ms {
+            config.min_api_interval_ms = min_api_interval_ms;
+        }
+        if let Some(max_calls_per_hour) = self.max_calls_per_hour {
+            config.max_calls_per_hour = max_calls_per_hour;
+        }
+        if let Some(error_backoff_max_minutes) = self.error_backoff_max_minutes {
+            config.error_backoff_max_minutes = error_backoff_max_minutes;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use app_config::AppConfig;
+
+    #[test]
+    fn updates_are_seen_by_every_clone() {
+        let shared = SharedScanConfig::new(ScanExecutorConfig::from_config(&AppConfig::default()));
+        let worker = shared.clone();
+
+        let updated = shared.update(&ScanConfigUpdate {
+            min_api_interval_ms: Some(1500),
+            max_calls_per_hour: None,
+            error_backoff_max_minutes: Some(0),
+        });
+
+        let seen = worker.get();
+        assert_eq!(seen.min_api_interval_ms, 1500);
+        assert_eq!(seen.error_backoff_max_minutes, 0);
+        // Left out, so kept
+        assert_eq!(seen.max_calls_per_hour, 1000);
+        assert_eq!(updated.min_api_interval_ms, seen.min_api_interval_ms);
+    }
+}
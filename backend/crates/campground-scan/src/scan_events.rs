@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool, Row};
+use uuid::Uuid;
+
+/// Most events returned by one timeline request
+pub const MAX_SCAN_EVENTS: i64 = 500;
+
+/// Kind of entry in a scan's activity timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanEventKind {
+    /// The scan was created
+    Created,
+    /// The scan's campground was checked; details hold the open sites seen
+    Polled,
+    /// The owner was alerted about open sites
+    NotificationSent,
+    /// A check failed; details hold the error
+    Error,
+    /// The scan was paused
+    Paused,
+    /// The scan was resumed
+    Resumed,
+    /// The scan was cancelled or marked completed by its owner
+    Stopped,
+    /// The scan's dates, facility type, or flexibility changed
+    Modified,
+    /// The scan's expiry was pushed back
+    Extended,
+    /// The scan was moved to another campground
+    Remapped,
+    /// The scan expired, or ended once its check-in date passed
+    Expired,
+    /// The owner reported what came of the scan (booked, missed, gave up)
+    OutcomeRecorded,
+    /// The scan was deleted; details hold the status it had
+    Deleted,
+    /// A deleted scan was restored
+    Restored,
+}
+
+impl ScanEventKind {
+    /// Value stored in `scan_events.event_type`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanEventKind::Created => "created",
+            ScanEventKind::Polled => "polled",
+            ScanEventKind::NotificationSent => "notification_sent",
+            ScanEventKind::Error => "error",
+            ScanEventKind::Paused => "paused",
+            ScanEventKind::Resumed => "resumed",
+            ScanEventKind::Stopped => "stopped",
+            ScanEventKind::Modified => "modified",
+            ScanEventKind::Extended => "extended",
+            ScanEventKind::Remapped => "remapped",
+            ScanEventKind::Expired => "expired",
+            ScanEventKind::OutcomeRecorded => "outcome_recorded",
+            ScanEventKind::Deleted => "deleted",
+            ScanEventKind::Restored => "restored",
+        }
+    }
+
+    /// Event recorded when a scan's owner sets it to `status`, if any
+    pub fn for_status(status: &str) -> Option<Self> {
+        match status {
+            "active" => Some(ScanEventKind::Resumed),
+            "paused" => Some(ScanEventKind::Paused),
+            "completed" | "cancelled" => Some(ScanEventKind::Stopped),
+            _ => None,
+        }
+    }
+}
+
+/// An entry in a scan's activity timeline
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanEvent {
+    /// Event ID, increasing over time
+    pub id: i64,
+    /// What happened (see `ScanEventKind`)
+    pub event_type: String,
+    /// Event-specific details, e.g. `{"open_sites": 3}` for polls
+    pub details: serde_json::Value,
+    /// When it happened
+    pub created_at: DateTime<Utc>,
+}
+
+/// Query parameters of the scan timeline endpoint
+#[derive(Debug, Deserialize)]
+pub struct ScanEventsQuery {
+    /// Most recent events to return (default 100, at most `MAX_SCAN_EVENTS`)
+    pub limit: Option<i64>,
+}
+
+/// Records an event in a scan's timeline. Takes any executor so events can be written in
+/// the transaction that made the change.
+pub async fn record_scan_event<'e>(
+    executor: impl PgExecutor<'e>,
+    scan_id: &Uuid,
+    kind: ScanEventKind,
+    details: serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO scan_events (user_scan_id, event_type, details) VALUES ($1, $2, $3)")
+        .bind(scan_id)
+        .bind(kind.as_str())
+        .bind(details)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+/// Reads and writes scan activity timelines
+#[derive(Clone)]
+pub struct ScanEventLog {
+    pool: PgPool,
+}
+
+impl ScanEventLog {
+    /// Creates a log over the database pool
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Records an event. Failures are logged rather than returned, since a missing
+    /// timeline entry shouldn't fail the check that produced it.
+    pub async fn record(&self, scan_id: &Uuid, kind: ScanEventKind, details: serde_json::Value) {
+        if let Err(e) = record_scan_event(&self.pool, scan_id, kind, details).await {
+            log::warn!(
+                "⚠️ Failed to record {} event for scan {}: {}",
+                kind.as_str(),
+                scan_id,
+                e
+            );
+        }
+    }
+
+    /// The scan's most recent events, oldest first
+    pub async fn recent(&self, scan_id: &Uuid, limit: i64) -> Result<Vec<ScanEvent>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, event_type, details, created_at
+            FROM (
+                SELECT id, event_type, details, created_at
+                FROM scan_events
+                WHERE user_scan_id = $1
+                ORDER BY created_at DESC, id DESC
+                LIMIT $2
+            ) recent
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(scan_id)
+        .bind(limit.clamp(1, MAX_SCAN_EVENTS))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ScanEvent {
+                id: row.get("id"),
+                event_type: row.get("event_type"),
+                details: row.get("details"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,697 @@
+use app_config::AppConfig;
+use chrono::{NaiveDate, Utc};
+use rec_gov::{
+    CampsiteAvailability, FacilityType, Provider, ProviderClient, RecGovApi, RecGovClient,
+    RecGovError, ReserveCaliforniaClient,
+};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::availability_diff::{AvailableSites, available_sites, candidate_stays, matching_sites};
+use crate::availability_snapshots::AvailabilitySnapshots;
+use crate::campsite_attributes::CampsiteAttributeStore;
+use crate::poll_batching::PollBatch;
+use crate::scan_events::{ScanEventKind, ScanEventLog};
+use crate::scan_types::UserScan;
+use crate::scan_updates::{ScanUpdate, ScanUpdateHub};
+
+/// Priority of polling jobs in sniper mode, above every scarcity score
+pub const SNIPER_PRIORITY: i32 = 4;
+
+/// Frequency and priority polling jobs return to when sniper mode ends; hourly tuning
+/// adjusts the frequency from there
+const DEFAULT_POLL_SCHEDULE: (i32, i32) = (15, 1);
+
+/// Settings for how scans are polled
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanExecutorConfig {
+    /// Whether scans checking in soon are polled in sniper mode
+    pub sniper_enabled: bool,
+    /// Scans checking in within this many hours get sniper mode
+    pub sniper_window_hours: u32,
+    /// Poll interval in sniper mode, in minutes
+    pub sniper_poll_interval_minutes: u32,
+    /// Upstream requests per hour reserved for sniper mode
+    pub sniper_hourly_request_budget: u32,
+    /// Least time between two paced recreation.gov calls of this instance, in
+    /// milliseconds; 0 leaves spacing to the pacing profile
+    pub min_api_interval_ms: u64,
+    /// Calls to recreation.gov and RIDB allowed per rolling hour; 0 removes the cap
+    pub max_calls_per_hour: u32,
+    /// Longest a campground whose polls keep failing waits between polls, in minutes;
+    /// 0 turns backoff off
+    pub error_backoff_max_minutes: u32,
+    /// Whether campgrounds with a release schedule are polled in bursts around release
+    pub release_burst_enabled: bool,
+    /// Seconds between polls during a release burst
+    pub release_burst_interval_seconds: u32,
+    /// Minutes before a release time its burst starts
+    pub release_burst_lead_minutes: u32,
+    /// Minutes after a release time its burst lasts
+    pub release_burst_minutes: u32,
+    /// Days past nights of cached availability and availability history are kept; 0
+    /// keeps them forever
+    pub availability_retention_days: u32,
+    /// Days notification records are kept; 0 keeps them forever
+    pub notification_retention_days: u32,
+    /// Days scan timeline events are kept; 0 keeps them forever
+    pub scan_event_retention_days: u32,
+}
+
+impl ScanExecutorConfig {
+    /// Reads the sniper mode, upstream call, error backoff, release burst and retention
+    /// settings from the configuration.
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            sniper_enabled: config.sniper_mode_enabled,
+            sniper_window_hours: config.sniper_window_hours,
+            sniper_poll_interval_minutes: config.sniper_poll_interval_minutes.max(1),
+            sniper_hourly_request_budget: config.sniper_hourly_request_budget,
+            min_api_interval_ms: config.recreation_gov_min_call_interval_ms,
+            max_calls_per_hour: config.recreation_gov_hourly_request_budget,
+            error_backoff_max_minutes: config.poll_error_backoff_max_minutes,
+            release_burst_enabled: config.release_burst_enabled,
+            release_burst_interval_seconds: config.release_burst_poll_interval_seconds,
+            release_burst_lead_minutes: config.release_burst_lead_minutes,
+            release_burst_minutes: config.release_burst_minutes,
+            availability_retention_days: config.availability_retention_days,
+            notification_retention_days: config.notification_retention_days,
+            scan_event_retention_days: config.scan_event_retention_days,
+        }
+    }
+
+    /// Number of campgrounds that fit in the sniper request budget at the sniper interval
+    pub fn sniper_campground_limit(&self) -> i64 {
+        let polls_per_hour = (60 / self.sniper_poll_interval_minutes).max(1);
+        (self.sniper_hourly_request_budget / polls_per_hour).into()
+    }
+
+    /// Whether a scan checking in on `check_in_date` is within the sniper window
+    pub fn is_sniper_trip(&self, check_in_date: NaiveDate) -> bool {
+        self.is_sniper_trip_on(check_in_date, Utc::now().date_naive())
+    }
+
+    /// Whether a scan checking in on `check_in_date` is within the sniper window as of
+    /// `today`
+    pub fn is_sniper_trip_on(&self, check_in_date: NaiveDate, today: NaiveDate) -> bool {
+        let hours_until = (check_in_date - today).num_hours();
+
+        self.sniper_enabled
+            && check_in_date >= today
+            && hours_until <= self.sniper_window_hours.into()
+    }
+
+    /// Order to try notification channels for a scan: SMS first for sniper trips, where
+    /// minutes matter, email first otherwise.
+    pub fn notification_order(&self, check_in_date: NaiveDate) -> [&'static str; 2] {
+        self.notification_order_on(check_in_date, Utc::now().date_naive())
+    }
+
+    /// Order to try notification channels for a scan as of `today`
+    pub fn notification_order_on(
+        &self,
+        check_in_date: NaiveDate,
+        today: NaiveDate,
+    ) -> [&'static str; 2] {
+        if self.is_sniper_trip_on(check_in_date, today) {
+            ["sms", "email"]
+        } else {
+            ["email", "sms"]
+        }
+    }
+}
+
+/// What checking a scan found
+#[derive(Debug, Clone, Default)]
+pub struct ScanPoll {
+    /// Sites bookable for the scan (see [`ScanExecutor::open_sites`])
+    pub open_sites: Vec<CampsiteAvailability>,
+    /// Every site of the campground available on each night polled, whatever its type,
+    /// price, or attributes, for booking analytics
+    pub available: AvailableSites,
+}
+
+/// Checks scans against the reservation system their campground is booked through,
+/// recording each check in the scan's timeline and the campground's availability
+/// snapshots, and publishing it to the owner's live dashboards. Recreation.gov is
+/// reached through `R`, which tests replace with [`MockRecGov`](rec_gov::MockRecGov).
+#[derive(Clone)]
+pub struct ScanExecutor<R: RecGovApi = RecGovClient> {
+    rec_gov: R,
+    reserve_california: ReserveCaliforniaClient,
+    events: ScanEventLog,
+    snapshots: AvailabilitySnapshots,
+    attributes: CampsiteAttributeStore,
+    updates: ScanUpdateHub,
+    config: ScanExecutorConfig,
+}
+
+impl<R: RecGovApi> ScanExecutor<R> {
+    /// Creates an executor over the provider clients
+    pub fn new(
+        rec_gov: R,
+        reserve_california: ReserveCaliforniaClient,
+        events: ScanEventLog,
+        snapshots: AvailabilitySnapshots,
+        attributes: CampsiteAttributeStore,
+        updates: ScanUpdateHub,
+        config: ScanExecutorConfig,
+    ) -> Self {
+        Self {
+            rec_gov,
+            reserve_california,
+            events,
+            snapshots,
+            attributes,
+            updates,
+            config,
+        }
+    }
+
+    /// Polling settings of the executor
+    pub fn config(&self) -> &ScanExecutorConfig {
+        &self.config
+    }
+
+    /// Reservation system a scan's campground is booked through
+    pub fn provider_of(&self, scan: &UserScan) -> Provider {
+        Provider::parse(&scan.provider)
+            .unwrap_or_else(|| Provider::of_campground(&scan.campground_id))
+    }
+
+    /// Sites of the scan's facility type that are bookable every night of its stay, or of
+    /// the stay shifted by up to the scan's flexibility, fetched from the scan's provider.
+    /// Nights priced above the scan's max price don't count as bookable; nights without
+    /// a reported price do. With site filters, only sites whose RIDB attributes meet them
+    /// count.
+    /// The check and the number of open sites, or its error, go in the scan's timeline,
+    /// and a heartbeat (plus a hit if sites are open) goes to the owner's live dashboards.
+    pub async fn open_sites(
+        &self,
+        scan: &UserScan,
+    ) -> Result<Vec<CampsiteAvailability>, RecGovError> {
+        self.check(scan).await.map(|poll| poll.open_sites)
+    }
+
+    /// [`open_sites`](Self::open_sites), along with the campground's availability on
+    /// every night polled
+    pub async fn check(&self, scan: &UserScan) -> Result<ScanPoll, RecGovError> {
+        let result = self.find_open_sites(scan).await;
+        let polled_at = Utc::now();
+
+        self.updates.publish(
+            scan.user_id,
+            ScanUpdate::PollHeartbeat {
+                scan_id: scan.id,
+                campground_id: scan.campground_id.clone(),
+                open_sites: result.as_ref().ok().map(|poll| poll.open_sites.len()),
+                polled_at,
+            },
+        );
+
+        match &result {
+            Ok(ScanPoll {
+                open_sites: sites, ..
+            }) => {
+                let site_ids: Vec<&str> = sites.iter().map(|site| site.site.as_str()).collect();
+                if !sites.is_empty() {
+                    self.updates.publish(
+                        scan.user_id,
+                        ScanUpdate::AvailabilityHit {
+                            scan_id: scan.id,
+                            campground_id: scan.campground_id.clone(),
+                            sites: site_ids.iter().map(|site| site.to_string()).collect(),
+                            polled_at,
+                        },
+                    );
+                }
+                self.events
+                    .record(
+                        &scan.id,
+                        ScanEventKind::Polled,
+                        serde_json::json!({
+                            "provider": self.provider_of(scan).as_str(),
+                            "open_sites": sites.len(),
+                            "sites": site_ids,
+                        }),
+                    )
+                    .await;
+            }
+            Err(e) => {
+                self.events
+                    .record(
+                        &scan.id,
+                        ScanEventKind::Error,
+                        serde_json::json!({
+                            "error": e.to_string(),
+                            "kind": e.kind(),
+                            "status": e.status(),
+                            "retry": e.retry(),
+                        }),
+                    )
+                    .await;
+            }
+        }
+
+        result
+    }
+
+    /// This executor with every recreation.gov call sharing one session and pacing for
+    /// the batch (see [`RecGovApi::batch`]), so checking a recreation area's campgrounds
+    /// back to back warms up one session instead of several. Clones share the session.
+    pub fn batched(&self, batch: &PollBatch) -> Self {
+        Self {
+            rec_gov: self.rec_gov.batch(&batch.key()),
+            ..self.clone()
+        }
+    }
+
+    /// Fetches and filters the sites `open_sites` reports
+    async fn find_open_sites(&self, scan: &UserScan) -> Result<ScanPoll, RecGovError> {
+        let stays = candidate_stays(
+            scan.check_in_date,
+            scan.check_out_date,
+            scan.flexibility_days,
+            Utc::now().date_naive(),
+        );
+        let (Some((start, _)), Some((_, end))) = (stays.first(), stays.last()) else {
+            return Ok(ScanPoll::default());
+        };
+
+        let sites = match self.provider_of(scan) {
+            Provider::RecreationGov => fetch_nights(&self.rec_gov, scan, *start, *end).await?,
+            Provider::ReserveCalifornia => {
+                fetch_nights(&self.reserve_california, scan, *start, *end).await?
+            }
+        };
+
+        self.snapshots
+            .record(&scan.campground_id, *start, *end, &sites)
+            .await;
+        let polled = available_sites(&sites, None);
+
+        let facility_type = FacilityType::parse(&scan.facility_type).unwrap_or_default();
+        let mut sites: Vec<CampsiteAvailability> = sites
+            .into_iter()
+            .filter(|site| site.facility_type() == facility_type)
+            .collect();
+
+        if !scan.site_filters.is_empty() {
+            let attributes = self
+                .attributes
+                .for_campground(&self.rec_gov, &scan.campground_id)
+                .await?;
+            sites.retain(|site| {
+                attributes
+                    .get(&site.campsite_id)
+                    .is_some_and(|attributes| scan.site_filters.matches(attributes))
+            });
+        }
+
+        let available = available_sites(&sites, scan.max_price.map(f64::from));
+        let matching = matching_sites(&stays, &available);
+
+        Ok(ScanPoll {
+            open_sites: sites
+                .into_iter()
+                .filter(|site| matching.contains(&site.campsite_id))
+                .collect(),
+            available: polled,
+        })
+    }
+}
+
+/// Availability of the scan's campground for the nights `start..end`
+async fn fetch_nights<C: ProviderClient>(
+    client: &C,
+    scan: &UserScan,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<CampsiteAvailability>, RecGovError> {
+    client
+        .campground_availability(&scan.campground_id, start, end)
+        .await
+}
+
+/// Result of a sniper mode pass
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SniperUpdate {
+    /// Campgrounds now polled in sniper mode
+    pub sniping: u64,
+    /// Campgrounds whose sniper mode ended
+    pub released: u64,
+    /// Sniper scans given an expiry at their check-in date
+    pub expiring_scans: u64,
+}
+
+/// Moves campgrounds of scans checking in soon into sniper mode, within the reserved
+/// request budget
+pub struct SniperScheduler {
+    pool: PgPool,
+    config: ScanExecutorConfig,
+}
+
+impl SniperScheduler {
+    /// Creates a scheduler with the given settings
+    pub fn new(pool: PgPool, config: ScanExecutorConfig) -> Self {
+        Self { pool, config }
+    }
+
+    /// Ends sniper mode for campgrounds whose trips have started, then puts the campgrounds
+    /// with the soonest active same-week scans into sniper mode, as many as the request
+    /// budget allows. Only scans of users whose plan includes sniper mode count, and
+    /// campgrounds an admin pinned the schedule of are left alone. Sniper scans without an
+    /// expiry expire once check-in passes.
+    pub async fn apply(&self) -> Result<SniperUpdate, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let (frequency, priority) = DEFAULT_POLL_SCHEDULE;
+        let released = sqlx::query(
+            r#"
+            UPDATE polling_jobs
+            SET sniper_until = NULL, poll_frequency_minutes = $1, priority = $2, updated_at = NOW()
+            WHERE sniper_until IS NOT NULL AND (sniper_until <= NOW() OR NOT $3)
+            "#,
+        )
+        .bind(frequency)
+        .bind(priority)
+        .bind(self.config.sniper_enabled)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if !self.config.sniper_enabled {
+            tx.commit().await?;
+            return Ok(SniperUpdate {
+                released,
+                ..Default::default()
+            });
+        }
+
+        let window_days = (self.config.sniper_window_hours / 24) as i32;
+
+        let sniping = sqlx::query(
+            r#"
+            WITH candidates AS (
+                SELECT us.campground_id, MAX(us.check_in_date) AS last_check_in
+                FROM user_scans us
+                JOIN users u ON u.id = us.user_id
+                JOIN plans p ON p.id = u.plan
+                WHERE us.status = 'active'
+                  AND us.deleted_at IS NULL
+                  AND p.sniper_mode
+                  AND us.check_in_date >= CURRENT_DATE
+                  AND us.check_in_date <= CURRENT_DATE + $1
+                GROUP BY us.campground_id
+                ORDER BY MIN(us.check_in_date), COUNT(*) DESC
+                LIMIT $2
+            )
+            UPDATE polling_jobs pj
+            SET sniper_until = ((c.last_check_in + 1)::TIMESTAMP AT TIME ZONE 'UTC'),
+                poll_frequency_minutes = $3,
+                priority = $4,
+                next_poll_at = LEAST(
+                    COALESCE(pj.next_poll_at, NOW()),
+                    NOW() + make_interval(mins => $3)
+                ),
+                updated_at = NOW()
+            FROM candidates c
+            WHERE pj.campground_id = c.campground_id AND NOT pj.pinned
+            "#,
+        )
+        .bind(window_days)
+        .bind(self.config.sniper_campground_limit())
+        .bind(self.config.sniper_poll_interval_minutes as i32)
+        .bind(SNIPER_PRIORITY)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        // Automatic expiry fallback: sniper scans end once their check-in date passes
+        let expiring_scans = sqlx::query(
+            r#"
+            UPDATE user_scans us
+            SET expires_at = ((us.check_in_date + 1)::TIMESTAMP AT TIME ZONE 'UTC'),
+                updated_at = NOW()
+            FROM polling_jobs pj
+            WHERE pj.campground_id = us.campground_id
+              AND pj.sniper_until IS NOT NULL
+              AND us.status = 'active'
+              AND us.deleted_at IS NULL
+              AND us.expires_at IS NULL
+              AND us.check_in_date >= CURRENT_DATE
+              AND us.check_in_date <= CURRENT_DATE + $1
+            "#,
+        )
+        .bind(window_days)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+
+        Ok(SniperUpdate {
+            sniping,
+            released,
+            expiring_scans,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan_notifications::availability_alert;
+    use chrono::Duration;
+    use rec_gov::{ApiCallLedger, MockCall, MockRecGov, SiteFilters};
+    use sqlx::postgres::PgPoolOptions;
+    use std::collections::BTreeMap;
+    use uuid::Uuid;
+
+    /// Executor polling `rec_gov`, with a database that never answers (timeline events
+    /// and availability snapshots are logged and dropped)
+    fn executor(rec_gov: MockRecGov) -> ScanExecutor<MockRecGov> {
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(std::time::Duration::from_millis(50))
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap();
+        let config = AppConfig::default();
+
+        ScanExecutor::new(
+            rec_gov,
+            ReserveCaliforniaClient::new(&config, ApiCallLedger::new(pool.clone())),
+            ScanEventLog::new(pool.clone()),
+            AvailabilitySnapshots::new(pool.clone()),
+            CampsiteAttributeStore::new(pool),
+            ScanUpdateHub::new(),
+            ScanExecutorConfig::from_config(&config),
+        )
+    }
+
+    fn scan(check_in_date: NaiveDate, nights: i64) -> UserScan {
+        UserScan {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            campground_id: "232447".to_string(),
+            check_in_date,
+            check_out_date: check_in_date + Duration::days(nights),
+            nights: nights as i32,
+            status: "active".to_string(),
+            notification_sent: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            expires_at: None,
+            organization_id: None,
+            facility_type: "campsite".to_string(),
+            provider: "recreation_gov".to_string(),
+            flexibility_days: 0,
+            max_price: None,
+            site_filters: SiteFilters::default(),
+            quick_book: false,
+        }
+    }
+
+    fn site(name: &str, open_nights: &[NaiveDate]) -> CampsiteAvailability {
+        CampsiteAvailability {
+            campsite_id: format!("232447-{}", name),
+            site: name.to_string(),
+            loop_name: Some("Loop A".to_string()),
+            campsite_type: Some("STANDARD NONELECTRIC".to_string()),
+            availabilities: open_nights
+                .iter()
+                .map(|night| {
+                    (
+                        format!("{}T00:00:00Z", night.format("%Y-%m-%d")),
+                        "Available".to_string(),
+                    )
+                })
+                .collect(),
+            quantities: BTreeMap::new(),
+            type_of_use: Some("Overnight".to_string()),
+            prices: BTreeMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn polls_match_stays_and_build_alerts() {
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let nights = [check_in, check_in + Duration::days(1)];
+        let rec_gov = MockRecGov::new();
+        let executor = executor(rec_gov.clone());
+        let mut scan = scan(check_in, 2);
+
+        // A1 is open the whole stay, A2 only the first night
+        rec_gov.set_availability(
+            "232447",
+            vec![site("A1", &nights), site("A2", &nights[..1])],
+        );
+        let open = executor.open_sites(&scan).await.unwrap();
+        let alert = availability_alert(&scan, "Upper Pines", &open);
+        assert_eq!(alert.site_summary(), "A1 (Loop A)");
+        assert_eq!(alert.listed_sites()[0].open_nights, nights);
+        assert_eq!(alert.listed_sites()[0].booking_url, None);
+
+        // With quick-book links, the alert links to A1's page for the stay
+        scan.quick_book = true;
+        let alert = availability_alert(&scan, "Upper Pines", &open);
+        assert_eq!(
+            alert.listed_sites()[0].booking_url,
+            Some(format!(
+                "https://www.recreation.gov/camping/campsites/232447-A1?startDate={}&endDate={}",
+                check_in,
+                check_in + Duration::days(2)
+            ))
+        );
+
+        // Then A2 opens up for the second night too
+        rec_gov.set_availability("232447", vec![site("A1", &nights), site("A2", &nights)]);
+        let open = executor.open_sites(&scan).await.unwrap();
+        let alert = availability_alert(&scan, "Upper Pines", &open);
+        assert_eq!(alert.site_summary(), "A1 (Loop A), A2 (Loop A)");
+
+        assert_eq!(
+            rec_gov.calls()[0],
+            MockCall::Availability {
+                campground_id: "232447".to_string(),
+                start: check_in,
+                end: check_in + Duration::days(2),
+            }
+        );
+        assert_eq!(rec_gov.calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn batched_executors_check_scans_alike() {
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let rec_gov = MockRecGov::new();
+        let executor = executor(rec_gov.clone());
+        rec_gov.set_availability(
+            "232447",
+            vec![site("A1", &[check_in, check_in + Duration::days(1)])],
+        );
+
+        let batch = PollBatch {
+            recarea_id: Some("2991".to_string()),
+            campground_ids: vec!["232449".to_string(), "232447".to_string()],
+        };
+        let batched = executor.batched(&batch);
+
+        let poll = batched.check(&scan(check_in, 2)).await.unwrap();
+        assert_eq!(poll.open_sites.len(), 1);
+        assert_eq!(rec_gov.calls().len(), 1);
+    }
+
+    /// `site` with every one of its nights costing `price` dollars
+    fn priced(mut site: CampsiteAvailability, price: f64) -> CampsiteAvailability {
+        site.prices = site
+            .availabilities
+            .keys()
+            .map(|night| (night.clone(), price))
+            .collect();
+        site
+    }
+
+    #[tokio::test]
+    async fn max_price_skips_pricier_sites() {
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let nights = [check_in, check_in + Duration::days(1)];
+        let rec_gov = MockRecGov::new();
+        let executor = executor(rec_gov.clone());
+        let mut scan = scan(check_in, 2);
+        scan.max_price = Some(50);
+
+        // A group site over budget, a site within it, and one without a price
+        rec_gov.set_availability(
+            "232447",
+            vec![
+                priced(site("G1", &nights), 150.0),
+                priced(site("A1", &nights), 42.5),
+                site("A2", &nights),
+            ],
+        );
+        let open = executor.open_sites(&scan).await.unwrap();
+        let alert = availability_alert(&scan, "Upper Pines", &open);
+        assert_eq!(alert.site_summary(), "A1 (Loop A), A2 (Loop A)");
+        assert_eq!(alert.listed_sites()[0].nightly_price_cents, Some(4_250));
+        assert_eq!(alert.listed_sites()[1].nightly_price_cents, None);
+
+        scan.max_price = None;
+        let open = executor.open_sites(&scan).await.unwrap();
+        assert_eq!(open.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn site_filters_match_ridb_attributes() {
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let rec_gov = MockRecGov::new();
+        let executor = executor(rec_gov.clone());
+        let mut scan = scan(check_in, 1);
+        scan.site_filters = SiteFilters {
+            electric_hookup: true,
+            vehicle_length: Some(30),
+            ..SiteFilters::default()
+        };
+
+        // Of the fixture sites, only 001 and 004 have hookups and fit a 30ft trailer
+        rec_gov.set_availability(
+            "232447",
+            ["001", "002", "003", "004", "005"]
+                .into_iter()
+                .map(|name| site(name, &[check_in]))
+                .collect(),
+        );
+        let open = executor.open_sites(&scan).await.unwrap();
+        let alert = availability_alert(&scan, "Upper Pines", &open);
+        assert_eq!(alert.site_summary(), "001 (Loop A), 004 (Loop A)");
+        assert!(
+            rec_gov
+                .calls()
+                .contains(&MockCall::CampsiteAttributes("232447-004".to_string()))
+        );
+
+        scan.site_filters.pets_allowed = true;
+        scan.site_filters.sewer_hookup = true;
+        assert!(executor.open_sites(&scan).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn serves_fixtures_and_reports_upstream_errors() {
+        let rec_gov = MockRecGov::new();
+        let executor = executor(rec_gov.clone());
+        let scan = scan(Utc::now().date_naive() + Duration::days(30), 1);
+
+        // Every fourth night of each fixture site is open
+        let open = executor.open_sites(&scan).await.unwrap();
+        assert!(!open.is_empty());
+        assert!(
+            open.iter()
+                .all(|site| site.facility_type() == FacilityType::Campsite)
+        );
+
+        rec_gov.set_rate_limited(true);
+        assert!(matches!(
+            executor.open_sites(&scan).await,
+            Err(RecGovError::RateLimited)
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+use app_config::AppConfig;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use i18n::{Locale, Tz, user_timezone};
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// When scans expire and when their owners are warned
+#[derive(Debug, Clone, Copy)]
+pub struct ScanExpiryPolicy {
+    /// Days a scan runs before it expires unless extended; 0 lets it run until check-in
+    pub max_age_days: u32,
+    /// Days before expiry that the owner is emailed
+    pub warning_days: u32,
+}
+
+impl Default for ScanExpiryPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_days: 180,
+            warning_days: 3,
+        }
+    }
+}
+
+impl ScanExpiryPolicy {
+    /// Reads the expiry settings from the configuration.
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            max_age_days: config.scan_max_age_days,
+            warning_days: config.scan_expiry_warning_days,
+        }
+    }
+
+    /// When a scan for `check_in_date` started or extended at `from` expires: once the
+    /// check-in date has passed (midnight UTC after it), or after the maximum age,
+    /// whichever comes first.
+    pub fn expires_at(&self, check_in_date: NaiveDate, from: DateTime<Utc>) -> DateTime<Utc> {
+        let after_check_in = check_in_date
+            .succ_opt()
+            .unwrap_or(check_in_date)
+            .and_hms_opt(0, 0, 0)
+            .unwrap_or_default()
+            .and_utc();
+
+        if self.max_age_days == 0 {
+            return after_check_in;
+        }
+
+        after_check_in.min(from + Duration::days(self.max_age_days.into()))
+    }
+}
+
+/// Scans ended by an expiry pass
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExpiredScans {
+    /// Scans whose check-in date passed
+    pub completed: u64,
+    /// Scans that reached their maximum age before check-in
+    pub expired: u64,
+}
+
+/// A scan about to reach its maximum age, with what its owner needs to extend it
+#[derive(Debug, Clone)]
+pub struct ExpiringScan {
+    /// Scan ID
+    pub scan_id: Uuid,
+    /// Owner's user ID
+    pub user_id: Uuid,
+    /// Owner's email address
+    pub email: String,
+    /// Name of the scanned campground
+    pub campground_name: String,
+    /// Check-in date
+    pub check_in_date: NaiveDate,
+    /// Check-out date
+    pub check_out_date: NaiveDate,
+    /// When the scan expires
+    pub expires_at: DateTime<Utc>,
+    /// Language the owner gets notifications in
+    pub locale: Locale,
+    /// Time zone of the owner
+    pub timezone: Tz,
+}
+
+/// Ends scans past their expiry and finds scans to warn about
+pub struct ScanExpiryService {
+    pool: PgPool,
+    policy: ScanExpiryPolicy,
+}
+
+impl ScanExpiryService {
+    /// Creates a service applying the given policy
+    pub fn new(pool: PgPool, policy: ScanExpiryPolicy) -> Self {
+        Self { pool, policy }
+    }
+
+    /// Ends active and paused scans whose expiry passed: `completed` once the check-in
+    /// date has passed, `expired` otherwise. Their campgrounds' polling jobs lose them and
+    /// their timelines record it.
+    pub async fn expire_due(&self) -> Result<ExpiredScans, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            WITH ended AS (
+                UPDATE user_scans
+                SET status = CASE WHEN check_in_date < CURRENT_DATE THEN 'completed' ELSE 'expired' END,
+                    updated_at = NOW()
+                WHERE status IN ('active', 'paused') AND expires_at <= NOW() AND deleted_at IS NULL
+                RETURNING id, campground_id, status
+            ),
+            events AS (
+                INSERT INTO scan_events (user_scan_id, event_type, details)
+                SELECT id, 'expired', jsonb_build_object('status', status)
+                FROM ended
+            ),
+            per_campground AS (
+                SELECT campground_id, COUNT(*) AS scans FROM ended GROUP BY campground_id
+            ),
+            jobs AS (
+                UPDATE polling_jobs pj
+                SET active_scan_count = GREATEST(pj.active_scan_count - pc.scans, 0),
+                    updated_at = NOW()
+                FROM per_campground pc
+                WHERE pj.campground_id = pc.campground_id
+            )
+            SELECT
+                COUNT(*) FILTER (WHERE status = 'completed') AS completed,
+                COUNT(*) FILTER (WHERE status = 'expired') AS expired
+            FROM ended
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(ExpiredScans {
+            completed: row.get::<i64, _>("completed") as u64,
+            expired: row.get::<i64, _>("expired") as u64,
+        })
+    }
+
+    /// Active scans reaching their maximum age within the warning window whose owners
+    /// haven't been warned. Scans ending at check-in aren't included, since extending
+    /// them changes nothing, nor are owners who unsubscribed from email alerts or from
+    /// the scan.
+    pub async fn expiring_soon(&self) -> Result<Vec<ExpiringScan>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT us.id, us.user_id, u.email, u.locale, u.timezone, COALESCE(c.name, us.campground_id) AS campground_name,
+                   us.check_in_date, us.check_out_date, us.expires_at
+            FROM user_scans us
+            JOIN users u ON u.id = us.user_id
+            LEFT JOIN campgrounds c ON c.id = us.campground_id
+            WHERE us.status = 'active'
+              AND us.deleted_at IS NULL
+              AND NOT us.expiry_warning_sent
+              AND us.expires_at > NOW()
+              AND us.expires_at <= NOW() + make_interval(days => $1)
+              AND us.expires_at < (us.check_in_date::TIMESTAMP AT TIME ZONE 'UTC')
+              AND u.is_active = true
+              AND COALESCE(u.notification_preferences->>'email', 'true') <> 'false'
+              AND NOT EXISTS (
+                SELECT 1 FROM scan_notification_opt_outs o
+                WHERE o.user_id = us.user_id AND o.scan_id = us.id
+              )
+            ORDER BY us.expires_at
+            "#,
+        )
+        .bind(self.policy.warning_days as i32)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ExpiringScan {
+                scan_id: row.get("id"),
+                user_id: row.get("user_id"),
+                email: row.get("email"),
+                campground_name: row.get("campground_name"),
+                check_in_date: row.get("check_in_date"),
+                check_out_date: row.get("check_out_date"),
+                expires_at: row.get("expires_at"),
+                locale: row.get::<String, _>("locale").parse().unwrap_or_default(),
+                timezone: user_timezone(row.get::<Option<String>, _>("timezone").as_deref()),
+            })
+            .collect())
+    }
+
+    /// Records that a scan's owner was warned about its expiry
+    pub async fn mark_warned(&self, scan_id: &Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE user_scans SET expiry_warning_sent = TRUE WHERE id = $1")
+            .bind(scan_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
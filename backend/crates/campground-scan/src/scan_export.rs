@@ -0,0 +1,384 @@
+use actix_web::web::Bytes;
+use chrono::{DateTime, NaiveDate, Utc};
+use futures_util::Stream;
+use futures_util::stream;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::scan_service::ScanService;
+use crate::scan_types::{ScanError, UserScanWithCampground};
+
+/// Rows fetched per query while streaming an export
+const EXPORT_PAGE_SIZE: i64 = 500;
+
+/// Longest date range of one availability export
+pub const MAX_AVAILABILITY_EXPORT_DAYS: i64 = 366;
+
+/// File format of an export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    #[default]
+    Csv,
+    /// A JSON array of objects
+    Json,
+}
+
+impl ExportFormat {
+    /// Content type of the response
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/json",
+        }
+    }
+
+    /// File name extension
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Query parameters of the scan export
+#[derive(Debug, Deserialize)]
+pub struct ScanExportQuery {
+    /// `csv` (default) or `json`
+    pub format: Option<ExportFormat>,
+}
+
+/// Query parameters of the admin availability export
+#[derive(Debug, Deserialize)]
+pub struct AvailabilityExportQuery {
+    /// First date exported
+    pub start_date: NaiveDate,
+    /// Last date exported
+    pub end_date: NaiveDate,
+    /// Only export this campground
+    pub campground_id: Option<String>,
+    /// `csv` (default) or `json`
+    pub format: Option<ExportFormat>,
+}
+
+impl AvailabilityExportQuery {
+    /// Checks the date range is in order and at most [`MAX_AVAILABILITY_EXPORT_DAYS`] long
+    pub fn validate(&self) -> Result<(), ScanError> {
+        if self.end_date < self.start_date {
+            return Err(ScanError::Validation(
+                "end_date must not be before start_date".to_string(),
+            ));
+        }
+        if (self.end_date - self.start_date).num_days() >= MAX_AVAILABILITY_EXPORT_DAYS {
+            return Err(ScanError::Validation(format!(
+                "Exports cover at most {} days",
+                MAX_AVAILABILITY_EXPORT_DAYS
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Cached availability of a campground on one date, as exported
+#[derive(Debug, Clone, Serialize)]
+pub struct AvailabilityExportRow {
+    /// ID of the campground
+    pub campground_id: String,
+    /// Name of the campground
+    pub campground_name: Option<String>,
+    /// Date the availability is for
+    pub date: NaiveDate,
+    /// Sites open that night
+    pub available_sites: Option<i32>,
+    /// Sites at the campground
+    pub total_sites: Option<i32>,
+    /// Cheapest nightly price reported for a site that night, in dollars
+    pub lowest_price: Option<f64>,
+    /// When the date was last checked
+    pub last_checked: Option<DateTime<Utc>>,
+    /// Outcome of the last check (`success`, `error`, `rate_limited`)
+    pub check_status: Option<String>,
+    /// Error of the last check, if it failed
+    pub error_message: Option<String>,
+}
+
+/// Row of an export, written as a CSV record or a JSON object
+trait ExportRow: Serialize {
+    /// Header row of the CSV file
+    const COLUMNS: &'static [&'static str];
+
+    /// Where the next page starts after this row
+    type Cursor: Clone + Send + 'static;
+
+    /// Fields of the CSV record, in the order of [`Self::COLUMNS`]
+    fn csv_fields(&self) -> Vec<String>;
+
+    /// Position of this row, where the next page starts
+    fn cursor(&self) -> Self::Cursor;
+}
+
+impl ExportRow for UserScanWithCampground {
+    const COLUMNS: &'static [&'static str] = &[
+        "id",
+        "campground_id",
+        "campground_name",
+        "check_in_date",
+        "check_out_date",
+        "nights",
+        "status",
+        "facility_type",
+        "provider",
+        "flexibility_days",
+        "max_price",
+        "site_filters",
+        "quick_book",
+        "notification_sent",
+        "organization_id",
+        "created_at",
+        "expires_at",
+    ];
+
+    type Cursor = (DateTime<Utc>, Uuid);
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.campground_id.clone(),
+            self.campground_name.clone(),
+            self.check_in_date.to_string(),
+            self.check_out_date.to_string(),
+            self.nights.to_string(),
+            self.status.clone(),
+            self.facility_type.clone(),
+            self.provider.clone(),
+            self.flexibility_days.to_string(),
+            optional(self.max_price),
+            serde_json::to_string(&self.site_filters).unwrap_or_default(),
+            self.quick_book.to_string(),
+            self.notification_sent.to_string(),
+            optional(self.organization_id),
+            self.created_at.to_rfc3339(),
+            optional(self.expires_at.map(|expires_at| expires_at.to_rfc3339())),
+        ]
+    }
+
+    fn cursor(&self) -> Self::Cursor {
+        (self.created_at, self.id)
+    }
+}
+
+impl ExportRow for AvailabilityExportRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "campground_id",
+        "campground_name",
+        "date",
+        "available_sites",
+        "total_sites",
+        "lowest_price",
+        "last_checked",
+        "check_status",
+        "error_message",
+    ];
+
+    type Cursor = (String, NaiveDate);
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.campground_id.clone(),
+            optional(self.campground_name.clone()),
+            self.date.to_string(),
+            optional(self.available_sites),
+            optional(self.total_sites),
+            optional(self.lowest_price),
+            optional(self.last_checked.map(|checked| checked.to_rfc3339())),
+            optional(self.check_status.clone()),
+            optional(self.error_message.clone()),
+        ]
+    }
+
+    fn cursor(&self) -> Self::Cursor {
+        (self.campground_id.clone(), self.date)
+    }
+}
+
+fn optional(value: Option<impl ToString>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Streams the user's scans, including those shared with their organizations, in
+/// creation order
+pub fn scan_export(
+    pool: PgPool,
+    user_id: Uuid,
+    format: ExportFormat,
+) -> impl Stream<Item = Result<Bytes, ScanError>> + 'static {
+    export_stream(format, move |after| {
+        let pool = pool.clone();
+        async move {
+            ScanService::new(pool)
+                .user_scans_page(&user_id, after, EXPORT_PAGE_SIZE)
+                .await
+        }
+    })
+}
+
+/// Streams the cached availability of every campground (or one) over a date range,
+/// by campground and date. Per-site availability data isn't included.
+pub fn availability_export(
+    pool: PgPool,
+    query: AvailabilityExportQuery,
+) -> impl Stream<Item = Result<Bytes, ScanError>> + 'static {
+    let format = query.format.unwrap_or_default();
+
+    export_stream(format, move |after: Option<(String, NaiveDate)>| {
+        let pool = pool.clone();
+        let campground_id = query.campground_id.clone();
+        let (start_date, end_date) = (query.start_date, query.end_date);
+        async move {
+            let (after_campground, after_date) = after.unzip();
+            let rows = sqlx::query(
+                r#"
+                SELECT ca.campground_id, c.name AS campground_name, ca.date,
+                       ca.available_sites, ca.total_sites, ca.last_checked,
+                       ca.check_status, ca.error_message,
+                       (SELECT MIN(price::FLOAT8) FROM jsonb_each_text(ca.site_prices) AS p(site, price))
+                           AS lowest_price
+                FROM campground_availability ca
+                LEFT JOIN campgrounds c ON c.id = ca.campground_id
+                WHERE ca.date BETWEEN $1 AND $2
+                  AND ($3::text IS NULL OR ca.campground_id = $3)
+                  AND ($4::text IS NULL OR (ca.campground_id, ca.date) > ($4, $5))
+                ORDER BY ca.campground_id, ca.date
+                LIMIT $6
+                "#,
+            )
+            .bind(start_date)
+            .bind(end_date)
+            .bind(campground_id)
+            .bind(after_campground)
+            .bind(after_date)
+            .bind(EXPORT_PAGE_SIZE)
+            .fetch_all(&pool)
+            .await?;
+
+            Ok(rows
+                .iter()
+                .map(|row| AvailabilityExportRow {
+                    campground_id: row.get("campground_id"),
+                    campground_name: row.get("campground_name"),
+                    date: row.get("date"),
+                    available_sites: row.get("available_sites"),
+                    total_sites: row.get("total_sites"),
+                    lowest_price: row.get("lowest_price"),
+                    last_checked: row.get("last_checked"),
+                    check_status: row.get("check_status"),
+                    error_message: row.get("error_message"),
+                })
+                .collect())
+        }
+    })
+}
+
+/// Where an export stream is
+enum ExportState<C> {
+    Start,
+    After(C),
+    Done,
+}
+
+/// Streams rows a page at a time, so only one page is ever held in memory. Each page
+/// becomes one chunk of the body; the CSV header or JSON brackets are written around
+/// them. A failed query ends the stream, cutting the response short.
+fn export_stream<R, F, Fut>(
+    format: ExportFormat,
+    fetch_page: F,
+) -> impl Stream<Item = Result<Bytes, ScanError>> + 'static
+where
+    R: ExportRow,
+    F: Fn(Option<R::Cursor>) -> Fut + 'static,
+    Fut: Future<Output = Result<Vec<R>, ScanError>> + 'static,
+{
+    stream::unfold(
+        (ExportState::Start, fetch_page),
+        move |(state, fetch_page)| async move {
+            let (first, after) = match state {
+                ExportState::Start => (true, None),
+                ExportState::After(cursor) => (false, Some(cursor)),
+                ExportState::Done => return None,
+            };
+
+            let rows = match fetch_page(after).await {
+                Ok(rows) => rows,
+                Err(e) => {
+                    log::error!("❌ Export failed partway: {}", e);
+                    return Some((Err(e), (ExportState::Done, fetch_page)));
+                }
+            };
+            let last_page = rows.len() < EXPORT_PAGE_SIZE as usize;
+
+            let mut chunk = String::new();
+            match format {
+                ExportFormat::Csv => {
+                    if first {
+                        chunk.push_str(&csv_record(R::COLUMNS.iter().map(|c| c.to_string())));
+                    }
+                    for row in &rows {
+                        chunk.push_str(&csv_record(row.csv_fields()));
+                    }
+                }
+                ExportFormat::Json => {
+                    if first {
+                        chunk.push('[');
+                    }
+                    for (i, row) in rows.iter().enumerate() {
+                        if !first || i > 0 {
+                            chunk.push(',');
+                        }
+                        match serde_json::to_string(row) {
+                            Ok(json) => chunk.push_str(&json),
+                            Err(e) => log::error!("❌ Failed to serialize export row: {}", e),
+                        }
+                    }
+                    if last_page {
+                        chunk.push(']');
+                    }
+                }
+            }
+
+            let next = match rows.last() {
+                Some(row) if !last_page => ExportState::After(row.cursor()),
+                _ => ExportState::Done,
+            };
+            Some((Ok(Bytes::from(chunk)), (next, fetch_page)))
+        },
+    )
+}
+
+/// One CSV line (RFC 4180). Fields with commas, quotes or line breaks are quoted, and
+/// fields a spreadsheet would read as a formula are prefixed with `'`.
+fn csv_record(fields: impl IntoIterator<Item = String>) -> String {
+    let mut line = fields
+        .into_iter()
+        .map(|field| {
+            let field = if field.starts_with(['=', '+', '@', '\t', '\r'])
+                || (field.starts_with('-') && field.parse::<f64>().is_err())
+            {
+                format!("'{}", field)
+            } else {
+                field
+            };
+
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
@@ -0,0 +1,158 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use i18n::{Locale, Tz, user_timezone};
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// Hours a campground's polls must keep failing before its scans count as blocked and
+/// their owners are told
+pub const BLOCKED_AFTER_HOURS: i64 = 24;
+
+/// Whether a scan's campground can be checked, from its polling job's error streak
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanHealth {
+    /// The last poll succeeded (or the campground hasn't been polled yet)
+    Healthy,
+    /// Recent polls failed, for less than [`BLOCKED_AFTER_HOURS`]
+    Degraded,
+    /// Polls have failed for [`BLOCKED_AFTER_HOURS`] or more, e.g. the facility is
+    /// closed for the season
+    Blocked,
+}
+
+impl ScanHealth {
+    /// Health of a campground with `consecutive_errors` failed polls in a row, failing
+    /// since `failing_since`, as of `now`
+    pub fn of(
+        consecutive_errors: i32,
+        failing_since: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        if consecutive_errors <= 0 {
+            return ScanHealth::Healthy;
+        }
+
+        match failing_since {
+            Some(since) if now - since >= Duration::hours(BLOCKED_AFTER_HOURS) => {
+                ScanHealth::Blocked
+            }
+            _ => ScanHealth::Degraded,
+        }
+    }
+}
+
+/// An active scan whose campground has been failing long enough to tell its owner
+#[derive(Debug, Clone)]
+pub struct BlockedScan {
+    /// Scan ID
+    pub scan_id: Uuid,
+    /// Owner's user ID
+    pub user_id: Uuid,
+    /// Owner's email address
+    pub email: String,
+    /// Name of the scanned campground
+    pub campground_name: String,
+    /// Check-in date
+    pub check_in_date: NaiveDate,
+    /// Check-out date
+    pub check_out_date: NaiveDate,
+    /// When the campground's polls started failing
+    pub failing_since: DateTime<Utc>,
+    /// Language the owner gets notifications in
+    pub locale: Locale,
+    /// Time zone of the owner
+    pub timezone: Tz,
+}
+
+/// Finds scans whose campground can't be checked, so their owners hear about it instead
+/// of wondering why no alert comes
+pub struct ScanHealthService {
+    pool: PgPool,
+}
+
+impl ScanHealthService {
+    /// Creates the service backed by the given pool
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Active scans of owners reachable by email whose campground has been failing for
+    /// [`BLOCKED_AFTER_HOURS`] or more and who haven't been told during this streak
+    pub async fn newly_blocked(&self) -> Result<Vec<BlockedScan>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT us.id, us.user_id, u.email, u.locale, u.timezone,
+                   COALESCE(c.name, us.campground_id) AS campground_name,
+                   us.check_in_date, us.check_out_date, pj.failing_since
+            FROM user_scans us
+            JOIN users u ON u.id = us.user_id
+            JOIN polling_jobs pj ON pj.campground_id = us.campground_id
+            LEFT JOIN campgrounds c ON c.id = us.campground_id
+            WHERE us.status = 'active'
+              AND us.deleted_at IS NULL
+              AND COALESCE(pj.consecutive_errors, 0) > 0
+              AND pj.failing_since <= NOW() - make_interval(hours => $1)
+              AND (us.blocked_notice_sent_at IS NULL
+                   OR us.blocked_notice_sent_at < pj.failing_since)
+              AND u.is_active = true
+              AND COALESCE(u.notification_preferences->>'email', 'true') <> 'false'
+              AND NOT EXISTS (
+                SELECT 1 FROM scan_notification_opt_outs o
+                WHERE o.user_id = us.user_id AND o.scan_id = us.id
+              )
+            ORDER BY pj.failing_since, us.id
+            "#,
+        )
+        .bind(BLOCKED_AFTER_HOURS as i32)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| BlockedScan {
+                scan_id: row.get("id"),
+                user_id: row.get("user_id"),
+                email: row.get("email"),
+                campground_name: row.get("campground_name"),
+                check_in_date: row.get("check_in_date"),
+                check_out_date: row.get("check_out_date"),
+                failing_since: row.get("failing_since"),
+                locale: row.get::<String, _>("locale").parse().unwrap_or_default(),
+                timezone: user_timezone(row.get::<Option<String>, _>("timezone").as_deref()),
+            })
+            .collect())
+    }
+
+    /// Records that the scan's owner was told its campground can't be checked
+    pub async fn mark_notified(&self, scan_id: &Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE user_scans SET blocked_notice_sent_at = NOW() WHERE id = $1")
+            .bind(scan_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn health_follows_the_error_streak() {
+        let now = Utc::now();
+
+        assert_eq!(ScanHealth::of(0, None, now), ScanHealth::Healthy);
+        assert_eq!(
+            ScanHealth::of(3, Some(now - Duration::hours(2)), now),
+            ScanHealth::Degraded
+        );
+        assert_eq!(
+            ScanHealth::of(40, Some(now - Duration::hours(BLOCKED_AFTER_HOURS)), now),
+            ScanHealth::Blocked
+        );
+        // Streaks recorded before failures were timed aren't known to be long
+        assert_eq!(ScanHealth::of(5, None, now), ScanHealth::Degraded);
+    }
+}
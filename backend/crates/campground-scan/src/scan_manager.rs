@@ -0,0 +1,889 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use i18n::{Locale, Tz, user_timezone};
+use notification_services::AvailabilityAlert;
+use rec_gov::{CampsiteAvailability, Geocoder, RecGovApi, RecGovClient, RecGovError};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Row};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinError;
+use uuid::Uuid;
+
+use crate::availability_diff::{
+    AvailableSites, available_sites, candidate_stays, find_new_availability,
+};
+use crate::booking_analytics::{BookingAnalytics, ChangeReason, alert_reason};
+use crate::poll_batching::coalesce_by_recarea;
+use crate::polling_jobs::{ClaimedPollingJob, PollingJobQueue};
+use crate::scan_events::{ScanEventKind, ScanEventLog};
+use crate::scan_executor::ScanExecutor;
+use crate::scan_notifications::{AvailabilityNotifier, availability_alert};
+use crate::scan_recipients::ScanRecipientService;
+use crate::scan_service::ScanService;
+use crate::scan_types::UserScan;
+
+/// Most polling jobs one round claims; due jobs beyond that wait for the next tick
+pub const MAX_JOBS_PER_ROUND: i64 = 20;
+
+/// Polls in a row that panic before a campground is quarantined
+pub const QUARANTINE_AFTER_PANICS: u32 = 3;
+
+/// How long a quarantined campground goes unpolled: a day
+pub const PANIC_QUARANTINE_MINUTES: i64 = 24 * 60;
+
+/// What polling a set of claimed campgrounds did
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScanRound {
+    /// Campgrounds polled
+    pub campgrounds: usize,
+    /// Active scans checked
+    pub scans_checked: usize,
+    /// Campgrounds whose poll failed
+    pub failed: usize,
+    /// Campgrounds handed back unpolled because the hourly call budget ran out
+    pub deferred: usize,
+    /// Campgrounds whose poll panicked, handed back or quarantined
+    pub panicked: usize,
+    /// Alerts delivered to owners, organization members, and invited recipients
+    pub alerts_sent: usize,
+}
+
+/// What this instance's scan manager has been doing, for admins
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanManagerStatus {
+    /// ID this instance claims polling jobs under
+    pub instance_id: String,
+    /// Whether the manager is polling; `false` once it stopped for shutdown
+    pub running: bool,
+    /// Rounds that polled at least one campground since the instance started
+    pub rounds: u64,
+    /// Campground polls that panicked since the instance started
+    pub panics: u64,
+    /// When the latest of those rounds finished
+    pub last_round_at: Option<DateTime<Utc>>,
+    /// What the latest of those rounds did
+    pub last_round: Option<ScanRound>,
+}
+
+/// Who an active scan being polled belongs to, for its alerts
+struct ScanContext {
+    campground_name: String,
+    /// Latitude and longitude of the campground, when known
+    campground_coordinates: Option<(f64, f64)>,
+    owner_locale: Locale,
+    owner_timezone: Tz,
+}
+
+/// What checking one campground's active scans did
+#[derive(Default)]
+struct CampgroundPoll {
+    scans_checked: usize,
+    alerts_sent: usize,
+    /// First failure, if any
+    failure: Option<RecGovError>,
+    /// Whether the hourly call budget ran out
+    deferred: bool,
+}
+
+/// Sites each scan's latest poll found bookable, so a site is alerted about once while
+/// it stays open rather than on every poll. Kept in memory: after a restart, or once
+/// another instance claims the campground, sites still open are alerted about once more.
+#[derive(Debug, Default)]
+struct LastPolls {
+    /// Campground and bookable sites of each scan's latest successful poll
+    scans: HashMap<Uuid, (String, AvailableSites)>,
+}
+
+impl LastPolls {
+    /// The sites of the scan's poll that weren't bookable for it in its previous poll,
+    /// remembering this poll for the next
+    fn newly_open(
+        &mut self,
+        scan: &UserScan,
+        open: Vec<CampsiteAvailability>,
+        today: NaiveDate,
+    ) -> Vec<CampsiteAvailability> {
+        let stays = candidate_stays(
+            scan.check_in_date,
+            scan.check_out_date,
+            scan.flexibility_days,
+            today,
+        );
+        let current = available_sites(&open, scan.max_price.map(f64::from));
+        let previous = self
+            .scans
+            .insert(scan.id, (scan.campground_id.clone(), current.clone()))
+            .map(|(_, previous)| previous)
+            .unwrap_or_default();
+        let new = find_new_availability(&stays, &previous, &current);
+
+        open.into_iter()
+            .filter(|site| new.contains(&site.campsite_id))
+            .collect()
+    }
+
+    /// Forgets the scans of the polled campgrounds that are no longer active
+    fn forget_inactive(&mut self, campground_ids: &[String], active: &[UserScan]) {
+        let active: HashSet<Uuid> = active.iter().map(|scan| scan.id).collect();
+        self.scans.retain(|scan_id, (campground_id, _)| {
+            active.contains(scan_id) || !campground_ids.contains(campground_id)
+        });
+    }
+}
+
+/// Polls the campgrounds this instance claims: checks their active scans through the
+/// [`ScanExecutor`], alerts everyone who should hear about open sites, and records each
+/// poll on the campground's polling job so the next one is scheduled, and in the booking
+/// analytics night by night. A scan is alerted about a site when it opens up, not again
+/// while it stays open. Each campground is polled in its own task, so a panic loses only
+/// that campground's poll; one that keeps panicking is quarantined. Each instance runs
+/// one; polling job claims keep instances from polling the same campground. Clones share
+/// the status, the latest polls, and the panic counts.
+#[derive(Clone)]
+pub struct ScanManager<R: RecGovApi = RecGovClient> {
+    pool: PgPool,
+    queue: PollingJobQueue,
+    executor: ScanExecutor<R>,
+    notifier: AvailabilityNotifier,
+    geocoder: Geocoder,
+    scans: ScanService,
+    recipients: ScanRecipientService,
+    events: ScanEventLog,
+    analytics: BookingAnalytics,
+    status: Arc<Mutex<ScanManagerStatus>>,
+    last_polls: Arc<Mutex<LastPolls>>,
+    /// Panics in a row of the campgrounds whose latest poll panicked
+    panics: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+impl<R: RecGovApi> ScanManager<R> {
+    /// Creates a manager claiming jobs from `queue`, checking scans with `executor`,
+    /// looking up who to alert through `scans` and alerting them through `notifier`, with
+    /// the campground's location described by `geocoder`
+    pub fn new(
+        pool: PgPool,
+        queue: PollingJobQueue,
+        executor: ScanExecutor<R>,
+        scans: ScanService,
+        notifier: AvailabilityNotifier,
+        geocoder: Geocoder,
+    ) -> Self {
+        let status = ScanManagerStatus {
+            instance_id: queue.instance_id().to_string(),
+            running: true,
+            rounds: 0,
+            panics: 0,
+            last_round_at: None,
+            last_round: None,
+        };
+
+        Self {
+            recipients: ScanRecipientService::new(pool.clone()),
+            events: ScanEventLog::new(pool.clone()),
+            analytics: BookingAnalytics::new(pool.clone()),
+            pool,
+            queue,
+            executor,
+            scans,
+            notifier,
+            geocoder,
+            status: Arc::new(Mutex::new(status)),
+            last_polls: Arc::new(Mutex::new(LastPolls::default())),
+            panics: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// What the manager has been doing
+    pub fn status(&self) -> ScanManagerStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Claims up to [`MAX_JOBS_PER_ROUND`] due campgrounds and polls them. Does nothing
+    /// once the manager has stopped.
+    pub async fn run_round(&self) -> Result<ScanRound, sqlx::Error> {
+        if !self.status().running {
+            return Ok(ScanRound::default());
+        }
+
+        let jobs = self.queue.get_jobs_needing_poll(MAX_JOBS_PER_ROUND).await?;
+        if jobs.is_empty() {
+            return Ok(ScanRound::default());
+        }
+
+        let round = self.poll(&jobs).await?;
+
+        let mut status = self.status.lock().unwrap();
+        status.rounds += 1;
+        status.last_round_at = Some(Utc::now());
+        status.last_round = Some(round.clone());
+
+        Ok(round)
+    }
+
+    /// Polls one campground right away, due or not, for an admin. Returns `None` when it
+    /// has no active scans or another instance is polling it.
+    pub async fn force_scan(&self, campground_id: &str) -> Result<Option<ScanRound>, sqlx::Error> {
+        let Some(job) = self.queue.claim(campground_id).await? else {
+            return Ok(None);
+        };
+
+        self.poll(std::slice::from_ref(&job)).await.map(Some)
+    }
+
+    /// Stops polling and releases the claims this instance still holds, so other
+    /// instances pick the campgrounds up right away. Run at shutdown once the polling
+    /// worker has stopped; returns how many claims were released.
+    pub async fn stop(&self) -> Result<u64, sqlx::Error> {
+        self.status.lock().unwrap().running = false;
+        self.queue.release_claims().await
+    }
+
+    /// Checks the active scans of claimed campgrounds a recreation area batch at a time,
+    /// alerts about sites that opened up since each scan's previous poll, and finishes
+    /// each job with its first failure, if any. Once the hourly call budget runs out, the
+    /// campgrounds left are released unpolled; a campground whose poll panicked is
+    /// released or quarantined (see [`contain_panic`](Self::contain_panic)).
+    async fn poll(&self, jobs: &[ClaimedPollingJob]) -> Result<ScanRound, sqlx::Error> {
+        let campground_ids: Vec<String> =
+            jobs.iter().map(|job| job.campground_id.clone()).collect();
+        let (scans, mut contexts) = self.active_scans(&campground_ids).await?;
+        self.last_polls
+            .lock()
+            .unwrap()
+            .forget_inactive(&campground_ids, &scans);
+        let today = Utc::now().date_naive();
+
+        let mut scans_by_campground: HashMap<String, Vec<UserScan>> = HashMap::new();
+        for scan in scans {
+            scans_by_campground
+                .entry(scan.campground_id.clone())
+                .or_default()
+                .push(scan);
+        }
+
+        let mut round = ScanRound::default();
+        let mut failures: HashMap<String, RecGovError> = HashMap::new();
+        let mut deferred: HashSet<String> = HashSet::new();
+        let mut panicked: HashSet<String> = HashSet::new();
+
+        for batch in coalesce_by_recarea(jobs) {
+            if !deferred.is_empty() {
+                deferred.extend(batch.campground_ids);
+                continue;
+            }
+
+            let executor = self.executor.batched(&batch);
+            for campground_id in &batch.campground_ids {
+                let campground_scans = scans_by_campground
+                    .remove(campground_id)
+                    .unwrap_or_default();
+                let campground_contexts = campground_scans
+                    .iter()
+                    .filter_map(|scan| Some((scan.id, contexts.remove(&scan.id)?)))
+                    .collect();
+
+                let checked = tokio::spawn(self.clone().poll_campground(
+                    executor.clone(),
+                    campground_id.clone(),
+                    campground_scans,
+                    campground_contexts,
+                    today,
+                ))
+                .await;
+
+                let campground = match checked {
+                    Ok(campground) => campground,
+                    Err(e) => {
+                        self.contain_panic(campground_id, e).await;
+                        panicked.insert(campground_id.clone());
+                        continue;
+                    }
+                };
+
+                round.scans_checked += campground.scans_checked;
+                round.alerts_sent += campground.alerts_sent;
+                if campground.deferred {
+                    deferred.insert(campground_id.clone());
+                }
+                if let Some(failure) = campground.failure {
+                    failures.insert(campground_id.clone(), failure);
+                }
+            }
+        }
+
+        self.panics.lock().unwrap().retain(|campground_id, _| {
+            !campground_ids.contains(campground_id) || panicked.contains(campground_id)
+        });
+
+        for campground_id in campground_ids
+            .iter()
+            .filter(|campground_id| !panicked.contains(*campground_id))
+        {
+            let finished = if deferred.contains(campground_id) {
+                self.queue.release(campground_id).await
+            } else {
+                self.queue
+                    .finish(campground_id, failures.get(campground_id))
+                    .await
+            };
+
+            // Keep handing back the other claims, so they aren't stuck until their lease
+            // expires
+            match finished {
+                Ok(true) => {}
+                Ok(false) => log::warn!(
+                    "⚠️ Claim of campground {} lapsed before its poll was recorded",
+                    campground_id
+                ),
+                Err(e) => log::error!(
+                    "❌ Failed to record the poll of campground {}: {}",
+                    campground_id,
+                    e
+                ),
+            }
+        }
+
+        round.campgrounds = campground_ids.len() - deferred.len() - panicked.len();
+        round.failed = failures
+            .keys()
+            .filter(|campground_id| !deferred.contains(*campground_id))
+            .count();
+        round.deferred = deferred.len();
+        round.panicked = panicked.len();
+
+        Ok(round)
+    }
+
+    /// Checks one campground's active scans with `executor`, alerting about the sites
+    /// that opened up since each scan's previous poll. Runs as a task of its own.
+    async fn poll_campground(
+        self,
+        executor: ScanExecutor<R>,
+        campground_id: String,
+        scans: Vec<UserScan>,
+        contexts: HashMap<Uuid, ScanContext>,
+        today: NaiveDate,
+    ) -> CampgroundPoll {
+        let mut campground = CampgroundPoll::default();
+        let mut available = AvailableSites::new();
+        let mut opened = Vec::new();
+
+        for scan in &scans {
+            match executor.check(scan).await {
+                Ok(poll) => {
+                    campground.scans_checked += 1;
+                    for (night, sites) in poll.available {
+                        available.entry(night).or_default().extend(sites);
+                    }
+                    let sites =
+                        self.last_polls
+                            .lock()
+                            .unwrap()
+                            .newly_open(scan, poll.open_sites, today);
+                    if !sites.is_empty() {
+                        opened.push((scan, sites));
+                    }
+                }
+                Err(RecGovError::RateLimited) => campground.deferred = true,
+                Err(e) => {
+                    campground.scans_checked += 1;
+                    campground.failure.get_or_insert(e);
+                }
+            }
+        }
+
+        // Classify the openings first, so alerts can say whether they're cancellations
+        let reasons = self.record_availability(&campground_id, available).await;
+        let mut location = None;
+        for (scan, sites) in opened {
+            let Some(context) = contexts.get(&scan.id) else {
+                continue;
+            };
+            if location.is_none()
+                && let Some((latitude, longitude)) = context.campground_coordinates
+            {
+                location = Some(self.geocoder.describe_location(latitude, longitude).await);
+            }
+
+            let mut alert = availability_alert(scan, &context.campground_name, &sites);
+            if let Some(reason) = alert_reason(&reasons, &sites) {
+                alert = alert.with_reason(reason.message_id());
+            }
+            if let Some(Some(location)) = &location {
+                alert = alert.with_location(location);
+            }
+            campground.alerts_sent += self.alert(scan, context, &alert).await;
+        }
+
+        campground
+    }
+
+    /// Hands back the claim of a campground whose poll panicked, so it's retried on the
+    /// next tick. After [`QUARANTINE_AFTER_PANICS`] panics in a row it's quarantined for
+    /// [`PANIC_QUARANTINE_MINUTES`] instead, so it can't keep failing polls; admins can
+    /// enable it again once it's fixed. Database errors are logged rather than returned,
+    /// so the rest of the round's claims are still handed back.
+    async fn contain_panic(&self, campground_id: &str, error: JoinError) {
+        let panics = {
+            let mut panics = self.panics.lock().unwrap();
+            let in_row = panics.entry(campground_id.to_string()).or_default();
+            *in_row += 1;
+            *in_row
+        };
+        self.status.lock().unwrap().panics += 1;
+        log::error!(
+            "💥 Poll of campground {} panicked ({} in a row): {}",
+            campground_id,
+            panics,
+            error
+        );
+
+        if panics < QUARANTINE_AFTER_PANICS {
+            if let Err(e) = self.queue.release(campground_id).await {
+                log::error!(
+                    "❌ Failed to release campground {} after its poll panicked: {}",
+                    campground_id,
+                    e
+                );
+            }
+            return;
+        }
+
+        self.panics.lock().unwrap().remove(campground_id);
+        match self
+            .queue
+            .quarantine(campground_id, PANIC_QUARANTINE_MINUTES)
+            .await
+        {
+            Ok(Some(until)) => log::error!(
+                "🚧 Quarantined campground {} until {} after {} panicked polls in a row",
+                campground_id,
+                until,
+                panics
+            ),
+            Ok(None) => {}
+            Err(e) => log::error!(
+                "❌ Failed to quarantine campground {} after {} panicked polls in a row: {}",
+                campground_id,
+                panics,
+                e
+            ),
+        }
+    }
+
+    /// Records a campground's polled nights in the booking analytics, once per night
+    /// however many scans covered it, and returns why sites likely opened up on each
+    /// night. Failures are logged, since a poll shouldn't fail over its bookkeeping.
+    async fn record_availability(
+        &self,
+        campground_id: &str,
+        available: AvailableSites,
+    ) -> BTreeMap<NaiveDate, ChangeReason> {
+        let mut reasons = BTreeMap::new();
+
+        for (night, sites) in available {
+            let site_ids: Vec<String> = sites.into_iter().collect();
+            match self
+                .analytics
+                .record_poll(campground_id, night, &site_ids)
+                .await
+            {
+                Ok(Some(reason)) => {
+                    reasons.insert(night, reason);
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!(
+                    "⚠️ Failed to record availability of campground {} on {}: {}",
+                    campground_id,
+                    night,
+                    e
+                ),
+            }
+        }
+
+        reasons
+    }
+
+    /// Active scans of the campgrounds, oldest first, with who they belong to
+    async fn active_scans(
+        &self,
+        campground_ids: &[String],
+    ) -> Result<(Vec<UserScan>, HashMap<Uuid, ScanContext>), sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT us.id, us.user_id, us.campground_id, us.check_in_date, us.check_out_date,
+                   us.nights, us.status, us.notification_sent, us.created_at, us.updated_at,
+                   us.expires_at, us.organization_id, us.facility_type, us.provider,
+                   us.flexibility_days, us.max_price, us.site_filters, us.quick_book,
+                   COALESCE(c.name, us.campground_id) AS campground_name,
+                   c.latitude::FLOAT8 AS latitude, c.longitude::FLOAT8 AS longitude,
+                   u.locale, u.timezone
+            FROM user_scans us
+            JOIN users u ON u.id = us.user_id
+            LEFT JOIN campgrounds c ON c.id = us.campground_id
+            WHERE us.campground_id = ANY($1)
+              AND us.status = 'active'
+              AND us.deleted_at IS NULL
+            ORDER BY us.created_at, us.id
+            "#,
+        )
+        .bind(campground_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut scans = Vec::with_capacity(rows.len());
+        let mut contexts = HashMap::with_capacity(rows.len());
+
+        for row in rows {
+            let scan = UserScan::from_row(&row)?;
+            contexts.insert(
+                scan.id,
+                ScanContext {
+                    campground_name: row.get("campground_name"),
+                    campground_coordinates: row
+                        .get::<Option<f64>, _>("latitude")
+                        .zip(row.get("longitude")),
+                    owner_locale: row.get::<String, _>("locale").parse().unwrap_or_default(),
+                    owner_timezone: user_timezone(
+                        row.get::<Option<String>, _>("timezone").as_deref(),
+                    ),
+                },
+            );
+            scans.push(scan);
+        }
+
+        Ok((scans, contexts))
+    }
+
+    /// Alerts the scan's owner, organization members, and confirmed invited recipients
+    /// with `alert`, noting it in the scan's timeline. Failures are logged; returns how
+    /// many were alerted.
+    async fn alert(
+        &self,
+        scan: &UserScan,
+        context: &ScanContext,
+        alert: &AvailabilityAlert,
+    ) -> usize {
+        let mut channels = Vec::new();
+
+        match self.scans.scan_recipients(&scan.id).await {
+            Ok(recipients) => {
+                for recipient in &recipients {
+                    match self
+                        .notifier
+                        .send_notifications_for_new_availability(recipient, scan, alert)
+                        .await
+                    {
+                        Ok(Some(channel)) => channels.push(channel),
+                        Ok(None) => {}
+                        Err(e) => log::error!(
+                            "❌ Failed to alert user {} about scan {}: {}",
+                            recipient.user_id,
+                            scan.id,
+                            e
+                        ),
+                    }
+                }
+            }
+            Err(e) => log::error!("❌ Failed to look up recipients of scan {}: {}", scan.id, e),
+        }
+
+        let invited = match self.recipients.confirmed(&scan.id).await {
+            Ok(invited) => {
+                self.notifier
+                    .send_to_invited_recipients(
+                        &invited,
+                        scan,
+                        alert,
+                        context.owner_locale,
+                        context.owner_timezone,
+                    )
+                    .await
+            }
+            Err(e) => {
+                log::error!(
+                    "❌ Failed to look up invited recipients of scan {}: {}",
+                    scan.id,
+                    e
+                );
+                0
+            }
+        };
+
+        let alerted = channels.len() + invited;
+        if alerted == 0 {
+            return 0;
+        }
+
+        log::info!(
+            "🔔 Alerted {} recipients of scan {} about {} open sites",
+            alerted,
+            scan.id,
+            alert.site_count()
+        );
+        self.events
+            .record(
+                &scan.id,
+                ScanEventKind::NotificationSent,
+                serde_json::json!({
+                    "channels": channels,
+                    "invited_recipients": invited,
+                    "open_sites": alert.site_count(),
+                }),
+            )
+            .await;
+
+        if let Err(e) = sqlx::query(
+            "UPDATE user_scans SET notification_sent = TRUE, updated_at = NOW() WHERE id = $1",
+        )
+        .bind(scan.id)
+        .execute(&self.pool)
+        .await
+        {
+            log::warn!("⚠️ Failed to mark scan {} notified: {}", scan.id, e);
+        }
+
+        alerted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::availability_snapshots::AvailabilitySnapshots;
+    use crate::campsite_attributes::CampsiteAttributeStore;
+    use crate::scan_config::SharedScanConfig;
+    use crate::scan_executor::ScanExecutorConfig;
+    use crate::scan_updates::ScanUpdateHub;
+    use app_config::AppConfig;
+    use chrono::Duration;
+    use notification_services::{
+        AlertClaims, NotificationService, NotificationTemplates, SmsBudget,
+    };
+    use postgres::test_fixtures::{insert_campground, insert_user};
+    use rec_gov::{ApiCallLedger, MockRecGov, ReserveCaliforniaClient, SiteFilters};
+
+    fn scan(check_in_date: NaiveDate, nights: i64) -> UserScan {
+        UserScan {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            campground_id: "232447".to_string(),
+            check_in_date,
+            check_out_date: check_in_date + Duration::days(nights),
+            nights: nights as i32,
+            status: "active".to_string(),
+            notification_sent: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            expires_at: None,
+            organization_id: None,
+            facility_type: "campsite".to_string(),
+            provider: "recreation_gov".to_string(),
+            flexibility_days: 0,
+            max_price: None,
+            site_filters: SiteFilters::default(),
+            quick_book: false,
+        }
+    }
+
+    fn site(name: &str, open_nights: &[NaiveDate]) -> CampsiteAvailability {
+        CampsiteAvailability {
+            campsite_id: format!("232447-{}", name),
+            site: name.to_string(),
+            loop_name: Some("Loop A".to_string()),
+            campsite_type: Some("STANDARD NONELECTRIC".to_string()),
+            availabilities: open_nights
+                .iter()
+                .map(|night| {
+                    (
+                        format!("{}T00:00:00Z", night.format("%Y-%m-%d")),
+                        "Available".to_string(),
+                    )
+                })
+                .collect(),
+            quantities: BTreeMap::new(),
+            type_of_use: Some("Overnight".to_string()),
+            prices: BTreeMap::new(),
+        }
+    }
+
+    fn names(sites: &[CampsiteAvailability]) -> Vec<&str> {
+        sites.iter().map(|site| site.site.as_str()).collect()
+    }
+
+    #[test]
+    fn sites_staying_open_are_alerted_about_once() {
+        let today = Utc::now().date_naive();
+        let check_in = today + Duration::days(30);
+        let nights = [check_in, check_in + Duration::days(1)];
+        let scan = scan(check_in, 2);
+        let mut last_polls = LastPolls::default();
+
+        let first = last_polls.newly_open(&scan, vec![site("A1", &nights)], today);
+        assert_eq!(names(&first), ["A1"]);
+
+        // The same site still open on the next poll isn't news
+        let second = last_polls.newly_open(&scan, vec![site("A1", &nights)], today);
+        assert!(second.is_empty());
+
+        // A site opening next to it is, alone
+        let third =
+            last_polls.newly_open(&scan, vec![site("A1", &nights), site("A2", &nights)], today);
+        assert_eq!(names(&third), ["A2"]);
+
+        // A site that was booked and opens up again is alerted about again
+        last_polls.newly_open(&scan, vec![site("A2", &nights)], today);
+        let reopened =
+            last_polls.newly_open(&scan, vec![site("A1", &nights), site("A2", &nights)], today);
+        assert_eq!(names(&reopened), ["A1"]);
+    }
+
+    /// Manager polling `rec_gov` against `pool`, alerting through sandbox notifications
+    fn manager(pool: PgPool, rec_gov: MockRecGov) -> ScanManager<MockRecGov> {
+        let config = AppConfig::default();
+        let executor_config = ScanExecutorConfig::from_config(&config);
+        let shared_config = SharedScanConfig::new(executor_config.clone());
+        let notifications = NotificationService::sandbox(
+            &config,
+            NotificationTemplates::load(None).unwrap(),
+            pool.clone(),
+        );
+
+        ScanManager::new(
+            pool.clone(),
+            PollingJobQueue::new(pool.clone(), shared_config.clone()),
+            ScanExecutor::new(
+                rec_gov,
+                ReserveCaliforniaClient::new(&config, ApiCallLedger::new(pool.clone())),
+                ScanEventLog::new(pool.clone()),
+                AvailabilitySnapshots::new(pool.clone()),
+                CampsiteAttributeStore::new(pool.clone()),
+                ScanUpdateHub::new(),
+                executor_config,
+            ),
+            ScanService::from_config(pool.clone(), &config),
+            AvailabilityNotifier::new(
+                notifications,
+                SmsBudget::from_config(pool.clone(), &config),
+                AlertClaims::new(pool),
+                shared_config,
+            ),
+            Geocoder::disabled(),
+        )
+    }
+
+    /// Inserts an active two-night scan of campground 232447 checking in on `check_in`
+    async fn insert_scan(pool: &PgPool, check_in: NaiveDate) {
+        insert_campground(pool, "232447", "Upper Pines").await;
+        let user_id = insert_user(pool, "camper@example.com").await;
+        sqlx::query(
+            r#"
+            INSERT INTO user_scans (user_id, campground_id, check_in_date, check_out_date)
+            VALUES ($1, '232447', $2, $3)
+            "#,
+        )
+        .bind(user_id)
+        .bind(check_in)
+        .bind(check_in + Duration::days(2))
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn panicking_campgrounds_are_released_then_quarantined(pool: PgPool) {
+        insert_scan(&pool, Utc::now().date_naive() + Duration::days(30)).await;
+        let rec_gov = MockRecGov::new();
+        rec_gov.set_panicking("232447");
+        let manager = manager(pool.clone(), rec_gov);
+
+        for polled in 1..=QUARANTINE_AFTER_PANICS {
+            let round = manager.force_scan("232447").await.unwrap().unwrap();
+            assert_eq!(round.panicked, 1);
+            assert_eq!(round.campgrounds, 0);
+
+            let (claimed, disabled): (bool, bool) = sqlx::query_as(
+                r#"
+                SELECT claimed_by IS NOT NULL, COALESCE(disabled_until > NOW(), FALSE)
+                FROM polling_jobs WHERE campground_id = '232447'
+                "#,
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            assert!(!claimed);
+            assert_eq!(disabled, polled == QUARANTINE_AFTER_PANICS);
+        }
+
+        assert_eq!(manager.status().panics, u64::from(QUARANTINE_AFTER_PANICS));
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn polls_record_availability_windows(pool: PgPool) {
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let nights = [check_in, check_in + Duration::days(1)];
+
+        insert_scan(&pool, check_in).await;
+
+        let rec_gov = MockRecGov::new();
+        rec_gov.set_availability("232447", vec![site("A1", &nights)]);
+        let manager = manager(pool.clone(), rec_gov);
+
+        let round = manager.force_scan("232447").await.unwrap().unwrap();
+        assert_eq!(round.scans_checked, 1);
+
+        let windows: Vec<(String, NaiveDate)> = sqlx::query_as(
+            r#"
+            SELECT campsite_id, date FROM site_availability_windows
+            WHERE campground_id = '232447' AND closed_at IS NULL
+            ORDER BY date
+            "#,
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            windows,
+            nights
+                .iter()
+                .map(|night| ("232447-A1".to_string(), *night))
+                .collect::<Vec<_>>()
+        );
+
+        // Polling again with the site still open neither alerts nor opens new windows
+        let round = manager.force_scan("232447").await.unwrap().unwrap();
+        assert_eq!(round.alerts_sent, 0);
+        let open_windows: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM site_availability_windows WHERE closed_at IS NULL",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(open_windows, 2);
+    }
+
+    #[test]
+    fn scans_no_longer_active_are_forgotten() {
+        let today = Utc::now().date_naive();
+        let check_in = today + Duration::days(30);
+        let nights = [check_in];
+        let polled = scan(check_in, 1);
+        let elsewhere = UserScan {
+            campground_id: "232449".to_string(),
+            ..scan(check_in, 1)
+        };
+        let mut last_polls = LastPolls::default();
+        last_polls.newly_open(&polled, vec![site("A1", &nights)], today);
+        last_polls.newly_open(&elsewhere, vec![site("B1", &nights)], today);
+
+        // The first scan was cancelled; the other campground wasn't polled this round
+        last_polls.forget_inactive(&["232447".to_string()], &[]);
+
+        assert!(!last_polls.scans.contains_key(&polled.id));
+        assert!(last_polls.scans.contains_key(&elsewhere.id));
+        let again = last_polls.newly_open(&polled, vec![site("A1", &nights)], today);
+        assert_eq!(names(&again), ["A1"]);
+    }
+}
@@ -0,0 +1,342 @@
+use chrono::{NaiveDate, Utc};
+use i18n::{Locale, Tz};
+use notification_services::{
+    AlertClaims, AlertSite, AvailabilityAlert, NotificationError, NotificationService, SmsBudget,
+};
+use rec_gov::{CampsiteAvailability, Provider, is_available_status};
+use std::collections::BTreeSet;
+
+use crate::availability_diff::candidate_stays;
+use crate::scan_config::SharedScanConfig;
+use crate::scan_recipients::InvitedRecipient;
+use crate::scan_types::{ScanRecipient, UserScan};
+
+/// Groups the open sites a poll found for a scan into a single alert, noting which
+/// nights of the stay each one is open and its nightly price when known. Scans with
+/// quick-book links get a link to each site's booking page for the stay it matched.
+pub fn availability_alert(
+    scan: &UserScan,
+    campground_name: &str,
+    sites: &[CampsiteAvailability],
+) -> AvailabilityAlert {
+    let provider = Provider::parse(&scan.provider)
+        .unwrap_or_else(|| Provider::of_campground(&scan.campground_id));
+
+    AvailabilityAlert::new(
+        campground_name,
+        scan.check_in_date,
+        scan.check_out_date,
+        provider.display_name(),
+        &provider.booking_url(&scan.campground_id),
+        sites.iter().map(|site| AlertSite {
+            loop_name: site.loop_name.clone(),
+            site: if site.site.is_empty() {
+                site.campsite_id.clone()
+            } else {
+                site.site.clone()
+            },
+            open_nights: site
+                .nights()
+                .filter(|(night, status)| {
+                    (scan.check_in_date..scan.check_out_date).contains(night)
+                        && is_available_status(status)
+                })
+                .map(|(night, _)| night)
+                .collect(),
+            nightly_price_cents: site
+                .highest_price(scan.check_in_date, scan.check_out_date)
+                .map(|price| (price * 100.0).round() as u32),
+            booking_url: scan
+                .quick_book
+                .then(|| {
+                    let (check_in, check_out) = booked_stay(scan, site);
+                    provider.site_booking_url(&site.campsite_id, check_in, check_out)
+                })
+                .flatten(),
+        }),
+    )
+}
+
+/// First stay the scan accepts that the site is open every night of, or the scan's own
+/// dates if none is
+fn booked_stay(scan: &UserScan, site: &CampsiteAvailability) -> (NaiveDate, NaiveDate) {
+    let open: BTreeSet<NaiveDate> = site
+        .nights()
+        .filter(|(_, status)| is_available_status(status))
+        .map(|(night, _)| night)
+        .collect();
+
+    candidate_stays(
+        scan.check_in_date,
+        scan.check_out_date,
+        scan.flexibility_days,
+        Utc::now().date_naive(),
+    )
+    .into_iter()
+    .find(|(check_in, check_out)| {
+        check_in
+            .iter_days()
+            .take_while(|night| night < check_out)
+            .all(|night| open.contains(&night))
+    })
+    .unwrap_or((scan.check_in_date, scan.check_out_date))
+}
+
+/// Alerts scan recipients about open sites: one message per scan per poll, however many
+/// sites opened, on the first channel that gets through
+#[derive(Clone)]
+pub struct AvailabilityNotifier {
+    notification_service: NotificationService,
+    sms_budget: SmsBudget,
+    claims: AlertClaims,
+    config: SharedScanConfig,
+}
+
+impl AvailabilityNotifier {
+    /// Creates a notifier sending through the given service and SMS budget, claiming
+    /// each alert with `claims` first, in the channel order `config` says at the time
+    pub fn new(
+        notification_service: NotificationService,
+        sms_budget: SmsBudget,
+        claims: AlertClaims,
+        config: SharedScanConfig,
+    ) -> Self {
+        Self {
+            notification_service,
+            sms_budget,
+            claims,
+            config,
+        }
+    }
+
+    /// Sends the alert listing every site a poll found open for `scan`, trying the
+    /// recipient's channels in the scan's notification order (SMS first for sniper
+    /// trips). When the SMS budget is used up, the email says so. Returns the channel
+    /// that delivered, or `None` when there was nothing to send, no channel enabled, or
+    /// the same alert was already sent (alerts are claimed before sending, so retries and
+    /// overlapping polls don't send it twice).
+    pub async fn send_notifications_for_new_availability(
+        &self,
+        recipient: &ScanRecipient,
+        scan: &UserScan,
+        alert: &AvailabilityAlert,
+    ) -> Result<Option<&'static str>, NotificationError> {
+        if alert.site_count() == 0 {
+            return Ok(None);
+        }
+
+        let key = alert.idempotency_key(&scan.id, &recipient.user_id.to_string());
+        let Some(claim_id) = self
+            .claims
+            .claim(
+                &recipient.user_id,
+                &scan.id,
+                &recipient.email,
+                &key,
+                &alert.site_summary(),
+            )
+            .await?
+        else {
+            log::info!(
+                "🔁 Alert for scan {} about these sites already sent, skipping",
+                scan.id
+            );
+            return Ok(None);
+        };
+
+        let sent = self.send_on_first_channel(recipient, scan, alert).await;
+        let settled = match &sent {
+            Ok(Some(channel)) => self.claims.sent(&claim_id, channel).await,
+            Ok(None) => self.claims.release(&claim_id, false).await,
+            Err(_) => self.claims.release(&claim_id, true).await,
+        };
+        if let Err(e) = settled {
+            log::error!("❌ Failed to settle alert claim of scan {}: {}", scan.id, e);
+        }
+
+        sent
+    }
+
+    /// Sends `alert` on the first of the recipient's channels that gets through
+    async fn send_on_first_channel(
+        &self,
+        recipient: &ScanRecipient,
+        scan: &UserScan,
+        alert: &AvailabilityAlert,
+    ) -> Result<Option<&'static str>, NotificationError> {
+        let mut notice = None;
+        let mut last_error = None;
+
+        for channel in self.config.get().notification_order(scan.check_in_date) {
+            let sent = match channel {
+                "sms" => {
+                    let Some(phone) = recipient.phone.as_deref().filter(|_| recipient.sms_enabled)
+                    else {
+                        continue;
+                    };
+
+                    self.notification_service
+                        .send_availability_sms(
+                            &self.sms_budget,
+                            &recipient.user_id,
+                            phone,
+                            alert,
+                            &scan.id,
+                            recipient.locale,
+                            recipient.timezone,
+                        )
+                        .await
+                        .map(|allowance| {
+                            notice = allowance.fallback_notice(recipient.locale);
+                            notice.is_none()
+                        })
+                }
+                _ => {
+                    if !recipient.email_enabled && notice.is_none() {
+                        continue;
+                    }
+
+                    self.notification_service
+                        .send_availability_alert(
+                            &recipient.user_id,
+                            &recipient.email,
+                            alert,
+                            &scan.id,
+                            notice.as_deref(),
+                            recipient.locale,
+                            recipient.timezone,
+                        )
+                        .await
+                        .map(|()| true)
+                }
+            };
+
+            match sent {
+                Ok(true) => return Ok(Some(channel)),
+                Ok(false) => {}
+                Err(e) => {
+                    log::warn!(
+                        "⚠️ {} alert for scan {} failed, trying the next channel: {}",
+                        channel,
+                        scan.id,
+                        e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) => Err(e),
+            None => Ok(None),
+        }
+    }
+
+    /// Sends the alert for `scan` to each recipient its owner invited and who confirmed,
+    /// on the channel they were invited on, in the owner's language `locale` and time
+    /// zone `timezone`. Texts count against the owner's SMS budget. Failures are logged;
+    /// returns how many recipients were alerted.
+    pub async fn send_to_invited_recipients(
+        &self,
+        recipients: &[InvitedRecipient],
+        scan: &UserScan,
+        alert: &AvailabilityAlert,
+        locale: Locale,
+        timezone: Tz,
+    ) -> usize {
+        if alert.site_count() == 0 {
+            return 0;
+        }
+
+        let mut alerted = 0;
+
+        for recipient in recipients {
+            let key = alert.idempotency_key(&scan.id, &recipient.address);
+            let claim_id = match self
+                .claims
+                .claim(
+                    &scan.user_id,
+                    &scan.id,
+                    &recipient.address,
+                    &key,
+                    &alert.site_summary(),
+                )
+                .await
+            {
+                Ok(Some(claim_id)) => claim_id,
+                Ok(None) => {
+                    log::info!(
+                        "🔁 Alert for recipient {} of scan {} already sent, skipping",
+                        recipient.id,
+                        scan.id
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    log::warn!(
+                        "⚠️ Failed to claim alert for recipient {} of scan {}: {}",
+                        recipient.id,
+                        scan.id,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            let sent = match recipient.channel.as_str() {
+                "sms" => self
+                    .notification_service
+                    .send_availability_sms(
+                        &self.sms_budget,
+                        &scan.user_id,
+                        &recipient.address,
+                        alert,
+                        &scan.id,
+                        locale,
+                        timezone,
+                    )
+                    .await
+                    .map(|allowance| allowance.is_allowed()),
+                _ => self
+                    .notification_service
+                    .send_recipient_availability_alert(
+                        &scan.user_id,
+                        &recipient.address,
+                        alert,
+                        &scan.id,
+                        &recipient.token,
+                        locale,
+                        timezone,
+                    )
+                    .await
+                    .map(|()| true),
+            };
+
+            let settled = match &sent {
+                Ok(true) => self.claims.sent(&claim_id, &recipient.channel).await,
+                Ok(false) => self.claims.release(&claim_id, false).await,
+                Err(_) => self.claims.release(&claim_id, true).await,
+            };
+            if let Err(e) = settled {
+                log::error!("❌ Failed to settle alert claim of scan {}: {}", scan.id, e);
+            }
+
+            match sent {
+                Ok(true) => alerted += 1,
+                Ok(false) => log::warn!(
+                    "⚠️ SMS budget of scan {} owner used up, recipient {} not texted",
+                    scan.id,
+                    recipient.id
+                ),
+                Err(e) => log::warn!(
+                    "⚠️ Failed to alert recipient {} of scan {}: {}",
+                    recipient.id,
+                    scan.id,
+                    e
+                ),
+            }
+        }
+
+        alerted
+    }
+}
@@ -0,0 +1,395 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use i18n::Locale;
+use notification_services::NotificationService;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::scan_types::ScanError;
+
+/// Most recipients a scan can have besides its owner and organization, declined ones
+/// included
+pub const MAX_SCAN_RECIPIENTS: i64 = 5;
+
+/// Request to add someone to a scan's alerts: exactly one of an email address or a US
+/// phone number
+#[derive(Debug, Deserialize, Validate)]
+pub struct AddScanRecipientRequest {
+    /// Email address to alert
+    #[validate(email(message = "Invalid email format"))]
+    pub email: Option<String>,
+    /// Phone number to text, in any common US format
+    pub phone: Option<String>,
+}
+
+/// Query of the confirm and decline links sent to a scan recipient
+#[derive(Debug, Deserialize)]
+pub struct ScanRecipientLinkQuery {
+    /// Secret identifying the recipient
+    pub token: String,
+}
+
+/// Someone outside the scan's owner and organization who gets its alerts once they
+/// confirm
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct InvitedRecipient {
+    /// Unique identifier of the recipient
+    pub id: Uuid,
+    /// Scan whose alerts they get
+    pub scan_id: Uuid,
+    /// `email` or `sms`
+    pub channel: String,
+    /// Email address or phone number
+    pub address: String,
+    /// `pending` until they respond, then `confirmed` or `declined`
+    pub status: String,
+    /// Secret of their confirm, decline, and unsubscribe links
+    #[serde(skip)]
+    pub token: String,
+    /// When the owner added them
+    pub created_at: Option<DateTime<Utc>>,
+    /// When they confirmed or declined
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+/// What a recipient invitation tells the person invited
+#[derive(Debug, Clone)]
+pub struct RecipientInvitation {
+    /// The recipient invited
+    pub recipient: InvitedRecipient,
+    /// Name of the scan's owner
+    pub owner_name: String,
+    /// Campground watched
+    pub campground_name: String,
+    /// Check-in date of the scan
+    pub check_in_date: NaiveDate,
+    /// Check-out date of the scan
+    pub check_out_date: NaiveDate,
+    /// Language of the scan's owner, which the invitation is written in
+    pub locale: Locale,
+}
+
+impl RecipientInvitation {
+    /// Stay as written in the invitation, e.g. "Fri Jul 4 to Sun Jul 6, 2025"
+    pub fn dates(&self) -> String {
+        self.locale
+            .date_range(self.check_in_date, self.check_out_date)
+    }
+}
+
+/// Extra recipients of scan alerts, added by the scan's owner and confirmed by the
+/// recipients themselves
+#[derive(Debug, Clone)]
+pub struct ScanRecipientService {
+    pool: PgPool,
+}
+
+impl ScanRecipientService {
+    /// Creates the service backed by the given pool
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Adds a pending recipient to one of the user's own scans. `phone` must already be
+    /// in E.164 format. Someone who declined alerts for the scan can't be added again,
+    /// and a scan takes at most [`MAX_SCAN_RECIPIENTS`].
+    pub async fn add(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+        channel: &str,
+        address: &str,
+    ) -> Result<RecipientInvitation, ScanError> {
+        let scan = sqlx::query(
+            r#"
+            SELECT us.check_in_date, us.check_out_date, u.name AS owner_name, u.locale,
+                   c.name AS campground_name
+            FROM user_scans us
+            JOIN users u ON u.id = us.user_id
+            LEFT JOIN campgrounds c ON c.id = us.campground_id
+            WHERE us.id = $1 AND us.user_id = $2 AND us.deleted_at IS NULL
+            "#,
+        )
+        .bind(scan_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(ScanError::NotFound)?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // Lock the scan so concurrent requests can't both take the last slot
+        sqlx::query("SELECT id FROM user_scans WHERE id = $1 FOR UPDATE")
+            .bind(scan_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let existing: Option<String> = sqlx::query_scalar(
+            "SELECT status FROM scan_recipients WHERE scan_id = $1 AND channel = $2 AND address = $3",
+        )
+        .bind(scan_id)
+        .bind(channel)
+        .bind(address)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match existing.as_deref() {
+            Some("declined") => {
+                return Err(ScanError::Validation(format!(
+                    "{} declined alerts for this scan",
+                    address
+                )));
+            }
+            Some(_) => {
+                return Err(ScanError::Validation(format!(
+                    "{} already gets alerts for this scan",
+                    address
+                )));
+            }
+            None => {}
+        }
+
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM scan_recipients WHERE scan_id = $1")
+                .bind(scan_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        if count >= MAX_SCAN_RECIPIENTS {
+            return Err(ScanError::Validation(format!(
+                "A scan can have at most {} extra recipients",
+                MAX_SCAN_RECIPIENTS
+            )));
+        }
+
+        let recipient = sqlx::query_as::<_, InvitedRecipient>(
+            r#"
+            INSERT INTO scan_recipients (scan_id, channel, address, token)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, scan_id, channel, address, status, token, created_at, responded_at
+            "#,
+        )
+        .bind(scan_id)
+        .bind(channel)
+        .bind(address)
+        .bind(NotificationService::generate_verification_token())
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(RecipientInvitation {
+            recipient,
+            owner_name: scan.get("owner_name"),
+            campground_name: scan
+                .get::<Option<String>, _>("campground_name")
+                .unwrap_or_else(|| "Unknown Campground".to_string()),
+            check_in_date: scan.get("check_in_date"),
+            check_out_date: scan.get("check_out_date"),
+            locale: scan.get::<String, _>("locale").parse().unwrap_or_default(),
+        })
+    }
+
+    /// Recipients of one of the user's own scans, in the order they were added
+    pub async fn list(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+    ) -> Result<Vec<InvitedRecipient>, ScanError> {
+        self.ensure_owner(user_id, scan_id).await?;
+
+        Ok(sqlx::query_as::<_, InvitedRecipient>(
+            r#"
+            SELECT id, scan_id, channel, address, status, token, created_at, responded_at
+            FROM scan_recipients
+            WHERE scan_id = $1
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(scan_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Removes a recipient from one of the user's own scans. Declined recipients stay, so
+    /// they aren't invited again.
+    pub async fn remove(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+        recipient_id: &Uuid,
+    ) -> Result<(), ScanError> {
+        self.ensure_owner(user_id, scan_id).await?;
+
+        let removed = sqlx::query(
+            "DELETE FROM scan_recipients WHERE id = $1 AND scan_id = $2 AND status <> 'declined'",
+        )
+        .bind(recipient_id)
+        .bind(scan_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if removed == 0 {
+            return Err(ScanError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Confirms or declines alerts from the link sent to a recipient. Confirming works
+    /// after declining too, so someone who changes their mind can opt back in. `None`
+    /// when the link doesn't match a recipient.
+    pub async fn respond(
+        &self,
+        token: &str,
+        confirmed: bool,
+    ) -> Result<Option<InvitedRecipient>, ScanError> {
+        Ok(sqlx::query_as::<_, InvitedRecipient>(
+            r#"
+            UPDATE scan_recipients
+            SET status = $2, responded_at = NOW()
+            WHERE token = $1
+            RETURNING id, scan_id, channel, address, status, token, created_at, responded_at
+            "#,
+        )
+        .bind(token)
+        .bind(if confirmed { "confirmed" } else { "declined" })
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// Confirmed recipients of a scan, to alert alongside its owner and organization
+    pub async fn confirmed(&self, scan_id: &Uuid) -> Result<Vec<InvitedRecipient>, ScanError> {
+        Ok(sqlx::query_as::<_, InvitedRecipient>(
+            r#"
+            SELECT id, scan_id, channel, address, status, token, created_at, responded_at
+            FROM scan_recipients
+            WHERE scan_id = $1 AND status = 'confirmed'
+            "#,
+        )
+        .bind(scan_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Fails unless the user owns the scan; organization members can't manage recipients
+    async fn ensure_owner(&self, user_id: &Uuid, scan_id: &Uuid) -> Result<(), ScanError> {
+        let owned: Option<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM user_scans WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+        )
+        .bind(scan_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        owned.map(|_| ()).ok_or(ScanError::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan_service::ScanService;
+    use crate::scan_types::CreateScanRequest;
+    use notification_services::VerificationThrottle;
+    use notification_services::types::{NotificationError, VerificationChannel};
+    use notification_services::verification_throttle::MAX_INVITATION_SENDS;
+    use postgres::test_fixtures::insert_user;
+
+    /// A scan owned by a new user, returning both IDs
+    async fn owned_scan(pool: &PgPool) -> (Uuid, Uuid) {
+        let user_id = insert_user(pool, "owner@example.com").await;
+        let check_in = Utc::now().date_naive() + chrono::Duration::days(30);
+        let request: CreateScanRequest = serde_json::from_value(serde_json::json!({
+            "campground_id": "232447",
+            "campground_name": "Upper Pines",
+            "check_in_date": check_in,
+            "check_out_date": check_in + chrono::Duration::days(2),
+        }))
+        .unwrap();
+        let scan_id = ScanService::new(pool.clone())
+            .create_scan(&user_id, &request)
+            .await
+            .unwrap()
+            .scan
+            .id;
+
+        (user_id, scan_id)
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn scans_take_a_limited_number_of_recipients(pool: PgPool) {
+        let (user_id, scan_id) = owned_scan(&pool).await;
+        let recipients = ScanRecipientService::new(pool);
+
+        let mut added = Vec::new();
+        for i in 0..MAX_SCAN_RECIPIENTS {
+            let address = format!("friend{}@example.com", i);
+            let invitation = recipients
+                .add(&user_id, &scan_id, "email", &address)
+                .await
+                .unwrap();
+            added.push(invitation.recipient.id);
+        }
+
+        assert!(matches!(
+            recipients
+                .add(&user_id, &scan_id, "email", "one-more@example.com")
+                .await,
+            Err(ScanError::Validation(_))
+        ));
+
+        // Removing someone frees their slot, and only theirs
+        recipients
+            .remove(&user_id, &scan_id, &added[0])
+            .await
+            .unwrap();
+        recipients
+            .add(&user_id, &scan_id, "email", "one-more@example.com")
+            .await
+            .unwrap();
+        assert!(matches!(
+            recipients
+                .add(&user_id, &scan_id, "email", "friend0@example.com")
+                .await,
+            Err(ScanError::Validation(_))
+        ));
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn removing_and_re_adding_an_address_runs_out_of_invitations(pool: PgPool) {
+        let (user_id, scan_id) = owned_scan(&pool).await;
+        let recipients = ScanRecipientService::new(pool.clone());
+        let throttle = VerificationThrottle::new(pool);
+
+        // What adding a recipient does: add them, then count the invitation sent
+        for _ in 0..MAX_INVITATION_SENDS {
+            let invitation = recipients
+                .add(&user_id, &scan_id, "email", "stranger@example.com")
+                .await
+                .unwrap();
+            throttle
+                .acquire_invitation(&scan_id, VerificationChannel::Email, "stranger@example.com")
+                .await
+                .unwrap();
+            recipients
+                .remove(&user_id, &scan_id, &invitation.recipient.id)
+                .await
+                .unwrap();
+        }
+
+        recipients
+            .add(&user_id, &scan_id, "email", "stranger@example.com")
+            .await
+            .unwrap();
+        assert!(matches!(
+            throttle
+                .acquire_invitation(&scan_id, VerificationChannel::Email, "stranger@example.com")
+                .await,
+            Err(NotificationError::TooManySends { .. })
+        ));
+    }
+}
@@ -0,0 +1,259 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::BTreeSet;
+
+use crate::availability_diff::{AvailableSites, candidate_stays, matching_sites};
+use crate::scan_executor::ScanExecutorConfig;
+
+/// A recorded day range of one campground, the scans to replay against it, and the
+/// notifications they are expected to produce
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayScenario {
+    /// Campground whose availability history is replayed
+    pub campground_id: String,
+    /// First day of history replayed
+    pub from: NaiveDate,
+    /// Last day of history replayed
+    pub to: NaiveDate,
+    /// Scans to replay
+    pub scans: Vec<ReplayScan>,
+    /// Notifications the scans should produce, in order
+    #[serde(default)]
+    pub expected: Vec<ReplayNotification>,
+}
+
+/// A scan as it would have been set up during the replayed days
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayScan {
+    /// Name identifying the scan in the results
+    pub label: String,
+    /// Check-in date
+    pub check_in_date: NaiveDate,
+    /// Check-out date
+    pub check_out_date: NaiveDate,
+    /// Days the stay may shift earlier or later
+    #[serde(default)]
+    pub flexibility_days: i32,
+    /// Channels the owner enabled (`email`, `sms`)
+    #[serde(default = "default_channels")]
+    pub channels: Vec<String>,
+}
+
+fn default_channels() -> Vec<String> {
+    vec!["email".to_string()]
+}
+
+/// One recorded poll of a campground night
+#[derive(Debug, Clone)]
+pub struct HistorySnapshot {
+    /// Night polled
+    pub date: NaiveDate,
+    /// When the poll ran
+    pub observed_at: DateTime<Utc>,
+    /// Sites available that night at the time
+    pub available_site_ids: Vec<String>,
+}
+
+/// A notification the pipeline sent, or would have sent, during a replay
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayNotification {
+    /// Replayed day it fired on
+    pub day: NaiveDate,
+    /// Label of the scan alerted
+    pub scan: String,
+    /// Channel that delivered it
+    pub channel: String,
+    /// Sites open for the scan's stay
+    pub site_ids: Vec<String>,
+}
+
+/// A notification channel a replay delivers to instead of email or SMS
+pub trait AlertChannel {
+    /// Channel name (`email`, `sms`)
+    fn name(&self) -> &str;
+
+    /// Delivers a notification; returns false if the channel failed, so the next channel
+    /// is tried.
+    fn deliver(&mut self, notification: &ReplayNotification) -> bool;
+}
+
+/// Channel that records what it was sent, optionally failing every delivery
+#[derive(Debug, Clone)]
+pub struct MockChannel {
+    name: String,
+    failing: bool,
+    /// Notifications delivered to the channel
+    pub delivered: Vec<ReplayNotification>,
+}
+
+impl MockChannel {
+    /// Creates a channel that accepts every notification
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            failing: false,
+            delivered: Vec::new(),
+        }
+    }
+
+    /// Creates a channel that fails every delivery, e.g. SMS with its budget used up
+    pub fn failing(name: &str) -> Self {
+        Self {
+            failing: true,
+            ..Self::new(name)
+        }
+    }
+}
+
+impl AlertChannel for MockChannel {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn deliver(&mut self, notification: &ReplayNotification) -> bool {
+        if self.failing {
+            return false;
+        }
+        self.delivered.push(notification.clone());
+        true
+    }
+}
+
+/// Differences between the notifications a replay produced and those expected
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayReport {
+    /// Polls replayed
+    pub snapshots: usize,
+    /// Notifications produced
+    pub notifications: Vec<ReplayNotification>,
+    /// Expected notifications that weren't produced
+    pub missing: Vec<ReplayNotification>,
+    /// Produced notifications that weren't expected
+    pub unexpected: Vec<ReplayNotification>,
+}
+
+impl ReplayReport {
+    /// Whether the replay produced exactly the expected notifications
+    pub fn passed(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+/// Loads a campground's recorded polls observed on the days `from..=to`, oldest first.
+pub async fn load_history(
+    pool: &PgPool,
+    campground_id: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<HistorySnapshot>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT date, observed_at, available_site_ids
+        FROM availability_history
+        WHERE campground_id = $1
+          AND observed_at >= ($2::DATE)::TIMESTAMP AT TIME ZONE 'UTC'
+          AND observed_at < ($3::DATE)::TIMESTAMP AT TIME ZONE 'UTC'
+        ORDER BY observed_at, id
+        "#,
+    )
+    .bind(campground_id)
+    .bind(from)
+    .bind(to + Duration::days(1))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| HistorySnapshot {
+            date: row.get("date"),
+            observed_at: row.get("observed_at"),
+            available_site_ids: row.get("available_site_ids"),
+        })
+        .collect())
+}
+
+/// Feeds recorded polls through the matching and notification pipeline in order, as if
+/// the scenario's scans had been active then. Each scan is alerted once, the first time
+/// a site is open for its stay, through the first of its channels that delivers, tried
+/// in the order live alerts use on that day.
+pub fn replay(
+    scenario: &ReplayScenario,
+    history: &[HistorySnapshot],
+    config: &ScanExecutorConfig,
+    channels: &mut [&mut dyn AlertChannel],
+) -> ReplayReport {
+    let mut available = AvailableSites::new();
+    let mut notified = BTreeSet::new();
+    let mut notifications = Vec::new();
+
+    for snapshot in history {
+        available.insert(
+            snapshot.date,
+            snapshot.available_site_ids.iter().cloned().collect(),
+        );
+
+        let day = snapshot.observed_at.date_naive();
+
+        for scan in &scenario.scans {
+            if notified.contains(&scan.label) {
+                continue;
+            }
+
+            let stays = candidate_stays(
+                scan.check_in_date,
+                scan.check_out_date,
+                scan.flexibility_days,
+                day,
+            );
+            let open = matching_sites(&stays, &available);
+            if open.is_empty() {
+                continue;
+            }
+
+            for channel_name in config.notification_order_on(scan.check_in_date, day) {
+                if !scan.channels.iter().any(|enabled| enabled == channel_name) {
+                    continue;
+                }
+                let Some(channel) = channels
+                    .iter_mut()
+                    .find(|channel| channel.name() == channel_name)
+                else {
+                    continue;
+                };
+
+                let notification = ReplayNotification {
+                    day,
+                    scan: scan.label.clone(),
+                    channel: channel_name.to_string(),
+                    site_ids: open.iter().cloned().collect(),
+                };
+
+                if channel.deliver(&notification) {
+                    notifications.push(notification);
+                    notified.insert(scan.label.clone());
+                    break;
+                }
+            }
+        }
+    }
+
+    let missing = scenario
+        .expected
+        .iter()
+        .filter(|expected| !notifications.contains(expected))
+        .cloned()
+        .collect();
+    let unexpected = notifications
+        .iter()
+        .filter(|produced| !scenario.expected.contains(produced))
+        .cloned()
+        .collect();
+
+    ReplayReport {
+        snapshots: history.len(),
+        notifications,
+        missing,
+        unexpected,
+    }
+}
@@ -1,52 +1,166 @@
-use sqlx::{PgPool, Row};
+use app_config::AppConfig;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use i18n::{FluentArgs, current_locale, message_with, today_in, user_timezone};
+use rec_gov::{Provider, SiteFilters, distance_miles};
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgPool, Row};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
+use crate::scan_events::{ScanEventKind, record_scan_event};
+use crate::scan_expiry::ScanExpiryPolicy;
+use crate::scan_health::ScanHealth;
 use crate::scan_types::*;
+use crate::seasonal_closure::{CLOSED_AFTER_POLLS, SeasonalClosure};
+use crate::trips::pause_trip_alternatives;
+
+/// Days during which a deleted scan can be restored
+pub const SCAN_RESTORE_WINDOW_DAYS: i32 = 30;
+
+/// Scans of one user on one campground from which creating another warns
+const REDUNDANT_SCAN_THRESHOLD: usize = 3;
+
+/// Maximum number of remap suggestions returned
+const MAX_REMAP_SUGGESTIONS: usize = 5;
+
+/// Campgrounds farther than this from the scanned facility are only suggested on name
+const REMAP_RADIUS_MILES: f64 = 50.0;
+
+/// Words that say what kind of facility it is rather than which one
+const GENERIC_FACILITY_WORDS: &[&str] = &[
+    "campground",
+    "camp",
+    "day",
+    "use",
+    "area",
+    "site",
+    "sites",
+    "picnic",
+    "group",
+    "trailhead",
+];
 
 /// Service for handling campground scan operations
+#[derive(Clone)]
 pub struct ScanService {
     pool: PgPool,
+    expiry: ScanExpiryPolicy,
 }
 
 impl ScanService {
     /// Creates a new instance of `ScanService` with the provided database connection pool
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            expiry: ScanExpiryPolicy::default(),
+        }
+    }
+
+    /// Creates a service expiring scans as configured
+    pub fn from_config(pool: PgPool, config: &AppConfig) -> Self {
+        Self {
+            pool,
+            expiry: ScanExpiryPolicy::from_config(config),
+        }
     }
 
-    /// Creates a new scan for the specified user
+    /// Creates a new scan for the specified user. Warns when the user now has several
+    /// scans on the campground, since that doesn't make it polled more often.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id))]
     pub async fn create_scan(
         &self,
         user_id: &Uuid,
         request: &CreateScanRequest,
-    ) -> Result<UserScan, ScanError> {
-        // Validate date range
-        if request.check_out_date <= request.check_in_date {
-            return Err(ScanError::InvalidDateRange);
+    ) -> Result<CreatedScan, ScanError> {
+        validate_stay(
+            request.check_in_date,
+            request.check_out_date,
+            request.flexibility_days,
+            self.user_today(user_id).await?,
+        )?;
+        validate_site_filters(request.provider, &request.site_filters)?;
+
+        // Don't create scans whose alerts could never reach the user
+        self.ensure_deliverable_channel(user_id).await?;
+
+        if let Some(organization_id) = &request.organization_id {
+            self.ensure_organization_member(user_id, organization_id)
+                .await?;
+        }
+
+        // ReserveCalifornia facility IDs are namespaced so they can't collide with RIDB's
+        let campground_id = request.provider.campground_id(&request.campground_id);
+        if Provider::of_campground(&campground_id) != request.provider {
+            return Err(ScanError::Validation(format!(
+                "Campground {} isn't a {} campground",
+                request.campground_id,
+                request.provider.as_str()
+            )));
+        }
+
+        // First-come, first-served campgrounds never show availability
+        let reservable: Option<Option<bool>> =
+            sqlx::query_scalar("SELECT is_reservable FROM campgrounds WHERE id = $1")
+                .bind(&campground_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        if reservable.flatten() == Some(false) {
+            return Err(ScanError::NotReservable(request.campground_name.clone()));
         }
 
-        // First, ensure the campground exists in our database
-        self.ensure_campground_exists(&request.campground_id, &request.campground_name)
+        // Ensure the campground exists in our database
+        self.ensure_campground_exists(&campground_id, &request.campground_name)
             .await?;
 
+        // Lock the quota check and the insert together so concurrent requests can't both
+        // take the last slot
+        let mut tx = self.pool.begin().await?;
+        enforce_scan_quota(&mut tx, user_id).await?;
+
         // Create the scan
         let row = sqlx::query(
             r#"
             INSERT INTO user_scans (
-                user_id, campground_id, check_in_date, check_out_date
-            ) VALUES ($1, $2, $3, $4)
+                user_id, campground_id, check_in_date, check_out_date, organization_id,
+                facility_type, provider, expires_at, flexibility_days, max_price,
+                site_filters, quick_book
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             RETURNING 
                 id, user_id, campground_id, check_in_date, check_out_date,
-                nights, status, notification_sent, created_at, updated_at, expires_at
+                nights, status, notification_sent, created_at, updated_at, expires_at,
+                organization_id, facility_type, provider, flexibility_days, max_price,
+                site_filters, quick_book
             "#,
         )
         .bind(user_id)
-        .bind(&request.campground_id)
+        .bind(&campground_id)
         .bind(request.check_in_date)
         .bind(request.check_out_date)
-        .fetch_one(&self.pool)
+        .bind(request.organization_id)
+        .bind(request.facility_type.as_str())
+        .bind(request.provider.as_str())
+        .bind(self.expires_at(request.check_in_date, request.flexibility_days))
+        .bind(request.flexibility_days)
+        .bind(request.max_price)
+        .bind(Json(&request.site_filters))
+        .bind(request.quick_book)
+        .fetch_one(&mut *tx)
         .await?;
 
+        record_scan_event(
+            &mut *tx,
+            &row.get("id"),
+            ScanEventKind::Created,
+            serde_json::json!({
+                "campground_id": campground_id,
+                "check_in_date": request.check_in_date,
+                "check_out_date": request.check_out_date,
+            }),
+        )
+        .await?;
+
+        tx.commit().await?;
+
         let scan = UserScan {
             id: row.get("id"),
             user_id: row.get("user_id"),
@@ -59,97 +173,183 @@ impl ScanService {
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
             expires_at: row.get("expires_at"),
+            organization_id: row.get("organization_id"),
+            facility_type: row.get("facility_type"),
+            provider: row.get("provider"),
+            flexibility_days: row.get("flexibility_days"),
+            max_price: row.get("max_price"),
+            site_filters: row.get::<Json<SiteFilters>, _>("site_filters").0,
+            quick_book: row.get("quick_book"),
         };
 
-        Ok(scan)
+        let warnings = self
+            .redundant_scan_warning(user_id, &campground_id, &request.campground_name)
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(CreatedScan { scan, warnings })
     }
 
-    /// Gets all scans for a specific user with campground information
+    /// Warns a user with `REDUNDANT_SCAN_THRESHOLD` or more active or paused scans on a
+    /// campground, suggesting flexible scans that could replace several of them. The
+    /// message is written in the request's locale.
+    async fn redundant_scan_warning(
+        &self,
+        user_id: &Uuid,
+        campground_id: &str,
+        campground_name: &str,
+    ) -> Result<Option<ScanWarning>, ScanError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, check_in_date, nights, flexibility_days
+            FROM user_scans
+            WHERE user_id = $1 AND campground_id = $2 AND status IN ('active', 'paused')
+              AND deleted_at IS NULL
+            ORDER BY check_in_date
+            "#,
+        )
+        .bind(user_id)
+        .bind(campground_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        if rows.len() < REDUNDANT_SCAN_THRESHOLD {
+            return Ok(None);
+        }
+
+        let mut by_length: BTreeMap<i32, Vec<(Uuid, NaiveDate, i32)>> = BTreeMap::new();
+        for row in &rows {
+            by_length.entry(row.get("nights")).or_default().push((
+                row.get("id"),
+                row.get("check_in_date"),
+                row.get("flexibility_days"),
+            ));
+        }
+
+        let consolidate = by_length
+            .into_iter()
+            .filter_map(|(nights, scans)| consolidation(nights, &scans))
+            .collect();
+
+        let mut args = FluentArgs::new();
+        args.set("count", rows.len());
+        args.set("campground", campground_name);
+
+        Ok(Some(ScanWarning::RedundantCampgroundScans {
+            campground_id: campground_id.to_string(),
+            scan_count: rows.len() as i64,
+            message: message_with(current_locale(), "redundant-scans", &args),
+            consolidate,
+        }))
+    }
+
+    /// Gets all scans for a specific user with campground information, including scans
+    /// shared with the user's organizations
+    #[tracing::instrument(skip_all, fields(user_id = %user_id))]
     pub async fn get_user_scans(
         &self,
         user_id: &Uuid,
     ) -> Result<Vec<UserScanWithCampground>, ScanError> {
-        let rows = sqlx::query(
+        let rows = sqlx::query(&format!(
             r#"
-            SELECT 
+            SELECT
                 us.id, us.campground_id, us.check_in_date, us.check_out_date,
-                us.nights, us.status, us.notification_sent, us.created_at, 
-                us.updated_at, us.expires_at, c.name as campground_name
+                us.nights, us.status, us.notification_sent, us.created_at,
+                us.updated_at, us.expires_at, us.organization_id, us.facility_type, us.provider,
+                us.flexibility_days, us.max_price, us.site_filters, us.quick_book, us.outcome,
+                us.outcome_at, us.trip_id,
+                c.name as campground_name, {SCAN_FRESHNESS_COLUMNS}
             FROM user_scans us
             LEFT JOIN campgrounds c ON us.campground_id = c.id
-            WHERE us.user_id = $1
+            {SCAN_FRESHNESS_JOINS}
+            WHERE {}
             ORDER BY us.created_at DESC
             "#,
-        )
+            accessible_by(1)
+        ))
         .bind(user_id)
         .fetch_all(&self.pool)
         .await?;
 
-        let scans = rows
-            .into_iter()
-            .map(|row| UserScanWithCampground {
-                id: row.get("id"),
-                campground_id: row.get("campground_id"),
-                campground_name: row
-                    .get::<Option<String>, _>("campground_name")
-                    .unwrap_or_else(|| "Unknown Campground".to_string()),
-                check_in_date: row.get("check_in_date"),
-                check_out_date: row.get("check_out_date"),
-                nights: row.get("nights"),
-                status: row.get("status"),
-                notification_sent: row.get("notification_sent"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-                expires_at: row.get("expires_at"),
-            })
-            .collect();
+        let scans = rows.into_iter().map(|row| scan_from_row(&row)).collect();
 
         Ok(scans)
     }
 
-    /// Gets a specific scan by ID, ensuring it belongs to the user
+    /// Gets a page of the user's scans (including shared ones) in creation order, starting
+    /// after the `(created_at, id)` of the previous page's last scan
+    pub async fn user_scans_page(
+        &self,
+        user_id: &Uuid,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<UserScanWithCampground>, ScanError> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT
+                us.id, us.campground_id, us.check_in_date, us.check_out_date,
+                us.nights, us.status, us.notification_sent, us.created_at,
+                us.updated_at, us.expires_at, us.organization_id, us.facility_type, us.provider,
+                us.flexibility_days, us.max_price, us.site_filters, us.quick_book, us.outcome,
+                us.outcome_at, us.trip_id,
+                c.name as campground_name, {SCAN_FRESHNESS_COLUMNS}
+            FROM user_scans us
+            LEFT JOIN campgrounds c ON us.campground_id = c.id
+            {SCAN_FRESHNESS_JOINS}
+            WHERE {}
+              AND ($2::timestamptz IS NULL OR (us.created_at, us.id) > ($2, $3))
+            ORDER BY us.created_at, us.id
+            LIMIT $4
+            "#,
+            accessible_by(1)
+        ))
+        .bind(user_id)
+        .bind(after.map(|(created_at, _)| created_at))
+        .bind(after.map(|(_, id)| id))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(scan_from_row).collect())
+    }
+
+    /// Gets a specific scan by ID, ensuring it belongs to the user or is shared with them
+    #[tracing::instrument(skip_all, fields(user_id = %user_id, scan_id = %scan_id))]
     pub async fn get_user_scan(
         &self,
         user_id: &Uuid,
         scan_id: &Uuid,
     ) -> Result<UserScanWithCampground, ScanError> {
-        let row = sqlx::query(
+        let row = sqlx::query(&format!(
             r#"
-            SELECT 
+            SELECT
                 us.id, us.campground_id, us.check_in_date, us.check_out_date,
-                us.nights, us.status, us.notification_sent, us.created_at, 
-                us.updated_at, us.expires_at, c.name as campground_name
+                us.nights, us.status, us.notification_sent, us.created_at,
+                us.updated_at, us.expires_at, us.organization_id, us.facility_type, us.provider,
+                us.flexibility_days, us.max_price, us.site_filters, us.quick_book, us.outcome,
+                us.outcome_at, us.trip_id,
+                c.name as campground_name, {SCAN_FRESHNESS_COLUMNS}
             FROM user_scans us
             LEFT JOIN campgrounds c ON us.campground_id = c.id
-            WHERE us.id = $1 AND us.user_id = $2
+            {SCAN_FRESHNESS_JOINS}
+            WHERE us.id = $1 AND {}
             "#,
-        )
+            accessible_by(2)
+        ))
         .bind(scan_id)
         .bind(user_id)
         .fetch_optional(&self.pool)
         .await?;
 
         match row {
-            Some(row) => Ok(UserScanWithCampground {
-                id: row.get("id"),
-                campground_id: row.get("campground_id"),
-                campground_name: row
-                    .get::<Option<String>, _>("campground_name")
-                    .unwrap_or_else(|| "Unknown Campground".to_string()),
-                check_in_date: row.get("check_in_date"),
-                check_out_date: row.get("check_out_date"),
-                nights: row.get("nights"),
-                status: row.get("status"),
-                notification_sent: row.get("notification_sent"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-                expires_at: row.get("expires_at"),
-            }),
+            Some(row) => Ok(scan_from_row(&row)),
             None => Err(ScanError::NotFound),
         }
     }
 
     /// Updates a scan's status
+    #[tracing::instrument(skip_all, fields(user_id = %user_id, scan_id = %scan_id))]
     pub async fn update_scan_status(
         &self,
         user_id: &Uuid,
@@ -157,55 +357,796 @@ impl ScanService {
         new_status: &str,
     ) -> Result<UserScanWithCampground, ScanError> {
         // First check if the scan exists and belongs to the user
-        let _existing_scan = self.get_user_scan(user_id, scan_id).await?;
+        let existing_scan = self.get_user_scan(user_id, scan_id).await?;
+
+        // Expiry took the scan off its polling job; extending puts it back
+        if existing_scan.status == "expired" {
+            return Err(ScanError::Validation(
+                "This scan expired; extend it to resume scanning".to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        // Resuming a scan takes a slot of its owner's quota
+        if new_status == "active" && existing_scan.status != "active" {
+            let owner_id = scan_owner(&mut tx, scan_id).await?;
+            enforce_scan_quota(&mut tx, &owner_id).await?;
+        }
 
         // Update the scan
-        let row = sqlx::query(
+        let previous = sqlx::query(&format!(
             r#"
-            UPDATE user_scans 
+            UPDATE user_scans us
             SET status = $1, updated_at = NOW()
-            WHERE id = $2 AND user_id = $3
-            RETURNING 
-                id, user_id, campground_id, check_in_date, check_out_date,
-                nights, status, notification_sent, created_at, updated_at, expires_at
+            FROM user_scans previous
+            WHERE us.id = $2 AND previous.id = us.id AND {}
+            RETURNING previous.status, us.campground_id
             "#,
-        )
+            accessible_by(3)
+        ))
         .bind(new_status)
         .bind(scan_id)
         .bind(user_id)
-        .fetch_one(&self.pool)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ScanError::NotFound)?;
+
+        move_polling_count(
+            &mut tx,
+            previous.get("campground_id"),
+            previous.get("status"),
+            new_status,
+        )
         .await?;
 
-        // Get the campground name
-        let campground_name = self
-            .get_campground_name(&row.get::<String, _>("campground_id"))
+        if new_status != existing_scan.status
+            && let Some(kind) = ScanEventKind::for_status(new_status)
+        {
+            record_scan_event(
+                &mut *tx,
+                scan_id,
+                kind,
+                serde_json::json!({ "from": existing_scan.status, "to": new_status }),
+            )
             .await?;
+        }
 
-        Ok(UserScanWithCampground {
-            id: row.get("id"),
-            campground_id: row.get("campground_id"),
-            campground_name,
-            check_in_date: row.get("check_in_date"),
-            check_out_date: row.get("check_out_date"),
-            nights: row.get("nights"),
-            status: row.get("status"),
-            notification_sent: row.get("notification_sent"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-            expires_at: row.get("expires_at"),
-        })
+        tx.commit().await?;
+
+        self.get_user_scan(user_id, scan_id).await
     }
 
-    /// Deletes a scan
-    pub async fn delete_scan(&self, user_id: &Uuid, scan_id: &Uuid) -> Result<(), ScanError> {
-        let result = sqlx::query("DELETE FROM user_scans WHERE id = $1 AND user_id = $2")
-            .bind(scan_id)
+    /// Changes a scan's stay, facility type, flexibility, max price, site filters, or
+    /// quick-book links. The new stay is validated like a new scan's, the expiry is
+    /// recomputed from it, alerts already sent are forgotten so matches for the new stay
+    /// are reported, and the campground's polling job is due right away.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id, scan_id = %scan_id))]
+    pub async fn modify_scan(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+        request: &PatchScanRequest,
+    ) -> Result<UserScanWithCampground, ScanError> {
+        let scan = self.get_user_scan(user_id, scan_id).await?;
+
+        if !matches!(scan.status.as_str(), "active" | "paused") {
+            return Err(ScanError::Validation(format!(
+                "A {} scan can't be changed",
+                scan.status
+            )));
+        }
+
+        let check_in_date = request.check_in_date.unwrap_or(scan.check_in_date);
+        let check_out_date = request.check_out_date.unwrap_or(scan.check_out_date);
+        let flexibility_days = request.flexibility_days.unwrap_or(scan.flexibility_days);
+        let facility_type = request
+            .facility_type
+            .map(|facility_type| facility_type.as_str().to_string())
+            .unwrap_or(scan.facility_type);
+
+        let max_price = match request.max_price {
+            Some(0) => None,
+            Some(max_price) if max_price < 0 => {
+                return Err(ScanError::Validation(
+                    "Max price must be at least $1 a night".to_string(),
+                ));
+            }
+            Some(max_price) => Some(max_price),
+            None => scan.max_price,
+        };
+
+        let site_filters = request.site_filters.clone().unwrap_or(scan.site_filters);
+        let quick_book = request.quick_book.unwrap_or(scan.quick_book);
+
+        validate_stay(
+            check_in_date,
+            check_out_date,
+            flexibility_days,
+            self.user_today(user_id).await?,
+        )?;
+        validate_site_filters(
+            Provider::parse(&scan.provider)
+                .unwrap_or_else(|| Provider::of_campground(&scan.campground_id)),
+            &site_filters,
+        )?;
+
+        let dates_changed = check_in_date != scan.check_in_date
+            || check_out_date != scan.check_out_date
+            || flexibility_days != scan.flexibility_days;
+        let expires_at = if dates_changed {
+            Some(self.expires_at(check_in_date, flexibility_days))
+        } else {
+            scan.expires_at
+        };
+
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query(&format!(
+            r#"
+            UPDATE user_scans us
+            SET check_in_date = $1,
+                check_out_date = $2,
+                facility_type = $3,
+                flexibility_days = $4,
+                max_price = $5,
+                site_filters = $6,
+                quick_book = $7,
+                expires_at = $8,
+                expiry_warning_sent = CASE WHEN $9 THEN FALSE ELSE us.expiry_warning_sent END,
+                notification_sent = FALSE,
+                updated_at = NOW()
+            WHERE us.id = $10 AND {}
+            "#,
+            accessible_by(11)
+        ))
+        .bind(check_in_date)
+        .bind(check_out_date)
+        .bind(&facility_type)
+        .bind(flexibility_days)
+        .bind(max_price)
+        .bind(Json(&site_filters))
+        .bind(quick_book)
+        .bind(expires_at)
+        .bind(dates_changed)
+        .bind(scan_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if updated == 0 {
+            return Err(ScanError::NotFound);
+        }
+
+        // Check the changed scan on the next polling cycle
+        sqlx::query(
+            r#"
+            UPDATE polling_jobs
+            SET next_poll_at = NOW(), updated_at = NOW()
+            WHERE campground_id = $1
+            "#,
+        )
+        .bind(&scan.campground_id)
+        .execute(&mut *tx)
+        .await?;
+
+        record_scan_event(
+            &mut *tx,
+            scan_id,
+            ScanEventKind::Modified,
+            serde_json::json!({
+                "check_in_date": check_in_date,
+                "check_out_date": check_out_date,
+                "facility_type": facility_type,
+                "flexibility_days": flexibility_days,
+                "max_price": max_price,
+                "site_filters": site_filters,
+                "quick_book": quick_book,
+            }),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_user_scan(user_id, scan_id).await
+    }
+
+    /// Records what came of a scan. Booking completes an active or paused scan and giving
+    /// up cancels it; a missed booking keeps it running and re-arms its alerts, so the
+    /// next opening is reported. The outcome can be changed later. Booking a scan of a
+    /// trip pauses the trip's other active scans unless the trip opted out.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id, scan_id = %scan_id))]
+    pub async fn set_outcome(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+        outcome: ScanOutcome,
+    ) -> Result<UserScanWithCampground, ScanError> {
+        let scan = self.get_user_scan(user_id, scan_id).await?;
+        let ends_scan = matches!(scan.status.as_str(), "active" | "paused");
+        let status = outcome
+            .final_status()
+            .filter(|_| ends_scan)
+            .unwrap_or(&scan.status);
+
+        let mut tx = self.pool.begin().await?;
+
+        let previous = sqlx::query(&format!(
+            r#"
+            UPDATE user_scans us
+            SET outcome = $1,
+                outcome_at = NOW(),
+                status = $2,
+                notification_sent = CASE WHEN $3 THEN FALSE ELSE us.notification_sent END,
+                updated_at = NOW()
+            FROM user_scans previous
+            WHERE us.id = $4 AND previous.id = us.id AND {}
+            RETURNING previous.status, us.campground_id
+            "#,
+            accessible_by(5)
+        ))
+        .bind(outcome.as_str())
+        .bind(status)
+        .bind(outcome == ScanOutcome::Missed)
+        .bind(scan_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ScanError::NotFound)?;
+
+        // A completed or cancelled scan no longer keeps its campground polled
+        move_polling_count(
+            &mut tx,
+            previous.get("campground_id"),
+            previous.get("status"),
+            status,
+        )
+        .await?;
+
+        record_scan_event(
+            &mut *tx,
+            scan_id,
+            ScanEventKind::OutcomeRecorded,
+            serde_json::json!({ "outcome": outcome.as_str(), "status": status }),
+        )
+        .await?;
+
+        // Booking one campground of a trip makes its alternatives moot
+        if outcome == ScanOutcome::Booked {
+            let paused = pause_trip_alternatives(&mut tx, scan_id).await?;
+            if paused > 0 {
+                log::info!(
+                    "⏸️ Paused {} other scans of the trip of booked scan {}",
+                    paused,
+                    scan_id
+                );
+            }
+        }
+
+        tx.commit().await?;
+
+        self.get_user_scan(user_id, scan_id).await
+    }
+
+    /// Pushes a scan's expiry back to the full maximum age from now (still ending once
+    /// check-in passes) and re-arms the expiry warning. Expired scans are reactivated if
+    /// their owner's plan has room for another active scan.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id, scan_id = %scan_id))]
+    pub async fn extend_scan(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+    ) -> Result<UserScanWithCampground, ScanError> {
+        let scan = self.get_user_scan(user_id, scan_id).await?;
+
+        if !matches!(scan.status.as_str(), "active" | "paused" | "expired") {
+            return Err(ScanError::Validation(format!(
+                "A {} scan can't be extended",
+                scan.status
+            )));
+        }
+        if scan.check_in_date < self.user_today(user_id).await? {
+            return Err(ScanError::Validation(
+                "The check-in date has passed".to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        // Reactivating an expired scan takes a slot of its owner's quota
+        if scan.status == "expired" {
+            let owner_id = scan_owner(&mut tx, scan_id).await?;
+            enforce_scan_quota(&mut tx, &owner_id).await?;
+        }
+
+        let expires_at = self.expires_at(scan.check_in_date, scan.flexibility_days);
+        let previous_status: String = sqlx::query_scalar(&format!(
+            r#"
+            UPDATE user_scans us
+            SET expires_at = $1,
+                expiry_warning_sent = FALSE,
+                status = CASE WHEN us.status = 'expired' THEN 'active' ELSE us.status END,
+                updated_at = NOW()
+            FROM user_scans previous
+            WHERE us.id = $2 AND previous.id = us.id AND {}
+            RETURNING previous.status
+            "#,
+            accessible_by(3)
+        ))
+        .bind(expires_at)
+        .bind(scan_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ScanError::NotFound)?;
+
+        // Expiry took the scan off its campground's polling job
+        if previous_status == "expired" {
+            add_to_polling_job(&mut tx, &scan.campground_id).await?;
+        }
+
+        record_scan_event(
+            &mut *tx,
+            scan_id,
+            ScanEventKind::Extended,
+            serde_json::json!({
+                "expires_at": expires_at,
+                "reactivated": previous_status == "expired",
+            }),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_user_scan(user_id, scan_id).await
+    }
+
+    /// Gets the user's plan and how many more active scans it allows
+    #[tracing::instrument(skip_all, fields(user_id = %user_id))]
+    pub async fn scan_quota(&self, user_id: &Uuid) -> Result<ScanQuota, ScanError> {
+        let mut conn = self.pool.acquire().await?;
+        read_scan_quota(&mut conn, user_id, false).await
+    }
+
+    /// Moves a user to another plan. Scans above a lower quota keep running; only new and
+    /// resumed scans are refused. Returns `None` if the user doesn't exist.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id, plan_id = %plan_id))]
+    pub async fn set_user_plan(
+        &self,
+        user_id: &Uuid,
+        plan_id: &str,
+    ) -> Result<Option<Plan>, ScanError> {
+        let plan: Option<Plan> = sqlx::query_as(
+            "SELECT id, name, max_active_scans, sniper_mode FROM plans WHERE id = $1",
+        )
+        .bind(plan_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(plan) = plan else {
+            return Err(ScanError::Validation(format!("Unknown plan: {}", plan_id)));
+        };
+
+        let updated = sqlx::query("UPDATE users SET plan = $1, updated_at = NOW() WHERE id = $2")
+            .bind(&plan.id)
             .bind(user_id)
             .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+        Ok((updated > 0).then_some(plan))
+    }
+
+    /// Deletes a scan. The scan only disappears from listings and polling; its history is
+    /// kept, and it can be restored for `SCAN_RESTORE_WINDOW_DAYS`.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id, scan_id = %scan_id))]
+    pub async fn delete_scan(&self, user_id: &Uuid, scan_id: &Uuid) -> Result<(), ScanError> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE user_scans us
+            SET deleted_at = NOW(), updated_at = NOW()
+            WHERE us.id = $1 AND {}
+            RETURNING us.campground_id, us.status
+            "#,
+            accessible_by(2)
+        ))
+        .bind(scan_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ScanError::NotFound)?;
+
+        // Deleted scans no longer keep their campground's polling job alive
+        let status: String = row.get("status");
+        if keeps_polling(&status) {
+            remove_from_polling_job(&mut tx, row.get("campground_id")).await?;
+        }
+
+        record_scan_event(
+            &mut *tx,
+            scan_id,
+            ScanEventKind::Deleted,
+            serde_json::json!({ "status": status }),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Brings back a scan deleted within the last `SCAN_RESTORE_WINDOW_DAYS`, in the
+    /// status it had. Restoring an active scan takes a slot of its owner's quota.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id, scan_id = %scan_id))]
+    pub async fn restore_scan(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+    ) -> Result<UserScanWithCampground, ScanError> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT us.user_id, us.campground_id, us.status
+            FROM user_scans us
+            WHERE us.id = $1
+              AND us.deleted_at > NOW() - make_interval(days => $3)
+              AND {}
+            FOR UPDATE OF us
+            "#,
+            owned_or_shared_with(2)
+        ))
+        .bind(scan_id)
+        .bind(user_id)
+        .bind(SCAN_RESTORE_WINDOW_DAYS)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ScanError::NotFound)?;
+
+        let status: String = row.get("status");
+        if status == "active" {
+            enforce_scan_quota(&mut tx, &row.get("user_id")).await?;
+        }
+
+        sqlx::query("UPDATE user_scans SET deleted_at = NULL, updated_at = NOW() WHERE id = $1")
+            .bind(scan_id)
+            .execute(&mut *tx)
             .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(ScanError::NotFound);
+        if keeps_polling(&status) {
+            add_to_polling_job(&mut tx, row.get("campground_id")).await?;
+        }
+
+        record_scan_event(
+            &mut *tx,
+            scan_id,
+            ScanEventKind::Restored,
+            serde_json::json!({ "status": status }),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_user_scan(user_id, scan_id).await
+    }
+
+    /// Suggests reservable campgrounds the user may have meant instead of the one the
+    /// scan points at, ranked by name similarity and distance.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id, scan_id = %scan_id))]
+    pub async fn suggest_remap_targets(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+    ) -> Result<RemapSuggestionsResponse, ScanError> {
+        let scan = self.get_user_scan(user_id, scan_id).await?;
+
+        let current = sqlx::query(
+            r#"
+            SELECT name, parent_recarea_id,
+                latitude::FLOAT8 AS latitude, longitude::FLOAT8 AS longitude
+            FROM campgrounds
+            WHERE id = $1
+            "#,
+        )
+        .bind(&scan.campground_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(ScanError::CampgroundNotFound)?;
+
+        let current_name: String = current.get("name");
+        let current_recarea: Option<String> = current.get("parent_recarea_id");
+        let current_location: Option<(f64, f64)> = current
+            .get::<Option<f64>, _>("latitude")
+            .zip(current.get::<Option<f64>, _>("longitude"));
+
+        // Narrow the mirror down to the same recreation area, the surrounding ~50 miles,
+        // or a shared name word before scoring in Rust
+        let name_words = significant_words(&current_name);
+        let name_patterns: Vec<String> = name_words.iter().map(|w| format!("%{}%", w)).collect();
+        let (lat, lng) = current_location.unwrap_or_default();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, latitude::FLOAT8 AS latitude, longitude::FLOAT8 AS longitude
+            FROM campgrounds
+            WHERE id <> $1 AND is_reservable = true AND is_active = true
+              AND (
+                (parent_recarea_id IS NOT NULL AND parent_recarea_id = $2)
+                OR ($3 AND latitude BETWEEN $4 - 0.75 AND $4 + 0.75
+                       AND longitude BETWEEN $5 - 1.0 AND $5 + 1.0)
+                OR name ILIKE ANY($6)
+              )
+            LIMIT 200
+            "#,
+        )
+        .bind(&scan.campground_id)
+        .bind(&current_recarea)
+        .bind(current_location.is_some())
+        .bind(lat)
+        .bind(lng)
+        .bind(&name_patterns)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut suggestions: Vec<RemapSuggestion> = rows
+            .into_iter()
+            .map(|row| {
+                let name: String = row.get("name");
+                let location = row
+                    .get::<Option<f64>, _>("latitude")
+                    .zip(row.get::<Option<f64>, _>("longitude"));
+                let distance = current_location
+                    .zip(location)
+                    .map(|((lat1, lng1), (lat2, lng2))| distance_miles(lat1, lng1, lat2, lng2));
+
+                RemapSuggestion {
+                    campground_id: row.get("id"),
+                    score: remap_score(&current_name, &name, distance),
+                    campground_name: name,
+                    distance_miles: distance,
+                }
+            })
+            .collect();
+
+        suggestions.sort_by(|a, b| b.score.total_cmp(&a.score));
+        suggestions.truncate(MAX_REMAP_SUGGESTIONS);
+
+        Ok(RemapSuggestionsResponse {
+            scan_id: scan.id,
+            current_campground_id: scan.campground_id,
+            suggestions,
+        })
+    }
+
+    /// Moves a scan to a different campground, moving its polling job count along with
+    /// it in the same transaction. The scan's notification state is reset.
+    #[tracing::instrument(skip_all, fields(user_id = %user_id, scan_id = %scan_id, campground_id = %campground_id))]
+    pub async fn remap_scan(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+        campground_id: &str,
+        campground_name: Option<&str>,
+    ) -> Result<UserScanWithCampground, ScanError> {
+        let scan = self.get_user_scan(user_id, scan_id).await?;
+
+        if scan.campground_id == campground_id {
+            return Err(ScanError::Validation(
+                "The scan already points at this campground".to_string(),
+            ));
+        }
+
+        match campground_name {
+            Some(name) => self.ensure_campground_exists(campground_id, name).await?,
+            None => {
+                let exists = sqlx::query("SELECT 1 FROM campgrounds WHERE id = $1")
+                    .bind(campground_id)
+                    .fetch_optional(&self.pool)
+                    .await?
+                    .is_some();
+
+                if !exists {
+                    return Err(ScanError::CampgroundNotFound);
+                }
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        // Lock the scan and re-read its campground so concurrent remaps don't
+        // double-count polling jobs
+        let previous = sqlx::query(&format!(
+            "SELECT us.campground_id, us.status FROM user_scans us WHERE us.id = $1 AND {} FOR UPDATE",
+            accessible_by(2)
+        ))
+        .bind(scan_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ScanError::NotFound)?;
+        let previous_campground_id: String = previous.get("campground_id");
+        let status: String = previous.get("status");
+
+        sqlx::query(
+            r#"
+            UPDATE user_scans
+            SET campground_id = $1, provider = $2, notification_sent = false, updated_at = NOW()
+            WHERE id = $3
+            "#,
+        )
+        .bind(campground_id)
+        .bind(Provider::of_campground(campground_id).as_str())
+        .bind(scan_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // The polling job trigger only fires on insert and delete
+        if keeps_polling(&status) {
+            remove_from_polling_job(&mut tx, &previous_campground_id).await?;
+            add_to_polling_job(&mut tx, campground_id).await?;
+        }
+
+        record_scan_event(
+            &mut *tx,
+            scan_id,
+            ScanEventKind::Remapped,
+            serde_json::json!({ "from": previous_campground_id, "to": campground_id }),
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        log::info!(
+            "🔀 Remapped scan {} from campground {} to {}",
+            scan_id,
+            previous_campground_id,
+            campground_id
+        );
+
+        self.get_user_scan(user_id, scan_id).await
+    }
+
+    /// Gets the scans shared with an organization. Callers must check membership.
+    #[tracing::instrument(skip_all, fields(organization_id = %organization_id))]
+    pub async fn get_organization_scans(
+        &self,
+        organization_id: &Uuid,
+    ) -> Result<Vec<UserScanWithCampground>, ScanError> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT
+                us.id, us.campground_id, us.check_in_date, us.check_out_date,
+                us.nights, us.status, us.notification_sent, us.created_at,
+                us.updated_at, us.expires_at, us.organization_id, us.facility_type, us.provider,
+                us.flexibility_days, us.max_price, us.site_filters, us.quick_book, us.outcome,
+                us.outcome_at, us.trip_id,
+                c.name as campground_name, {SCAN_FRESHNESS_COLUMNS}
+            FROM user_scans us
+            LEFT JOIN campgrounds c ON us.campground_id = c.id
+            {SCAN_FRESHNESS_JOINS}
+            WHERE us.organization_id = $1 AND us.deleted_at IS NULL
+            ORDER BY us.created_at DESC
+            "#
+        ))
+        .bind(organization_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(scan_from_row).collect())
+    }
+
+    /// Gets everyone who should be notified about a scan: its creator and, for shared
+    /// scans, every member of the organization, except anyone who unsubscribed from the
+    /// scan. Each recipient's own channel preferences decide how they are notified.
+    #[tracing::instrument(skip_all, fields(scan_id = %scan_id))]
+    pub async fn scan_recipients(&self, scan_id: &Uuid) -> Result<Vec<ScanRecipient>, ScanError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT u.id, u.email, u.phone, u.notification_preferences, u.locale, u.timezone
+            FROM users u
+            WHERE u.is_active = true
+              AND (
+                u.id = (SELECT user_id FROM user_scans WHERE id = $1)
+                OR u.id IN (
+                    SELECT m.user_id
+                    FROM organization_members m
+                    JOIN user_scans us ON us.organization_id = m.organization_id
+                    WHERE us.id = $1
+                )
+              )
+              AND NOT EXISTS (
+                SELECT 1 FROM scan_notification_opt_outs o
+                WHERE o.user_id = u.id AND o.scan_id = $1
+              )
+            "#,
+        )
+        .bind(scan_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let recipients = rows
+            .into_iter()
+            .map(|row| {
+                let preferences: Option<serde_json::Value> = row.get("notification_preferences");
+                let preferences = preferences.unwrap_or_default();
+
+                ScanRecipient {
+                    user_id: row.get("id"),
+                    email: row.get("email"),
+                    phone: row.get("phone"),
+                    email_enabled: preferences["email"].as_bool().unwrap_or(false),
+                    sms_enabled: preferences["sms"].as_bool().unwrap_or(false),
+                    locale: row.get::<String, _>("locale").parse().unwrap_or_default(),
+                    timezone: user_timezone(row.get::<Option<String>, _>("timezone").as_deref()),
+                }
+            })
+            .collect();
+
+        Ok(recipients)
+    }
+
+    /// Version of the user's calendar feed link; links of older versions no longer open
+    /// the feed. `None` for an unknown user.
+    pub async fn calendar_feed_version(&self, user_id: &Uuid) -> Result<Option<i32>, ScanError> {
+        Ok(
+            sqlx::query_scalar("SELECT calendar_feed_version FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?,
+        )
+    }
+
+    /// Revokes the user's calendar feed link and returns the version of the new one
+    pub async fn reset_calendar_feed(&self, user_id: &Uuid) -> Result<i32, ScanError> {
+        sqlx::query_scalar(
+            r#"
+            UPDATE users
+            SET calendar_feed_version = calendar_feed_version + 1, updated_at = NOW()
+            WHERE id = $1
+            RETURNING calendar_feed_version
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(ScanError::NotFound)
+    }
+
+    /// Today's date in the user's time zone, so a stay starting tonight can still be
+    /// scanned after midnight UTC
+    async fn user_today(&self, user_id: &Uuid) -> Result<NaiveDate, ScanError> {
+        let timezone: Option<String> =
+            sqlx::query_scalar("SELECT timezone FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .flatten();
+
+        Ok(today_in(user_timezone(timezone.as_deref())))
+    }
+
+    /// Fails unless the user is a member of the organization
+    async fn ensure_organization_member(
+        &self,
+        user_id: &Uuid,
+        organization_id: &Uuid,
+    ) -> Result<(), ScanError> {
+        let is_member = sqlx::query(
+            "SELECT 1 FROM organization_members WHERE organization_id = $1 AND user_id = $2",
+        )
+        .bind(organization_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .is_some();
+
+        if !is_member {
+            return Err(ScanError::Validation(
+                "You are not a member of this organization".to_string(),
+            ));
         }
 
         Ok(())
@@ -219,8 +1160,8 @@ impl ScanService {
     ) -> Result<(), ScanError> {
         sqlx::query(
             r#"
-            INSERT INTO campgrounds (id, name)
-            VALUES ($1, $2)
+            INSERT INTO campgrounds (id, name, provider)
+            VALUES ($1, $2, $3)
             ON CONFLICT (id) DO UPDATE SET
                 name = EXCLUDED.name,
                 last_updated = NOW()
@@ -228,22 +1169,734 @@ impl ScanService {
         )
         .bind(campground_id)
         .bind(campground_name)
+        .bind(Provider::of_campground(campground_id).as_str())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records that a campground is first-come, first-served, so scans of it are refused
+    /// and it isn't suggested as a remap target
+    #[tracing::instrument(skip_all, fields(campground_id = %campground_id))]
+    pub async fn mark_not_reservable(
+        &self,
+        campground_id: &str,
+        campground_name: &str,
+    ) -> Result<(), ScanError> {
+        sqlx::query(
+            r#"
+            INSERT INTO campgrounds (id, name, is_reservable)
+            VALUES ($1, $2, false)
+            ON CONFLICT (id) DO UPDATE SET
+                is_reservable = false,
+                last_updated = NOW()
+            "#,
+        )
+        .bind(campground_id)
+        .bind(campground_name)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
-    /// Gets the name of a campground by ID
-    async fn get_campground_name(&self, campground_id: &str) -> Result<String, ScanError> {
-        let row = sqlx::query("SELECT name FROM campgrounds WHERE id = $1")
+    /// Fails if every notification channel the user has enabled is known to be
+    /// undeliverable. Channels that were never checked are assumed to be deliverable.
+    pub(crate) async fn ensure_deliverable_channel(&self, user_id: &Uuid) -> Result<(), ScanError> {
+        let row = sqlx::query(
+            "SELECT notification_preferences, channel_deliverability FROM users WHERE id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(());
+        };
+
+        let preferences: Option<serde_json::Value> = row.get("notification_preferences");
+        let deliverability: Option<serde_json::Value> = row.get("channel_deliverability");
+        let preferences = preferences.unwrap_or_default();
+        let deliverability = deliverability.unwrap_or_default();
+
+        let enabled: Vec<&str> = ["email", "sms"]
+            .into_iter()
+            .filter(|channel| preferences[channel].as_bool().unwrap_or(false))
+            .collect();
+
+        let all_undeliverable = !enabled.is_empty()
+            && enabled
+                .iter()
+                .all(|channel| deliverability[channel]["deliverable"].as_bool() == Some(false));
+
+        if all_undeliverable {
+            return Err(ScanError::NoDeliverableChannel);
+        }
+
+        Ok(())
+    }
+
+    /// When a scan started or extended now expires; flexible scans run until their latest
+    /// acceptable check-in has passed.
+    fn expires_at(&self, check_in_date: NaiveDate, flexibility_days: i32) -> DateTime<Utc> {
+        let latest_check_in = check_in_date + Duration::days(flexibility_days.max(0).into());
+        self.expiry.expires_at(latest_check_in, Utc::now())
+    }
+}
+
+/// Columns of a scan's polling freshness, selected with [`SCAN_FRESHNESS_JOINS`] for
+/// every scan [`scan_from_row`] builds. The next poll is only set while the scan is active
+/// and its campground's polling isn't paused, and the error only while polls are failing;
+/// the error streak gives the scan's health. The campground's description is only
+/// selected while the stay's nights are closed, to check the closure against its season.
+pub(crate) const SCAN_FRESHNESS_COLUMNS: &str = r#"
+    pj.last_polled,
+    CASE WHEN us.status = 'active' AND NOT COALESCE(pj.paused, FALSE)
+         THEN GREATEST(pj.next_poll_at, pj.disabled_until) END AS next_poll_at,
+    failure.error_message AS last_error,
+    COALESCE(pj.consecutive_errors, 0) AS consecutive_errors,
+    pj.failing_since,
+    COALESCE(closure.closed_polls, 0) AS closed_polls,
+    closure.closed_since,
+    CASE WHEN closure.closed_polls > 0
+         THEN (SELECT description FROM campgrounds WHERE id = us.campground_id)
+    END AS campground_description
+"#;
+
+/// Joins of the polling job, the latest failed availability check, and the closed streak
+/// of the stay's nights (within its flexibility, from today on) of a scan's (aliased
+/// `us`) campground, for [`SCAN_FRESHNESS_COLUMNS`]. The streak is the shortest of those
+/// nights', and only counts once every one of them was polled.
+pub(crate) const SCAN_FRESHNESS_JOINS: &str = r#"
+    LEFT JOIN polling_jobs pj ON pj.campground_id = us.campground_id
+    LEFT JOIN LATERAL (
+        SELECT ca.error_message
+        FROM campground_availability ca
+        WHERE ca.campground_id = us.campground_id
+          AND COALESCE(pj.consecutive_errors, 0) > 0
+          AND ca.check_status IS DISTINCT FROM 'success'
+        ORDER BY ca.last_checked DESC NULLS LAST
+        LIMIT 1
+    ) failure ON TRUE
+    LEFT JOIN LATERAL (
+        SELECT MIN(ca.closed_polls) AS closed_polls, MAX(ca.closed_since) AS closed_since
+        FROM campground_availability ca
+        WHERE ca.campground_id = us.campground_id
+          AND us.status = 'active'
+          AND ca.date >= GREATEST(us.check_in_date - us.flexibility_days, CURRENT_DATE)
+          AND ca.date < us.check_out_date + us.flexibility_days
+        HAVING COUNT(*) = (us.check_out_date + us.flexibility_days)
+                          - GREATEST(us.check_in_date - us.flexibility_days, CURRENT_DATE)
+    ) closure ON TRUE
+"#;
+
+/// SQL condition matching scans (aliased `us`) the user in parameter `$param` may see and
+/// manage: their own, and those shared with an organization they belong to, unless
+/// deleted.
+fn accessible_by(param: u8) -> String {
+    format!(
+        "(us.deleted_at IS NULL AND {})",
+        owned_or_shared_with(param)
+    )
+}
+
+/// SQL condition matching scans (aliased `us`) of the user in parameter `$param`, or
+/// shared with an organization they belong to, deleted or not.
+fn owned_or_shared_with(param: u8) -> String {
+    format!(
+        "(us.user_id = ${param} OR us.organization_id IN \
+         (SELECT organization_id FROM organization_members WHERE user_id = ${param}))"
+    )
+}
+
+/// Checks that a stay doesn't start before the user's `today`, ends after it starts, and
+/// shifts by no more than `MAX_FLEXIBILITY_DAYS`.
+fn validate_stay(
+    check_in_date: NaiveDate,
+    check_out_date: NaiveDate,
+    flexibility_days: i32,
+    today: NaiveDate,
+) -> Result<(), ScanError> {
+    if check_out_date <= check_in_date {
+        return Err(ScanError::InvalidDateRange);
+    }
+    if check_in_date < today {
+        return Err(ScanError::Validation(
+            "The check-in date has already passed".to_string(),
+        ));
+    }
+    if !(0..=MAX_FLEXIBILITY_DAYS).contains(&flexibility_days) {
+        return Err(ScanError::Validation(format!(
+            "Flexibility must be between 0 and {} days",
+            MAX_FLEXIBILITY_DAYS
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks a scan's site filters: a vehicle length must be positive, and filters need
+/// RIDB's campsite attributes, which only recreation.gov campgrounds have.
+fn validate_site_filters(provider: Provider, site_filters: &SiteFilters) -> Result<(), ScanError> {
+    if site_filters
+        .vehicle_length
+        .is_some_and(|length| length <= 0)
+    {
+        return Err(ScanError::Validation(
+            "Vehicle length must be at least 1 foot".to_string(),
+        ));
+    }
+    if provider != Provider::RecreationGov && !site_filters.is_empty() {
+        return Err(ScanError::Validation(format!(
+            "Site filters aren't available for {} campgrounds",
+            provider.as_str()
+        )));
+    }
+
+    Ok(())
+}
+
+/// One flexible scan covering every stay of `scans` (ID, check-in, flexibility) of
+/// `nights` nights, if there are several and they fit within `MAX_FLEXIBILITY_DAYS` of a
+/// middle date.
+fn consolidation(nights: i32, scans: &[(Uuid, NaiveDate, i32)]) -> Option<ConsolidationSuggestion> {
+    let earliest = scans
+        .iter()
+        .map(|(_, check_in, flexibility)| *check_in - Duration::days((*flexibility).into()))
+        .min()?;
+    let latest = scans
+        .iter()
+        .map(|(_, check_in, flexibility)| *check_in + Duration::days((*flexibility).into()))
+        .max()?;
+
+    let flexibility_days = ((latest - earliest).num_days() + 1) / 2;
+    if scans.len() < 2 || flexibility_days > MAX_FLEXIBILITY_DAYS.into() {
+        return None;
+    }
+
+    let check_in_date = (earliest + Duration::days(flexibility_days)).max(Utc::now().date_naive());
+
+    Some(ConsolidationSuggestion {
+        scan_ids: scans.iter().map(|(id, _, _)| *id).collect(),
+        check_in_date,
+        check_out_date: check_in_date + Duration::days(nights.into()),
+        flexibility_days: flexibility_days as i32,
+    })
+}
+
+/// Reads a user's active scan quota. With `lock`, the user's row stays locked until the
+/// transaction ends, serializing quota checks of the same user.
+async fn read_scan_quota(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    lock: bool,
+) -> Result<ScanQuota, ScanError> {
+    let row = sqlx::query(&format!(
+        r#"
+        SELECT p.id AS plan, p.max_active_scans,
+               (SELECT COUNT(*) FROM user_scans us
+                WHERE us.user_id = u.id AND us.status = 'active'
+                  AND us.deleted_at IS NULL) AS active_scans
+        FROM users u
+        JOIN plans p ON p.id = u.plan
+        WHERE u.id = $1
+        {}
+        "#,
+        if lock { "FOR UPDATE OF u" } else { "" }
+    ))
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await?
+    .ok_or(ScanError::Unauthorized)?;
+
+    let max_active_scans: Option<i32> = row.get("max_active_scans");
+    let active_scans: i64 = row.get("active_scans");
+
+    Ok(ScanQuota {
+        plan: row.get("plan"),
+        max_active_scans,
+        active_scans,
+        remaining: max_active_scans.map(|limit| (i64::from(limit) - active_scans).max(0)),
+    })
+}
+
+/// Fails if the user's plan doesn't allow another active scan. Call inside the transaction
+/// that activates the scan.
+async fn enforce_scan_quota(conn: &mut PgConnection, user_id: &Uuid) -> Result<(), ScanError> {
+    let quota = read_scan_quota(conn, user_id, true).await?;
+
+    match quota.max_active_scans {
+        Some(limit) if quota.remaining == Some(0) => Err(ScanError::QuotaExceeded {
+            plan: quota.plan,
+            limit,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Gets the user who created a scan, whose quota it counts against
+async fn scan_owner(conn: &mut PgConnection, scan_id: &Uuid) -> Result<Uuid, ScanError> {
+    sqlx::query_scalar("SELECT user_id FROM user_scans WHERE id = $1")
+        .bind(scan_id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or(ScanError::NotFound)
+}
+
+/// Whether a scan in this status counts towards its campground's polling job
+fn keeps_polling(status: &str) -> bool {
+    matches!(status, "active" | "paused")
+}
+
+/// Counts a scan towards its campground's polling job, which is due right away
+async fn add_to_polling_job(conn: &mut PgConnection, campground_id: &str) -> Result<(), ScanError> {
+    sqlx::query(
+        r#"
+        INSERT INTO polling_jobs (campground_id, active_scan_count, next_poll_at)
+        VALUES ($1, 1, NOW())
+        ON CONFLICT (campground_id)
+        DO UPDATE SET
+            active_scan_count = polling_jobs.active_scan_count + 1,
+            next_poll_at = LEAST(polling_jobs.next_poll_at, NOW()),
+            updated_at = NOW()
+        "#,
+    )
+    .bind(campground_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Stops counting a scan towards its campground's polling job
+async fn remove_from_polling_job(
+    conn: &mut PgConnection,
+    campground_id: &str,
+) -> Result<(), ScanError> {
+    sqlx::query(
+        r#"
+        UPDATE polling_jobs
+        SET active_scan_count = GREATEST(active_scan_count - 1, 0), updated_at = NOW()
+        WHERE campground_id = $1
+        "#,
+    )
+    .bind(campground_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Updates the campground's polling job for a scan going from one status to another
+async fn move_polling_count(
+    conn: &mut PgConnection,
+    campground_id: &str,
+    from: &str,
+    to: &str,
+) -> Result<(), ScanError> {
+    match (keeps_polling(from), keeps_polling(to)) {
+        (false, true) => add_to_polling_job(conn, campground_id).await,
+        (true, false) => remove_from_polling_job(conn, campground_id).await,
+        _ => Ok(()),
+    }
+}
+
+/// Builds a scan from a row selected with the campground name and
+/// [`SCAN_FRESHNESS_COLUMNS`].
+pub(crate) fn scan_from_row(row: &sqlx::postgres::PgRow) -> UserScanWithCampground {
+    UserScanWithCampground {
+        id: row.get("id"),
+        campground_id: row.get("campground_id"),
+        campground_name: row
+            .get::<Option<String>, _>("campground_name")
+            .unwrap_or_else(|| "Unknown Campground".to_string()),
+        check_in_date: row.get("check_in_date"),
+        check_out_date: row.get("check_out_date"),
+        nights: row.get("nights"),
+        status: row.get("status"),
+        notification_sent: row.get("notification_sent"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        expires_at: row.get("expires_at"),
+        organization_id: row.get("organization_id"),
+        facility_type: row.get("facility_type"),
+        provider: row.get("provider"),
+        flexibility_days: row.get("flexibility_days"),
+        max_price: row.get("max_price"),
+        site_filters: row.get::<Json<SiteFilters>, _>("site_filters").0,
+        quick_book: row.get("quick_book"),
+        outcome: row.get("outcome"),
+        outcome_at: row.get("outcome_at"),
+        trip_id: row.get("trip_id"),
+        last_polled: row.get("last_polled"),
+        next_poll_at: row.get("next_poll_at"),
+        last_error: row.get("last_error"),
+        health: ScanHealth::of(
+            row.get("consecutive_errors"),
+            row.get("failing_since"),
+            Utc::now(),
+        ),
+        seasonal_closure: row
+            .get::<Option<DateTime<Utc>>, _>("closed_since")
+            .filter(|_| row.get::<i32, _>("closed_polls") >= CLOSED_AFTER_POLLS)
+            .map(|closed_since| {
+                SeasonalClosure::of(
+                    closed_since,
+                    row.get::<Option<String>, _>("campground_description")
+                        .as_deref(),
+                    row.get("check_in_date"),
+                    row.get("check_out_date"),
+                )
+            }),
+    }
+}
+
+/// Lower-cased words of a facility name that identify it, e.g. "Upper Pines Day Use
+/// Area" -> ["upper", "pines"].
+fn significant_words(name: &str) -> Vec<String> {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 2 && !GENERIC_FACILITY_WORDS.contains(word))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Scores a candidate campground from 0 to 1: mostly name similarity (ignoring words like
+/// "campground" or "day use"), plus proximity when both locations are known.
+fn remap_score(current_name: &str, candidate_name: &str, distance: Option<f64>) -> f64 {
+    let similarity = strsim::jaro_winkler(
+        &significant_words(current_name).join(" "),
+        &significant_words(candidate_name).join(" "),
+    );
+
+    match distance {
+        Some(miles) => {
+            let proximity = (1.0 - miles / REMAP_RADIUS_MILES).max(0.0);
+            0.6 * similarity + 0.4 * proximity
+        }
+        None => similarity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use auth_services::organizations::{OrganizationMember, OrganizationService};
+    use i18n::{Locale, with_locale};
+    use postgres::test_fixtures::insert_user;
+
+    async fn create_scan(service: &ScanService, user_id: &Uuid, campground_id: &str) -> Uuid {
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let request: CreateScanRequest = serde_json::from_value(serde_json::json!({
+            "campground_id": campground_id,
+            "campground_name": "Upper Pines",
+            "check_in_date": check_in,
+            "check_out_date": check_in + Duration::days(2),
+        }))
+        .unwrap();
+
+        service
+            .create_scan(user_id, &request)
+            .await
+            .unwrap()
+            .scan
+            .id
+    }
+
+    async fn active_scan_count(pool: &PgPool, campground_id: &str) -> i32 {
+        sqlx::query_scalar("SELECT active_scan_count FROM polling_jobs WHERE campground_id = $1")
             .bind(campground_id)
-            .fetch_optional(&self.pool)
-            .await?;
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
 
-        match row {
-            Some(row) => Ok(row.get("name")),
-            None => Ok("Unknown Campground".to_string()),
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn booked_then_deleted_scans_release_their_polling_job(pool: PgPool) {
+        let service = ScanService::new(pool.clone());
+        let user_id = insert_user(&pool, "camper@example.com").await;
+        let scan_id = create_scan(&service, &user_id, "232447").await;
+        assert_eq!(active_scan_count(&pool, "232447").await, 1);
+
+        let booked = service
+            .set_outcome(&user_id, &scan_id, ScanOutcome::Booked)
+            .await
+            .unwrap();
+        assert_eq!(booked.status, "completed");
+        assert_eq!(active_scan_count(&pool, "232447").await, 0);
+
+        service.delete_scan(&user_id, &scan_id).await.unwrap();
+        assert_eq!(active_scan_count(&pool, "232447").await, 0);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn status_changes_keep_the_polling_job_count(pool: PgPool) {
+        let service = ScanService::new(pool.clone());
+        let user_id = insert_user(&pool, "camper@example.com").await;
+        let scan_id = create_scan(&service, &user_id, "232447").await;
+
+        service
+            .update_scan_status(&user_id, &scan_id, "paused")
+            .await
+            .unwrap();
+        assert_eq!(active_scan_count(&pool, "232447").await, 1);
+
+        service
+            .update_scan_status(&user_id, &scan_id, "cancelled")
+            .await
+            .unwrap();
+        assert_eq!(active_scan_count(&pool, "232447").await, 0);
+
+        service
+            .update_scan_status(&user_id, &scan_id, "active")
+            .await
+            .unwrap();
+        assert_eq!(active_scan_count(&pool, "232447").await, 1);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn remapping_a_cancelled_scan_leaves_polling_jobs_alone(pool: PgPool) {
+        let service = ScanService::new(pool.clone());
+        let user_id = insert_user(&pool, "camper@example.com").await;
+        let scan_id = create_scan(&service, &user_id, "232447").await;
+        service
+            .set_outcome(&user_id, &scan_id, ScanOutcome::GaveUp)
+            .await
+            .unwrap();
+
+        service
+            .remap_scan(&user_id, &scan_id, "232450", Some("Lower Pines"))
+            .await
+            .unwrap();
+
+        assert_eq!(active_scan_count(&pool, "232447").await, 0);
+        let new_job: Option<i32> = sqlx::query_scalar(
+            "SELECT active_scan_count FROM polling_jobs WHERE campground_id = '232450'",
+        )
+        .fetch_optional(&pool)
+        .await
+        .unwrap();
+        assert_eq!(new_job.unwrap_or(0), 0);
+    }
+
+    #[test]
+    fn stays_must_end_after_they_start() {
+        let today = NaiveDate::from_ymd_opt(2025, 8, 10).unwrap();
+        let day = |offset| today + Duration::days(offset);
+
+        for (check_in, check_out, allowed) in [
+            (day(0), day(1), true),
+            (day(0), day(0), false),
+            (day(1), day(0), false),
+            (day(-1), day(1), false),
+            (day(30), day(31), true),
+        ] {
+            assert_eq!(
+                validate_stay(check_in, check_out, 0, today).is_ok(),
+                allowed,
+                "{} to {}",
+                check_in,
+                check_out
+            );
         }
+
+        assert!(matches!(
+            validate_stay(day(2), day(2), 0, today),
+            Err(ScanError::InvalidDateRange)
+        ));
+    }
+
+    #[test]
+    fn flexibility_is_bounded() {
+        let today = NaiveDate::from_ymd_opt(2025, 8, 10).unwrap();
+        let check_out = today + Duration::days(2);
+
+        for (flexibility_days, allowed) in [
+            (-1, false),
+            (0, true),
+            (MAX_FLEXIBILITY_DAYS, true),
+            (MAX_FLEXIBILITY_DAYS + 1, false),
+        ] {
+            assert_eq!(
+                validate_stay(today, check_out, flexibility_days, today).is_ok(),
+                allowed,
+                "{} days",
+                flexibility_days
+            );
+        }
+    }
+
+    /// An organization owned by `owner`, with `member` invited and joined
+    async fn organization_with(pool: &PgPool, owner: &Uuid, member: &Uuid) -> Uuid {
+        let organizations = OrganizationService::new(pool.clone());
+        let organization = organizations
+            .create_organization(owner, "Camp Crew")
+            .await
+            .unwrap();
+        let as_owner = OrganizationMember {
+            user_id: *owner,
+            organization_id: organization.id,
+            role: organization.role,
+        };
+        organizations
+            .create_invitation(&as_owner, "member@example.com", "invite-token")
+            .await
+            .unwrap();
+        organizations
+            .accept_invitation(member, "invite-token")
+            .await
+            .unwrap();
+
+        organization.id
+    }
+
+    /// A scan created by `user_id` and shared with the organization
+    async fn create_organization_scan(
+        service: &ScanService,
+        user_id: &Uuid,
+        organization_id: &Uuid,
+    ) -> Result<Uuid, ScanError> {
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let request: CreateScanRequest = serde_json::from_value(serde_json::json!({
+            "campground_id": "232447",
+            "campground_name": "Upper Pines",
+            "check_in_date": check_in,
+            "check_out_date": check_in + Duration::days(2),
+            "organization_id": organization_id,
+        }))
+        .unwrap();
+
+        Ok(service.create_scan(user_id, &request).await?.scan.id)
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn organization_scans_are_hidden_from_non_members(pool: PgPool) {
+        let service = ScanService::new(pool.clone());
+        let owner = insert_user(&pool, "owner@example.com").await;
+        let member = insert_user(&pool, "member@example.com").await;
+        let outsider = insert_user(&pool, "outsider@example.com").await;
+        let organization_id = organization_with(&pool, &owner, &member).await;
+        let scan_id = create_organization_scan(&service, &owner, &organization_id)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            service.get_user_scan(&member, &scan_id).await.unwrap().id,
+            scan_id
+        );
+
+        assert!(matches!(
+            service.get_user_scan(&outsider, &scan_id).await,
+            Err(ScanError::NotFound)
+        ));
+        assert!(service.get_user_scans(&outsider).await.unwrap().is_empty());
+        assert!(matches!(
+            service
+                .update_scan_status(&outsider, &scan_id, "paused")
+                .await,
+            Err(ScanError::NotFound)
+        ));
+        assert!(matches!(
+            create_organization_scan(&service, &outsider, &organization_id).await,
+            Err(ScanError::Validation(_))
+        ));
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn removed_members_lose_access_to_organization_scans(pool: PgPool) {
+        let service = ScanService::new(pool.clone());
+        let owner = insert_user(&pool, "owner@example.com").await;
+        let member = insert_user(&pool, "member@example.com").await;
+        let organization_id = organization_with(&pool, &owner, &member).await;
+        let owners_scan = create_organization_scan(&service, &owner, &organization_id)
+            .await
+            .unwrap();
+        let members_scan = create_organization_scan(&service, &member, &organization_id)
+            .await
+            .unwrap();
+        assert_eq!(service.get_user_scans(&member).await.unwrap().len(), 2);
+
+        let as_owner = OrganizationMember {
+            user_id: owner,
+            organization_id,
+            role: "owner".to_string(),
+        };
+        OrganizationService::new(pool.clone())
+            .remove_member(&as_owner, &member)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            service.get_user_scan(&member, &owners_scan).await,
+            Err(ScanError::NotFound)
+        ));
+        assert!(matches!(
+            service
+                .update_scan_status(&member, &owners_scan, "paused")
+                .await,
+            Err(ScanError::NotFound)
+        ));
+        assert!(matches!(
+            create_organization_scan(&service, &member, &organization_id).await,
+            Err(ScanError::Validation(_))
+        ));
+
+        // What they shared stays with the organization, and they keep their own scan
+        let remaining: Vec<Uuid> = service
+            .get_user_scans(&member)
+            .await
+            .unwrap()
+            .iter()
+            .map(|scan| scan.id)
+            .collect();
+        assert_eq!(remaining, [members_scan]);
+        service.get_user_scan(&owner, &members_scan).await.unwrap();
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn redundant_scans_are_warned_about_in_the_request_locale(pool: PgPool) {
+        let service = ScanService::new(pool.clone());
+        let user_id = insert_user(&pool, "camper@example.com").await;
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+
+        let mut warnings = Vec::new();
+        for week in 0..REDUNDANT_SCAN_THRESHOLD as i64 {
+            let check_in = check_in + Duration::weeks(week);
+            let request: CreateScanRequest = serde_json::from_value(serde_json::json!({
+                "campground_id": "232447",
+                "campground_name": "Upper Pines",
+                "check_in_date": check_in,
+                "check_out_date": check_in + Duration::days(2),
+            }))
+            .unwrap();
+            warnings = with_locale(Locale::Spanish, service.create_scan(&user_id, &request))
+                .await
+                .unwrap()
+                .warnings;
+        }
+
+        let [
+            ScanWarning::RedundantCampgroundScans {
+                scan_count,
+                message,
+                ..
+            },
+        ] = warnings.as_slice()
+        else {
+            panic!("expected one redundant scan warning, got {:?}", warnings);
+        };
+        assert_eq!(*scan_count, REDUNDANT_SCAN_THRESHOLD as i64);
+        assert!(
+            message.starts_with("Tienes 3 búsquedas en Upper Pines."),
+            "{}",
+            message
+        );
     }
 }
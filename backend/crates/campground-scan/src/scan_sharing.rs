@@ -0,0 +1,415 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use notification_services::escape_html;
+use rec_gov::{FacilityType, Provider, SiteFilters};
+use serde::Serialize;
+use sqlx::types::Json;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::scan_service::ScanService;
+use crate::scan_types::{CreateScanRequest, CreatedScan, ScanError};
+
+/// Characters share slugs are written with
+const SLUG_ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Public link to a scan
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanShareLink {
+    /// Scan shared
+    pub scan_id: Uuid,
+    /// Slug identifying the link
+    pub slug: String,
+    /// Public page of the scan, `{base_url}/s/{slug}`
+    pub url: String,
+    /// Scans other users cloned from the link
+    pub clone_count: i32,
+}
+
+/// What a share link shows of a scan. The owner, their price limit, alert preferences,
+/// and organization stay private.
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedScan {
+    /// Slug of the share link
+    pub slug: String,
+    /// ID of the campground
+    pub campground_id: String,
+    /// Name of the campground
+    pub campground_name: String,
+    /// Reservation system the campground is booked through
+    pub provider: String,
+    /// Kind of inventory watched
+    pub facility_type: String,
+    /// Check-in date
+    pub check_in_date: NaiveDate,
+    /// Check-out date
+    pub check_out_date: NaiveDate,
+    /// Number of nights
+    pub nights: i32,
+    /// Days the stay may shift earlier or later
+    pub flexibility_days: i32,
+    /// Site features required
+    pub site_filters: SiteFilters,
+    /// Scan status (active, paused, completed, cancelled, expired)
+    pub status: String,
+    /// Scans other users cloned from the link
+    pub clone_count: i32,
+    /// When the link was created
+    pub shared_at: Option<DateTime<Utc>>,
+}
+
+impl SharedScan {
+    /// Request creating the same scan in another account
+    pub fn clone_request(&self) -> CreateScanRequest {
+        CreateScanRequest {
+            campground_id: self.campground_id.clone(),
+            campground_name: self.campground_name.clone(),
+            check_in_date: self.check_in_date,
+            check_out_date: self.check_out_date,
+            organization_id: None,
+            facility_type: FacilityType::parse(&self.facility_type).unwrap_or_default(),
+            provider: Provider::parse(&self.provider).unwrap_or_default(),
+            flexibility_days: self.flexibility_days,
+            max_price: None,
+            site_filters: self.site_filters.clone(),
+            quick_book: false,
+        }
+    }
+}
+
+/// Public read-only links to scans, and cloning shared scans into other accounts
+#[derive(Debug, Clone)]
+pub struct ScanSharing {
+    pool: PgPool,
+}
+
+impl ScanSharing {
+    /// Creates the service backed by the given pool
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Shares a scan, or returns its existing link. Only the scan's owner can share it;
+    /// organization members who can see it can't.
+    pub async fn share(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+        base_url: &str,
+    ) -> Result<ScanShareLink, ScanError> {
+        let owned: Option<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM user_scans WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+        )
+        .bind(scan_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        if owned.is_none() {
+            return Err(ScanError::NotFound);
+        }
+
+        // A concurrent request may have shared the scan first; its slug wins
+        let row = sqlx::query(
+            r#"
+            WITH created AS (
+                INSERT INTO scan_shares (slug, scan_id, created_by)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (scan_id) DO NOTHING
+                RETURNING slug, clone_count
+            )
+            SELECT slug, clone_count FROM created
+            UNION ALL
+            SELECT slug, clone_count FROM scan_shares WHERE scan_id = $2
+            LIMIT 1
+            "#,
+        )
+        .bind(share_slug())
+        .bind(scan_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let slug: String = row.get("slug");
+
+        Ok(ScanShareLink {
+            scan_id: *scan_id,
+            url: share_url(base_url, &slug),
+            slug,
+            clone_count: row.get("clone_count"),
+        })
+    }
+
+    /// Removes a scan's share link; the link stops working right away. Returns `false`
+    /// when the scan wasn't shared.
+    pub async fn unshare(&self, user_id: &Uuid, scan_id: &Uuid) -> Result<bool, ScanError> {
+        let owned: Option<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM user_scans WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+        )
+        .bind(scan_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        if owned.is_none() {
+            return Err(ScanError::NotFound);
+        }
+
+        let removed = sqlx::query("DELETE FROM scan_shares WHERE scan_id = $1")
+            .bind(scan_id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+        Ok(removed > 0)
+    }
+
+    /// The scan behind a share link
+    pub async fn shared_scan(&self, slug: &str) -> Result<SharedScan, ScanError> {
+        let row = sqlx::query(
+            r#"
+            SELECT ss.slug, ss.clone_count, ss.created_at AS shared_at,
+                   us.campground_id, us.provider, us.facility_type, us.check_in_date,
+                   us.check_out_date, us.nights, us.flexibility_days, us.site_filters,
+                   us.status, c.name AS campground_name
+            FROM scan_shares ss
+            JOIN user_scans us ON us.id = ss.scan_id
+            LEFT JOIN campgrounds c ON c.id = us.campground_id
+            WHERE ss.slug = $1 AND us.deleted_at IS NULL
+            "#,
+        )
+        .bind(slug)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(ScanError::NotFound)?;
+
+        Ok(SharedScan {
+            slug: row.get("slug"),
+            campground_id: row.get("campground_id"),
+            campground_name: row
+                .get::<Option<String>, _>("campground_name")
+                .unwrap_or_else(|| "Unknown Campground".to_string()),
+            provider: row.get("provider"),
+            facility_type: row.get("facility_type"),
+            check_in_date: row.get("check_in_date"),
+            check_out_date: row.get("check_out_date"),
+            nights: row.get("nights"),
+            flexibility_days: row.get("flexibility_days"),
+            site_filters: row.get::<Json<SiteFilters>, _>("site_filters").0,
+            status: row.get("status"),
+            clone_count: row.get("clone_count"),
+            shared_at: row.get("shared_at"),
+        })
+    }
+
+    /// Creates a copy of a shared scan in `user_id`'s account: same campground, stay,
+    /// facility type, flexibility, and site filters. It goes through the same checks
+    /// and plan quota as any new scan, and counts toward the link's clones.
+    pub async fn clone_scan(
+        &self,
+        scan_service: &ScanService,
+        user_id: &Uuid,
+        shared: &SharedScan,
+    ) -> Result<CreatedScan, ScanError> {
+        let (source_id, owner_id): (Uuid, Uuid) = sqlx::query_as(
+            r#"
+            SELECT us.id, us.user_id
+            FROM scan_shares ss
+            JOIN user_scans us ON us.id = ss.scan_id
+            WHERE ss.slug = $1 AND us.deleted_at IS NULL
+            "#,
+        )
+        .bind(&shared.slug)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(ScanError::NotFound)?;
+
+        if owner_id == *user_id {
+            return Err(ScanError::Validation(
+                "This scan is already yours".to_string(),
+            ));
+        }
+
+        let created = scan_service
+            .create_scan(user_id, &shared.clone_request())
+            .await?;
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("UPDATE user_scans SET cloned_from = $1 WHERE id = $2")
+            .bind(source_id)
+            .bind(created.scan.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE scan_shares SET clone_count = clone_count + 1 WHERE slug = $1")
+            .bind(&shared.slug)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(created)
+    }
+}
+
+/// Public page of a share link
+pub fn share_url(base_url: &str, slug: &str) -> String {
+    format!("{}/s/{}", base_url.trim_end_matches('/'), slug)
+}
+
+/// Random 11-character slug (62 random bits)
+fn share_slug() -> String {
+    // The low 64 bits of a v4 UUID hold 62 random bits and the variant
+    let mut bits = Uuid::new_v4().as_u128() as u64;
+    let mut slug = String::with_capacity(11);
+
+    for _ in 0..11 {
+        slug.push(SLUG_ALPHABET[(bits % 62) as usize] as char);
+        bits /= 62;
+    }
+
+    slug
+}
+
+/// Read-only HTML page of a shared scan
+pub fn shared_scan_page(scan: &SharedScan) -> String {
+    let flexibility = if scan.flexibility_days > 0 {
+        format!(
+            "<p>Flexible by up to {} days either way</p>",
+            scan.flexibility_days
+        )
+    } else {
+        String::new()
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>{name} - CampTracker</title>
+    <style>
+        body {{ font-family: Arial, sans-serif; text-align: center; padding: 50px; background: #f0f9ff; }}
+        .container {{ max-width: 500px; margin: 0 auto; background: white; padding: 30px; border-radius: 10px; box-shadow: 0 4px 6px rgba(0,0,0,0.1); }}
+        h1 {{ color: #2c3e50; }}
+        .status {{ display: inline-block; padding: 4px 12px; border-radius: 12px; background: #e0f2fe; color: #075985; }}
+        .button {{ background: #4a6741; color: white; padding: 12px 24px; text-decoration: none; border-radius: 8px; display: inline-block; margin-top: 20px; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>{name}</h1>
+        <p>{check_in} to {check_out} ({nights} nights)</p>
+        {flexibility}
+        <p><span class="status">{status}</span></p>
+        <p>Someone is watching this campground for open sites with CampTracker.</p>
+        <a href="/" class="button">Watch it too on CampTracker</a>
+    </div>
+</body>
+</html>
+"#,
+        name = escape_html(&scan.campground_name),
+        check_in = scan.check_in_date.format("%b %-d, %Y"),
+        check_out = scan.check_out_date.format("%b %-d, %Y"),
+        nights = scan.nights,
+        flexibility = flexibility,
+        status = escape_html(&scan.status),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use postgres::test_fixtures::insert_user;
+
+    /// A scan shared by a new user, with the link
+    async fn shared_scan(pool: &PgPool, service: &ScanService) -> (Uuid, ScanShareLink) {
+        let owner = insert_user(pool, "owner@example.com").await;
+        let check_in = Utc::now().date_naive() + Duration::days(30);
+        let request: CreateScanRequest = serde_json::from_value(serde_json::json!({
+            "campground_id": "232447",
+            "campground_name": "Upper Pines",
+            "check_in_date": check_in,
+            "check_out_date": check_in + Duration::days(2),
+            "flexibility_days": 1,
+        }))
+        .unwrap();
+        let scan_id = service.create_scan(&owner, &request).await.unwrap().scan.id;
+
+        let link = ScanSharing::new(pool.clone())
+            .share(&owner, &scan_id, "https://example.com")
+            .await
+            .unwrap();
+
+        (owner, link)
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn clones_count_toward_the_link(pool: PgPool) {
+        let service = ScanService::new(pool.clone());
+        let sharing = ScanSharing::new(pool.clone());
+        let (owner, link) = shared_scan(&pool, &service).await;
+        assert_eq!(link.clone_count, 0);
+
+        let shared = sharing.shared_scan(&link.slug).await.unwrap();
+        for email in ["first@example.com", "second@example.com"] {
+            let user_id = insert_user(&pool, email).await;
+            let created = sharing
+                .clone_scan(&service, &user_id, &shared)
+                .await
+                .unwrap();
+
+            assert_eq!(created.scan.user_id, user_id);
+            assert_eq!(created.scan.flexibility_days, 1);
+            let cloned_from: Option<Uuid> =
+                sqlx::query_scalar("SELECT cloned_from FROM user_scans WHERE id = $1")
+                    .bind(created.scan.id)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(cloned_from, Some(link.scan_id));
+        }
+
+        // The owner can't clone their own scan, and that doesn't count
+        assert!(matches!(
+            sharing.clone_scan(&service, &owner, &shared).await,
+            Err(ScanError::Validation(_))
+        ));
+
+        assert_eq!(
+            sharing.shared_scan(&link.slug).await.unwrap().clone_count,
+            2
+        );
+        let again = sharing
+            .share(&owner, &link.scan_id, "https://example.com")
+            .await
+            .unwrap();
+        assert_eq!((again.slug, again.clone_count), (link.slug, 2));
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn revoked_links_cannot_be_cloned(pool: PgPool) {
+        let service = ScanService::new(pool.clone());
+        let sharing = ScanSharing::new(pool.clone());
+        let (owner, link) = shared_scan(&pool, &service).await;
+        let shared = sharing.shared_scan(&link.slug).await.unwrap();
+        let user_id = insert_user(&pool, "late@example.com").await;
+
+        assert!(sharing.unshare(&owner, &link.scan_id).await.unwrap());
+        assert!(!sharing.unshare(&owner, &link.scan_id).await.unwrap());
+
+        assert!(matches!(
+            sharing.shared_scan(&link.slug).await,
+            Err(ScanError::NotFound)
+        ));
+        assert!(matches!(
+            sharing.clone_scan(&service, &user_id, &shared).await,
+            Err(ScanError::NotFound)
+        ));
+        let scans: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM user_scans WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(scans, 0);
+    }
+}
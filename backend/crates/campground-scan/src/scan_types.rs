@@ -1,12 +1,20 @@
 use chrono::{DateTime, NaiveDate, Utc};
+use i18n::{FluentArgs, Locale, Tz, current_locale, message, message_with};
+use rec_gov::{FacilityType, Provider, SiteFilters};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::scan_health::ScanHealth;
+use crate::seasonal_closure::SeasonalClosure;
+
+/// Most days a scan's stay may shift earlier or later
+pub const MAX_FLEXIBILITY_DAYS: i32 = 7;
+
 /// Request structure for creating a new campground scan
 #[derive(Debug, Deserialize, Validate)]
 pub struct CreateScanRequest {
-    /// ID of the campground to scan (RIDB facility ID)
+    /// ID of the campground to scan (RIDB facility ID, or ReserveCalifornia facility ID)
     #[validate(length(min = 1, message = "Campground ID is required"))]
     pub campground_id: String,
 
@@ -19,6 +27,36 @@ pub struct CreateScanRequest {
 
     /// Check-out date for the camping reservation
     pub check_out_date: NaiveDate,
+
+    /// Organization to share the scan with, if any
+    #[serde(default)]
+    pub organization_id: Option<Uuid>,
+
+    /// Kind of inventory to watch (campsite, cabin, lookout, group site)
+    #[serde(default)]
+    pub facility_type: FacilityType,
+
+    /// Reservation system the campground is booked through (defaults to recreation.gov)
+    #[serde(default)]
+    pub provider: Provider,
+
+    /// Days the stay may shift earlier or later, up to `MAX_FLEXIBILITY_DAYS`
+    #[serde(default)]
+    pub flexibility_days: i32,
+
+    /// Highest nightly price to alert about, in whole dollars; no limit when omitted
+    #[validate(range(min = 1, message = "Max price must be at least $1 a night"))]
+    #[serde(default)]
+    pub max_price: Option<i32>,
+
+    /// Site features to require (hookups, vehicle length, pets); any site when omitted
+    #[serde(default)]
+    pub site_filters: SiteFilters,
+
+    /// Whether alerts link straight to each open site's booking page with the dates
+    /// filled in
+    #[serde(default)]
+    pub quick_book: bool,
 }
 
 /// Response structure for creating a scan
@@ -42,6 +80,62 @@ pub struct CreateScanResponse {
     pub notification_sent: bool,
     /// When the scan was created
     pub created_at: DateTime<Utc>,
+    /// Organization the scan is shared with, if any
+    pub organization_id: Option<Uuid>,
+    /// Kind of inventory watched (`campsite`, `cabin`, `lookout`, `group_site`)
+    pub facility_type: String,
+    /// Reservation system (`recreation_gov`, `reserve_california`)
+    pub provider: String,
+    /// Days the stay may shift earlier or later
+    pub flexibility_days: i32,
+    /// Highest nightly price alerted about, in whole dollars
+    pub max_price: Option<i32>,
+    /// Site features required
+    pub site_filters: SiteFilters,
+    /// Whether alerts link straight to each open site's booking page
+    pub quick_book: bool,
+    /// Advice about the scan that didn't stop it from being created
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<ScanWarning>,
+}
+
+/// Advice returned with a newly created scan
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScanWarning {
+    /// The user has several scans on one campground, which doesn't make it polled more often
+    RedundantCampgroundScans {
+        /// Campground the scans watch
+        campground_id: String,
+        /// The user's active and paused scans on it, including the new one
+        scan_count: i64,
+        /// Explanation for the user
+        message: String,
+        /// Groups of scans that one flexible scan could replace
+        consolidate: Vec<ConsolidationSuggestion>,
+    },
+}
+
+/// Scans with the same stay length that one flexible scan would cover
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsolidationSuggestion {
+    /// Scans the suggested scan would replace
+    pub scan_ids: Vec<Uuid>,
+    /// Check-in date of the suggested scan
+    pub check_in_date: NaiveDate,
+    /// Check-out date of the suggested scan
+    pub check_out_date: NaiveDate,
+    /// Flexibility of the suggested scan, in days
+    pub flexibility_days: i32,
+}
+
+/// A newly created scan, with any advice about it
+#[derive(Debug)]
+pub struct CreatedScan {
+    /// The scan
+    pub scan: UserScan,
+    /// Advice about the scan
+    pub warnings: Vec<ScanWarning>,
 }
 
 /// Structure representing a user scan from the database
@@ -69,6 +163,21 @@ pub struct UserScan {
     pub updated_at: DateTime<Utc>,
     /// When the scan expires (optional)
     pub expires_at: Option<DateTime<Utc>>,
+    /// Organization the scan is shared with, if any
+    pub organization_id: Option<Uuid>,
+    /// Kind of inventory watched (`campsite`, `cabin`, `lookout`, `group_site`)
+    pub facility_type: String,
+    /// Reservation system (`recreation_gov`, `reserve_california`)
+    pub provider: String,
+    /// Days the stay may shift earlier or later
+    pub flexibility_days: i32,
+    /// Highest nightly price alerted about, in whole dollars
+    pub max_price: Option<i32>,
+    /// Site features required
+    #[sqlx(json)]
+    pub site_filters: SiteFilters,
+    /// Whether alerts link straight to each open site's booking page
+    pub quick_book: bool,
 }
 
 /// Enhanced user scan with campground information
@@ -96,6 +205,38 @@ pub struct UserScanWithCampground {
     pub updated_at: DateTime<Utc>,
     /// When the scan expires (optional)
     pub expires_at: Option<DateTime<Utc>>,
+    /// Organization the scan is shared with, if any
+    pub organization_id: Option<Uuid>,
+    /// Kind of inventory watched (`campsite`, `cabin`, `lookout`, `group_site`)
+    pub facility_type: String,
+    /// Reservation system (`recreation_gov`, `reserve_california`)
+    pub provider: String,
+    /// Days the stay may shift earlier or later
+    pub flexibility_days: i32,
+    /// Highest nightly price alerted about, in whole dollars
+    pub max_price: Option<i32>,
+    /// Site features required
+    pub site_filters: SiteFilters,
+    /// Whether alerts link straight to each open site's booking page
+    pub quick_book: bool,
+    /// What came of the scan (`booked`, `missed`, `gave_up`), if its owner said
+    pub outcome: Option<String>,
+    /// When the outcome was recorded
+    pub outcome_at: Option<DateTime<Utc>>,
+    /// Trip the scan belongs to, if any
+    pub trip_id: Option<Uuid>,
+    /// When the scan's campground was last polled
+    pub last_polled: Option<DateTime<Utc>>,
+    /// When the campground is polled next; `None` while the scan isn't active or its
+    /// polling is paused
+    pub next_poll_at: Option<DateTime<Utc>>,
+    /// Why the latest availability check failed, while the campground's polls are failing
+    pub last_error: Option<String>,
+    /// Whether the campground can be checked, from its polling errors
+    pub health: ScanHealth,
+    /// Set when the campground has shown no reservable site on any night of the stay for
+    /// several polls, with a suggestion for better dates when its season is known
+    pub seasonal_closure: Option<SeasonalClosure>,
 }
 
 /// Request structure for updating a scan
@@ -106,6 +247,198 @@ pub struct UpdateScanRequest {
     pub status: String,
 }
 
+/// Request structure for changing a scan's stay or filters; omitted fields are kept
+#[derive(Debug, Default, Deserialize)]
+pub struct PatchScanRequest {
+    /// New check-in date
+    pub check_in_date: Option<NaiveDate>,
+    /// New check-out date
+    pub check_out_date: Option<NaiveDate>,
+    /// New kind of inventory to watch
+    pub facility_type: Option<FacilityType>,
+    /// New number of days the stay may shift earlier or later
+    pub flexibility_days: Option<i32>,
+    /// New highest nightly price in whole dollars; 0 removes the limit
+    pub max_price: Option<i32>,
+    /// New site features to require, replacing the current ones
+    pub site_filters: Option<SiteFilters>,
+    /// Whether alerts should link straight to each open site's booking page
+    pub quick_book: Option<bool>,
+}
+
+/// What came of a scan, as reported by its owner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanOutcome {
+    /// The user booked a site; the scan is completed
+    Booked,
+    /// The sites were gone by the time the user tried; the scan keeps running
+    Missed,
+    /// The user stopped looking; the scan is cancelled
+    GaveUp,
+}
+
+impl ScanOutcome {
+    /// Value stored in `user_scans.outcome`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanOutcome::Booked => "booked",
+            ScanOutcome::Missed => "missed",
+            ScanOutcome::GaveUp => "gave_up",
+        }
+    }
+
+    /// Status an active or paused scan moves to, if the outcome ends it
+    pub fn final_status(&self) -> Option<&'static str> {
+        match self {
+            ScanOutcome::Booked => Some("completed"),
+            ScanOutcome::Missed => None,
+            ScanOutcome::GaveUp => Some("cancelled"),
+        }
+    }
+}
+
+/// Request structure for recording what came of a scan
+#[derive(Debug, Deserialize)]
+pub struct ScanOutcomeRequest {
+    /// The outcome
+    pub outcome: ScanOutcome,
+}
+
+/// Request structure for moving a scan to a different campground
+#[derive(Debug, Deserialize)]
+pub struct RemapScanRequest {
+    /// Campground to move the scan to; when omitted, suggestions are returned instead
+    pub campground_id: Option<String>,
+    /// Name of the target campground, needed if it isn't known locally yet
+    pub campground_name: Option<String>,
+}
+
+/// A reservable campground the user may have meant to scan
+#[derive(Debug, Serialize)]
+pub struct RemapSuggestion {
+    /// ID of the suggested campground
+    pub campground_id: String,
+    /// Name of the suggested campground
+    pub campground_name: String,
+    /// Distance from the scanned facility, if both locations are known
+    pub distance_miles: Option<f64>,
+    /// How likely this is the intended campground, from 0 to 1
+    pub score: f64,
+}
+
+/// Response structure for remap suggestions
+#[derive(Debug, Serialize)]
+pub struct RemapSuggestionsResponse {
+    /// ID of the scan being remapped
+    pub scan_id: Uuid,
+    /// Campground the scan currently points at
+    pub current_campground_id: String,
+    /// Suggested campgrounds, most likely first
+    pub suggestions: Vec<RemapSuggestion>,
+}
+
+/// A user who should be notified about a scan: its creator, plus every member of the
+/// organization it is shared with
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanRecipient {
+    /// ID of the user
+    pub user_id: Uuid,
+    /// Email address of the user
+    pub email: String,
+    /// Phone number of the user, if any
+    pub phone: Option<String>,
+    /// Whether the user wants email notifications
+    pub email_enabled: bool,
+    /// Whether the user wants SMS notifications
+    pub sms_enabled: bool,
+    /// Language the user gets notifications in
+    pub locale: Locale,
+    /// Time zone the user reads notification dates in
+    pub timezone: Tz,
+}
+
+/// Request structure for creating a permit scan
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreatePermitScanRequest {
+    /// Recreation.gov permit ID (e.g. `234652` for Half Dome)
+    #[validate(length(min = 1, message = "Permit ID is required"))]
+    pub permit_id: String,
+
+    /// Name of the permit for display purposes
+    #[validate(length(min = 1, message = "Permit name is required"))]
+    pub permit_name: String,
+
+    /// Division (trailhead or zone) to watch; every division when omitted
+    pub division_id: Option<String>,
+
+    /// First acceptable entry date
+    pub start_date: NaiveDate,
+
+    /// Last acceptable entry date (inclusive)
+    pub end_date: NaiveDate,
+
+    /// Number of permits needed
+    #[validate(range(min = 1, max = 30, message = "Group size must be between 1 and 30"))]
+    #[serde(default = "default_group_size")]
+    pub group_size: i32,
+}
+
+fn default_group_size() -> i32 {
+    1
+}
+
+/// Structure representing a permit scan from the database
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PermitScan {
+    /// Unique identifier for the scan
+    pub id: Uuid,
+    /// ID of the user who created the scan
+    pub user_id: Uuid,
+    /// Recreation.gov permit ID
+    pub permit_id: String,
+    /// Name of the permit
+    pub permit_name: String,
+    /// Division being watched, if limited to one
+    pub division_id: Option<String>,
+    /// First acceptable entry date
+    pub start_date: NaiveDate,
+    /// Last acceptable entry date (inclusive)
+    pub end_date: NaiveDate,
+    /// Number of permits needed
+    pub group_size: i32,
+    /// Current status of the scan
+    pub status: String,
+    /// Whether a notification has been sent
+    pub notification_sent: bool,
+    /// When permit availability was last checked
+    pub last_checked_at: Option<DateTime<Utc>>,
+    /// When the scan was created
+    pub created_at: DateTime<Utc>,
+    /// When the scan was last updated
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Released permit slots matching a permit scan
+#[derive(Debug, Clone, Serialize)]
+pub struct PermitOpening {
+    /// ID of the permit scan
+    pub scan_id: Uuid,
+    /// Division with enough permits
+    pub division_id: String,
+    /// Entry dates with enough permits
+    pub dates: Vec<NaiveDate>,
+}
+
+/// Response structure for listing permit scans
+#[derive(Debug, Serialize)]
+pub struct ListPermitScansResponse {
+    /// Permit scans of the user
+    pub scans: Vec<PermitScan>,
+    /// Total count of scans
+    pub total: i64,
+}
+
 /// Response structure for listing user scans
 #[derive(Debug, Serialize)]
 pub struct ListScansResponse {
@@ -113,6 +446,42 @@ pub struct ListScansResponse {
     pub scans: Vec<UserScanWithCampground>,
     /// Total count of scans
     pub total: i64,
+    /// The user's active scan quota, when listing their own scans
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota: Option<ScanQuota>,
+}
+
+/// Plan tier, with the limits it grants
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Plan {
+    /// Plan ID (`free`, `plus`, `pro`)
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// Active scans allowed at once; unlimited when `None`
+    pub max_active_scans: Option<i32>,
+    /// Whether same-week trips are polled in sniper mode
+    pub sniper_mode: bool,
+}
+
+/// A user's active scan quota
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanQuota {
+    /// Plan ID
+    pub plan: String,
+    /// Active scans allowed at once; unlimited when `None`
+    pub max_active_scans: Option<i32>,
+    /// Scans the user owns that are active
+    pub active_scans: i64,
+    /// Active scans the user may still add; unlimited when `None`
+    pub remaining: Option<i64>,
+}
+
+/// Request structure for changing a user's plan
+#[derive(Debug, Deserialize)]
+pub struct UpdatePlanRequest {
+    /// Plan ID (`free`, `plus`, `pro`)
+    pub plan: String,
 }
 
 /// Custom error type for scan operations
@@ -141,12 +510,50 @@ pub enum ScanError {
     /// Campground not found
     #[error("Campground not found")]
     CampgroundNotFound,
+
+    /// Trip not found
+    #[error("Trip not found")]
+    TripNotFound,
+
+    /// The campground is first-come, first-served and never shows availability
+    #[error("Campground is not reservable: {0}")]
+    NotReservable(String),
+
+    /// The stay is longer than the campground allows
+    #[error("{campground} allows stays of at most {max_nights} nights, not {nights}")]
+    StayTooLong {
+        /// Campground name
+        campground: String,
+        /// Nights requested
+        nights: i64,
+        /// Longest stay the campground allows
+        max_nights: i64,
+    },
+
+    /// Every notification channel the user has enabled failed its deliverability check
+    #[error("No deliverable notification channel")]
+    NoDeliverableChannel,
+
+    /// The user already has as many active scans as their plan allows
+    #[error("Active scan quota of the {plan} plan reached ({limit} scans)")]
+    QuotaExceeded {
+        /// Plan ID
+        plan: String,
+        /// Active scans the plan allows
+        limit: i32,
+    },
+
+    /// The action was requested too often; it can be retried after the given seconds
+    #[error("Rate limited for {0} seconds")]
+    RateLimited(u64),
 }
 
 impl actix_web::ResponseError for ScanError {
     fn error_response(&self) -> actix_web::HttpResponse {
         use actix_web::HttpResponse;
 
+        let locale = current_locale();
+
         match self {
             ScanError::Validation(msg) => HttpResponse::BadRequest().json(serde_json::json!({
                 "error": "validation_error",
@@ -154,23 +561,80 @@ impl actix_web::ResponseError for ScanError {
             })),
             ScanError::NotFound => HttpResponse::NotFound().json(serde_json::json!({
                 "error": "scan_not_found",
-                "message": "Scan not found"
+                "message": message(locale, "scan-not-found")
             })),
             ScanError::Unauthorized => HttpResponse::Forbidden().json(serde_json::json!({
                 "error": "unauthorized",
-                "message": "You are not authorized to access this scan"
+                "message": message(locale, "scan-unauthorized")
             })),
             ScanError::InvalidDateRange => HttpResponse::BadRequest().json(serde_json::json!({
                 "error": "invalid_date_range",
-                "message": "Check-out date must be after check-in date"
+                "message": message(locale, "invalid-date-range")
             })),
             ScanError::CampgroundNotFound => HttpResponse::NotFound().json(serde_json::json!({
                 "error": "campground_not_found",
-                "message": "Campground not found"
+                "message": message(locale, "campground-not-found")
+            })),
+            ScanError::TripNotFound => HttpResponse::NotFound().json(serde_json::json!({
+                "error": "trip_not_found",
+                "message": message(locale, "trip-not-found")
             })),
+            ScanError::NotReservable(campground_name) => {
+                let mut args = FluentArgs::new();
+                args.set("campground", campground_name.as_str());
+
+                HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                    "error": "not_reservable",
+                    "message": message_with(locale, "not-reservable", &args)
+                }))
+            }
+            ScanError::StayTooLong {
+                campground,
+                nights,
+                max_nights,
+            } => {
+                let mut args = FluentArgs::new();
+                args.set("campground", campground.as_str());
+                args.set("nights", *nights);
+                args.set("max", *max_nights);
+
+                HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                    "error": "stay_too_long",
+                    "message": message_with(locale, "stay-too-long", &args),
+                    "nights": nights,
+                    "max_nights": max_nights
+                }))
+            }
+            ScanError::NoDeliverableChannel => HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "no_deliverable_channel",
+                "message": message(locale, "no-deliverable-channel")
+            })),
+            ScanError::QuotaExceeded { plan, limit } => {
+                let mut args = FluentArgs::new();
+                args.set("plan", plan.as_str());
+                args.set("limit", *limit);
+
+                HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "quota_exceeded",
+                    "message": message_with(locale, "quota-exceeded", &args),
+                    "plan": plan,
+                    "limit": limit
+                }))
+            }
+            ScanError::RateLimited(retry_after_secs) => {
+                let mut args = FluentArgs::new();
+                args.set("minutes", retry_after_secs.div_ceil(60));
+
+                HttpResponse::TooManyRequests()
+                    .insert_header(("Retry-After", retry_after_secs.to_string()))
+                    .json(serde_json::json!({
+                        "error": "rate_limited",
+                        "message": message_with(locale, "rate-limited", &args)
+                    }))
+            }
             _ => HttpResponse::InternalServerError().json(serde_json::json!({
                 "error": "internal_error",
-                "message": "An internal error occurred"
+                "message": message(locale, "internal-error")
             })),
         }
     }
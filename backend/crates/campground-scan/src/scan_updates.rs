@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Updates buffered per subscriber before the slowest ones start missing some
+const SCAN_UPDATE_BUFFER: usize = 256;
+
+/// Live update about a scan, pushed to its owner's connected dashboards
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScanUpdate {
+    /// Sites opened up for the scan's stay
+    AvailabilityHit {
+        /// Scan ID
+        scan_id: Uuid,
+        /// Campground checked
+        campground_id: String,
+        /// Names of the open sites
+        sites: Vec<String>,
+        /// When the check ran
+        polled_at: DateTime<Utc>,
+    },
+    /// The scan's campground was checked
+    PollHeartbeat {
+        /// Scan ID
+        scan_id: Uuid,
+        /// Campground checked
+        campground_id: String,
+        /// Number of open sites found, or none if the check failed
+        open_sites: Option<usize>,
+        /// When the check ran
+        polled_at: DateTime<Utc>,
+    },
+}
+
+/// Fans scan updates out to connected clients. Cloning shares the same channel.
+#[derive(Clone)]
+pub struct ScanUpdateHub {
+    sender: broadcast::Sender<(Uuid, ScanUpdate)>,
+}
+
+impl Default for ScanUpdateHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScanUpdateHub {
+    /// Creates a hub with no subscribers
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(SCAN_UPDATE_BUFFER);
+        Self { sender }
+    }
+
+    /// Publishes an update for the given user's clients. Updates published while nobody is
+    /// connected are dropped.
+    pub fn publish(&self, user_id: Uuid, update: ScanUpdate) {
+        let _ = self.sender.send((user_id, update));
+    }
+
+    /// Subscribes to every update published from now on, tagged with the user it's for
+    pub fn subscribe(&self) -> broadcast::Receiver<(Uuid, ScanUpdate)> {
+        self.sender.subscribe()
+    }
+}
@@ -0,0 +1,414 @@
+use app_config::AppConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+
+/// Minutes of upstream API calls the error rate is computed over
+pub const WATCHDOG_API_WINDOW_MINUTES: i32 = 15;
+
+/// Fewest calls in the window before the error rate is trusted, so a couple of failed
+/// calls on a quiet night don't page anyone
+const MIN_API_CALLS: i64 = 20;
+
+/// Thresholds the watchdog checks the scan system against
+#[derive(Debug, Clone, Copy)]
+pub struct WatchdogSettings {
+    /// Minutes without a completed poll before polling counts as stalled; 0 disables
+    pub poll_stale_minutes: u32,
+    /// Percentage of failed upstream calls that counts as an outage; 0 disables
+    pub api_error_rate_percent: u32,
+    /// Blocked recreation.gov sessions that count as an outage; 0 disables
+    pub session_blocks: u32,
+}
+
+impl WatchdogSettings {
+    /// Reads the thresholds from `WATCHDOG_*` settings
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            poll_stale_minutes: config.watchdog_poll_stale_minutes,
+            api_error_rate_percent: config.watchdog_api_error_rate_percent,
+            session_blocks: config.watchdog_session_blocks,
+        }
+    }
+}
+
+/// Something wrong with the scan system
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WatchdogProblem {
+    /// Campgrounds are due but no poll has completed for too long
+    PollsStalled {
+        /// When a poll last completed, if ever
+        last_poll: Option<DateTime<Utc>>,
+        /// Threshold that was crossed
+        stale_after_minutes: u32,
+    },
+    /// Too many upstream API calls are failing
+    ApiErrors {
+        /// Calls in the window
+        calls: i64,
+        /// Calls that failed or returned an error status
+        errors: i64,
+        /// Threshold that was crossed
+        threshold_percent: u32,
+    },
+    /// Recreation.gov keeps blocking sessions, so starting over as a new visitor isn't
+    /// getting calls through
+    SessionsBlocked {
+        /// Calls blocked in the window, each of which renewed its session
+        blocked: i64,
+        /// Threshold that was crossed
+        threshold: u32,
+    },
+}
+
+impl WatchdogProblem {
+    /// One line describing the problem, for alerts
+    pub fn describe(&self) -> String {
+        match self {
+            WatchdogProblem::PollsStalled {
+                last_poll,
+                stale_after_minutes,
+            } => match last_poll {
+                Some(last_poll) => format!(
+                    "No campground poll has completed since {} (threshold {} minutes)",
+                    last_poll.format("%Y-%m-%d %H:%M UTC"),
+                    stale_after_minutes
+                ),
+                None => format!(
+                    "Campgrounds have been due for over {} minutes and none has been polled",
+                    stale_after_minutes
+                ),
+            },
+            WatchdogProblem::ApiErrors {
+                calls,
+                errors,
+                threshold_percent,
+            } => format!(
+                "{} of {} upstream API calls failed in the last {} minutes (threshold {}%)",
+                errors, calls, WATCHDOG_API_WINDOW_MINUTES, threshold_percent
+            ),
+            WatchdogProblem::SessionsBlocked { blocked, threshold } => format!(
+                "Recreation.gov blocked {} calls in the last {} minutes, starting a new session each time (threshold {})",
+                blocked, WATCHDOG_API_WINDOW_MINUTES, threshold
+            ),
+        }
+    }
+}
+
+/// The watchdog's latest verdict on the scan system
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemStatus {
+    /// `ok` or `down`
+    pub status: String,
+    /// What's wrong, when down
+    pub problems: Vec<WatchdogProblem>,
+    /// When the watchdog last checked, `None` before its first round
+    pub checked_at: Option<DateTime<Utc>>,
+    /// When the status last changed
+    pub changed_at: Option<DateTime<Utc>>,
+}
+
+/// Change of the scan system's status, which is what gets alerted on
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatusChange {
+    /// The system went down
+    Down(Vec<WatchdogProblem>),
+    /// The system is back up
+    Recovered,
+}
+
+/// Figures and status of the scan system, for the admin dashboard
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanSystemStats {
+    /// `ok` or `down`, as of the watchdog's last check
+    pub system_status: String,
+    /// What's wrong, when down
+    pub problems: Vec<WatchdogProblem>,
+    /// When the watchdog last checked
+    pub checked_at: Option<DateTime<Utc>>,
+    /// When the status last changed
+    pub status_changed_at: Option<DateTime<Utc>>,
+    /// Active scans
+    pub active_scans: i64,
+    /// Campgrounds with active scans
+    pub active_polling_jobs: i64,
+    /// Campgrounds an admin disabled or paused polling of
+    pub disabled_polling_jobs: i64,
+    /// Campgrounds whose last poll failed
+    pub erroring_polling_jobs: i64,
+    /// When a poll last completed
+    pub last_poll: Option<DateTime<Utc>>,
+    /// Upstream API calls in the last [`WATCHDOG_API_WINDOW_MINUTES`] minutes
+    pub recent_api_calls: i64,
+    /// Of those, calls that failed or returned an error status
+    pub recent_api_errors: i64,
+}
+
+/// Watches the scan system for stalled polling and upstream API failures. Its verdict is
+/// kept in the database so every server instance reports the same status.
+#[derive(Debug, Clone)]
+pub struct ScanWatchdog {
+    pool: PgPool,
+    settings: WatchdogSettings,
+}
+
+impl ScanWatchdog {
+    /// Creates a watchdog with the given thresholds
+    pub fn new(pool: PgPool, settings: WatchdogSettings) -> Self {
+        Self { pool, settings }
+    }
+
+    /// Checks polling and the upstream error rate against the thresholds
+    pub async fn check(&self) -> Result<Vec<WatchdogProblem>, sqlx::Error> {
+        let mut problems = Vec::new();
+
+        if self.settings.poll_stale_minutes > 0 {
+            let row = sqlx::query(
+                r#"
+                SELECT MAX(last_polled) AS last_poll,
+                       COALESCE(MAX(last_polled), MIN(next_poll_at))
+                           < NOW() - make_interval(mins => $1) AS stalled
+                FROM polling_jobs
+                WHERE active_scan_count > 0
+                  AND NOT paused
+                  AND (disabled_until IS NULL OR disabled_until <= NOW())
+                "#,
+            )
+            .bind(self.settings.poll_stale_minutes as i32)
+            .fetch_one(&self.pool)
+            .await?;
+
+            if row.get::<Option<bool>, _>("stalled").unwrap_or(false) {
+                problems.push(WatchdogProblem::PollsStalled {
+                    last_poll: row.get("last_poll"),
+                    stale_after_minutes: self.settings.poll_stale_minutes,
+                });
+            }
+        }
+
+        if self.settings.api_error_rate_percent > 0 {
+            let (calls, errors) = self.recent_api_calls().await?;
+
+            if calls >= MIN_API_CALLS
+                && errors * 100 > calls * self.settings.api_error_rate_percent as i64
+            {
+                problems.push(WatchdogProblem::ApiErrors {
+                    calls,
+                    errors,
+                    threshold_percent: self.settings.api_error_rate_percent,
+                });
+            }
+        }
+
+        if self.settings.session_blocks > 0 {
+            let blocked = self.recent_session_blocks().await?;
+
+            if blocked >= self.settings.session_blocks as i64 {
+                problems.push(WatchdogProblem::SessionsBlocked {
+                    blocked,
+                    threshold: self.settings.session_blocks,
+                });
+            }
+        }
+
+        Ok(problems)
+    }
+
+    /// Stores the verdict of a check, returning the change when the status flipped
+    pub async fn record(
+        &self,
+        problems: &[WatchdogProblem],
+    ) -> Result<Option<StatusChange>, sqlx::Error> {
+        let status = if problems.is_empty() { "ok" } else { "down" };
+
+        let previous: Option<String> = sqlx::query_scalar(
+            r#"
+            WITH previous AS (SELECT status FROM scan_system_status WHERE id)
+            INSERT INTO scan_system_status (id, status, problems, checked_at, changed_at)
+            VALUES (TRUE, $1, $2, NOW(), NOW())
+            ON CONFLICT (id) DO UPDATE
+            SET status = EXCLUDED.status,
+                problems = EXCLUDED.problems,
+                checked_at = NOW(),
+                changed_at = CASE
+                    WHEN scan_system_status.status = EXCLUDED.status THEN scan_system_status.changed_at
+                    ELSE NOW()
+                END
+            RETURNING (SELECT status FROM previous)
+            "#,
+        )
+        .bind(status)
+        .bind(serde_json::to_value(problems).unwrap_or_default())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(match (previous.as_deref().unwrap_or("ok"), status) {
+            ("ok", "down") => Some(StatusChange::Down(problems.to_vec())),
+            ("down", "ok") => Some(StatusChange::Recovered),
+            _ => None,
+        })
+    }
+
+    /// The latest verdict; `ok` before the watchdog's first round
+    pub async fn status(&self) -> Result<SystemStatus, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT status, problems, checked_at, changed_at FROM scan_system_status WHERE id",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(match row {
+            Some(row) => SystemStatus {
+                status: row.get("status"),
+                problems: serde_json::from_value(row.get("problems")).unwrap_or_default(),
+                checked_at: row.get("checked_at"),
+                changed_at: row.get("changed_at"),
+            },
+            None => SystemStatus {
+                status: "ok".to_string(),
+                problems: Vec::new(),
+                checked_at: None,
+                changed_at: None,
+            },
+        })
+    }
+
+    /// The watchdog's status with the figures behind it
+    pub async fn stats(&self) -> Result<ScanSystemStats, sqlx::Error> {
+        let status = self.status().await?;
+        let (recent_api_calls, recent_api_errors) = self.recent_api_calls().await?;
+
+        let row = sqlx::query(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM user_scans WHERE status = 'active' AND deleted_at IS NULL) AS active_scans,
+                COUNT(*) FILTER (WHERE active_scan_count > 0) AS active_polling_jobs,
+                COUNT(*) FILTER (WHERE paused OR disabled_until > NOW()) AS disabled_polling_jobs,
+                COUNT(*) FILTER (WHERE consecutive_errors > 0) AS erroring_polling_jobs,
+                MAX(last_polled) AS last_poll
+            FROM polling_jobs
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(ScanSystemStats {
+            system_status: status.status,
+            problems: status.problems,
+            checked_at: status.checked_at,
+            status_changed_at: status.changed_at,
+            active_scans: row.get("active_scans"),
+            active_polling_jobs: row.get("active_polling_jobs"),
+            disabled_polling_jobs: row.get("disabled_polling_jobs"),
+            erroring_polling_jobs: row.get("erroring_polling_jobs"),
+            last_poll: row.get("last_poll"),
+            recent_api_calls,
+            recent_api_errors,
+        })
+    }
+
+    /// Upstream calls and failed calls in the last [`WATCHDOG_API_WINDOW_MINUTES`]
+    async fn recent_api_calls(&self) -> Result<(i64, i64), sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT COUNT(*) AS calls,
+                   COUNT(*) FILTER (WHERE status_code IS NULL OR status_code >= 400) AS errors
+            FROM api_call_ledger
+            WHERE called_at > NOW() - make_interval(mins => $1)
+            "#,
+        )
+        .bind(WATCHDOG_API_WINDOW_MINUTES)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok((row.get("calls"), row.get("errors")))
+    }
+
+    /// Calls recreation.gov blocked in the last [`WATCHDOG_API_WINDOW_MINUTES`]. The
+    /// proxy pool renews a route's session on every one of these, so the ledger is the
+    /// record of session failures across instances.
+    async fn recent_session_blocks(&self) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM api_call_ledger
+            WHERE called_at > NOW() - make_interval(mins => $1)
+              AND status_code IN (403, 429)
+            "#,
+        )
+        .bind(WATCHDOG_API_WINDOW_MINUTES)
+        .fetch_one(&self.pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> WatchdogSettings {
+        WatchdogSettings {
+            poll_stale_minutes: 0,
+            api_error_rate_percent: 0,
+            session_blocks: 3,
+        }
+    }
+
+    async fn record_calls(pool: &PgPool, status_code: Option<i32>, count: i32) {
+        sqlx::query(
+            r#"
+            INSERT INTO api_call_ledger (endpoint, status_code, duration_ms)
+            SELECT 'recgov_availability_month', $1, 120 FROM generate_series(1, $2)
+            "#,
+        )
+        .bind(status_code)
+        .bind(count)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn repeatedly_blocked_sessions_take_the_system_down(pool: PgPool) {
+        let watchdog = ScanWatchdog::new(pool.clone(), settings());
+        record_calls(&pool, Some(200), 40).await;
+        record_calls(&pool, Some(403), 1).await;
+        record_calls(&pool, Some(429), 1).await;
+        record_calls(&pool, Some(500), 5).await;
+
+        assert_eq!(watchdog.check().await.unwrap(), []);
+
+        record_calls(&pool, Some(403), 1).await;
+        let problems = watchdog.check().await.unwrap();
+        assert_eq!(
+            problems,
+            [WatchdogProblem::SessionsBlocked {
+                blocked: 3,
+                threshold: 3
+            }]
+        );
+
+        assert_eq!(
+            watchdog.record(&problems).await.unwrap(),
+            Some(StatusChange::Down(problems.clone()))
+        );
+        let status = watchdog.status().await.unwrap();
+        assert_eq!(status.status, "down");
+        assert_eq!(status.problems, problems);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn old_session_blocks_are_forgotten(pool: PgPool) {
+        let watchdog = ScanWatchdog::new(pool.clone(), settings());
+        record_calls(&pool, Some(403), 5).await;
+        sqlx::query("UPDATE api_call_ledger SET called_at = NOW() - INTERVAL '1 hour'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(watchdog.check().await.unwrap(), []);
+    }
+}
@@ -0,0 +1,84 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rec_gov::OperatingSeason;
+use serde::Serialize;
+
+/// Polls in a row that must find every night of a scan's stay closed before its
+/// campground counts as closed for the season
+pub const CLOSED_AFTER_POLLS: i32 = 3;
+
+/// Least time between polls of a campground closed on every night polled, in minutes, so
+/// closed facilities aren't polled all winter but reopening is still seen within a day
+pub const CLOSED_POLL_INTERVAL_MINUTES: i32 = 24 * 60;
+
+/// A scan's campground has reported no reservable site on any night of the stay for
+/// [`CLOSED_AFTER_POLLS`] polls or more, most likely because it's closed for the season
+#[derive(Debug, Clone, Serialize)]
+pub struct SeasonalClosure {
+    /// Since when the stay's nights have shown no reservable site
+    pub closed_since: DateTime<Utc>,
+    /// Months the campground is open, from its RIDB description, when it gives them
+    pub season: Option<OperatingSeason>,
+    /// Whether the operating season confirms the closure, i.e. the stay falls outside it;
+    /// unknown without a season
+    pub outside_season: Option<bool>,
+    /// Earliest check-in date in season on or after the scan's, suggested for moving the
+    /// stay when it starts out of season
+    pub suggested_check_in: Option<NaiveDate>,
+}
+
+impl SeasonalClosure {
+    /// Closure of a stay from `check_in_date` to `check_out_date` closed since
+    /// `closed_since`, cross-checked with the operating season in the campground's
+    /// description
+    pub fn of(
+        closed_since: DateTime<Utc>,
+        description: Option<&str>,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+    ) -> Self {
+        let season = description.and_then(OperatingSeason::parse);
+        let last_night = check_out_date.pred_opt().unwrap_or(check_out_date);
+
+        Self {
+            closed_since,
+            season,
+            outside_season: season
+                .map(|season| !season.contains(check_in_date) || !season.contains(last_night)),
+            suggested_check_in: season
+                .filter(|season| !season.contains(check_in_date))
+                .map(|season| season.next_opening(check_in_date)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closures_are_checked_against_the_season() {
+        let date = |month, day| NaiveDate::from_ymd_opt(2026, month, day).unwrap();
+        let description = "<p>Open late May through September, weather permitting.</p>";
+
+        let winter = SeasonalClosure::of(Utc::now(), Some(description), date(12, 18), date(12, 21));
+        assert_eq!(winter.outside_season, Some(true));
+        assert_eq!(
+            winter.suggested_check_in,
+            NaiveDate::from_ymd_opt(2027, 5, 1)
+        );
+
+        // Closed in season: nothing to suggest, the closure isn't seasonal as far as RIDB says
+        let summer = SeasonalClosure::of(Utc::now(), Some(description), date(7, 3), date(7, 5));
+        assert_eq!(summer.outside_season, Some(false));
+        assert_eq!(summer.suggested_check_in, None);
+
+        let unknown = SeasonalClosure::of(
+            Utc::now(),
+            Some("Shady sites by the river"),
+            date(1, 2),
+            date(1, 4),
+        );
+        assert_eq!(unknown.season, None);
+        assert_eq!(unknown.outside_season, None);
+    }
+}
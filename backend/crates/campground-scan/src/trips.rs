@@ -0,0 +1,491 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool, Row};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::scan_service::{SCAN_FRESHNESS_COLUMNS, SCAN_FRESHNESS_JOINS, scan_from_row};
+use crate::scan_types::{ScanError, UserScanWithCampground};
+
+/// Request body for `POST /api/trips`
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateTripRequest {
+    /// Name of the trip (e.g. "Yosemite, Labor Day")
+    #[validate(length(min = 1, max = 100, message = "Trip names are 1 to 100 characters"))]
+    pub name: String,
+
+    /// People going
+    #[validate(range(min = 1, max = 100, message = "Party size must be between 1 and 100"))]
+    #[serde(default = "default_party_size")]
+    pub party_size: i32,
+
+    /// First night the trip may start
+    pub start_date: NaiveDate,
+
+    /// Last day of the trip; every scan's check-out falls on or before it
+    pub end_date: NaiveDate,
+
+    /// Pause the trip's other active scans once one of them is booked
+    #[serde(default = "default_auto_pause")]
+    pub auto_pause_on_booking: bool,
+}
+
+fn default_party_size() -> i32 {
+    1
+}
+
+fn default_auto_pause() -> bool {
+    true
+}
+
+/// A trip grouping scans of alternative campgrounds for one getaway
+#[derive(Debug, Clone, Serialize)]
+pub struct Trip {
+    /// Trip ID
+    pub id: Uuid,
+    /// Name of the trip
+    pub name: String,
+    /// People going
+    pub party_size: i32,
+    /// First night the trip may start
+    pub start_date: NaiveDate,
+    /// Last day of the trip
+    pub end_date: NaiveDate,
+    /// Whether booking one scan pauses the trip's other active scans
+    pub auto_pause_on_booking: bool,
+    /// When the trip was created
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Where a trip stands, from its scans
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TripStatus {
+    /// No scans yet
+    Planning,
+    /// At least one scan is active
+    Scanning,
+    /// Scans exist but none is active, e.g. all paused
+    Paused,
+    /// One of the scans was booked
+    Booked,
+    /// Every scan ended without a booking
+    Ended,
+}
+
+/// Aggregate of a trip's scans
+#[derive(Debug, Clone, Serialize)]
+pub struct TripProgress {
+    /// Where the trip stands
+    pub status: TripStatus,
+    /// Number of scans in each status
+    pub scans_by_status: BTreeMap<String, i64>,
+    /// The scan that was booked, if any
+    pub booked_scan_id: Option<Uuid>,
+}
+
+impl TripProgress {
+    /// Aggregates a trip's scans, given as (scan ID, status, outcome)
+    pub fn from_scans<'a>(
+        scans: impl IntoIterator<Item = (Uuid, &'a str, Option<&'a str>)>,
+    ) -> Self {
+        let mut scans_by_status = BTreeMap::new();
+        let mut booked_scan_id = None;
+
+        for (id, status, outcome) in scans {
+            *scans_by_status.entry(status.to_string()).or_insert(0) += 1;
+            if outcome == Some("booked") && booked_scan_id.is_none() {
+                booked_scan_id = Some(id);
+            }
+        }
+
+        let status = if booked_scan_id.is_some() {
+            TripStatus::Booked
+        } else if scans_by_status.is_empty() {
+            TripStatus::Planning
+        } else if scans_by_status.contains_key("active") {
+            TripStatus::Scanning
+        } else if scans_by_status.contains_key("paused") {
+            TripStatus::Paused
+        } else {
+            TripStatus::Ended
+        };
+
+        Self {
+            status,
+            scans_by_status,
+            booked_scan_id,
+        }
+    }
+}
+
+/// A trip with the aggregate of its scans
+#[derive(Debug, Clone, Serialize)]
+pub struct TripOverview {
+    /// The trip
+    #[serde(flatten)]
+    pub trip: Trip,
+    /// Aggregate of its scans
+    #[serde(flatten)]
+    pub progress: TripProgress,
+}
+
+/// Response to `GET /api/trips/{id}`: the trip, its aggregate, and its scans
+#[derive(Debug, Serialize)]
+pub struct TripDetails {
+    /// The trip and the aggregate of its scans
+    #[serde(flatten)]
+    pub overview: TripOverview,
+    /// Scans of the trip, soonest check-in first
+    pub scans: Vec<UserScanWithCampground>,
+}
+
+/// Response to `GET /api/trips`
+#[derive(Debug, Serialize)]
+pub struct ListTripsResponse {
+    /// Trips, soonest first
+    pub trips: Vec<TripOverview>,
+}
+
+/// Scan of a trip as (scan ID, status, outcome)
+type TripScan = (Uuid, String, Option<String>);
+
+/// Service for trips and the scans they group
+pub struct TripService {
+    pool: PgPool,
+}
+
+impl TripService {
+    /// Creates a trip service
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates a trip for the user
+    pub async fn create(
+        &self,
+        user_id: &Uuid,
+        request: &CreateTripRequest,
+    ) -> Result<TripOverview, ScanError> {
+        if request.end_date <= request.start_date {
+            return Err(ScanError::InvalidDateRange);
+        }
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO trips (user_id, name, party_size, start_date, end_date, auto_pause_on_booking)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, name, party_size, start_date, end_date, auto_pause_on_booking, created_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(&request.name)
+        .bind(request.party_size)
+        .bind(request.start_date)
+        .bind(request.end_date)
+        .bind(request.auto_pause_on_booking)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(TripOverview {
+            trip: trip_from_row(&row),
+            progress: TripProgress::from_scans([]),
+        })
+    }
+
+    /// Lists the user's trips, soonest first, with the aggregate of their scans
+    pub async fn list(&self, user_id: &Uuid) -> Result<Vec<TripOverview>, ScanError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT t.id, t.name, t.party_size, t.start_date, t.end_date,
+                   t.auto_pause_on_booking, t.created_at,
+                   us.id AS scan_id, us.status, us.outcome
+            FROM trips t
+            LEFT JOIN user_scans us ON us.trip_id = t.id AND us.deleted_at IS NULL
+            WHERE t.user_id = $1
+            ORDER BY t.start_date, t.created_at, t.id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Rows of a trip are adjacent; each carries one of its scans, if any
+        let mut trips: Vec<(Trip, Vec<TripScan>)> = Vec::new();
+        for row in &rows {
+            let id: Uuid = row.get("id");
+            if trips.last().is_none_or(|(trip, _)| trip.id != id) {
+                trips.push((trip_from_row(row), Vec::new()));
+            }
+            if let Some(scan_id) = row.get::<Option<Uuid>, _>("scan_id")
+                && let Some((_, scans)) = trips.last_mut()
+            {
+                scans.push((scan_id, row.get("status"), row.get("outcome")));
+            }
+        }
+
+        Ok(trips
+            .into_iter()
+            .map(|(trip, scans)| TripOverview {
+                trip,
+                progress: TripProgress::from_scans(
+                    scans
+                        .iter()
+                        .map(|(id, status, outcome)| (*id, status.as_str(), outcome.as_deref())),
+                ),
+            })
+            .collect())
+    }
+
+    /// Gets one of the user's trips with its scans
+    pub async fn get(&self, user_id: &Uuid, trip_id: &Uuid) -> Result<TripDetails, ScanError> {
+        let trip = self.get_trip(user_id, trip_id).await?;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT
+                us.id, us.campground_id, us.check_in_date, us.check_out_date,
+                us.nights, us.status, us.notification_sent, us.created_at,
+                us.updated_at, us.expires_at, us.organization_id, us.facility_type, us.provider,
+                us.flexibility_days, us.max_price, us.site_filters, us.quick_book, us.outcome,
+                us.outcome_at, us.trip_id,
+                c.name as campground_name, {SCAN_FRESHNESS_COLUMNS}
+            FROM user_scans us
+            LEFT JOIN campgrounds c ON us.campground_id = c.id
+            {SCAN_FRESHNESS_JOINS}
+            WHERE us.trip_id = $1 AND us.deleted_at IS NULL
+            ORDER BY us.check_in_date, us.created_at
+            "#
+        ))
+        .bind(trip_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let scans: Vec<UserScanWithCampground> = rows.iter().map(scan_from_row).collect();
+        let progress = TripProgress::from_scans(
+            scans
+                .iter()
+                .map(|scan| (scan.id, scan.status.as_str(), scan.outcome.as_deref())),
+        );
+
+        Ok(TripDetails {
+            overview: TripOverview { trip, progress },
+            scans,
+        })
+    }
+
+    /// Deletes a trip; its scans keep running on their own
+    pub async fn delete(&self, user_id: &Uuid, trip_id: &Uuid) -> Result<(), ScanError> {
+        let deleted = sqlx::query("DELETE FROM trips WHERE id = $1 AND user_id = $2")
+            .bind(trip_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+
+        if deleted == 0 {
+            return Err(ScanError::TripNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Adds one of the user's scans to a trip, moving it out of any other trip. The
+    /// scan's stay must fall within the trip's dates.
+    pub async fn add_scan(
+        &self,
+        user_id: &Uuid,
+        trip_id: &Uuid,
+        scan_id: &Uuid,
+    ) -> Result<TripDetails, ScanError> {
+        let trip = self.get_trip(user_id, trip_id).await?;
+
+        let stay: Option<(NaiveDate, NaiveDate)> = sqlx::query_as(
+            r#"
+            SELECT check_in_date, check_out_date FROM user_scans
+            WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(scan_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        let (check_in_date, check_out_date) = stay.ok_or(ScanError::NotFound)?;
+
+        if check_in_date < trip.start_date || check_out_date > trip.end_date {
+            return Err(ScanError::Validation(format!(
+                "The scan's stay ({} to {}) falls outside the trip's dates ({} to {})",
+                check_in_date, check_out_date, trip.start_date, trip.end_date
+            )));
+        }
+
+        sqlx::query("UPDATE user_scans SET trip_id = $1, updated_at = NOW() WHERE id = $2")
+            .bind(trip_id)
+            .bind(scan_id)
+            .execute(&self.pool)
+            .await?;
+
+        self.get(user_id, trip_id).await
+    }
+
+    /// Takes a scan out of a trip; the scan keeps running on its own
+    pub async fn remove_scan(
+        &self,
+        user_id: &Uuid,
+        trip_id: &Uuid,
+        scan_id: &Uuid,
+    ) -> Result<TripDetails, ScanError> {
+        self.get_trip(user_id, trip_id).await?;
+
+        let removed = sqlx::query(
+            r#"
+            UPDATE user_scans SET trip_id = NULL, updated_at = NOW()
+            WHERE id = $1 AND trip_id = $2 AND user_id = $3
+            "#,
+        )
+        .bind(scan_id)
+        .bind(trip_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        if removed == 0 {
+            return Err(ScanError::NotFound);
+        }
+
+        self.get(user_id, trip_id).await
+    }
+
+    /// Gets one of the user's trips
+    async fn get_trip(&self, user_id: &Uuid, trip_id: &Uuid) -> Result<Trip, ScanError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, name, party_size, start_date, end_date, auto_pause_on_booking, created_at
+            FROM trips
+            WHERE id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(trip_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(ScanError::TripNotFound)?;
+
+        Ok(trip_from_row(&row))
+    }
+}
+
+/// Pauses the other active scans of a booked scan's trip, if the trip asks for it, and
+/// records why on their timelines. Returns the number of scans paused.
+pub(crate) async fn pause_trip_alternatives(
+    conn: &mut PgConnection,
+    booked_scan_id: &Uuid,
+) -> Result<u64, sqlx::Error> {
+    let paused: i64 = sqlx::query_scalar(
+        r#"
+        WITH paused AS (
+            UPDATE user_scans us
+            SET status = 'paused', updated_at = NOW()
+            FROM trips t
+            WHERE t.id = (SELECT trip_id FROM user_scans WHERE id = $1)
+              AND t.auto_pause_on_booking
+              AND us.trip_id = t.id
+              AND us.id <> $1
+              AND us.status = 'active'
+              AND us.deleted_at IS NULL
+            RETURNING us.id
+        ),
+        events AS (
+            INSERT INTO scan_events (user_scan_id, event_type, details)
+            SELECT id, 'paused', jsonb_build_object(
+                'from', 'active', 'to', 'paused', 'booked_scan_id', $1::TEXT
+            )
+            FROM paused
+        )
+        SELECT COUNT(*) FROM paused
+        "#,
+    )
+    .bind(booked_scan_id)
+    .fetch_one(conn)
+    .await?;
+
+    Ok(paused as u64)
+}
+
+/// Builds a trip from a row selecting its columns
+fn trip_from_row(row: &sqlx::postgres::PgRow) -> Trip {
+    Trip {
+        id: row.get("id"),
+        name: row.get("name"),
+        party_size: row.get("party_size"),
+        start_date: row.get("start_date"),
+        end_date: row.get("end_date"),
+        auto_pause_on_booking: row.get("auto_pause_on_booking"),
+        created_at: row.get("created_at"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregates_trip_status() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert_eq!(TripProgress::from_scans([]).status, TripStatus::Planning);
+
+        let scanning = TripProgress::from_scans([(a, "active", None), (b, "paused", None)]);
+        assert_eq!(scanning.status, TripStatus::Scanning);
+        assert_eq!(scanning.scans_by_status["active"], 1);
+        assert_eq!(scanning.scans_by_status["paused"], 1);
+
+        let paused = TripProgress::from_scans([(a, "paused", None), (b, "expired", None)]);
+        assert_eq!(paused.status, TripStatus::Paused);
+
+        let booked =
+            TripProgress::from_scans([(a, "completed", Some("booked")), (b, "paused", None)]);
+        assert_eq!(booked.status, TripStatus::Booked);
+        assert_eq!(booked.booked_scan_id, Some(a));
+
+        let ended =
+            TripProgress::from_scans([(a, "cancelled", Some("gave_up")), (b, "expired", None)]);
+        assert_eq!(ended.status, TripStatus::Ended);
+    }
+
+    #[test]
+    fn party_sizes_are_bounded() {
+        for (party_size, allowed) in [
+            (-1, false),
+            (0, false),
+            (1, true),
+            (100, true),
+            (101, false),
+        ] {
+            let request: CreateTripRequest = serde_json::from_value(serde_json::json!({
+                "name": "Yosemite",
+                "party_size": party_size,
+                "start_date": "2025-08-14",
+                "end_date": "2025-08-18",
+            }))
+            .unwrap();
+
+            assert_eq!(
+                request.validate().is_ok(),
+                allowed,
+                "party of {}",
+                party_size
+            );
+        }
+
+        let request: CreateTripRequest = serde_json::from_value(serde_json::json!({
+            "name": "Yosemite",
+            "start_date": "2025-08-14",
+            "end_date": "2025-08-18",
+        }))
+        .unwrap();
+        assert_eq!(request.party_size, 1);
+    }
+}
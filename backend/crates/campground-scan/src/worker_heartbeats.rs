@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Extra time a worker gets on top of two missed intervals before it counts as stalled
+const STALL_GRACE: Duration = Duration::from_secs(60);
+
+/// Last beat of one background worker
+#[derive(Debug, Clone)]
+struct WorkerBeat {
+    interval: Duration,
+    registered_at: DateTime<Utc>,
+    last_beat: Option<DateTime<Utc>>,
+}
+
+/// Health of one background worker, as reported by the health endpoints
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerStatus {
+    /// Name the worker registered under
+    pub name: &'static str,
+    /// When the worker last started a round of work
+    pub last_beat: Option<DateTime<Utc>>,
+    /// Seconds without a beat after which the worker counts as stalled
+    pub stalled_after_secs: u64,
+    /// Whether the worker beat recently enough
+    pub healthy: bool,
+}
+
+/// Heartbeats of the background workers (scan expiry, sniper mode, permit polling, ...),
+/// so health checks can tell when one died or got stuck.
+#[derive(Debug, Clone, Default)]
+pub struct WorkerHeartbeats {
+    workers: Arc<Mutex<BTreeMap<&'static str, WorkerBeat>>>,
+}
+
+impl WorkerHeartbeats {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a worker that does a round of work every `interval`, returning the
+    /// handle it beats with.
+    pub fn register(&self, name: &'static str, interval: Duration) -> Heartbeat {
+        self.workers.lock().unwrap().insert(
+            name,
+            WorkerBeat {
+                interval,
+                registered_at: Utc::now(),
+                last_beat: None,
+            },
+        );
+
+        Heartbeat {
+            name,
+            workers: self.workers.clone(),
+        }
+    }
+
+    /// Health of every registered worker. A worker is stalled once it has gone two
+    /// intervals (plus a minute) without beating.
+    pub fn statuses(&self) -> Vec<WorkerStatus> {
+        let now = Utc::now();
+
+        self.workers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, beat)| {
+                let stalled_after = beat.interval * 2 + STALL_GRACE;
+                let since = beat.last_beat.unwrap_or(beat.registered_at);
+                let silent_for = (now - since).to_std().unwrap_or_default();
+
+                WorkerStatus {
+                    name,
+                    last_beat: beat.last_beat,
+                    stalled_after_secs: stalled_after.as_secs(),
+                    healthy: silent_for <= stalled_after,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Handle a worker beats each time it starts a round of work
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    name: &'static str,
+    workers: Arc<Mutex<BTreeMap<&'static str, WorkerBeat>>>,
+}
+
+impl Heartbeat {
+    /// Records that the worker is alive.
+    pub fn beat(&self) {
+        if let Some(worker) = self.workers.lock().unwrap().get_mut(self.name) {
+            worker.last_beat = Some(Utc::now());
+        }
+    }
+}
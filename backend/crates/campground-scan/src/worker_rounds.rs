@@ -0,0 +1,44 @@
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// Share of a worker's interval that must pass before another round may start, so
+/// instances whose timers drift slightly don't skip a round
+const ROUND_SPACING_PERCENT: u32 = 90;
+
+/// Keeps each round of a background worker (scan expiry, sniper mode, permit polling, ...)
+/// to one server instance, so running several replicas doesn't send emails twice or poll
+/// Recreation.gov twice. Every instance ticks on its own; the first to claim a round runs
+/// it and the others skip it.
+#[derive(Debug, Clone)]
+pub struct WorkerRounds {
+    pool: PgPool,
+}
+
+impl WorkerRounds {
+    /// Creates a round tracker over the given pool
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Claims a round of `worker`, which runs every `interval`. Returns `false` when
+    /// another instance started one less than an interval ago.
+    pub async fn claim(&self, worker: &str, interval: Duration) -> Result<bool, sqlx::Error> {
+        let spacing = interval * ROUND_SPACING_PERCENT / 100;
+
+        let claimed = sqlx::query(
+            r#"
+            INSERT INTO worker_rounds (worker, started_at)
+            VALUES ($1, NOW())
+            ON CONFLICT (worker) DO UPDATE SET started_at = NOW()
+            WHERE worker_rounds.started_at <= NOW() - make_interval(secs => $2)
+            "#,
+        )
+        .bind(worker)
+        .bind(spacing.as_secs_f64())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(claimed > 0)
+    }
+}
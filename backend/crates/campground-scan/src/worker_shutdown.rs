@@ -0,0 +1,84 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Interval;
+
+/// Stops the background workers (scan expiry, sniper mode, permit polling, ...) at
+/// shutdown: each finishes the round it's in, then exits instead of starting another.
+#[derive(Debug, Clone)]
+pub struct WorkerShutdown {
+    requested: Arc<watch::Sender<bool>>,
+    workers: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl Default for WorkerShutdown {
+    fn default() -> Self {
+        Self {
+            requested: Arc::new(watch::Sender::new(false)),
+            workers: Arc::default(),
+        }
+    }
+}
+
+impl WorkerShutdown {
+    /// Creates a coordinator with no workers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signal a worker waits on between rounds
+    pub fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal {
+            requested: self.requested.subscribe(),
+        }
+    }
+
+    /// Tracks a spawned worker so [`WorkerShutdown::stop`] waits for it.
+    pub fn track(&self, worker: JoinHandle<()>) {
+        self.workers.lock().unwrap().push(worker);
+    }
+
+    /// Asks every worker to stop and waits up to `timeout` for the rounds in progress to
+    /// finish. Workers still running after that are aborted; returns how many were.
+    pub async fn stop(&self, timeout: Duration) -> usize {
+        self.requested.send_replace(true);
+
+        let workers = std::mem::take(&mut *self.workers.lock().unwrap());
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut aborted = 0;
+
+        for mut worker in workers {
+            if tokio::time::timeout_at(deadline, &mut worker)
+                .await
+                .is_err()
+            {
+                worker.abort();
+                aborted += 1;
+            }
+        }
+
+        aborted
+    }
+}
+
+/// Tells a worker when shutdown has begun
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal {
+    requested: watch::Receiver<bool>,
+}
+
+impl ShutdownSignal {
+    /// Waits for the next tick of `interval`. Returns `false` instead once shutdown has
+    /// begun, so workers loop with `while signal.tick(&mut interval).await`.
+    pub async fn tick(&mut self, interval: &mut Interval) -> bool {
+        if *self.requested.borrow() {
+            return false;
+        }
+
+        tokio::select! {
+            _ = interval.tick() => true,
+            _ = self.requested.wait_for(|requested| *requested) => false,
+        }
+    }
+}
@@ -0,0 +1,540 @@
+use chrono::NaiveDate;
+use reqwest::{Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::types::*;
+
+/// Errors returned by the API client
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The request couldn't be sent or the response couldn't be read
+    #[error("Request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// The API answered with an error status
+    #[error("API error {status} ({error}): {message}")]
+    Api {
+        /// HTTP status code
+        status: u16,
+        /// Machine-readable error code (e.g. `scan_not_found`)
+        error: String,
+        /// Human-readable message
+        message: String,
+    },
+
+    /// An authenticated endpoint was called without signing in first
+    #[error("Not signed in")]
+    NotSignedIn,
+}
+
+/// Typed client for the Campsite Tracker API.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), campsite_client::ClientError> {
+/// let mut client = campsite_client::CampsiteClient::new("http://localhost:8080");
+/// client.login("camper@example.com", "password").await?;
+/// let scans = client.list_scans().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CampsiteClient {
+    http: reqwest::Client,
+    base_url: String,
+    access_token: Option<String>,
+}
+
+impl CampsiteClient {
+    /// Creates a client for the server at `base_url` (e.g. `https://campsite-tracker.example`).
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            access_token: None,
+        }
+    }
+
+    /// Uses an existing access token for authenticated requests.
+    pub fn with_access_token(mut self, access_token: impl Into<String>) -> Self {
+        self.access_token = Some(access_token.into());
+        self
+    }
+
+    /// Returns the access token used for authenticated requests, if signed in.
+    pub fn access_token(&self) -> Option<&str> {
+        self.access_token.as_deref()
+    }
+
+    // Auth
+
+    /// Creates an account and signs in as the new user.
+    pub async fn signup(&mut self, request: &SignUpRequest) -> Result<AuthResponse, ClientError> {
+        let response: AuthResponse = self
+            .send(self.public(Method::POST, "/api/auth/signup").json(request))
+            .await?;
+
+        self.access_token = Some(response.access_token.clone());
+        Ok(response)
+    }
+
+    /// Signs in and keeps the access token for later requests.
+    pub async fn login(
+        &mut self,
+        email: &str,
+        password: &str,
+    ) -> Result<AuthResponse, ClientError> {
+        let request = LoginRequest {
+            email: email.to_string(),
+            password: password.to_string(),
+        };
+
+        let response: AuthResponse = self
+            .send(self.public(Method::POST, "/api/auth/login").json(&request))
+            .await?;
+
+        self.access_token = Some(response.access_token.clone());
+        Ok(response)
+    }
+
+    // Profile and notifications
+
+    /// Returns the signed-in user's profile.
+    pub async fn profile(&self) -> Result<Profile, ClientError> {
+        self.send(self.authenticated(Method::GET, "/api/user/profile")?)
+            .await
+    }
+
+    /// Changes the profile fields that are set in the request.
+    pub async fn patch_profile(
+        &self,
+        request: &PatchProfileRequest,
+    ) -> Result<Profile, ClientError> {
+        self.send(
+            self.authenticated(Method::PATCH, "/api/user/profile")?
+                .json(request),
+        )
+        .await
+    }
+
+    /// Emails a verification link to the user's address.
+    pub async fn send_email_verification(&self) -> Result<MessageResponse, ClientError> {
+        self.send(self.authenticated(Method::POST, "/api/user/verify/email/send")?)
+            .await
+    }
+
+    /// Texts a verification code to the user's phone.
+    pub async fn send_sms_verification(&self) -> Result<MessageResponse, ClientError> {
+        self.send(self.authenticated(Method::POST, "/api/user/verify/sms/send")?)
+            .await
+    }
+
+    /// Verifies the user's phone with the code they received.
+    pub async fn verify_phone(&self, code: &str) -> Result<MessageResponse, ClientError> {
+        self.send(
+            self.authenticated(Method::POST, "/api/user/verify/sms")?
+                .json(&serde_json::json!({ "code": code })),
+        )
+        .await
+    }
+
+    // Scans
+
+    /// Creates a scan.
+    pub async fn create_scan(&self, request: &CreateScanRequest) -> Result<Scan, ClientError> {
+        self.send(
+            self.authenticated(Method::POST, "/api/scans")?
+                .json(request),
+        )
+        .await
+    }
+
+    /// Lists all of the user's scans.
+    pub async fn list_scans(&self) -> Result<ListScansResponse, ClientError> {
+        self.send(self.authenticated(Method::GET, "/api/scans")?)
+            .await
+    }
+
+    /// Lists the user's active scans.
+    pub async fn list_active_scans(&self) -> Result<ListScansResponse, ClientError> {
+        self.send(self.authenticated(Method::GET, "/api/scans/active")?)
+            .await
+    }
+
+    /// Returns one scan.
+    pub async fn get_scan(&self, scan_id: Uuid) -> Result<Scan, ClientError> {
+        self.send(self.authenticated(Method::GET, &format!("/api/scans/{}", scan_id))?)
+            .await
+    }
+
+    /// Changes the status of a scan.
+    pub async fn update_scan(
+        &self,
+        scan_id: Uuid,
+        request: &UpdateScanRequest,
+    ) -> Result<Scan, ClientError> {
+        self.send(
+            self.authenticated(Method::PUT, &format!("/api/scans/{}", scan_id))?
+                .json(request),
+        )
+        .await
+    }
+
+    /// Changes a scan's dates, facility type, or flexibility.
+    pub async fn patch_scan(
+        &self,
+        scan_id: Uuid,
+        request: &PatchScanRequest,
+    ) -> Result<Scan, ClientError> {
+        self.send(
+            self.authenticated(Method::PATCH, &format!("/api/scans/{}", scan_id))?
+                .json(request),
+        )
+        .await
+    }
+
+    /// Records what came of a scan (`booked`, `missed`, `gave_up`).
+    pub async fn set_scan_outcome(
+        &self,
+        scan_id: Uuid,
+        outcome: &str,
+    ) -> Result<Scan, ClientError> {
+        self.send(
+            self.authenticated(Method::PUT, &format!("/api/scans/{}/outcome", scan_id))?
+                .json(&ScanOutcomeRequest {
+                    outcome: outcome.to_string(),
+                }),
+        )
+        .await
+    }
+
+    /// Creates a public read-only link to one of the user's scans, or gets the existing
+    /// one.
+    pub async fn share_scan(&self, scan_id: Uuid) -> Result<ScanShareLink, ClientError> {
+        self.send(self.authenticated(Method::POST, &format!("/api/scans/{}/share", scan_id))?)
+            .await
+    }
+
+    /// Removes a scan's public link.
+    pub async fn unshare_scan(&self, scan_id: Uuid) -> Result<(), ClientError> {
+        let request =
+            self.authenticated(Method::DELETE, &format!("/api/scans/{}/share", scan_id))?;
+        check_status(request.send().await?).await?;
+        Ok(())
+    }
+
+    /// Gets the scan behind a share link; no login needed.
+    pub async fn shared_scan(&self, slug: &str) -> Result<SharedScan, ClientError> {
+        self.send(self.public(Method::GET, &format!("/api/shared/{}", slug)))
+            .await
+    }
+
+    /// Copies a shared scan into the user's account.
+    pub async fn clone_shared_scan(&self, slug: &str) -> Result<Scan, ClientError> {
+        self.send(self.authenticated(Method::POST, &format!("/api/shared/{}/clone", slug))?)
+            .await
+    }
+
+    /// Adds a friend's email or phone to a scan's alerts; they're alerted once they
+    /// confirm through the link sent to them.
+    pub async fn add_scan_recipient(
+        &self,
+        scan_id: Uuid,
+        request: &AddScanRecipientRequest,
+    ) -> Result<InvitedRecipient, ClientError> {
+        self.send(
+            self.authenticated(Method::POST, &format!("/api/scans/{}/recipients", scan_id))?
+                .json(request),
+        )
+        .await
+    }
+
+    /// Lists the extra recipients of a scan.
+    pub async fn scan_recipients(
+        &self,
+        scan_id: Uuid,
+    ) -> Result<ScanRecipientsResponse, ClientError> {
+        self.send(self.authenticated(Method::GET, &format!("/api/scans/{}/recipients", scan_id))?)
+            .await
+    }
+
+    /// Removes an extra recipient from a scan.
+    pub async fn remove_scan_recipient(
+        &self,
+        scan_id: Uuid,
+        recipient_id: Uuid,
+    ) -> Result<(), ClientError> {
+        let request = self.authenticated(
+            Method::DELETE,
+            &format!("/api/scans/{}/recipients/{}", scan_id, recipient_id),
+        )?;
+        check_status(request.send().await?).await?;
+        Ok(())
+    }
+
+    /// Gets a scan's most recent timeline events, oldest first.
+    pub async fn scan_events(&self, scan_id: Uuid) -> Result<ScanEventsResponse, ClientError> {
+        self.send(self.authenticated(Method::GET, &format!("/api/scans/{}/events", scan_id))?)
+            .await
+    }
+
+    /// Deletes a scan; it can be restored for 30 days.
+    pub async fn delete_scan(&self, scan_id: Uuid) -> Result<(), ClientError> {
+        let request = self.authenticated(Method::DELETE, &format!("/api/scans/{}", scan_id))?;
+        check_status(request.send().await?).await?;
+        Ok(())
+    }
+
+    /// Restores a scan deleted within the last 30 days.
+    pub async fn restore_scan(&self, scan_id: Uuid) -> Result<Scan, ClientError> {
+        self.send(self.authenticated(Method::POST, &format!("/api/scans/{}/restore", scan_id))?)
+            .await
+    }
+
+    /// Suggests campgrounds the scan may have been meant for.
+    pub async fn remap_suggestions(
+        &self,
+        scan_id: Uuid,
+    ) -> Result<RemapSuggestionsResponse, ClientError> {
+        self.send(
+            self.authenticated(Method::POST, &format!("/api/scans/{}/remap", scan_id))?
+                .json(&RemapScanRequest::default()),
+        )
+        .await
+    }
+
+    /// Moves a scan to another campground.
+    pub async fn remap_scan(
+        &self,
+        scan_id: Uuid,
+        campground_id: &str,
+        campground_name: Option<&str>,
+    ) -> Result<Scan, ClientError> {
+        let request = RemapScanRequest {
+            campground_id: Some(campground_id.to_string()),
+            campground_name: campground_name.map(str::to_string),
+        };
+
+        self.send(
+            self.authenticated(Method::POST, &format!("/api/scans/{}/remap", scan_id))?
+                .json(&request),
+        )
+        .await
+    }
+
+    // Trips
+
+    /// Creates a trip.
+    pub async fn create_trip(&self, request: &CreateTripRequest) -> Result<Trip, ClientError> {
+        self.send(
+            self.authenticated(Method::POST, "/api/trips")?
+                .json(request),
+        )
+        .await
+    }
+
+    /// Lists the user's trips.
+    pub async fn trips(&self) -> Result<Vec<Trip>, ClientError> {
+        let response: TripsResponse = self
+            .send(self.authenticated(Method::GET, "/api/trips")?)
+            .await?;
+
+        Ok(response.trips)
+    }
+
+    /// Gets a trip with its scans.
+    pub async fn trip(&self, trip_id: Uuid) -> Result<Trip, ClientError> {
+        self.send(self.authenticated(Method::GET, &format!("/api/trips/{}", trip_id))?)
+            .await
+    }
+
+    /// Deletes a trip; its scans keep running.
+    pub async fn delete_trip(&self, trip_id: Uuid) -> Result<(), ClientError> {
+        let request = self.authenticated(Method::DELETE, &format!("/api/trips/{}", trip_id))?;
+        check_status(request.send().await?).await?;
+        Ok(())
+    }
+
+    /// Adds a scan to a trip.
+    pub async fn add_trip_scan(&self, trip_id: Uuid, scan_id: Uuid) -> Result<Trip, ClientError> {
+        self.send(self.authenticated(
+            Method::PUT,
+            &format!("/api/trips/{}/scans/{}", trip_id, scan_id),
+        )?)
+        .await
+    }
+
+    /// Takes a scan out of a trip.
+    pub async fn remove_trip_scan(
+        &self,
+        trip_id: Uuid,
+        scan_id: Uuid,
+    ) -> Result<Trip, ClientError> {
+        self.send(self.authenticated(
+            Method::DELETE,
+            &format!("/api/trips/{}/scans/{}", trip_id, scan_id),
+        )?)
+        .await
+    }
+
+    // Campgrounds
+
+    /// Lists the user's favorite campgrounds with their cached availability.
+    pub async fn favorites(&self) -> Result<Vec<Favorite>, ClientError> {
+        let response: FavoritesResponse = self
+            .send(self.authenticated(Method::GET, "/api/user/favorites")?)
+            .await?;
+
+        Ok(response.favorites)
+    }
+
+    /// Bookmarks a campground, or updates the note of one already bookmarked.
+    pub async fn add_favorite(
+        &self,
+        request: &AddFavoriteRequest,
+    ) -> Result<Favorite, ClientError> {
+        self.send(
+            self.authenticated(Method::POST, "/api/user/favorites")?
+                .json(request),
+        )
+        .await
+    }
+
+    /// Removes a campground from the user's favorites.
+    pub async fn remove_favorite(&self, campground_id: &str) -> Result<(), ClientError> {
+        let request = self.authenticated(
+            Method::DELETE,
+            &format!("/api/user/favorites/{}", campground_id),
+        )?;
+        check_status(request.send().await?).await?;
+        Ok(())
+    }
+
+    /// Creates a scan of a favorite campground for a stay.
+    pub async fn scan_favorite(
+        &self,
+        campground_id: &str,
+        request: &FavoriteScanRequest,
+    ) -> Result<Scan, ClientError> {
+        self.send(
+            self.authenticated(
+                Method::POST,
+                &format!("/api/user/favorites/{}/scan", campground_id),
+            )?
+            .json(request),
+        )
+        .await
+    }
+
+    /// Searches campgrounds by recreation area name.
+    pub async fn search_facilities(&self, query: &str) -> Result<Vec<Facility>, ClientError> {
+        let response: FacilitySearchResponse = self
+            .send(
+                self.public(Method::GET, "/api/facilities/search")
+                    .query(&[("q", query)]),
+            )
+            .await?;
+
+        Ok(response.records)
+    }
+
+    /// Searches campgrounds by recreation area name, narrowed down by state, managing
+    /// agency, or facility type.
+    pub async fn search_facilities_filtered(
+        &self,
+        query: &str,
+        filters: &FacilitySearchFilters,
+    ) -> Result<Vec<Facility>, ClientError> {
+        let response: FacilitySearchResponse = self
+            .send(
+                self.public(Method::GET, "/api/facilities/search")
+                    .query(&[("q", query)])
+                    .query(filters),
+            )
+            .await?;
+
+        Ok(response.records)
+    }
+
+    /// Autocompletes campground names from the server's cache, falling back to RIDB
+    /// when nothing cached matches.
+    pub async fn search_campgrounds(
+        &self,
+        query: &str,
+        limit: Option<u32>,
+    ) -> Result<CampgroundSearchResponse, ClientError> {
+        let mut request = self
+            .public(Method::GET, "/api/campgrounds/search")
+            .query(&[("q", query)]);
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)]);
+        }
+
+        self.send(request).await
+    }
+
+    /// Returns a campground's campsites and photos.
+    pub async fn campground_details(
+        &self,
+        campground_id: &str,
+    ) -> Result<CampgroundDetails, ClientError> {
+        self.send(self.public(Method::GET, &format!("/api/campgrounds/{}", campground_id)))
+            .await
+    }
+
+    /// Returns the current availability of a campground for the nights `start..end`.
+    pub async fn campground_availability(
+        &self,
+        campground_id: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<AvailabilityPreview, ClientError> {
+        self.send(
+            self.public(
+                Method::GET,
+                &format!("/api/campgrounds/{}/availability", campground_id),
+            )
+            .query(&[("start", start.to_string()), ("end", end.to_string())]),
+        )
+        .await
+    }
+
+    fn public(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}{}", self.base_url, path))
+    }
+
+    fn authenticated(&self, method: Method, path: &str) -> Result<RequestBuilder, ClientError> {
+        let token = self.access_token.as_ref().ok_or(ClientError::NotSignedIn)?;
+        Ok(self.public(method, path).bearer_auth(token))
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ClientError> {
+        let response = check_status(request.send().await?).await?;
+        Ok(response.json().await?)
+    }
+}
+
+/// Turns an error status into `ClientError::Api`, reading the API's error body.
+async fn check_status(response: Response) -> Result<Response, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await?;
+    let (error, message) = match serde_json::from_str::<ApiErrorBody>(&body) {
+        Ok(body) => (body.error, body.message),
+        // Some endpoints answer with a plain JSON string
+        Err(_) => (
+            status.canonical_reason().unwrap_or("error").to_string(),
+            serde_json::from_str::<String>(&body).unwrap_or(body),
+        ),
+    };
+
+    Err(ClientError::Api {
+        status: status.as_u16(),
+        error,
+        message,
+    })
+}
@@ -0,0 +1,12 @@
+//! # Campsite Client
+//!
+//! Typed HTTP client for the Campsite Tracker API, shared by integrations and tooling so
+//! request and response shapes live in one place.
+
+/// HTTP client with one method per API endpoint.
+mod client;
+pub use client::*;
+
+/// Request and response types of the API.
+mod types;
+pub use types::*;
@@ -0,0 +1,1433 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Preferences for user notifications
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    /// Whether the user wants to receive email notifications
+    pub email: bool,
+    /// Whether the user wants to receive SMS notifications
+    pub sms: bool,
+}
+
+/// Request body for `POST /api/auth/signup`
+#[derive(Debug, Clone, Serialize)]
+pub struct SignUpRequest {
+    /// Name of the user
+    pub name: String,
+    /// Email address of the user
+    pub email: String,
+    /// Phone number of the user
+    pub phone: String,
+    /// Password for the account
+    pub password: String,
+    /// Notification preferences for the user
+    pub notification_preferences: NotificationPreferences,
+    /// Language of emails, texts, and error messages (`en` or `es`); the request's
+    /// `Accept-Language` when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// IANA time zone used for alert and expiry dates (e.g. `America/Denver`);
+    /// `America/Los_Angeles` when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+}
+
+/// Request body for `POST /api/auth/login`
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginRequest {
+    /// Email address of the user
+    pub email: String,
+    /// Password for the account
+    pub password: String,
+}
+
+/// Tokens and user information returned by sign-up and login
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthResponse {
+    /// Access token sent as the bearer token on authenticated requests
+    pub access_token: String,
+    /// Refresh token for the session
+    pub refresh_token: String,
+    /// The signed-in user
+    pub user: UserInfo,
+}
+
+/// Information about a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserInfo {
+    /// Unique identifier for the user
+    pub id: Uuid,
+    /// Name of the user
+    pub name: String,
+    /// Email address of the user
+    pub email: String,
+    /// Phone number of the user
+    pub phone: String,
+    /// Whether the email address is verified
+    pub email_verified: bool,
+    /// Whether the phone number is verified
+    pub phone_verified: bool,
+    /// Notification preferences of the user
+    pub notification_preferences: NotificationPreferences,
+    /// Language of the user's emails, texts, and error messages (`en` or `es`)
+    #[serde(default)]
+    pub locale: String,
+    /// IANA time zone of the user
+    #[serde(default)]
+    pub timezone: String,
+}
+
+/// Result of the pre-flight check for one notification channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelDeliverability {
+    /// `Some(false)` if the channel can't deliver, `None` if the check couldn't run
+    pub deliverable: Option<bool>,
+    /// Why the channel is undeliverable or unknown
+    pub reason: Option<String>,
+}
+
+/// Deliverability of each notification channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliverabilityStatus {
+    /// Email channel
+    pub email: ChannelDeliverability,
+    /// SMS channel
+    pub sms: ChannelDeliverability,
+}
+
+/// Profile returned by `GET /api/user/profile` and profile updates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    /// Profile information
+    #[serde(flatten)]
+    pub user: UserInfo,
+    /// Deliverability of each notification channel
+    pub deliverability: DeliverabilityStatus,
+    /// New email address waiting to be confirmed
+    #[serde(default)]
+    pub pending_email: Option<String>,
+}
+
+/// Request body for `PATCH /api/user/profile`; unset fields are left unchanged
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PatchProfileRequest {
+    /// New name of the user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// New email address of the user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// New phone number of the user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    /// New notification preferences of the user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notification_preferences: Option<NotificationPreferences>,
+    /// New language of the user (`en` or `es`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// New IANA time zone of the user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+}
+
+/// Message returned by verification endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageResponse {
+    /// Human-readable result
+    pub message: String,
+}
+
+/// Request body for `POST /api/scans`
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateScanRequest {
+    /// ID of the campground to scan (RIDB facility ID)
+    pub campground_id: String,
+    /// Name of the campground
+    pub campground_name: String,
+    /// Check-in date
+    pub check_in_date: NaiveDate,
+    /// Check-out date
+    pub check_out_date: NaiveDate,
+    /// Organization to share the scan with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<Uuid>,
+    /// Kind of inventory to watch (`campsite`, `cabin`, `lookout`, `group_site`);
+    /// campsites when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facility_type: Option<String>,
+    /// Reservation system (`recreation_gov`, `reserve_california`); recreation.gov when
+    /// omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Days the stay may shift earlier or later; exact dates when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flexibility_days: Option<i32>,
+    /// Highest nightly price to alert about, in whole dollars; no limit when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<i32>,
+    /// Site features to require; any site when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site_filters: Option<SiteFilters>,
+    /// Whether alerts link straight to each open site's booking page; off when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quick_book: Option<bool>,
+}
+
+/// Site features a scan requires before it alerts about a site
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SiteFilters {
+    /// Only sites with an electric hookup
+    pub electric_hookup: bool,
+    /// Only sites with a water hookup
+    pub water_hookup: bool,
+    /// Only sites with a sewer hookup
+    pub sewer_hookup: bool,
+    /// Only sites fitting a vehicle or trailer this long, in feet
+    pub vehicle_length: Option<i32>,
+    /// Only sites allowing pets
+    pub pets_allowed: bool,
+}
+
+/// A scan with its campground name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scan {
+    /// Unique identifier for the scan
+    pub id: Uuid,
+    /// ID of the campground
+    pub campground_id: String,
+    /// Name of the campground
+    pub campground_name: String,
+    /// Check-in date
+    pub check_in_date: NaiveDate,
+    /// Check-out date
+    pub check_out_date: NaiveDate,
+    /// Number of nights
+    pub nights: i32,
+    /// Current status of the scan
+    pub status: String,
+    /// Whether a notification has been sent
+    pub notification_sent: bool,
+    /// When the scan was created
+    pub created_at: DateTime<Utc>,
+    /// When the scan was last updated; not included when a scan is created
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// When the scan expires
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Organization the scan is shared with
+    #[serde(default)]
+    pub organization_id: Option<Uuid>,
+    /// Kind of inventory watched (`campsite`, `cabin`, `lookout`, `group_site`)
+    #[serde(default)]
+    pub facility_type: Option<String>,
+    /// Reservation system (`recreation_gov`, `reserve_california`)
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Days the stay may shift earlier or later
+    #[serde(default)]
+    pub flexibility_days: i32,
+    /// Highest nightly price alerted about, in whole dollars
+    #[serde(default)]
+    pub max_price: Option<i32>,
+    /// Site features required
+    #[serde(default)]
+    pub site_filters: SiteFilters,
+    /// Whether alerts link straight to each open site's booking page
+    #[serde(default)]
+    pub quick_book: bool,
+    /// What came of the scan (`booked`, `missed`, `gave_up`), if recorded
+    #[serde(default)]
+    pub outcome: Option<String>,
+    /// When the outcome was recorded
+    #[serde(default)]
+    pub outcome_at: Option<DateTime<Utc>>,
+    /// Trip the scan belongs to
+    #[serde(default)]
+    pub trip_id: Option<Uuid>,
+    /// When the scan's campground was last polled
+    #[serde(default)]
+    pub last_polled: Option<DateTime<Utc>>,
+    /// When the campground is polled next, while the scan is active
+    #[serde(default)]
+    pub next_poll_at: Option<DateTime<Utc>>,
+    /// Why the latest availability check failed, while polls are failing
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// Whether the campground can be checked (`healthy`, `degraded`, `blocked`)
+    #[serde(default)]
+    pub health: Option<String>,
+    /// Set when the campground looks closed for the season on every night of the stay
+    #[serde(default)]
+    pub seasonal_closure: Option<SeasonalClosure>,
+    /// Advice returned when the scan is created
+    #[serde(default)]
+    pub warnings: Vec<ScanWarning>,
+}
+
+/// Months a campground is open, from its RIDB description
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperatingSeason {
+    /// First month open, from 1 (January) to 12
+    pub opens_month: u32,
+    /// Last month open, from 1 (January) to 12
+    pub closes_month: u32,
+}
+
+/// A scan's campground showing no reservable site on any night of the stay, poll after
+/// poll
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonalClosure {
+    /// Since when the stay's nights have shown no reservable site
+    pub closed_since: DateTime<Utc>,
+    /// Months the campground is open, when known
+    #[serde(default)]
+    pub season: Option<OperatingSeason>,
+    /// Whether the stay falls outside the operating season, when known
+    #[serde(default)]
+    pub outside_season: Option<bool>,
+    /// In-season check-in date suggested instead
+    #[serde(default)]
+    pub suggested_check_in: Option<NaiveDate>,
+}
+
+/// Public link to a scan, from `POST /api/scans/{id}/share`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanShareLink {
+    /// Scan shared
+    pub scan_id: Uuid,
+    /// Slug identifying the link
+    pub slug: String,
+    /// Public page of the scan
+    pub url: String,
+    /// Scans other users cloned from the link
+    pub clone_count: i32,
+}
+
+/// What a share link shows of a scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedScan {
+    /// Slug of the share link
+    pub slug: String,
+    /// ID of the campground
+    pub campground_id: String,
+    /// Name of the campground
+    pub campground_name: String,
+    /// Reservation system (`recreation_gov`, `reserve_california`)
+    pub provider: String,
+    /// Kind of inventory watched
+    pub facility_type: String,
+    /// Check-in date
+    pub check_in_date: NaiveDate,
+    /// Check-out date
+    pub check_out_date: NaiveDate,
+    /// Number of nights
+    pub nights: i32,
+    /// Days the stay may shift earlier or later
+    pub flexibility_days: i32,
+    /// Site features required
+    #[serde(default)]
+    pub site_filters: SiteFilters,
+    /// Current status of the scan
+    pub status: String,
+    /// Scans other users cloned from the link
+    pub clone_count: i32,
+    /// When the link was created
+    pub shared_at: Option<DateTime<Utc>>,
+}
+
+/// Request body for `POST /api/scans/{id}/recipients`: an email or a phone number
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AddScanRecipientRequest {
+    /// Email address to alert
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// US phone number to text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+}
+
+/// Someone the scan's owner added to its alerts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvitedRecipient {
+    /// Unique identifier of the recipient
+    pub id: Uuid,
+    /// Scan whose alerts they get
+    pub scan_id: Uuid,
+    /// `email` or `sms`
+    pub channel: String,
+    /// Email address or phone number
+    pub address: String,
+    /// `pending`, `confirmed`, or `declined`
+    pub status: String,
+    /// When the owner added them
+    pub created_at: Option<DateTime<Utc>>,
+    /// When they confirmed or declined
+    pub responded_at: Option<DateTime<Utc>>,
+}
+
+/// Response of `GET /api/scans/{id}/recipients`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanRecipientsResponse {
+    /// Scan the recipients belong to
+    pub scan_id: Uuid,
+    /// Recipients in the order they were added
+    pub recipients: Vec<InvitedRecipient>,
+}
+
+/// Advice returned with a newly created scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanWarning {
+    /// Warning kind (`redundant_campground_scans`)
+    pub kind: String,
+    /// Campground the warning is about
+    pub campground_id: String,
+    /// The user's active and paused scans on the campground
+    pub scan_count: i64,
+    /// Explanation for the user
+    pub message: String,
+    /// Groups of scans that one flexible scan could replace
+    #[serde(default)]
+    pub consolidate: Vec<ConsolidationSuggestion>,
+}
+
+/// Scans with the same stay length that one flexible scan would cover
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsolidationSuggestion {
+    /// Scans the suggested scan would replace
+    pub scan_ids: Vec<Uuid>,
+    /// Check-in date of the suggested scan
+    pub check_in_date: NaiveDate,
+    /// Check-out date of the suggested scan
+    pub check_out_date: NaiveDate,
+    /// Flexibility of the suggested scan, in days
+    pub flexibility_days: i32,
+}
+
+/// Response of the scan listing endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListScansResponse {
+    /// Scans with campground information
+    pub scans: Vec<Scan>,
+    /// Total number of scans
+    pub total: i64,
+    /// The user's active scan quota; absent for organization listings
+    #[serde(default)]
+    pub quota: Option<ScanQuota>,
+}
+
+/// A user's active scan quota
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanQuota {
+    /// Plan ID (`free`, `plus`, `pro`)
+    pub plan: String,
+    /// Active scans allowed at once; unlimited when `None`
+    pub max_active_scans: Option<i32>,
+    /// Scans the user owns that are active
+    pub active_scans: i64,
+    /// Active scans the user may still add; unlimited when `None`
+    pub remaining: Option<i64>,
+}
+
+/// Request body for `PUT /api/scans/{id}`
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateScanRequest {
+    /// New status (`active`, `paused`, `completed`, `cancelled`)
+    pub status: String,
+}
+
+/// Request body for `PUT /api/scans/{id}/outcome`
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanOutcomeRequest {
+    /// `booked`, `missed`, or `gave_up`
+    pub outcome: String,
+}
+
+/// Request body for `PATCH /api/scans/{id}`; unset fields are kept
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PatchScanRequest {
+    /// New check-in date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_in_date: Option<NaiveDate>,
+    /// New check-out date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_out_date: Option<NaiveDate>,
+    /// New kind of inventory to watch (`campsite`, `cabin`, `lookout`, `group_site`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facility_type: Option<String>,
+    /// New number of days the stay may shift earlier or later
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flexibility_days: Option<i32>,
+    /// New highest nightly price in whole dollars; 0 removes the limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<i32>,
+    /// New site features to require, replacing the current ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site_filters: Option<SiteFilters>,
+    /// Whether alerts should link straight to each open site's booking page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quick_book: Option<bool>,
+}
+
+/// An entry in a scan's activity timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanEvent {
+    /// Event ID, increasing over time
+    pub id: i64,
+    /// What happened (`created`, `polled`, `notification_sent`, `error`, `paused`, ...)
+    pub event_type: String,
+    /// Event-specific details, e.g. `{"open_sites": 3}` for polls
+    pub details: serde_json::Value,
+    /// When it happened
+    pub created_at: DateTime<Utc>,
+}
+
+/// Response of `GET /api/scans/{id}/events`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanEventsResponse {
+    /// Scan ID
+    pub scan_id: Uuid,
+    /// Most recent events, oldest first
+    pub events: Vec<ScanEvent>,
+}
+
+/// Request body for `POST /api/scans/{id}/remap`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RemapScanRequest {
+    /// Campground to move the scan to; when unset, suggestions are returned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub campground_id: Option<String>,
+    /// Name of the target campground, needed if the server doesn't know it yet
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub campground_name: Option<String>,
+}
+
+/// A campground the user may have meant to scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemapSuggestion {
+    /// ID of the suggested campground
+    pub campground_id: String,
+    /// Name of the suggested campground
+    pub campground_name: String,
+    /// Distance from the scanned facility, if known
+    pub distance_miles: Option<f64>,
+    /// How likely this is the intended campground, from 0 to 1
+    pub score: f64,
+}
+
+/// Remap suggestions for a scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemapSuggestionsResponse {
+    /// ID of the scan
+    pub scan_id: Uuid,
+    /// Campground the scan currently points at
+    pub current_campground_id: String,
+    /// Suggested campgrounds, most likely first
+    pub suggestions: Vec<RemapSuggestion>,
+}
+
+/// Filters for `GET /api/facilities/search`; unset filters are left out
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FacilitySearchFilters {
+    /// Only facilities in this state (two-letter code)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// Only facilities managed by this agency (`NPS`, `USFS` or `BLM`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agency: Option<String>,
+    /// Only campgrounds, leaving out trailheads, day-use areas and the like
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub campgrounds_only: bool,
+}
+
+/// A recreation.gov facility, with RIDB field names
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Facility {
+    /// RIDB facility ID
+    #[serde(rename = "FacilityID")]
+    pub id: String,
+    /// Name of the facility
+    #[serde(rename = "FacilityName")]
+    pub name: String,
+    /// HTML description of the facility
+    #[serde(rename = "FacilityDescription", default)]
+    pub description: String,
+    /// Latitude, if known
+    #[serde(rename = "FacilityLatitude", default)]
+    pub latitude: Option<f64>,
+    /// Longitude, if known
+    #[serde(rename = "FacilityLongitude", default)]
+    pub longitude: Option<f64>,
+    /// Two-letter state code
+    #[serde(rename = "AddressStateCode", default)]
+    pub state: Option<String>,
+    /// Whether the facility takes reservations
+    #[serde(rename = "Reservable", default)]
+    pub reservable: Option<bool>,
+    /// Free-text stay limit (e.g. "14 days")
+    #[serde(rename = "StayLimit", default)]
+    pub stay_limit: Option<String>,
+    /// Human-readable location (e.g. "12 mi NE of Bishop, CA")
+    #[serde(rename = "LocationDescription", default)]
+    pub location_description: Option<String>,
+}
+
+/// A campground matching an autocomplete search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampgroundSearchResult {
+    /// Campground ID
+    pub id: String,
+    /// Campground name
+    pub name: String,
+    /// Two-letter state code
+    pub state: Option<String>,
+    /// Recreation area the campground is in, when known
+    pub recarea_name: Option<String>,
+    /// Reservation system the campground is booked through
+    pub provider: String,
+    /// Whether the campground takes reservations
+    pub reservable: bool,
+    /// Latitude, when known
+    pub latitude: Option<f64>,
+    /// Longitude, when known
+    pub longitude: Option<f64>,
+}
+
+/// Campgrounds matching an autocomplete search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampgroundSearchResponse {
+    /// Matching campgrounds, best match first
+    pub campgrounds: Vec<CampgroundSearchResult>,
+    /// `cache` when served from the database, `ridb` when RIDB was asked
+    pub source: String,
+}
+
+/// A campsite within a campground, with RIDB field names
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Campsite {
+    /// RIDB campsite ID
+    #[serde(rename = "CampsiteID")]
+    pub id: String,
+    /// Site name or number
+    #[serde(rename = "CampsiteName", default)]
+    pub name: String,
+    /// Site type (e.g. `STANDARD NONELECTRIC`)
+    #[serde(rename = "CampsiteType", default)]
+    pub site_type: Option<String>,
+    /// Campground loop the site belongs to
+    #[serde(rename = "Loop", default)]
+    pub loop_name: Option<String>,
+    /// Whether the site is accessible
+    #[serde(rename = "CampsiteAccessible", default)]
+    pub accessible: bool,
+}
+
+/// A photo of a campground, with RIDB field names
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Photo {
+    /// Title of the photo
+    #[serde(rename = "Title", default)]
+    pub title: String,
+    /// Link to the photo
+    #[serde(rename = "URL", default)]
+    pub url: String,
+    /// Attribution to show alongside the photo
+    #[serde(rename = "Credits", default)]
+    pub credits: Option<String>,
+    /// Whether this is the campground's main photo
+    #[serde(rename = "IsPrimary", default)]
+    pub is_primary: bool,
+}
+
+/// Response of `GET /api/campgrounds/{id}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampgroundDetails {
+    /// Facility information
+    pub facility: Facility,
+    /// Campsites of the campground
+    pub campsites: Vec<Campsite>,
+    /// Photos, primary photo first
+    pub photos: Vec<Photo>,
+    /// When the details were fetched from recreation.gov
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Night-by-night status of one campsite
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiteCalendar {
+    /// Recreation.gov campsite ID
+    pub campsite_id: String,
+    /// Site name or number
+    pub site: String,
+    /// Campground loop
+    pub loop_name: Option<String>,
+    /// Site type
+    pub campsite_type: Option<String>,
+    /// Kind of inventory (`campsite`, `cabin`, `lookout`, `group_site`)
+    #[serde(default)]
+    pub facility_type: Option<String>,
+    /// Status of each night (e.g. `Available`, `Reserved`)
+    pub nights: BTreeMap<NaiveDate, String>,
+    /// Price of each night in dollars, for nights reported with one
+    #[serde(default)]
+    pub nightly_prices: BTreeMap<NaiveDate, f64>,
+}
+
+/// Response of `GET /api/campgrounds/{id}/availability`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailabilityPreview {
+    /// RIDB facility ID
+    pub campground_id: String,
+    /// First night shown
+    pub start: NaiveDate,
+    /// Check-out date
+    pub end: NaiveDate,
+    /// Number of available sites per night
+    pub available_sites_per_night: BTreeMap<NaiveDate, usize>,
+    /// Calendar of every campsite
+    pub campsites: Vec<SiteCalendar>,
+    /// When the preview was built
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Response of `GET /api/facilities/search`
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct FacilitySearchResponse {
+    #[serde(rename = "RECDATA", default)]
+    pub(crate) records: Vec<Facility>,
+}
+
+/// Error body returned by the API
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ApiErrorBody {
+    pub(crate) error: String,
+    pub(crate) message: String,
+}
+
+/// Request body for `POST /api/user/favorites`
+#[derive(Debug, Clone, Serialize)]
+pub struct AddFavoriteRequest {
+    /// ID of the campground
+    pub campground_id: String,
+    /// Name of the campground
+    pub campground_name: String,
+    /// Reservation system (`recreation_gov`, `reserve_california`); recreation.gov when
+    /// omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Personal note about the campground
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Request body for `POST /api/user/favorites/{campground_id}/scan`
+#[derive(Debug, Clone, Serialize)]
+pub struct FavoriteScanRequest {
+    /// Check-in date
+    pub check_in_date: NaiveDate,
+    /// Check-out date
+    pub check_out_date: NaiveDate,
+    /// Kind of inventory to watch; campsites when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facility_type: Option<String>,
+    /// Days the stay may shift earlier or later; exact dates when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flexibility_days: Option<i32>,
+}
+
+/// Cached availability of a favorite campground over the next 60 days
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavoriteAvailability {
+    /// Nights with at least one open site
+    pub nights_available: i64,
+    /// First night with an open site
+    pub first_available_date: Option<NaiveDate>,
+    /// Most open sites seen on any night
+    pub max_available_sites: i32,
+    /// When the campground was last checked
+    pub last_checked: DateTime<Utc>,
+}
+
+/// A campground the user bookmarked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Favorite {
+    /// ID of the campground
+    pub campground_id: String,
+    /// Name of the campground
+    pub campground_name: String,
+    /// Reservation system the campground is booked through
+    pub provider: String,
+    /// Two-letter state code
+    pub state: Option<String>,
+    /// Personal note about the campground
+    pub note: Option<String>,
+    /// Active or paused scans the user has on the campground
+    pub active_scans: i64,
+    /// Cached availability, if the campground was ever polled
+    pub availability: Option<FavoriteAvailability>,
+    /// When the campground was bookmarked
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Response of `GET /api/user/favorites`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavoritesResponse {
+    /// Favorite campgrounds, most recently added first
+    pub favorites: Vec<Favorite>,
+}
+
+/// Request body for `POST /api/trips`
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateTripRequest {
+    /// Name of the trip
+    pub name: String,
+    /// People going
+    pub party_size: i32,
+    /// First night the trip may start
+    pub start_date: NaiveDate,
+    /// Last day of the trip
+    pub end_date: NaiveDate,
+    /// Pause the trip's other active scans once one of them is booked; on when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_pause_on_booking: Option<bool>,
+}
+
+/// A trip with the aggregate status of its scans
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trip {
+    /// Trip ID
+    pub id: Uuid,
+    /// Name of the trip
+    pub name: String,
+    /// People going
+    pub party_size: i32,
+    /// First night the trip may start
+    pub start_date: NaiveDate,
+    /// Last day of the trip
+    pub end_date: NaiveDate,
+    /// Whether booking one scan pauses the trip's other active scans
+    pub auto_pause_on_booking: bool,
+    /// `planning`, `scanning`, `paused`, `booked`, or `ended`
+    pub status: String,
+    /// Number of scans in each status
+    pub scans_by_status: BTreeMap<String, i64>,
+    /// The scan that was booked, if any
+    pub booked_scan_id: Option<Uuid>,
+    /// Scans of the trip; only returned for a single trip
+    #[serde(default)]
+    pub scans: Vec<Scan>,
+}
+
+/// Response of `GET /api/trips`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TripsResponse {
+    /// Trips, soonest first
+    pub trips: Vec<Trip>,
+}
+
+#[cfg(test)]
+mod tests {
+    //! Contract between these types and the server's: values the server builds are read
+    //! back with the client types, and client requests are parsed with the server's, so
+    //! a field renamed or retyped on one side fails here.
+
+    use super::*;
+    use chrono::TimeZone;
+    use serde::de::DeserializeOwned;
+    use serde_json::{Value, json};
+
+    use auth_services::types as auth;
+    use campground_scan as scans;
+    use notification_services::{
+        ChannelDeliverability, DeliverabilityStatus as ServerDeliverability,
+    };
+    use rec_gov as ridb;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, day).unwrap()
+    }
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, hour, 0, 0).unwrap()
+    }
+
+    /// Serializes `server` as the API sends it, reads it as `C`, and checks every field
+    /// the client read came back as sent. `absent` names the client fields this response
+    /// legitimately leaves out.
+    fn assert_reads<C: Serialize + DeserializeOwned>(server: impl Serialize, absent: &[&str]) {
+        let sent = serde_json::to_value(server).unwrap();
+        let read: C = serde_json::from_value(sent.clone()).unwrap_or_else(|e| {
+            panic!("{} can't read {}: {}", std::any::type_name::<C>(), sent, e)
+        });
+
+        assert_subset(&serde_json::to_value(read).unwrap(), &sent, "$", absent);
+    }
+
+    fn assert_subset(read: &Value, sent: &Value, path: &str, absent: &[&str]) {
+        match (read, sent) {
+            (Value::Object(read), Value::Object(sent)) => {
+                for (key, value) in read {
+                    let path = format!("{}.{}", path, key);
+                    match sent.get(key) {
+                        Some(sent) => assert_subset(value, sent, &path, absent),
+                        None => assert!(
+                            absent.contains(&key.as_str()),
+                            "{} is read by the client but not sent by the server",
+                            path
+                        ),
+                    }
+                }
+            }
+            (Value::Array(read), Value::Array(sent)) if read.len() == sent.len() => {
+                for (i, (read, sent)) in read.iter().zip(sent).enumerate() {
+                    assert_subset(read, sent, &format!("{}[{}]", path, i), absent);
+                }
+            }
+            _ => assert_eq!(read, sent, "{} differs", path),
+        }
+    }
+
+    /// Parses a client request the way the server's handler does
+    fn parse<S: DeserializeOwned>(client: impl Serialize) -> S {
+        serde_json::from_value(serde_json::to_value(client).unwrap()).unwrap()
+    }
+
+    fn site_filters() -> ridb::SiteFilters {
+        ridb::SiteFilters {
+            electric_hookup: true,
+            water_hookup: true,
+            sewer_hookup: true,
+            vehicle_length: Some(30),
+            pets_allowed: true,
+        }
+    }
+
+    fn user_info() -> auth::UserInfo {
+        auth::UserInfo {
+            id: Uuid::new_v4(),
+            name: "Camper".to_string(),
+            email: "camper@example.com".to_string(),
+            phone: "+15555550123".to_string(),
+            email_verified: true,
+            phone_verified: true,
+            notification_preferences: auth::NotificationPreferences {
+                email: true,
+                sms: true,
+            },
+            locale: i18n::Locale::Spanish,
+            timezone: "America/Denver".to_string(),
+        }
+    }
+
+    fn scan() -> scans::UserScanWithCampground {
+        scans::UserScanWithCampground {
+            id: Uuid::new_v4(),
+            campground_id: "232447".to_string(),
+            campground_name: "Upper Pines".to_string(),
+            check_in_date: date(14),
+            check_out_date: date(16),
+            nights: 2,
+            status: "active".to_string(),
+            notification_sent: true,
+            created_at: at(1),
+            updated_at: at(2),
+            expires_at: Some(at(3)),
+            organization_id: Some(Uuid::new_v4()),
+            facility_type: "cabin".to_string(),
+            provider: "recreation_gov".to_string(),
+            flexibility_days: 2,
+            max_price: Some(40),
+            site_filters: site_filters(),
+            quick_book: true,
+            outcome: Some("booked".to_string()),
+            outcome_at: Some(at(4)),
+            trip_id: Some(Uuid::new_v4()),
+            last_polled: Some(at(5)),
+            next_poll_at: Some(at(6)),
+            last_error: Some("Recreation.gov timed out".to_string()),
+            health: scans::ScanHealth::Degraded,
+            seasonal_closure: Some(scans::SeasonalClosure {
+                closed_since: at(7),
+                season: Some(ridb::OperatingSeason {
+                    opens_month: 5,
+                    closes_month: 10,
+                }),
+                outside_season: Some(true),
+                suggested_check_in: Some(date(20)),
+            }),
+        }
+    }
+
+    #[test]
+    fn reads_auth_and_profile_responses() {
+        assert_reads::<AuthResponse>(
+            auth::AuthResponse {
+                access_token: "access".to_string(),
+                refresh_token: "refresh".to_string(),
+                user: user_info(),
+            },
+            &[],
+        );
+
+        assert_reads::<Profile>(
+            web_handlers::ProfileResponse {
+                user: user_info(),
+                deliverability: ServerDeliverability {
+                    email: ChannelDeliverability::deliverable(),
+                    sms: ChannelDeliverability::undeliverable("Landline"),
+                },
+                pending_email: Some("new@example.com".to_string()),
+            },
+            &[],
+        );
+    }
+
+    #[test]
+    fn reads_scan_responses() {
+        assert_reads::<ListScansResponse>(
+            scans::ListScansResponse {
+                scans: vec![scan()],
+                total: 1,
+                quota: Some(scans::ScanQuota {
+                    plan: "plus".to_string(),
+                    max_active_scans: Some(10),
+                    active_scans: 1,
+                    remaining: Some(9),
+                }),
+            },
+            &["warnings"],
+        );
+
+        let created = scan();
+        assert_reads::<Scan>(
+            scans::CreateScanResponse {
+                id: created.id,
+                campground_id: created.campground_id,
+                campground_name: created.campground_name,
+                check_in_date: created.check_in_date,
+                check_out_date: created.check_out_date,
+                nights: created.nights,
+                status: created.status,
+                notification_sent: created.notification_sent,
+                created_at: created.created_at,
+                organization_id: created.organization_id,
+                facility_type: created.facility_type,
+                provider: created.provider,
+                flexibility_days: created.flexibility_days,
+                max_price: created.max_price,
+                site_filters: created.site_filters,
+                quick_book: created.quick_book,
+                warnings: vec![scans::ScanWarning::RedundantCampgroundScans {
+                    campground_id: "232447".to_string(),
+                    scan_count: 3,
+                    message: "You already watch Upper Pines".to_string(),
+                    consolidate: vec![scans::ConsolidationSuggestion {
+                        scan_ids: vec![Uuid::new_v4(), Uuid::new_v4()],
+                        check_in_date: date(14),
+                        check_out_date: date(16),
+                        flexibility_days: 3,
+                    }],
+                }],
+            },
+            // Only known once the scan has been polled
+            &[
+                "updated_at",
+                "expires_at",
+                "outcome",
+                "outcome_at",
+                "trip_id",
+                "last_polled",
+                "next_poll_at",
+                "last_error",
+                "health",
+                "seasonal_closure",
+            ],
+        );
+
+        assert_reads::<RemapSuggestionsResponse>(
+            scans::RemapSuggestionsResponse {
+                scan_id: Uuid::new_v4(),
+                current_campground_id: "232447".to_string(),
+                suggestions: vec![scans::RemapSuggestion {
+                    campground_id: "232450".to_string(),
+                    campground_name: "Lower Pines".to_string(),
+                    distance_miles: Some(0.4),
+                    score: 0.9,
+                }],
+            },
+            &[],
+        );
+
+        assert_reads::<ScanEventsResponse>(
+            json!({
+                "scan_id": Uuid::new_v4(),
+                "events": [scans::ScanEvent {
+                    id: 7,
+                    event_type: "polled".to_string(),
+                    details: json!({"open_sites": 3}),
+                    created_at: at(1),
+                }],
+            }),
+            &[],
+        );
+    }
+
+    #[test]
+    fn reads_sharing_and_recipient_responses() {
+        assert_reads::<ScanShareLink>(
+            scans::ScanShareLink {
+                scan_id: Uuid::new_v4(),
+                slug: "upper-pines-ab12".to_string(),
+                url: "https://example.com/shared/upper-pines-ab12".to_string(),
+                clone_count: 2,
+            },
+            &[],
+        );
+
+        assert_reads::<SharedScan>(
+            scans::SharedScan {
+                slug: "upper-pines-ab12".to_string(),
+                campground_id: "232447".to_string(),
+                campground_name: "Upper Pines".to_string(),
+                provider: "recreation_gov".to_string(),
+                facility_type: "campsite".to_string(),
+                check_in_date: date(14),
+                check_out_date: date(16),
+                nights: 2,
+                flexibility_days: 1,
+                site_filters: site_filters(),
+                status: "active".to_string(),
+                clone_count: 2,
+                shared_at: Some(at(1)),
+            },
+            &[],
+        );
+
+        assert_reads::<ScanRecipientsResponse>(
+            json!({
+                "scan_id": Uuid::new_v4(),
+                "recipients": [scans::InvitedRecipient {
+                    id: Uuid::new_v4(),
+                    scan_id: Uuid::new_v4(),
+                    channel: "email".to_string(),
+                    address: "friend@example.com".to_string(),
+                    status: "confirmed".to_string(),
+                    token: "secret".to_string(),
+                    created_at: Some(at(1)),
+                    responded_at: Some(at(2)),
+                }],
+            }),
+            &[],
+        );
+    }
+
+    #[test]
+    fn reads_campground_responses() {
+        assert_reads::<CampgroundSearchResponse>(
+            ridb::CampgroundSearchResponse {
+                campgrounds: vec![ridb::CampgroundSearchResult {
+                    id: "232447".to_string(),
+                    name: "Upper Pines".to_string(),
+                    state: Some("CA".to_string()),
+                    recarea_name: Some("Yosemite National Park".to_string()),
+                    provider: "recreation_gov".to_string(),
+                    reservable: true,
+                    latitude: Some(37.7356),
+                    longitude: Some(-119.5627),
+                }],
+                source: ridb::SearchSource::Cache,
+            },
+            &[],
+        );
+
+        assert_reads::<CampgroundDetails>(
+            ridb::CampgroundDetails {
+                facility: ridb::Facility {
+                    id: "232447".to_string(),
+                    name: "Upper Pines".to_string(),
+                    description: "<p>In Yosemite Valley</p>".to_string(),
+                    type_description: Some("Campground".to_string()),
+                    latitude: Some(37.7356),
+                    longitude: Some(-119.5627),
+                    state: Some("CA".to_string()),
+                    reservable: Some(true),
+                    stay_limit: Some("7 days".to_string()),
+                    location_description: Some("3 mi SE of Yosemite Valley, CA".to_string()),
+                    organizations: Vec::new(),
+                    addresses: Vec::new(),
+                },
+                campsites: vec![ridb::Campsite {
+                    id: "100".to_string(),
+                    name: "A01".to_string(),
+                    site_type: Some("STANDARD NONELECTRIC".to_string()),
+                    type_of_use: Some("Overnight".to_string()),
+                    loop_name: Some("A".to_string()),
+                    accessible: true,
+                }],
+                photos: vec![ridb::EntityMedia {
+                    media_type: "Image".to_string(),
+                    title: "Meadow".to_string(),
+                    url: "https://example.com/meadow.jpg".to_string(),
+                    credits: Some("NPS".to_string()),
+                    is_primary: true,
+                }],
+                fetched_at: at(1),
+            },
+            &[],
+        );
+
+        assert_reads::<AvailabilityPreview>(
+            ridb::AvailabilityPreview {
+                campground_id: "232447".to_string(),
+                start: date(14),
+                end: date(16),
+                available_sites_per_night: [(date(14), 1), (date(15), 0)].into(),
+                campsites: vec![ridb::SiteCalendar {
+                    campsite_id: "100".to_string(),
+                    site: "A01".to_string(),
+                    loop_name: Some("A".to_string()),
+                    campsite_type: Some("STANDARD NONELECTRIC".to_string()),
+                    facility_type: ridb::FacilityType::GroupSite,
+                    nights: [
+                        (date(14), "Available".to_string()),
+                        (date(15), "Reserved".to_string()),
+                    ]
+                    .into(),
+                    nightly_prices: [(date(14), 36.0)].into(),
+                }],
+                generated_at: at(1),
+            },
+            &[],
+        );
+    }
+
+    #[test]
+    fn reads_favorite_and_trip_responses() {
+        assert_reads::<FavoritesResponse>(
+            scans::FavoritesResponse {
+                favorites: vec![scans::FavoriteCampground {
+                    campground_id: "232447".to_string(),
+                    campground_name: "Upper Pines".to_string(),
+                    provider: "recreation_gov".to_string(),
+                    state: Some("CA".to_string()),
+                    note: Some("Loop A by the river".to_string()),
+                    active_scans: 1,
+                    availability: Some(scans::FavoriteAvailability {
+                        nights_available: 4,
+                        first_available_date: Some(date(14)),
+                        max_available_sites: 2,
+                        last_checked: at(1),
+                    }),
+                    created_at: Some(at(2)),
+                }],
+            },
+            &[],
+        );
+
+        let overview = scans::TripOverview {
+            trip: scans::Trip {
+                id: Uuid::new_v4(),
+                name: "Yosemite".to_string(),
+                party_size: 4,
+                start_date: date(14),
+                end_date: date(18),
+                auto_pause_on_booking: true,
+                created_at: Some(at(1)),
+            },
+            progress: scans::TripProgress {
+                status: scans::TripStatus::Booked,
+                scans_by_status: [("completed".to_string(), 1)].into(),
+                booked_scan_id: Some(Uuid::new_v4()),
+            },
+        };
+
+        assert_reads::<TripsResponse>(
+            scans::ListTripsResponse {
+                trips: vec![overview.clone()],
+            },
+            // Scans only come with a single trip
+            &["scans"],
+        );
+        assert_reads::<Trip>(
+            scans::TripDetails {
+                overview,
+                scans: vec![scan()],
+            },
+            &["warnings"],
+        );
+    }
+
+    #[test]
+    fn server_parses_auth_and_profile_requests() {
+        let signup: auth::SignUpRequest = parse(SignUpRequest {
+            name: "Camper".to_string(),
+            email: "camper@example.com".to_string(),
+            phone: "5555550123".to_string(),
+            password: "correct horse".to_string(),
+            notification_preferences: NotificationPreferences {
+                email: true,
+                sms: false,
+            },
+            locale: Some("es".to_string()),
+            timezone: Some("America/Denver".to_string()),
+        });
+        assert_eq!(signup.name, "Camper");
+        assert_eq!(signup.email, "camper@example.com");
+        assert_eq!(signup.phone, "5555550123");
+        assert_eq!(signup.password, "correct horse");
+        assert!(signup.notification_preferences.email && !signup.notification_preferences.sms);
+        assert_eq!(signup.locale, Some(i18n::Locale::Spanish));
+        assert_eq!(signup.timezone.as_deref(), Some("America/Denver"));
+
+        let login: auth::LoginRequest = parse(LoginRequest {
+            email: "camper@example.com".to_string(),
+            password: "correct horse".to_string(),
+        });
+        assert_eq!(login.email, "camper@example.com");
+        assert_eq!(login.password, "correct horse");
+
+        let patch: auth::PatchProfileRequest = parse(PatchProfileRequest {
+            name: Some("Camper".to_string()),
+            email: Some("new@example.com".to_string()),
+            phone: Some("5555550123".to_string()),
+            notification_preferences: Some(NotificationPreferences {
+                email: false,
+                sms: true,
+            }),
+            locale: Some("es".to_string()),
+            timezone: Some("America/Denver".to_string()),
+        });
+        assert_eq!(patch.name.as_deref(), Some("Camper"));
+        assert_eq!(patch.email.as_deref(), Some("new@example.com"));
+        assert_eq!(patch.phone.as_deref(), Some("5555550123"));
+        assert!(
+            patch
+                .notification_preferences
+                .is_some_and(|p| p.sms && !p.email)
+        );
+        assert_eq!(patch.locale, Some(i18n::Locale::Spanish));
+        assert_eq!(patch.timezone.as_deref(), Some("America/Denver"));
+    }
+
+    #[test]
+    fn server_parses_scan_requests() {
+        let organization_id = Uuid::new_v4();
+        let create: scans::CreateScanRequest = parse(CreateScanRequest {
+            campground_id: "232447".to_string(),
+            campground_name: "Upper Pines".to_string(),
+            check_in_date: date(14),
+            check_out_date: date(16),
+            organization_id: Some(organization_id),
+            facility_type: Some("cabin".to_string()),
+            provider: Some("reserve_california".to_string()),
+            flexibility_days: Some(2),
+            max_price: Some(40),
+            site_filters: Some(SiteFilters {
+                electric_hookup: true,
+                water_hookup: true,
+                sewer_hookup: true,
+                vehicle_length: Some(30),
+                pets_allowed: true,
+            }),
+            quick_book: Some(true),
+        });
+        assert_eq!(create.campground_id, "232447");
+        assert_eq!(create.campground_name, "Upper Pines");
+        assert_eq!(
+            (create.check_in_date, create.check_out_date),
+            (date(14), date(16))
+        );
+        assert_eq!(create.organization_id, Some(organization_id));
+        assert_eq!(create.facility_type, ridb::FacilityType::Cabin);
+        assert_eq!(create.provider, ridb::Provider::ReserveCalifornia);
+        assert_eq!(create.flexibility_days, 2);
+        assert_eq!(create.max_price, Some(40));
+        assert_eq!(create.site_filters, site_filters());
+        assert!(create.quick_book);
+
+        let update: scans::UpdateScanRequest = parse(UpdateScanRequest {
+            status: "paused".to_string(),
+        });
+        assert_eq!(update.status, "paused");
+
+        let outcome: scans::ScanOutcomeRequest = parse(ScanOutcomeRequest {
+            outcome: "gave_up".to_string(),
+        });
+        assert_eq!(outcome.outcome, scans::ScanOutcome::GaveUp);
+
+        let patch: scans::PatchScanRequest = parse(PatchScanRequest {
+            check_in_date: Some(date(15)),
+            check_out_date: Some(date(17)),
+            facility_type: Some("lookout".to_string()),
+            flexibility_days: Some(1),
+            max_price: Some(0),
+            site_filters: Some(SiteFilters::default()),
+            quick_book: Some(false),
+        });
+        assert_eq!(patch.check_in_date, Some(date(15)));
+        assert_eq!(patch.check_out_date, Some(date(17)));
+        assert_eq!(patch.facility_type, Some(ridb::FacilityType::Lookout));
+        assert_eq!(patch.flexibility_days, Some(1));
+        assert_eq!(patch.max_price, Some(0));
+        assert_eq!(patch.site_filters, Some(ridb::SiteFilters::default()));
+        assert_eq!(patch.quick_book, Some(false));
+
+        let remap: scans::RemapScanRequest = parse(RemapScanRequest {
+            campground_id: Some("232450".to_string()),
+            campground_name: Some("Lower Pines".to_string()),
+        });
+        assert_eq!(remap.campground_id.as_deref(), Some("232450"));
+        assert_eq!(remap.campground_name.as_deref(), Some("Lower Pines"));
+
+        let recipient: scans::AddScanRecipientRequest = parse(AddScanRecipientRequest {
+            email: Some("friend@example.com".to_string()),
+            phone: Some("5555550123".to_string()),
+        });
+        assert_eq!(recipient.email.as_deref(), Some("friend@example.com"));
+        assert_eq!(recipient.phone.as_deref(), Some("5555550123"));
+    }
+
+    #[test]
+    fn server_parses_favorite_and_trip_requests() {
+        let favorite: scans::AddFavoriteRequest = parse(AddFavoriteRequest {
+            campground_id: "232447".to_string(),
+            campground_name: "Upper Pines".to_string(),
+            provider: Some("reserve_california".to_string()),
+            note: Some("Loop A by the river".to_string()),
+        });
+        assert_eq!(favorite.campground_id, "232447");
+        assert_eq!(favorite.campground_name, "Upper Pines");
+        assert_eq!(favorite.provider, ridb::Provider::ReserveCalifornia);
+        assert_eq!(favorite.note.as_deref(), Some("Loop A by the river"));
+
+        let scan: scans::FavoriteScanRequest = parse(FavoriteScanRequest {
+            check_in_date: date(14),
+            check_out_date: date(16),
+            facility_type: Some("group_site".to_string()),
+            flexibility_days: Some(3),
+        });
+        assert_eq!(
+            (scan.check_in_date, scan.check_out_date),
+            (date(14), date(16))
+        );
+        assert_eq!(scan.facility_type, ridb::FacilityType::GroupSite);
+        assert_eq!(scan.flexibility_days, 3);
+
+        let trip: scans::CreateTripRequest = parse(CreateTripRequest {
+            name: "Yosemite".to_string(),
+            party_size: 4,
+            start_date: date(14),
+            end_date: date(18),
+            auto_pause_on_booking: Some(false),
+        });
+        assert_eq!(trip.name, "Yosemite");
+        assert_eq!(trip.party_size, 4);
+        assert_eq!((trip.start_date, trip.end_date), (date(14), date(18)));
+        assert!(!trip.auto_pause_on_booking);
+    }
+}
@@ -0,0 +1,53 @@
+# Messages of API error responses, by error code
+
+missing-token = Authorization token is required
+invalid-token = Invalid or expired token
+internal-error = An internal error occurred
+rate-limited =
+    { $minutes ->
+        [one] Too many requests; try again in a minute
+       *[other] Too many requests; try again in { $minutes } minutes
+    }
+
+## Accounts
+
+email-exists = An account with this email already exists
+invalid-credentials = Invalid email or password
+user-not-found = User not found
+invalid-phone-number = Please enter a valid US phone number
+two-factor-enabled = Two-factor authentication is already enabled
+two-factor-not-set-up = Set up two-factor authentication first
+invalid-two-factor-code = Invalid authentication code
+too-many-attempts = Too many wrong codes; try again in 15 minutes
+invalid-challenge = Login challenge is invalid or expired; sign in again
+
+## Organizations
+
+organization-not-found = Organization not found
+owner-required = Only the organization owner can do this
+invalid-invitation = This invitation is invalid or has expired
+
+## Scans
+
+scan-not-found = Scan not found
+scan-unauthorized = You are not authorized to access this scan
+invalid-date-range = Check-out date must be after check-in date
+campground-not-found = Campground not found
+trip-not-found = Trip not found
+not-reservable = { $campground } is first-come, first-served and can't be reserved online, so a scan would never find availability.
+stay-too-long = { $campground } allows stays of at most { $max } nights, so a { $nights }-night stay can't be booked.
+no-deliverable-channel = None of your enabled notification channels can currently receive messages. Check your profile before creating a scan.
+quota-exceeded = Your { $plan } plan allows { $limit } active scans. Pause or delete a scan, or upgrade your plan.
+redundant-scans = You have { $count } scans on { $campground }. A campground is checked on one schedule however many scans watch it, so more scans don't find openings sooner.
+
+## Campgrounds and permits
+
+missing-search-query = Missing query parameter
+unknown-agency = Unknown agency '{ $agency }', expected NPS, USFS or BLM
+search-failed = Search failed
+campground-load-failed = Failed to load campground
+preview-end-before-start = End must be after start
+preview-too-long = The preview covers at most { $max } nights
+availability-load-failed = Failed to load availability
+permit-date-range = End must be on or after start and at most { $max } days later
+permit-availability-failed = Failed to fetch permit availability from recreation.gov
@@ -0,0 +1,11 @@
+# Text that email and SMS templates get already written
+
+date-range = { $start } to { $end }
+stay-tonight = tonight, { $stay }
+stay-tomorrow = tomorrow night, { $stay }
+site-price = { $site } – { $price }/night
+sms-user-budget-exhausted = You've reached this month's text message limit, so this alert was sent by email instead.
+sms-global-budget-exhausted = Text message alerts are paused for the rest of the month, so this alert was sent by email instead.
+test-alert-notice = This is a test alert sent from the admin dashboard. No sites actually opened up.
+alert-reason-cancellation = Likely a cancellation, so it may go fast.
+alert-reason-release = Newly released sites.
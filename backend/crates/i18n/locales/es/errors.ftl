@@ -0,0 +1,53 @@
+# Mensajes de las respuestas de error de la API, por código de error
+
+missing-token = Se requiere un token de autorización
+invalid-token = Token no válido o vencido
+internal-error = Se produjo un error interno
+rate-limited =
+    { $minutes ->
+        [one] Demasiadas solicitudes; inténtalo de nuevo en un minuto
+       *[other] Demasiadas solicitudes; inténtalo de nuevo en { $minutes } minutos
+    }
+
+## Cuentas
+
+email-exists = Ya existe una cuenta con este correo electrónico
+invalid-credentials = Correo electrónico o contraseña incorrectos
+user-not-found = Usuario no encontrado
+invalid-phone-number = Ingresa un número de teléfono válido de EE. UU.
+two-factor-enabled = La autenticación en dos pasos ya está activada
+two-factor-not-set-up = Primero configura la autenticación en dos pasos
+invalid-two-factor-code = Código de autenticación no válido
+too-many-attempts = Demasiados códigos incorrectos; inténtalo de nuevo en 15 minutos
+invalid-challenge = El desafío de inicio de sesión no es válido o venció; vuelve a iniciar sesión
+
+## Organizaciones
+
+organization-not-found = Organización no encontrada
+owner-required = Solo el propietario de la organización puede hacer esto
+invalid-invitation = Esta invitación no es válida o ya venció
+
+## Búsquedas
+
+scan-not-found = Búsqueda no encontrada
+scan-unauthorized = No tienes permiso para acceder a esta búsqueda
+invalid-date-range = La fecha de salida debe ser posterior a la fecha de llegada
+campground-not-found = Campamento no encontrado
+trip-not-found = Viaje no encontrado
+not-reservable = { $campground } funciona por orden de llegada y no se puede reservar en línea, así que una búsqueda nunca encontraría disponibilidad.
+stay-too-long = { $campground } permite estancias de { $max } noches como máximo, así que no se puede reservar una estancia de { $nights } noches.
+no-deliverable-channel = Ninguno de tus canales de notificación activos puede recibir mensajes en este momento. Revisa tu perfil antes de crear una búsqueda.
+quota-exceeded = Tu plan { $plan } permite { $limit } búsquedas activas. Pausa o elimina una búsqueda, o mejora tu plan.
+redundant-scans = Tienes { $count } búsquedas en { $campground }. Un campamento se revisa con un solo horario sin importar cuántas búsquedas lo vigilen, así que más búsquedas no encuentran disponibilidad antes.
+
+## Campamentos y permisos
+
+missing-search-query = Falta el parámetro de búsqueda
+unknown-agency = Agencia desconocida «{ $agency }»; se esperaba NPS, USFS o BLM
+search-failed = La búsqueda falló
+campground-load-failed = No se pudo cargar el campamento
+preview-end-before-start = La fecha final debe ser posterior a la inicial
+preview-too-long = La vista previa cubre como máximo { $max } noches
+availability-load-failed = No se pudo cargar la disponibilidad
+permit-date-range = La fecha final debe ser igual o posterior a la inicial y como máximo { $max } días después
+permit-availability-failed = No se pudo obtener la disponibilidad de permisos de recreation.gov
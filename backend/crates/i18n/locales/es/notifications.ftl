@@ -0,0 +1,11 @@
+# Texto que las plantillas de correo y SMS reciben ya escrito
+
+date-range = del { $start } al { $end }
+stay-tonight = esta noche, { $stay }
+stay-tomorrow = mañana por la noche, { $stay }
+site-price = { $site } – { $price }/noche
+sms-user-budget-exhausted = Llegaste al límite de mensajes de texto de este mes, así que esta alerta se envió por correo electrónico.
+sms-global-budget-exhausted = Las alertas por mensaje de texto están en pausa por el resto del mes, así que esta alerta se envió por correo electrónico.
+test-alert-notice = Esta es una alerta de prueba enviada desde el panel de administración. No se ha liberado ningún sitio.
+alert-reason-cancellation = Probablemente una cancelación, así que puede volar.
+alert-reason-release = Sitios recién liberados.
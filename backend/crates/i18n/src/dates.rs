@@ -0,0 +1,82 @@
+use chrono::{Datelike, NaiveDate};
+
+use crate::locale::Locale;
+use crate::messages::{FluentArgs, message_with};
+
+const ENGLISH_WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const SPANISH_WEEKDAYS: [&str; 7] = ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"];
+const ENGLISH_MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const SPANISH_MONTHS: [&str; 12] = [
+    "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+];
+
+impl Locale {
+    /// Abbreviated weekday, e.g. `Thu` or `jue`
+    pub fn weekday(&self, date: NaiveDate) -> &'static str {
+        let weekday = date.weekday().num_days_from_monday() as usize;
+        match self {
+            Locale::English => ENGLISH_WEEKDAYS[weekday],
+            Locale::Spanish => SPANISH_WEEKDAYS[weekday],
+        }
+    }
+
+    /// Abbreviated month, e.g. `Aug` or `ago`
+    pub fn month(&self, date: NaiveDate) -> &'static str {
+        let month = date.month0() as usize;
+        match self {
+            Locale::English => ENGLISH_MONTHS[month],
+            Locale::Spanish => SPANISH_MONTHS[month],
+        }
+    }
+
+    /// Day and month, e.g. `Aug 14` or `14 ago`
+    pub fn short_date(&self, date: NaiveDate) -> String {
+        match self {
+            Locale::English => format!("{} {}", self.month(date), date.day()),
+            Locale::Spanish => format!("{} {}", date.day(), self.month(date)),
+        }
+    }
+
+    /// Weekday, day and month, e.g. `Thu Aug 14` or `jue 14 ago`
+    pub fn weekday_date(&self, date: NaiveDate) -> String {
+        format!("{} {}", self.weekday(date), self.short_date(date))
+    }
+
+    /// Weekday, day, month and year, e.g. `Thu Aug 14, 2025` or `jue 14 ago 2025`
+    pub fn full_date(&self, date: NaiveDate) -> String {
+        match self {
+            Locale::English => format!("{}, {}", self.weekday_date(date), date.year()),
+            Locale::Spanish => format!("{} {}", self.weekday_date(date), date.year()),
+        }
+    }
+
+    /// Dates of a stay, e.g. `Thu Aug 14 to Sat Aug 16, 2025` or
+    /// `del jue 14 ago al sáb 16 ago 2025`
+    pub fn date_range(&self, start: NaiveDate, end: NaiveDate) -> String {
+        let mut args = FluentArgs::new();
+        args.set("start", self.weekday_date(start));
+        args.set("end", self.full_date(end));
+        message_with(*self, "date-range", &args)
+    }
+
+    /// Stay as shown in alerts, e.g. `Aug 14 – Aug 16`, led by `tonight` or `tomorrow
+    /// night` when it starts on `today` or the day after
+    pub fn stay(&self, check_in: NaiveDate, check_out: NaiveDate, today: NaiveDate) -> String {
+        let stay = format!(
+            "{} – {}",
+            self.short_date(check_in),
+            self.short_date(check_out)
+        );
+        let id = match (check_in - today).num_days() {
+            0 => "stay-tonight",
+            1 => "stay-tomorrow",
+            _ => return stay,
+        };
+
+        let mut args = FluentArgs::new();
+        args.set("stay", stay);
+        message_with(*self, id, &args)
+    }
+}
@@ -0,0 +1,27 @@
+//! # I18n
+//!
+//! This crate provides the languages CampTracker speaks: the locale of each request and
+//! user, the translated messages of API errors and notifications (Fluent files in
+//! `locales/`), dates written the way each language writes them, users' time zones, and US phone numbers.
+
+/// Supported locales, negotiation from `Accept-Language`, and the locale of the request
+/// being handled
+mod locale;
+pub use locale::*;
+
+/// Translated messages loaded from the Fluent files
+mod messages;
+pub use messages::*;
+
+/// Dates written in each locale, as methods of [`Locale`]
+mod dates;
+
+/// Users' time zones and the dates they see
+mod timezone;
+pub use timezone::*;
+
+/// US phone numbers as users type them and as SMS providers expect them
+mod phone;
+pub use phone::*;
+
+pub use chrono_tz::Tz;
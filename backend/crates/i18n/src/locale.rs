@@ -0,0 +1,147 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::future::Future;
+use std::str::FromStr;
+
+/// Language messages and notifications are written in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    /// English, the fallback for anything not translated
+    #[default]
+    English,
+    /// Spanish
+    Spanish,
+}
+
+impl Locale {
+    /// Every supported locale
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::Spanish];
+
+    /// Language code stored with users and used in template paths (`en`, `es`)
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::Spanish => "es",
+        }
+    }
+
+    /// Picks the supported locale the client prefers most from an `Accept-Language`
+    /// header, e.g. `es-MX,es;q=0.9,en;q=0.8`. `None` when none of them is supported.
+    pub fn from_accept_language(header: &str) -> Option<Locale> {
+        let mut best: Option<(Locale, f32)> = None;
+
+        for entry in header.split(',') {
+            let mut parts = entry.split(';');
+            let Ok(locale) = parts.next().unwrap_or_default().parse::<Locale>() else {
+                continue;
+            };
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|quality| quality.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if quality > 0.0 && best.is_none_or(|(_, best_quality)| quality > best_quality) {
+                best = Some((locale, quality));
+            }
+        }
+
+        best.map(|(locale, _)| locale)
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// A language code that isn't supported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedLocale(pub String);
+
+impl fmt::Display for UnsupportedLocale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unsupported language '{}', expected one of {}",
+            self.0,
+            Locale::ALL.map(|locale| locale.code()).join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedLocale {}
+
+impl FromStr for Locale {
+    type Err = UnsupportedLocale;
+
+    /// Reads a language tag such as `es`, `es-MX` or `en_US`; only the language counts.
+    fn from_str(tag: &str) -> Result<Self, Self::Err> {
+        let language = tag
+            .trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        match language.as_str() {
+            "en" => Ok(Locale::English),
+            "es" => Ok(Locale::Spanish),
+            _ => Err(UnsupportedLocale(tag.trim().to_string())),
+        }
+    }
+}
+
+impl Serialize for Locale {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for Locale {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+tokio::task_local! {
+    static LOCALE: Locale;
+}
+
+/// Runs `future` with `locale` as the current locale, so the errors and messages it
+/// produces are written in it.
+pub async fn with_locale<F: Future>(locale: Locale, future: F) -> F::Output {
+    LOCALE.scope(locale, future).await
+}
+
+/// Locale of the request being handled; English outside a request (background jobs
+/// pass the recipient's locale explicitly).
+pub fn current_locale() -> Locale {
+    LOCALE.try_with(|locale| *locale).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiates_accept_language() {
+        assert_eq!(
+            Locale::from_accept_language("es-MX,es;q=0.9,en;q=0.8"),
+            Some(Locale::Spanish)
+        );
+        assert_eq!(
+            Locale::from_accept_language("fr-FR, en;q=0.5, es;q=0.7"),
+            Some(Locale::Spanish)
+        );
+        assert_eq!(
+            Locale::from_accept_language("en-US,es;q=0"),
+            Some(Locale::English)
+        );
+        assert_eq!(Locale::from_accept_language("de, fr;q=0.8"), None);
+        assert_eq!("ES_es".parse(), Ok(Locale::Spanish));
+        assert!("pt".parse::<Locale>().is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use fluent_bundle::FluentResource;
+use fluent_bundle::concurrent::FluentBundle;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use unic_langid::LanguageIdentifier;
+
+use crate::locale::Locale;
+
+pub use fluent_bundle::{FluentArgs, FluentValue};
+
+/// Fluent files of each locale, compiled into the binary
+const MESSAGE_FILES: &[(Locale, &str)] = &[
+    (Locale::English, include_str!("../locales/en/errors.ftl")),
+    (
+        Locale::English,
+        include_str!("../locales/en/notifications.ftl"),
+    ),
+    (Locale::Spanish, include_str!("../locales/es/errors.ftl")),
+    (
+        Locale::Spanish,
+        include_str!("../locales/es/notifications.ftl"),
+    ),
+];
+
+/// Message bundles by locale, parsed on first use. The files are part of the binary, so
+/// a syntax error in one is a bug caught by the tests rather than a runtime condition.
+static BUNDLES: LazyLock<HashMap<Locale, FluentBundle<FluentResource>>> = LazyLock::new(|| {
+    let mut bundles = HashMap::new();
+
+    for (locale, source) in MESSAGE_FILES {
+        let bundle = bundles.entry(*locale).or_insert_with(|| {
+            let language: LanguageIdentifier = locale
+                .code()
+                .parse()
+                .expect("locale codes are valid language identifiers");
+            let mut bundle = FluentBundle::new_concurrent(vec![language]);
+            // Messages end up in JSON and plain text, where Unicode isolation marks
+            // around arguments would show
+            bundle.set_use_isolating(false);
+            bundle
+        });
+
+        let resource =
+            FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+                log::error!("❌ Invalid {} messages: {:?}", locale, errors);
+                resource
+            });
+        if let Err(errors) = bundle.add_resource(resource) {
+            log::error!("❌ Duplicate {} messages: {:?}", locale, errors);
+        }
+    }
+
+    bundles
+});
+
+/// Message `id` written in `locale`, falling back to English when it isn't translated
+/// and to the ID itself when it doesn't exist.
+pub fn message(locale: Locale, id: &str) -> String {
+    format_message(locale, id, None)
+}
+
+/// Message `id` written in `locale` with its arguments filled in, e.g.
+/// `{ $campground } is first-come, first-served`.
+pub fn message_with(locale: Locale, id: &str, args: &FluentArgs) -> String {
+    format_message(locale, id, Some(args))
+}
+
+/// Returns true if every locale has message `id`.
+pub fn is_translated(id: &str) -> bool {
+    Locale::ALL.iter().all(|locale| {
+        BUNDLES
+            .get(locale)
+            .is_some_and(|bundle| bundle.has_message(id))
+    })
+}
+
+fn format_message(locale: Locale, id: &str, args: Option<&FluentArgs>) -> String {
+    for locale in [locale, Locale::English] {
+        let Some(bundle) = BUNDLES.get(&locale) else {
+            continue;
+        };
+        let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+            continue;
+        };
+
+        let mut errors = Vec::new();
+        let formatted = bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            log::warn!(
+                "⚠️ Errors formatting {} message {}: {:?}",
+                locale,
+                id,
+                errors
+            );
+        }
+        return formatted.into_owned();
+    }
+
+    log::warn!("⚠️ Missing message {}", id);
+    id.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_message_is_translated_and_formats() {
+        for (locale, source) in MESSAGE_FILES {
+            FluentResource::try_new(source.to_string())
+                .unwrap_or_else(|(_, errors)| panic!("invalid {} messages: {:?}", locale, errors));
+
+            // Messages start a line with their ID
+            for line in source.lines() {
+                if let Some((id, _)) = line.split_once(" =")
+                    && id.starts_with(|c: char| c.is_ascii_lowercase())
+                {
+                    assert!(is_translated(id), "{} isn't in every locale", id);
+                }
+            }
+        }
+
+        let mut args = FluentArgs::new();
+        args.set("plan", "plus");
+        args.set("limit", 5);
+        assert_eq!(
+            message_with(Locale::Spanish, "quota-exceeded", &args),
+            "Tu plan plus permite 5 búsquedas activas. Pausa o elimina una búsqueda, o mejora tu plan."
+        );
+
+        let mut args = FluentArgs::new();
+        args.set("minutes", 1);
+        assert_eq!(
+            message_with(Locale::English, "rate-limited", &args),
+            "Too many requests; try again in a minute"
+        );
+        assert_eq!(
+            message(Locale::Spanish, "no-such-message"),
+            "no-such-message"
+        );
+    }
+}
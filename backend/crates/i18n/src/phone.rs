@@ -0,0 +1,42 @@
+/// Validates a US phone number format
+pub fn validate_phone_number(phone: &str) -> bool {
+    // Remove all non-digit characters
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    // US phone numbers should be 10 digits, or 11 if they include the country code (1)
+    match digits.len() {
+        10 => true,
+        11 => digits.starts_with('1'),
+        _ => false,
+    }
+}
+
+/// Formats a phone number that passed `validate_phone_number` to E.164 format
+pub fn format_phone_number(phone: &str) -> String {
+    // Remove all non-digit characters
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    // Add +1 if it's a 10-digit US number
+    if digits.len() == 10 {
+        format!("+1{}", digits)
+    } else {
+        // For 11-digit numbers starting with 1, or any other format, just add +
+        format!("+{}", digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn us_numbers_are_formatted_as_e164() {
+        assert!(validate_phone_number("(555) 555-0123"));
+        assert!(validate_phone_number("1-555-555-0123"));
+        assert!(!validate_phone_number("2-555-555-0123"));
+        assert!(!validate_phone_number("555-0123"));
+
+        assert_eq!(format_phone_number("(555) 555-0123"), "+15555550123");
+        assert_eq!(format_phone_number("1-555-555-0123"), "+15555550123");
+    }
+}
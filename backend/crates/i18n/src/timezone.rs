@@ -0,0 +1,26 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+/// Time zone of users who haven't picked one, matching the `users.timezone` default
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::America::Los_Angeles;
+
+/// Reads an IANA time zone name such as `America/Denver`, or `None` if it isn't one
+pub fn parse_timezone(name: &str) -> Option<Tz> {
+    name.trim().parse().ok()
+}
+
+/// Time zone of a user as stored in `users.timezone`; [`DEFAULT_TIMEZONE`] when it's
+/// missing or not a known zone
+pub fn user_timezone(name: Option<&str>) -> Tz {
+    name.and_then(parse_timezone).unwrap_or(DEFAULT_TIMEZONE)
+}
+
+/// Today's date in `timezone`
+pub fn today_in(timezone: Tz) -> NaiveDate {
+    local_date(Utc::now(), timezone)
+}
+
+/// Date of an instant in `timezone`
+pub fn local_date(instant: DateTime<Utc>, timezone: Tz) -> NaiveDate {
+    instant.with_timezone(&timezone).date_naive()
+}
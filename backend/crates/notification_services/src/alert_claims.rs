@@ -0,0 +1,102 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// How long a sent alert's claim holds its key. After that the same sites opening up
+/// again are news, so the alert may go out again.
+const CLAIM_HOLD_MINUTES: i32 = 60;
+
+/// Claims availability alerts in the notifications ledger before they're sent, so a
+/// retry or a second job polling the same scan can't send an identical alert twice.
+/// Claims are `alert` rows keyed by [`AvailabilityAlert::idempotency_key`]; the unique
+/// index on the key settles concurrent claims.
+///
+/// [`AvailabilityAlert::idempotency_key`]: crate::AvailabilityAlert::idempotency_key
+#[derive(Debug, Clone)]
+pub struct AlertClaims {
+    pool: PgPool,
+}
+
+impl AlertClaims {
+    /// Creates claims kept in the notifications ledger
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Claims the alert with `key` for sending. Returns the claim's ID, or `None` when
+    /// the alert was already sent recently or is being sent by someone else.
+    pub async fn claim(
+        &self,
+        user_id: &Uuid,
+        scan_id: &Uuid,
+        recipient: &str,
+        key: &str,
+        summary: &str,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        // Claims older than the hold give up their key, so the alert can go out again
+        sqlx::query(
+            r#"
+            UPDATE notifications
+            SET idempotency_key = NULL
+            WHERE idempotency_key = $1
+              AND created_at < NOW() - make_interval(mins => $2)
+            "#,
+        )
+        .bind(key)
+        .bind(CLAIM_HOLD_MINUTES)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO notifications
+                (user_id, user_scan_id, type, recipient, message, status, idempotency_key)
+            VALUES ($1, $2, 'alert', LEFT($3, 255), $4, 'pending', $5)
+            ON CONFLICT (idempotency_key) WHERE idempotency_key IS NOT NULL DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(scan_id)
+        .bind(recipient)
+        .bind(summary)
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Marks a claimed alert as sent on `channel`. Its key stays taken for the hold.
+    pub async fn sent(&self, claim_id: &Uuid, channel: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE notifications
+            SET status = 'sent', sent_at = NOW(),
+                availability_details = jsonb_build_object('channel', $2::TEXT)
+            WHERE id = $1
+            "#,
+        )
+        .bind(claim_id)
+        .bind(channel)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Gives up a claim whose alert wasn't sent, so a retry can claim it again. Claims
+    /// of alerts that failed are kept as failed; ones with no channel to send on are
+    /// dropped.
+    pub async fn release(&self, claim_id: &Uuid, failed: bool) -> Result<(), sqlx::Error> {
+        let query = if failed {
+            "UPDATE notifications SET status = 'failed', idempotency_key = NULL WHERE id = $1"
+        } else {
+            "DELETE FROM notifications WHERE id = $1"
+        };
+
+        sqlx::query(query)
+            .bind(claim_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,385 @@
+use chrono::{Datelike, NaiveDate, Utc};
+use i18n::{FluentArgs, Locale, message, message_with};
+use ring::digest::{SHA256, digest};
+use serde::Serialize;
+use tera::Context;
+use uuid::Uuid;
+
+use crate::calendar::CalendarEvent;
+
+/// Most sites named in one availability alert; the rest are summed up as "and N more"
+pub const MAX_ALERT_SITES: usize = 10;
+
+/// Longest stay drawn as a calendar grid in alert emails; longer stays get a plain list
+/// of sites, since a wider grid doesn't fit a phone screen
+pub const MAX_CALENDAR_NIGHTS: usize = 14;
+
+/// Open site named in an availability alert
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AlertSite {
+    /// Loop the site belongs to, if the campground has loops
+    pub loop_name: Option<String>,
+    /// Site name or number
+    pub site: String,
+    /// Nights of the scan's stay the site is open
+    pub open_nights: Vec<NaiveDate>,
+    /// Highest nightly price of the stay in cents, when the provider reported one
+    pub nightly_price_cents: Option<u32>,
+    /// Booking page of this site with the dates filled in, for scans with quick-book
+    /// links
+    pub booking_url: Option<String>,
+}
+
+impl AlertSite {
+    /// Site as shown in alerts, e.g. `A12 (Loop A)`
+    pub fn label(&self) -> String {
+        match &self.loop_name {
+            Some(loop_name) if !loop_name.is_empty() => format!("{} ({})", self.site, loop_name),
+            _ => self.site.clone(),
+        }
+    }
+
+    /// Nightly price as shown in alerts, e.g. `$35` or `$42.50`
+    pub fn price(&self) -> Option<String> {
+        self.nightly_price_cents.map(|cents| match cents % 100 {
+            0 => format!("${}", cents / 100),
+            rest => format!("${}.{:02}", cents / 100, rest),
+        })
+    }
+
+    /// Site with its price, e.g. `A12 (Loop A) – $35/night`
+    pub fn listing(&self, locale: Locale) -> String {
+        match self.price() {
+            Some(price) => {
+                let mut args = FluentArgs::new();
+                args.set("site", self.label());
+                args.set("price", price);
+                message_with(locale, "site-price", &args)
+            }
+            None => self.label(),
+        }
+    }
+}
+
+/// Every site one poll found open for a scan, sent to its owner as a single alert
+#[derive(Debug, Clone)]
+pub struct AvailabilityAlert {
+    /// Name of the campground
+    pub campground_name: String,
+    /// First night of the scan
+    pub check_in_date: NaiveDate,
+    /// Morning the scan's stay ends
+    pub check_out_date: NaiveDate,
+    /// Name of the reservation system the sites are booked on
+    pub provider_name: String,
+    /// Page where the sites can be booked
+    pub booking_url: String,
+    sites: Vec<AlertSite>,
+    /// Message ID of why the sites likely opened up (a cancellation or a new release)
+    reason: Option<&'static str>,
+    /// Where the campground is relative to the nearest town, e.g. `12 mi NE of Bishop, CA`
+    location: Option<String>,
+}
+
+impl AvailabilityAlert {
+    /// Groups the open sites of a poll into one alert, sorted and without duplicates
+    pub fn new(
+        campground_name: &str,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+        provider_name: &str,
+        booking_url: &str,
+        sites: impl IntoIterator<Item = AlertSite>,
+    ) -> Self {
+        let mut sites: Vec<AlertSite> = sites.into_iter().collect();
+        sites.sort();
+        sites.dedup();
+
+        Self {
+            campground_name: campground_name.to_string(),
+            check_in_date,
+            check_out_date,
+            provider_name: provider_name.to_string(),
+            booking_url: booking_url.to_string(),
+            sites,
+            reason: None,
+            location: None,
+        }
+    }
+
+    /// Tags the alert with why the sites likely opened up, given as the ID of the
+    /// message explaining it, e.g. `alert-reason-cancellation`
+    pub fn with_reason(mut self, message_id: &'static str) -> Self {
+        self.reason = Some(message_id);
+        self
+    }
+
+    /// Adds where the campground is, e.g. `12 mi NE of Bishop, CA`
+    pub fn with_location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+
+    /// Number of open sites
+    pub fn site_count(&self) -> usize {
+        self.sites.len()
+    }
+
+    /// Sites named in the alert, at most [`MAX_ALERT_SITES`]
+    pub fn listed_sites(&self) -> &[AlertSite] {
+        &self.sites[..self.sites.len().min(MAX_ALERT_SITES)]
+    }
+
+    /// Open sites left out of the list
+    pub fn more_sites(&self) -> usize {
+        self.sites.len().saturating_sub(MAX_ALERT_SITES)
+    }
+
+    /// Sites as one line, e.g. `A1, A2, B7 and 9 more`
+    pub fn site_summary(&self) -> String {
+        let listed = self
+            .listed_sites()
+            .iter()
+            .map(AlertSite::label)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match self.more_sites() {
+            0 => listed,
+            more => format!("{} and {} more", listed, more),
+        }
+    }
+
+    /// Stay as shown in alerts, e.g. `Aug 14 – Aug 16`, or `tonight, Aug 14 – Aug 16`
+    /// when it starts on the reader's `today`
+    pub fn stay(&self, locale: Locale, today: NaiveDate) -> String {
+        locale.stay(self.check_in_date, self.check_out_date, today)
+    }
+
+    /// Nights of the stay, from check-in up to the night before check-out
+    pub fn nights(&self) -> impl Iterator<Item = NaiveDate> {
+        self.check_in_date
+            .iter_days()
+            .take_while(|date| *date < self.check_out_date)
+    }
+
+    /// The stay as an all-day calendar event, attached to alert emails so it can be added
+    /// to the user's calendar while they book
+    pub fn calendar_event(&self, scan_id: &Uuid) -> CalendarEvent {
+        CalendarEvent {
+            uid: CalendarEvent::scan_uid(scan_id),
+            start: self.check_in_date,
+            end: self.check_out_date,
+            summary: format!("Camping at {}", self.campground_name),
+            description: Some(format!(
+                "Open on {}: {}\n{}",
+                self.provider_name,
+                self.site_summary(),
+                self.booking_url
+            )),
+            url: Some(self.booking_url.clone()),
+            stamp: Utc::now(),
+        }
+    }
+
+    /// Key identifying this alert to `recipient` about `scan_id`: a hex SHA-256 of the
+    /// stay and each site with the nights it's open. Prices and links don't count, so
+    /// the same sites found open again make the same key.
+    pub fn idempotency_key(&self, scan_id: &Uuid, recipient: &str) -> String {
+        let mut content = format!(
+            "{}\n{}\n{}\n{}",
+            scan_id, recipient, self.check_in_date, self.check_out_date
+        );
+        for site in &self.sites {
+            let nights: Vec<String> = site.open_nights.iter().map(NaiveDate::to_string).collect();
+            content.push_str(&format!(
+                "\n{}|{}|{}",
+                site.loop_name.as_deref().unwrap_or_default(),
+                site.site,
+                nights.join(",")
+            ));
+        }
+
+        digest(&SHA256, content.as_bytes())
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Header and rows of the email's calendar grid: one column per night of the stay
+    /// and one row per listed site, marking the nights it's open. Empty when the stay
+    /// is longer than [`MAX_CALENDAR_NIGHTS`].
+    fn calendar(&self, locale: Locale) -> (Vec<CalendarNight>, Vec<CalendarRow>) {
+        let nights: Vec<NaiveDate> = self.nights().take(MAX_CALENDAR_NIGHTS + 1).collect();
+        if nights.len() > MAX_CALENDAR_NIGHTS {
+            return (Vec::new(), Vec::new());
+        }
+
+        let rows = self
+            .listed_sites()
+            .iter()
+            .map(|site| CalendarRow {
+                label: site.listing(locale),
+                open: nights
+                    .iter()
+                    .map(|night| site.open_nights.contains(night))
+                    .collect(),
+            })
+            .collect();
+        let nights = nights
+            .into_iter()
+            .map(|night| CalendarNight {
+                weekday: locale.weekday(night).to_string(),
+                day: night.day().to_string(),
+                month: locale.month(night).to_string(),
+            })
+            .collect();
+
+        (nights, rows)
+    }
+
+    /// Template variables of the alert's email and SMS, with each site's nightly price
+    /// when known and its quick-book link when it has one. The SMS names only the first
+    /// few sites, since it has to fit a couple of segments. `today` is the reader's date,
+    /// in their time zone.
+    pub(crate) fn context(&self, locale: Locale, today: NaiveDate) -> Context {
+        const SMS_SITES: usize = 3;
+
+        let mut context = Context::new();
+        context.insert("campground_name", &self.campground_name);
+        context.insert("location", &self.location);
+        context.insert("stay", &self.stay(locale, today));
+        context.insert("site_count", &self.site_count());
+        context.insert(
+            "sites",
+            &self
+                .listed_sites()
+                .iter()
+                .map(|site| site.listing(locale))
+                .collect::<Vec<_>>(),
+        );
+        context.insert("more_sites", &self.more_sites());
+        let (nights, calendar) = self.calendar(locale);
+        context.insert("nights", &nights);
+        context.insert("calendar", &calendar);
+        context.insert(
+            "sms_sites",
+            &self
+                .sites
+                .iter()
+                .take(SMS_SITES)
+                .map(|site| match site.price() {
+                    Some(price) => format!("{} ({})", site.site, price),
+                    None => site.site.clone(),
+                })
+                .collect::<Vec<_>>(),
+        );
+        context.insert(
+            "sms_more_sites",
+            &self.sites.len().saturating_sub(SMS_SITES),
+        );
+        context.insert("provider_name", &self.provider_name);
+        context.insert("booking_url", &self.booking_url);
+        context.insert(
+            "site_links",
+            &self
+                .listed_sites()
+                .iter()
+                .filter_map(|site| {
+                    site.booking_url.as_ref().map(|url| SiteLink {
+                        site: site.site.clone(),
+                        url: url.clone(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        );
+        context.insert("reason", &self.reason.map(|id| message(locale, id)));
+        context
+    }
+}
+
+/// Quick-book link to one open site, the first of which leads the alert
+#[derive(Serialize)]
+struct SiteLink {
+    site: String,
+    url: String,
+}
+
+/// Column of an alert's calendar grid
+#[derive(Serialize)]
+struct CalendarNight {
+    weekday: String,
+    day: String,
+    month: String,
+}
+
+/// Site row of an alert's calendar grid, with whether it's open each night
+#[derive(Serialize)]
+struct CalendarRow {
+    label: String,
+    open: Vec<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 7, day).unwrap()
+    }
+
+    fn site(site: &str, nights: &[u32], price: Option<u32>) -> AlertSite {
+        AlertSite {
+            loop_name: Some("Loop A".to_string()),
+            site: site.to_string(),
+            open_nights: nights.iter().map(|day| date(*day)).collect(),
+            nightly_price_cents: price,
+            booking_url: None,
+        }
+    }
+
+    fn alert(sites: Vec<AlertSite>) -> AvailabilityAlert {
+        AvailabilityAlert::new(
+            "Upper Pines",
+            date(1),
+            date(3),
+            "Recreation.gov",
+            "https://www.recreation.gov/camping/campgrounds/232447",
+            sites,
+        )
+    }
+
+    #[test]
+    fn keys_alerts_by_scan_recipient_sites_and_nights() {
+        let scan_id = Uuid::new_v4();
+        let key = alert(vec![
+            site("A1", &[1, 2], Some(3500)),
+            site("B2", &[1, 2], None),
+        ])
+        .idempotency_key(&scan_id, "camper@example.com");
+
+        assert_eq!(key.len(), 64);
+        // Site order and prices don't matter
+        assert_eq!(
+            alert(vec![
+                site("B2", &[1, 2], Some(4000)),
+                site("A1", &[1, 2], None)
+            ])
+            .idempotency_key(&scan_id, "camper@example.com"),
+            key
+        );
+        // Other sites, nights, recipients or scans do
+        for other in [
+            alert(vec![site("A1", &[1, 2], None)]).idempotency_key(&scan_id, "camper@example.com"),
+            alert(vec![site("A1", &[1], None), site("B2", &[1, 2], None)])
+                .idempotency_key(&scan_id, "camper@example.com"),
+            alert(vec![site("A1", &[1, 2], None), site("B2", &[1, 2], None)])
+                .idempotency_key(&scan_id, "friend@example.com"),
+            alert(vec![site("A1", &[1, 2], None), site("B2", &[1, 2], None)])
+                .idempotency_key(&Uuid::new_v4(), "camper@example.com"),
+        ] {
+            assert_ne!(other, key);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::signing::LinkSigner;
+
+/// Longest line of an iCalendar file in octets, before it's folded (RFC 5545 §3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// All-day event in an iCalendar file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarEvent {
+    /// Identifier that stays the same when the event changes, so calendars update the
+    /// event instead of adding another one
+    pub uid: String,
+    /// First day of the event
+    pub start: NaiveDate,
+    /// Day after the event's last day
+    pub end: NaiveDate,
+    /// Event title
+    pub summary: String,
+    /// Longer description, shown in the event's details
+    pub description: Option<String>,
+    /// Link shown with the event
+    pub url: Option<String>,
+    /// When the event last changed
+    pub stamp: DateTime<Utc>,
+}
+
+impl CalendarEvent {
+    /// UID of the event for a scan's stay, shared by the feed and alert attachments so
+    /// importing the stay again updates it
+    pub fn scan_uid(scan_id: &Uuid) -> String {
+        format!("scan-{}@campsite-tracker", scan_id)
+    }
+}
+
+/// Writes events as an iCalendar (RFC 5545) file, with `method` (e.g. `PUBLISH`) set
+/// for files sent as email attachments
+pub fn ics_calendar(name: &str, method: Option<&str>, events: &[CalendarEvent]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Campsite Tracker//Scans//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    if let Some(method) = method {
+        lines.push(format!("METHOD:{}", method));
+    }
+    lines.push(format!("X-WR-CALNAME:{}", escape_text(name)));
+
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid));
+        lines.push(format!("DTSTAMP:{}", event.stamp.format("%Y%m%dT%H%M%SZ")));
+        lines.push(format!(
+            "DTSTART;VALUE=DATE:{}",
+            event.start.format("%Y%m%d")
+        ));
+        lines.push(format!("DTEND;VALUE=DATE:{}", event.end.format("%Y%m%d")));
+        lines.push(format!("SUMMARY:{}", escape_text(&event.summary)));
+        if let Some(description) = &event.description {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        if let Some(url) = &event.url {
+            lines.push(format!("URL:{}", url));
+        }
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+/// Escapes a TEXT value: backslashes, semicolons, commas and newlines
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Ends a content line with CRLF, folding it onto continuation lines (starting with a
+/// space) so none is longer than 75 octets, without splitting a UTF-8 character
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Builds and verifies the signed links of users' scan calendar feeds. A feed URL is all
+/// a calendar app needs to subscribe, so the token stands in for a login. Tokens carry
+/// the version of the user's link they were issued for, so a user resetting their link
+/// revokes the older ones; rotating `LINK_SIGNING_KEY` revokes every feed.
+#[derive(Debug, Clone)]
+pub struct CalendarFeed {
+    signer: LinkSigner,
+    base_url: String,
+}
+
+impl CalendarFeed {
+    /// Creates feed links signed with a key derived from the configured key material
+    pub fn new(key_material: &str, base_url: &str) -> Self {
+        Self {
+            signer: LinkSigner::new(key_material, b"calendar-feeds"),
+            base_url: base_url.to_string(),
+        }
+    }
+
+    /// Address calendar apps subscribe to for the user's scans, at the given version of
+    /// the user's link
+    pub fn url(&self, user_id: &Uuid, version: i32) -> String {
+        format!(
+            "{}/api/user/scans.ics?token={}",
+            self.base_url,
+            self.token(user_id, version)
+        )
+    }
+
+    /// Token of a feed link: `<user id>.<version>.<signature>`
+    pub fn token(&self, user_id: &Uuid, version: i32) -> String {
+        self.signer.sign(&format!("{}.{}", user_id, version))
+    }
+
+    /// Checks a token's signature and returns whose feed it opens and the version of
+    /// their link it was issued for, or `None` when the token was altered or wasn't signed
+    /// with this key. Tokens from before links had versions are version 0.
+    pub fn verify(&self, token: &str) -> Option<(Uuid, i32)> {
+        let payload = self.signer.verify(token)?;
+        let (user_id, version) = match payload.split_once('.') {
+            Some((user_id, version)) => (user_id, version.parse().ok()?),
+            None => (payload, 0),
+        };
+
+        Some((Uuid::parse_str(user_id).ok()?, version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn writes_all_day_events() {
+        let event = CalendarEvent {
+            uid: "scan-1@campsite-tracker".to_string(),
+            start: NaiveDate::from_ymd_opt(2025, 7, 4).unwrap(),
+            end: NaiveDate::from_ymd_opt(2025, 7, 6).unwrap(),
+            summary: "Upper Pines; Yosemite, CA".to_string(),
+            description: Some(format!("Sites\n{}", "A".repeat(80))),
+            url: None,
+            stamp: Utc.with_ymd_and_hms(2025, 6, 1, 12, 30, 0).unwrap(),
+        };
+
+        let ics = ics_calendar("Scans", Some("PUBLISH"), &[event]);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("METHOD:PUBLISH\r\n"));
+        assert!(ics.contains("DTSTAMP:20250601T123000Z\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250704\r\nDTEND;VALUE=DATE:20250706\r\n"));
+        assert!(ics.contains("SUMMARY:Upper Pines\\; Yosemite\\, CA\r\n"));
+        assert!(ics.contains("DESCRIPTION:Sites\\nAAAA"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(!ics.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn folds_without_splitting_characters() {
+        let folded = fold_line(&format!("SUMMARY:{}", "é".repeat(60)));
+
+        for line in folded.trim_end().split("\r\n") {
+            assert!(line.len() <= MAX_LINE_OCTETS);
+        }
+        assert_eq!(
+            folded.replace("\r\n ", "").trim_end(),
+            format!("SUMMARY:{}", "é".repeat(60))
+        );
+    }
+
+    #[test]
+    fn feed_tokens_are_tied_to_the_user() {
+        let feed = CalendarFeed::new("a".repeat(32).as_str(), "http://localhost:8080");
+        let user_id = Uuid::new_v4();
+        let token = feed.token(&user_id, 3);
+
+        assert_eq!(feed.verify(&token), Some((user_id, 3)));
+        assert_eq!(
+            feed.verify(&token.replace(&user_id.to_string(), &Uuid::new_v4().to_string())),
+            None
+        );
+        assert_eq!(
+            feed.verify(&token.replacen(&format!("{}.3", user_id), &format!("{}.4", user_id), 1)),
+            None
+        );
+        assert!(
+            feed.url(&user_id, 3)
+                .starts_with("http://localhost:8080/api/user/scans.ics?token=")
+        );
+
+        // Links issued before feed versions open version 0
+        let unversioned =
+            LinkSigner::new("a".repeat(32).as_str(), b"calendar-feeds").sign(&user_id.to_string());
+        assert_eq!(feed.verify(&unversioned), Some((user_id, 0)));
+
+        let unsubscribe = crate::UnsubscribeLinks::new("a".repeat(32).as_str(), "");
+        let other_purpose = unsubscribe.token(&user_id, crate::UnsubscribeTarget::Email);
+        assert_eq!(feed.verify(&other_purpose), None);
+    }
+}
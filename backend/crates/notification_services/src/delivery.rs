@@ -0,0 +1,463 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::types::{ChannelDeliverability, NotificationError};
+
+/// Envelope SNS posts to HTTP subscribers: a subscription confirmation, or a notification
+/// whose `Message` is the JSON published to the topic. SNS sends it as `text/plain`.
+#[derive(Debug, Deserialize)]
+pub struct SnsEnvelope {
+    /// `SubscriptionConfirmation`, `Notification`, or `UnsubscribeConfirmation`
+    #[serde(rename = "Type")]
+    pub kind: String,
+    /// Topic the message was published to
+    #[serde(rename = "TopicArn")]
+    pub topic_arn: String,
+    /// Published message
+    #[serde(rename = "Message")]
+    pub message: String,
+    /// ID SNS gave the message
+    #[serde(rename = "MessageId")]
+    pub message_id: String,
+    /// Subject the message was published with, if any
+    #[serde(rename = "Subject", default)]
+    pub subject: Option<String>,
+    /// When the message was published, as SNS formatted it
+    #[serde(rename = "Timestamp")]
+    pub timestamp: String,
+    /// URL to visit to confirm a new subscription
+    #[serde(rename = "SubscribeURL", default)]
+    pub subscribe_url: Option<String>,
+    /// Token of a subscription confirmation
+    #[serde(rename = "Token", default)]
+    pub token: Option<String>,
+    /// `1` for SHA1withRSA signatures, `2` for SHA256withRSA
+    #[serde(rename = "SignatureVersion")]
+    pub signature_version: String,
+    /// Base64 signature of the message's fields
+    #[serde(rename = "Signature")]
+    pub signature: String,
+    /// URL of the certificate the message was signed with
+    #[serde(rename = "SigningCertURL")]
+    pub signing_cert_url: String,
+}
+
+impl SnsEnvelope {
+    /// Confirms a new subscription by visiting its `SubscribeURL`. Only HTTPS URLs on
+    /// amazonaws.com are followed, so a forged request can't make the server fetch
+    /// arbitrary URLs.
+    pub async fn confirm_subscription(&self) -> Result<(), NotificationError> {
+        let url = self
+            .subscribe_url
+            .as_deref()
+            .and_then(|url| reqwest::Url::parse(url).ok())
+            .filter(|url| {
+                url.scheme() == "https"
+                    && url
+                        .host_str()
+                        .is_some_and(|host| host.ends_with(".amazonaws.com"))
+            })
+            .ok_or_else(|| {
+                NotificationError::SnsError("Invalid SubscribeURL in confirmation".to_string())
+            })?;
+
+        reqwest::get(url)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| {
+                NotificationError::SnsError(format!("Subscription confirmation failed: {}", e))
+            })?;
+
+        log::info!("📬 Confirmed SNS subscription to {}", self.topic_arn);
+        Ok(())
+    }
+}
+
+/// Delivery outcome SES or SNS reported for a sent message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryEvent {
+    /// The recipient's mail server or carrier accepted the message
+    Delivered {
+        /// SES or SNS message ID
+        message_id: String,
+    },
+    /// The email bounced
+    Bounced {
+        /// SES message ID
+        message_id: String,
+        /// Addresses that bounced
+        recipients: Vec<String>,
+        /// Whether the bounce is permanent (the address doesn't exist or rejects mail)
+        /// rather than transient (a full mailbox, a server down)
+        permanent: bool,
+    },
+    /// The recipient marked the email as spam
+    Complained {
+        /// SES message ID
+        message_id: String,
+        /// Addresses that complained
+        recipients: Vec<String>,
+    },
+    /// The carrier couldn't deliver the SMS
+    Failed {
+        /// SNS message ID
+        message_id: String,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SesNotification {
+    #[serde(alias = "eventType")]
+    notification_type: String,
+    mail: SesMail,
+    #[serde(default)]
+    bounce: Option<SesBounce>,
+    #[serde(default)]
+    complaint: Option<SesComplaint>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SesMail {
+    message_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SesBounce {
+    bounce_type: String,
+    #[serde(default)]
+    bounced_recipients: Vec<SesRecipient>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SesComplaint {
+    #[serde(default)]
+    complained_recipients: Vec<SesRecipient>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SesRecipient {
+    email_address: String,
+}
+
+#[derive(Deserialize)]
+struct SmsDeliveryStatus {
+    notification: SmsNotification,
+    status: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SmsNotification {
+    message_id: String,
+}
+
+impl DeliveryEvent {
+    /// Parses an SES feedback notification (`Bounce`, `Complaint`, or `Delivery`).
+    /// Returns `None` for other notification types.
+    pub fn from_ses(message: &str) -> Result<Option<Self>, NotificationError> {
+        let notification: SesNotification = serde_json::from_str(message)
+            .map_err(|e| NotificationError::SesError(format!("Invalid SES notification: {}", e)))?;
+        let message_id = notification.mail.message_id;
+
+        Ok(match notification.notification_type.as_str() {
+            "Delivery" => Some(DeliveryEvent::Delivered { message_id }),
+            "Bounce" => {
+                let bounce = notification.bounce.ok_or_else(|| {
+                    NotificationError::SesError("Bounce notification without bounce".to_string())
+                })?;
+                Some(DeliveryEvent::Bounced {
+                    message_id,
+                    permanent: bounce.bounce_type == "Permanent",
+                    recipients: addresses(bounce.bounced_recipients),
+                })
+            }
+            "Complaint" => Some(DeliveryEvent::Complained {
+                message_id,
+                recipients: notification
+                    .complaint
+                    .map(|complaint| addresses(complaint.complained_recipients))
+                    .unwrap_or_default(),
+            }),
+            _ => None,
+        })
+    }
+
+    /// Parses an SNS SMS delivery status record (`SUCCESS` or `FAILURE`)
+    pub fn from_sms(message: &str) -> Result<Self, NotificationError> {
+        let record: SmsDeliveryStatus = serde_json::from_str(message).map_err(|e| {
+            NotificationError::SnsError(format!("Invalid SMS delivery status: {}", e))
+        })?;
+        let message_id = record.notification.message_id;
+
+        Ok(match record.status.as_str() {
+            "SUCCESS" => DeliveryEvent::Delivered { message_id },
+            _ => DeliveryEvent::Failed { message_id },
+        })
+    }
+
+    /// ID of the message the event is about
+    pub fn message_id(&self) -> &str {
+        match self {
+            DeliveryEvent::Delivered { message_id }
+            | DeliveryEvent::Bounced { message_id, .. }
+            | DeliveryEvent::Complained { message_id, .. }
+            | DeliveryEvent::Failed { message_id } => message_id,
+        }
+    }
+
+    /// Value of `notifications.status` the event leads to
+    fn status(&self) -> &'static str {
+        match self {
+            DeliveryEvent::Delivered { .. } => "delivered",
+            DeliveryEvent::Bounced { .. } => "bounced",
+            DeliveryEvent::Complained { .. } => "complained",
+            DeliveryEvent::Failed { .. } => "failed",
+        }
+    }
+
+    /// Addresses that shouldn't get email anymore: hard bounces and complaints
+    fn addresses_to_disable(&self) -> &[String] {
+        match self {
+            DeliveryEvent::Bounced {
+                recipients,
+                permanent: true,
+                ..
+            }
+            | DeliveryEvent::Complained { recipients, .. } => recipients,
+            _ => &[],
+        }
+    }
+}
+
+fn addresses(recipients: Vec<SesRecipient>) -> Vec<String> {
+    recipients
+        .into_iter()
+        .map(|recipient| recipient.email_address.to_lowercase())
+        .collect()
+}
+
+/// What a delivery event changed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeliveryUpdate {
+    /// Notification records whose status was updated
+    pub notifications: u64,
+    /// Users whose email alerts were turned off
+    pub users_disabled: u64,
+}
+
+/// Records sent emails in the notifications ledger and applies SES/SNS delivery
+/// feedback to it
+#[derive(Debug, Clone)]
+pub struct DeliveryTracker {
+    pool: PgPool,
+}
+
+impl DeliveryTracker {
+    /// Creates a tracker writing to the notifications ledger
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Records a sent email. `external_id` is the SES message ID feedback refers to.
+    pub async fn record_email(
+        &self,
+        user_id: &Uuid,
+        scan_id: Option<&Uuid>,
+        email: &str,
+        subject: &str,
+        message: &str,
+        external_id: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO notifications
+                (user_id, user_scan_id, type, recipient, subject, message, status, sent_at, external_id)
+            VALUES ($1, $2, 'email', $3, LEFT($4, 255), $5, 'sent', NOW(), $6)
+            "#,
+        )
+        .bind(user_id)
+        .bind(scan_id)
+        .bind(email)
+        .bind(subject)
+        .bind(message)
+        .bind(external_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Updates the status of the notification the event is about. Hard bounces and
+    /// complaints also turn off email alerts for the address and mark it undeliverable,
+    /// since sending to it again hurts the SES account's reputation. A delivery report
+    /// never overrides a bounce or complaint that arrived first.
+    pub async fn apply(&self, event: &DeliveryEvent) -> Result<DeliveryUpdate, sqlx::Error> {
+        let notifications = sqlx::query(
+            r#"
+            UPDATE notifications
+            SET status = $2
+            WHERE external_id = $1
+              AND ($2 <> 'delivered' OR status IN ('pending', 'sent'))
+            "#,
+        )
+        .bind(event.message_id())
+        .bind(event.status())
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        let addresses = event.addresses_to_disable();
+        let users_disabled = if addresses.is_empty() {
+            0
+        } else {
+            let reason = match event {
+                DeliveryEvent::Complained { .. } => "This address reported our email as spam",
+                _ => "Email to this address bounced",
+            };
+            let deliverability = serde_json::to_value(ChannelDeliverability::undeliverable(reason))
+                .unwrap_or_default();
+
+            sqlx::query(
+                r#"
+                UPDATE users
+                SET notification_preferences =
+                        COALESCE(notification_preferences, '{}'::jsonb) || '{"email": false}'::jsonb,
+                    channel_deliverability =
+                        COALESCE(channel_deliverability, '{}'::jsonb) || jsonb_build_object('email', $2::jsonb),
+                    deliverability_checked_at = NOW(),
+                    updated_at = NOW()
+                WHERE LOWER(email) = ANY($1)
+                "#,
+            )
+            .bind(addresses)
+            .bind(deliverability)
+            .execute(&self.pool)
+            .await?
+            .rows_affected()
+        };
+
+        Ok(DeliveryUpdate {
+            notifications,
+            users_disabled,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ses_bounces() {
+        let event = DeliveryEvent::from_ses(
+            r#"{
+                "notificationType": "Bounce",
+                "bounce": {
+                    "bounceType": "Permanent",
+                    "bounceSubType": "General",
+                    "bouncedRecipients": [{"emailAddress": "Ana@Example.com"}]
+                },
+                "mail": {"messageId": "0100-abc", "destination": ["Ana@Example.com"]}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            event,
+            Some(DeliveryEvent::Bounced {
+                message_id: "0100-abc".to_string(),
+                recipients: vec!["ana@example.com".to_string()],
+                permanent: true,
+            })
+        );
+        assert_eq!(
+            event.unwrap().addresses_to_disable(),
+            ["ana@example.com".to_string()]
+        );
+    }
+
+    #[test]
+    fn transient_bounces_keep_email_on() {
+        let event = DeliveryEvent::from_ses(
+            r#"{
+                "eventType": "Bounce",
+                "bounce": {
+                    "bounceType": "Transient",
+                    "bouncedRecipients": [{"emailAddress": "ana@example.com"}]
+                },
+                "mail": {"messageId": "0100-abc"}
+            }"#,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(event.status(), "bounced");
+        assert!(event.addresses_to_disable().is_empty());
+    }
+
+    #[test]
+    fn parses_ses_complaints_and_deliveries() {
+        let complaint = DeliveryEvent::from_ses(
+            r#"{
+                "notificationType": "Complaint",
+                "complaint": {"complainedRecipients": [{"emailAddress": "ana@example.com"}]},
+                "mail": {"messageId": "0100-abc"}
+            }"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(complaint.status(), "complained");
+        assert_eq!(complaint.addresses_to_disable().len(), 1);
+
+        let delivery = DeliveryEvent::from_ses(
+            r#"{"notificationType": "Delivery", "mail": {"messageId": "0100-abc"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            delivery,
+            Some(DeliveryEvent::Delivered {
+                message_id: "0100-abc".to_string()
+            })
+        );
+
+        assert_eq!(
+            DeliveryEvent::from_ses(r#"{"eventType": "Open", "mail": {"messageId": "1"}}"#)
+                .unwrap(),
+            None
+        );
+        assert!(DeliveryEvent::from_ses("not json").is_err());
+    }
+
+    #[test]
+    fn parses_sms_delivery_status() {
+        let record = |status: &str| {
+            format!(
+                r#"{{
+                    "notification": {{"messageId": "sns-1", "timestamp": "2025-08-14 10:00:00.000"}},
+                    "delivery": {{"destination": "+15555550100", "providerResponse": "Delivered"}},
+                    "status": "{}"
+                }}"#,
+                status
+            )
+        };
+
+        assert_eq!(
+            DeliveryEvent::from_sms(&record("SUCCESS")).unwrap(),
+            DeliveryEvent::Delivered {
+                message_id: "sns-1".to_string()
+            }
+        );
+        assert_eq!(
+            DeliveryEvent::from_sms(&record("FAILURE")).unwrap(),
+            DeliveryEvent::Failed {
+                message_id: "sns-1".to_string()
+            }
+        );
+    }
+}
@@ -3,10 +3,56 @@
 //! This crate provides authentication services for the application.
 //! //! It includes JWT token handling, middleware for request authentication, and service definitions.
 
+/// Claims of availability alerts that keep retries from sending them twice.
+pub mod alert_claims;
+/// Availability alerts grouping every site a poll found open for a scan.
+pub mod availability_alert;
+/// iCalendar files of scan dates and the signed feed links users subscribe to.
+pub mod calendar;
+/// Delivery feedback from SES and SNS applied to the notifications ledger.
+pub mod delivery;
+/// Admin listing of the notifications ledger.
+pub mod notification_log;
+/// Alerts to the people running the service, by email and webhook.
+pub mod ops_alerts;
+/// Redis-backed storage for pending verification tokens.
+#[cfg(feature = "redis")]
+pub mod redis_store;
 /// Service definitions for user management and authentication operations.
 pub mod service;
+/// Signed tokens of links sent to users.
+mod signing;
+/// Monthly SMS budgets tracked against the notifications ledger.
+pub mod sms_budget;
+/// Signature checks of the messages SNS posts to the delivery webhooks.
+pub mod sns_signature;
+/// Email and SMS templates, with per-deployment overrides.
+pub mod templates;
 /// Types and structures used in authentication services.
 pub mod types;
+/// Signed one-click unsubscribe links in notification emails.
+pub mod unsubscribe;
+/// Database-backed storage for pending email and SMS verification tokens.
+pub mod verification;
+/// Reminders for users who haven't finished verifying their email or phone.
+pub mod verification_reminders;
+/// Limits on how often verification messages and scan invitations can be resent.
+pub mod verification_throttle;
 
-pub use service::{NotificationService, create_verification_store};
-pub use types::{NotificationError, VerificationStore};
+pub use alert_claims::AlertClaims;
+pub use availability_alert::{AlertSite, AvailabilityAlert, MAX_ALERT_SITES, MAX_CALENDAR_NIGHTS};
+pub use calendar::{CalendarEvent, CalendarFeed, ics_calendar};
+pub use delivery::{DeliveryEvent, DeliveryTracker, DeliveryUpdate, SnsEnvelope};
+pub use notification_log::{NotificationListQuery, NotificationLog, NotificationRecord};
+pub use ops_alerts::OpsAlerts;
+pub use service::NotificationService;
+pub use sms_budget::{SmsAllowance, SmsBudget, SmsBudgetUsage};
+pub use sns_signature::SnsSignatureVerifier;
+pub use templates::{NotificationTemplates, RenderedEmail, escape_html};
+pub use types::{
+    ChannelDeliverability, DeliverabilityStatus, NotificationError, VerificationChannel,
+};
+pub use unsubscribe::{Unsubscribe, UnsubscribeLinks, UnsubscribeTarget};
+pub use verification::VerificationStore;
+pub use verification_reminders::{VerificationReminder, VerificationReminders};
+pub use verification_throttle::VerificationThrottle;
@@ -0,0 +1,154 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use postgres::pagination::{Page, Pagination};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// Statuses a notification moves through
+pub const NOTIFICATION_STATUSES: &[&str] = &[
+    "pending",
+    "sent",
+    "failed",
+    "delivered",
+    "bounced",
+    "complained",
+];
+
+/// Filter shared by the notification list and its count; binds email search, status,
+/// channel, and date range as $1 to $5
+const NOTIFICATION_LIST_FILTER: &str = r#"
+    ($1::TEXT IS NULL
+        OR strpos(lower(n.recipient), lower($1)) > 0
+        OR strpos(lower(u.email), lower($1)) > 0)
+    AND ($2::TEXT IS NULL OR n.status = $2)
+    AND ($3::TEXT IS NULL OR n.type = $3)
+    AND ($4::DATE IS NULL OR n.created_at >= $4::DATE::TIMESTAMP AT TIME ZONE 'UTC')
+    AND ($5::DATE IS NULL OR n.created_at < ($5::DATE + 1)::TIMESTAMP AT TIME ZONE 'UTC')
+"#;
+
+/// Query parameters of the admin notification list
+#[derive(Debug, Default, Deserialize)]
+pub struct NotificationListQuery {
+    /// Page number, starting at 1
+    pub page: Option<i64>,
+    /// Notifications per page (default 50, at most 200)
+    pub per_page: Option<i64>,
+    /// Only notifications whose recipient or user's email contains this text, ignoring case
+    pub email: Option<String>,
+    /// Only notifications in this status (see [`NOTIFICATION_STATUSES`])
+    pub status: Option<String>,
+    /// Only notifications sent over this channel (`email`, `sms`)
+    #[serde(rename = "type")]
+    pub channel: Option<String>,
+    /// Only notifications created on or after this day (UTC)
+    pub from: Option<NaiveDate>,
+    /// Only notifications created on or before this day (UTC)
+    pub to: Option<NaiveDate>,
+}
+
+/// An entry of the notifications ledger
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationRecord {
+    /// Notification ID
+    pub id: Uuid,
+    /// User notified
+    pub user_id: Uuid,
+    /// That user's email address
+    pub user_email: String,
+    /// Scan the notification was about, if any
+    pub user_scan_id: Option<Uuid>,
+    /// Channel (`email`, `sms`)
+    #[serde(rename = "type")]
+    pub channel: String,
+    /// Email address or phone number it went to
+    pub recipient: String,
+    /// Email subject
+    pub subject: Option<String>,
+    /// Message body
+    pub message: String,
+    /// Delivery status
+    pub status: String,
+    /// When it was sent
+    pub sent_at: Option<DateTime<Utc>>,
+    /// When it was queued
+    pub created_at: DateTime<Utc>,
+}
+
+/// Reads the notifications ledger for admins
+pub struct NotificationLog {
+    pool: PgPool,
+}
+
+impl NotificationLog {
+    /// Creates a log over the database pool
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Lists notifications, newest first, filtered by email, status, channel, and date.
+    pub async fn list(
+        &self,
+        query: &NotificationListQuery,
+    ) -> Result<Page<NotificationRecord>, sqlx::Error> {
+        let pagination = Pagination::new(query.page, query.per_page);
+        let email = query
+            .email
+            .as_deref()
+            .map(str::trim)
+            .filter(|e| !e.is_empty());
+
+        let total: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM notifications n JOIN users u ON u.id = n.user_id WHERE {}",
+            NOTIFICATION_LIST_FILTER
+        ))
+        .bind(email)
+        .bind(query.status.as_deref())
+        .bind(query.channel.as_deref())
+        .bind(query.from)
+        .bind(query.to)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT n.id, n.user_id, u.email AS user_email, n.user_scan_id, n.type,
+                   n.recipient, n.subject, n.message, COALESCE(n.status, 'pending') AS status,
+                   n.sent_at, n.created_at
+            FROM notifications n
+            JOIN users u ON u.id = n.user_id
+            WHERE {}
+            ORDER BY n.created_at DESC, n.id
+            LIMIT $6 OFFSET $7
+            "#,
+            NOTIFICATION_LIST_FILTER
+        ))
+        .bind(email)
+        .bind(query.status.as_deref())
+        .bind(query.channel.as_deref())
+        .bind(query.from)
+        .bind(query.to)
+        .bind(pagination.per_page)
+        .bind(pagination.offset())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let notifications = rows
+            .into_iter()
+            .map(|row| NotificationRecord {
+                id: row.get("id"),
+                user_id: row.get("user_id"),
+                user_email: row.get("user_email"),
+                user_scan_id: row.get("user_scan_id"),
+                channel: row.get("type"),
+                recipient: row.get("recipient"),
+                subject: row.get("subject"),
+                message: row.get("message"),
+                status: row.get("status"),
+                sent_at: row.get("sent_at"),
+                created_at: row.get("created_at"),
+            })
+            .collect();
+
+        Ok(Page::new(notifications, pagination, total))
+    }
+}
@@ -0,0 +1,93 @@
+use app_config::AppConfig;
+use std::time::Duration;
+
+use crate::service::NotificationService;
+use crate::types::NotificationError;
+
+/// How long a webhook gets to accept an alert
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Alerts to whoever runs the service (not its users) about the service itself, sent to
+/// `OPS_ALERT_EMAIL` and `OPS_ALERT_WEBHOOK_URL`. With neither set, alerts are only
+/// logged.
+#[derive(Debug, Clone)]
+pub struct OpsAlerts {
+    notification_service: NotificationService,
+    email: Option<String>,
+    webhook_url: Option<String>,
+    http: reqwest::Client,
+    sandbox: bool,
+}
+
+impl OpsAlerts {
+    /// Creates alerts sent to the configured ops email and webhook
+    pub fn new(config: &AppConfig, notification_service: NotificationService) -> Self {
+        Self {
+            notification_service,
+            email: config.ops_alert_email.clone(),
+            webhook_url: config.ops_alert_webhook_url.clone(),
+            http: reqwest::Client::builder()
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            sandbox: config.sandbox_mode,
+        }
+    }
+
+    /// Sends an alert on every configured channel. Each channel is tried even when
+    /// another fails; the last failure is returned.
+    pub async fn send(&self, subject: &str, body: &str) -> Result<(), NotificationError> {
+        log::warn!("📟 Ops alert: {}\n{}", subject, body);
+
+        let mut result = Ok(());
+
+        if let Some(email) = &self.email
+            && let Err(e) = self
+                .notification_service
+                .send_ops_email(email, subject, body)
+                .await
+        {
+            log::error!("❌ Failed to email ops alert: {}", e);
+            result = Err(e);
+        }
+
+        if let Some(url) = &self.webhook_url
+            && let Err(e) = self.post_webhook(url, subject, body).await
+        {
+            log::error!("❌ Failed to post ops alert to webhook: {}", e);
+            result = Err(e);
+        }
+
+        result
+    }
+
+    /// Posts `{"text": "<subject>\n<body>"}`, the payload Slack and Mattermost incoming
+    /// webhooks take
+    async fn post_webhook(
+        &self,
+        url: &str,
+        subject: &str,
+        body: &str,
+    ) -> Result<(), NotificationError> {
+        if self.sandbox {
+            log::info!("🧪 [sandbox] Ops alert webhook to {} not called", url);
+            return Ok(());
+        }
+
+        let response = self
+            .http
+            .post(url)
+            .json(&serde_json::json!({ "text": format!("{}\n{}", subject, body) }))
+            .send()
+            .await
+            .map_err(|e| NotificationError::Webhook(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(NotificationError::Webhook(format!(
+                "webhook answered {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
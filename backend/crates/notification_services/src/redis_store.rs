@@ -0,0 +1,136 @@
+use redis::{AsyncCommands, Script, aio::ConnectionManager};
+use uuid::Uuid;
+
+use crate::types::{NotificationError, VerificationChannel};
+use crate::verification::MAX_VERIFICATION_ATTEMPTS;
+
+/// Counts an attempt against a pending verification and returns its target, token, and
+/// attempt count, or nil if nothing is pending. Done in a script so an expiring key is
+/// never recreated without a TTL.
+const COUNT_ATTEMPT_SCRIPT: &str = r#"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return nil
+end
+redis.call('HINCRBY', KEYS[1], 'attempts', 1)
+return redis.call('HMGET', KEYS[1], 'target', 'token', 'attempts')
+"#;
+
+/// Redis-backed storage for pending verification tokens. Expiry is handled by key TTLs.
+#[derive(Clone)]
+pub struct RedisVerificationStore {
+    conn: ConnectionManager,
+}
+
+impl RedisVerificationStore {
+    /// Connects to Redis at the given URL.
+    pub async fn connect(url: &str) -> Result<Self, NotificationError> {
+        let client = redis::Client::open(url)?;
+        let conn = ConnectionManager::new(client).await?;
+
+        Ok(Self { conn })
+    }
+
+    /// Stores a verification token for the user, replacing any pending token on the same channel.
+    pub async fn store_token(
+        &self,
+        user_id: &Uuid,
+        channel: VerificationChannel,
+        target: &str,
+        token: &str,
+        expires_in_minutes: i64,
+    ) -> Result<(), NotificationError> {
+        let mut conn = self.conn.clone();
+        let key = pending_key(user_id, channel);
+        let ttl_seconds = expires_in_minutes * 60;
+
+        // Email links are looked up by token, so drop the lookup key of a superseded token
+        let previous_token: Option<String> = conn.hget(&key, "token").await?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        if let Some(previous_token) = previous_token
+            && channel == VerificationChannel::Email
+        {
+            pipe.del(email_token_key(&previous_token));
+        }
+        pipe.del(&key)
+            .hset_multiple(
+                &key,
+                &[("target", target), ("token", token), ("attempts", "0")],
+            )
+            .expire(&key, ttl_seconds);
+        if channel == VerificationChannel::Email {
+            pipe.set_ex(
+                email_token_key(token),
+                user_id.to_string(),
+                ttl_seconds as u64,
+            );
+        }
+        pipe.query_async::<()>(&mut conn).await?;
+
+        Ok(())
+    }
+
+    /// Verifies the provided code against the pending token for the user and target.
+    pub async fn verify_code(
+        &self,
+        user_id: &Uuid,
+        channel: VerificationChannel,
+        target: &str,
+        provided_code: &str,
+    ) -> Result<bool, NotificationError> {
+        let mut conn = self.conn.clone();
+        let key = pending_key(user_id, channel);
+
+        let pending: Option<(String, String, i32)> = Script::new(COUNT_ATTEMPT_SCRIPT)
+            .key(&key)
+            .invoke_async(&mut conn)
+            .await?;
+
+        // A code issued for a different email/phone doesn't verify the current one
+        let (token, attempts) = match pending {
+            Some((pending_target, token, attempts)) if pending_target == target => {
+                (token, attempts)
+            }
+            _ => return Err(NotificationError::VerificationNotFound),
+        };
+
+        if attempts > MAX_VERIFICATION_ATTEMPTS {
+            conn.del::<_, ()>(&key).await?;
+            return Err(NotificationError::TooManyAttempts);
+        }
+
+        if token == provided_code {
+            conn.del::<_, ()>(&[key, email_token_key(&token)]).await?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Consumes an email verification token, returning the owning user ID if it is still valid.
+    pub async fn consume_email_token(
+        &self,
+        token: &str,
+    ) -> Result<Option<Uuid>, NotificationError> {
+        let mut conn = self.conn.clone();
+
+        let user_id: Option<String> = conn.get_del(email_token_key(token)).await?;
+        let user_id = user_id.and_then(|id| Uuid::parse_str(&id).ok());
+
+        if let Some(user_id) = user_id {
+            conn.del::<_, ()>(pending_key(&user_id, VerificationChannel::Email))
+                .await?;
+        }
+
+        Ok(user_id)
+    }
+}
+
+fn pending_key(user_id: &Uuid, channel: VerificationChannel) -> String {
+    format!("verification:{}:{}", channel.as_str(), user_id)
+}
+
+fn email_token_key(token: &str) -> String {
+    format!("verification:email_token:{}", token)
+}
@@ -1,12 +1,20 @@
+use crate::availability_alert::{AlertSite, AvailabilityAlert};
+use crate::calendar::{CalendarFeed, ics_calendar};
+use crate::delivery::DeliveryTracker;
+use crate::sms_budget::{SmsAllowance, SmsBudget};
+use crate::templates::NotificationTemplates;
 use crate::types::*;
-use aws_config::BehaviorVersion;
+use crate::unsubscribe::{UnsubscribeLinks, UnsubscribeTarget};
+use app_config::AppConfig;
+use aws_config::{BehaviorVersion, Region};
 use aws_sdk_ses::Client as SesClient;
 use aws_sdk_sns::Client as SnsClient;
-use chrono::{Duration, Utc};
-use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
-};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, NaiveDate, Utc};
+use i18n::{Locale, Tz, format_phone_number, local_date, message, today_in};
+use sqlx::PgPool;
+use tera::Context;
 use uuid::Uuid;
 
 /// Notification service for sending emails and SMS messages.
@@ -14,27 +22,94 @@ use uuid::Uuid;
 pub struct NotificationService {
     ses_client: SesClient,
     sns_client: SnsClient,
-    from_email: String,
+    templates: NotificationTemplates,
+    unsubscribe: UnsubscribeLinks,
+    calendar_feed: CalendarFeed,
+    deliveries: DeliveryTracker,
+    sender: String,
+    reply_to: Option<String>,
+    base_url: String,
+    ses_sandbox: bool,
+    sandbox: bool,
 }
 
 impl NotificationService {
     /// Creates a new instance of the NotificationService with AWS clients initialized.
-    pub async fn new() -> Result<Self, NotificationError> {
-        let config = aws_config::defaults(BehaviorVersion::latest()).load().await;
+    pub async fn new(
+        config: &AppConfig,
+        templates: NotificationTemplates,
+        pool: PgPool,
+    ) -> Result<Self, NotificationError> {
+        let from_email = config.sender_address().ok_or_else(|| {
+            NotificationError::SesError(
+                "No sender address: set FROM_EMAIL or EMAIL_SENDING_DOMAIN".to_string(),
+            )
+        })?;
+        let aws_config = aws_config::defaults(BehaviorVersion::latest()).load().await;
 
-        let ses_client = SesClient::new(&config);
-        let sns_client = SnsClient::new(&config);
-
-        let from_email = std::env::var("FROM_EMAIL")
-            .unwrap_or_else(|_| "noreplycampsitetracker@gmail.com".to_string());
+        let ses_client = SesClient::new(&aws_config);
+        let sns_client = SnsClient::new(&aws_config);
 
         Ok(Self {
             ses_client,
             sns_client,
-            from_email,
+            templates,
+            unsubscribe: UnsubscribeLinks::new(config.link_key_material(), &config.base_url),
+            calendar_feed: CalendarFeed::new(config.link_key_material(), &config.base_url),
+            deliveries: DeliveryTracker::new(pool),
+            sender: sender_mailbox(&config.from_name, &from_email),
+            reply_to: config.reply_to_email.clone(),
+            base_url: config.base_url.clone(),
+            // While the SES account is in the sandbox, only verified identities can receive email
+            ses_sandbox: config.ses_sandbox,
+            sandbox: false,
         })
     }
 
+    /// Creates a sandbox NotificationService that logs messages instead of sending them.
+    /// No AWS configuration or credentials are loaded.
+    pub fn sandbox(config: &AppConfig, templates: NotificationTemplates, pool: PgPool) -> Self {
+        let region = Region::new("us-west-2");
+
+        let ses_config = aws_sdk_ses::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(region.clone())
+            .build();
+        let sns_config = aws_sdk_sns::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(region)
+            .build();
+
+        Self {
+            ses_client: SesClient::from_conf(ses_config),
+            sns_client: SnsClient::from_conf(sns_config),
+            templates,
+            unsubscribe: UnsubscribeLinks::new(config.link_key_material(), &config.base_url),
+            calendar_feed: CalendarFeed::new(config.link_key_material(), &config.base_url),
+            deliveries: DeliveryTracker::new(pool),
+            sender: sender_mailbox(
+                &config.from_name,
+                &config
+                    .sender_address()
+                    .unwrap_or_else(|| "sandbox@camptracker.test".to_string()),
+            ),
+            reply_to: config.reply_to_email.clone(),
+            base_url: config.base_url.clone(),
+            ses_sandbox: false,
+            sandbox: true,
+        }
+    }
+
+    /// Signs and verifies the unsubscribe links in notification emails
+    pub fn unsubscribe_links(&self) -> &UnsubscribeLinks {
+        &self.unsubscribe
+    }
+
+    /// Signs and verifies the links of users' scan calendar feeds
+    pub fn calendar_feed(&self) -> &CalendarFeed {
+        &self.calendar_feed
+    }
+
     /// Sends an email verification LINK to the user (NEW FUNCTION)
     pub async fn send_email_verification_link(
         &self,
@@ -42,6 +117,7 @@ impl NotificationService {
         email: &str,
         name: &str,
         verification_token: &str,
+        locale: Locale,
     ) -> Result<(), NotificationError> {
         log::info!(
             "📧 Sending verification link to {} for user {}",
@@ -51,54 +127,695 @@ impl NotificationService {
 
         // Build the verification URL
         let verification_url = format!(
-            "http://localhost:8080/verify-email?token={}",
-            verification_token
+            "{}/verify-email?token={}",
+            self.base_url, verification_token
         );
 
-        let subject = "Verify your CampTracker email";
-        let html_body = format!(
-            r#"
-            <html>
-            <body style="font-family: Arial, sans-serif; max-width: 600px; margin: 0 auto;">
-                <div style="background: linear-gradient(135deg, #2c3e50 0%, #4a6741 100%); padding: 20px; text-align: center;">
-                    <h1 style="color: white; margin: 0;">🏕️ CampTracker</h1>
-                </div>
-                <div style="padding: 30px; background: white;">
-                    <h2 style="color: #2c3e50;">Hi {}!</h2>
-                    <p style="font-size: 16px; line-height: 1.6; color: #374151;">
-                        Welcome to CampTracker! Please verify your email address to complete your account setup.
-                    </p>
-                    <div style="text-align: center; margin: 30px 0;">
-                        <a href="{}" style="
-                            display: inline-block;
-                            background: #4a6741;
-                            color: white;
-                            text-decoration: none;
-                            padding: 12px 24px;
-                            border-radius: 8px;
-                            font-weight: bold;
-                            font-size: 16px;
-                        ">Verify Email Address</a>
-                    </div>
-                    <p style="font-size: 14px; color: #6b7280;">
-                        This link will expire in 24 hours. If you didn't create this account, you can safely ignore this email.
-                    </p>
-                </div>
-                <div style="background: #f9fafb; padding: 20px; text-align: center; color: #6b7280; font-size: 12px;">
-                    <p>© 2025 CampTracker. Never miss a campsite!</p>
-                </div>
-            </body>
-            </html>
-            "#,
-            name, verification_url
+        let mut context = Context::new();
+        context.insert("name", name);
+        context.insert("verification_url", &verification_url);
+
+        self.send_templated_email(email, "email_verification", locale, &context)
+            .await?;
+
+        log::info!(
+            "✅ Email verification link sent to {} for user {}",
+            email,
+            user_id
         );
+        Ok(())
+    }
+
+    /// Emails a link confirming that the user wants to switch their account to this new
+    /// address.
+    pub async fn send_email_change_confirmation(
+        &self,
+        new_email: &str,
+        name: &str,
+        token: &str,
+        locale: Locale,
+    ) -> Result<(), NotificationError> {
+        log::info!("📧 Sending email change confirmation to {}", new_email);
+
+        let confirmation_url = format!("{}/confirm-email-change?token={}", self.base_url, token);
+
+        let mut context = Context::new();
+        context.insert("name", name);
+        context.insert("confirmation_url", &confirmation_url);
+
+        self.send_templated_email(new_email, "email_change_confirmation", locale, &context)
+            .await
+    }
+
+    /// Tells the previous address of an account that its email was changed, so an
+    /// unexpected change doesn't go unnoticed.
+    pub async fn send_email_changed_notice(
+        &self,
+        old_email: &str,
+        name: &str,
+        new_email: &str,
+        locale: Locale,
+    ) -> Result<(), NotificationError> {
+        log::info!("📧 Sending email change notice to {}", old_email);
+
+        let mut context = Context::new();
+        context.insert("name", name);
+        context.insert("new_email", new_email);
+
+        self.send_templated_email(old_email, "email_changed_notice", locale, &context)
+            .await
+    }
+
+    /// Reminds a user who hasn't finished verifying that alerts can't reach them yet.
+    /// `verification_token` adds a link verifying the email address; `phone_unverified`
+    /// adds a note to verify the phone number from the profile.
+    pub async fn send_verification_reminder(
+        &self,
+        email: &str,
+        name: &str,
+        verification_token: Option<&str>,
+        phone_unverified: bool,
+        locale: Locale,
+    ) -> Result<(), NotificationError> {
+        log::info!("📧 Sending verification reminder to {}", email);
+
+        let verification_url = verification_token
+            .map(|token| format!("{}/verify-email?token={}", self.base_url, token));
+
+        let mut context = Context::new();
+        context.insert("name", name);
+        context.insert("verification_url", &verification_url);
+        context.insert("phone_unverified", &phone_unverified);
+        context.insert("profile_url", &format!("{}/?profile", self.base_url));
+
+        self.send_templated_email(email, "verification_reminder", locale, &context)
+            .await
+    }
+
+    /// Emails an invitation to join an organization.
+    pub async fn send_organization_invitation(
+        &self,
+        email: &str,
+        inviter_name: &str,
+        organization_name: &str,
+        invitation_token: &str,
+        locale: Locale,
+    ) -> Result<(), NotificationError> {
+        log::info!(
+            "📧 Sending invitation to {} for organization {}",
+            email,
+            organization_name
+        );
+
+        let invitation_url = format!("{}/?invitation={}", self.base_url, invitation_token);
+
+        let mut context = Context::new();
+        context.insert("inviter_name", inviter_name);
+        context.insert("organization_name", organization_name);
+        context.insert("invitation_url", &invitation_url);
+
+        self.send_templated_email(email, "organization_invitation", locale, &context)
+            .await
+    }
+
+    /// Emails someone a scan's owner added as a recipient, asking them to confirm before
+    /// they get the scan's alerts. Recipients have no language of their own, so it's
+    /// written in the owner's.
+    pub async fn send_scan_recipient_invitation(
+        &self,
+        email: &str,
+        owner_name: &str,
+        campground_name: &str,
+        dates: &str,
+        recipient_token: &str,
+        locale: Locale,
+    ) -> Result<(), NotificationError> {
+        log::info!(
+            "📧 Sending scan recipient invitation to {} for {}",
+            email,
+            campground_name
+        );
+
+        let context =
+            self.scan_recipient_context(owner_name, campground_name, dates, recipient_token);
+
+        self.send_templated_email(email, "scan_recipient_invitation", locale, &context)
+            .await
+    }
+
+    /// Texts someone a scan's owner added as a recipient, asking them to confirm before
+    /// they get the scan's alerts. The text counts against the owner's SMS budget; nothing
+    /// is sent unless the returned allowance is `Allowed`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_scan_recipient_invitation_sms(
+        &self,
+        budget: &SmsBudget,
+        owner_id: &Uuid,
+        phone: &str,
+        owner_name: &str,
+        campground_name: &str,
+        dates: &str,
+        recipient_token: &str,
+        locale: Locale,
+    ) -> Result<SmsAllowance, NotificationError> {
+        let message = self.templates.render_sms(
+            "scan_recipient_invitation",
+            locale,
+            &self.scan_recipient_context(owner_name, campground_name, dates, recipient_token),
+        )?;
+
+        self.send_sms_within_budget(budget, owner_id, phone, &message, None)
+            .await
+    }
+
+    /// Emails an availability alert to a confirmed scan recipient who isn't the owner.
+    /// Their unsubscribe link stops this scan's alerts, and the email is recorded in the
+    /// notifications ledger under the scan's owner.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_recipient_availability_alert(
+        &self,
+        owner_id: &Uuid,
+        email: &str,
+        alert: &AvailabilityAlert,
+        scan_id: &Uuid,
+        recipient_token: &str,
+        locale: Locale,
+        timezone: Tz,
+    ) -> Result<(), NotificationError> {
+        log::info!(
+            "📧 Sending availability alert to recipient {} of scan {} ({} sites)",
+            email,
+            scan_id,
+            alert.site_count()
+        );
+
+        let stop_url = self.scan_recipient_url("decline", recipient_token);
+        let mut context = alert.context(locale, today_in(timezone));
+        // Recipients have no account to view the scan in
+        context.insert("scan_url", &None::<String>);
+        context.insert("notice", &None::<String>);
+        context.insert("unsubscribe_url", &stop_url);
+        context.insert("scan_unsubscribe_url", &None::<String>);
 
-        let text_body = format!(
-            "Hi {}!\n\nWelcome to CampTracker!\n\nPlease verify your email by visiting this link:\n{}\n\nThis link will expire in 24 hours.\n\n© 2025 CampTracker",
-            name, verification_url
+        let rendered = self
+            .templates
+            .render_email("availability_alert", locale, &context)?;
+        let stay = stay_attachment(alert, scan_id);
+
+        let message_id = self
+            .send_email(
+                email,
+                &rendered.subject,
+                rendered.html,
+                rendered.text.clone(),
+                Some(&stop_url),
+                Some(&stay),
+            )
+            .await?;
+
+        if let Err(e) = self
+            .deliveries
+            .record_email(
+                owner_id,
+                Some(scan_id),
+                email,
+                &rendered.subject,
+                &rendered.text,
+                message_id.as_deref(),
+            )
+            .await
+        {
+            log::error!(
+                "❌ Failed to record email to recipient of scan {}: {}",
+                scan_id,
+                e
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Context of the scan recipient invitation, linking to its confirm and decline pages
+    fn scan_recipient_context(
+        &self,
+        owner_name: &str,
+        campground_name: &str,
+        dates: &str,
+        recipient_token: &str,
+    ) -> Context {
+        let mut context = Context::new();
+        context.insert("owner_name", owner_name);
+        context.insert("campground_name", campground_name);
+        context.insert("dates", dates);
+        context.insert(
+            "confirm_url",
+            &self.scan_recipient_url("confirm", recipient_token),
+        );
+        context.insert(
+            "decline_url",
+            &self.scan_recipient_url("decline", recipient_token),
+        );
+        context
+    }
+
+    /// Link confirming or declining a scan recipient invitation
+    fn scan_recipient_url(&self, action: &str, recipient_token: &str) -> String {
+        format!(
+            "{}/recipients/{}?token={}",
+            self.base_url, action, recipient_token
+        )
+    }
+
+    /// Emails a user that permits they're watching were released on the given entry dates.
+    /// `notice` explains why the alert came by email, e.g. when the SMS budget ran out.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_permit_available(
+        &self,
+        user_id: &Uuid,
+        email: &str,
+        permit_name: &str,
+        permit_id: &str,
+        dates: &[NaiveDate],
+        notice: Option<&str>,
+        locale: Locale,
+    ) -> Result<(), NotificationError> {
+        log::info!("📧 Sending permit alert to {} for {}", email, permit_name);
+
+        let mut context = permit_context(permit_name, permit_id, dates, locale);
+        context.insert(
+            "dates",
+            &dates
+                .iter()
+                .map(|date| locale.full_date(*date))
+                .collect::<Vec<_>>(),
+        );
+        context.insert("notice", &notice);
+
+        self.send_notification_email(
+            user_id,
+            email,
+            "permit_available",
+            locale,
+            &mut context,
+            None,
+            None,
+        )
+        .await
+    }
+
+    /// Texts a user that permits they're watching were released, within the monthly SMS
+    /// budgets. Nothing is sent unless the returned allowance is `Allowed`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_permit_sms(
+        &self,
+        budget: &SmsBudget,
+        user_id: &Uuid,
+        phone: &str,
+        permit_name: &str,
+        permit_id: &str,
+        dates: &[NaiveDate],
+        locale: Locale,
+    ) -> Result<SmsAllowance, NotificationError> {
+        let message = self.templates.render_sms(
+            "permit_available",
+            locale,
+            &permit_context(permit_name, permit_id, dates, locale),
+        )?;
+
+        self.send_sms_within_budget(budget, user_id, phone, &message, None)
+            .await
+    }
+
+    /// Emails a user every site a poll found open for one of their scans, naming up to
+    /// [`MAX_ALERT_SITES`](crate::availability_alert::MAX_ALERT_SITES) and summing up the
+    /// rest. `notice` explains why the alert came by email, e.g. when the SMS budget ran
+    /// out. A stay starting today or tomorrow in the user's `timezone` is called tonight
+    /// or tomorrow night.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_availability_alert(
+        &self,
+        user_id: &Uuid,
+        email: &str,
+        alert: &AvailabilityAlert,
+        scan_id: &Uuid,
+        notice: Option<&str>,
+        locale: Locale,
+        timezone: Tz,
+    ) -> Result<(), NotificationError> {
+        log::info!(
+            "📧 Sending availability alert to {} for scan {} ({} sites)",
+            email,
+            scan_id,
+            alert.site_count()
+        );
+
+        self.send_alert_email(
+            user_id,
+            email,
+            alert,
+            Some(scan_id),
+            notice,
+            locale,
+            timezone,
+        )
+        .await
+    }
+
+    /// Sends a sample availability alert to an admin by email, through the same templates,
+    /// SES client and notifications ledger as real alerts, so a broken SES setup shows up
+    /// before a real alert is lost.
+    pub async fn send_test_alert_email(
+        &self,
+        user_id: &Uuid,
+        email: &str,
+        locale: Locale,
+        timezone: Tz,
+    ) -> Result<(), NotificationError> {
+        log::info!("🧪 Sending test availability alert to {}", email);
+
+        let notice = message(locale, "test-alert-notice");
+        self.send_alert_email(
+            user_id,
+            email,
+            &test_alert(),
+            None,
+            Some(&notice),
+            locale,
+            timezone,
+        )
+        .await
+    }
+
+    /// Texts a sample availability alert to an admin through SNS, like
+    /// [`send_test_alert_email`](Self::send_test_alert_email). The text counts against the
+    /// SMS budgets like any other; nothing is sent unless the allowance is `Allowed`.
+    pub async fn send_test_alert_sms(
+        &self,
+        budget: &SmsBudget,
+        user_id: &Uuid,
+        phone: &str,
+        locale: Locale,
+        timezone: Tz,
+    ) -> Result<SmsAllowance, NotificationError> {
+        log::info!("🧪 Sending test availability SMS to user {}", user_id);
+
+        let message = self.templates.render_sms(
+            "availability_alert",
+            locale,
+            &test_alert().context(locale, today_in(timezone)),
+        )?;
+
+        self.send_sms_within_budget(budget, user_id, phone, &message, None)
+            .await
+    }
+
+    /// Emails an availability alert with the stay attached as a calendar event, linking to
+    /// the scan when there is one
+    #[allow(clippy::too_many_arguments)]
+    async fn send_alert_email(
+        &self,
+        user_id: &Uuid,
+        email: &str,
+        alert: &AvailabilityAlert,
+        scan_id: Option<&Uuid>,
+        notice: Option<&str>,
+        locale: Locale,
+        timezone: Tz,
+    ) -> Result<(), NotificationError> {
+        let mut context = alert.context(locale, today_in(timezone));
+        context.insert(
+            "scan_url",
+            &match scan_id {
+                Some(scan_id) => format!("{}/?scan={}", self.base_url, scan_id),
+                None => format!("{}/", self.base_url),
+            },
+        );
+        context.insert("notice", &notice);
+
+        let event_id = scan_id.copied().unwrap_or_else(Uuid::new_v4);
+        let stay = stay_attachment(alert, &event_id);
+
+        self.send_notification_email(
+            user_id,
+            email,
+            "availability_alert",
+            locale,
+            &mut context,
+            scan_id,
+            Some(&stay),
+        )
+        .await
+    }
+
+    /// Texts a user the sites a poll found open for one of their scans, within the
+    /// monthly SMS budgets. Nothing is sent unless the returned allowance is `Allowed`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_availability_sms(
+        &self,
+        budget: &SmsBudget,
+        user_id: &Uuid,
+        phone: &str,
+        alert: &AvailabilityAlert,
+        scan_id: &Uuid,
+        locale: Locale,
+        timezone: Tz,
+    ) -> Result<SmsAllowance, NotificationError> {
+        let message = self.templates.render_sms(
+            "availability_alert",
+            locale,
+            &alert.context(locale, today_in(timezone)),
+        )?;
+
+        self.send_sms_within_budget(budget, user_id, phone, &message, Some(scan_id))
+            .await
+    }
+
+    /// Emails a user that a scan reaches its maximum age soon, with a link to extend it.
+    /// The expiry is dated in the user's `timezone`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_scan_expiring(
+        &self,
+        user_id: &Uuid,
+        email: &str,
+        campground_name: &str,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+        expires_at: DateTime<Utc>,
+        scan_id: &Uuid,
+        locale: Locale,
+        timezone: Tz,
+    ) -> Result<(), NotificationError> {
+        log::info!(
+            "📧 Sending scan expiry warning to {} for {}",
+            email,
+            scan_id
         );
 
-        // Rest is the same as your existing send_email_verification function
+        let mut context = Context::new();
+        context.insert("campground_name", campground_name);
+        context.insert("dates", &locale.date_range(check_in_date, check_out_date));
+        context.insert(
+            "expires",
+            &locale.short_date(local_date(expires_at, timezone)),
+        );
+        context.insert("scan_url", &format!("{}/?scan={}", self.base_url, scan_id));
+
+        self.send_notification_email(
+            user_id,
+            email,
+            "scan_expiring",
+            locale,
+            &mut context,
+            Some(scan_id),
+            None,
+        )
+        .await
+    }
+
+    /// Emails the owner of a scan that its campground has been failing to be checked since
+    /// `failing_since` (e.g. closed for the season), with a link to the scan.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_scan_blocked(
+        &self,
+        user_id: &Uuid,
+        email: &str,
+        campground_name: &str,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+        failing_since: DateTime<Utc>,
+        scan_id: &Uuid,
+        locale: Locale,
+        timezone: Tz,
+    ) -> Result<(), NotificationError> {
+        log::info!(
+            "📧 Sending blocked scan notice to {} for {}",
+            email,
+            scan_id
+        );
+
+        let mut context = Context::new();
+        context.insert("campground_name", campground_name);
+        context.insert("dates", &locale.date_range(check_in_date, check_out_date));
+        context.insert(
+            "since",
+            &locale.short_date(local_date(failing_since, timezone)),
+        );
+        context.insert("scan_url", &format!("{}/?scan={}", self.base_url, scan_id));
+
+        self.send_notification_email(
+            user_id,
+            email,
+            "scan_blocked",
+            locale,
+            &mut context,
+            Some(scan_id),
+            None,
+        )
+        .await
+    }
+
+    /// Emails an operational alert as plain text, e.g. from the scan watchdog. Not
+    /// recorded in the notifications ledger, which holds messages to users.
+    pub async fn send_ops_email(
+        &self,
+        email: &str,
+        subject: &str,
+        body: &str,
+    ) -> Result<(), NotificationError> {
+        let html = format!(
+            "<pre style=\"font-family: monospace; white-space: pre-wrap;\">{}</pre>",
+            tera::escape_html(body)
+        );
+
+        self.send_email(email, subject, html, body.to_string(), None, None)
+            .await?;
+        Ok(())
+    }
+
+    /// Renders the email template `name` in `locale` and sends it.
+    async fn send_templated_email(
+        &self,
+        email: &str,
+        name: &str,
+        locale: Locale,
+        context: &Context,
+    ) -> Result<(), NotificationError> {
+        let rendered = self.templates.render_email(name, locale, context)?;
+
+        self.send_email(
+            email,
+            &rendered.subject,
+            rendered.html,
+            rendered.text,
+            None,
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Renders the alert email template `name` in `locale` with links unsubscribing the user from
+    /// email alerts and, given `scan_id`, from that scan, and sends it with
+    /// `List-Unsubscribe` headers so mail clients offer one-click unsubscribe. The email is
+    /// recorded in the notifications ledger, where SES feedback updates its status.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_notification_email(
+        &self,
+        user_id: &Uuid,
+        email: &str,
+        name: &str,
+        locale: Locale,
+        context: &mut Context,
+        scan_id: Option<&Uuid>,
+        attachment: Option<&EmailAttachment>,
+    ) -> Result<(), NotificationError> {
+        let unsubscribe_url = self.unsubscribe.url(user_id, UnsubscribeTarget::Email);
+        context.insert("unsubscribe_url", &unsubscribe_url);
+        context.insert(
+            "scan_unsubscribe_url",
+            &scan_id.map(|scan_id| {
+                self.unsubscribe
+                    .url(user_id, UnsubscribeTarget::Scan(*scan_id))
+            }),
+        );
+
+        let rendered = self.templates.render_email(name, locale, context)?;
+
+        let message_id = self
+            .send_email(
+                email,
+                &rendered.subject,
+                rendered.html,
+                rendered.text.clone(),
+                Some(&unsubscribe_url),
+                attachment,
+            )
+            .await?;
+
+        // The email is out; failing here would only get it sent again
+        if let Err(e) = self
+            .deliveries
+            .record_email(
+                user_id,
+                scan_id,
+                email,
+                &rendered.subject,
+                &rendered.text,
+                message_id.as_deref(),
+            )
+            .await
+        {
+            log::error!("❌ Failed to record email to user {}: {}", user_id, e);
+        }
+
+        Ok(())
+    }
+
+    /// Sends an email through SES, or logs it in sandbox mode. With an
+    /// `unsubscribe_url` or an attachment, the email goes out as a raw MIME message
+    /// carrying the `List-Unsubscribe` headers and the attachment, which SES's simple send
+    /// doesn't support. Returns the SES message ID, which is `None` in sandbox mode.
+    async fn send_email(
+        &self,
+        email: &str,
+        subject: &str,
+        html_body: String,
+        text_body: String,
+        unsubscribe_url: Option<&str>,
+        attachment: Option<&EmailAttachment>,
+    ) -> Result<Option<String>, NotificationError> {
+        if self.sandbox {
+            log::info!(
+                "🧪 [sandbox] Email from {} to {} not sent. Subject: {}\n{}",
+                self.sender,
+                email,
+                subject,
+                text_body
+            );
+            if let Some(attachment) = attachment {
+                log::info!(
+                    "🧪 [sandbox] Attachment {}:\n{}",
+                    attachment.filename,
+                    attachment.body
+                );
+            }
+            return Ok(None);
+        }
+
+        if unsubscribe_url.is_some() || attachment.is_some() {
+            let message = mime_message(
+                &self.sender,
+                self.reply_to.as_deref(),
+                email,
+                subject,
+                &html_body,
+                &text_body,
+                unsubscribe_url,
+                attachment,
+            );
+            return self.send_raw_email(message).await;
+        }
+
         let subject_content = aws_sdk_ses::types::Content::builder()
             .data(subject)
             .build()
@@ -142,7 +859,8 @@ impl NotificationService {
         let result = self
             .ses_client
             .send_email()
-            .source(&self.from_email)
+            .source(&self.sender)
+            .set_reply_to_addresses(self.reply_to.clone().map(|address| vec![address]))
             .destination(destination)
             .message(message)
             .send()
@@ -150,14 +868,43 @@ impl NotificationService {
 
         match result {
             Ok(output) => {
-                log::info!(
-                    "✅ Email verification link sent to {} for user {}",
-                    email,
-                    user_id
-                );
                 let message_id = output.message_id();
                 log::info!("📧 SES Message ID: {}", message_id);
-                Ok(())
+                Ok(Some(message_id.to_string()))
+            }
+            Err(e) => {
+                log::error!("❌ AWS SES error: {:#?}", e);
+                let error_msg = if let Some(service_error) = e.as_service_error() {
+                    format!("AWS SES service error: {:?}", service_error)
+                } else {
+                    format!("AWS SES error: {}", e)
+                };
+                Err(NotificationError::SesError(error_msg))
+            }
+        }
+    }
+
+    /// Sends a complete MIME message through SES, returning its message ID
+    async fn send_raw_email(&self, message: String) -> Result<Option<String>, NotificationError> {
+        let raw_message = aws_sdk_ses::types::RawMessage::builder()
+            .data(aws_sdk_ses::primitives::Blob::new(message))
+            .build()
+            .map_err(|e| {
+                NotificationError::SesError(format!("Failed to build raw message: {}", e))
+            })?;
+
+        log::info!("📧 Sending raw email via AWS SES...");
+
+        match self
+            .ses_client
+            .send_raw_email()
+            .raw_message(raw_message)
+            .send()
+            .await
+        {
+            Ok(output) => {
+                log::info!("📧 SES Message ID: {}", output.message_id());
+                Ok(Some(output.message_id().to_string()))
             }
             Err(e) => {
                 log::error!("❌ AWS SES error: {:#?}", e);
@@ -171,39 +918,197 @@ impl NotificationService {
         }
     }
 
-    /// Sends an SMS verification message to the user.
+    /// Sends an SMS verification message to the user, within the monthly SMS budgets.
     pub async fn send_sms_verification(
         &self,
+        budget: &SmsBudget,
         user_id: &Uuid,
         phone: &str,
         verification_code: &str,
+        locale: Locale,
     ) -> Result<(), NotificationError> {
-        // Ensure phone number is in E.164 format
-        let formatted_phone = if phone.starts_with('+') {
-            phone.to_string()
-        } else {
-            format!("+{}", phone.replace(['(', ')', '-', ' ', '.'], ""))
-        };
+        let mut context = Context::new();
+        context.insert("code", verification_code);
+        let message = self
+            .templates
+            .render_sms("sms_verification", locale, &context)?;
 
-        let message = format!(
-            "Your CampTracker verification code is: {}\n\nThis code expires in 10 minutes.\n\nIf you didn't request this, ignore this message.",
-            verification_code
-        );
+        let allowance = self
+            .send_sms_within_budget(budget, user_id, phone, &message, None)
+            .await?;
+        if !allowance.is_allowed() {
+            return Err(NotificationError::SmsBudgetExhausted(allowance));
+        }
 
-        self.sns_client
+        log::info!("SMS verification sent to {} for user {}", phone, user_id);
+        Ok(())
+    }
+
+    /// Sends an SMS if the monthly budgets allow it, and records it in the notifications
+    /// ledger. Nothing is sent unless the returned allowance is `Allowed`; callers fall
+    /// back to email with the allowance's notice.
+    pub async fn send_sms_within_budget(
+        &self,
+        budget: &SmsBudget,
+        user_id: &Uuid,
+        phone: &str,
+        message: &str,
+        scan_id: Option<&Uuid>,
+    ) -> Result<SmsAllowance, NotificationError> {
+        let allowance = budget.check(user_id).await?;
+        if !allowance.is_allowed() {
+            log::warn!("📵 SMS to user {} not sent: {:?}", user_id, allowance);
+            return Ok(allowance);
+        }
+
+        let (formatted_phone, message_id) = self.send_sms(phone, message).await?;
+        budget
+            .record(
+                user_id,
+                &formatted_phone,
+                message,
+                scan_id,
+                message_id.as_deref(),
+            )
+            .await?;
+
+        Ok(allowance)
+    }
+
+    /// Sends an SMS through SNS, or logs it in sandbox mode. Returns the number in E.164
+    /// format and the SNS message ID, which is `None` in sandbox mode.
+    async fn send_sms(
+        &self,
+        phone: &str,
+        message: &str,
+    ) -> Result<(String, Option<String>), NotificationError> {
+        let formatted_phone = format_phone_number(phone);
+
+        if self.sandbox {
+            log::info!(
+                "🧪 [sandbox] SMS to {} not sent. Message: {}",
+                formatted_phone,
+                message
+            );
+            return Ok((formatted_phone, None));
+        }
+
+        let output = self
+            .sns_client
             .publish()
             .phone_number(&formatted_phone)
-            .message(&message)
+            .message(message)
             .send()
             .await
             .map_err(|e| NotificationError::SnsError(e.to_string()))?;
 
-        log::info!(
-            "SMS verification sent to {} for user {}",
-            formatted_phone,
-            user_id
-        );
-        Ok(())
+        Ok((formatted_phone, output.message_id().map(str::to_string)))
+    }
+
+    /// Checks whether email sent to the address would be delivered: SES sending must be
+    /// enabled, and while the account is in the SES sandbox the recipient must be a
+    /// verified identity.
+    pub async fn check_email_deliverability(
+        &self,
+        email: &str,
+    ) -> Result<ChannelDeliverability, NotificationError> {
+        if self.sandbox {
+            return Ok(ChannelDeliverability::deliverable());
+        }
+
+        let sending = self
+            .ses_client
+            .get_account_sending_enabled()
+            .send()
+            .await
+            .map_err(|e| NotificationError::SesError(e.to_string()))?;
+
+        if !sending.enabled() {
+            return Ok(ChannelDeliverability::undeliverable(
+                "Email sending is currently disabled",
+            ));
+        }
+
+        if self.ses_sandbox {
+            let attributes = self
+                .ses_client
+                .get_identity_verification_attributes()
+                .identities(email)
+                .send()
+                .await
+                .map_err(|e| NotificationError::SesError(e.to_string()))?;
+
+            let verified = attributes
+                .verification_attributes()
+                .get(email)
+                .is_some_and(|a| {
+                    *a.verification_status() == aws_sdk_ses::types::VerificationStatus::Success
+                });
+
+            if !verified {
+                return Ok(ChannelDeliverability::undeliverable(
+                    "Email can only be sent to approved recipients at the moment",
+                ));
+            }
+        }
+
+        Ok(ChannelDeliverability::deliverable())
+    }
+
+    /// Checks whether SMS sent to the phone number would be delivered, i.e. the number
+    /// hasn't opted out of SMS from this account.
+    pub async fn check_sms_deliverability(
+        &self,
+        phone: &str,
+    ) -> Result<ChannelDeliverability, NotificationError> {
+        if self.sandbox {
+            return Ok(ChannelDeliverability::deliverable());
+        }
+
+        let result = self
+            .sns_client
+            .check_if_phone_number_is_opted_out()
+            .phone_number(phone)
+            .send()
+            .await
+            .map_err(|e| NotificationError::SnsError(e.to_string()))?;
+
+        if result.is_opted_out() {
+            return Ok(ChannelDeliverability::undeliverable(
+                "This number has opted out of SMS. Reply START to a CampTracker message to opt back in",
+            ));
+        }
+
+        Ok(ChannelDeliverability::deliverable())
+    }
+
+    /// Runs the pre-flight checks for every channel of a user. Checks that fail to run
+    /// are logged and reported as unknown rather than undeliverable.
+    pub async fn check_deliverability(
+        &self,
+        email: &str,
+        phone: Option<&str>,
+    ) -> DeliverabilityStatus {
+        let email = self
+            .check_email_deliverability(email)
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("⚠️ Email deliverability check failed: {}", e);
+                ChannelDeliverability::unknown("Email deliverability could not be checked")
+            });
+
+        let sms = match phone {
+            Some(phone) => self
+                .check_sms_deliverability(phone)
+                .await
+                .unwrap_or_else(|e| {
+                    log::warn!("⚠️ SMS deliverability check failed: {}", e);
+                    ChannelDeliverability::unknown("SMS deliverability could not be checked")
+                }),
+            None => ChannelDeliverability::undeliverable("No phone number on file"),
+        };
+
+        DeliverabilityStatus { email, sms }
     }
 
     /// Generates a random 6-digit verification code (for SMS).
@@ -226,53 +1131,170 @@ impl NotificationService {
     }
 }
 
-/// A thread-safe store for verification codes, allowing concurrent access.
-pub fn create_verification_store() -> VerificationStore {
-    Arc::new(Mutex::new(HashMap::new()))
+/// Template variables shared by the permit alert email and SMS
+fn permit_context(
+    permit_name: &str,
+    permit_id: &str,
+    dates: &[NaiveDate],
+    locale: Locale,
+) -> Context {
+    let mut context = Context::new();
+    context.insert("permit_name", permit_name);
+    context.insert(
+        "booking_url",
+        &format!("https://www.recreation.gov/permits/{}", permit_id),
+    );
+    context.insert("date_count", &dates.len());
+    context.insert(
+        "first_date",
+        &dates
+            .first()
+            .map(|date| locale.short_date(*date))
+            .unwrap_or_default(),
+    );
+    context
 }
 
-/// Represents a verification code for user actions like phone number or email verification.
-pub fn store_verification_code(
-    store: &VerificationStore,
-    key: &str,
-    code: &str,
-    expires_in_minutes: i64,
-) {
-    let verification = VerificationCode {
-        code: code.to_string(),
-        expires_at: Utc::now() + Duration::minutes(expires_in_minutes),
-        attempts: 0,
-    };
+/// Made-up alert sent by the admin notification test: two sites open for a weekend a
+/// month out
+fn test_alert() -> AvailabilityAlert {
+    let check_in_date = Utc::now().date_naive() + chrono::Duration::days(30);
+    let check_out_date = check_in_date + chrono::Duration::days(2);
+    let open_nights: Vec<NaiveDate> = check_in_date
+        .iter_days()
+        .take_while(|night| *night < check_out_date)
+        .collect();
 
-    store.lock().unwrap().insert(key.to_string(), verification);
+    AvailabilityAlert::new(
+        "CampTracker Test Campground",
+        check_in_date,
+        check_out_date,
+        "Recreation.gov",
+        "https://www.recreation.gov/camping/campgrounds/232447",
+        ["A01", "A02"].map(|site| AlertSite {
+            loop_name: Some("Loop A".to_string()),
+            site: site.to_string(),
+            open_nights: open_nights.clone(),
+            nightly_price_cents: Some(3_500),
+            booking_url: None,
+        }),
+    )
 }
 
-/// Verifies the provided code against the stored verification code.
-pub fn verify_code(
-    store: &VerificationStore,
-    key: &str,
-    provided_code: &str,
-) -> Result<bool, String> {
-    let mut store = store.lock().unwrap();
-
-    let verification = store.get_mut(key).ok_or("Verification code not found")?;
+/// File attached to an email
+struct EmailAttachment {
+    filename: &'static str,
+    content_type: &'static str,
+    body: String,
+}
 
-    if verification.expires_at < Utc::now() {
-        store.remove(key);
-        return Err("Verification code has expired".to_string());
+/// The stay of an availability alert as a calendar event to attach
+fn stay_attachment(alert: &AvailabilityAlert, event_id: &Uuid) -> EmailAttachment {
+    EmailAttachment {
+        filename: "stay.ics",
+        content_type: "text/calendar; method=PUBLISH",
+        body: ics_calendar(
+            &alert.campground_name,
+            Some("PUBLISH"),
+            &[alert.calendar_event(event_id)],
+        ),
     }
+}
 
-    verification.attempts += 1;
+/// Builds a multipart/alternative email, wrapped in multipart/mixed with the attachment
+/// when there is one. Given an `unsubscribe_url`, it carries `List-Unsubscribe` and
+/// `List-Unsubscribe-Post` headers (RFC 8058), so mail clients can unsubscribe with one
+/// click instead of the user reporting spam. With a `reply_to` address it carries a
+/// `Reply-To` header. Bodies are base64-encoded.
+#[allow(clippy::too_many_arguments)]
+fn mime_message(
+    from: &str,
+    reply_to: Option<&str>,
+    to: &str,
+    subject: &str,
+    html_body: &str,
+    text_body: &str,
+    unsubscribe_url: Option<&str>,
+    attachment: Option<&EmailAttachment>,
+) -> String {
+    let boundary = format!("camptracker-{}", Uuid::new_v4().simple());
+    let part = |content_type: &str, body: &str| {
+        format!(
+            "--{boundary}\r\nContent-Type: {content_type}; charset=UTF-8\r\nContent-Transfer-Encoding: base64\r\n\r\n{}\r\n",
+            wrap_base64(body)
+        )
+    };
+    let alternative = format!(
+        "Content-Type: multipart/alternative; boundary=\"{boundary}\"\r\n\r\n{}{}--{boundary}--\r\n",
+        part("text/plain", text_body),
+        part("text/html", html_body),
+    );
 
-    if verification.attempts > 3 {
-        store.remove(key);
-        return Err("Too many verification attempts".to_string());
-    }
+    let unsubscribe_headers = unsubscribe_url
+        .map(|url| {
+            format!(
+                "List-Unsubscribe: <{url}>\r\nList-Unsubscribe-Post: List-Unsubscribe=One-Click\r\n"
+            )
+        })
+        .unwrap_or_default();
+    let reply_to_header = reply_to
+        .map(|address| format!("Reply-To: {address}\r\n"))
+        .unwrap_or_default();
+    let content = match attachment {
+        Some(attachment) => {
+            let mixed = format!("camptracker-{}", Uuid::new_v4().simple());
+            format!(
+                "Content-Type: multipart/mixed; boundary=\"{mixed}\"\r\n\r\n--{mixed}\r\n{alternative}--{mixed}\r\nContent-Type: {}; charset=UTF-8; name=\"{filename}\"\r\nContent-Disposition: attachment; filename=\"{filename}\"\r\nContent-Transfer-Encoding: base64\r\n\r\n{}\r\n--{mixed}--\r\n",
+                attachment.content_type,
+                wrap_base64(&attachment.body),
+                filename = attachment.filename,
+            )
+        }
+        None => alternative,
+    };
 
-    if verification.code == provided_code {
-        store.remove(key);
-        Ok(true)
+    format!(
+        "From: {from}\r\n{reply_to_header}To: {to}\r\nSubject: {}\r\nMIME-Version: 1.0\r\n{unsubscribe_headers}{content}",
+        encode_header(subject),
+    )
+}
+
+/// Sender mailbox such as `CampTracker <noreply@example.com>`, with the display name
+/// encoded when it isn't plain ASCII
+fn sender_mailbox(name: &str, address: &str) -> String {
+    if name.is_ascii() {
+        format!("\"{}\" <{}>", name, address)
     } else {
-        Ok(false)
+        format!("{} <{}>", encode_header(name), address)
+    }
+}
+
+/// Encodes a header value as RFC 2047 encoded words, split so each stays under the
+/// 75-character limit without breaking a character apart
+fn encode_header(value: &str) -> String {
+    const CHUNK_BYTES: usize = 45;
+
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for c in value.chars() {
+        if chunk.len() + c.len_utf8() > CHUNK_BYTES {
+            words.push(format!("=?UTF-8?B?{}?=", STANDARD.encode(&chunk)));
+            chunk.clear();
+        }
+        chunk.push(c);
     }
+    words.push(format!("=?UTF-8?B?{}?=", STANDARD.encode(&chunk)));
+
+    words.join("\r\n ")
+}
+
+/// Base64 with lines of 76 characters, as MIME requires
+fn wrap_base64(body: &str) -> String {
+    STANDARD
+        .encode(body)
+        .as_bytes()
+        .chunks(76)
+        .map(|line| std::str::from_utf8(line).expect("base64 is ASCII"))
+        .collect::<Vec<_>>()
+        .join("\r\n")
 }
@@ -0,0 +1,48 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use ring::{hkdf, hmac};
+
+/// Signs the tokens of links sent to users (unsubscribe links, calendar feeds) with
+/// HMAC-SHA256, using a key derived per purpose so a token for one can't be used for
+/// another
+#[derive(Clone)]
+pub(crate) struct LinkSigner {
+    key: hmac::Key,
+}
+
+impl LinkSigner {
+    /// Derives the key for `purpose` from the configured key material with HKDF-SHA256.
+    pub(crate) fn new(key_material: &str, purpose: &[u8]) -> Self {
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, b"campsite-tracker")
+            .extract(key_material.as_bytes());
+        let info = [purpose];
+        let okm = prk
+            .expand(&info, hmac::HMAC_SHA256)
+            .expect("HMAC-SHA256 key length is a valid HKDF output length");
+
+        Self {
+            key: hmac::Key::from(okm),
+        }
+    }
+
+    /// Token carrying `payload`: `<payload>.<signature>`
+    pub(crate) fn sign(&self, payload: &str) -> String {
+        let signature = hmac::sign(&self.key, payload.as_bytes());
+        format!("{}.{}", payload, URL_SAFE_NO_PAD.encode(signature.as_ref()))
+    }
+
+    /// Returns the payload of a token signed with this key, or `None` when the token was
+    /// altered or signed with another key
+    pub(crate) fn verify<'a>(&self, token: &'a str) -> Option<&'a str> {
+        let (payload, signature) = token.rsplit_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        hmac::verify(&self.key, payload.as_bytes(), &signature).ok()?;
+        Some(payload)
+    }
+}
+
+impl std::fmt::Debug for LinkSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LinkSigner").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,243 @@
+use app_config::AppConfig;
+use i18n::{Locale, message};
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+/// Whether an SMS may be sent under the monthly budgets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmsAllowance {
+    /// Both budgets have room
+    Allowed,
+    /// The recipient used up their monthly SMS budget
+    UserBudgetExhausted,
+    /// The monthly SMS budget of the whole service is used up
+    GlobalBudgetExhausted,
+}
+
+impl SmsAllowance {
+    /// Returns true if the SMS may be sent.
+    pub fn is_allowed(&self) -> bool {
+        *self == SmsAllowance::Allowed
+    }
+
+    /// Notice shown to users, in their language, when an alert falls back to email
+    /// instead of SMS.
+    pub fn fallback_notice(&self, locale: Locale) -> Option<String> {
+        match self {
+            SmsAllowance::Allowed => None,
+            SmsAllowance::UserBudgetExhausted => Some(message(locale, "sms-user-budget-exhausted")),
+            SmsAllowance::GlobalBudgetExhausted => {
+                Some(message(locale, "sms-global-budget-exhausted"))
+            }
+        }
+    }
+}
+
+/// SMS sent so far this month against a budget
+#[derive(Debug, Clone, Serialize)]
+pub struct SmsBudgetUsage {
+    /// Messages sent since the start of the calendar month (UTC)
+    pub sent: i64,
+    /// Monthly limit
+    pub limit: i64,
+    /// Messages left this month
+    pub remaining: i64,
+}
+
+/// Monthly SMS budgets, global and per user, tracked against the `notifications` ledger.
+#[derive(Debug, Clone)]
+pub struct SmsBudget {
+    pool: PgPool,
+    monthly_limit: i64,
+    user_monthly_limit: i64,
+}
+
+impl SmsBudget {
+    /// Creates a budget with the limits from the configuration.
+    pub fn from_config(pool: PgPool, config: &AppConfig) -> Self {
+        Self {
+            pool,
+            monthly_limit: config.sms_monthly_budget.into(),
+            user_monthly_limit: config.sms_monthly_user_budget.into(),
+        }
+    }
+
+    /// Reports the service-wide SMS usage this month.
+    pub async fn global_usage(&self) -> Result<SmsBudgetUsage, sqlx::Error> {
+        let sent = self.sent_this_month(None).await?;
+        Ok(usage(sent, self.monthly_limit))
+    }
+
+    /// Reports a user's SMS usage this month.
+    pub async fn user_usage(&self, user_id: &Uuid) -> Result<SmsBudgetUsage, sqlx::Error> {
+        let sent = self.sent_this_month(Some(user_id)).await?;
+        Ok(usage(sent, self.user_monthly_limit))
+    }
+
+    /// Checks whether another SMS may be sent to the user this month.
+    pub async fn check(&self, user_id: &Uuid) -> Result<SmsAllowance, sqlx::Error> {
+        if self.global_usage().await?.remaining == 0 {
+            return Ok(SmsAllowance::GlobalBudgetExhausted);
+        }
+
+        if self.user_usage(user_id).await?.remaining == 0 {
+            return Ok(SmsAllowance::UserBudgetExhausted);
+        }
+
+        Ok(SmsAllowance::Allowed)
+    }
+
+    /// Records a sent SMS in the notifications ledger. `external_id` is the SNS message
+    /// ID that delivery status reports refer to.
+    pub async fn record(
+        &self,
+        user_id: &Uuid,
+        phone: &str,
+        message: &str,
+        scan_id: Option<&Uuid>,
+        external_id: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO notifications
+                (user_id, user_scan_id, type, recipient, message, status, sent_at, external_id)
+            VALUES ($1, $2, 'sms', $3, $4, 'sent', NOW(), $5)
+            "#,
+        )
+        .bind(user_id)
+        .bind(scan_id)
+        .bind(phone)
+        .bind(message)
+        .bind(external_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Counts SMS sent since the start of the month, by everyone or one user. Messages
+    /// SNS reported as failed still count, since they were billed.
+    async fn sent_this_month(&self, user_id: Option<&Uuid>) -> Result<i64, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT COUNT(*) AS sent
+            FROM notifications
+            WHERE type = 'sms'
+              AND status IN ('sent', 'delivered', 'failed')
+              AND created_at >= date_trunc('month', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+              AND ($1::UUID IS NULL OR user_id = $1)
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.get("sent"))
+    }
+}
+
+fn usage(sent: i64, limit: i64) -> SmsBudgetUsage {
+    SmsBudgetUsage {
+        sent,
+        limit,
+        remaining: (limit - sent).max(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use postgres::test_fixtures::insert_user;
+
+    fn budget(pool: PgPool, monthly_limit: i64, user_monthly_limit: i64) -> SmsBudget {
+        SmsBudget {
+            pool,
+            monthly_limit,
+            user_monthly_limit,
+        }
+    }
+
+    async fn send(budget: &SmsBudget, user_id: &Uuid) {
+        budget
+            .record(user_id, "+15555550123", "Sites open", None, None)
+            .await
+            .unwrap();
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn users_are_stopped_exactly_at_their_budget(pool: PgPool) {
+        let user_id = insert_user(&pool, "camper@example.com").await;
+        let budget = budget(pool, 100, 3);
+
+        for _ in 0..2 {
+            send(&budget, &user_id).await;
+        }
+        assert_eq!(budget.check(&user_id).await.unwrap(), SmsAllowance::Allowed);
+
+        send(&budget, &user_id).await;
+        assert_eq!(
+            budget.check(&user_id).await.unwrap(),
+            SmsAllowance::UserBudgetExhausted
+        );
+
+        // A message that raced past the check doesn't make the budget negative
+        send(&budget, &user_id).await;
+        let usage = budget.user_usage(&user_id).await.unwrap();
+        assert_eq!((usage.sent, usage.remaining), (4, 0));
+        assert_eq!(
+            budget.check(&user_id).await.unwrap(),
+            SmsAllowance::UserBudgetExhausted
+        );
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn the_global_budget_stops_everyone(pool: PgPool) {
+        let first = insert_user(&pool, "first@example.com").await;
+        let second = insert_user(&pool, "second@example.com").await;
+        let budget = budget(pool, 2, 5);
+
+        send(&budget, &first).await;
+        assert_eq!(budget.check(&second).await.unwrap(), SmsAllowance::Allowed);
+
+        send(&budget, &first).await;
+        assert_eq!(
+            budget.check(&second).await.unwrap(),
+            SmsAllowance::GlobalBudgetExhausted
+        );
+        assert_eq!(budget.global_usage().await.unwrap().remaining, 0);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn budgets_reset_at_the_start_of_the_month(pool: PgPool) {
+        let user_id = insert_user(&pool, "camper@example.com").await;
+        let budget = budget(pool.clone(), 2, 2);
+
+        for _ in 0..2 {
+            send(&budget, &user_id).await;
+        }
+        assert_eq!(
+            budget.check(&user_id).await.unwrap(),
+            SmsAllowance::GlobalBudgetExhausted
+        );
+
+        // Move the messages to the last second of the previous month (UTC)
+        sqlx::query(
+            r#"
+            UPDATE notifications
+            SET created_at = date_trunc('month', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+                             - INTERVAL '1 second'
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(budget.check(&user_id).await.unwrap(), SmsAllowance::Allowed);
+        assert_eq!(budget.user_usage(&user_id).await.unwrap().sent, 0);
+    }
+}
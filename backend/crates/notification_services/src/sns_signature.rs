@@ -0,0 +1,354 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Public};
+use openssl::sign::Verifier;
+use openssl::x509::X509;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::delivery::SnsEnvelope;
+use crate::types::NotificationError;
+
+/// Most signing certificates kept at once; SNS rotates its certificate rarely
+const MAX_CACHED_CERTIFICATES: usize = 16;
+
+/// Checks that messages posted to the delivery webhooks come from one of the configured
+/// SNS topics and were signed by SNS, with the certificate each message names. Any AWS
+/// account's topic is signed by SNS, so the signature alone doesn't tell the topics
+/// apart. Certificates are only fetched over HTTPS from `sns.<region>.amazonaws.com`, and
+/// their keys are kept once fetched. Clones share them.
+#[derive(Clone)]
+pub struct SnsSignatureVerifier {
+    topic_arns: Arc<HashSet<String>>,
+    keys: Arc<Mutex<HashMap<String, PKey<Public>>>>,
+}
+
+impl SnsSignatureVerifier {
+    /// Creates a verifier accepting messages from the given topics, with no certificates
+    /// fetched yet
+    pub fn new<I, S>(topic_arns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            topic_arns: Arc::new(topic_arns.into_iter().map(Into::into).collect()),
+            keys: Arc::default(),
+        }
+    }
+
+    /// Checks the message's topic and signature, fetching its signing certificate the
+    /// first time it's named
+    pub async fn verify(&self, envelope: &SnsEnvelope) -> Result<(), NotificationError> {
+        self.check_topic(envelope)?;
+        let key = self.signing_key(&envelope.signing_cert_url).await?;
+        verify_signature(envelope, &key)
+    }
+
+    /// Checks that the message comes from one of the configured topics
+    fn check_topic(&self, envelope: &SnsEnvelope) -> Result<(), NotificationError> {
+        if self.topic_arns.contains(&envelope.topic_arn) {
+            Ok(())
+        } else {
+            Err(NotificationError::SnsError(format!(
+                "Message {} is from unknown topic {}",
+                envelope.message_id, envelope.topic_arn
+            )))
+        }
+    }
+
+    /// Public key of the certificate at `cert_url`
+    async fn signing_key(&self, cert_url: &str) -> Result<PKey<Public>, NotificationError> {
+        if let Some(key) = self.keys.lock().unwrap().get(cert_url) {
+            return Ok(key.clone());
+        }
+
+        let url = signing_cert_url(cert_url).ok_or_else(|| {
+            NotificationError::SnsError(format!("Untrusted SigningCertURL {}", cert_url))
+        })?;
+        let pem = reqwest::get(url)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| {
+                NotificationError::SnsError(format!("Failed to fetch {}: {}", cert_url, e))
+            })?
+            .bytes()
+            .await
+            .map_err(|e| {
+                NotificationError::SnsError(format!("Failed to fetch {}: {}", cert_url, e))
+            })?;
+        let key = X509::from_pem(&pem)
+            .and_then(|certificate| certificate.public_key())
+            .map_err(|e| {
+                NotificationError::SnsError(format!("Invalid certificate {}: {}", cert_url, e))
+            })?;
+
+        let mut keys = self.keys.lock().unwrap();
+        if keys.len() >= MAX_CACHED_CERTIFICATES {
+            keys.clear();
+        }
+        keys.insert(cert_url.to_string(), key.clone());
+
+        Ok(key)
+    }
+}
+
+/// The URL when it's an SNS signing certificate: an HTTPS `.pem` on
+/// `sns.<region>.amazonaws.com`
+fn signing_cert_url(url: &str) -> Option<reqwest::Url> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let region = url
+        .host_str()?
+        .strip_prefix("sns.")?
+        .strip_suffix(".amazonaws.com")?;
+
+    let trusted = url.scheme() == "https"
+        && url.port().is_none()
+        && !region.is_empty()
+        && region
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && url.path().ends_with(".pem");
+
+    trusted.then_some(url)
+}
+
+/// Text SNS signs for a message: the name and value of each of its type's fields on
+/// lines of their own, in byte order, leaving out fields the message doesn't have.
+/// `None` for unknown message types.
+fn string_to_sign(envelope: &SnsEnvelope) -> Option<String> {
+    let fields = match envelope.kind.as_str() {
+        "Notification" => vec![
+            ("Message", Some(&envelope.message)),
+            ("MessageId", Some(&envelope.message_id)),
+            ("Subject", envelope.subject.as_ref()),
+            ("Timestamp", Some(&envelope.timestamp)),
+            ("TopicArn", Some(&envelope.topic_arn)),
+            ("Type", Some(&envelope.kind)),
+        ],
+        "SubscriptionConfirmation" | "UnsubscribeConfirmation" => vec![
+            ("Message", Some(&envelope.message)),
+            ("MessageId", Some(&envelope.message_id)),
+            ("SubscribeURL", envelope.subscribe_url.as_ref()),
+            ("Timestamp", Some(&envelope.timestamp)),
+            ("Token", envelope.token.as_ref()),
+            ("TopicArn", Some(&envelope.topic_arn)),
+            ("Type", Some(&envelope.kind)),
+        ],
+        _ => return None,
+    };
+
+    Some(
+        fields
+            .into_iter()
+            .filter_map(|(name, value)| Some(format!("{}\n{}\n", name, value?)))
+            .collect(),
+    )
+}
+
+/// Checks the message's signature against the signing certificate's key
+fn verify_signature(envelope: &SnsEnvelope, key: &PKey<Public>) -> Result<(), NotificationError> {
+    let digest = match envelope.signature_version.as_str() {
+        "1" => MessageDigest::sha1(),
+        "2" => MessageDigest::sha256(),
+        version => {
+            return Err(NotificationError::SnsError(format!(
+                "Unsupported SignatureVersion {}",
+                version
+            )));
+        }
+    };
+    let text = string_to_sign(envelope).ok_or_else(|| {
+        NotificationError::SnsError(format!("Unknown SNS message type {}", envelope.kind))
+    })?;
+    let signature = STANDARD
+        .decode(&envelope.signature)
+        .map_err(|_| NotificationError::SnsError("Signature isn't base64".to_string()))?;
+
+    let verified = Verifier::new(digest, key)
+        .and_then(|mut verifier| {
+            verifier.update(text.as_bytes())?;
+            verifier.verify(&signature)
+        })
+        .unwrap_or(false);
+
+    if verified {
+        Ok(())
+    } else {
+        Err(NotificationError::SnsError(format!(
+            "Signature of SNS message {} doesn't match",
+            envelope.message_id
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::pkey::Private;
+    use openssl::rsa::Rsa;
+    use openssl::sign::Signer;
+
+    fn notification(subject: Option<&str>) -> SnsEnvelope {
+        SnsEnvelope {
+            kind: "Notification".to_string(),
+            topic_arn: "arn:aws:sns:us-west-2:123456789012:ses-feedback".to_string(),
+            message: r#"{"notificationType":"Delivery"}"#.to_string(),
+            message_id: "22b80b92-fdea-4c2c-8f9d-bdfb0c7bf324".to_string(),
+            subject: subject.map(str::to_string),
+            timestamp: "2025-06-01T12:00:00.000Z".to_string(),
+            subscribe_url: None,
+            token: None,
+            signature_version: "2".to_string(),
+            signature: String::new(),
+            signing_cert_url:
+                "https://sns.us-west-2.amazonaws.com/SimpleNotificationService-abc.pem".to_string(),
+        }
+    }
+
+    /// Signs the message with `key` the way SNS does for its signature version
+    fn sign(envelope: &mut SnsEnvelope, key: &PKey<Private>) {
+        let digest = match envelope.signature_version.as_str() {
+            "1" => MessageDigest::sha1(),
+            _ => MessageDigest::sha256(),
+        };
+        let mut signer = Signer::new(digest, key).unwrap();
+        signer
+            .update(string_to_sign(envelope).unwrap().as_bytes())
+            .unwrap();
+        envelope.signature = STANDARD.encode(signer.sign_to_vec().unwrap());
+    }
+
+    fn key_pair() -> (PKey<Private>, PKey<Public>) {
+        let private = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let public = PKey::public_key_from_pem(&private.public_key_to_pem().unwrap()).unwrap();
+        (private, public)
+    }
+
+    #[test]
+    fn signed_text_lists_the_fields_of_the_message_type() {
+        assert_eq!(
+            string_to_sign(&notification(None)).unwrap(),
+            "Message\n{\"notificationType\":\"Delivery\"}\n\
+             MessageId\n22b80b92-fdea-4c2c-8f9d-bdfb0c7bf324\n\
+             Timestamp\n2025-06-01T12:00:00.000Z\n\
+             TopicArn\narn:aws:sns:us-west-2:123456789012:ses-feedback\n\
+             Type\nNotification\n"
+        );
+        assert!(
+            string_to_sign(&notification(Some("Bounce")))
+                .unwrap()
+                .contains(
+                    "MessageId\n22b80b92-fdea-4c2c-8f9d-bdfb0c7bf324\nSubject\nBounce\nTimestamp\n"
+                )
+        );
+
+        let confirmation = SnsEnvelope {
+            kind: "SubscriptionConfirmation".to_string(),
+            subscribe_url: Some("https://sns.us-west-2.amazonaws.com/?Action=Confirm".to_string()),
+            token: Some("2336412f37".to_string()),
+            ..notification(Some("ignored"))
+        };
+        let text = string_to_sign(&confirmation).unwrap();
+        assert!(text.contains(
+            "SubscribeURL\nhttps://sns.us-west-2.amazonaws.com/?Action=Confirm\nTimestamp\n"
+        ));
+        assert!(text.contains("Token\n2336412f37\nTopicArn\n"));
+        assert!(!text.contains("Subject"));
+
+        let unknown = SnsEnvelope {
+            kind: "Other".to_string(),
+            ..notification(None)
+        };
+        assert_eq!(string_to_sign(&unknown), None);
+    }
+
+    #[test]
+    fn signatures_of_both_versions_verify() {
+        let (private, public) = key_pair();
+
+        for version in ["1", "2"] {
+            let mut envelope = notification(Some("Bounce"));
+            envelope.signature_version = version.to_string();
+            sign(&mut envelope, &private);
+
+            assert!(verify_signature(&envelope, &public).is_ok());
+        }
+    }
+
+    #[test]
+    fn tampered_or_foreign_messages_are_rejected() {
+        let (private, public) = key_pair();
+        let mut envelope = notification(None);
+        sign(&mut envelope, &private);
+
+        let tampered = SnsEnvelope {
+            message: r#"{"notificationType":"Complaint"}"#.to_string(),
+            ..notification(None)
+        };
+        assert!(
+            verify_signature(
+                &SnsEnvelope {
+                    signature: envelope.signature.clone(),
+                    ..tampered
+                },
+                &public
+            )
+            .is_err()
+        );
+
+        let (_, other_public) = key_pair();
+        assert!(verify_signature(&envelope, &other_public).is_err());
+
+        envelope.signature_version = "3".to_string();
+        assert!(verify_signature(&envelope, &public).is_err());
+    }
+
+    #[test]
+    fn only_configured_topics_are_accepted() {
+        let verifier =
+            SnsSignatureVerifier::new(["arn:aws:sns:us-west-2:123456789012:ses-feedback"]);
+        assert!(verifier.check_topic(&notification(None)).is_ok());
+
+        // Validly signed by SNS, but for a topic in someone else's account
+        let foreign = SnsEnvelope {
+            kind: "SubscriptionConfirmation".to_string(),
+            topic_arn: "arn:aws:sns:us-west-2:999999999999:ses-feedback".to_string(),
+            subscribe_url: Some("https://sns.us-west-2.amazonaws.com/?Action=Confirm".to_string()),
+            token: Some("2336412f37".to_string()),
+            ..notification(None)
+        };
+        assert!(verifier.check_topic(&foreign).is_err());
+
+        let none: [&str; 0] = [];
+        assert!(
+            SnsSignatureVerifier::new(none)
+                .check_topic(&notification(None))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn certificates_are_only_fetched_from_sns() {
+        for trusted in [
+            "https://sns.us-east-1.amazonaws.com/SimpleNotificationService-9c6465fa7f48f5cacd23014631ec1136.pem",
+            "https://sns.eu-central-1.amazonaws.com/SimpleNotificationService-abc.pem",
+        ] {
+            assert!(signing_cert_url(trusted).is_some(), "{}", trusted);
+        }
+
+        for untrusted in [
+            "http://sns.us-east-1.amazonaws.com/SimpleNotificationService-abc.pem",
+            "https://sns.us-east-1.amazonaws.com.evil.example/SimpleNotificationService-abc.pem",
+            "https://evil.example/sns.us-east-1.amazonaws.com/cert.pem",
+            "https://s3.us-east-1.amazonaws.com/SimpleNotificationService-abc.pem",
+            "https://sns.amazonaws.com/SimpleNotificationService-abc.pem",
+            "https://sns.us-east-1.amazonaws.com:8443/SimpleNotificationService-abc.pem",
+            "https://sns.us-east-1.amazonaws.com/redirect?to=evil",
+            "not a url",
+        ] {
+            assert!(signing_cert_url(untrusted).is_none(), "{}", untrusted);
+        }
+    }
+}
@@ -0,0 +1,948 @@
+use i18n::Locale;
+use std::path::Path;
+use std::sync::Arc;
+use tera::{Context, Tera};
+
+use crate::types::NotificationError;
+
+/// Templates compiled into the binary, by name. A message `<name>` has a `<name>.subject`,
+/// `<name>.html`, and `<name>.txt` template when it's an email and a `<name>.sms`
+/// template when it's a text message. HTML templates extend `layout.html`; alert emails
+/// end their plain text with `unsubscribe.txt`. Translations live under the locale's
+/// code (`es/<name>.html`) and extend `es/layout.html`; a message without a translation
+/// is sent in English.
+const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("layout.html", include_str!("../templates/layout.html")),
+    ("macros.html", include_str!("../templates/macros.html")),
+    (
+        "unsubscribe.txt",
+        include_str!("../templates/unsubscribe.txt"),
+    ),
+    (
+        "availability_alert.subject",
+        include_str!("../templates/availability_alert.subject"),
+    ),
+    (
+        "availability_alert.html",
+        include_str!("../templates/availability_alert.html"),
+    ),
+    (
+        "availability_alert.txt",
+        include_str!("../templates/availability_alert.txt"),
+    ),
+    (
+        "availability_alert.sms",
+        include_str!("../templates/availability_alert.sms"),
+    ),
+    (
+        "email_change_confirmation.subject",
+        include_str!("../templates/email_change_confirmation.subject"),
+    ),
+    (
+        "email_change_confirmation.html",
+        include_str!("../templates/email_change_confirmation.html"),
+    ),
+    (
+        "email_change_confirmation.txt",
+        include_str!("../templates/email_change_confirmation.txt"),
+    ),
+    (
+        "email_changed_notice.subject",
+        include_str!("../templates/email_changed_notice.subject"),
+    ),
+    (
+        "email_changed_notice.html",
+        include_str!("../templates/email_changed_notice.html"),
+    ),
+    (
+        "email_changed_notice.txt",
+        include_str!("../templates/email_changed_notice.txt"),
+    ),
+    (
+        "email_verification.subject",
+        include_str!("../templates/email_verification.subject"),
+    ),
+    (
+        "email_verification.html",
+        include_str!("../templates/email_verification.html"),
+    ),
+    (
+        "email_verification.txt",
+        include_str!("../templates/email_verification.txt"),
+    ),
+    (
+        "organization_invitation.subject",
+        include_str!("../templates/organization_invitation.subject"),
+    ),
+    (
+        "organization_invitation.html",
+        include_str!("../templates/organization_invitation.html"),
+    ),
+    (
+        "organization_invitation.txt",
+        include_str!("../templates/organization_invitation.txt"),
+    ),
+    (
+        "permit_available.subject",
+        include_str!("../templates/permit_available.subject"),
+    ),
+    (
+        "permit_available.html",
+        include_str!("../templates/permit_available.html"),
+    ),
+    (
+        "permit_available.txt",
+        include_str!("../templates/permit_available.txt"),
+    ),
+    (
+        "permit_available.sms",
+        include_str!("../templates/permit_available.sms"),
+    ),
+    (
+        "scan_blocked.subject",
+        include_str!("../templates/scan_blocked.subject"),
+    ),
+    (
+        "scan_blocked.html",
+        include_str!("../templates/scan_blocked.html"),
+    ),
+    (
+        "scan_blocked.txt",
+        include_str!("../templates/scan_blocked.txt"),
+    ),
+    (
+        "scan_expiring.subject",
+        include_str!("../templates/scan_expiring.subject"),
+    ),
+    (
+        "scan_expiring.html",
+        include_str!("../templates/scan_expiring.html"),
+    ),
+    (
+        "scan_expiring.txt",
+        include_str!("../templates/scan_expiring.txt"),
+    ),
+    (
+        "scan_recipient_invitation.subject",
+        include_str!("../templates/scan_recipient_invitation.subject"),
+    ),
+    (
+        "scan_recipient_invitation.html",
+        include_str!("../templates/scan_recipient_invitation.html"),
+    ),
+    (
+        "scan_recipient_invitation.txt",
+        include_str!("../templates/scan_recipient_invitation.txt"),
+    ),
+    (
+        "scan_recipient_invitation.sms",
+        include_str!("../templates/scan_recipient_invitation.sms"),
+    ),
+    (
+        "sms_verification.sms",
+        include_str!("../templates/sms_verification.sms"),
+    ),
+    (
+        "verification_reminder.subject",
+        include_str!("../templates/verification_reminder.subject"),
+    ),
+    (
+        "verification_reminder.html",
+        include_str!("../templates/verification_reminder.html"),
+    ),
+    (
+        "verification_reminder.txt",
+        include_str!("../templates/verification_reminder.txt"),
+    ),
+    (
+        "es/availability_alert.html",
+        include_str!("../templates/es/availability_alert.html"),
+    ),
+    (
+        "es/availability_alert.sms",
+        include_str!("../templates/es/availability_alert.sms"),
+    ),
+    (
+        "es/availability_alert.subject",
+        include_str!("../templates/es/availability_alert.subject"),
+    ),
+    (
+        "es/availability_alert.txt",
+        include_str!("../templates/es/availability_alert.txt"),
+    ),
+    (
+        "es/email_change_confirmation.html",
+        include_str!("../templates/es/email_change_confirmation.html"),
+    ),
+    (
+        "es/email_change_confirmation.subject",
+        include_str!("../templates/es/email_change_confirmation.subject"),
+    ),
+    (
+        "es/email_change_confirmation.txt",
+        include_str!("../templates/es/email_change_confirmation.txt"),
+    ),
+    (
+        "es/email_changed_notice.html",
+        include_str!("../templates/es/email_changed_notice.html"),
+    ),
+    (
+        "es/email_changed_notice.subject",
+        include_str!("../templates/es/email_changed_notice.subject"),
+    ),
+    (
+        "es/email_changed_notice.txt",
+        include_str!("../templates/es/email_changed_notice.txt"),
+    ),
+    (
+        "es/email_verification.html",
+        include_str!("../templates/es/email_verification.html"),
+    ),
+    (
+        "es/email_verification.subject",
+        include_str!("../templates/es/email_verification.subject"),
+    ),
+    (
+        "es/email_verification.txt",
+        include_str!("../templates/es/email_verification.txt"),
+    ),
+    (
+        "es/layout.html",
+        include_str!("../templates/es/layout.html"),
+    ),
+    (
+        "es/organization_invitation.html",
+        include_str!("../templates/es/organization_invitation.html"),
+    ),
+    (
+        "es/organization_invitation.subject",
+        include_str!("../templates/es/organization_invitation.subject"),
+    ),
+    (
+        "es/organization_invitation.txt",
+        include_str!("../templates/es/organization_invitation.txt"),
+    ),
+    (
+        "es/permit_available.html",
+        include_str!("../templates/es/permit_available.html"),
+    ),
+    (
+        "es/permit_available.sms",
+        include_str!("../templates/es/permit_available.sms"),
+    ),
+    (
+        "es/permit_available.subject",
+        include_str!("../templates/es/permit_available.subject"),
+    ),
+    (
+        "es/permit_available.txt",
+        include_str!("../templates/es/permit_available.txt"),
+    ),
+    (
+        "es/scan_blocked.html",
+        include_str!("../templates/es/scan_blocked.html"),
+    ),
+    (
+        "es/scan_blocked.subject",
+        include_str!("../templates/es/scan_blocked.subject"),
+    ),
+    (
+        "es/scan_blocked.txt",
+        include_str!("../templates/es/scan_blocked.txt"),
+    ),
+    (
+        "es/scan_expiring.html",
+        include_str!("../templates/es/scan_expiring.html"),
+    ),
+    (
+        "es/scan_expiring.subject",
+        include_str!("../templates/es/scan_expiring.subject"),
+    ),
+    (
+        "es/scan_expiring.txt",
+        include_str!("../templates/es/scan_expiring.txt"),
+    ),
+    (
+        "es/scan_recipient_invitation.html",
+        include_str!("../templates/es/scan_recipient_invitation.html"),
+    ),
+    (
+        "es/scan_recipient_invitation.sms",
+        include_str!("../templates/es/scan_recipient_invitation.sms"),
+    ),
+    (
+        "es/scan_recipient_invitation.subject",
+        include_str!("../templates/es/scan_recipient_invitation.subject"),
+    ),
+    (
+        "es/scan_recipient_invitation.txt",
+        include_str!("../templates/es/scan_recipient_invitation.txt"),
+    ),
+    (
+        "es/sms_verification.sms",
+        include_str!("../templates/es/sms_verification.sms"),
+    ),
+    (
+        "es/unsubscribe.txt",
+        include_str!("../templates/es/unsubscribe.txt"),
+    ),
+    (
+        "es/verification_reminder.html",
+        include_str!("../templates/es/verification_reminder.html"),
+    ),
+    (
+        "es/verification_reminder.subject",
+        include_str!("../templates/es/verification_reminder.subject"),
+    ),
+    (
+        "es/verification_reminder.txt",
+        include_str!("../templates/es/verification_reminder.txt"),
+    ),
+];
+
+/// Subject and bodies of a rendered email
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    /// Subject line
+    pub subject: String,
+    /// HTML body
+    pub html: String,
+    /// Plain text body
+    pub text: String,
+}
+
+/// Email and SMS templates: the built-in ones, with any file of the same name in the
+/// deployment's template directory used instead.
+#[derive(Debug, Clone)]
+pub struct NotificationTemplates {
+    tera: Arc<Tera>,
+}
+
+impl NotificationTemplates {
+    /// Loads the built-in templates only.
+    pub fn builtin() -> Result<Self, NotificationError> {
+        Self::load(None)
+    }
+
+    /// Loads the built-in templates, overridden by the files in `overrides_dir` when
+    /// given. Every file in the directory is loaded under its file name, so it can replace
+    /// a built-in template (e.g. `layout.html`) or add a partial the overrides include.
+    /// Files in a locale's subdirectory (e.g. `es/layout.html`) replace that translation.
+    pub fn load(overrides_dir: Option<&Path>) -> Result<Self, NotificationError> {
+        let mut tera = Tera::default();
+        tera.set_escape_fn(escape_html);
+        tera.add_raw_templates(BUILTIN_TEMPLATES.iter().copied())
+            .map_err(template_error)?;
+
+        if let Some(dir) = overrides_dir {
+            let mut files = override_files(dir, None)?;
+            for locale in Locale::ALL {
+                let locale_dir = dir.join(locale.code());
+                if locale_dir.is_dir() {
+                    files.extend(override_files(&locale_dir, Some(locale))?);
+                }
+            }
+
+            if !files.is_empty() {
+                log::info!(
+                    "📝 Loaded {} notification template overrides from {}",
+                    files.len(),
+                    dir.display()
+                );
+            }
+            tera.add_template_files(files).map_err(template_error)?;
+        }
+
+        Ok(Self {
+            tera: Arc::new(tera),
+        })
+    }
+
+    /// Renders the subject, HTML, and plain text templates of the email `name` in
+    /// `locale`.
+    pub fn render_email(
+        &self,
+        name: &str,
+        locale: Locale,
+        context: &Context,
+    ) -> Result<RenderedEmail, NotificationError> {
+        Ok(RenderedEmail {
+            subject: self.render(&self.localized(name, "subject", locale), context)?,
+            html: self.render(&self.localized(name, "html", locale), context)?,
+            text: self.render(&self.localized(name, "txt", locale), context)?,
+        })
+    }
+
+    /// Renders the SMS template of the message `name` in `locale`.
+    pub fn render_sms(
+        &self,
+        name: &str,
+        locale: Locale,
+        context: &Context,
+    ) -> Result<String, NotificationError> {
+        self.render(&self.localized(name, "sms", locale), context)
+    }
+
+    /// Name of the `locale` translation of a template, or of the English template when
+    /// it isn't translated
+    fn localized(&self, name: &str, extension: &str, locale: Locale) -> String {
+        let template = format!("{}.{}", name, extension);
+        if locale == Locale::default() {
+            return template;
+        }
+
+        let translated = format!("{}/{}", locale.code(), template);
+        if self.tera.get_template(&translated).is_ok() {
+            translated
+        } else {
+            template
+        }
+    }
+
+    /// Renders one template, without the trailing newline of its file
+    fn render(&self, template: &str, context: &Context) -> Result<String, NotificationError> {
+        let rendered = self
+            .tera
+            .render(template, context)
+            .map_err(template_error)?;
+
+        Ok(rendered.trim_end().to_string())
+    }
+}
+
+/// Files of an override directory, named after their file name (under the locale's code
+/// for a translation)
+fn override_files(
+    dir: &Path,
+    locale: Option<Locale>,
+) -> Result<Vec<(std::path::PathBuf, Option<String>)>, NotificationError> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| NotificationError::Template(format!("Can't read {}: {}", dir.display(), e)))?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| NotificationError::Template(e.to_string()))?
+            .path();
+        if !path.is_file() {
+            continue;
+        }
+        if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+            let name = match locale {
+                Some(locale) => format!("{}/{}", locale.code(), name),
+                None => name.to_string(),
+            };
+            files.push((path.clone(), Some(name)));
+        }
+    }
+
+    Ok(files)
+}
+
+/// Escapes text for HTML templates. Unlike Tera's default, `/` is left alone so links
+/// stay readable in the email source.
+pub fn escape_html(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Describes a Tera error with its causes (the top-level message rarely says what's wrong)
+fn template_error(error: tera::Error) -> NotificationError {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(&error);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+
+    NotificationError::Template(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(values: &[(&str, &str)]) -> Context {
+        let mut context = Context::new();
+        for (key, value) in values {
+            context.insert(*key, value);
+        }
+        context
+    }
+
+    fn alert_context(site_count: usize, more_sites: usize, notice: Option<&str>) -> Context {
+        let mut context = Context::new();
+        context.insert("campground_name", "Upper Pines");
+        context.insert("stay", "Aug 14 – Aug 16");
+        context.insert("site_count", &site_count);
+        context.insert("sites", &["A01 (Loop A)", "A02 (Loop A)"]);
+        context.insert("more_sites", &more_sites);
+        context.insert("nights", &[] as &[()]);
+        context.insert("calendar", &[] as &[()]);
+        context.insert("sms_sites", &["A01", "A02"]);
+        context.insert("sms_more_sites", &more_sites);
+        context.insert("provider_name", "Recreation.gov");
+        context.insert(
+            "booking_url",
+            "https://www.recreation.gov/camping/campgrounds/232447",
+        );
+        context.insert("site_links", &[] as &[()]);
+        context.insert("scan_url", "http://localhost:8080/?scan=1");
+        context.insert("notice", &notice);
+        context.insert(
+            "unsubscribe_url",
+            "http://localhost:8080/unsubscribe?token=email",
+        );
+        context.insert(
+            "scan_unsubscribe_url",
+            "http://localhost:8080/unsubscribe?token=scan",
+        );
+        context
+    }
+
+    #[test]
+    fn builtin_templates_load() {
+        assert!(NotificationTemplates::builtin().is_ok());
+    }
+
+    #[test]
+    fn renders_email_with_layout() {
+        let templates = NotificationTemplates::builtin().unwrap();
+        let email = templates
+            .render_email(
+                "email_verification",
+                Locale::English,
+                &context(&[
+                    ("name", "Ana"),
+                    (
+                        "verification_url",
+                        "http://localhost:8080/verify-email?token=abc",
+                    ),
+                ]),
+            )
+            .unwrap();
+
+        assert_eq!(email.subject, "Verify your CampTracker email");
+        assert!(email.html.contains("Hi Ana!"));
+        assert!(email.html.contains("🏕️ CampTracker</h1>"));
+        assert!(
+            email
+                .html
+                .contains(r#"href="http://localhost:8080/verify-email?token=abc""#)
+        );
+        assert!(email.html.contains("Verify Email Address</a>"));
+        assert!(email.text.starts_with("Hi Ana!\n\nWelcome to CampTracker!"));
+        assert!(email.text.ends_with("© 2025 CampTracker"));
+        assert!(!email.html.contains("Unsubscribe"));
+    }
+
+    #[test]
+    fn escapes_html_but_not_text() {
+        let templates = NotificationTemplates::builtin().unwrap();
+        let email = templates
+            .render_email(
+                "email_changed_notice",
+                Locale::English,
+                &context(&[("name", "<b>Ana</b>"), ("new_email", "ana@example.com")]),
+            )
+            .unwrap();
+
+        assert!(email.html.contains("Hi &lt;b&gt;Ana&lt;/b&gt;!"));
+        assert!(email.text.starts_with("Hi <b>Ana</b>!"));
+    }
+
+    #[test]
+    fn renders_optional_sections() {
+        let templates = NotificationTemplates::builtin().unwrap();
+        let mut values = Context::new();
+        values.insert("name", "Ana");
+        values.insert("verification_url", &None::<String>);
+        values.insert("phone_unverified", &true);
+        values.insert("profile_url", "http://localhost:8080/?profile");
+
+        let email = templates
+            .render_email("verification_reminder", Locale::English, &values)
+            .unwrap();
+
+        assert!(!email.html.contains("Verify Email Address"));
+        assert!(email.html.contains("Verify it from your profile"));
+        assert_eq!(
+            email.text,
+            "Hi Ana!\n\nWe only send availability alerts to verified contact details, so your scans can't reach you yet.\n\nText alerts are on, but your phone number isn't verified yet. Verify it from your profile:\nhttp://localhost:8080/?profile\n\n© 2025 CampTracker"
+        );
+    }
+
+    #[test]
+    fn renders_availability_alert_summary() {
+        let templates = NotificationTemplates::builtin().unwrap();
+
+        let email = templates
+            .render_email(
+                "availability_alert",
+                Locale::English,
+                &alert_context(12, 10, None),
+            )
+            .unwrap();
+        assert_eq!(email.subject, "🏕️ 12 sites open at Upper Pines!");
+        assert!(email.html.contains("<li>A02 (Loop A)</li>"));
+        assert!(email.html.contains("and 10 more"));
+        assert!(email.html.contains("Book on Recreation.gov</a>"));
+        assert!(email.html.contains(
+            r#"<a href="http://localhost:8080/unsubscribe?token=scan" style="color: #6b7280;">Stop alerts for this scan</a>"#
+        ));
+        assert!(email.html.contains(
+            r#"<a href="http://localhost:8080/unsubscribe?token=email" style="color: #6b7280;">Unsubscribe from email alerts</a>"#
+        ));
+        assert!(
+            email
+                .text
+                .contains("\n\nA01 (Loop A), A02 (Loop A) and 10 more\n\n")
+        );
+
+        let single = templates
+            .render_email(
+                "availability_alert",
+                Locale::English,
+                &alert_context(1, 0, Some("Sent by email.")),
+            )
+            .unwrap();
+        assert_eq!(single.subject, "🏕️ 1 site open at Upper Pines!");
+        assert!(!single.html.contains("more</p>"));
+        assert!(
+            single
+                .text
+                .contains("\n\nSent by email.\n\nStop alerts for this scan:\nhttp://localhost:8080/unsubscribe?token=scan\n\nStop all email alerts:\nhttp://localhost:8080/unsubscribe?token=email\n\n© 2025 CampTracker")
+        );
+    }
+
+    #[test]
+    fn renders_availability_calendar() {
+        use crate::{AlertSite, AvailabilityAlert};
+        use chrono::NaiveDate;
+
+        let date = |day| NaiveDate::from_ymd_opt(2025, 8, day).unwrap();
+        let alert = |check_out| {
+            AvailabilityAlert::new(
+                "Upper Pines",
+                date(14),
+                check_out,
+                "Recreation.gov",
+                "https://www.recreation.gov/camping/campgrounds/232447",
+                [
+                    AlertSite {
+                        loop_name: Some("Loop A".to_string()),
+                        site: "A01".to_string(),
+                        open_nights: vec![date(14), date(15)],
+                        nightly_price_cents: Some(4_250),
+                        booking_url: Some(
+                            "https://www.recreation.gov/camping/campsites/70001?startDate=2025-08-14&endDate=2025-08-16"
+                                .to_string(),
+                        ),
+                    },
+                    AlertSite {
+                        loop_name: None,
+                        site: "B07".to_string(),
+                        open_nights: vec![date(15)],
+                        nightly_price_cents: None,
+                        booking_url: None,
+                    },
+                ],
+            )
+        };
+        let render = |alert: AvailabilityAlert| {
+            let mut context = alert.context(Locale::English, date(1));
+            context.insert("scan_url", "http://localhost:8080/?scan=1");
+            context.insert("notice", &None::<String>);
+            context.insert("unsubscribe_url", "http://localhost:8080/unsubscribe");
+            context.insert("scan_unsubscribe_url", &None::<String>);
+            NotificationTemplates::builtin()
+                .unwrap()
+                .render_email("availability_alert", Locale::English, &context)
+                .unwrap()
+        };
+
+        let email = render(alert(date(16)));
+        assert!(
+            email
+                .html
+                .contains("Thu<br><strong style=\"color: #374151;\">14</strong><br>Aug")
+        );
+        assert_eq!(email.html.matches(">✓</td>").count(), 3);
+        assert_eq!(email.html.matches(">–</td>").count(), 1);
+        assert!(email.html.contains(">B07</td>"));
+        assert!(!email.html.contains("<li>"));
+        assert!(
+            email
+                .text
+                .contains("\n\nB07, A01 (Loop A) – $42.50/night\n\n")
+        );
+
+        // A01 has a quick-book link, which leads the email and the SMS
+        assert!(email.html.contains(">Book A01 now</a>"));
+        assert!(email.html.contains(">See every site on Recreation.gov</a>"));
+        assert!(email.text.contains("\n\nBook A01 now:\nhttps://www.recreation.gov/camping/campsites/70001?startDate=2025-08-14&endDate=2025-08-16\n\nEvery site on Recreation.gov:\nhttps://www.recreation.gov/camping/campgrounds/232447\n\n"));
+        assert_eq!(
+            NotificationTemplates::builtin()
+                .unwrap()
+                .render_sms(
+                    "availability_alert",
+                    Locale::English,
+                    &alert(date(16)).context(Locale::English, date(1))
+                )
+                .unwrap(),
+            "🏕️ CampTracker: 2 sites open at Upper Pines for Aug 14 – Aug 16: B07, A01 ($42.50). Book A01 now: https://www.recreation.gov/camping/campsites/70001?startDate=2025-08-14&endDate=2025-08-16"
+        );
+
+        let long_stay = render(alert(date(30)));
+        assert!(!long_stay.html.contains("✓"));
+        assert!(long_stay.html.contains("<li>B07</li>"));
+    }
+
+    #[test]
+    fn renders_why_sites_opened_up() {
+        use crate::{AlertSite, AvailabilityAlert};
+        use chrono::NaiveDate;
+
+        let date = |day| NaiveDate::from_ymd_opt(2025, 8, day).unwrap();
+        let alert = AvailabilityAlert::new(
+            "Upper Pines",
+            date(14),
+            date(16),
+            "Recreation.gov",
+            "https://www.recreation.gov/camping/campgrounds/232447",
+            [AlertSite {
+                loop_name: None,
+                site: "A01".to_string(),
+                open_nights: vec![date(14), date(15)],
+                nightly_price_cents: None,
+                booking_url: None,
+            }],
+        )
+        .with_reason("alert-reason-cancellation");
+        let templates = NotificationTemplates::builtin().unwrap();
+
+        let mut context = alert.context(Locale::English, date(1));
+        context.insert("scan_url", &None::<String>);
+        context.insert("notice", &None::<String>);
+        context.insert("unsubscribe_url", "http://localhost:8080/unsubscribe");
+        context.insert("scan_unsubscribe_url", &None::<String>);
+        let email = templates
+            .render_email("availability_alert", Locale::English, &context)
+            .unwrap();
+        assert!(
+            email
+                .html
+                .contains(">Likely a cancellation, so it may go fast.</p>")
+        );
+        assert!(
+            email
+                .text
+                .contains("\n\nA01\n\nLikely a cancellation, so it may go fast.\n\n")
+        );
+
+        assert_eq!(
+            templates
+                .render_sms("availability_alert", Locale::English, &context)
+                .unwrap(),
+            "🏕️ CampTracker: 1 site open at Upper Pines for Aug 14 – Aug 16: A01. Likely a cancellation, so it may go fast. Book: https://www.recreation.gov/camping/campgrounds/232447"
+        );
+        assert_eq!(
+            templates
+                .render_sms(
+                    "availability_alert",
+                    Locale::Spanish,
+                    &alert
+                        .with_reason("alert-reason-release")
+                        .context(Locale::Spanish, date(1))
+                )
+                .unwrap(),
+            "🏕️ CampTracker: 1 sitio libre en Upper Pines para 14 ago – 16 ago: A01. Sitios recién liberados. Reserva: https://www.recreation.gov/camping/campgrounds/232447"
+        );
+    }
+
+    #[test]
+    fn renders_campground_location() {
+        use crate::{AlertSite, AvailabilityAlert};
+        use chrono::NaiveDate;
+
+        let date = |day| NaiveDate::from_ymd_opt(2025, 8, day).unwrap();
+        let alert = AvailabilityAlert::new(
+            "Upper Pines",
+            date(14),
+            date(16),
+            "Recreation.gov",
+            "https://www.recreation.gov/camping/campgrounds/232447",
+            [AlertSite {
+                loop_name: None,
+                site: "A01".to_string(),
+                open_nights: vec![date(14), date(15)],
+                nightly_price_cents: None,
+                booking_url: None,
+            }],
+        )
+        .with_location("3 mi SE of Yosemite Valley, CA");
+
+        let mut context = alert.context(Locale::English, date(1));
+        context.insert("scan_url", &None::<String>);
+        context.insert("notice", &None::<String>);
+        context.insert("unsubscribe_url", "http://localhost:8080/unsubscribe");
+        context.insert("scan_unsubscribe_url", &None::<String>);
+        let email = NotificationTemplates::builtin()
+            .unwrap()
+            .render_email("availability_alert", Locale::English, &context)
+            .unwrap();
+
+        assert!(email.html.contains(">3 mi SE of Yosemite Valley, CA</p>"));
+        assert!(email.text.starts_with(
+            "Sites you're watching at Upper Pines (3 mi SE of Yosemite Valley, CA) are available"
+        ));
+    }
+
+    #[test]
+    fn renders_translations() {
+        let templates = NotificationTemplates::builtin().unwrap();
+        let alert = crate::AvailabilityAlert::new(
+            "Upper Pines",
+            chrono::NaiveDate::from_ymd_opt(2025, 8, 14).unwrap(),
+            chrono::NaiveDate::from_ymd_opt(2025, 8, 16).unwrap(),
+            "Recreation.gov",
+            "https://www.recreation.gov/camping/campgrounds/232447",
+            [crate::AlertSite {
+                loop_name: None,
+                site: "A01".to_string(),
+                open_nights: Vec::new(),
+                nightly_price_cents: Some(3_500),
+                booking_url: None,
+            }],
+        );
+
+        assert_eq!(
+            templates
+                .render_sms(
+                    "availability_alert",
+                    Locale::Spanish,
+                    &alert.context(
+                        Locale::Spanish,
+                        chrono::NaiveDate::from_ymd_opt(2025, 8, 13).unwrap()
+                    )
+                )
+                .unwrap(),
+            "🏕️ CampTracker: 1 sitio libre en Upper Pines para mañana por la noche, 14 ago – 16 ago: A01 ($35). Reserva: https://www.recreation.gov/camping/campgrounds/232447"
+        );
+
+        let email = templates
+            .render_email(
+                "email_verification",
+                Locale::Spanish,
+                &context(&[
+                    ("name", "Ana"),
+                    ("verification_url", "http://localhost:8080"),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(email.subject, "Verifica tu email de CampTracker");
+        assert!(email.html.contains("¡Hola, Ana!"));
+        assert!(email.html.contains("No te pierdas ningún sitio"));
+    }
+
+    #[test]
+    fn renders_sms() {
+        let templates = NotificationTemplates::builtin().unwrap();
+
+        assert_eq!(
+            templates
+                .render_sms(
+                    "availability_alert",
+                    Locale::English,
+                    &alert_context(12, 10, None)
+                )
+                .unwrap(),
+            "🏕️ CampTracker: 12 sites open at Upper Pines for Aug 14 – Aug 16: A01, A02 +10 more. Book: https://www.recreation.gov/camping/campgrounds/232447"
+        );
+        assert_eq!(
+            templates
+                .render_sms(
+                    "sms_verification",
+                    Locale::English,
+                    &context(&[("code", "123456")])
+                )
+                .unwrap(),
+            "Your CampTracker verification code is: 123456\n\nThis code expires in 10 minutes.\n\nIf you didn't request this, ignore this message."
+        );
+    }
+
+    #[test]
+    fn overrides_replace_builtin_templates() {
+        let dir = std::env::temp_dir().join(format!(
+            "notification-templates-{}",
+            uuid::Uuid::new_v4().simple()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("layout.html"),
+            "<main>{% block content %}{% endblock content %}</main>",
+        )
+        .unwrap();
+        std::fs::write(dir.join("sms_verification.sms"), "Code: {{ code }}\n").unwrap();
+
+        let templates = NotificationTemplates::load(Some(&dir)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let email = templates
+            .render_email(
+                "email_changed_notice",
+                Locale::English,
+                &context(&[("name", "Ana"), ("new_email", "ana@example.com")]),
+            )
+            .unwrap();
+        assert!(email.html.starts_with("<main>"));
+        assert!(!email.html.contains("Never miss a campsite"));
+        assert_eq!(email.subject, "Your CampTracker email was changed");
+        assert_eq!(
+            templates
+                .render_sms(
+                    "sms_verification",
+                    Locale::English,
+                    &context(&[("code", "42")])
+                )
+                .unwrap(),
+            "Code: 42"
+        );
+    }
+
+    #[test]
+    fn invalid_overrides_are_reported() {
+        let dir = std::env::temp_dir().join(format!(
+            "notification-templates-{}",
+            uuid::Uuid::new_v4().simple()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("scan_expiring.html"), "{% if %}").unwrap();
+
+        let result = NotificationTemplates::load(Some(&dir));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(result, Err(NotificationError::Template(_))));
+        assert!(NotificationTemplates::load(Some(Path::new("/nonexistent/templates"))).is_err());
+    }
+
+    #[test]
+    fn missing_variables_are_errors() {
+        let templates = NotificationTemplates::builtin().unwrap();
+
+        assert!(matches!(
+            templates.render_email("scan_expiring", Locale::English, &Context::new()),
+            Err(NotificationError::Template(_))
+        ));
+    }
+}
@@ -1,9 +1,4 @@
-use chrono::{DateTime, Utc};
-use std::{
-    collections::HashMap,
-    sync::{Arc, Mutex},
-};
-
+use crate::sms_budget::SmsAllowance;
 /// Types for notifications (email and SMS).
 #[derive(Debug, thiserror::Error)]
 pub enum NotificationError {
@@ -22,17 +17,116 @@ pub enum NotificationError {
     /// Invalid email format.
     #[error("Invalid email format")]
     InvalidEmail,
+
+    /// No pending verification code was found.
+    #[error("Verification code not found")]
+    VerificationNotFound,
+
+    /// The verification code has expired.
+    #[error("Verification code has expired")]
+    VerificationExpired,
+
+    /// Too many attempts were made against the verification code.
+    #[error("Too many verification attempts")]
+    TooManyAttempts,
+
+    /// Too many verification messages were requested recently.
+    #[error("Too many verification messages requested; try again in {retry_after_secs} seconds")]
+    TooManySends {
+        /// Seconds until another message can be requested
+        retry_after_secs: u64,
+    },
+
+    /// The monthly SMS budget doesn't allow another message.
+    #[error("Monthly SMS limit reached")]
+    SmsBudgetExhausted(SmsAllowance),
+
+    /// An ops alert webhook couldn't be reached or refused the alert.
+    #[error("Webhook error: {0}")]
+    Webhook(String),
+
+    /// A notification template couldn't be loaded or rendered.
+    #[error("Template error: {0}")]
+    Template(String),
+
+    /// Database errors while storing or checking verification tokens.
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    /// Redis errors while storing or checking verification tokens.
+    #[cfg(feature = "redis")]
+    #[error("Redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+}
+
+/// Channel a verification token was issued for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationChannel {
+    /// Email verification link.
+    Email,
+    /// SMS verification code.
+    Sms,
 }
 
-/// Represents a verification code for user actions like phone number or email verification.
-#[derive(Clone)]
-pub struct VerificationCode {
-    /// The verification code itself, a 6-digit number.
-    pub code: String,
-    /// The expiration time of the verification code.
-    pub expires_at: DateTime<Utc>,
-    /// The number of attempts made to verify this code.
-    pub attempts: u32,
+impl VerificationChannel {
+    /// Returns the value stored in the `channel` column of `verification_tokens`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationChannel::Email => "email",
+            VerificationChannel::Sms => "sms",
+        }
+    }
+}
+
+/// Result of a pre-flight deliverability check for one notification channel.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ChannelDeliverability {
+    /// `Some(true)` if messages can be delivered, `Some(false)` if they can't,
+    /// `None` if the check couldn't be run
+    pub deliverable: Option<bool>,
+    /// Why the channel is undeliverable or unchecked
+    pub reason: Option<String>,
+}
+
+impl ChannelDeliverability {
+    /// A channel that passed its pre-flight check.
+    pub fn deliverable() -> Self {
+        Self {
+            deliverable: Some(true),
+            reason: None,
+        }
+    }
+
+    /// A channel that failed its pre-flight check.
+    pub fn undeliverable(reason: impl Into<String>) -> Self {
+        Self {
+            deliverable: Some(false),
+            reason: Some(reason.into()),
+        }
+    }
+
+    /// A channel whose pre-flight check couldn't be run.
+    pub fn unknown(reason: impl Into<String>) -> Self {
+        Self {
+            deliverable: None,
+            reason: Some(reason.into()),
+        }
+    }
+
+    /// Returns true only if the check ran and failed; unchecked channels are given
+    /// the benefit of the doubt.
+    pub fn is_undeliverable(&self) -> bool {
+        self.deliverable == Some(false)
+    }
+}
+
+/// Pre-flight deliverability of each notification channel for a user.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DeliverabilityStatus {
+    /// Email (SES) deliverability
+    pub email: ChannelDeliverability,
+    /// SMS (SNS) deliverability
+    pub sms: ChannelDeliverability,
 }
 
 /// Request structure for sending email verification
@@ -42,6 +136,27 @@ pub struct EmailVerificationQuery {
     pub token: String,
 }
 
+/// Query of an unsubscribe link
+#[derive(serde::Deserialize)]
+pub struct UnsubscribeQuery {
+    /// Signed token naming the user and what they unsubscribe from
+    pub token: String,
+}
+
+/// Query of the SES/SNS delivery webhooks
+#[derive(serde::Deserialize)]
+pub struct WebhookQuery {
+    /// Shared secret from `DELIVERY_WEBHOOK_TOKEN`, part of the subscribed URL
+    pub token: Option<String>,
+}
+
+/// Request of the admin notification test
+#[derive(serde::Deserialize)]
+pub struct TestNotificationRequest {
+    /// `email` or `sms`
+    pub channel: String,
+}
+
 /// Request structure for listing users
 #[derive(serde::Deserialize)]
 pub struct DeleteUserQuery {
@@ -101,5 +216,106 @@ pub const EMAIL_VERIFICATION_ERROR_HTML: &str = r#"
 </html>
 "#;
 
-/// A thread-safe store for verification codes, allowing concurrent access.
-pub type VerificationStore = Arc<Mutex<HashMap<String, VerificationCode>>>;
+/// HTML page shown once a new email address is confirmed
+pub const EMAIL_CHANGE_SUCCESS_HTML: &str = r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Email Changed - CampTracker</title>
+    <style>
+        body { font-family: Arial, sans-serif; text-align: center; padding: 50px; background: #f0f9ff; }
+        .container { max-width: 500px; margin: 0 auto; background: white; padding: 30px; border-radius: 10px; box-shadow: 0 4px 6px rgba(0,0,0,0.1); }
+        .success { color: #059669; font-size: 48px; margin-bottom: 20px; }
+        h1 { color: #2c3e50; }
+        .button { background: #4a6741; color: white; padding: 12px 24px; text-decoration: none; border-radius: 8px; display: inline-block; margin-top: 20px; }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="success">&#x2705;</div>
+        <h1>Email Changed</h1>
+        <p>Your account now uses this address for sign-in and campsite availability notifications.</p>
+        <a href="/" class="button">Return to CampTracker</a>
+    </div>
+</body>
+</html>
+"#;
+
+/// HTML page shown when an email change link can't be used
+pub const EMAIL_CHANGE_ERROR_HTML: &str = r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Email Change Error - CampTracker</title>
+    <style>
+        body { font-family: Arial, sans-serif; text-align: center; padding: 50px; background: #fef2f2; }
+        .container { max-width: 500px; margin: 0 auto; background: white; padding: 30px; border-radius: 10px; box-shadow: 0 4px 6px rgba(0,0,0,0.1); }
+        .error { color: #dc2626; font-size: 48px; margin-bottom: 20px; }
+        h1 { color: #2c3e50; }
+        .button { background: #4a6741; color: white; padding: 12px 24px; text-decoration: none; border-radius: 8px; display: inline-block; margin-top: 20px; }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="error">&#x274C;</div>
+        <h1>Email Change Link Invalid</h1>
+        <p>This link has expired, was replaced by a newer one, or the address now belongs to another account. Your current email is unchanged; you can request the change again from your profile.</p>
+        <a href="/" class="button">Return to CampTracker</a>
+    </div>
+</body>
+</html>
+"#;
+
+/// HTML page shown once an unsubscribe link turned notifications off
+pub const UNSUBSCRIBE_SUCCESS_HTML: &str = r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Unsubscribed - CampTracker</title>
+    <style>
+        body { font-family: Arial, sans-serif; text-align: center; padding: 50px; background: #f0f9ff; }
+        .container { max-width: 500px; margin: 0 auto; background: white; padding: 30px; border-radius: 10px; box-shadow: 0 4px 6px rgba(0,0,0,0.1); }
+        .success { color: #059669; font-size: 48px; margin-bottom: 20px; }
+        h1 { color: #2c3e50; }
+        .button { background: #4a6741; color: white; padding: 12px 24px; text-decoration: none; border-radius: 8px; display: inline-block; margin-top: 20px; }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="success">&#x2705;</div>
+        <h1>You're Unsubscribed</h1>
+        <p>You won't get these alerts anymore. You can turn notifications back on from your profile.</p>
+        <a href="/" class="button">Return to CampTracker</a>
+    </div>
+</body>
+</html>
+"#;
+
+/// HTML page shown when an unsubscribe link can't be used
+pub const UNSUBSCRIBE_ERROR_HTML: &str = r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Unsubscribe Error - CampTracker</title>
+    <style>
+        body { font-family: Arial, sans-serif; text-align: center; padding: 50px; background: #fef2f2; }
+        .container { max-width: 500px; margin: 0 auto; background: white; padding: 30px; border-radius: 10px; box-shadow: 0 4px 6px rgba(0,0,0,0.1); }
+        .error { color: #dc2626; font-size: 48px; margin-bottom: 20px; }
+        h1 { color: #2c3e50; }
+        .button { background: #4a6741; color: white; padding: 12px 24px; text-decoration: none; border-radius: 8px; display: inline-block; margin-top: 20px; }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="error">&#x274C;</div>
+        <h1>Unsubscribe Link Invalid</h1>
+        <p>This link is incomplete or invalid. You can turn off notifications from your profile instead.</p>
+        <a href="/" class="button">Return to CampTracker</a>
+    </div>
+</body>
+</html>
+"#;
@@ -0,0 +1,178 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::signing::LinkSigner;
+
+/// Notifications an unsubscribe link turns off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsubscribeTarget {
+    /// Every email alert
+    Email,
+    /// Every text message alert
+    Sms,
+    /// Alerts about one scan, on every channel
+    Scan(Uuid),
+}
+
+impl UnsubscribeTarget {
+    /// Value signed into the token
+    fn encode(&self) -> String {
+        match self {
+            UnsubscribeTarget::Email => "email".to_string(),
+            UnsubscribeTarget::Sms => "sms".to_string(),
+            UnsubscribeTarget::Scan(scan_id) => format!("scan-{}", scan_id),
+        }
+    }
+
+    fn decode(value: &str) -> Option<Self> {
+        match value {
+            "email" => Some(UnsubscribeTarget::Email),
+            "sms" => Some(UnsubscribeTarget::Sms),
+            _ => value
+                .strip_prefix("scan-")
+                .and_then(|scan_id| Uuid::parse_str(scan_id).ok())
+                .map(UnsubscribeTarget::Scan),
+        }
+    }
+}
+
+/// A verified unsubscribe request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unsubscribe {
+    /// User who received the email
+    pub user_id: Uuid,
+    /// What they're unsubscribing from
+    pub target: UnsubscribeTarget,
+}
+
+impl Unsubscribe {
+    /// Turns the notifications off: a channel in the user's notification preferences, or
+    /// a scan through an opt-out that leaves the scan running for anyone else it alerts.
+    pub async fn apply(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let channel = match self.target {
+            UnsubscribeTarget::Email => "email",
+            UnsubscribeTarget::Sms => "sms",
+            UnsubscribeTarget::Scan(scan_id) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO scan_notification_opt_outs (user_id, scan_id)
+                    SELECT $1, id FROM user_scans WHERE id = $2
+                    ON CONFLICT DO NOTHING
+                    "#,
+                )
+                .bind(self.user_id)
+                .bind(scan_id)
+                .execute(pool)
+                .await?;
+                return Ok(());
+            }
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET notification_preferences =
+                    COALESCE(notification_preferences, '{}'::jsonb) || jsonb_build_object($2::text, false),
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(self.user_id)
+        .bind(channel)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Builds and verifies the signed one-click unsubscribe links in notification emails.
+/// Links don't expire: they have to keep working for as long as the email is kept.
+#[derive(Debug, Clone)]
+pub struct UnsubscribeLinks {
+    signer: LinkSigner,
+    base_url: String,
+}
+
+impl UnsubscribeLinks {
+    /// Creates links signed with a key derived from the configured key material
+    pub fn new(key_material: &str, base_url: &str) -> Self {
+        Self {
+            signer: LinkSigner::new(key_material, b"unsubscribe-links"),
+            base_url: base_url.to_string(),
+        }
+    }
+
+    /// Link that unsubscribes the user from `target` when opened
+    pub fn url(&self, user_id: &Uuid, target: UnsubscribeTarget) -> String {
+        format!(
+            "{}/unsubscribe?token={}",
+            self.base_url,
+            self.token(user_id, target)
+        )
+    }
+
+    /// Token of an unsubscribe link: `<user id>.<target>.<signature>`
+    pub fn token(&self, user_id: &Uuid, target: UnsubscribeTarget) -> String {
+        self.signer
+            .sign(&format!("{}.{}", user_id, target.encode()))
+    }
+
+    /// Checks a token's signature and returns what it unsubscribes from, or `None` when
+    /// the token was altered or wasn't signed with this key.
+    pub fn verify(&self, token: &str) -> Option<Unsubscribe> {
+        let (user_id, target) = self.signer.verify(token)?.split_once('.')?;
+
+        Some(Unsubscribe {
+            user_id: Uuid::parse_str(user_id).ok()?,
+            target: UnsubscribeTarget::decode(target)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_round_trip() {
+        let links = UnsubscribeLinks::new("a".repeat(32).as_str(), "http://localhost:8080");
+        let user_id = Uuid::new_v4();
+        let scan_id = Uuid::new_v4();
+
+        for target in [
+            UnsubscribeTarget::Email,
+            UnsubscribeTarget::Sms,
+            UnsubscribeTarget::Scan(scan_id),
+        ] {
+            assert_eq!(
+                links.verify(&links.token(&user_id, target)),
+                Some(Unsubscribe { user_id, target })
+            );
+        }
+
+        assert!(
+            links
+                .url(&user_id, UnsubscribeTarget::Email)
+                .starts_with(&format!(
+                    "http://localhost:8080/unsubscribe?token={}.email.",
+                    user_id
+                ))
+        );
+    }
+
+    #[test]
+    fn rejects_altered_tokens() {
+        let links = UnsubscribeLinks::new("a".repeat(32).as_str(), "http://localhost:8080");
+        let user_id = Uuid::new_v4();
+        let token = links.token(&user_id, UnsubscribeTarget::Sms);
+
+        let other_user = token.replace(&user_id.to_string(), &Uuid::new_v4().to_string());
+        assert_eq!(links.verify(&other_user), None);
+        assert_eq!(links.verify(&token.replace(".sms.", ".email.")), None);
+        assert_eq!(links.verify("not-a-token"), None);
+
+        let other_key = UnsubscribeLinks::new("b".repeat(32).as_str(), "http://localhost:8080");
+        assert_eq!(other_key.verify(&token), None);
+    }
+}
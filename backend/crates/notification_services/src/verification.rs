@@ -0,0 +1,227 @@
+use app_config::AppConfig;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+#[cfg(feature = "redis")]
+use crate::redis_store::RedisVerificationStore;
+use crate::types::{NotificationError, VerificationChannel};
+
+/// Maximum number of attempts allowed before a verification token is discarded.
+pub(crate) const MAX_VERIFICATION_ATTEMPTS: i32 = 3;
+
+/// Backend used to persist pending email and SMS verification tokens.
+#[derive(Clone)]
+pub enum VerificationStore {
+    /// Tokens stored in the `verification_tokens` table.
+    Postgres(PgPool),
+    /// Tokens stored in Redis, shared by every server behind the load balancer.
+    #[cfg(feature = "redis")]
+    Redis(RedisVerificationStore),
+}
+
+impl VerificationStore {
+    /// Creates the verification store, using Redis when `REDIS_URL` is set and the
+    /// `redis` feature is enabled, and falling back to Postgres otherwise.
+    pub async fn from_config(pool: PgPool, config: &AppConfig) -> Self {
+        match &config.redis_url {
+            Some(url) => Self::redis_or_postgres(url, pool).await,
+            None => VerificationStore::Postgres(pool),
+        }
+    }
+
+    #[cfg(feature = "redis")]
+    async fn redis_or_postgres(url: &str, pool: PgPool) -> Self {
+        match RedisVerificationStore::connect(url).await {
+            Ok(store) => {
+                log::info!("🔑 Using Redis verification store");
+                VerificationStore::Redis(store)
+            }
+            Err(e) => {
+                log::error!(
+                    "❌ Failed to connect to Redis, falling back to Postgres: {}",
+                    e
+                );
+                VerificationStore::Postgres(pool)
+            }
+        }
+    }
+
+    #[cfg(not(feature = "redis"))]
+    async fn redis_or_postgres(_url: &str, pool: PgPool) -> Self {
+        log::warn!("⚠️ REDIS_URL is set but the `redis` feature is disabled, using Postgres");
+        VerificationStore::Postgres(pool)
+    }
+
+    /// Stores a verification token for the user, replacing any pending token on the same channel.
+    pub async fn store_token(
+        &self,
+        user_id: &Uuid,
+        channel: VerificationChannel,
+        target: &str,
+        token: &str,
+        expires_in_minutes: i64,
+    ) -> Result<(), NotificationError> {
+        match self {
+            VerificationStore::Postgres(pool) => {
+                store_verification_token(pool, user_id, channel, target, token, expires_in_minutes)
+                    .await
+            }
+            #[cfg(feature = "redis")]
+            VerificationStore::Redis(store) => {
+                store
+                    .store_token(user_id, channel, target, token, expires_in_minutes)
+                    .await
+            }
+        }
+    }
+
+    /// Verifies the provided code against the pending token for the user and target.
+    pub async fn verify_code(
+        &self,
+        user_id: &Uuid,
+        channel: VerificationChannel,
+        target: &str,
+        provided_code: &str,
+    ) -> Result<bool, NotificationError> {
+        match self {
+            VerificationStore::Postgres(pool) => {
+                verify_code(pool, user_id, channel, target, provided_code).await
+            }
+            #[cfg(feature = "redis")]
+            VerificationStore::Redis(store) => {
+                store
+                    .verify_code(user_id, channel, target, provided_code)
+                    .await
+            }
+        }
+    }
+
+    /// Consumes an email verification token, returning the owning user ID if it is still valid.
+    pub async fn consume_email_token(
+        &self,
+        token: &str,
+    ) -> Result<Option<Uuid>, NotificationError> {
+        match self {
+            VerificationStore::Postgres(pool) => consume_email_token(pool, token).await,
+            #[cfg(feature = "redis")]
+            VerificationStore::Redis(store) => store.consume_email_token(token).await,
+        }
+    }
+}
+
+/// Stores a verification token for the user, replacing any pending token on the same channel.
+async fn store_verification_token(
+    pool: &PgPool,
+    user_id: &Uuid,
+    channel: VerificationChannel,
+    target: &str,
+    token: &str,
+    expires_in_minutes: i64,
+) -> Result<(), NotificationError> {
+    sqlx::query(
+        r#"
+        INSERT INTO verification_tokens (user_id, channel, target, token, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (user_id, channel) DO UPDATE SET
+            target = EXCLUDED.target,
+            token = EXCLUDED.token,
+            attempts = 0,
+            expires_at = EXCLUDED.expires_at,
+            created_at = NOW()
+        "#,
+    )
+    .bind(user_id)
+    .bind(channel.as_str())
+    .bind(target)
+    .bind(token)
+    .bind(Utc::now() + Duration::minutes(expires_in_minutes))
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Verifies the provided code against the pending token for the user and target.
+/// The token is deleted once it is consumed, has expired, or runs out of attempts.
+async fn verify_code(
+    pool: &PgPool,
+    user_id: &Uuid,
+    channel: VerificationChannel,
+    target: &str,
+    provided_code: &str,
+) -> Result<bool, NotificationError> {
+    // Count the attempt atomically so concurrent guesses can't bypass the limit
+    let row = sqlx::query(
+        r#"
+        UPDATE verification_tokens
+        SET attempts = attempts + 1
+        WHERE user_id = $1 AND channel = $2 AND target = $3
+        RETURNING id, token, attempts, expires_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(channel.as_str())
+    .bind(target)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(NotificationError::VerificationNotFound)?;
+
+    let token_id: Uuid = row.get("id");
+    let token: String = row.get("token");
+    let attempts: i32 = row.get("attempts");
+    let expires_at: DateTime<Utc> = row.get("expires_at");
+
+    if expires_at < Utc::now() {
+        delete_verification_token(pool, &token_id).await?;
+        return Err(NotificationError::VerificationExpired);
+    }
+
+    if attempts > MAX_VERIFICATION_ATTEMPTS {
+        delete_verification_token(pool, &token_id).await?;
+        return Err(NotificationError::TooManyAttempts);
+    }
+
+    if token == provided_code {
+        delete_verification_token(pool, &token_id).await?;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// Consumes an email verification token (from an email link), returning the owning user ID
+/// if the token exists and has not expired.
+async fn consume_email_token(
+    pool: &PgPool,
+    token: &str,
+) -> Result<Option<Uuid>, NotificationError> {
+    let row = sqlx::query(
+        r#"
+        DELETE FROM verification_tokens
+        WHERE channel = $1 AND token = $2
+        RETURNING user_id, expires_at
+        "#,
+    )
+    .bind(VerificationChannel::Email.as_str())
+    .bind(token)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.and_then(|row| {
+        let expires_at: DateTime<Utc> = row.get("expires_at");
+        (expires_at > Utc::now()).then(|| row.get("user_id"))
+    }))
+}
+
+async fn delete_verification_token(
+    pool: &PgPool,
+    token_id: &Uuid,
+) -> Result<(), NotificationError> {
+    sqlx::query("DELETE FROM verification_tokens WHERE id = $1")
+        .bind(token_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
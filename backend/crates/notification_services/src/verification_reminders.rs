@@ -0,0 +1,108 @@
+use i18n::Locale;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::types::NotificationError;
+
+/// Hours after signup that each reminder is sent to users who haven't verified yet
+pub const VERIFICATION_REMINDER_HOURS: [i32; 2] = [24, 72];
+
+/// A user due a reminder to finish verifying their contact details
+#[derive(Debug, Clone)]
+pub struct VerificationReminder {
+    /// User to remind
+    pub user_id: Uuid,
+    /// Email address of the user
+    pub email: String,
+    /// Name of the user
+    pub name: String,
+    /// Whether the email address still needs verifying
+    pub email_unverified: bool,
+    /// Whether SMS alerts are on for a phone number that still needs verifying
+    pub phone_unverified: bool,
+    /// Which reminder this is, starting at 1
+    pub number: i32,
+    /// Language the user gets notifications in
+    pub locale: Locale,
+}
+
+/// Finds users who signed up but never verified, since alerts aren't sent to unverified
+/// channels and those users otherwise don't find out why they hear nothing.
+#[derive(Debug, Clone)]
+pub struct VerificationReminders {
+    pool: PgPool,
+}
+
+impl VerificationReminders {
+    /// Creates the reminder schedule over the `users` table.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Users due their next reminder: past its delay after signup, and at least as long
+    /// after the previous reminder as the schedule spaces them.
+    pub async fn due(&self) -> Result<Vec<VerificationReminder>, NotificationError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, email, name, locale, email_unverified, phone_unverified,
+                   verification_reminders_sent + 1 AS number
+            FROM (
+                SELECT id, email, name, locale, created_at, verification_reminders_sent,
+                       last_verification_reminder_at,
+                       NOT COALESCE(email_verified, false) AS email_unverified,
+                       (phone IS NOT NULL
+                        AND NOT COALESCE(phone_verified, false)
+                        AND COALESCE((notification_preferences->>'sms')::BOOLEAN, false)
+                       ) AS phone_unverified
+                FROM users
+                WHERE COALESCE(is_active, true)
+                  AND verification_reminders_sent < cardinality($1::INT[])
+            ) candidates
+            WHERE (email_unverified OR phone_unverified)
+              AND created_at <= NOW()
+                  - make_interval(hours => ($1::INT[])[verification_reminders_sent + 1])
+              AND (
+                  last_verification_reminder_at IS NULL
+                  OR last_verification_reminder_at <= NOW() - make_interval(
+                      hours => ($1::INT[])[verification_reminders_sent + 1]
+                             - ($1::INT[])[verification_reminders_sent]
+                  )
+              )
+            ORDER BY created_at
+            "#,
+        )
+        .bind(VERIFICATION_REMINDER_HOURS.as_slice())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| VerificationReminder {
+                user_id: row.get("id"),
+                email: row.get("email"),
+                name: row.get("name"),
+                email_unverified: row.get("email_unverified"),
+                phone_unverified: row.get("phone_unverified"),
+                number: row.get("number"),
+                locale: row.get::<String, _>("locale").parse().unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// Records that the user was sent reminder `number`.
+    pub async fn mark_sent(&self, user_id: &Uuid, number: i32) -> Result<(), NotificationError> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET verification_reminders_sent = $1, last_verification_reminder_at = NOW()
+            WHERE id = $2
+            "#,
+        )
+        .bind(number)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::types::{NotificationError, VerificationChannel};
+
+/// Verification messages a user can request per channel within [`THROTTLE_WINDOW_MINUTES`]
+pub const MAX_VERIFICATION_SENDS: i64 = 3;
+
+/// Window that [`MAX_VERIFICATION_SENDS`] applies to
+pub const THROTTLE_WINDOW_MINUTES: i64 = 60;
+
+/// Invitations one address can be sent for one scan within [`INVITATION_WINDOW_HOURS`]
+pub const MAX_INVITATION_SENDS: i64 = 2;
+
+/// Window that [`MAX_INVITATION_SENDS`] applies to
+pub const INVITATION_WINDOW_HOURS: i64 = 24;
+
+/// Caps how often a user can have verification emails and SMS resent, and how often a
+/// scan's owner can invite the same address, so the endpoints can't be used to flood an
+/// inbox or run up the SMS bill.
+#[derive(Debug, Clone)]
+pub struct VerificationThrottle {
+    pool: PgPool,
+}
+
+impl VerificationThrottle {
+    /// Creates a throttle backed by the `verification_sends` table.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Records a send on the channel if the user is under the limit, or returns
+    /// [`NotificationError::TooManySends`] with the seconds until the next send is allowed.
+    pub async fn acquire(
+        &self,
+        user_id: &Uuid,
+        channel: VerificationChannel,
+    ) -> Result<(), NotificationError> {
+        let window_start = Utc::now() - Duration::minutes(THROTTLE_WINDOW_MINUTES);
+        let mut tx = self.pool.begin().await?;
+
+        // Serialize concurrent requests of the same user so they can't all pass the count
+        sqlx::query("SELECT id FROM users WHERE id = $1 FOR UPDATE")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let row = sqlx::query(
+            r#"
+            SELECT COUNT(*) AS sends, MIN(sent_at) AS oldest
+            FROM verification_sends
+            WHERE user_id = $1 AND channel = $2 AND sent_at > $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(channel.as_str())
+        .bind(window_start)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let sends: i64 = row.get("sends");
+        if sends >= MAX_VERIFICATION_SENDS {
+            let oldest: Option<DateTime<Utc>> = row.get("oldest");
+            let retry_after = oldest
+                .map(|oldest| (oldest - window_start).num_seconds().max(1) as u64)
+                .unwrap_or(1);
+            return Err(NotificationError::TooManySends {
+                retry_after_secs: retry_after,
+            });
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO verification_sends (user_id, channel) VALUES ($1, $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(channel.as_str())
+        .execute(&mut *tx)
+        .await?;
+
+        // Older sends no longer count towards any limit
+        sqlx::query("DELETE FROM verification_sends WHERE user_id = $1 AND sent_at <= $2")
+            .bind(user_id)
+            .bind(window_start)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Records an invitation to `address` for the scan if it's under the limit, or returns
+    /// [`NotificationError::TooManySends`] with the seconds until it can be invited again.
+    /// Removing a recipient doesn't forget their invitations.
+    pub async fn acquire_invitation(
+        &self,
+        scan_id: &Uuid,
+        channel: VerificationChannel,
+        address: &str,
+    ) -> Result<(), NotificationError> {
+        let window_start = Utc::now() - Duration::hours(INVITATION_WINDOW_HOURS);
+        let mut tx = self.pool.begin().await?;
+
+        // Serialize concurrent invitations to the same scan
+        sqlx::query("SELECT id FROM user_scans WHERE id = $1 FOR UPDATE")
+            .bind(scan_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let row = sqlx::query(
+            r#"
+            SELECT COUNT(*) AS sends, MIN(sent_at) AS oldest
+            FROM scan_invitation_sends
+            WHERE scan_id = $1 AND channel = $2 AND address = $3 AND sent_at > $4
+            "#,
+        )
+        .bind(scan_id)
+        .bind(channel.as_str())
+        .bind(address)
+        .bind(window_start)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let sends: i64 = row.get("sends");
+        if sends >= MAX_INVITATION_SENDS {
+            let oldest: Option<DateTime<Utc>> = row.get("oldest");
+            let retry_after = oldest
+                .map(|oldest| (oldest - window_start).num_seconds().max(1) as u64)
+                .unwrap_or(1);
+            return Err(NotificationError::TooManySends {
+                retry_after_secs: retry_after,
+            });
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO scan_invitation_sends (scan_id, channel, address) VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(scan_id)
+        .bind(channel.as_str())
+        .bind(address)
+        .execute(&mut *tx)
+        .await?;
+
+        // Older invitations no longer count towards any limit
+        sqlx::query("DELETE FROM scan_invitation_sends WHERE scan_id = $1 AND sent_at <= $2")
+            .bind(scan_id)
+            .bind(window_start)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use postgres::test_fixtures::{insert_campground, insert_user};
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn invitations_to_one_address_are_capped_per_scan(pool: PgPool) {
+        insert_campground(&pool, "232447", "Upper Pines").await;
+        let user_id = insert_user(&pool, "camper@example.com").await;
+        let scan_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO user_scans (user_id, campground_id, check_in_date, check_out_date)
+            VALUES ($1, '232447', CURRENT_DATE + 30, CURRENT_DATE + 32)
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let throttle = VerificationThrottle::new(pool);
+
+        for _ in 0..MAX_INVITATION_SENDS {
+            throttle
+                .acquire_invitation(&scan_id, VerificationChannel::Email, "friend@example.com")
+                .await
+                .unwrap();
+        }
+
+        let throttled = throttle
+            .acquire_invitation(&scan_id, VerificationChannel::Email, "friend@example.com")
+            .await;
+        assert!(matches!(
+            throttled,
+            Err(NotificationError::TooManySends { retry_after_secs })
+                if retry_after_secs > 0
+                    && retry_after_secs <= INVITATION_WINDOW_HOURS as u64 * 3600
+        ));
+
+        // Other addresses of the scan have their own limit
+        throttle
+            .acquire_invitation(&scan_id, VerificationChannel::Email, "other@example.com")
+            .await
+            .unwrap();
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn verification_sends_are_allowed_again_after_the_window(pool: PgPool) {
+        let user_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO users (email, name, password_hash)
+            VALUES ('camper@example.com', 'Camper', 'x')
+            RETURNING id
+            "#,
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let throttle = VerificationThrottle::new(pool.clone());
+
+        for _ in 0..MAX_VERIFICATION_SENDS {
+            throttle
+                .acquire(&user_id, VerificationChannel::Email)
+                .await
+                .unwrap();
+        }
+
+        let throttled = throttle.acquire(&user_id, VerificationChannel::Email).await;
+        assert!(matches!(
+            throttled,
+            Err(NotificationError::TooManySends { retry_after_secs })
+                if retry_after_secs > 0 && retry_after_secs <= THROTTLE_WINDOW_MINUTES as u64 * 60
+        ));
+
+        // SMS is counted separately
+        throttle
+            .acquire(&user_id, VerificationChannel::Sms)
+            .await
+            .unwrap();
+
+        // Once the window has passed the earlier sends stop counting
+        sqlx::query("UPDATE verification_sends SET sent_at = sent_at - make_interval(mins => $1)")
+            .bind(THROTTLE_WINDOW_MINUTES as i32)
+            .execute(&pool)
+            .await
+            .unwrap();
+        throttle
+            .acquire(&user_id, VerificationChannel::Email)
+            .await
+            .unwrap();
+    }
+}
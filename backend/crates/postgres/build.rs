@@ -0,0 +1,5 @@
+//! Rebuilds the crate when migrations change so `sqlx::migrate!` embeds the latest files.
+
+fn main() {
+    println!("cargo:rerun-if-changed=../../migrations");
+}
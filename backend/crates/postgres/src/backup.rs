@@ -0,0 +1,323 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::collections::BTreeMap;
+
+/// Version of the archive format written by [`export_archive`]
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// Tables included in an archive, in an order that satisfies their foreign keys
+pub const ARCHIVE_TABLES: &[&str] = &[
+    "campgrounds",
+    "campsites",
+    "plans",
+    "users",
+    "favorite_campgrounds",
+    "organizations",
+    "organization_members",
+    "trips",
+    "user_scans",
+    "scan_shares",
+    "scan_recipients",
+    "scan_notification_opt_outs",
+    "polling_jobs",
+];
+
+/// Password hash given to users when PII is stripped; bcrypt never verifies it, so
+/// cloned accounts can't be signed in to
+const STRIPPED_PASSWORD_HASH: &str = "!stripped";
+
+/// Snapshot of operational data (campground mirrors, users, organizations, and scans)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupArchive {
+    /// Archive format version, see [`ARCHIVE_FORMAT_VERSION`]
+    pub format_version: u32,
+    /// When the archive was created
+    pub created_at: DateTime<Utc>,
+    /// Whether emails, names, phone numbers, and passwords were replaced
+    pub pii_stripped: bool,
+    /// Rows of each table, as JSON objects keyed by column name
+    pub tables: BTreeMap<String, Vec<serde_json::Value>>,
+}
+
+/// Query parameters for exporting an archive
+#[derive(Debug, Deserialize)]
+pub struct BackupQuery {
+    /// Replace user PII, e.g. when cloning production into staging
+    pub strip_pii: Option<bool>,
+}
+
+/// Rows inserted per table by [`restore_archive`]
+#[derive(Debug, Serialize)]
+pub struct RestoreSummary {
+    /// Archive format version that was restored
+    pub format_version: u32,
+    /// Rows inserted per table; rows that already existed are skipped
+    pub inserted: BTreeMap<String, u64>,
+}
+
+/// Errors from exporting or restoring an archive
+#[derive(thiserror::Error, Debug)]
+pub enum BackupError {
+    /// Database error
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    /// The archive was written by a newer version of the server
+    #[error(
+        "Unsupported archive format version {0} (this server reads up to {ARCHIVE_FORMAT_VERSION})"
+    )]
+    UnsupportedVersion(u32),
+
+    /// The archive contains a table that isn't part of the format
+    #[error("Unknown table in archive: {0}")]
+    UnknownTable(String),
+}
+
+/// Exports every archive table. With `strip_pii`, user emails, names, phone numbers,
+/// and password hashes are replaced so the archive can seed a staging environment.
+pub async fn export_archive(pool: &PgPool, strip_pii: bool) -> Result<BackupArchive, BackupError> {
+    // One snapshot, so rows referenced across tables are consistent
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+
+    let mut tables = BTreeMap::new();
+
+    for &table in ARCHIVE_TABLES {
+        let row = if table == "users" && strip_pii {
+            sqlx::query(
+                r#"
+                SELECT COALESCE(jsonb_agg(
+                    to_jsonb(u) || jsonb_build_object(
+                        'email', 'user-' || u.id || '@example.invalid',
+                        'name', 'User ' || LEFT(u.id::TEXT, 8),
+                        'phone', NULL,
+                        'phone_verified', FALSE,
+                        'password_hash', $1::TEXT
+                    )
+                ), '[]'::JSONB) AS rows
+                FROM users u
+                "#,
+            )
+            .bind(STRIPPED_PASSWORD_HASH)
+            .fetch_one(&mut *tx)
+            .await?
+        } else {
+            sqlx::query(&format!(
+                r#"SELECT COALESCE(jsonb_agg(to_jsonb(t)), '[]'::JSONB) AS rows FROM "{}" t"#,
+                table
+            ))
+            .fetch_one(&mut *tx)
+            .await?
+        };
+
+        let rows: serde_json::Value = row.get("rows");
+        let rows = match rows {
+            serde_json::Value::Array(rows) => rows,
+            _ => Vec::new(),
+        };
+
+        tables.insert(table.to_string(), rows);
+    }
+
+    tx.commit().await?;
+
+    Ok(BackupArchive {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        created_at: Utc::now(),
+        pii_stripped: strip_pii,
+        tables,
+    })
+}
+
+/// Restores an archive in a single transaction. Rows whose key already exists are left
+/// untouched, so restoring into a live database only fills in what's missing.
+pub async fn restore_archive(
+    pool: &PgPool,
+    archive: &BackupArchive,
+) -> Result<RestoreSummary, BackupError> {
+    if archive.format_version > ARCHIVE_FORMAT_VERSION {
+        return Err(BackupError::UnsupportedVersion(archive.format_version));
+    }
+
+    if let Some(unknown) = archive
+        .tables
+        .keys()
+        .find(|table| !ARCHIVE_TABLES.contains(&table.as_str()))
+    {
+        return Err(BackupError::UnknownTable(unknown.clone()));
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut inserted = BTreeMap::new();
+
+    for &table in ARCHIVE_TABLES {
+        let Some(rows) = archive.tables.get(table) else {
+            continue;
+        };
+
+        let count = restore_table(&mut tx, table, rows).await?;
+        inserted.insert(table.to_string(), count);
+    }
+
+    tx.commit().await?;
+
+    Ok(RestoreSummary {
+        format_version: archive.format_version,
+        inserted,
+    })
+}
+
+/// Inserts the rows of one table, skipping generated columns (e.g. `user_scans.nights`)
+async fn restore_table(
+    tx: &mut Transaction<'_, Postgres>,
+    table: &str,
+    rows: &[serde_json::Value],
+) -> Result<u64, sqlx::Error> {
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let columns: Vec<String> = sqlx::query(
+        r#"
+        SELECT column_name::TEXT AS column_name FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = $1 AND is_generated = 'NEVER'
+        ORDER BY ordinal_position
+        "#,
+    )
+    .bind(table)
+    .fetch_all(&mut **tx)
+    .await?
+    .iter()
+    .map(|row| format!(r#""{}""#, row.get::<String, _>("column_name")))
+    .collect();
+
+    let columns = columns.join(", ");
+
+    let result = sqlx::query(&format!(
+        r#"
+        INSERT INTO "{table}" ({columns})
+        SELECT {columns} FROM jsonb_populate_recordset(NULL::"{table}", $1)
+        ON CONFLICT DO NOTHING
+        "#
+    ))
+    .bind(serde_json::Value::Array(rows.to_vec()))
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{insert_campground, insert_user};
+    use uuid::Uuid;
+
+    /// A user with a scan of Upper Pines and two recipients of its alerts, returning the
+    /// user's ID
+    async fn user_with_scan(pool: &PgPool) -> Uuid {
+        insert_campground(pool, "232447", "Upper Pines").await;
+        let user_id = insert_user(pool, "camper@example.com").await;
+        let scan_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO user_scans (user_id, campground_id, check_in_date, check_out_date)
+            VALUES ($1, '232447', CURRENT_DATE + 30, CURRENT_DATE + 32)
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO scan_recipients (scan_id, channel, address, status, token)
+            VALUES ($1, 'email', 'friend@example.com', 'confirmed', 'token-1'),
+                   ($1, 'sms', '+15555550123', 'pending', 'token-2')
+            "#,
+        )
+        .bind(scan_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        user_id
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn scans_and_their_recipients_survive_a_restore(pool: PgPool) {
+        let user_id = user_with_scan(&pool).await;
+        let archive = export_archive(&pool, false).await.unwrap();
+        assert_eq!(archive.tables["user_scans"].len(), 1);
+        assert_eq!(archive.tables["scan_recipients"].len(), 2);
+
+        // Deleting the user takes their scan and its recipients with it
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let summary = restore_archive(&pool, &archive).await.unwrap();
+        assert_eq!(summary.inserted["campgrounds"], 0);
+        assert_eq!(summary.inserted["users"], 1);
+        assert_eq!(summary.inserted["user_scans"], 1);
+        assert_eq!(summary.inserted["scan_recipients"], 2);
+
+        let restored = export_archive(&pool, false).await.unwrap();
+        for table in ["users", "user_scans", "scan_recipients"] {
+            assert_eq!(restored.tables[table], archive.tables[table], "{}", table);
+        }
+
+        // Restoring again leaves existing rows alone
+        let summary = restore_archive(&pool, &archive).await.unwrap();
+        assert!(summary.inserted.values().all(|&count| count == 0));
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn stripped_archives_keep_scans_but_not_who_owns_them(pool: PgPool) {
+        let user_id = user_with_scan(&pool).await;
+        let archive = export_archive(&pool, true).await.unwrap();
+
+        let user = &archive.tables["users"][0];
+        assert_eq!(
+            user["email"],
+            format!("user-{}@example.invalid", user_id).as_str()
+        );
+        assert_ne!(user["name"], "Camper");
+        assert_eq!(user["password_hash"], STRIPPED_PASSWORD_HASH);
+        assert!(archive.pii_stripped);
+
+        assert_eq!(
+            archive.tables["user_scans"][0]["user_id"],
+            user_id.to_string()
+        );
+        assert_eq!(archive.tables["scan_recipients"].len(), 2);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn archives_from_newer_servers_are_refused(pool: PgPool) {
+        let mut archive = export_archive(&pool, false).await.unwrap();
+        archive.format_version = ARCHIVE_FORMAT_VERSION + 1;
+
+        assert!(matches!(
+            restore_archive(&pool, &archive).await,
+            Err(BackupError::UnsupportedVersion(_))
+        ));
+
+        archive.format_version = ARCHIVE_FORMAT_VERSION;
+        archive
+            .tables
+            .insert("api_call_ledger".to_string(), Vec::new());
+        assert!(matches!(
+            restore_archive(&pool, &archive).await,
+            Err(BackupError::UnknownTable(table)) if table == "api_call_ledger"
+        ));
+    }
+}
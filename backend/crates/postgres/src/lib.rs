@@ -2,5 +2,14 @@
 //!
 //! This crate provides a client for the Campsite Tracker application to interact with a PostgreSQL database.
 
+/// Versioned export and restore of operational data.
+pub mod backup;
 /// Database client for the campsite tracker application.
 pub mod database;
+/// Embedded schema migrations.
+pub mod migrations;
+/// Page size handling for admin listings.
+pub mod pagination;
+/// Rows shared by the database tests of every crate.
+#[cfg(any(test, feature = "test-fixtures"))]
+pub mod test_fixtures;
@@ -0,0 +1,17 @@
+use sqlx::PgPool;
+use sqlx::migrate::{MigrateError, Migrator};
+
+/// Migrations embedded from `backend/migrations` at compile time.
+static MIGRATOR: Migrator = sqlx::migrate!("../../migrations");
+
+/// Applies any pending database migrations, creating the schema on a fresh database.
+pub async fn run_migrations(pool: &PgPool) -> Result<(), MigrateError> {
+    MIGRATOR.run(pool).await?;
+
+    log::info!(
+        "🗃️ Database schema up to date ({} migrations)",
+        MIGRATOR.iter().count()
+    );
+
+    Ok(())
+}
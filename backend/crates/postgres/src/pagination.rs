@@ -0,0 +1,62 @@
+use serde::Serialize;
+
+/// Page size used when a request doesn't ask for one
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// Largest page a request may ask for
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+/// Page of a listing, resolved from optional `page` and `per_page` query parameters
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Pagination {
+    /// Page number, starting at 1
+    pub page: i64,
+    /// Rows per page
+    pub per_page: i64,
+}
+
+impl Pagination {
+    /// Resolves the requested page, defaulting to the first page of
+    /// [`DEFAULT_PAGE_SIZE`] rows and capping the size at [`MAX_PAGE_SIZE`].
+    pub fn new(page: Option<i64>, per_page: Option<i64>) -> Self {
+        Self {
+            page: page.unwrap_or(1).max(1),
+            per_page: per_page
+                .unwrap_or(DEFAULT_PAGE_SIZE)
+                .clamp(1, MAX_PAGE_SIZE),
+        }
+    }
+
+    /// Rows to skip before this page
+    pub fn offset(&self) -> i64 {
+        (self.page - 1).saturating_mul(self.per_page)
+    }
+}
+
+/// One page of a listing and where it sits in the whole
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    /// Rows on this page
+    pub items: Vec<T>,
+    /// Page number, starting at 1
+    pub page: i64,
+    /// Rows per page
+    pub per_page: i64,
+    /// Rows matching the filters across all pages
+    pub total: i64,
+    /// Number of pages
+    pub total_pages: i64,
+}
+
+impl<T> Page<T> {
+    /// Wraps the rows of a page with the total number of matching rows
+    pub fn new(items: Vec<T>, pagination: Pagination, total: i64) -> Self {
+        Self {
+            items,
+            page: pagination.page,
+            per_page: pagination.per_page,
+            total,
+            total_pages: (total + pagination.per_page - 1) / pagination.per_page,
+        }
+    }
+}
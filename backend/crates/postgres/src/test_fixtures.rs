@@ -0,0 +1,27 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Inserts a verified user named Camper with `email`, returning its ID
+pub async fn insert_user(pool: &PgPool, email: &str) -> Uuid {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO users (email, name, password_hash, email_verified)
+        VALUES ($1, 'Camper', 'x', TRUE)
+        RETURNING id
+        "#,
+    )
+    .bind(email)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+/// Inserts campground `id` named `name`
+pub async fn insert_campground(pool: &PgPool, id: &str, name: &str) {
+    sqlx::query("INSERT INTO campgrounds (id, name) VALUES ($1, $2)")
+        .bind(id)
+        .bind(name)
+        .execute(pool)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,475 @@
+use app_config::AppConfig;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::validation::{PayloadIssue, QUARANTINE_SAMPLE_SIZE};
+
+/// A single call made to an upstream recreation.gov / RIDB endpoint.
+#[derive(Debug, Clone)]
+pub struct ApiCallRecord {
+    /// Logical name of the upstream endpoint (e.g. `ridb_recareas`)
+    pub endpoint: &'static str,
+    /// Campground the call was made on behalf of, if any
+    pub campground_id: Option<String>,
+    /// HTTP status code, or `None` if no response was received
+    pub status_code: Option<u16>,
+    /// Wall-clock duration of the call
+    pub duration: Duration,
+}
+
+/// Aggregated API usage for one day, endpoint, and campground
+#[derive(Debug, Serialize)]
+pub struct DailyApiUsage {
+    /// Day the calls were made (UTC)
+    pub day: NaiveDate,
+    /// Logical name of the upstream endpoint
+    pub endpoint: String,
+    /// Campground the calls were attributed to, if any
+    pub campground_id: Option<String>,
+    /// Number of calls made
+    pub calls: i64,
+    /// Number of calls that failed or returned an error status
+    pub errors: i64,
+    /// Average call duration in milliseconds
+    pub avg_duration_ms: f64,
+}
+
+/// Query parameters for the API usage report
+#[derive(Debug, Deserialize)]
+pub struct ApiUsageQuery {
+    /// Number of days to report on (defaults to 7)
+    pub days: Option<i32>,
+}
+
+/// Kind of upstream call, each with its own hourly budget so a burst of one kind (e.g.
+/// user searches or previews) can't starve another (availability polling)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointClass {
+    /// Campground availability and permit quota polls
+    Availability,
+    /// Campground availability and permit quotas users look up on demand
+    OnDemand,
+    /// Facility, campsite, and media lookups
+    Details,
+    /// Recreation area and facility searches
+    Search,
+}
+
+impl EndpointClass {
+    /// Every class
+    pub const ALL: [EndpointClass; 4] = [
+        EndpointClass::Availability,
+        EndpointClass::OnDemand,
+        EndpointClass::Details,
+        EndpointClass::Search,
+    ];
+
+    /// Class of a logical endpoint name as recorded in the ledger, for calls made by
+    /// polling and other background work
+    pub fn of(endpoint: &str) -> Self {
+        match endpoint {
+            "recgov_availability_month" | "recgov_permit_month" => EndpointClass::Availability,
+            "ridb_recareas" | "ridb_recarea_facilities" => EndpointClass::Search,
+            _ => EndpointClass::Details,
+        }
+    }
+
+    /// Class of a call to `endpoint` made on demand for a user: availability lookups get
+    /// their own budget, apart from polling
+    pub fn on_demand(endpoint: &str) -> Self {
+        match EndpointClass::of(endpoint) {
+            EndpointClass::Availability => EndpointClass::OnDemand,
+            class => class,
+        }
+    }
+
+    /// Value stored in `api_budget_windows.endpoint_class`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EndpointClass::Availability => "availability",
+            EndpointClass::OnDemand => "on_demand",
+            EndpointClass::Details => "details",
+            EndpointClass::Search => "search",
+        }
+    }
+}
+
+/// Hourly call budgets: one across every call, and one per [`EndpointClass`]. 0 leaves
+/// a budget uncapped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ApiBudgets {
+    /// Calls of any class per rolling hour
+    pub total: u32,
+    /// Availability polls per rolling hour
+    pub availability: u32,
+    /// On-demand availability lookups per rolling hour
+    pub on_demand: u32,
+    /// Detail lookups per rolling hour
+    pub details: u32,
+    /// Searches per rolling hour
+    pub search: u32,
+}
+
+impl ApiBudgets {
+    /// Budgets set by `RECREATION_GOV_HOURLY_*_BUDGET`
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            total: config.recreation_gov_hourly_request_budget,
+            availability: config.recreation_gov_hourly_availability_budget,
+            on_demand: config.recreation_gov_hourly_on_demand_budget,
+            details: config.recreation_gov_hourly_details_budget,
+            search: config.recreation_gov_hourly_search_budget,
+        }
+    }
+
+    /// Budget of one class
+    pub fn of(&self, class: EndpointClass) -> u32 {
+        match class {
+            EndpointClass::Availability => self.availability,
+            EndpointClass::OnDemand => self.on_demand,
+            EndpointClass::Details => self.details,
+            EndpointClass::Search => self.search,
+        }
+    }
+}
+
+/// Calls made in the last rolling hour against one budget
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetUsage {
+    /// Calls counted in the last 60 minutes, across every instance
+    pub used: i64,
+    /// Calls allowed per rolling hour; 0 when there's no cap
+    pub limit: i64,
+    /// Calls left this hour, or `None` when there's no cap
+    pub remaining: Option<i64>,
+}
+
+impl BudgetUsage {
+    fn new(used: i64, limit: u32) -> Self {
+        let limit = i64::from(limit);
+
+        Self {
+            used,
+            limit,
+            remaining: (limit > 0).then(|| (limit - used).max(0)),
+        }
+    }
+}
+
+/// Upstream calls made in the last rolling hour against the hourly budgets
+#[derive(Debug, Clone, Serialize)]
+pub struct HourlyApiBudget {
+    /// Calls of any class
+    #[serde(flatten)]
+    pub total: BudgetUsage,
+    /// Availability polls
+    pub availability: BudgetUsage,
+    /// On-demand availability lookups
+    pub on_demand: BudgetUsage,
+    /// Detail lookups
+    pub details: BudgetUsage,
+    /// Searches
+    pub search: BudgetUsage,
+}
+
+/// Ledger of upstream API calls, used to attribute API spend to campgrounds and to
+/// keep calls within rolling hourly budgets shared by every instance. Clones share their
+/// budgets, so a budget changed at runtime applies to every clone.
+#[derive(Clone)]
+pub struct ApiCallLedger {
+    pool: PgPool,
+    budgets: Arc<RwLock<ApiBudgets>>,
+}
+
+impl ApiCallLedger {
+    /// Creates a new ledger backed by the provided database connection pool, with no
+    /// hourly budgets.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            budgets: Arc::new(RwLock::new(ApiBudgets::default())),
+        }
+    }
+
+    /// Creates a ledger capping upstream calls at the configured hourly budgets.
+    pub fn from_config(pool: PgPool, config: &AppConfig) -> Self {
+        Self {
+            pool,
+            budgets: Arc::new(RwLock::new(ApiBudgets::from_config(config))),
+        }
+    }
+
+    /// Hourly budgets calls are checked against right now
+    pub fn budgets(&self) -> ApiBudgets {
+        *self.budgets.read().unwrap()
+    }
+
+    /// Changes the overall hourly budget on this instance from the next call on, e.g.
+    /// to back off during a recreation.gov incident; 0 removes the cap. Restarting goes
+    /// back to `RECREATION_GOV_HOURLY_REQUEST_BUDGET`.
+    pub fn set_total_budget(&self, total: u32) {
+        self.budgets.write().unwrap().total = total;
+    }
+
+    /// Records an upstream API call. Failures are logged and never propagated,
+    /// so bookkeeping can't break the request that made the call.
+    pub async fn record(&self, record: ApiCallRecord) {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO api_call_ledger (endpoint, campground_id, status_code, duration_ms)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(record.endpoint)
+        .bind(&record.campground_id)
+        .bind(record.status_code.map(i32::from))
+        .bind(record.duration.as_millis().min(i32::MAX as u128) as i32)
+        .execute(&self.pool)
+        .await;
+
+        if let Err(e) = result {
+            log::warn!(
+                "⚠️ Failed to record API call to {} in ledger: {}",
+                record.endpoint,
+                e
+            );
+        }
+    }
+
+    /// Counts a call of `class` about to be made against the hourly budgets, unless the
+    /// last 60 minutes already used up the total budget or that of the class. The counts live in Postgres and are taken under an advisory lock, so they
+    /// hold across restarts and between instances. Returns whether the call may go out;
+    /// when the budgets can't be checked the call is allowed and a warning logged, so a
+    /// database hiccup doesn't stop polling.
+    pub async fn reserve_call(&self, class: EndpointClass) -> bool {
+        let budgets = self.budgets();
+        if budgets.total == 0 && budgets.of(class) == 0 {
+            return true;
+        }
+
+        match self.try_reserve_call(class, budgets).await {
+            Ok(reserved) => reserved,
+            Err(e) => {
+                log::warn!("⚠️ Failed to check the hourly API budget: {}", e);
+                true
+            }
+        }
+    }
+
+    async fn try_reserve_call(
+        &self,
+        class: EndpointClass,
+        budgets: ApiBudgets,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('api_budget_windows'))")
+            .execute(&mut *tx)
+            .await?;
+
+        let reserved = sqlx::query(
+            r#"
+            WITH used AS (
+                SELECT COALESCE(SUM(calls), 0) AS total,
+                       COALESCE(SUM(calls) FILTER (WHERE endpoint_class = $1), 0) AS class
+                FROM api_budget_windows
+                WHERE window_start > date_trunc('minute', NOW()) - INTERVAL '1 hour'
+            )
+            INSERT INTO api_budget_windows (endpoint_class, window_start, calls)
+            SELECT $1, date_trunc('minute', NOW()), 1
+            FROM used
+            WHERE ($2 = 0 OR used.total < $2) AND ($3 = 0 OR used.class < $3)
+            ON CONFLICT (endpoint_class, window_start)
+                DO UPDATE SET calls = api_budget_windows.calls + 1
+            "#,
+        )
+        .bind(class.as_str())
+        .bind(i64::from(budgets.total))
+        .bind(i64::from(budgets.of(class)))
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+
+        sqlx::query("DELETE FROM api_budget_windows WHERE window_start < NOW() - INTERVAL '1 day'")
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(reserved)
+    }
+
+    /// Reports the calls counted against the hourly budgets in the last 60 minutes.
+    pub async fn hourly_budget(&self) -> Result<HourlyApiBudget, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT endpoint_class, SUM(calls)::BIGINT AS calls
+            FROM api_budget_windows
+            WHERE window_start > date_trunc('minute', NOW()) - INTERVAL '1 hour'
+            GROUP BY endpoint_class
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let used = |class: Option<EndpointClass>| -> i64 {
+            rows.iter()
+                .filter(|row| {
+                    class.is_none_or(|class| {
+                        row.get::<String, _>("endpoint_class") == class.as_str()
+                    })
+                })
+                .map(|row| row.get::<i64, _>("calls"))
+                .sum()
+        };
+        let budgets = self.budgets();
+        let class_usage =
+            |class: EndpointClass| BudgetUsage::new(used(Some(class)), budgets.of(class));
+
+        Ok(HourlyApiBudget {
+            total: BudgetUsage::new(used(None), budgets.total),
+            availability: class_usage(EndpointClass::Availability),
+            on_demand: class_usage(EndpointClass::OnDemand),
+            details: class_usage(EndpointClass::Details),
+            search: class_usage(EndpointClass::Search),
+        })
+    }
+
+    /// Quarantines an upstream payload that failed validation, keeping the first issues
+    /// and a sample of the offending entries. Like [`record`](Self::record), failures are
+    /// only logged.
+    pub async fn quarantine(
+        &self,
+        endpoint: &'static str,
+        entity_id: &str,
+        month: NaiveDate,
+        issues: &[PayloadIssue],
+        sample: serde_json::Value,
+    ) {
+        let logged = &issues[..issues.len().min(QUARANTINE_SAMPLE_SIZE)];
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO quarantined_payloads (endpoint, entity_id, month, issue_count, issues, sample)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(endpoint)
+        .bind(entity_id)
+        .bind(month)
+        .bind(issues.len().min(i32::MAX as usize) as i32)
+        .bind(serde_json::to_value(logged).unwrap_or_default())
+        .bind(sample)
+        .execute(&self.pool)
+        .await;
+
+        if let Err(e) = result {
+            log::warn!(
+                "⚠️ Failed to quarantine {} payload for {}: {}",
+                endpoint,
+                entity_id,
+                e
+            );
+        }
+    }
+
+    /// Returns per-day usage for the last `days` days, grouped by endpoint and campground.
+    pub async fn daily_usage(&self, days: i32) -> Result<Vec<DailyApiUsage>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT
+                DATE(called_at) AS day, endpoint, campground_id,
+                COUNT(*) AS calls,
+                COUNT(*) FILTER (WHERE status_code IS NULL OR status_code >= 400) AS errors,
+                AVG(duration_ms)::FLOAT8 AS avg_duration_ms
+            FROM api_call_ledger
+            WHERE called_at >= NOW() - make_interval(days => $1)
+            GROUP BY DATE(called_at), endpoint, campground_id
+            ORDER BY day DESC, calls DESC
+            "#,
+        )
+        .bind(days)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let usage = rows
+            .into_iter()
+            .map(|row| DailyApiUsage {
+                day: row.get("day"),
+                endpoint: row.get("endpoint"),
+                campground_id: row.get("campground_id"),
+                calls: row.get("calls"),
+                errors: row.get("errors"),
+                avg_duration_ms: row.get("avg_duration_ms"),
+            })
+            .collect();
+
+        Ok(usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_endpoints() {
+        assert_eq!(
+            EndpointClass::of("recgov_availability_month"),
+            EndpointClass::Availability
+        );
+        assert_eq!(
+            EndpointClass::of("recgov_permit_month"),
+            EndpointClass::Availability
+        );
+        assert_eq!(
+            EndpointClass::of("ridb_recarea_facilities"),
+            EndpointClass::Search
+        );
+        assert_eq!(EndpointClass::of("ridb_facility"), EndpointClass::Details);
+        assert_eq!(
+            EndpointClass::on_demand("recgov_availability_month"),
+            EndpointClass::OnDemand
+        );
+        assert_eq!(
+            EndpointClass::on_demand("recgov_permit_month"),
+            EndpointClass::OnDemand
+        );
+        assert_eq!(
+            EndpointClass::on_demand("ridb_facility"),
+            EndpointClass::Details
+        );
+        assert_eq!(
+            EndpointClass::of("ridb_campsite_attributes"),
+            EndpointClass::Details
+        );
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "needs a Postgres server at DATABASE_URL"]
+    async fn on_demand_lookups_have_their_own_budget(pool: PgPool) {
+        let ledger = ApiCallLedger::from_config(
+            pool,
+            &AppConfig {
+                recreation_gov_hourly_availability_budget: 1,
+                recreation_gov_hourly_on_demand_budget: 1,
+                ..AppConfig::default()
+            },
+        );
+
+        assert!(ledger.reserve_call(EndpointClass::OnDemand).await);
+        assert!(!ledger.reserve_call(EndpointClass::OnDemand).await);
+
+        // Users spending their budget leaves polling its own
+        assert!(ledger.reserve_call(EndpointClass::Availability).await);
+        assert!(!ledger.reserve_call(EndpointClass::Availability).await);
+
+        let budget = ledger.hourly_budget().await.unwrap();
+        assert_eq!(budget.on_demand.used, 1);
+        assert_eq!(budget.availability.used, 1);
+    }
+}
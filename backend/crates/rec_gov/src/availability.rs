@@ -0,0 +1,582 @@
+use actix_web::{HttpResponse, Result, web};
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use i18n::{FluentArgs, current_locale, message, message_with};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use crate::client::{RecGovClient, RecGovError};
+use crate::drift::{
+    deserialize_id, deserialize_name, deserialize_prices, deserialize_quantities,
+    deserialize_site_count, deserialize_statuses, parse_night_key,
+};
+use crate::provider::{Provider, ProviderClient};
+use crate::reserve_california::ReserveCaliforniaClient;
+
+/// Longest date range a preview may cover
+const MAX_PREVIEW_NIGHTS: i64 = 93;
+
+/// Oldest cached data the live availability endpoint serves unless told otherwise
+const DEFAULT_LIVE_MAX_AGE_SECS: u64 = 5 * 60;
+
+/// Freshest data the live availability endpoint can be asked for, so callers can't make
+/// every request go upstream
+const MIN_LIVE_MAX_AGE_SECS: u64 = 60;
+
+/// Status recreation.gov reports for a bookable night
+pub const AVAILABLE: &str = "Available";
+
+/// Status lodging (cabins, lookouts) may report for a bookable night instead of `Available`
+pub const OPEN: &str = "Open";
+
+/// Status reported for nights derived from zero inventory
+pub(crate) const RESERVED: &str = "Reserved";
+
+/// Returns true if a night with this status can be booked.
+pub fn is_available_status(status: &str) -> bool {
+    status == AVAILABLE || status == OPEN
+}
+
+/// Statuses of nights the facility isn't taking reservations for at all, e.g. while it's
+/// closed for the season
+const CLOSED_STATUSES: &[&str] = &["Closed", "Not Reservable", "Not Reservable Management"];
+
+/// Returns true if a night with this status can't be reserved by anyone, as opposed to
+/// being booked, not released yet, or restricted.
+pub fn is_closed_status(status: &str) -> bool {
+    CLOSED_STATUSES.contains(&status)
+}
+
+/// Kind of inventory a scan watches. Recreation.gov lists cabins, fire lookouts, and group
+/// sites as campsites of a facility, told apart by their campsite type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FacilityType {
+    /// Standard tent or RV campsite
+    #[default]
+    Campsite,
+    /// Cabin, yurt, or other camping lodging
+    Cabin,
+    /// Fire lookout tower
+    Lookout,
+    /// Group campsite
+    GroupSite,
+}
+
+impl FacilityType {
+    /// Every facility type, in the order shown to users
+    pub const ALL: [FacilityType; 4] = [
+        FacilityType::Campsite,
+        FacilityType::Cabin,
+        FacilityType::Lookout,
+        FacilityType::GroupSite,
+    ];
+
+    /// Classifies a recreation.gov campsite type such as `CABIN NONELECTRIC` or
+    /// `GROUP STANDARD NONELECTRIC`.
+    pub fn from_campsite_type(campsite_type: Option<&str>) -> Self {
+        let campsite_type = campsite_type.unwrap_or_default().to_uppercase();
+
+        if campsite_type.contains("LOOKOUT") {
+            FacilityType::Lookout
+        } else if ["CABIN", "YURT", "LODGING", "SHELTER"]
+            .iter()
+            .any(|word| campsite_type.contains(word))
+        {
+            FacilityType::Cabin
+        } else if campsite_type.starts_with("GROUP") {
+            FacilityType::GroupSite
+        } else {
+            FacilityType::Campsite
+        }
+    }
+
+    /// Parses the value stored in `user_scans.facility_type`.
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+
+    /// Value stored in `user_scans.facility_type`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FacilityType::Campsite => "campsite",
+            FacilityType::Cabin => "cabin",
+            FacilityType::Lookout => "lookout",
+            FacilityType::GroupSite => "group_site",
+        }
+    }
+
+    /// Name of one unit of this inventory in notification text
+    pub fn noun(&self) -> &'static str {
+        match self {
+            FacilityType::Campsite => "campsite",
+            FacilityType::Cabin => "cabin",
+            FacilityType::Lookout => "fire lookout",
+            FacilityType::GroupSite => "group site",
+        }
+    }
+
+    /// Alert text for a scan of this type whose dates opened up.
+    pub fn alert_message(
+        &self,
+        campground_name: &str,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+    ) -> String {
+        let dates = format!(
+            "{} to {}",
+            check_in_date.format("%b %-d"),
+            check_out_date.format("%b %-d")
+        );
+
+        match self {
+            FacilityType::Campsite => {
+                format!("A campsite opened up at {} for {}.", campground_name, dates)
+            }
+            FacilityType::Cabin => format!(
+                "A cabin opened up at {} for {}. Cabins rarely come back, so book soon.",
+                campground_name, dates
+            ),
+            FacilityType::Lookout => format!(
+                "The {} fire lookout is available for {}. Lookouts book within minutes.",
+                campground_name, dates
+            ),
+            FacilityType::GroupSite => format!(
+                "A group site opened up at {} for {}. Check its group size limits before booking.",
+                campground_name, dates
+            ),
+        }
+    }
+}
+
+/// One campsite's entry in recreation.gov's monthly availability response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampsiteAvailability {
+    /// Recreation.gov campsite ID
+    #[serde(deserialize_with = "deserialize_id")]
+    pub campsite_id: String,
+    /// Site name or number
+    #[serde(default, deserialize_with = "deserialize_name")]
+    pub site: String,
+    /// Campground loop the site belongs to
+    #[serde(rename = "loop", default)]
+    pub loop_name: Option<String>,
+    /// Site type (e.g. `STANDARD NONELECTRIC`)
+    #[serde(default)]
+    pub campsite_type: Option<String>,
+    /// Status per night, keyed by `YYYY-MM-DDT00:00:00Z`. Known statuses are stored in
+    /// their usual spelling, whatever case they arrived in.
+    #[serde(default, deserialize_with = "deserialize_statuses")]
+    pub availabilities: BTreeMap<String, String>,
+    /// Units left per night, keyed like `availabilities`. Group sites and some lodging
+    /// report inventory counts here instead of a status.
+    #[serde(default, deserialize_with = "deserialize_quantities")]
+    pub quantities: BTreeMap<String, i32>,
+    /// Kind of stay (e.g. `Overnight`, `Day`)
+    #[serde(default)]
+    pub type_of_use: Option<String>,
+    /// Price per night in dollars, keyed like `availabilities`. Only some responses
+    /// include prices, and then not always for every night.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        deserialize_with = "deserialize_prices"
+    )]
+    pub prices: BTreeMap<String, f64>,
+}
+
+impl CampsiteAvailability {
+    /// Returns the status of each night, skipping keys that aren't dates. Nights only
+    /// reported as quantities are `Available` while units are left.
+    pub fn nights(&self) -> impl Iterator<Item = (NaiveDate, &str)> {
+        let statuses = self
+            .availabilities
+            .iter()
+            .map(|(key, status)| (key, status.as_str()));
+        let quantities = self
+            .quantities
+            .iter()
+            .filter(|(key, _)| !self.availabilities.contains_key(*key))
+            .map(|(key, quantity)| (key, if *quantity > 0 { AVAILABLE } else { RESERVED }));
+
+        statuses.chain(quantities).filter_map(|(key, status)| {
+            let date = parse_night_key(key)?;
+            Some((date, status))
+        })
+    }
+
+    /// Returns true if every night of `check_in..check_out` is reported and bookable.
+    pub fn is_open_for(&self, check_in: NaiveDate, check_out: NaiveDate) -> bool {
+        let open: BTreeMap<NaiveDate, bool> = self
+            .nights()
+            .map(|(date, status)| (date, is_available_status(status)))
+            .collect();
+
+        check_in
+            .iter_days()
+            .take_while(|date| *date < check_out)
+            .all(|date| open.get(&date).copied().unwrap_or(false))
+    }
+
+    /// Returns the price of each night reported with one, skipping keys that aren't dates
+    pub fn nightly_prices(&self) -> impl Iterator<Item = (NaiveDate, f64)> {
+        self.prices
+            .iter()
+            .filter_map(|(key, price)| Some((parse_night_key(key)?, *price)))
+    }
+
+    /// Highest price reported for a night of `check_in..check_out`, or None when no
+    /// night of the stay has a price
+    pub fn highest_price(&self, check_in: NaiveDate, check_out: NaiveDate) -> Option<f64> {
+        self.nightly_prices()
+            .filter(|(date, _)| (check_in..check_out).contains(date))
+            .map(|(_, price)| price)
+            .reduce(f64::max)
+    }
+
+    /// Kind of inventory this site is
+    pub fn facility_type(&self) -> FacilityType {
+        FacilityType::from_campsite_type(self.campsite_type.as_deref())
+    }
+}
+
+/// Recreation.gov's monthly availability response
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MonthAvailability {
+    /// Campsites keyed by campsite ID
+    #[serde(default)]
+    pub campsites: HashMap<String, CampsiteAvailability>,
+    /// Number of campsites recreation.gov says the campground has
+    #[serde(default, deserialize_with = "deserialize_site_count")]
+    pub count: Option<usize>,
+}
+
+/// Campsite availability with how fresh it is
+#[derive(Debug)]
+pub struct FetchedAvailability {
+    /// Availability of every campsite
+    pub sites: Vec<CampsiteAvailability>,
+    /// Age of the oldest month the sites were built from
+    pub age: Duration,
+    /// Whether every month came from the cache
+    pub from_cache: bool,
+}
+
+impl Default for FetchedAvailability {
+    fn default() -> Self {
+        Self {
+            sites: Vec::new(),
+            age: Duration::ZERO,
+            from_cache: true,
+        }
+    }
+}
+
+/// Query parameters for the availability preview
+#[derive(Debug, Deserialize)]
+pub struct AvailabilityPreviewQuery {
+    /// First night to show
+    pub start: NaiveDate,
+    /// Day after the last night to show (the check-out date)
+    pub end: NaiveDate,
+}
+
+/// Query parameters for live availability
+#[derive(Debug, Deserialize)]
+pub struct LiveAvailabilityQuery {
+    /// First night to show
+    pub start: NaiveDate,
+    /// Day after the last night to show (the check-out date)
+    pub end: NaiveDate,
+    /// Oldest cached data to accept, in seconds; older data is fetched again. Values under
+    /// a minute are raised to one.
+    pub max_age_secs: Option<u64>,
+}
+
+/// Availability calendar with how fresh its data is
+#[derive(Debug, Serialize)]
+pub struct LiveAvailability {
+    /// The calendar
+    #[serde(flatten)]
+    pub preview: AvailabilityPreview,
+    /// Age of the oldest data the calendar was built from, in seconds
+    pub data_age_secs: u64,
+    /// Whether the calendar was served entirely from the cache
+    pub from_cache: bool,
+}
+
+/// Night-by-night status of one campsite
+#[derive(Debug, Serialize)]
+pub struct SiteCalendar {
+    /// Recreation.gov campsite ID
+    pub campsite_id: String,
+    /// Site name or number
+    pub site: String,
+    /// Campground loop
+    pub loop_name: Option<String>,
+    /// Site type
+    pub campsite_type: Option<String>,
+    /// Kind of inventory (campsite, cabin, lookout, group site)
+    pub facility_type: FacilityType,
+    /// Status of each night in the range
+    pub nights: BTreeMap<NaiveDate, String>,
+    /// Price of each night in the range in dollars, for nights reported with one
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub nightly_prices: BTreeMap<NaiveDate, f64>,
+}
+
+/// Current availability calendar of a campground
+#[derive(Debug, Serialize)]
+pub struct AvailabilityPreview {
+    /// RIDB facility ID
+    pub campground_id: String,
+    /// First night shown
+    pub start: NaiveDate,
+    /// Check-out date
+    pub end: NaiveDate,
+    /// Number of available sites per night
+    pub available_sites_per_night: BTreeMap<NaiveDate, usize>,
+    /// Calendar of every campsite
+    pub campsites: Vec<SiteCalendar>,
+    /// When the preview was built
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Returns the first day of each month overlapping the nights `start..end`.
+pub fn months_between(start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
+    let mut months = Vec::new();
+    let mut month = start.with_day(1).unwrap_or(start);
+
+    while month < end {
+        months.push(month);
+        month = month + Months::new(1);
+    }
+
+    months
+}
+
+/// Builds the calendar for the nights `start..end` from per-site availability.
+pub fn build_preview(
+    campground_id: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+    sites: Vec<CampsiteAvailability>,
+) -> AvailabilityPreview {
+    let mut available_sites_per_night: BTreeMap<NaiveDate, usize> = start
+        .iter_days()
+        .take_while(|d| *d < end)
+        .map(|d| (d, 0))
+        .collect();
+
+    let mut campsites: Vec<SiteCalendar> = sites
+        .into_iter()
+        .map(|site| {
+            let nights: BTreeMap<NaiveDate, String> = site
+                .nights()
+                .filter(|(date, _)| (start..end).contains(date))
+                .map(|(date, status)| (date, status.to_string()))
+                .collect();
+
+            for (date, status) in &nights {
+                if is_available_status(status) {
+                    *available_sites_per_night.entry(*date).or_default() += 1;
+                }
+            }
+
+            let nightly_prices = site
+                .nightly_prices()
+                .filter(|(date, _)| (start..end).contains(date))
+                .collect();
+
+            SiteCalendar {
+                facility_type: site.facility_type(),
+                campsite_id: site.campsite_id,
+                site: site.site,
+                loop_name: site.loop_name,
+                campsite_type: site.campsite_type,
+                nights,
+                nightly_prices,
+            }
+        })
+        .collect();
+
+    campsites.sort_by(|a, b| (&a.loop_name, &a.site).cmp(&(&b.loop_name, &b.site)));
+
+    AvailabilityPreview {
+        campground_id: campground_id.to_string(),
+        start,
+        end,
+        available_sites_per_night,
+        campsites,
+        generated_at: Utc::now(),
+    }
+}
+
+/// Handler showing a campground's current availability for a date range, so users can
+/// check whether a scan is needed before creating one. ReserveCalifornia campgrounds
+/// (`rc-` IDs) are looked up there.
+pub async fn get_campground_availability(
+    client: web::Data<RecGovClient>,
+    reserve_california: web::Data<ReserveCaliforniaClient>,
+    path: web::Path<String>,
+    query: web::Query<AvailabilityPreviewQuery>,
+) -> Result<HttpResponse> {
+    let campground_id = path.into_inner();
+    let AvailabilityPreviewQuery { start, end } = query.into_inner();
+
+    if let Some(invalid) = invalid_preview_range(start, end) {
+        return Ok(invalid);
+    }
+
+    let sites = match Provider::of_campground(&campground_id) {
+        Provider::RecreationGov => {
+            client
+                .on_demand()
+                .campground_availability(&campground_id, start, end)
+                .await
+        }
+        Provider::ReserveCalifornia => {
+            reserve_california
+                .campground_availability(&campground_id, start, end)
+                .await
+        }
+    };
+
+    match sites {
+        Ok(sites) => Ok(HttpResponse::Ok().json(build_preview(&campground_id, start, end, sites))),
+        Err(e) => Ok(availability_error(&campground_id, e)),
+    }
+}
+
+/// Handler serving a campground's availability from the cache when it is at most
+/// `max_age_secs` old (at least a minute), and fetching it from recreation.gov otherwise.
+/// Concurrent requests for the same campground share one upstream fetch, which counts
+/// against the on-demand rate limit. ReserveCalifornia campgrounds are always fetched.
+pub async fn get_live_campground_availability(
+    client: web::Data<RecGovClient>,
+    reserve_california: web::Data<ReserveCaliforniaClient>,
+    path: web::Path<String>,
+    query: web::Query<LiveAvailabilityQuery>,
+) -> Result<HttpResponse> {
+    let campground_id = path.into_inner();
+    let LiveAvailabilityQuery {
+        start,
+        end,
+        max_age_secs,
+    } = query.into_inner();
+
+    if let Some(invalid) = invalid_preview_range(start, end) {
+        return Ok(invalid);
+    }
+
+    let max_age = Duration::from_secs(
+        max_age_secs
+            .unwrap_or(DEFAULT_LIVE_MAX_AGE_SECS)
+            .max(MIN_LIVE_MAX_AGE_SECS),
+    );
+
+    let fetched = match Provider::of_campground(&campground_id) {
+        Provider::RecreationGov => {
+            client
+                .on_demand()
+                .campground_availability_within(&campground_id, start, end, max_age)
+                .await
+        }
+        Provider::ReserveCalifornia => reserve_california
+            .campground_availability(&campground_id, start, end)
+            .await
+            .map(|sites| FetchedAvailability {
+                sites,
+                from_cache: false,
+                ..Default::default()
+            }),
+    };
+
+    match fetched {
+        Ok(fetched) => Ok(HttpResponse::Ok().json(LiveAvailability {
+            preview: build_preview(&campground_id, start, end, fetched.sites),
+            data_age_secs: fetched.age.as_secs(),
+            from_cache: fetched.from_cache,
+        })),
+        Err(e) => Ok(availability_error(&campground_id, e)),
+    }
+}
+
+/// Rejects date ranges a preview can't cover.
+fn invalid_preview_range(start: NaiveDate, end: NaiveDate) -> Option<HttpResponse> {
+    let locale = current_locale();
+
+    if end <= start {
+        return Some(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "invalid_date_range",
+            "message": message(locale, "preview-end-before-start")
+        })));
+    }
+    if (end - start).num_days() > MAX_PREVIEW_NIGHTS {
+        let mut args = FluentArgs::new();
+        args.set("max", MAX_PREVIEW_NIGHTS);
+
+        return Some(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "invalid_date_range",
+            "message": message_with(locale, "preview-too-long", &args)
+        })));
+    }
+
+    None
+}
+
+/// Response for a failed availability lookup
+fn availability_error(campground_id: &str, error: RecGovError) -> HttpResponse {
+    let locale = current_locale();
+
+    match error {
+        RecGovError::RateLimited => {
+            let mut args = FluentArgs::new();
+            args.set("minutes", 1);
+
+            HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", "60"))
+                .json(serde_json::json!({
+                    "error": "rate_limited",
+                    "message": message_with(locale, "rate-limited", &args)
+                }))
+        }
+        e => {
+            log::error!(
+                "❌ Error fetching availability for campground {}: {}",
+                campground_id,
+                e
+            );
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "availability_load_failed",
+                "message": message(locale, "availability-load-failed")
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+    use i18n::{Locale, with_locale};
+
+    #[tokio::test]
+    async fn preview_range_errors_are_written_in_the_request_locale() {
+        let start = NaiveDate::from_ymd_opt(2026, 7, 1).unwrap();
+        let response = with_locale(Locale::Spanish, async {
+            invalid_preview_range(start, start)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body["error"], "invalid_date_range");
+        assert_eq!(
+            body["message"],
+            "La fecha final debe ser posterior a la inicial"
+        );
+    }
+}
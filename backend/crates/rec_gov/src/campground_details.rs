@@ -0,0 +1,171 @@
+use actix_web::{HttpResponse, Result, web};
+use chrono::{DateTime, Duration, Utc};
+use i18n::{current_locale, message};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+
+use crate::client::{RecGovClient, RecGovError};
+use crate::geocoding::Geocoder;
+use crate::ridb::{Campsite, EntityMedia, Facility};
+
+/// How long cached campground details are served before RIDB is asked again
+const DETAILS_CACHE_TTL_HOURS: i64 = 24;
+
+/// Everything the scan-creation UI shows about a campground before a scan is created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampgroundDetails {
+    /// Facility information
+    pub facility: Facility,
+    /// Campsites with their type and loop
+    pub campsites: Vec<Campsite>,
+    /// Photos of the campground, primary photo first
+    pub photos: Vec<EntityMedia>,
+    /// When the details were fetched from RIDB
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Returns the details of a campground, served from the campgrounds table while fresh.
+/// Returns `None` if RIDB doesn't know the facility.
+pub async fn campground_details(
+    pool: &PgPool,
+    client: &RecGovClient,
+    campground_id: &str,
+) -> Result<Option<CampgroundDetails>, RecGovError> {
+    match cached_details(pool, campground_id).await {
+        Ok(Some(details))
+            if details.fetched_at > Utc::now() - Duration::hours(DETAILS_CACHE_TTL_HOURS) =>
+        {
+            return Ok(Some(details));
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!(
+            "⚠️ Failed to read cached details for campground {}: {}",
+            campground_id,
+            e
+        ),
+    }
+
+    let Some(facility) = client.facility(campground_id).await? else {
+        return Ok(None);
+    };
+
+    let campsites = client.facility_campsites(campground_id).await?;
+    let mut photos: Vec<EntityMedia> = client
+        .facility_media(campground_id)
+        .await?
+        .into_iter()
+        .filter(EntityMedia::is_image)
+        .collect();
+    photos.sort_by_key(|photo| !photo.is_primary);
+
+    let details = CampgroundDetails {
+        facility,
+        campsites,
+        photos,
+        fetched_at: Utc::now(),
+    };
+
+    // A failed cache write shouldn't fail the request
+    if let Err(e) = store_details(pool, &details).await {
+        log::warn!(
+            "⚠️ Failed to cache details for campground {}: {}",
+            campground_id,
+            e
+        );
+    }
+
+    Ok(Some(details))
+}
+
+/// Reads previously fetched details from the campgrounds table.
+async fn cached_details(
+    pool: &PgPool,
+    campground_id: &str,
+) -> Result<Option<CampgroundDetails>, sqlx::Error> {
+    let row = sqlx::query("SELECT details FROM campgrounds WHERE id = $1 AND details IS NOT NULL")
+        .bind(campground_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.and_then(|row| serde_json::from_value(row.get("details")).ok()))
+}
+
+/// Saves fetched details in the campgrounds table, adding the campground if needed.
+async fn store_details(pool: &PgPool, details: &CampgroundDetails) -> Result<(), sqlx::Error> {
+    let facility = &details.facility;
+    let (latitude, longitude) = facility.coordinates().unzip();
+    let value = serde_json::to_value(details).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO campgrounds (
+            id, name, state, latitude, longitude, total_sites, is_reservable,
+            description, details, details_updated_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, true), $8, $9, $10)
+        ON CONFLICT (id) DO UPDATE SET
+            total_sites = EXCLUDED.total_sites,
+            description = EXCLUDED.description,
+            state = COALESCE(campgrounds.state, EXCLUDED.state),
+            latitude = COALESCE(campgrounds.latitude, EXCLUDED.latitude),
+            longitude = COALESCE(campgrounds.longitude, EXCLUDED.longitude),
+            details = EXCLUDED.details,
+            details_updated_at = EXCLUDED.details_updated_at,
+            last_updated = NOW()
+        "#,
+    )
+    .bind(&facility.id)
+    .bind(&facility.name)
+    .bind(&facility.state)
+    .bind(latitude)
+    .bind(longitude)
+    .bind(details.campsites.len() as i32)
+    .bind(facility.reservable)
+    .bind(&facility.description)
+    .bind(value)
+    .bind(details.fetched_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Handler returning a campground's facility information, campsites, and photos, with
+/// where it is relative to the nearest town when a geocoder is configured
+pub async fn get_campground_details(
+    pool: web::Data<PgPool>,
+    client: web::Data<RecGovClient>,
+    geocoder: web::Data<Geocoder>,
+    path: web::Path<String>,
+) -> Result<HttpResponse> {
+    let campground_id = path.into_inner();
+
+    match campground_details(&pool, &client, &campground_id).await {
+        Ok(Some(mut details)) => {
+            let facility = &mut details.facility;
+            if facility.location_description.is_none()
+                && let Some((latitude, longitude)) = facility.coordinates()
+            {
+                facility.location_description =
+                    geocoder.describe_location(latitude, longitude).await;
+            }
+
+            Ok(HttpResponse::Ok().json(details))
+        }
+        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "campground_not_found",
+            "message": message(current_locale(), "campground-not-found")
+        }))),
+        Err(e) => {
+            log::error!(
+                "❌ Error fetching details for campground {}: {}",
+                campground_id,
+                e
+            );
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "campground_load_failed",
+                "message": message(current_locale(), "campground-load-failed")
+            })))
+        }
+    }
+}
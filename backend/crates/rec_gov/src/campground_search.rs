@@ -0,0 +1,262 @@
+use actix_web::{HttpResponse, Result, web};
+use i18n::{current_locale, message};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+
+use crate::client::{RecGovClient, RecGovError};
+use crate::provider::Provider;
+use crate::ridb::Facility;
+
+/// Results returned when the request doesn't ask for a number
+const DEFAULT_SEARCH_LIMIT: i64 = 10;
+/// Most results one search returns
+const MAX_SEARCH_LIMIT: i64 = 25;
+/// How closely a name must match the query to count despite typos (0 to 1)
+const WORD_SIMILARITY_THRESHOLD: f32 = 0.4;
+
+/// Query string of a campground search
+#[derive(Debug, Deserialize)]
+pub struct CampgroundSearchQuery {
+    /// What the user typed so far
+    pub q: String,
+    /// Number of results, at most 25
+    pub limit: Option<i64>,
+}
+
+/// Where search results came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchSource {
+    /// Campgrounds already cached in the database
+    Cache,
+    /// RIDB, queried because nothing cached matched
+    Ridb,
+}
+
+/// A campground matching a search
+#[derive(Debug, Clone, Serialize)]
+pub struct CampgroundSearchResult {
+    /// Campground ID
+    pub id: String,
+    /// Campground name
+    pub name: String,
+    /// Two-letter state code
+    pub state: Option<String>,
+    /// Recreation area the campground is in, when known
+    pub recarea_name: Option<String>,
+    /// Reservation system the campground is booked through
+    pub provider: String,
+    /// Whether the campground takes reservations
+    pub reservable: bool,
+    /// Latitude, when known
+    pub latitude: Option<f64>,
+    /// Longitude, when known
+    pub longitude: Option<f64>,
+}
+
+impl From<&Facility> for CampgroundSearchResult {
+    fn from(facility: &Facility) -> Self {
+        let (latitude, longitude) = facility.coordinates().unzip();
+
+        CampgroundSearchResult {
+            id: facility.id.clone(),
+            name: facility.name.clone(),
+            state: facility.state.clone(),
+            recarea_name: None,
+            provider: Provider::RecreationGov.as_str().to_string(),
+            reservable: facility.reservable.unwrap_or(true),
+            latitude,
+            longitude,
+        }
+    }
+}
+
+/// Response of `GET /api/campgrounds/search`
+#[derive(Debug, Clone, Serialize)]
+pub struct CampgroundSearchResponse {
+    /// Matching campgrounds, best match first
+    pub campgrounds: Vec<CampgroundSearchResult>,
+    /// Whether the results came from the cache or RIDB
+    pub source: SearchSource,
+}
+
+/// Turns what the user typed into a prefix tsquery, so "upper pi" matches
+/// "Upper Pines". Returns `None` when the query has no words.
+pub fn prefix_tsquery(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("{}:*", word.to_lowercase()))
+        .collect();
+
+    (!terms.is_empty()).then(|| terms.join(" & "))
+}
+
+/// Searches the cached campgrounds by name, recreation area, state and description,
+/// tolerating typos in the name.
+pub async fn search_cached_campgrounds(
+    pool: &PgPool,
+    query: &str,
+    limit: i64,
+) -> Result<Vec<CampgroundSearchResult>, sqlx::Error> {
+    let Some(tsquery) = prefix_tsquery(query) else {
+        return Ok(Vec::new());
+    };
+
+    let rows = sqlx::query(
+        r#"
+        SELECT c.id, c.name, c.state, c.parent_recarea_name, c.provider,
+               COALESCE(c.is_reservable, true) AS is_reservable,
+               c.latitude::float8 AS latitude, c.longitude::float8 AS longitude
+        FROM campgrounds c, to_tsquery('english', $1) AS query
+        WHERE COALESCE(c.is_active, true)
+          AND (c.search_vector @@ query OR word_similarity($2, c.name) >= $3)
+        ORDER BY ts_rank(c.search_vector, query) + word_similarity($2, c.name) DESC, c.name
+        LIMIT $4
+        "#,
+    )
+    .bind(tsquery)
+    .bind(query)
+    .bind(WORD_SIMILARITY_THRESHOLD)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| CampgroundSearchResult {
+            id: row.get("id"),
+            name: row.get("name"),
+            state: row.get("state"),
+            recarea_name: row.get("parent_recarea_name"),
+            provider: row.get("provider"),
+            reservable: row.get("is_reservable"),
+            latitude: row.get("latitude"),
+            longitude: row.get("longitude"),
+        })
+        .collect())
+}
+
+/// Saves facilities found on RIDB in the campgrounds table, so the next search for
+/// them is served from the cache.
+async fn cache_facilities(pool: &PgPool, facilities: &[Facility]) -> Result<(), sqlx::Error> {
+    for facility in facilities {
+        let (latitude, longitude) = facility.coordinates().unzip();
+
+        sqlx::query(
+            r#"
+            INSERT INTO campgrounds (
+                id, name, state, latitude, longitude, is_reservable, description
+            )
+            VALUES ($1, $2, $3, $4, $5, COALESCE($6, true), $7)
+            ON CONFLICT (id) DO UPDATE SET
+                name = EXCLUDED.name,
+                state = COALESCE(campgrounds.state, EXCLUDED.state),
+                latitude = COALESCE(campgrounds.latitude, EXCLUDED.latitude),
+                longitude = COALESCE(campgrounds.longitude, EXCLUDED.longitude),
+                description = COALESCE(campgrounds.description, EXCLUDED.description),
+                last_updated = NOW()
+            "#,
+        )
+        .bind(&facility.id)
+        .bind(&facility.name)
+        .bind(&facility.state)
+        .bind(latitude)
+        .bind(longitude)
+        .bind(facility.reservable)
+        .bind(&facility.description)
+        .execute(pool)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Searches campgrounds in the database first, only asking RIDB when nothing cached
+/// matches (or the database can't be searched). Campgrounds RIDB returns are cached
+/// for later searches.
+pub async fn search_campgrounds(
+    pool: &PgPool,
+    client: &RecGovClient,
+    query: &str,
+    limit: i64,
+) -> Result<CampgroundSearchResponse, RecGovError> {
+    let cached = search_cached_campgrounds(pool, query, limit)
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("⚠️ Cached campground search failed for '{}': {}", query, e);
+            Vec::new()
+        });
+    if !cached.is_empty() {
+        log::debug!("📦 {} cached campgrounds match '{}'", cached.len(), query);
+        return Ok(CampgroundSearchResponse {
+            campgrounds: cached,
+            source: SearchSource::Cache,
+        });
+    }
+
+    log::debug!("🌐 No cached campground matches '{}', asking RIDB", query);
+    let facilities = client.search_facilities(query).await?;
+
+    if let Err(e) = cache_facilities(pool, &facilities).await {
+        log::warn!("⚠️ Failed to cache search results for '{}': {}", query, e);
+    }
+
+    Ok(CampgroundSearchResponse {
+        campgrounds: facilities
+            .iter()
+            .take(limit as usize)
+            .map(CampgroundSearchResult::from)
+            .collect(),
+        source: SearchSource::Ridb,
+    })
+}
+
+/// Handler for campground autocomplete, served from the database when possible
+pub async fn campground_search(
+    pool: web::Data<PgPool>,
+    client: web::Data<RecGovClient>,
+    query: web::Query<CampgroundSearchQuery>,
+) -> Result<HttpResponse> {
+    let q = query.q.trim();
+    if q.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "missing_query",
+            "message": message(current_locale(), "missing-search-query")
+        })));
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+
+    match search_campgrounds(&pool, &client, q, limit).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) => {
+            log::error!("❌ Error searching campgrounds for '{}': {}", q, e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "search_failed",
+                "message": message(current_locale(), "search-failed")
+            })))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_prefix_tsqueries() {
+        assert_eq!(
+            prefix_tsquery("Upper pi").as_deref(),
+            Some("upper:* & pi:*")
+        );
+        assert_eq!(
+            prefix_tsquery("  o'neil's (CA) ").as_deref(),
+            Some("o:* & neil:* & s:* & ca:*")
+        );
+        assert_eq!(prefix_tsquery(" & | ! "), None);
+    }
+}
@@ -0,0 +1,1411 @@
+use app_config::AppConfig;
+use chrono::{DateTime, NaiveDate, Utc};
+use futures_util::lock::Mutex as AsyncMutex;
+use reqwest::Client;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::api_ledger::{ApiCallLedger, ApiCallRecord, EndpointClass};
+use crate::availability::{
+    CampsiteAvailability, FetchedAvailability, MonthAvailability, months_between,
+};
+use crate::fixtures::{FixtureMode, ResponseFixtures};
+use crate::pacing::{Pacer, PacingProfile};
+use crate::permits::{PermitDivisionAvailability, PermitMonthAvailability, PermitResponse};
+use crate::proxy::{ProxyHealth, ProxyLease, ProxyPool};
+use crate::request_id::{REQUEST_ID_HEADER, current_request_id};
+use crate::ridb::{Campsite, CampsiteAttribute, EntityMedia, Facility, RecArea, RidbResponse};
+use crate::sandbox::{
+    sandbox_availability, sandbox_campsite_attributes, sandbox_campsites, sandbox_facilities,
+    sandbox_facility, sandbox_permit_availability,
+};
+use crate::ttl_cache::TtlCache;
+use crate::validation::{
+    PayloadIssue, issue_sample, raw_payload_sample, validate_month_availability,
+    validate_permit_month,
+};
+
+/// Base URL of the RIDB API
+const RIDB_BASE_URL: &str = "https://ridb.recreation.gov/api/v1";
+
+/// Base URL of the recreation.gov API used by its website
+const RECGOV_BASE_URL: &str = "https://www.recreation.gov/api";
+
+/// Most availability requests on-demand lookups send upstream per minute, so users can't
+/// crowd out polling
+const ON_DEMAND_REQUESTS_PER_MINUTE: usize = 30;
+
+/// How long facility search results are reused
+const SEARCH_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Most facility searches kept in memory
+const SEARCH_CACHE_CAPACITY: usize = 256;
+
+/// Page size for paged RIDB listings (the API maximum)
+const RIDB_PAGE_SIZE: usize = 50;
+
+/// Upper bound on pages fetched for one listing
+const RIDB_MAX_PAGES: usize = 20;
+
+/// How long a recreation.gov reachability probe is reused, so frequent health checks
+/// don't turn into upstream traffic
+const REACHABILITY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// How long a reachability probe waits for recreation.gov
+const REACHABILITY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Cached facility search results keyed by normalized query.
+type SearchCache = Arc<Mutex<TtlCache<String, Vec<Facility>>>>;
+
+/// Cached monthly availability keyed by campground and first day of the month. Months
+/// past their time to live stay, so they can be revalidated instead of fetched again.
+type AvailabilityCache = Arc<Mutex<HashMap<(String, NaiveDate), CachedMonth>>>;
+
+/// `ETag` and `Last-Modified` of a response, sent back as `If-None-Match` and
+/// `If-Modified-Since` to ask recreation.gov whether it changed
+#[derive(Debug, Clone, Default)]
+struct CacheValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+
+        Self {
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Month of availability as fetched, with the validators to revalidate it
+#[derive(Clone)]
+struct CachedMonth {
+    fetched_at: Instant,
+    availability: MonthAvailability,
+    validators: CacheValidators,
+}
+
+/// Body of an upstream response, or word that the copy we have is still current
+enum UpstreamBody {
+    /// recreation.gov answered `304 Not Modified`
+    NotModified,
+    /// The response body with its validators
+    Body(String, CacheValidators),
+}
+
+/// Upstream monthly availability fetches in progress, keyed like the cache; concurrent
+/// requests for the same month wait on its lock instead of fetching again.
+type AvailabilityFetches = Arc<Mutex<HashMap<(String, NaiveDate), Arc<AsyncMutex<()>>>>>;
+
+/// Errors returned by the recreation.gov client
+#[derive(Debug, thiserror::Error)]
+pub enum RecGovError {
+    /// No API key was configured
+    #[error("RECREATION_GOV_API_KEY is not configured")]
+    MissingApiKey,
+
+    /// The request timed out, couldn't connect, or the connection dropped
+    #[error("Recreation.gov request timed out or failed to connect: {0}")]
+    Timeout(#[source] reqwest::Error),
+
+    /// Recreation.gov answered with an error status, other than refusing us
+    #[error("Recreation.gov request failed with HTTP {status}: {source}")]
+    Http {
+        /// HTTP status returned
+        status: u16,
+        /// Underlying error
+        source: reqwest::Error,
+    },
+
+    /// Recreation.gov refused to serve us (HTTP 403 or 429), e.g. after flagging our
+    /// traffic as a bot
+    #[error("Recreation.gov blocked the request with HTTP {status}")]
+    Blocked {
+        /// HTTP status returned
+        status: u16,
+    },
+
+    /// The response body couldn't be read as JSON
+    #[error("Recreation.gov response couldn't be decoded: {0}")]
+    Decode(#[source] reqwest::Error),
+
+    /// Too many on-demand requests were made recently
+    #[error("Recreation.gov request rate limit reached")]
+    RateLimited,
+
+    /// The response body couldn't be parsed into the shape expected. Availability
+    /// payloads that fail this way are quarantined as received.
+    #[error("Recreation.gov returned data in an unexpected format: {message}")]
+    DataFormat {
+        /// Parser error
+        message: String,
+        /// Response body as received
+        body: String,
+    },
+
+    /// Replaying, and no response was captured for the call
+    #[error("No recreation.gov fixture captured at {0}")]
+    MissingFixture(String),
+
+    /// The response described impossible availability and was quarantined
+    #[error("Recreation.gov returned invalid availability ({issues} issues, first: {first})")]
+    InvalidPayload {
+        /// Number of issues found
+        issues: usize,
+        /// First issue found
+        first: String,
+    },
+}
+
+impl From<reqwest::Error> for RecGovError {
+    fn from(error: reqwest::Error) -> Self {
+        match error.status() {
+            Some(status) if status == 403 || status == 429 => RecGovError::Blocked {
+                status: status.as_u16(),
+            },
+            Some(status) => RecGovError::Http {
+                status: status.as_u16(),
+                source: error,
+            },
+            None if error.is_decode() => RecGovError::Decode(error),
+            None => RecGovError::Timeout(error),
+        }
+    }
+}
+
+/// Kind of failure behind a [`RecGovError`], for telemetry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// Timed out, couldn't connect, or the connection dropped
+    Timeout,
+    /// Answered with an error status
+    Http,
+    /// Answered with a body that couldn't be parsed or describes impossible availability
+    Decode,
+    /// Refused to serve us, or our own request budget ran out
+    Blocked,
+    /// Not configured to make the call (no API key, no fixture)
+    Config,
+}
+
+impl FailureKind {
+    /// Name of the kind, as stored with polling jobs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureKind::Timeout => "timeout",
+            FailureKind::Http => "http",
+            FailureKind::Decode => "decode",
+            FailureKind::Blocked => "blocked",
+            FailureKind::Config => "config",
+        }
+    }
+}
+
+/// How soon a failed call is worth making again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Retry {
+    /// A blip: retry within a minute or two
+    Soon,
+    /// Upstream trouble that may take a while to clear: back off as failures add up
+    Backoff,
+    /// Retrying won't help for a while (blocked, gone, misconfigured): pause
+    Pause,
+}
+
+impl RecGovError {
+    /// Kind of failure, for telemetry
+    pub fn kind(&self) -> FailureKind {
+        match self {
+            RecGovError::Timeout(_) => FailureKind::Timeout,
+            RecGovError::Http { .. } => FailureKind::Http,
+            RecGovError::Decode(_)
+            | RecGovError::DataFormat { .. }
+            | RecGovError::InvalidPayload { .. } => FailureKind::Decode,
+            RecGovError::Blocked { .. } | RecGovError::RateLimited => FailureKind::Blocked,
+            RecGovError::MissingApiKey | RecGovError::MissingFixture(_) => FailureKind::Config,
+        }
+    }
+
+    /// HTTP status recreation.gov answered with, if it answered with an error
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            RecGovError::Http { status, .. } | RecGovError::Blocked { status } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// How soon the call is worth retrying: timeouts right away, server errors and
+    /// garbled responses with backoff, and blocks, other client errors and missing
+    /// configuration after a long pause
+    pub fn retry(&self) -> Retry {
+        match self {
+            RecGovError::Timeout(_) => Retry::Soon,
+            RecGovError::Http { status, .. } if *status >= 500 || *status == 408 => Retry::Backoff,
+            RecGovError::Decode(_)
+            | RecGovError::DataFormat { .. }
+            | RecGovError::InvalidPayload { .. } => Retry::Backoff,
+            RecGovError::Http { .. }
+            | RecGovError::Blocked { .. }
+            | RecGovError::RateLimited
+            | RecGovError::MissingApiKey
+            | RecGovError::MissingFixture(_) => Retry::Pause,
+        }
+    }
+
+    /// Whether retrying soon could succeed
+    pub fn is_retryable(&self) -> bool {
+        self.retry() != Retry::Pause
+    }
+}
+
+/// Result of probing whether recreation.gov answers
+#[derive(Debug, Clone, Serialize)]
+pub struct Reachability {
+    /// Whether recreation.gov answered without a server error
+    pub reachable: bool,
+    /// Round-trip time of the probe in milliseconds
+    pub latency_ms: u64,
+    /// Why recreation.gov is unreachable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// When the probe ran
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Client for the recreation.gov / RIDB APIs. Every call is recorded in the API ledger
+/// and goes out through the proxy pool when one is configured, and availability calls
+/// are paced by the configured [`PacingProfile`]; in sandbox mode fixtures are served
+/// instead.
+#[derive(Clone)]
+pub struct RecGovClient {
+    proxies: ProxyPool,
+    pacer: Pacer,
+    fixtures: ResponseFixtures,
+    recgov_base_url: String,
+    api_key: Option<String>,
+    ledger: ApiCallLedger,
+    sandbox: bool,
+    search_cache: SearchCache,
+    availability_cache: AvailabilityCache,
+    availability_cache_ttl: Duration,
+    availability_fetches: AvailabilityFetches,
+    on_demand_requests: Arc<Mutex<VecDeque<Instant>>>,
+    on_demand: bool,
+    reachability: Arc<Mutex<Option<(Instant, Reachability)>>>,
+    batch: Option<PollBatchRoute>,
+}
+
+/// Route and pacing key every call of a poll batch shares
+#[derive(Clone)]
+struct PollBatchRoute {
+    lease: ProxyLease,
+    key: String,
+}
+
+impl RecGovClient {
+    /// Creates a client using the API key and sandbox mode from the configuration.
+    pub fn new(config: &AppConfig, ledger: ApiCallLedger) -> Self {
+        Self {
+            proxies: ProxyPool::from_config(config, Client::new()),
+            pacer: Pacer::new(
+                PacingProfile::from_config(config),
+                Duration::from_millis(config.recreation_gov_min_call_interval_ms),
+            ),
+            fixtures: ResponseFixtures::from_config(config),
+            recgov_base_url: RECGOV_BASE_URL.to_string(),
+            api_key: config.recreation_gov_api_key.clone(),
+            ledger,
+            sandbox: config.sandbox_mode,
+            search_cache: Arc::new(Mutex::new(TtlCache::new(
+                SEARCH_CACHE_CAPACITY,
+                SEARCH_CACHE_TTL,
+            ))),
+            availability_cache: Arc::new(Mutex::new(HashMap::new())),
+            availability_cache_ttl: Duration::from_secs(
+                config.recreation_gov_availability_cache_secs,
+            ),
+            availability_fetches: Arc::new(Mutex::new(HashMap::new())),
+            on_demand_requests: Arc::new(Mutex::new(VecDeque::new())),
+            on_demand: false,
+            reachability: Arc::new(Mutex::new(None)),
+            batch: None,
+        }
+    }
+
+    /// Client for polling a batch of campgrounds in a row (e.g. those of one recreation
+    /// area): every call goes out on the same route, so the first warms up its session
+    /// and the rest reuse the cookies, and calls are paced under `key` so they're spaced
+    /// out as one visitor's would be. Caches are shared with this client.
+    pub fn batch(&self, key: &str) -> Self {
+        Self {
+            batch: Some(PollBatchRoute {
+                lease: self.proxies.lease(),
+                key: key.to_string(),
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// Client for lookups users make on demand (availability previews, live availability,
+    /// permit quotas). Its availability requests are limited to
+    /// `ON_DEMAND_REQUESTS_PER_MINUTE` on this instance and count against the on-demand
+    /// hourly budget, apart from polling, so bursts of users can't hold polling back.
+    /// Caches are shared with this client.
+    pub fn on_demand(&self) -> Self {
+        Self {
+            on_demand: true,
+            ..self.clone()
+        }
+    }
+
+    /// Changes the least time between two paced calls and the overall hourly call budget
+    /// (0 for no cap) on this instance, from the next call on. Every clone of the client,
+    /// and the ledger it was created with, follows.
+    pub fn set_call_limits(&self, min_interval: Duration, hourly_budget: u32) {
+        self.pacer.set_min_interval(min_interval);
+        self.ledger.set_total_budget(hourly_budget);
+    }
+
+    /// Key calls for `campground_id` are paced under
+    fn pacing_key<'a>(&'a self, campground_id: &'a str) -> &'a str {
+        self.batch
+            .as_ref()
+            .map_or(campground_id, |batch| batch.key.as_str())
+    }
+
+    /// Checks that recreation.gov answers, reusing the last result for a minute.
+    /// Returns `None` in sandbox mode and when replaying fixtures, where recreation.gov is
+    /// never called.
+    pub async fn reachability(&self) -> Option<Reachability> {
+        if self.sandbox || self.fixtures.mode() == FixtureMode::Replay {
+            return None;
+        }
+
+        if let Some((probed_at, reachability)) = self.reachability.lock().unwrap().as_ref()
+            && probed_at.elapsed() < REACHABILITY_CACHE_TTL
+        {
+            return Some(reachability.clone());
+        }
+
+        let lease = self.proxies.lease();
+        let started = Instant::now();
+        let response = lease
+            .client()
+            .head(&self.recgov_base_url)
+            .timeout(REACHABILITY_TIMEOUT)
+            .send()
+            .await;
+
+        let error = match response {
+            Ok(response) if response.status().is_server_error() => {
+                Some(format!("HTTP {}", response.status()))
+            }
+            Ok(_) => None,
+            Err(e) => Some(e.to_string()),
+        };
+
+        let reachability = Reachability {
+            reachable: error.is_none(),
+            latency_ms: started.elapsed().as_millis() as u64,
+            error,
+            checked_at: Utc::now(),
+        };
+
+        *self.reachability.lock().unwrap() = Some((Instant::now(), reachability.clone()));
+        Some(reachability)
+    }
+
+    /// Health of the configured proxies; empty when calls go out directly
+    pub fn proxy_health(&self) -> Vec<ProxyHealth> {
+        self.proxies.health()
+    }
+
+    /// Searches campgrounds in the recreation areas whose name contains the query.
+    /// Results are cached for an hour, unless a recreation area's facilities couldn't be
+    /// fetched and the results are partial.
+    pub async fn search_facilities(&self, query: &str) -> Result<Vec<Facility>, RecGovError> {
+        if self.sandbox {
+            log::debug!("🧪 Serving sandbox fixture facilities for: {}", query);
+            return Ok(sandbox_facilities(query));
+        }
+
+        let query_lower = query.trim().to_lowercase();
+
+        if let Some(facilities) = self.search_cache.lock().unwrap().get(&query_lower) {
+            log::debug!("📦 Serving cached facilities for: {}", query);
+            return Ok(facilities.clone());
+        }
+
+        let recareas = self.search_recreation_areas(query).await?;
+        log::debug!("🏞️ Found {} recreation areas", recareas.len());
+
+        let mut facilities = Vec::new();
+        let mut complete = true;
+        for recarea in recareas {
+            // Only include recreation areas that actually match the search query
+            if !recarea.name.to_lowercase().contains(&query_lower) {
+                log::debug!(
+                    "⏭️ Skipping: {} - doesn't match query '{}'",
+                    recarea.name,
+                    query
+                );
+                continue;
+            }
+
+            match self.recarea_facilities(&recarea.id).await {
+                Ok(recarea_facilities) => {
+                    log::debug!(
+                        "  📍 Found {} facilities in {}",
+                        recarea_facilities.len(),
+                        recarea.name
+                    );
+                    facilities.extend(recarea_facilities);
+                }
+                Err(e) => {
+                    log::error!("  ❌ Error getting facilities for {}: {}", recarea.name, e);
+                    complete = false;
+                }
+            }
+        }
+
+        if complete {
+            self.search_cache
+                .lock()
+                .unwrap()
+                .insert(query_lower, facilities.clone());
+        }
+
+        Ok(facilities)
+    }
+
+    /// Searches recreation areas with camping by name.
+    pub async fn search_recreation_areas(&self, query: &str) -> Result<Vec<RecArea>, RecGovError> {
+        let url = format!(
+            "{}/recareas?query={}&activity=CAMPING&limit=50",
+            RIDB_BASE_URL,
+            urlencoding::encode(query)
+        );
+
+        let response: RidbResponse<RecArea> = self.get_ridb("ridb_recareas", None, &url).await?;
+        Ok(response.records)
+    }
+
+    /// Lists the camping facilities of a recreation area, with the organizations and
+    /// addresses RIDB only includes in full responses.
+    pub async fn recarea_facilities(&self, recarea_id: &str) -> Result<Vec<Facility>, RecGovError> {
+        let url = format!(
+            "{}/recareas/{}/facilities?activity=CAMPING&limit=50&full=true",
+            RIDB_BASE_URL,
+            urlencoding::encode(recarea_id)
+        );
+
+        let response: RidbResponse<Facility> =
+            self.get_ridb("ridb_recarea_facilities", None, &url).await?;
+        Ok(response.records)
+    }
+
+    /// Fetches a single facility, or `None` if RIDB doesn't know the ID.
+    pub async fn facility(&self, facility_id: &str) -> Result<Option<Facility>, RecGovError> {
+        if self.sandbox {
+            return Ok(sandbox_facility(facility_id));
+        }
+
+        let url = format!(
+            "{}/facilities/{}",
+            RIDB_BASE_URL,
+            urlencoding::encode(facility_id)
+        );
+
+        // Unknown IDs come back as an empty object rather than a 404
+        let value: serde_json::Value = self
+            .get_ridb("ridb_facility", Some(facility_id), &url)
+            .await?;
+        Ok(serde_json::from_value(value).ok())
+    }
+
+    /// Lists every campsite of a facility, following RIDB paging.
+    pub async fn facility_campsites(
+        &self,
+        facility_id: &str,
+    ) -> Result<Vec<Campsite>, RecGovError> {
+        if self.sandbox {
+            return Ok(sandbox_campsites(facility_id));
+        }
+
+        let mut campsites = Vec::new();
+
+        for page in 0..RIDB_MAX_PAGES {
+            let url = format!(
+                "{}/facilities/{}/campsites?limit={}&offset={}",
+                RIDB_BASE_URL,
+                urlencoding::encode(facility_id),
+                RIDB_PAGE_SIZE,
+                page * RIDB_PAGE_SIZE
+            );
+
+            let response: RidbResponse<Campsite> = self
+                .get_ridb("ridb_facility_campsites", Some(facility_id), &url)
+                .await?;
+
+            let page_count = response.records.len();
+            campsites.extend(response.records);
+
+            if page_count < RIDB_PAGE_SIZE
+                || campsites.len() >= response.metadata.results.total_count
+            {
+                break;
+            }
+        }
+
+        Ok(campsites)
+    }
+
+    /// Lists the attributes (hookups, vehicle length, pet rules, ...) of a campsite.
+    pub async fn campsite_attributes(
+        &self,
+        campsite_id: &str,
+    ) -> Result<Vec<CampsiteAttribute>, RecGovError> {
+        if self.sandbox {
+            return Ok(sandbox_campsite_attributes(campsite_id));
+        }
+
+        let url = format!(
+            "{}/campsites/{}/attributes?limit={}",
+            RIDB_BASE_URL,
+            urlencoding::encode(campsite_id),
+            RIDB_PAGE_SIZE
+        );
+
+        let response: RidbResponse<CampsiteAttribute> = self
+            .get_ridb("ridb_campsite_attributes", None, &url)
+            .await?;
+        Ok(response.records)
+    }
+
+    /// Lists the photos and other media of a facility.
+    pub async fn facility_media(&self, facility_id: &str) -> Result<Vec<EntityMedia>, RecGovError> {
+        if self.sandbox {
+            return Ok(Vec::new());
+        }
+
+        let url = format!(
+            "{}/facilities/{}/media?limit={}",
+            RIDB_BASE_URL,
+            urlencoding::encode(facility_id),
+            RIDB_PAGE_SIZE
+        );
+
+        let response: RidbResponse<EntityMedia> = self
+            .get_ridb("ridb_facility_media", Some(facility_id), &url)
+            .await?;
+        Ok(response.records)
+    }
+
+    /// Fetches the availability of every campsite for the nights `start..end` from
+    /// recreation.gov's monthly availability API. Months are cached for
+    /// `RECREATION_GOV_AVAILABILITY_CACHE_SECS`, and upstream requests of an
+    /// [`on_demand`](Self::on_demand) client are rate limited.
+    pub async fn get_internal_campground_availability(
+        &self,
+        campground_id: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<CampsiteAvailability>, RecGovError> {
+        let fetched = self
+            .campground_availability_within(campground_id, start, end, self.availability_cache_ttl)
+            .await?;
+
+        Ok(fetched.sites)
+    }
+
+    /// Like [`get_internal_campground_availability`](Self::get_internal_campground_availability),
+    /// but serves cached months only if they are at most `max_age` old; older months are
+    /// fetched again, once for all concurrent callers.
+    pub async fn campground_availability_within(
+        &self,
+        campground_id: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+        max_age: Duration,
+    ) -> Result<FetchedAvailability, RecGovError> {
+        let mut sites: HashMap<String, CampsiteAvailability> = HashMap::new();
+        let mut fetched = FetchedAvailability::default();
+
+        for month in months_between(start, end) {
+            let (fetched_at, from_cache, month_availability) = self
+                .month_availability(campground_id, month, max_age)
+                .await?;
+
+            fetched.age = fetched.age.max(fetched_at.elapsed());
+            fetched.from_cache &= from_cache;
+
+            for (campsite_id, site) in month_availability.campsites {
+                match sites.get_mut(&campsite_id) {
+                    Some(existing) => existing.availabilities.extend(site.availabilities),
+                    None => {
+                        sites.insert(campsite_id, site);
+                    }
+                }
+            }
+        }
+
+        fetched.sites = sites.into_values().collect();
+        Ok(fetched)
+    }
+
+    /// Fetches the daily quota of every division of a permit (e.g. Half Dome) for the
+    /// entry dates `start..=end` from recreation.gov's monthly permit API. Requests share
+    /// the on-demand rate limit with campground availability when made on demand.
+    pub async fn get_permit_availability(
+        &self,
+        permit_id: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<PermitDivisionAvailability>, RecGovError> {
+        let mut divisions: HashMap<String, PermitDivisionAvailability> = HashMap::new();
+
+        for month in months_between(start, end.succ_opt().unwrap_or(end)) {
+            let month_availability = if self.sandbox {
+                sandbox_permit_availability(permit_id, month)
+            } else {
+                self.acquire_on_demand_request()?;
+                self.pacer.wait(permit_id).await;
+
+                let url = format!(
+                    "{}/permits/{}/availability/month?start_date={}T00:00:00.000Z",
+                    self.recgov_base_url,
+                    urlencoding::encode(permit_id),
+                    month.format("%Y-%m-%d")
+                );
+
+                let response: PermitResponse<PermitMonthAvailability> = match self
+                    .send_json("recgov_permit_month", Some(permit_id), &url, None)
+                    .await
+                {
+                    Err(RecGovError::DataFormat { message, body }) => {
+                        return Err(self
+                            .quarantine_unreadable(
+                                "recgov_permit_month",
+                                permit_id,
+                                month,
+                                message,
+                                body,
+                            )
+                            .await);
+                    }
+                    response => response?,
+                };
+
+                let issues = validate_permit_month(month, &response.payload);
+                if !issues.is_empty() {
+                    let sample = issue_sample(&issues, &response.payload.availability);
+                    return Err(self
+                        .quarantine("recgov_permit_month", permit_id, month, issues, sample)
+                        .await);
+                }
+
+                response.payload
+            };
+
+            for (division_id, division) in month_availability.availability {
+                match divisions.get_mut(&division_id) {
+                    Some(existing) => existing
+                        .date_availability
+                        .extend(division.date_availability),
+                    None => {
+                        divisions.insert(division_id, division);
+                    }
+                }
+            }
+        }
+
+        Ok(divisions.into_values().collect())
+    }
+
+    /// Returns one month of campground availability with when it was fetched and whether
+    /// it came from the cache, which is used while at most `max_age` old. Older months
+    /// are revalidated with a conditional request. Concurrent misses for the same month
+    /// share one upstream request.
+    async fn month_availability(
+        &self,
+        campground_id: &str,
+        month: NaiveDate,
+        max_age: Duration,
+    ) -> Result<(Instant, bool, MonthAvailability), RecGovError> {
+        if self.sandbox {
+            return Ok((
+                Instant::now(),
+                false,
+                sandbox_availability(campground_id, month),
+            ));
+        }
+
+        let key = (campground_id.to_string(), month);
+        if let Some((fetched_at, availability)) = self.cached_month(&key, max_age) {
+            return Ok((fetched_at, true, availability));
+        }
+
+        let flight = self
+            .availability_fetches
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let _in_flight = flight.lock().await;
+
+        // Another request may have fetched the month while we waited
+        if let Some((fetched_at, availability)) = self.cached_month(&key, max_age) {
+            return Ok((fetched_at, true, availability));
+        }
+
+        let stale = self.availability_cache.lock().unwrap().get(&key).cloned();
+        let fetched = self
+            .fetch_month_availability(campground_id, month, stale)
+            .await;
+        // Cache the month before ending the flight, so a request arriving in between
+        // finds it instead of fetching it again
+        if let Ok(cached) = &fetched {
+            self.availability_cache
+                .lock()
+                .unwrap()
+                .insert(key.clone(), cached.clone());
+        }
+        self.availability_fetches.lock().unwrap().remove(&key);
+        let cached = fetched?;
+
+        Ok((cached.fetched_at, false, cached.availability))
+    }
+
+    /// Returns a cached month if it is at most `max_age` old.
+    fn cached_month(
+        &self,
+        key: &(String, NaiveDate),
+        max_age: Duration,
+    ) -> Option<(Instant, MonthAvailability)> {
+        self.availability_cache
+            .lock()
+            .unwrap()
+            .get(key)
+            .filter(|cached| cached.fetched_at.elapsed() <= max_age)
+            .map(|cached| (cached.fetched_at, cached.availability.clone()))
+    }
+
+    /// Fetches one month of campground availability from recreation.gov and validates it.
+    /// With a `stale` copy that has validators, recreation.gov is asked whether the month
+    /// changed, and the copy is kept when it didn't.
+    async fn fetch_month_availability(
+        &self,
+        campground_id: &str,
+        month: NaiveDate,
+        stale: Option<CachedMonth>,
+    ) -> Result<CachedMonth, RecGovError> {
+        self.acquire_on_demand_request()?;
+        self.pacer.wait(self.pacing_key(campground_id)).await;
+
+        let url = format!(
+            "{}/camps/availability/campground/{}/month?start_date={}T00:00:00.000Z",
+            self.recgov_base_url,
+            urlencoding::encode(campground_id),
+            month.format("%Y-%m-%d")
+        );
+
+        let stale = stale.filter(|cached| !cached.validators.is_empty());
+        let (body, validators) = match self
+            .send(
+                "recgov_availability_month",
+                Some(campground_id),
+                &url,
+                None,
+                stale.as_ref().map(|cached| &cached.validators),
+            )
+            .await?
+        {
+            UpstreamBody::Body(body, validators) => (body, validators),
+            UpstreamBody::NotModified => match stale {
+                Some(cached) => {
+                    log::debug!(
+                        "♻️ Availability of {} for {} unchanged",
+                        campground_id,
+                        month.format("%Y-%m")
+                    );
+                    return Ok(CachedMonth {
+                        fetched_at: Instant::now(),
+                        ..cached
+                    });
+                }
+                None => return Err(unexpected_not_modified()),
+            },
+        };
+
+        let availability: MonthAvailability = match parse_body(body) {
+            Err(RecGovError::DataFormat { message, body }) => {
+                return Err(self
+                    .quarantine_unreadable(
+                        "recgov_availability_month",
+                        campground_id,
+                        month,
+                        message,
+                        body,
+                    )
+                    .await);
+            }
+            availability => availability?,
+        };
+
+        // Never cache impossible availability, or every preview and poll would see it
+        let issues = validate_month_availability(month, &availability);
+        if !issues.is_empty() {
+            let sample = issue_sample(&issues, &availability.campsites);
+            return Err(self
+                .quarantine(
+                    "recgov_availability_month",
+                    campground_id,
+                    month,
+                    issues,
+                    sample,
+                )
+                .await);
+        }
+
+        Ok(CachedMonth {
+            fetched_at: Instant::now(),
+            availability,
+            validators,
+        })
+    }
+
+    /// Logs and stores a payload that failed validation, returning the error to report.
+    async fn quarantine(
+        &self,
+        endpoint: &'static str,
+        entity_id: &str,
+        month: NaiveDate,
+        issues: Vec<PayloadIssue>,
+        sample: serde_json::Value,
+    ) -> RecGovError {
+        log::warn!(
+            "🧪 Quarantined {} payload for {} ({}): {} issues, e.g. {}; sample: {}",
+            endpoint,
+            entity_id,
+            month.format("%Y-%m"),
+            issues.len(),
+            issues
+                .iter()
+                .take(3)
+                .map(PayloadIssue::to_string)
+                .collect::<Vec<_>>()
+                .join("; "),
+            sample
+        );
+
+        self.ledger
+            .quarantine(endpoint, entity_id, month, &issues, sample)
+            .await;
+
+        RecGovError::InvalidPayload {
+            issues: issues.len(),
+            first: issues[0].to_string(),
+        }
+    }
+
+    /// Logs and stores a payload that couldn't be parsed at all, as received, returning
+    /// the error to report.
+    async fn quarantine_unreadable(
+        &self,
+        endpoint: &'static str,
+        entity_id: &str,
+        month: NaiveDate,
+        message: String,
+        body: String,
+    ) -> RecGovError {
+        log::warn!(
+            "🧪 Quarantined unreadable {} payload for {} ({}): {}",
+            endpoint,
+            entity_id,
+            month.format("%Y-%m"),
+            message
+        );
+
+        let issues = [PayloadIssue::DataFormat {
+            message: message.clone(),
+        }];
+        self.ledger
+            .quarantine(
+                endpoint,
+                entity_id,
+                month,
+                &issues,
+                raw_payload_sample(&body),
+            )
+            .await;
+
+        RecGovError::DataFormat { message, body }
+    }
+
+    /// Takes a slot in the sliding one-minute window of on-demand availability requests.
+    /// Polling isn't limited here; the pacer and hourly budgets keep it in check.
+    fn acquire_on_demand_request(&self) -> Result<(), RecGovError> {
+        if !self.on_demand {
+            return Ok(());
+        }
+
+        let mut requests = self.on_demand_requests.lock().unwrap();
+
+        while requests
+            .front()
+            .is_some_and(|sent| sent.elapsed() >= Duration::from_secs(60))
+        {
+            requests.pop_front();
+        }
+
+        if requests.len() >= ON_DEMAND_REQUESTS_PER_MINUTE {
+            return Err(RecGovError::RateLimited);
+        }
+
+        requests.push_back(Instant::now());
+        Ok(())
+    }
+
+    /// Sends a GET request to RIDB and records the call in the API ledger.
+    async fn get_ridb<T: DeserializeOwned>(
+        &self,
+        endpoint: &'static str,
+        campground_id: Option<&str>,
+        url: &str,
+    ) -> Result<T, RecGovError> {
+        let api_key = match self.api_key.as_deref() {
+            Some(api_key) => Some(api_key),
+            // Replayed calls don't need a key
+            None if self.fixtures.mode() == FixtureMode::Replay => None,
+            None => return Err(RecGovError::MissingApiKey),
+        };
+
+        self.send_json(endpoint, campground_id, url, api_key).await
+    }
+
+    /// Sends a GET request through the next proxy and parses the JSON response; see
+    /// [`send`](Self::send).
+    async fn send_json<T: DeserializeOwned>(
+        &self,
+        endpoint: &'static str,
+        campground_id: Option<&str>,
+        url: &str,
+        api_key: Option<&str>,
+    ) -> Result<T, RecGovError> {
+        match self
+            .send(endpoint, campground_id, url, api_key, None)
+            .await?
+        {
+            UpstreamBody::Body(body, _) => parse_body(body),
+            UpstreamBody::NotModified => Err(unexpected_not_modified()),
+        }
+    }
+
+    /// Sends a GET request through the next proxy with its session's user agent and
+    /// cookies, tagged with the ID of the request being handled, and records the call in
+    /// the API ledger and the proxy's health. With `validators`, the request is made
+    /// conditional and a `304 Not Modified` is reported as such. Responses are saved when
+    /// capturing fixtures, and read from disk instead when replaying them.
+    #[tracing::instrument(
+        name = "recreation_gov",
+        skip(self, api_key, validators),
+        fields(status_code)
+    )]
+    async fn send(
+        &self,
+        endpoint: &'static str,
+        campground_id: Option<&str>,
+        url: &str,
+        api_key: Option<&str>,
+        validators: Option<&CacheValidators>,
+    ) -> Result<UpstreamBody, RecGovError> {
+        if self.fixtures.mode() == FixtureMode::Replay {
+            let body = self.fixtures.load(endpoint, url)?;
+            return Ok(UpstreamBody::Body(body, CacheValidators::default()));
+        }
+
+        let class = if self.on_demand {
+            EndpointClass::on_demand(endpoint)
+        } else {
+            EndpointClass::of(endpoint)
+        };
+        if !self.ledger.reserve_call(class).await {
+            log::warn!(
+                "🚦 Hourly recreation.gov {} request budget spent, skipping {}",
+                class.as_str(),
+                endpoint
+            );
+            return Err(RecGovError::RateLimited);
+        }
+
+        let lease = match &self.batch {
+            Some(batch) => batch.lease.clone(),
+            None => self.proxies.lease(),
+        };
+        let mut request = self.proxies.get(&lease, url);
+        if let Some(api_key) = api_key {
+            request = request.header("apikey", api_key);
+        }
+        if let Some(request_id) = current_request_id() {
+            request = request.header(REQUEST_ID_HEADER, request_id);
+        }
+        if let Some(validators) = validators {
+            if let Some(etag) = &validators.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let started = Instant::now();
+
+        let response = request.send().await;
+        self.proxies.report(&lease, &response);
+        let status_code = response.as_ref().ok().map(|r| r.status().as_u16());
+        if let Some(status_code) = status_code {
+            tracing::Span::current().record("status_code", status_code);
+        }
+
+        self.ledger
+            .record(ApiCallRecord {
+                endpoint,
+                campground_id: campground_id.map(str::to_string),
+                status_code,
+                duration: started.elapsed(),
+            })
+            .await;
+
+        let response = response?.error_for_status()?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(UpstreamBody::NotModified);
+        }
+
+        let validators = CacheValidators::from_headers(response.headers());
+        let body = response.text().await?;
+        if self.fixtures.mode() == FixtureMode::Capture {
+            self.fixtures.save(endpoint, url, &body);
+        }
+
+        Ok(UpstreamBody::Body(body, validators))
+    }
+}
+
+/// Error for a `304 Not Modified` to a request that wasn't conditional
+fn unexpected_not_modified() -> RecGovError {
+    RecGovError::DataFormat {
+        message: "Unexpected 304 Not Modified".to_string(),
+        body: String::new(),
+    }
+}
+
+/// Parses a response body, keeping it in the error when it isn't in the shape expected
+fn parse_body<T: DeserializeOwned>(body: String) -> Result<T, RecGovError> {
+    serde_json::from_str(&body).map_err(|e| RecGovError::DataFormat {
+        message: e.to_string(),
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::availability::FacilityType;
+    use sqlx::postgres::PgPoolOptions;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answers one request per response with an empty month of availability, returning
+    /// the requests received (lower-cased)
+    async fn serve(listener: TcpListener, responses: &[(u16, &str)]) -> Vec<String> {
+        let mut requests = Vec::new();
+
+        for (status, headers) in responses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 8192];
+            let read = stream.read(&mut request).await.unwrap();
+            requests.push(String::from_utf8_lossy(&request[..read]).to_lowercase());
+
+            let body = r#"{"campsites":{}}"#;
+            let response = format!(
+                "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                headers,
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+
+        requests
+    }
+
+    /// Client calling `address` instead of recreation.gov, with a ledger whose database
+    /// never answers
+    fn test_client(address: std::net::SocketAddr) -> RecGovClient {
+        let mut client = configured_client(AppConfig::default());
+        client.recgov_base_url = format!("http://{}/api", address);
+        client
+    }
+
+    fn configured_client(config: AppConfig) -> RecGovClient {
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(50))
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap();
+
+        RecGovClient::new(&config, ApiCallLedger::new(pool))
+    }
+
+    /// Client replaying the fixtures in `dir`
+    fn replaying_client(dir: &str) -> RecGovClient {
+        configured_client(AppConfig {
+            recreation_gov_fixtures: "replay".to_string(),
+            recreation_gov_fixtures_dir: dir.to_string(),
+            ..AppConfig::default()
+        })
+    }
+
+    fn header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+        request
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("{}: ", name)))
+    }
+
+    fn nights(start: (u32, u32), end: (u32, u32)) -> (NaiveDate, NaiveDate) {
+        (
+            NaiveDate::from_ymd_opt(2026, start.0, start.1).unwrap(),
+            NaiveDate::from_ymd_opt(2026, end.0, end.1).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn cookies_persist_across_availability_calls() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = test_client(listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            serve(
+                listener,
+                &[
+                    (200, "Set-Cookie: visitor=abc; Path=/\r\n"),
+                    (200, ""),
+                    (200, ""),
+                ],
+            )
+            .await
+        });
+
+        // Two months, then a third in another lookup: one upstream call each
+        let (start, end) = nights((7, 1), (8, 15));
+        client
+            .get_internal_campground_availability("232447", start, end)
+            .await
+            .unwrap();
+        let (start, end) = nights((9, 1), (9, 3));
+        client
+            .get_internal_campground_availability("232447", start, end)
+            .await
+            .unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(header(&requests[0], "cookie"), None);
+        assert_eq!(header(&requests[1], "cookie"), Some("visitor=abc"));
+        assert_eq!(header(&requests[2], "cookie"), Some("visitor=abc"));
+
+        // The same visitor throughout
+        let user_agent = header(&requests[0], "user-agent").unwrap();
+        let accept_language = header(&requests[0], "accept-language").unwrap();
+        assert!(user_agent.starts_with("mozilla/5.0"));
+        assert!(accept_language.starts_with("en-us"));
+        assert!(requests.iter().all(|request| {
+            header(request, "user-agent") == Some(user_agent)
+                && header(request, "accept-language") == Some(accept_language)
+        }));
+    }
+
+    #[tokio::test]
+    async fn only_on_demand_requests_are_rate_limited() {
+        let polling = configured_client(AppConfig::default());
+        let on_demand = polling.on_demand();
+
+        for _ in 0..ON_DEMAND_REQUESTS_PER_MINUTE {
+            on_demand.acquire_on_demand_request().unwrap();
+        }
+        assert!(matches!(
+            on_demand.acquire_on_demand_request(),
+            Err(RecGovError::RateLimited)
+        ));
+
+        // Polling keeps going while users have spent the on-demand limit
+        polling.acquire_on_demand_request().unwrap();
+    }
+
+    #[tokio::test]
+    async fn revalidates_expired_months() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = test_client(listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            serve(
+                listener,
+                &[
+                    (
+                        200,
+                        "ETag: \"v1\"\r\nLast-Modified: Wed, 01 Jul 2026 00:00:00 GMT\r\n",
+                    ),
+                    (304, ""),
+                ],
+            )
+            .await
+        });
+
+        let (start, end) = nights((7, 1), (7, 2));
+        let first = client
+            .campground_availability_within("232447", start, end, Duration::ZERO)
+            .await
+            .unwrap();
+        let second = client
+            .campground_availability_within("232447", start, end, Duration::ZERO)
+            .await
+            .unwrap();
+        assert!(!first.from_cache && !second.from_cache);
+
+        let requests = server.await.unwrap();
+        assert_eq!(header(&requests[0], "if-none-match"), None);
+        assert_eq!(header(&requests[1], "if-none-match"), Some("\"v1\""));
+        assert_eq!(
+            header(&requests[1], "if-modified-since"),
+            Some("wed, 01 jul 2026 00:00:00 gmt")
+        );
+    }
+
+    #[tokio::test]
+    async fn blocked_session_starts_over() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = test_client(listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            serve(
+                listener,
+                &[(200, "Set-Cookie: visitor=abc\r\n"), (429, ""), (200, "")],
+            )
+            .await
+        });
+
+        for month in [7, 8, 9] {
+            let (start, end) = nights((month, 1), (month, 2));
+            let _ = client
+                .get_internal_campground_availability("232447", start, end)
+                .await;
+        }
+
+        let requests = server.await.unwrap();
+        assert_eq!(header(&requests[1], "cookie"), Some("visitor=abc"));
+        assert_eq!(header(&requests[2], "cookie"), None);
+        assert_ne!(
+            header(&requests[1], "user-agent"),
+            header(&requests[2], "user-agent")
+        );
+    }
+
+    #[tokio::test]
+    async fn parses_recorded_availability() {
+        let client = replaying_client(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"));
+        let (start, end) = nights((7, 10), (7, 12));
+
+        let mut sites = client
+            .get_internal_campground_availability("232447", start, end)
+            .await
+            .unwrap();
+        sites.sort_by(|a, b| a.site.cmp(&b.site));
+
+        let open: Vec<(&str, FacilityType)> = sites
+            .iter()
+            .filter(|site| site.is_open_for(start, end))
+            .map(|site| (site.site.as_str(), site.facility_type()))
+            .collect();
+        assert_eq!(
+            open,
+            [
+                ("001", FacilityType::Campsite),
+                ("C01", FacilityType::Cabin),
+                ("G01", FacilityType::GroupSite)
+            ]
+        );
+
+        let (start, end) = nights((8, 1), (8, 2));
+        assert!(matches!(
+            client
+                .get_internal_campground_availability("232447", start, end)
+                .await,
+            Err(RecGovError::MissingFixture(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn captured_responses_replay() {
+        let dir = std::env::temp_dir().join(format!("recgov-fixtures-{}", std::process::id()));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = configured_client(AppConfig {
+            recreation_gov_fixtures: "capture".to_string(),
+            recreation_gov_fixtures_dir: dir.display().to_string(),
+            ..AppConfig::default()
+        });
+        client.recgov_base_url = format!("http://{}/api", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move { serve(listener, &[(200, "")]).await });
+
+        let (start, end) = nights((7, 1), (7, 2));
+        client
+            .get_internal_campground_availability("232447", start, end)
+            .await
+            .unwrap();
+        server.await.unwrap();
+
+        let replayed = replaying_client(&dir.display().to_string())
+            .get_internal_campground_availability("232447", start, end)
+            .await;
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(replayed.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn unreadable_payloads_keep_their_body() {
+        let dir = std::env::temp_dir().join(format!("recgov-unreadable-{}", std::process::id()));
+        let month_dir = dir.join("recgov_availability_month");
+        std::fs::create_dir_all(&month_dir).unwrap();
+        let body = r#"{"campsites":[{"campsite_id":"1"}]}"#;
+        std::fs::write(
+            month_dir.join(
+                "api_camps_availability_campground_232447_month_start_date_2026-07-01T00_00_00.000Z.json",
+            ),
+            body,
+        )
+        .unwrap();
+
+        let (start, end) = nights((7, 1), (7, 2));
+        let result = replaying_client(&dir.display().to_string())
+            .get_internal_campground_availability("232447", start, end)
+            .await;
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        match result {
+            Err(RecGovError::DataFormat { body: kept, .. }) => assert_eq!(kept, body),
+            other => panic!("expected a data format error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
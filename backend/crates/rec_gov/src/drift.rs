@@ -0,0 +1,433 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::availability::{AVAILABLE, RESERVED};
+
+/// Statuses recreation.gov is known to report for a night, as spelled in its responses
+const KNOWN_STATUSES: &[&str] = &[
+    "Available",
+    "Open",
+    "Reserved",
+    "Closed",
+    "Lottery",
+    "NYR",
+    "Not Available",
+    "Not Available Cutoff",
+    "Not Reservable",
+    "Not Reservable Management",
+    "Walk Up",
+];
+
+/// Longest value kept as an example of a drifted field
+const MAX_EXAMPLE_LEN: usize = 100;
+
+/// Drifted fields seen since startup, by field and problem
+static FIELD_ERRORS: Mutex<BTreeMap<(&'static str, &'static str), FieldErrorCount>> =
+    Mutex::new(BTreeMap::new());
+
+/// How often one field of recreation.gov's availability responses came in a shape other
+/// than the one expected
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldErrorCount {
+    /// Field that drifted (e.g. `availabilities`)
+    pub field: &'static str,
+    /// What was wrong with it (e.g. `unknown_status`)
+    pub problem: &'static str,
+    /// Times it was seen since startup
+    pub count: u64,
+    /// Latest value seen, shortened
+    pub example: String,
+    /// When it was last seen
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Fields recreation.gov sent in an unexpected shape since startup, most frequent first
+pub fn field_error_counts() -> Vec<FieldErrorCount> {
+    let mut counts: Vec<FieldErrorCount> = FIELD_ERRORS.lock().unwrap().values().cloned().collect();
+    counts.sort_by_key(|count| std::cmp::Reverse(count.count));
+    counts
+}
+
+/// Counts a drifted field. The first occurrence of each problem is logged as a warning,
+/// later ones only at debug level.
+pub(crate) fn record_field_error(field: &'static str, problem: &'static str, value: &str) {
+    let example: String = value.chars().take(MAX_EXAMPLE_LEN).collect();
+    let mut errors = FIELD_ERRORS.lock().unwrap();
+
+    let entry = errors
+        .entry((field, problem))
+        .or_insert_with(|| FieldErrorCount {
+            field,
+            problem,
+            count: 0,
+            example: String::new(),
+            last_seen: Utc::now(),
+        });
+    entry.count += 1;
+    entry.example = example;
+    entry.last_seen = Utc::now();
+
+    if entry.count == 1 {
+        log::warn!(
+            "🧬 Recreation.gov sent {} with {}: {}",
+            field,
+            problem.replace('_', " "),
+            entry.example
+        );
+    } else {
+        log::debug!("🧬 {} with {}: {}", field, problem, entry.example);
+    }
+}
+
+/// Parses the date of a night key. Recreation.gov sends `2024-07-01T00:00:00Z`; other
+/// ISO 8601 timestamps, `07/01/2024`, and Unix timestamps (seconds or milliseconds) are
+/// accepted too.
+pub(crate) fn parse_night_key(key: &str) -> Option<NaiveDate> {
+    let key = key.trim();
+
+    if let Some(date) = key
+        .get(..10)
+        .and_then(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+    {
+        return Some(date);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(key, "%m/%d/%Y") {
+        return Some(date);
+    }
+
+    let timestamp: i64 = key
+        .parse()
+        .ok()
+        .filter(|_| key.chars().all(|c| c.is_ascii_digit()))?;
+    let timestamp = match key.len() {
+        13 => DateTime::from_timestamp_millis(timestamp),
+        10 => DateTime::from_timestamp(timestamp, 0),
+        _ => None,
+    };
+    timestamp.map(|timestamp| timestamp.date_naive())
+}
+
+/// Counts night keys that aren't in recreation.gov's usual format. Keys that can't be
+/// parsed at all are kept, so validation quarantines the month instead of a night
+/// silently going missing.
+fn check_night_key(field: &'static str, key: &str) {
+    let standard = key
+        .get(..10)
+        .is_some_and(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").is_ok())
+        && key.as_bytes().get(10).is_none_or(|c| *c == b'T');
+
+    if standard {
+        return;
+    }
+
+    match parse_night_key(key) {
+        Some(_) => record_field_error(field, "alternate_date_format", key),
+        None => record_field_error(field, "unparseable_date", key),
+    }
+}
+
+/// A night's status as it may arrive: recreation.gov sends strings, but flags, unit
+/// counts, and `{"status": ...}` objects are read too
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawStatus {
+    Text(String),
+    Flag(bool),
+    Count(i64),
+    Detailed { status: String },
+    Other(serde_json::Value),
+}
+
+/// Spelling of `status` in [`KNOWN_STATUSES`], ignoring case, surrounding whitespace,
+/// and underscores or dashes used for spaces
+fn known_status(status: &str) -> Option<&'static str> {
+    let normalize = |status: &str| status.trim().replace(['_', '-'], " ").to_ascii_uppercase();
+    let status = normalize(status);
+
+    KNOWN_STATUSES
+        .iter()
+        .find(|known| normalize(known) == status)
+        .copied()
+}
+
+/// Reads one night's status, or None when it can't be understood
+fn night_status(value: RawStatus) -> Option<String> {
+    match value {
+        RawStatus::Text(status) | RawStatus::Detailed { status } => {
+            match known_status(&status) {
+                Some(known) => {
+                    if known != status {
+                        record_field_error("availabilities", "status_spelling", &status);
+                    }
+                    Some(known.to_string())
+                }
+                // Kept as sent; only known statuses count as bookable
+                None => {
+                    record_field_error("availabilities", "unknown_status", &status);
+                    Some(status.trim().to_string())
+                }
+            }
+        }
+        RawStatus::Flag(available) => {
+            record_field_error("availabilities", "boolean_status", &available.to_string());
+            Some(if available { AVAILABLE } else { RESERVED }.to_string())
+        }
+        RawStatus::Count(units) => {
+            record_field_error("availabilities", "numeric_status", &units.to_string());
+            Some(if units > 0 { AVAILABLE } else { RESERVED }.to_string())
+        }
+        RawStatus::Other(value) => {
+            record_field_error("availabilities", "unexpected_type", &value.to_string());
+            None
+        }
+    }
+}
+
+/// Deserializes `availabilities`, accepting statuses in any shape [`RawStatus`] covers.
+/// Statuses that can't be read are dropped, so the night counts as not bookable.
+pub(crate) fn deserialize_statuses<'de, D>(
+    deserializer: D,
+) -> Result<BTreeMap<String, String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw: Option<BTreeMap<String, RawStatus>> = Option::deserialize(deserializer)?;
+
+    Ok(raw
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(key, status)| {
+            check_night_key("availabilities", &key);
+            night_status(status).map(|status| (key, status))
+        })
+        .collect())
+}
+
+/// A count as it may arrive: a number, a decimal, or a number in a string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawCount {
+    Integer(i64),
+    Decimal(f64),
+    Text(String),
+    Other(serde_json::Value),
+}
+
+/// Reads a count of `field`, or None when it can't be understood
+fn count(field: &'static str, value: RawCount) -> Option<i64> {
+    match value {
+        RawCount::Integer(count) => Some(count),
+        RawCount::Decimal(count) if count.fract() == 0.0 => {
+            record_field_error(field, "decimal_number", &count.to_string());
+            Some(count as i64)
+        }
+        RawCount::Text(text) => match text.trim().parse::<i64>() {
+            Ok(count) => {
+                record_field_error(field, "number_as_text", &text);
+                Some(count)
+            }
+            Err(_) => {
+                record_field_error(field, "not_a_number", &text);
+                None
+            }
+        },
+        RawCount::Decimal(count) => {
+            record_field_error(field, "not_a_number", &count.to_string());
+            None
+        }
+        RawCount::Other(value) => {
+            record_field_error(field, "unexpected_type", &value.to_string());
+            None
+        }
+    }
+}
+
+/// Deserializes `quantities`, accepting counts in any shape [`RawCount`] covers. Counts
+/// that can't be read are dropped.
+pub(crate) fn deserialize_quantities<'de, D>(
+    deserializer: D,
+) -> Result<BTreeMap<String, i32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw: Option<BTreeMap<String, RawCount>> = Option::deserialize(deserializer)?;
+
+    Ok(raw
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(key, value)| {
+            check_night_key("quantities", &key);
+            let quantity = count("quantities", value)?;
+            Some((key, quantity.clamp(i32::MIN as i64, i32::MAX as i64) as i32))
+        })
+        .collect())
+}
+
+/// A price as it may arrive: a number, or text such as `35.00` or `$35`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawPrice {
+    Number(f64),
+    Text(String),
+    Other(serde_json::Value),
+}
+
+/// Deserializes `prices`, accepting prices in any shape [`RawPrice`] covers. Prices that
+/// can't be read or are negative are dropped, so the night's price is unknown.
+pub(crate) fn deserialize_prices<'de, D>(deserializer: D) -> Result<BTreeMap<String, f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw: Option<BTreeMap<String, RawPrice>> = Option::deserialize(deserializer)?;
+
+    Ok(raw
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(key, value)| {
+            check_night_key("prices", &key);
+            let price = match value {
+                RawPrice::Number(price) => price,
+                RawPrice::Text(text) => {
+                    match text.trim().trim_start_matches('$').replace(',', "").parse() {
+                        Ok(price) => {
+                            record_field_error("prices", "price_as_text", &text);
+                            price
+                        }
+                        Err(_) => {
+                            record_field_error("prices", "not_a_number", &text);
+                            return None;
+                        }
+                    }
+                }
+                RawPrice::Other(value) => {
+                    record_field_error("prices", "unexpected_type", &value.to_string());
+                    return None;
+                }
+            };
+
+            if !(price.is_finite() && price >= 0.0) {
+                record_field_error("prices", "negative_price", &price.to_string());
+                return None;
+            }
+            Some((key, price))
+        })
+        .collect())
+}
+
+/// Deserializes the campground's site total, accepting it in any shape [`RawCount`]
+/// covers
+pub(crate) fn deserialize_site_count<'de, D>(deserializer: D) -> Result<Option<usize>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw: Option<RawCount> = Option::deserialize(deserializer)?;
+
+    Ok(raw
+        .and_then(|value| count("count", value))
+        .and_then(|count| usize::try_from(count).ok()))
+}
+
+/// An ID or name as it may arrive: a string or a number
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawText {
+    Text(String),
+    Number(serde_json::Number),
+}
+
+/// Deserializes an ID that recreation.gov may send as a number
+pub(crate) fn deserialize_id<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match RawText::deserialize(deserializer)? {
+        RawText::Text(text) => text,
+        RawText::Number(number) => {
+            record_field_error("campsite_id", "number_as_id", &number.to_string());
+            number.to_string()
+        }
+    })
+}
+
+/// Deserializes a site name that may be missing, null, or a number
+pub(crate) fn deserialize_name<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Option::<RawText>::deserialize(deserializer)? {
+        Some(RawText::Text(text)) => text,
+        Some(RawText::Number(number)) => number.to_string(),
+        None => String::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::availability::{CampsiteAvailability, is_available_status};
+
+    #[test]
+    fn reads_drifted_campsites() {
+        let site: CampsiteAvailability = serde_json::from_value(serde_json::json!({
+            "campsite_id": 1234,
+            "site": 7,
+            "loop": null,
+            "availabilities": {
+                "2026-07-01T00:00:00Z": "AVAILABLE",
+                "2026-07-02": "not_reservable",
+                "07/03/2026": {"status": "Open", "source": "edge"},
+                "2026-07-04T00:00:00Z": true,
+                "2026-07-05T00:00:00Z": "Held For Maintenance",
+                "2026-07-06T00:00:00Z": ["?"]
+            },
+            "quantities": {
+                "2026-07-07T00:00:00Z": "2",
+                "2026-07-08T00:00:00Z": 0.0,
+                "2026-07-09T00:00:00Z": "lots"
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(site.campsite_id, "1234");
+        assert_eq!(site.site, "7");
+
+        let nights: BTreeMap<u32, &str> = site
+            .nights()
+            .map(|(date, status)| (chrono::Datelike::day(&date), status))
+            .collect();
+        assert_eq!(
+            nights,
+            BTreeMap::from([
+                (1, "Available"),
+                (2, "Not Reservable"),
+                (3, "Open"),
+                (4, "Available"),
+                (5, "Held For Maintenance"),
+                (7, "Available"),
+                (8, "Reserved"),
+            ])
+        );
+        assert!(!is_available_status(nights[&5]));
+
+        let counts = field_error_counts();
+        for (field, problem) in [
+            ("availabilities", "unknown_status"),
+            ("availabilities", "unexpected_type"),
+            ("availabilities", "alternate_date_format"),
+            ("quantities", "number_as_text"),
+            ("quantities", "not_a_number"),
+            ("campsite_id", "number_as_id"),
+        ] {
+            assert!(
+                counts
+                    .iter()
+                    .any(|count| count.field == field && count.problem == problem),
+                "{} {} not counted",
+                field,
+                problem
+            );
+        }
+    }
+}
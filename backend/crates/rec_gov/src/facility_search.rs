@@ -1,144 +1,169 @@
 use actix_web::{HttpResponse, Result, web};
-use reqwest::Client;
-use serde_json::Value;
-use std::collections::HashMap;
-
-async fn search_recreation_areas(query: String) -> Result<Value, Box<dyn std::error::Error>> {
-    let client = Client::new();
-    let url = format!(
-        "https://ridb.recreation.gov/api/v1/recareas?query={}&activity=CAMPING&limit=50",
-        urlencoding::encode(&query)
-    );
-
-    let response = client
-        .get(&url)
-        .header("apikey", "da7bd758-b219-4a80-b885-556101d03afb")
-        .send()
-        .await?;
-
-    let data: Value = response.json().await?;
-    Ok(data)
+use i18n::{FluentArgs, current_locale, message, message_with};
+use serde::Deserialize;
+
+use crate::client::RecGovClient;
+use crate::geocoding::Geocoder;
+use crate::ridb::{Agency, Facility, RidbResponse};
+
+/// Adds a human-readable location (e.g. "12 mi NE of Bishop, CA") to each facility whose
+/// coordinates are already described. The geocoder sends at most one request a second,
+/// so the rest are described in the background and show up in later searches.
+fn add_location_descriptions(geocoder: &Geocoder, facilities: &mut [Facility]) {
+    for facility in facilities {
+        facility.location_description = facility
+            .coordinates()
+            .and_then(|(lat, lng)| geocoder.describe_cached_location(lat, lng));
+    }
+}
+
+/// Query string of a facility search
+#[derive(Debug, Deserialize)]
+pub struct FacilitySearchQuery {
+    /// Recreation area name to search
+    pub q: Option<String>,
+    /// Only facilities in this state (two-letter code)
+    pub state: Option<String>,
+    /// Only facilities managed by this agency (`NPS`, `USFS` or `BLM`)
+    pub agency: Option<String>,
+    /// Only campgrounds, leaving out trailheads, day-use areas and the like
+    #[serde(default)]
+    pub campgrounds_only: bool,
 }
 
-async fn get_facilities_for_recarea(recarea_id: &str) -> Result<Value, Box<dyn std::error::Error>> {
-    let client = Client::new();
-    let url = format!(
-        "https://ridb.recreation.gov/api/v1/recareas/{}/facilities?activity=CAMPING&limit=50",
-        recarea_id
-    );
-
-    let response = client
-        .get(&url)
-        .header("apikey", "da7bd758-b219-4a80-b885-556101d03afb")
-        .send()
-        .await?;
-
-    let data: Value = response.json().await?;
-    Ok(data)
+/// Narrows the facilities of the matching recreation areas down
+#[derive(Debug, Clone, Default)]
+pub struct FacilityFilters {
+    /// Two-letter state code
+    pub state: Option<String>,
+    /// Managing agency
+    pub agency: Option<Agency>,
+    /// Whether to keep campgrounds only
+    pub campgrounds_only: bool,
 }
 
-/// Handler for searching facilities based on a query parameter
-pub async fn facilities_search(query: web::Query<HashMap<String, String>>) -> Result<HttpResponse> {
+impl FacilityFilters {
+    /// Returns true if the facility passes every filter set.
+    pub fn matches(&self, facility: &Facility) -> bool {
+        let state_matches = self.state.as_deref().is_none_or(|state| {
+            facility
+                .state_code()
+                .is_some_and(|code| code.eq_ignore_ascii_case(state))
+        });
+        let agency_matches = self
+            .agency
+            .is_none_or(|agency| facility.agency() == Some(agency));
+
+        state_matches && agency_matches && (!self.campgrounds_only || facility.is_campground())
+    }
+}
+
+/// Handler for searching facilities based on a query parameter, optionally filtered by
+/// state, managing agency, and facility type
+pub async fn facilities_search(
+    client: web::Data<RecGovClient>,
+    geocoder: web::Data<Geocoder>,
+    query: web::Query<FacilitySearchQuery>,
+) -> Result<HttpResponse> {
     log::debug!("🔍 Facilities search called with query: {:?}", query);
 
-    if let Some(q) = query.get("q") {
-        log::debug!("📝 Searching for recreation areas: {}", q);
-
-        match search_recreation_areas(q.to_string()).await {
-            Ok(recarea_data) => {
-                let mut all_facilities = Vec::new();
-
-                // For each recreation area found, get its facilities
-                if let Some(recareas) = recarea_data.get("RECDATA").and_then(|v| v.as_array()) {
-                    log::debug!("🏞️ Found {} recreation areas", recareas.len());
-
-                    for recarea in recareas {
-                        if let Some(recarea_id) = recarea.get("RecAreaID").and_then(|v| v.as_str())
-                        {
-                            if let Some(recarea_name) =
-                                recarea.get("RecAreaName").and_then(|v| v.as_str())
-                            {
-                                // FILTER: Only include recreation areas that actually match the search query
-                                let query_lower = q.to_lowercase();
-                                let name_lower = recarea_name.to_lowercase();
-
-                                // Check if the recreation area name contains the search term
-                                if name_lower.contains(&query_lower) {
-                                    log::debug!(
-                                        "✅ Getting facilities for: {} (ID: {}) - MATCHES query",
-                                        recarea_name,
-                                        recarea_id
-                                    );
-
-                                    match get_facilities_for_recarea(recarea_id).await {
-                                        Ok(facilities_data) => {
-                                            if let Some(facilities) = facilities_data
-                                                .get("RECDATA")
-                                                .and_then(|v| v.as_array())
-                                            {
-                                                log::debug!(
-                                                    "  📍 Found {} facilities",
-                                                    facilities.len()
-                                                );
-                                                all_facilities.extend(facilities.iter().cloned());
-                                            }
-                                        }
-                                        Err(e) => {
-                                            log::error!(
-                                                "  ❌ Error getting facilities for {}: {}",
-                                                recarea_name,
-                                                e
-                                            );
-                                        }
-                                    }
-                                } else {
-                                    log::debug!(
-                                        "⏭️ Skipping: {} - doesn't match query '{}'",
-                                        recarea_name,
-                                        q
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-
-                // Filter to show only National Park Service campgrounds (ParentOrgID = "128")
-                // let nps_facilities: Vec<Value> = all_facilities
-                //     .into_iter()
-                //     .filter(|facility| {
-                //         facility
-                //             .get("ParentOrgID")
-                //             .and_then(|id| id.as_str())
-                //             .map(|id| id == "128")
-                //             .unwrap_or(false)
-                //     })
-                //     .collect();
-
-                log::debug!("🎯 Returning {} NPS facilities", all_facilities.len());
-
-                // Create response in the same format as the original API
-                let response = serde_json::json!({
-                    "RECDATA": all_facilities,
-                    "METADATA": {
-                        "RESULTS": {
-                            "CURRENT_COUNT": all_facilities.len(),
-                            "TOTAL_COUNT": all_facilities.len()
-                        }
-                    }
-                });
-
-                Ok(HttpResponse::Ok().json(response))
-            }
-            Err(e) => {
-                log::error!("❌ Error searching recreation areas: {}", e);
-                Ok(HttpResponse::InternalServerError().json("Search failed"))
-            }
-        }
-    } else {
+    let Some(q) = query.q.as_deref() else {
         log::error!(
             "❌ Error: There was an attempt to search for facilities, but missing the query parameter"
         );
-        Ok(HttpResponse::BadRequest().json("Missing query parameter"))
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "missing_query",
+            "message": message(current_locale(), "missing-search-query")
+        })));
+    };
+
+    let agency = match query.agency.as_deref().filter(|agency| !agency.is_empty()) {
+        Some(value) => match Agency::parse(value) {
+            Some(agency) => Some(agency),
+            None => {
+                let mut args = FluentArgs::new();
+                args.set("agency", value);
+
+                return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "unknown_agency",
+                    "message": message_with(current_locale(), "unknown-agency", &args)
+                })));
+            }
+        },
+        None => None,
+    };
+
+    let filters = FacilityFilters {
+        state: query.state.clone().filter(|state| !state.trim().is_empty()),
+        agency,
+        campgrounds_only: query.campgrounds_only,
+    };
+
+    match client.search_facilities(q).await {
+        Ok(facilities) => {
+            let mut facilities: Vec<Facility> = facilities
+                .into_iter()
+                .filter(|facility| filters.matches(facility))
+                .collect();
+            add_location_descriptions(&geocoder, &mut facilities);
+
+            log::debug!("🎯 Returning {} facilities", facilities.len());
+
+            // Same shape as the RIDB response the frontend was built against
+            Ok(HttpResponse::Ok().json(RidbResponse::new(facilities)))
+        }
+        Err(e) => {
+            log::error!("❌ Error searching recreation areas: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "search_failed",
+                "message": message(current_locale(), "search-failed")
+            })))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ridb::{FacilityAddress, Organization};
+
+    fn facility(kind: &str, state: &str, agency: &str) -> Facility {
+        Facility {
+            id: "1".to_string(),
+            name: "Test".to_string(),
+            type_description: Some(kind.to_string()),
+            addresses: vec![FacilityAddress {
+                state: Some(state.to_string()),
+            }],
+            organizations: vec![Organization {
+                abbreviation: agency.to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn filters_facilities() {
+        let campground = facility("Campground", "CA", "NPS");
+        let trailhead = facility("Trailhead", "CA", "NPS");
+        let forest_camp = facility("Campground", "OR", "USFS");
+
+        let california_parks = FacilityFilters {
+            state: Some("ca".to_string()),
+            agency: Agency::parse("nps"),
+            campgrounds_only: false,
+        };
+        assert!(california_parks.matches(&campground));
+        assert!(california_parks.matches(&trailhead));
+        assert!(!california_parks.matches(&forest_camp));
+
+        let campgrounds = FacilityFilters {
+            campgrounds_only: true,
+            ..Default::default()
+        };
+        assert!(campgrounds.matches(&campground));
+        assert!(!campgrounds.matches(&trailhead));
+        assert!(campgrounds.matches(&forest_camp));
     }
 }
@@ -0,0 +1,115 @@
+use app_config::AppConfig;
+use std::path::PathBuf;
+
+use crate::client::RecGovError;
+
+/// What the recreation.gov client does with raw responses on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// Responses come from recreation.gov and aren't kept
+    Off,
+    /// Responses come from recreation.gov and are saved as fixtures
+    Capture,
+    /// Responses come from saved fixtures; recreation.gov is never called
+    Replay,
+}
+
+impl FixtureMode {
+    /// Parses a `RECREATION_GOV_FIXTURES` value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(FixtureMode::Off),
+            "capture" => Some(FixtureMode::Capture),
+            "replay" => Some(FixtureMode::Replay),
+            _ => None,
+        }
+    }
+}
+
+/// Raw recreation.gov responses saved under `RECREATION_GOV_FIXTURES_DIR`, one file per
+/// endpoint and URL, e.g.
+/// `recgov_availability_month/api_camps_availability_campground_232447_month_start_date_2026-07-01T00_00_00.000Z.json`.
+/// Captured files are what recreation.gov sent, so a format change can be replayed
+/// through the parser and validation as a regression test.
+#[derive(Debug, Clone)]
+pub(crate) struct ResponseFixtures {
+    mode: FixtureMode,
+    dir: PathBuf,
+}
+
+impl ResponseFixtures {
+    /// Fixtures as configured by `RECREATION_GOV_FIXTURES` and `RECREATION_GOV_FIXTURES_DIR`
+    pub(crate) fn from_config(config: &AppConfig) -> Self {
+        let fixtures = Self {
+            mode: FixtureMode::parse(&config.recreation_gov_fixtures).unwrap_or(FixtureMode::Off),
+            dir: PathBuf::from(&config.recreation_gov_fixtures_dir),
+        };
+
+        match fixtures.mode {
+            FixtureMode::Off => {}
+            FixtureMode::Capture => log::info!(
+                "📼 Capturing recreation.gov responses to {}",
+                fixtures.dir.display()
+            ),
+            FixtureMode::Replay => log::info!(
+                "📼 Replaying recreation.gov responses from {}",
+                fixtures.dir.display()
+            ),
+        }
+        fixtures
+    }
+
+    /// What is done with responses
+    pub(crate) fn mode(&self) -> FixtureMode {
+        self.mode
+    }
+
+    /// Reads the fixture saved for a call
+    pub(crate) fn load(&self, endpoint: &str, url: &str) -> Result<String, RecGovError> {
+        let path = self.path(endpoint, url);
+
+        std::fs::read_to_string(&path)
+            .map_err(|_| RecGovError::MissingFixture(path.display().to_string()))
+    }
+
+    /// Saves the response to a call, replacing any earlier capture. Failures are logged,
+    /// since capturing mustn't break the call.
+    pub(crate) fn save(&self, endpoint: &str, url: &str, body: &str) {
+        let path = self.path(endpoint, url);
+
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&path, body));
+
+        match result {
+            Ok(()) => log::debug!("📼 Captured {}", path.display()),
+            Err(e) => log::warn!("⚠️ Failed to capture {}: {}", path.display(), e),
+        }
+    }
+
+    /// File a call's response is saved in: the URL's path and query, without scheme and
+    /// host, made safe for a file name
+    fn path(&self, endpoint: &str, url: &str) -> PathBuf {
+        let path_and_query = url
+            .split_once("://")
+            .map_or(url, |(_, rest)| rest)
+            .split_once('/')
+            .map_or("", |(_, path)| path);
+
+        let file_name: String = path_and_query
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        self.dir
+            .join(endpoint)
+            .join(format!("{}.json", file_name.trim_matches('_')))
+    }
+}
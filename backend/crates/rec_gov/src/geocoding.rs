@@ -0,0 +1,406 @@
+use app_config::AppConfig;
+use reqwest::Client;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::pacing::{Pacer, PacingProfile};
+use crate::ttl_cache::TtlCache;
+
+/// User agent sent to geocoding providers (Nominatim rejects anonymous clients).
+const GEOCODING_USER_AGENT: &str = "CampTracker/0.1 (campsite availability alerts)";
+
+/// Most location descriptions kept in memory
+const LOCATION_CACHE_CAPACITY: usize = 4096;
+
+/// How long a location description is reused before it's looked up again
+const LOCATION_CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Most coordinates waiting to be described in the background; more are dropped and
+/// picked up by a later search
+const LOCATION_BACKLOG_CAPACITY: usize = 500;
+
+/// Least time between two provider requests (Nominatim's usage policy allows one a second)
+const GEOCODING_MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Pacer key shared by all geocoding requests
+const GEOCODING_PACER_KEY: &str = "geocoding";
+
+/// Location descriptions keyed by coordinates rounded to two decimal places
+type LocationCache = Arc<Mutex<TtlCache<(i64, i64), Option<String>>>>;
+
+/// Coordinates waiting to be described by the background task, and whether that task is
+/// running. One task drains the backlog, so at most one of its lookups is waiting on the
+/// pacer at a time.
+#[derive(Debug, Default)]
+struct LocationBacklog {
+    queue: VecDeque<(f64, f64)>,
+    draining: bool,
+}
+
+/// A populated place near a set of coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearbyPlace {
+    /// Place name (e.g. `Bishop`)
+    pub name: String,
+    /// Two-letter state code, if known
+    pub state: Option<String>,
+    /// Latitude of the place
+    pub latitude: f64,
+    /// Longitude of the place
+    pub longitude: f64,
+}
+
+/// Reverse geocoding backend, selected with `GEOCODING_PROVIDER`.
+#[derive(Debug, Clone)]
+pub enum GeocodingProvider {
+    /// OpenStreetMap Nominatim (`GEOCODING_PROVIDER=nominatim`)
+    Nominatim {
+        /// Base URL of the Nominatim instance
+        base_url: String,
+    },
+    /// GeoNames nearby place lookup (`GEOCODING_PROVIDER=geonames`, needs `GEONAMES_USERNAME`)
+    GeoNames {
+        /// Base URL of the GeoNames web services
+        base_url: String,
+        /// GeoNames account name
+        username: String,
+    },
+    /// No geocoding; locations are never described
+    Disabled,
+}
+
+/// Reverse geocoder that turns campground coordinates into a human-readable location
+/// such as "12 mi NE of Bishop, CA". Lookups are cached in memory, and requests to the
+/// provider go out one at a time, at most one a second. Clones share the cache, the
+/// background backlog, and pacing.
+#[derive(Debug, Clone)]
+pub struct Geocoder {
+    provider: GeocodingProvider,
+    client: Client,
+    cache: LocationCache,
+    backlog: Arc<Mutex<LocationBacklog>>,
+    pacer: Pacer,
+}
+
+impl Geocoder {
+    /// Creates a geocoder backed by the given provider.
+    pub fn new(provider: GeocodingProvider) -> Self {
+        Self {
+            provider,
+            client: Client::new(),
+            cache: Arc::new(Mutex::new(TtlCache::new(
+                LOCATION_CACHE_CAPACITY,
+                LOCATION_CACHE_TTL,
+            ))),
+            backlog: Arc::default(),
+            pacer: Pacer::new(PacingProfile::Off, GEOCODING_MIN_INTERVAL),
+        }
+    }
+
+    /// Creates a geocoder that never describes locations.
+    pub fn disabled() -> Self {
+        Self::new(GeocodingProvider::Disabled)
+    }
+
+    /// Configures the provider from `GEOCODING_PROVIDER` (`nominatim`, `geonames`, or
+    /// `none`), with an optional `GEOCODING_BASE_URL` override.
+    pub fn from_config(config: &AppConfig) -> Self {
+        let base_url = config.geocoding_base_url.clone();
+
+        let provider = match (
+            config.geocoding_provider.as_str(),
+            &config.geonames_username,
+        ) {
+            ("nominatim", _) => GeocodingProvider::Nominatim {
+                base_url: base_url
+                    .unwrap_or_else(|| "https://nominatim.openstreetmap.org".to_string()),
+            },
+            ("geonames", Some(username)) => GeocodingProvider::GeoNames {
+                base_url: base_url.unwrap_or_else(|| "https://secure.geonames.org".to_string()),
+                username: username.clone(),
+            },
+            _ => GeocodingProvider::Disabled,
+        };
+
+        log::info!("🗺️ Geocoding provider: {:?}", provider);
+
+        Self::new(provider)
+    }
+
+    /// Describes the location of the coordinates relative to the nearest place, e.g.
+    /// "12 mi NE of Bishop, CA". Returns `None` if geocoding is disabled, no place is
+    /// found, or the provider fails.
+    pub async fn describe_location(&self, latitude: f64, longitude: f64) -> Option<String> {
+        if matches!(self.provider, GeocodingProvider::Disabled) {
+            return None;
+        }
+
+        let key = cache_key(latitude, longitude);
+        if let Some(cached) = self.cache.lock().unwrap().get(&key) {
+            return cached;
+        }
+
+        let place = match self.nearest_place(latitude, longitude).await {
+            Ok(place) => place,
+            Err(e) => {
+                log::warn!(
+                    "⚠️ Reverse geocoding ({}, {}) failed: {}",
+                    latitude,
+                    longitude,
+                    e
+                );
+                return None;
+            }
+        };
+
+        let description = place.map(|place| describe_relative_to(latitude, longitude, &place));
+        self.cache.lock().unwrap().insert(key, description.clone());
+
+        description
+    }
+
+    /// Describes the coordinates only if the description is cached (or geocoding is
+    /// disabled); otherwise queues them to be described in the background and returns
+    /// `None`. For request paths that can't wait on the provider's pacing.
+    pub fn describe_cached_location(&self, latitude: f64, longitude: f64) -> Option<String> {
+        if matches!(self.provider, GeocodingProvider::Disabled) {
+            return None;
+        }
+
+        if let Some(cached) = self
+            .cache
+            .lock()
+            .unwrap()
+            .get(&cache_key(latitude, longitude))
+        {
+            return cached;
+        }
+
+        self.describe_later(latitude, longitude);
+        None
+    }
+
+    /// Queues coordinates for the background task, starting it if it isn't running.
+    /// Coordinates beyond [`LOCATION_BACKLOG_CAPACITY`] are dropped.
+    fn describe_later(&self, latitude: f64, longitude: f64) {
+        let mut backlog = self.backlog.lock().unwrap();
+        if backlog.queue.len() >= LOCATION_BACKLOG_CAPACITY {
+            return;
+        }
+        backlog.queue.push_back((latitude, longitude));
+
+        if !backlog.draining {
+            backlog.draining = true;
+            tokio::spawn(self.clone().drain_backlog());
+        }
+    }
+
+    /// Describes queued coordinates one at a time until the backlog is empty
+    async fn drain_backlog(self) {
+        loop {
+            let next = {
+                let mut backlog = self.backlog.lock().unwrap();
+                let next = backlog.queue.pop_front();
+                backlog.draining = next.is_some();
+                next
+            };
+            let Some((latitude, longitude)) = next else {
+                return;
+            };
+
+            // Repeats of coordinates described earlier in the backlog hit the cache
+            self.describe_location(latitude, longitude).await;
+        }
+    }
+
+    /// Looks up the populated place nearest to the coordinates with the configured provider.
+    async fn nearest_place(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<Option<NearbyPlace>, reqwest::Error> {
+        match &self.provider {
+            GeocodingProvider::Nominatim { base_url } => {
+                let url = format!(
+                    "{}/reverse?format=jsonv2&lat={}&lon={}&zoom=10&addressdetails=1",
+                    base_url, latitude, longitude
+                );
+                let data: Value = self.get_json(&url).await?;
+
+                Ok(parse_nominatim_place(&data))
+            }
+            GeocodingProvider::GeoNames { base_url, username } => {
+                let url = format!(
+                    "{}/findNearbyPlaceNameJSON?lat={}&lng={}&cities=cities1000&username={}",
+                    base_url,
+                    latitude,
+                    longitude,
+                    urlencoding::encode(username)
+                );
+                let data: Value = self.get_json(&url).await?;
+
+                Ok(parse_geonames_place(&data))
+            }
+            GeocodingProvider::Disabled => Ok(None),
+        }
+    }
+
+    async fn get_json(&self, url: &str) -> Result<Value, reqwest::Error> {
+        self.pacer.wait(GEOCODING_PACER_KEY).await;
+
+        self.client
+            .get(url)
+            .header("User-Agent", GEOCODING_USER_AGENT)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+}
+
+/// Cache key of the coordinates, rounded to ~1 km so nearby lookups share an entry
+fn cache_key(latitude: f64, longitude: f64) -> (i64, i64) {
+    (
+        (latitude * 100.0).round() as i64,
+        (longitude * 100.0).round() as i64,
+    )
+}
+
+/// Extracts the nearest place from a Nominatim reverse geocoding response.
+fn parse_nominatim_place(data: &Value) -> Option<NearbyPlace> {
+    let address = data.get("address")?;
+    let name = ["city", "town", "village", "hamlet", "county"]
+        .iter()
+        .find_map(|field| address.get(field).and_then(|v| v.as_str()))?;
+
+    // "ISO3166-2-lvl4": "US-CA"
+    let state = address
+        .get("ISO3166-2-lvl4")
+        .and_then(|v| v.as_str())
+        .and_then(|code| code.strip_prefix("US-"))
+        .map(str::to_string);
+
+    Some(NearbyPlace {
+        name: name.to_string(),
+        state,
+        latitude: data.get("lat")?.as_str()?.parse().ok()?,
+        longitude: data.get("lon")?.as_str()?.parse().ok()?,
+    })
+}
+
+/// Extracts the nearest place from a GeoNames `findNearbyPlaceNameJSON` response.
+fn parse_geonames_place(data: &Value) -> Option<NearbyPlace> {
+    let place = data.get("geonames")?.as_array()?.first()?;
+
+    Some(NearbyPlace {
+        name: place.get("name")?.as_str()?.to_string(),
+        state: place
+            .get("adminCode1")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        latitude: place.get("lat")?.as_str()?.parse().ok()?,
+        longitude: place.get("lng")?.as_str()?.parse().ok()?,
+    })
+}
+
+/// Formats the coordinates relative to a place, e.g. "12 mi NE of Bishop, CA".
+pub fn describe_relative_to(latitude: f64, longitude: f64, place: &NearbyPlace) -> String {
+    let place_name = match &place.state {
+        Some(state) => format!("{}, {}", place.name, state),
+        None => place.name.clone(),
+    };
+
+    let miles = distance_miles(place.latitude, place.longitude, latitude, longitude);
+    if miles < 1.0 {
+        return format!("Near {}", place_name);
+    }
+
+    let direction = compass_direction(place.latitude, place.longitude, latitude, longitude);
+    format!("{:.0} mi {} of {}", miles, direction, place_name)
+}
+
+/// Great-circle distance between two coordinates in miles.
+pub fn distance_miles(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> f64 {
+    const EARTH_RADIUS_MILES: f64 = 3958.8;
+
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lng = (lng2 - lng1).to_radians();
+
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_MILES * a.sqrt().asin()
+}
+
+/// Eight-point compass direction of travel from the first coordinate to the second.
+fn compass_direction(lat1: f64, lng1: f64, lat2: f64, lng2: f64) -> &'static str {
+    const DIRECTIONS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lng = (lng2 - lng1).to_radians();
+
+    let y = d_lng.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_lng.cos();
+    let bearing = y.atan2(x).to_degrees().rem_euclid(360.0);
+
+    DIRECTIONS[((bearing + 22.5) / 45.0) as usize % 8]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_location_cache_evicts_the_least_recently_used_entry() {
+        let mut cache = TtlCache::new(2, LOCATION_CACHE_TTL);
+        cache.insert((1, 1), Some("Near Bishop, CA".to_string()));
+        cache.insert((2, 2), None);
+
+        // Using the first entry leaves the second as the least recently used
+        assert_eq!(
+            cache.get(&(1, 1)),
+            Some(Some("Near Bishop, CA".to_string()))
+        );
+        cache.insert((3, 3), Some("Near Lee Vining, CA".to_string()));
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&(2, 2)), None);
+        assert_eq!(
+            cache.get(&(1, 1)),
+            Some(Some("Near Bishop, CA".to_string()))
+        );
+        assert_eq!(
+            cache.get(&(3, 3)),
+            Some(Some("Near Lee Vining, CA".to_string()))
+        );
+    }
+
+    #[test]
+    fn expired_locations_are_looked_up_again() {
+        let mut cache = TtlCache::new(2, Duration::ZERO);
+        cache.insert((1, 1), Some("Near Bishop, CA".to_string()));
+
+        assert_eq!(cache.get(&(1, 1)), None);
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn cached_lookups_never_wait_on_the_provider() {
+        let geocoder = Geocoder::new(GeocodingProvider::Nominatim {
+            base_url: "http://127.0.0.1:1".to_string(),
+        });
+        geocoder.cache.lock().unwrap().insert(
+            cache_key(37.36, -118.39),
+            Some("Near Bishop, CA".to_string()),
+        );
+
+        assert_eq!(
+            geocoder.describe_cached_location(37.361, -118.392),
+            Some("Near Bishop, CA".to_string())
+        );
+        assert_eq!(geocoder.describe_cached_location(38.0, -119.0), None);
+        assert!(geocoder.backlog.lock().unwrap().draining);
+    }
+}
@@ -5,3 +5,89 @@
 /// Search for campgrounds given a query string on the Rec.gov API.
 mod facility_search;
 pub use facility_search::*;
+
+/// Local-first campground autocomplete over the cached campgrounds.
+mod campground_search;
+pub use campground_search::*;
+
+/// Campground detail page data (campsites and photos), cached in the database.
+mod campground_details;
+pub use campground_details::*;
+
+/// On-demand availability previews from recreation.gov.
+mod availability;
+pub use availability::*;
+
+/// Permit quotas (e.g. Half Dome) from recreation.gov.
+mod permits;
+pub use permits::*;
+
+/// Lenient parsing of availability fields, with counts of those that drifted.
+mod drift;
+pub use drift::{FieldErrorCount, field_error_counts};
+
+/// Sanity checks that keep corrupted upstream availability out of the cache.
+mod validation;
+pub use validation::*;
+
+/// Client for the recreation.gov / RIDB APIs.
+mod client;
+pub use client::*;
+
+/// Typed RIDB response structures.
+mod ridb;
+pub use ridb::*;
+
+/// Campsite hookups, vehicle length and pet rules, and the scan filters matching them.
+mod site_attributes;
+pub use site_attributes::*;
+
+/// The recreation.gov calls the scan pipeline makes, as a trait tests can mock.
+mod rec_gov_api;
+pub use rec_gov_api::*;
+
+/// Fixture-backed recreation.gov stand-in for tests.
+mod mock;
+pub use mock::*;
+
+/// Reservation systems scans can be checked against.
+mod provider;
+pub use provider::*;
+
+/// Rotating proxies recreation.gov calls go out through.
+mod proxy;
+pub use proxy::*;
+
+/// Captured recreation.gov responses, saved and replayed for regression tests.
+mod fixtures;
+pub use fixtures::FixtureMode;
+
+/// Pacing of recreation.gov calls, so the scanner looks less like a bot.
+mod pacing;
+pub use pacing::*;
+
+/// Browser-like sessions (user agent and cookies) held with recreation.gov.
+mod session;
+
+/// ReserveCalifornia (California state parks) client.
+mod reserve_california;
+pub use reserve_california::*;
+
+/// ID of the request being handled, forwarded on upstream calls.
+mod request_id;
+pub use request_id::*;
+
+/// Ledger of upstream API calls for usage reporting.
+mod api_ledger;
+pub use api_ledger::*;
+
+/// Sandbox mode flag and recreation.gov fixtures.
+mod sandbox;
+pub use sandbox::*;
+
+/// Size-bounded in-memory cache with expiring entries.
+mod ttl_cache;
+
+/// Reverse geocoding of campground coordinates into human-readable locations.
+mod geocoding;
+pub use geocoding::*;
@@ -0,0 +1,166 @@
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::availability::{CampsiteAvailability, months_between};
+use crate::client::RecGovError;
+use crate::provider::{Provider, ProviderClient};
+use crate::rec_gov_api::RecGovApi;
+use crate::ridb::{Campsite, CampsiteAttribute, Facility};
+use crate::sandbox::{
+    sandbox_availability, sandbox_campsite_attributes, sandbox_campsites, sandbox_facilities,
+    sandbox_facility,
+};
+
+/// A call made to a [`MockRecGov`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockCall {
+    /// Availability of a campground for the nights `start..end`
+    Availability {
+        /// Campground asked about
+        campground_id: String,
+        /// First night
+        start: NaiveDate,
+        /// Night after the last
+        end: NaiveDate,
+    },
+    /// Facility search
+    Search(String),
+    /// Facility lookup
+    Facility(String),
+    /// Campsite listing
+    Campsites(String),
+    /// Campsite attribute listing
+    CampsiteAttributes(String),
+}
+
+#[derive(Default)]
+struct MockState {
+    availability: HashMap<String, Vec<CampsiteAvailability>>,
+    rate_limited: bool,
+    panicking: HashSet<String>,
+    calls: Vec<MockCall>,
+}
+
+/// Recreation.gov stand-in for tests. It serves the sandbox fixtures unless a test sets a
+/// campground's availability, and records every call. Clones share their state, so a
+/// test can change what a pipeline under test sees between polls.
+#[derive(Clone, Default)]
+pub struct MockRecGov {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockRecGov {
+    /// Creates a mock serving the sandbox fixtures
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves `sites` as the campground's availability from now on
+    pub fn set_availability(&self, campground_id: &str, sites: Vec<CampsiteAvailability>) {
+        self.state
+            .lock()
+            .unwrap()
+            .availability
+            .insert(campground_id.to_string(), sites);
+    }
+
+    /// Makes every call fail as rate limited, or succeed again
+    pub fn set_rate_limited(&self, rate_limited: bool) {
+        self.state.lock().unwrap().rate_limited = rate_limited;
+    }
+
+    /// Makes availability calls for the campground panic, like a bug tripping over one of
+    /// its responses would
+    pub fn set_panicking(&self, campground_id: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .panicking
+            .insert(campground_id.to_string());
+    }
+
+    /// Calls made so far, oldest first
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    /// Records a call, failing it when rate limited
+    fn call(&self, call: MockCall) -> Result<(), RecGovError> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(call);
+
+        if state.rate_limited {
+            return Err(RecGovError::RateLimited);
+        }
+        Ok(())
+    }
+}
+
+impl ProviderClient for MockRecGov {
+    fn provider(&self) -> Provider {
+        Provider::RecreationGov
+    }
+
+    async fn campground_availability(
+        &self,
+        campground_id: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<CampsiteAvailability>, RecGovError> {
+        self.call(MockCall::Availability {
+            campground_id: campground_id.to_string(),
+            start,
+            end,
+        })?;
+
+        let panicking = self.state.lock().unwrap().panicking.contains(campground_id);
+        if panicking {
+            panic!("mock availability of campground {} panicked", campground_id);
+        }
+
+        if let Some(sites) = self.state.lock().unwrap().availability.get(campground_id) {
+            return Ok(sites.clone());
+        }
+
+        // Whole months, like the real client
+        let mut sites: HashMap<String, CampsiteAvailability> = HashMap::new();
+        for month in months_between(start, end) {
+            for (campsite_id, site) in sandbox_availability(campground_id, month).campsites {
+                match sites.get_mut(&campsite_id) {
+                    Some(existing) => existing.availabilities.extend(site.availabilities),
+                    None => {
+                        sites.insert(campsite_id, site);
+                    }
+                }
+            }
+        }
+
+        Ok(sites.into_values().collect())
+    }
+}
+
+impl RecGovApi for MockRecGov {
+    async fn search_facilities(&self, query: &str) -> Result<Vec<Facility>, RecGovError> {
+        self.call(MockCall::Search(query.to_string()))?;
+        Ok(sandbox_facilities(query))
+    }
+
+    async fn facility(&self, facility_id: &str) -> Result<Option<Facility>, RecGovError> {
+        self.call(MockCall::Facility(facility_id.to_string()))?;
+        Ok(sandbox_facility(facility_id))
+    }
+
+    async fn facility_campsites(&self, facility_id: &str) -> Result<Vec<Campsite>, RecGovError> {
+        self.call(MockCall::Campsites(facility_id.to_string()))?;
+        Ok(sandbox_campsites(facility_id))
+    }
+
+    async fn campsite_attributes(
+        &self,
+        campsite_id: &str,
+    ) -> Result<Vec<CampsiteAttribute>, RecGovError> {
+        self.call(MockCall::CampsiteAttributes(campsite_id.to_string()))?;
+        Ok(sandbox_campsite_attributes(campsite_id))
+    }
+}
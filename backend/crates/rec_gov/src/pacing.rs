@@ -0,0 +1,150 @@
+use app_config::AppConfig;
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How calls to recreation.gov's website API are spaced out, so the scanner looks less
+/// like a bot when it's busiest (e.g. on release mornings)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacingProfile {
+    /// Calls go out as soon as they're made
+    Off,
+    /// A short random delay before each call, and calls for one campground at least two
+    /// seconds apart
+    Steady,
+    /// Longer, irregular delays with the occasional pause, and calls for one campground at
+    /// least six seconds apart
+    Human,
+}
+
+impl PacingProfile {
+    /// Profile named by `RECREATION_GOV_PACING`; replayed fixtures are never paced
+    pub fn from_config(config: &AppConfig) -> Self {
+        if config.recreation_gov_fixtures == "replay" {
+            return PacingProfile::Off;
+        }
+
+        let profile = Self::parse(&config.recreation_gov_pacing).unwrap_or(PacingProfile::Off);
+
+        if profile != PacingProfile::Off && !config.sandbox_mode {
+            log::info!(
+                "🐢 Pacing recreation.gov availability calls with the {} profile",
+                profile.as_str()
+            );
+        }
+        profile
+    }
+
+    /// Parses a `RECREATION_GOV_PACING` value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(PacingProfile::Off),
+            "steady" => Some(PacingProfile::Steady),
+            "human" => Some(PacingProfile::Human),
+            _ => None,
+        }
+    }
+
+    /// Name of the profile in `RECREATION_GOV_PACING`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PacingProfile::Off => "off",
+            PacingProfile::Steady => "steady",
+            PacingProfile::Human => "human",
+        }
+    }
+
+    /// Shortest gap between two calls for the same campground
+    fn campground_spacing(&self) -> Duration {
+        match self {
+            PacingProfile::Off => Duration::ZERO,
+            PacingProfile::Steady => Duration::from_secs(2),
+            PacingProfile::Human => Duration::from_secs(6),
+        }
+    }
+
+    /// Random delay added before a call
+    fn jitter(&self) -> Duration {
+        let mut rng = rand::rng();
+
+        let millis = match self {
+            PacingProfile::Off => 0,
+            PacingProfile::Steady => rng.random_range(100..400),
+            // Mostly quick, sometimes a visitor stops to read the page
+            PacingProfile::Human if rng.random_bool(0.1) => rng.random_range(3_000..8_000),
+            PacingProfile::Human => rng.random_range(400..2_500),
+        };
+
+        Duration::from_millis(millis)
+    }
+}
+
+/// Holds calls back according to a [`PacingProfile`], and keeps any two calls at least
+/// the minimum interval apart. Each call reserves its slot before waiting, so concurrent
+/// calls for one campground queue up instead of bunching. Clones share their schedule
+/// and minimum interval.
+#[derive(Debug, Clone)]
+pub(crate) struct Pacer {
+    profile: PacingProfile,
+    /// Least time between any two calls, in milliseconds; changed at runtime
+    min_interval_ms: Arc<AtomicU64>,
+    /// When the latest call for each campground is scheduled to go out
+    scheduled: Arc<Mutex<HashMap<String, Instant>>>,
+    /// When the latest call of any campground is scheduled to go out
+    last_scheduled: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Pacer {
+    /// Creates a pacer following `profile`, with calls at least `min_interval` apart
+    pub(crate) fn new(profile: PacingProfile, min_interval: Duration) -> Self {
+        Self {
+            profile,
+            min_interval_ms: Arc::new(AtomicU64::new(min_interval.as_millis() as u64)),
+            scheduled: Arc::new(Mutex::new(HashMap::new())),
+            last_scheduled: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Least time between any two calls
+    pub(crate) fn min_interval(&self) -> Duration {
+        Duration::from_millis(self.min_interval_ms.load(Ordering::Relaxed))
+    }
+
+    /// Changes the least time between any two calls, from the next call on
+    pub(crate) fn set_min_interval(&self, min_interval: Duration) {
+        self.min_interval_ms
+            .store(min_interval.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Waits until a call for `campground_id` may go out
+    pub(crate) async fn wait(&self, campground_id: &str) {
+        let min_interval = self.min_interval();
+        if self.profile == PacingProfile::Off && min_interval.is_zero() {
+            return;
+        }
+
+        let now = Instant::now();
+        let send_at = {
+            let mut scheduled = self.scheduled.lock().unwrap();
+            scheduled.retain(|_, at| *at + self.profile.campground_spacing() > now);
+
+            let earliest = scheduled
+                .get(campground_id)
+                .map_or(now, |at| (*at + self.profile.campground_spacing()).max(now));
+            let mut send_at = earliest + self.profile.jitter();
+
+            let mut last_scheduled = self.last_scheduled.lock().unwrap();
+            if let Some(last) = *last_scheduled {
+                send_at = send_at.max(last + min_interval);
+            }
+            *last_scheduled = Some(last_scheduled.map_or(send_at, |last| last.max(send_at)));
+
+            scheduled.insert(campground_id.to_string(), send_at);
+            send_at
+        };
+
+        tokio::time::sleep_until(send_at.into()).await;
+    }
+}
@@ -0,0 +1,169 @@
+use actix_web::{HttpResponse, Result, web};
+use chrono::NaiveDate;
+use i18n::{FluentArgs, current_locale, message, message_with};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::client::{RecGovClient, RecGovError};
+use crate::drift::parse_night_key;
+
+/// Longest date range permit availability may be requested for
+const MAX_PERMIT_DAYS: i64 = 93;
+
+/// Quota of one permit division (trailhead, zone, or entry point) on one day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermitDateAvailability {
+    /// Permits issued for the day
+    #[serde(default)]
+    pub total: i32,
+    /// Permits still available to reserve
+    #[serde(default)]
+    pub remaining: i32,
+    /// Whether the remaining quota is held for walk-up issue
+    #[serde(default)]
+    pub show_walkup: bool,
+    /// Whether the quota is hidden from the public calendar
+    #[serde(default)]
+    pub is_secret_quota: bool,
+}
+
+/// Daily quotas of one permit division, from recreation.gov's monthly permit API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermitDivisionAvailability {
+    /// Recreation.gov division ID
+    pub division_id: String,
+    /// Quota per day, keyed by `YYYY-MM-DDT00:00:00Z`
+    #[serde(default)]
+    pub date_availability: BTreeMap<String, PermitDateAvailability>,
+}
+
+impl PermitDivisionAvailability {
+    /// Returns the quota of each day, skipping keys that aren't dates.
+    pub fn days(&self) -> impl Iterator<Item = (NaiveDate, &PermitDateAvailability)> {
+        self.date_availability.iter().filter_map(|(key, quota)| {
+            let date = parse_night_key(key)?;
+            Some((date, quota))
+        })
+    }
+
+    /// Days in `start..=end` with at least `group_size` reservable permits
+    pub fn open_days(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        group_size: i32,
+    ) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.days()
+            .filter(move |(date, quota)| {
+                *date >= start
+                    && *date <= end
+                    && !quota.show_walkup
+                    && quota.remaining >= group_size.max(1)
+            })
+            .map(|(date, _)| date)
+    }
+}
+
+/// Recreation.gov's monthly permit availability payload
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PermitMonthAvailability {
+    /// Divisions keyed by division ID
+    #[serde(default)]
+    pub availability: HashMap<String, PermitDivisionAvailability>,
+}
+
+/// Envelope of recreation.gov's permit responses
+#[derive(Debug, Deserialize)]
+pub(crate) struct PermitResponse<T> {
+    pub payload: T,
+}
+
+/// Query parameters for permit availability
+#[derive(Debug, Deserialize)]
+pub struct PermitAvailabilityQuery {
+    /// First entry date
+    pub start: NaiveDate,
+    /// Last entry date (inclusive)
+    pub end: NaiveDate,
+}
+
+/// Reservable permit quota of one division over a date range
+#[derive(Debug, Serialize)]
+pub struct PermitCalendar {
+    /// Recreation.gov division ID
+    pub division_id: String,
+    /// Permits remaining per day
+    pub remaining: BTreeMap<NaiveDate, i32>,
+}
+
+/// Public endpoint showing how many permits remain per division and day in
+/// `start..=end`
+pub async fn get_permit_availability(
+    client: web::Data<RecGovClient>,
+    path: web::Path<String>,
+    query: web::Query<PermitAvailabilityQuery>,
+) -> Result<HttpResponse> {
+    let permit_id = path.into_inner();
+    let days = (query.end - query.start).num_days();
+    let locale = current_locale();
+
+    if !(0..=MAX_PERMIT_DAYS).contains(&days) {
+        let mut args = FluentArgs::new();
+        args.set("max", MAX_PERMIT_DAYS);
+
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "invalid_date_range",
+            "message": message_with(locale, "permit-date-range", &args)
+        })));
+    }
+
+    let divisions = match client
+        .on_demand()
+        .get_permit_availability(&permit_id, query.start, query.end)
+        .await
+    {
+        Ok(divisions) => divisions,
+        Err(RecGovError::RateLimited) => {
+            let mut args = FluentArgs::new();
+            args.set("minutes", 1);
+
+            return Ok(HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", "60"))
+                .json(serde_json::json!({
+                    "error": "rate_limited",
+                    "message": message_with(locale, "rate-limited", &args)
+                })));
+        }
+        Err(e) => {
+            log::error!(
+                "❌ Failed to fetch permit {} availability: {}",
+                permit_id,
+                e
+            );
+            return Ok(HttpResponse::BadGateway().json(serde_json::json!({
+                "error": "upstream_error",
+                "message": message(locale, "permit-availability-failed")
+            })));
+        }
+    };
+
+    let mut calendars: Vec<PermitCalendar> = divisions
+        .iter()
+        .map(|division| PermitCalendar {
+            division_id: division.division_id.clone(),
+            remaining: division
+                .days()
+                .filter(|(date, _)| *date >= query.start && *date <= query.end)
+                .map(|(date, quota)| (date, quota.remaining))
+                .collect(),
+        })
+        .collect();
+    calendars.sort_by(|a, b| a.division_id.cmp(&b.division_id));
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "permit_id": permit_id,
+        "start": query.start,
+        "end": query.end,
+        "divisions": calendars
+    })))
+}
@@ -0,0 +1,143 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+use crate::availability::CampsiteAvailability;
+use crate::client::{RecGovClient, RecGovError};
+
+/// Prefix of the campground IDs of ReserveCalifornia facilities, keeping them apart from
+/// RIDB facility IDs, which are numeric too
+pub const RESERVE_CALIFORNIA_PREFIX: &str = "rc-";
+
+/// Reservation system a campground is booked through
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Provider {
+    /// Recreation.gov (federal campgrounds)
+    #[default]
+    RecreationGov,
+    /// ReserveCalifornia (California state parks, run on UseDirect)
+    ReserveCalifornia,
+}
+
+impl Provider {
+    /// Every provider
+    pub const ALL: [Provider; 2] = [Provider::RecreationGov, Provider::ReserveCalifornia];
+
+    /// Parses the value stored in `campgrounds.provider` and `user_scans.provider`.
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|provider| provider.as_str() == value)
+    }
+
+    /// Value stored in `campgrounds.provider` and `user_scans.provider`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Provider::RecreationGov => "recreation_gov",
+            Provider::ReserveCalifornia => "reserve_california",
+        }
+    }
+
+    /// Provider of a stored campground ID
+    pub fn of_campground(campground_id: &str) -> Self {
+        if campground_id.starts_with(RESERVE_CALIFORNIA_PREFIX) {
+            Provider::ReserveCalifornia
+        } else {
+            Provider::RecreationGov
+        }
+    }
+
+    /// Campground ID stored for a facility ID of this provider, e.g. `rc-718` for
+    /// ReserveCalifornia facility 718. IDs that are already namespaced are kept.
+    pub fn campground_id(&self, facility_id: &str) -> String {
+        match self {
+            Provider::RecreationGov => facility_id.to_string(),
+            Provider::ReserveCalifornia if facility_id.starts_with(RESERVE_CALIFORNIA_PREFIX) => {
+                facility_id.to_string()
+            }
+            Provider::ReserveCalifornia => format!("{}{}", RESERVE_CALIFORNIA_PREFIX, facility_id),
+        }
+    }
+
+    /// Name of the reservation system shown to users
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Provider::RecreationGov => "Recreation.gov",
+            Provider::ReserveCalifornia => "ReserveCalifornia",
+        }
+    }
+
+    /// Page where a stored campground can be booked
+    pub fn booking_url(&self, campground_id: &str) -> String {
+        match self {
+            Provider::RecreationGov => format!(
+                "https://www.recreation.gov/camping/campgrounds/{}",
+                self.facility_id(campground_id)
+            ),
+            Provider::ReserveCalifornia => "https://www.reservecalifornia.com/".to_string(),
+        }
+    }
+
+    /// Booking page of one campsite with the stay's dates filled in, so a user can go
+    /// from an alert to checkout in a tap. Recreation.gov has no public link into the
+    /// cart, so this opens the site's page with the dates selected; ReserveCalifornia
+    /// links can't name a site, so it has none.
+    pub fn site_booking_url(
+        &self,
+        campsite_id: &str,
+        check_in_date: NaiveDate,
+        check_out_date: NaiveDate,
+    ) -> Option<String> {
+        match self {
+            Provider::RecreationGov => Some(format!(
+                "https://www.recreation.gov/camping/campsites/{}?startDate={}&endDate={}",
+                urlencoding::encode(campsite_id),
+                check_in_date.format("%Y-%m-%d"),
+                check_out_date.format("%Y-%m-%d")
+            )),
+            Provider::ReserveCalifornia => None,
+        }
+    }
+
+    /// Facility ID the provider's API knows a stored campground ID by
+    pub fn facility_id<'a>(&self, campground_id: &'a str) -> &'a str {
+        match self {
+            Provider::RecreationGov => campground_id,
+            Provider::ReserveCalifornia => campground_id
+                .strip_prefix(RESERVE_CALIFORNIA_PREFIX)
+                .unwrap_or(campground_id),
+        }
+    }
+}
+
+/// Reservation system client that scans can be checked against
+pub trait ProviderClient {
+    /// Reservation system this client talks to
+    fn provider(&self) -> Provider;
+
+    /// Availability of every site of a campground for the nights `start..end`, keyed by
+    /// stored campground ID.
+    fn campground_availability(
+        &self,
+        campground_id: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> impl Future<Output = Result<Vec<CampsiteAvailability>, RecGovError>> + Send;
+}
+
+impl ProviderClient for RecGovClient {
+    fn provider(&self) -> Provider {
+        Provider::RecreationGov
+    }
+
+    async fn campground_availability(
+        &self,
+        campground_id: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<CampsiteAvailability>, RecGovError> {
+        self.get_internal_campground_availability(campground_id, start, end)
+            .await
+    }
+}
@@ -0,0 +1,256 @@
+use app_config::AppConfig;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Proxy, RequestBuilder, StatusCode, Url};
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::session::RecGovSession;
+
+/// Health of one proxy in the pool
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyHealth {
+    /// Proxy URL, with any password masked
+    pub url: String,
+    /// Whether the proxy is still in rotation
+    pub in_rotation: bool,
+    /// Calls sent through the proxy
+    pub requests: u64,
+    /// Calls that failed to connect or were blocked
+    pub failures: u64,
+    /// Failures since the last call that got through
+    pub consecutive_failures: u32,
+    /// Why the last failed call failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// When a call last got through
+    pub last_success: Option<DateTime<Utc>>,
+    /// When the proxy was taken out of rotation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed_at: Option<DateTime<Utc>>,
+}
+
+/// A proxy with the client that sends through it and the session held over it
+struct PooledProxy {
+    client: Client,
+    session: RecGovSession,
+    health: ProxyHealth,
+}
+
+/// Route a call goes out on: a proxy, or the direct connection
+#[derive(Clone)]
+pub(crate) struct ProxyLease {
+    client: Client,
+    proxy: Option<usize>,
+}
+
+impl ProxyLease {
+    /// Client sending through the route
+    pub(crate) fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+/// Rotating pool of proxies recreation.gov calls go out through, so one blocked IP
+/// doesn't stop polling. A proxy that fails to connect or gets blocked (403/429)
+/// `RECREATION_GOV_PROXY_MAX_FAILURES` times in a row is taken out of rotation until
+/// restart; once none is left, calls go out directly. Every route keeps its own
+/// [`RecGovSession`], renewed whenever recreation.gov blocks it.
+#[derive(Clone)]
+pub struct ProxyPool {
+    proxies: Arc<Mutex<Vec<PooledProxy>>>,
+    next: Arc<AtomicUsize>,
+    direct: Client,
+    direct_session: Arc<Mutex<RecGovSession>>,
+    max_failures: u32,
+}
+
+impl ProxyPool {
+    /// Creates a pool of the proxies in `RECREATION_GOV_PROXIES`. Proxies reqwest can't
+    /// use are logged and left out.
+    pub fn from_config(config: &AppConfig, direct: Client) -> Self {
+        let proxies = config
+            .recreation_gov_proxy_urls()
+            .into_iter()
+            .filter_map(|url| {
+                let masked = mask_password(url);
+                let client =
+                    Proxy::all(url).and_then(|proxy| Client::builder().proxy(proxy).build());
+
+                match client {
+                    Ok(client) => Some(PooledProxy {
+                        client,
+                        session: RecGovSession::new(),
+                        health: ProxyHealth {
+                            url: masked,
+                            in_rotation: true,
+                            requests: 0,
+                            failures: 0,
+                            consecutive_failures: 0,
+                            last_error: None,
+                            last_success: None,
+                            removed_at: None,
+                        },
+                    }),
+                    Err(e) => {
+                        log::error!("❌ Ignoring recreation.gov proxy {}: {}", masked, e);
+                        None
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if !proxies.is_empty() {
+            log::info!(
+                "🔀 Rotating recreation.gov calls through {} proxies",
+                proxies.len()
+            );
+        }
+
+        Self {
+            proxies: Arc::new(Mutex::new(proxies)),
+            next: Arc::new(AtomicUsize::new(0)),
+            direct,
+            direct_session: Arc::new(Mutex::new(RecGovSession::new())),
+            max_failures: config.recreation_gov_proxy_max_failures,
+        }
+    }
+
+    /// Route for the next call: the next proxy in rotation, or the direct connection
+    /// when no proxy is configured or left
+    pub(crate) fn lease(&self) -> ProxyLease {
+        let proxies = self.proxies.lock().unwrap();
+        let live: Vec<usize> = (0..proxies.len())
+            .filter(|&i| proxies[i].health.in_rotation)
+            .collect();
+
+        if live.is_empty() {
+            return ProxyLease {
+                client: self.direct.clone(),
+                proxy: None,
+            };
+        }
+
+        let index = live[self.next.fetch_add(1, Ordering::Relaxed) % live.len()];
+        ProxyLease {
+            client: proxies[index].client.clone(),
+            proxy: Some(index),
+        }
+    }
+
+    /// GET request to `url` on the leased route, carrying the route's session
+    pub(crate) fn get(&self, lease: &ProxyLease, url: &str) -> RequestBuilder {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_default();
+        let request = lease.client.get(url);
+
+        self.with_session(lease, |session| session.apply(&host, request))
+    }
+
+    /// Records how a call went: keeps the cookies it set, renews the session when
+    /// recreation.gov blocked it, and takes a proxy out of rotation after too many
+    /// failures in a row
+    pub(crate) fn report(
+        &self,
+        lease: &ProxyLease,
+        response: &Result<reqwest::Response, reqwest::Error>,
+    ) {
+        let blocked = response.as_ref().is_ok_and(|response| {
+            matches!(
+                response.status(),
+                StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+            )
+        });
+
+        self.with_session(lease, |session| match response {
+            Ok(_) if blocked => session.renew(),
+            Ok(response) => session.store(
+                response.url().host_str().unwrap_or_default(),
+                response.headers(),
+            ),
+            Err(_) => {}
+        });
+
+        let Some(index) = lease.proxy else {
+            return;
+        };
+
+        let error = match response {
+            Ok(response)
+                if matches!(
+                    response.status(),
+                    StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+                ) =>
+            {
+                Some(format!("blocked with HTTP {}", response.status()))
+            }
+            Ok(_) => None,
+            Err(e) if e.is_connect() || e.is_timeout() => Some(e.to_string()),
+            // Anything else isn't the proxy's fault
+            Err(_) => None,
+        };
+
+        let mut proxies = self.proxies.lock().unwrap();
+        let health = &mut proxies[index].health;
+        health.requests += 1;
+
+        let Some(error) = error else {
+            health.consecutive_failures = 0;
+            health.last_success = Some(Utc::now());
+            return;
+        };
+
+        health.failures += 1;
+        health.consecutive_failures += 1;
+        health.last_error = Some(error);
+
+        if health.in_rotation && health.consecutive_failures >= self.max_failures {
+            health.in_rotation = false;
+            health.removed_at = Some(Utc::now());
+            log::warn!(
+                "🪦 Took recreation.gov proxy {} out of rotation after {} failures in a row: {}",
+                health.url,
+                health.consecutive_failures,
+                health.last_error.as_deref().unwrap_or_default()
+            );
+
+            if proxies.iter().all(|proxy| !proxy.health.in_rotation) {
+                log::error!("❌ Every recreation.gov proxy is dead, calling directly");
+            }
+        }
+    }
+
+    /// Runs `f` on the session of the leased route
+    fn with_session<R>(&self, lease: &ProxyLease, f: impl FnOnce(&mut RecGovSession) -> R) -> R {
+        match lease.proxy {
+            Some(index) => f(&mut self.proxies.lock().unwrap()[index].session),
+            None => f(&mut self.direct_session.lock().unwrap()),
+        }
+    }
+
+    /// Health of every configured proxy, including those out of rotation
+    pub fn health(&self) -> Vec<ProxyHealth> {
+        self.proxies
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|proxy| proxy.health.clone())
+            .collect()
+    }
+}
+
+/// Masks the password of a proxy URL so it can be logged and reported
+fn mask_password(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut parsed) if parsed.password().is_some() => {
+            let _ = parsed.set_password(Some("***"));
+            parsed.to_string()
+        }
+        Ok(parsed) => parsed.to_string(),
+        Err(_) => url
+            .split_once('@')
+            .map_or(url.to_string(), |(_, host)| host.to_string()),
+    }
+}
@@ -0,0 +1,66 @@
+use std::future::Future;
+
+use crate::client::{RecGovClient, RecGovError};
+use crate::provider::ProviderClient;
+use crate::ridb::{Campsite, CampsiteAttribute, Facility};
+
+/// The recreation.gov calls the scan pipeline makes, implemented by [`RecGovClient`] and,
+/// for tests that mustn't touch the network, by [`MockRecGov`](crate::MockRecGov).
+/// Availability comes from [`ProviderClient`].
+pub trait RecGovApi: ProviderClient + Clone + Send + Sync + 'static {
+    /// Campgrounds in the recreation areas whose name contains the query
+    fn search_facilities(
+        &self,
+        query: &str,
+    ) -> impl Future<Output = Result<Vec<Facility>, RecGovError>> + Send;
+
+    /// A single facility, or `None` if the ID is unknown
+    fn facility(
+        &self,
+        facility_id: &str,
+    ) -> impl Future<Output = Result<Option<Facility>, RecGovError>> + Send;
+
+    /// Every campsite of a facility
+    fn facility_campsites(
+        &self,
+        facility_id: &str,
+    ) -> impl Future<Output = Result<Vec<Campsite>, RecGovError>> + Send;
+
+    /// The RIDB attributes of a campsite
+    fn campsite_attributes(
+        &self,
+        campsite_id: &str,
+    ) -> impl Future<Output = Result<Vec<CampsiteAttribute>, RecGovError>> + Send;
+
+    /// Client for polling a batch of campgrounds in a row, e.g. those of one recreation
+    /// area: its calls share one route and session and are spaced as if for a single
+    /// campground. Clients without sessions return themselves.
+    fn batch(&self, _key: &str) -> Self {
+        self.clone()
+    }
+}
+
+impl RecGovApi for RecGovClient {
+    async fn search_facilities(&self, query: &str) -> Result<Vec<Facility>, RecGovError> {
+        RecGovClient::search_facilities(self, query).await
+    }
+
+    async fn facility(&self, facility_id: &str) -> Result<Option<Facility>, RecGovError> {
+        RecGovClient::facility(self, facility_id).await
+    }
+
+    async fn facility_campsites(&self, facility_id: &str) -> Result<Vec<Campsite>, RecGovError> {
+        RecGovClient::facility_campsites(self, facility_id).await
+    }
+
+    async fn campsite_attributes(
+        &self,
+        campsite_id: &str,
+    ) -> Result<Vec<CampsiteAttribute>, RecGovError> {
+        RecGovClient::campsite_attributes(self, campsite_id).await
+    }
+
+    fn batch(&self, key: &str) -> Self {
+        RecGovClient::batch(self, key)
+    }
+}
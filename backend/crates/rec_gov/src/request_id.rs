@@ -0,0 +1,19 @@
+use std::future::Future;
+
+/// Header carrying the ID of the request a call was made for
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Runs `future` with `request_id` as the current request ID, so upstream calls it makes
+/// carry the ID.
+pub async fn with_request_id<F: Future>(request_id: String, future: F) -> F::Output {
+    REQUEST_ID.scope(request_id, future).await
+}
+
+/// ID of the request being handled, if any (background jobs have none).
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
@@ -0,0 +1,208 @@
+use app_config::AppConfig;
+use chrono::{NaiveDate, NaiveDateTime};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+
+use crate::api_ledger::{ApiCallLedger, ApiCallRecord};
+use crate::availability::{AVAILABLE, CampsiteAvailability, RESERVED};
+use crate::client::RecGovError;
+use crate::provider::{Provider, ProviderClient};
+use crate::sandbox::sandbox_reserve_california_availability;
+
+/// Availability grid endpoint of ReserveCalifornia's UseDirect API
+const RESERVE_CALIFORNIA_GRID_URL: &str = "https://calirdr.usedirect.com/rdr/rdr/search/grid";
+
+/// Request body of the UseDirect availability grid
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct GridRequest {
+    facility_id: String,
+    start_date: String,
+    end_date: String,
+    is_ada: bool,
+    min_vehicle_length: i32,
+    unit_category_id: i32,
+    sleeping_unit_id: i32,
+    unit_types_group_ids: Vec<i32>,
+    in_season_only: bool,
+    web_only: bool,
+}
+
+/// UseDirect availability grid response
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GridResponse {
+    #[serde(default)]
+    facility: Option<GridFacility>,
+}
+
+/// Facility of a grid response
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GridFacility {
+    #[serde(default)]
+    units: Option<HashMap<String, GridUnit>>,
+}
+
+/// One bookable unit (site, cabin, yurt) of a grid response
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GridUnit {
+    unit_id: i64,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    short_name: Option<String>,
+    #[serde(default)]
+    slices: HashMap<String, GridSlice>,
+}
+
+/// One night of a unit
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GridSlice {
+    date: NaiveDateTime,
+    #[serde(default)]
+    is_free: bool,
+    #[serde(default)]
+    is_blocked: bool,
+    #[serde(default)]
+    is_walkin: bool,
+}
+
+impl GridUnit {
+    /// Converts the unit into recreation.gov's campsite shape for nights in `start..end`.
+    fn into_campsite(self, start: NaiveDate, end: NaiveDate) -> CampsiteAvailability {
+        let availabilities = self
+            .slices
+            .values()
+            .filter(|slice| (start..end).contains(&slice.date.date()))
+            .map(|slice| {
+                let status = if slice.is_free && !slice.is_blocked && !slice.is_walkin {
+                    AVAILABLE
+                } else {
+                    RESERVED
+                };
+                (
+                    format!("{}T00:00:00Z", slice.date.format("%Y-%m-%d")),
+                    status.to_string(),
+                )
+            })
+            .collect();
+
+        let name = self.name.to_uppercase();
+        let campsite_type = ["CABIN", "YURT", "LODGING"]
+            .iter()
+            .find(|word| name.contains(*word))
+            .map(|word| word.to_string());
+
+        CampsiteAvailability {
+            campsite_id: self.unit_id.to_string(),
+            site: self.short_name.unwrap_or(self.name),
+            loop_name: None,
+            campsite_type,
+            availabilities,
+            quantities: BTreeMap::new(),
+            type_of_use: Some("Overnight".to_string()),
+            prices: BTreeMap::new(),
+        }
+    }
+}
+
+/// Client for ReserveCalifornia (California state parks). Every call is recorded in the
+/// API ledger; in sandbox mode fixtures are served instead.
+#[derive(Clone)]
+pub struct ReserveCaliforniaClient {
+    http: Client,
+    ledger: ApiCallLedger,
+    sandbox: bool,
+}
+
+impl ReserveCaliforniaClient {
+    /// Creates a client using sandbox mode from the configuration.
+    pub fn new(config: &AppConfig, ledger: ApiCallLedger) -> Self {
+        Self {
+            http: Client::new(),
+            ledger,
+            sandbox: config.sandbox_mode,
+        }
+    }
+
+    /// Fetches the availability of every unit of a facility for the nights `start..end`
+    /// from the UseDirect availability grid.
+    pub async fn facility_availability(
+        &self,
+        facility_id: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<CampsiteAvailability>, RecGovError> {
+        if self.sandbox {
+            return Ok(sandbox_reserve_california_availability(
+                facility_id,
+                start,
+                end,
+            ));
+        }
+
+        let last_night = end.pred_opt().unwrap_or(end);
+        let request = GridRequest {
+            facility_id: facility_id.to_string(),
+            start_date: start.format("%m-%d-%Y").to_string(),
+            end_date: last_night.format("%m-%d-%Y").to_string(),
+            is_ada: false,
+            min_vehicle_length: 0,
+            unit_category_id: 0,
+            sleeping_unit_id: 0,
+            unit_types_group_ids: Vec::new(),
+            in_season_only: true,
+            web_only: true,
+        };
+
+        let started = Instant::now();
+        let response = self
+            .http
+            .post(RESERVE_CALIFORNIA_GRID_URL)
+            .json(&request)
+            .send()
+            .await;
+
+        self.ledger
+            .record(ApiCallRecord {
+                endpoint: "reserve_california_grid",
+                campground_id: Some(Provider::ReserveCalifornia.campground_id(facility_id)),
+                status_code: response.as_ref().ok().map(|r| r.status().as_u16()),
+                duration: started.elapsed(),
+            })
+            .await;
+
+        let grid: GridResponse = response?.error_for_status()?.json().await?;
+
+        let units = grid
+            .facility
+            .and_then(|facility| facility.units)
+            .unwrap_or_default();
+
+        Ok(units
+            .into_values()
+            .map(|unit| unit.into_campsite(start, end))
+            .collect())
+    }
+}
+
+impl ProviderClient for ReserveCaliforniaClient {
+    fn provider(&self) -> Provider {
+        Provider::ReserveCalifornia
+    }
+
+    async fn campground_availability(
+        &self,
+        campground_id: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<CampsiteAvailability>, RecGovError> {
+        let facility_id = Provider::ReserveCalifornia.facility_id(campground_id);
+        self.facility_availability(facility_id, start, end).await
+    }
+}
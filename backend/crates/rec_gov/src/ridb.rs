@@ -0,0 +1,510 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// Paged RIDB response envelope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+pub struct RidbResponse<T> {
+    /// Records on this page
+    #[serde(rename = "RECDATA", default)]
+    pub records: Vec<T>,
+    /// Paging information
+    #[serde(rename = "METADATA", default)]
+    pub metadata: RidbMetadata,
+}
+
+impl<T> RidbResponse<T> {
+    /// Wraps records in an envelope describing a single, complete page.
+    pub fn new(records: Vec<T>) -> Self {
+        let count = records.len();
+
+        Self {
+            records,
+            metadata: RidbMetadata {
+                results: RidbResults {
+                    current_count: count,
+                    total_count: count,
+                },
+            },
+        }
+    }
+}
+
+/// RIDB response metadata
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RidbMetadata {
+    /// Result counts
+    #[serde(rename = "RESULTS", default)]
+    pub results: RidbResults,
+}
+
+/// RIDB result counts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RidbResults {
+    /// Number of records on this page
+    #[serde(rename = "CURRENT_COUNT", default)]
+    pub current_count: usize,
+    /// Number of records matching the query
+    #[serde(rename = "TOTAL_COUNT", default)]
+    pub total_count: usize,
+}
+
+/// A recreation area (national park, forest, lake, ...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecArea {
+    /// RIDB recreation area ID
+    #[serde(rename = "RecAreaID")]
+    pub id: String,
+    /// Name of the recreation area
+    #[serde(rename = "RecAreaName")]
+    pub name: String,
+}
+
+/// Federal agency managing a facility
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Agency {
+    /// National Park Service
+    #[serde(rename = "NPS")]
+    Nps,
+    /// USDA Forest Service
+    #[serde(rename = "USFS")]
+    Usfs,
+    /// Bureau of Land Management
+    #[serde(rename = "BLM")]
+    Blm,
+}
+
+impl Agency {
+    /// Every agency facility searches can be filtered by
+    pub const ALL: [Agency; 3] = [Agency::Nps, Agency::Usfs, Agency::Blm];
+
+    /// Abbreviation of the agency (e.g. `NPS`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Agency::Nps => "NPS",
+            Agency::Usfs => "USFS",
+            Agency::Blm => "BLM",
+        }
+    }
+
+    /// Parses an abbreviation such as `nps` or `USFS`, ignoring case.
+    pub fn parse(value: &str) -> Option<Agency> {
+        Agency::ALL
+            .into_iter()
+            .find(|agency| agency.as_str().eq_ignore_ascii_case(value.trim()))
+    }
+
+    /// Agency of a RIDB organization, by abbreviation or else by name
+    fn of_organization(organization: &Organization) -> Option<Agency> {
+        Agency::parse(&organization.abbreviation).or_else(|| {
+            let name = organization.name.to_lowercase();
+            if name.contains("national park service") {
+                Some(Agency::Nps)
+            } else if name.contains("forest service") {
+                Some(Agency::Usfs)
+            } else if name.contains("bureau of land management") {
+                Some(Agency::Blm)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// An organization managing a facility, as listed by RIDB's `full` responses
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Organization {
+    /// RIDB organization ID
+    #[serde(rename = "OrgID", default)]
+    pub id: String,
+    /// Name of the organization (e.g. `National Park Service`)
+    #[serde(rename = "OrgName", default)]
+    pub name: String,
+    /// Abbreviated name (e.g. `NPS`)
+    #[serde(rename = "OrgAbbrevName", default)]
+    pub abbreviation: String,
+}
+
+/// A facility address, as listed by RIDB's `full` responses
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FacilityAddress {
+    /// Two-letter state code
+    #[serde(rename = "AddressStateCode", default)]
+    pub state: Option<String>,
+}
+
+/// A facility (campground, cabin, lookout, ...). Serialized with RIDB field names, which
+/// the frontend reads directly.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Facility {
+    /// RIDB facility ID
+    #[serde(rename = "FacilityID")]
+    pub id: String,
+    /// Name of the facility
+    #[serde(rename = "FacilityName")]
+    pub name: String,
+    /// HTML description of the facility
+    #[serde(rename = "FacilityDescription", default)]
+    pub description: String,
+    /// Type of facility (e.g. `Campground`)
+    #[serde(rename = "FacilityTypeDescription", default)]
+    pub type_description: Option<String>,
+    /// Latitude, or 0 when RIDB doesn't know it
+    #[serde(rename = "FacilityLatitude", default)]
+    pub latitude: Option<f64>,
+    /// Longitude, or 0 when RIDB doesn't know it
+    #[serde(rename = "FacilityLongitude", default)]
+    pub longitude: Option<f64>,
+    /// Two-letter state code
+    #[serde(rename = "AddressStateCode", default)]
+    pub state: Option<String>,
+    /// Whether the facility takes reservations
+    #[serde(rename = "Reservable", default)]
+    pub reservable: Option<bool>,
+    /// Free-text stay limit (e.g. "14 days"), often empty
+    #[serde(rename = "StayLimit", default)]
+    pub stay_limit: Option<String>,
+    /// Human-readable location (e.g. "12 mi NE of Bishop, CA"), added by the geocoder
+    #[serde(
+        rename = "LocationDescription",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub location_description: Option<String>,
+    /// Organizations managing the facility
+    #[serde(
+        rename = "ORGANIZATION",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub organizations: Vec<Organization>,
+    /// Addresses of the facility
+    #[serde(
+        rename = "FACILITYADDRESS",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub addresses: Vec<FacilityAddress>,
+}
+
+/// Phrases RIDB descriptions use for campgrounds without reservations
+const FIRST_COME_FIRST_SERVED_PHRASES: &[&str] = &[
+    "first-come, first-served",
+    "first come, first served",
+    "first-come first-served",
+    "first come first served",
+    "non-reservable",
+];
+
+impl Facility {
+    /// Returns true if the facility can't be reserved and only takes campers on a
+    /// first-come, first-served basis, so recreation.gov will never show availability.
+    /// RIDB's `Reservable` flag decides when present; otherwise the description does.
+    pub fn is_first_come_first_served(&self) -> bool {
+        match self.reservable {
+            Some(reservable) => !reservable,
+            None => {
+                let description = self.description.to_lowercase();
+                FIRST_COME_FIRST_SERVED_PHRASES
+                    .iter()
+                    .any(|phrase| description.contains(phrase))
+            }
+        }
+    }
+
+    /// Returns the longest stay the facility allows in nights, parsed from RIDB's
+    /// free-text stay limit (e.g. "14 days", "2 weeks", "Maximum stay: 7 nights").
+    /// Returns `None` when there's no limit or it can't be understood.
+    pub fn max_stay_nights(&self) -> Option<i64> {
+        let limit = self.stay_limit.as_deref()?.to_lowercase();
+        let words: Vec<&str> = limit
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        words.windows(2).find_map(|pair| {
+            let count: i64 = pair[0].parse().ok().filter(|count| *count > 0)?;
+            match pair[1] {
+                "day" | "days" | "night" | "nights" => Some(count),
+                "week" | "weeks" => Some(count * 7),
+                _ => None,
+            }
+        })
+    }
+
+    /// Two-letter state code of the facility, from its address when RIDB leaves the
+    /// top-level field empty.
+    pub fn state_code(&self) -> Option<&str> {
+        self.state
+            .as_deref()
+            .or_else(|| {
+                self.addresses
+                    .iter()
+                    .find_map(|address| address.state.as_deref())
+            })
+            .filter(|state| !state.is_empty())
+    }
+
+    /// Federal agency managing the facility, when it's one facility searches know
+    pub fn agency(&self) -> Option<Agency> {
+        self.organizations.iter().find_map(Agency::of_organization)
+    }
+
+    /// Returns true if RIDB lists the facility as a campground, rather than a trailhead,
+    /// day-use area, or other facility in the recreation area.
+    pub fn is_campground(&self) -> bool {
+        self.type_description
+            .as_deref()
+            .is_some_and(|kind| kind.eq_ignore_ascii_case("campground"))
+    }
+
+    /// Returns the facility coordinates, if RIDB knows them.
+    pub fn coordinates(&self) -> Option<(f64, f64)> {
+        match (self.latitude, self.longitude) {
+            // RIDB reports missing coordinates as 0, 0
+            (Some(lat), Some(lng)) if lat != 0.0 || lng != 0.0 => Some((lat, lng)),
+            _ => None,
+        }
+    }
+
+    /// Months the facility is open, when its description gives them (see
+    /// [`OperatingSeason::parse`]).
+    pub fn operating_season(&self) -> Option<OperatingSeason> {
+        OperatingSeason::parse(&self.description)
+    }
+}
+
+/// Month names, in calendar order
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Words joining the two months of a season, e.g. "May through September"
+const SEASON_RANGE_WORDS: &[&str] = &["through", "thru", "to", "until", "till"];
+
+/// Words that may qualify the months of a season, e.g. "late May to mid-October"
+const SEASON_QUALIFIER_WORDS: &[&str] = &["early", "mid", "late", "end", "beginning", "of", "the"];
+
+/// Months a facility is open, inclusive. A season may wrap around the new year (e.g. a
+/// desert campground open November through April).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct OperatingSeason {
+    /// First month open, from 1 (January) to 12
+    pub opens_month: u32,
+    /// Last month open, from 1 (January) to 12
+    pub closes_month: u32,
+}
+
+impl OperatingSeason {
+    /// Finds the first month range in free text such as an RIDB description, e.g. "open
+    /// late May through September" or "Season: May 15-Oct 1". Returns `None` when there's
+    /// no range.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text
+            .to_lowercase()
+            .replace(['-', '\u{2013}', '\u{2014}'], " to ");
+        let words: Vec<&str> = text
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        words.iter().enumerate().find_map(|(i, word)| {
+            let opens_month = month_number(word)?;
+            let mut ranged = false;
+
+            for next in &words[i + 1..] {
+                if let Some(closes_month) = month_number(next) {
+                    return (ranged && closes_month != opens_month).then_some(Self {
+                        opens_month,
+                        closes_month,
+                    });
+                }
+                if SEASON_RANGE_WORDS.contains(next) {
+                    ranged = true;
+                } else if !SEASON_QUALIFIER_WORDS.contains(next)
+                    && !next.starts_with(|c: char| c.is_ascii_digit())
+                {
+                    return None;
+                }
+            }
+
+            None
+        })
+    }
+
+    /// Returns true if `date` falls in the season
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        let month = date.month();
+        if self.opens_month <= self.closes_month {
+            (self.opens_month..=self.closes_month).contains(&month)
+        } else {
+            month >= self.opens_month || month <= self.closes_month
+        }
+    }
+
+    /// First date on or after `date` in the season
+    pub fn next_opening(&self, date: NaiveDate) -> NaiveDate {
+        if self.contains(date) {
+            return date;
+        }
+
+        let opening = |year| NaiveDate::from_ymd_opt(year, self.opens_month, 1);
+        opening(date.year())
+            .filter(|opening| *opening > date)
+            .or_else(|| opening(date.year() + 1))
+            .unwrap_or(date)
+    }
+}
+
+/// Month (1 to 12) a word names, in full or abbreviated (e.g. "Sept", "Oct")
+fn month_number(word: &str) -> Option<u32> {
+    if word.len() < 3 {
+        return None;
+    }
+
+    MONTHS
+        .iter()
+        .position(|month| *month == word || (word.len() <= 4 && month.starts_with(word)))
+        .map(|index| index as u32 + 1)
+}
+
+/// A campsite within a facility. Serialized with RIDB field names.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Campsite {
+    /// RIDB campsite ID
+    #[serde(rename = "CampsiteID")]
+    pub id: String,
+    /// Site name or number (e.g. `042`)
+    #[serde(rename = "CampsiteName", default)]
+    pub name: String,
+    /// Site type (e.g. `STANDARD NONELECTRIC`, `TENT ONLY NONELECTRIC`)
+    #[serde(rename = "CampsiteType", default)]
+    pub site_type: Option<String>,
+    /// How the site is booked (`Overnight`, `Day`, ...)
+    #[serde(rename = "TypeOfUse", default)]
+    pub type_of_use: Option<String>,
+    /// Campground loop the site belongs to
+    #[serde(rename = "Loop", default)]
+    pub loop_name: Option<String>,
+    /// Whether the site is accessible
+    #[serde(rename = "CampsiteAccessible", default)]
+    pub accessible: bool,
+}
+
+/// A campsite attribute as RIDB lists it, e.g. `Max Vehicle Length` = `35`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CampsiteAttribute {
+    /// Name of the attribute (e.g. `Electricity Hookup`, `Pets Allowed`)
+    #[serde(rename = "AttributeName", default)]
+    pub name: String,
+    /// Value as free text (e.g. `50`, `Yes`, `N/A`)
+    #[serde(rename = "AttributeValue", default)]
+    pub value: String,
+}
+
+/// A photo, video, or other media attached to a RIDB entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityMedia {
+    /// Kind of media (e.g. `Image`)
+    #[serde(rename = "MediaType", default)]
+    pub media_type: String,
+    /// Title of the media
+    #[serde(rename = "Title", default)]
+    pub title: String,
+    /// Link to the media
+    #[serde(rename = "URL", default)]
+    pub url: String,
+    /// Attribution to show alongside the media
+    #[serde(rename = "Credits", default)]
+    pub credits: Option<String>,
+    /// Whether this is the entity's main photo
+    #[serde(rename = "IsPrimary", default)]
+    pub is_primary: bool,
+}
+
+impl EntityMedia {
+    /// Returns true if the media is a photo.
+    pub fn is_image(&self) -> bool {
+        self.media_type.eq_ignore_ascii_case("image")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facility_with_stay_limit(stay_limit: &str) -> Facility {
+        Facility {
+            id: "232447".to_string(),
+            name: "Upper Pines".to_string(),
+            reservable: Some(true),
+            stay_limit: Some(stay_limit.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_stay_limits() {
+        let max_stay = |limit| facility_with_stay_limit(limit).max_stay_nights();
+
+        assert_eq!(max_stay("14 days"), Some(14));
+        assert_eq!(max_stay("Maximum stay: 7 Nights per visit"), Some(7));
+        assert_eq!(max_stay("2 weeks"), Some(14));
+        assert_eq!(max_stay("May 1-Oct 15: 14 days"), Some(14));
+        assert_eq!(max_stay(""), None);
+        assert_eq!(max_stay("See rules"), None);
+    }
+
+    #[test]
+    fn parses_operating_seasons() {
+        let season = |opens_month, closes_month| {
+            Some(OperatingSeason {
+                opens_month,
+                closes_month,
+            })
+        };
+
+        assert_eq!(
+            OperatingSeason::parse("<p>The campground is open late May through September.</p>"),
+            season(5, 9)
+        );
+        assert_eq!(
+            OperatingSeason::parse("Season: May 15-Oct 1"),
+            season(5, 10)
+        );
+        assert_eq!(
+            OperatingSeason::parse("Open from November to April; closed in summer"),
+            season(11, 4)
+        );
+        // Months that aren't a range aren't a season
+        assert_eq!(
+            OperatingSeason::parse("Bears are active in May and June"),
+            None
+        );
+        assert_eq!(
+            OperatingSeason::parse("Campers may stay up to 14 days"),
+            None
+        );
+
+        let winter = season(11, 4).unwrap();
+        let date = |month, day| NaiveDate::from_ymd_opt(2026, month, day).unwrap();
+        assert!(winter.contains(date(1, 10)));
+        assert!(!winter.contains(date(7, 4)));
+        assert_eq!(winter.next_opening(date(7, 4)), date(11, 1));
+        assert_eq!(
+            season(5, 9).unwrap().next_opening(date(12, 20)),
+            NaiveDate::from_ymd_opt(2027, 5, 1).unwrap()
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use chrono::{Datelike, Months, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::availability::{AVAILABLE, CampsiteAvailability, MonthAvailability, RESERVED};
+use crate::permits::{PermitDateAvailability, PermitDivisionAvailability, PermitMonthAvailability};
+use crate::ridb::{Campsite, CampsiteAttribute, Facility, Organization};
+
+/// Whether the server runs in sandbox mode, where recreation.gov and notification
+/// providers are replaced with fixtures and mocks.
+#[derive(Debug, Clone, Copy)]
+pub struct SandboxMode(pub bool);
+
+impl SandboxMode {
+    /// Returns true if sandbox mode is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.0
+    }
+}
+
+/// Fixture facilities served instead of RIDB results in sandbox mode
+/// (facility ID, facility name, recreation area, state).
+pub const SANDBOX_FACILITIES: &[(&str, &str, &str, &str)] = &[
+    ("232447", "Upper Pines", "Yosemite National Park", "CA"),
+    ("232449", "North Pines", "Yosemite National Park", "CA"),
+    ("232450", "Lower Pines", "Yosemite National Park", "CA"),
+    ("232445", "Watchman Campground", "Zion National Park", "UT"),
+];
+
+/// Returns fixture facilities whose name or recreation area matches the query.
+pub fn sandbox_facilities(query: &str) -> Vec<Facility> {
+    let query_lower = query.to_lowercase();
+
+    SANDBOX_FACILITIES
+        .iter()
+        .filter(|(_, name, recarea, _)| {
+            name.to_lowercase().contains(&query_lower)
+                || recarea.to_lowercase().contains(&query_lower)
+        })
+        .map(fixture_facility)
+        .collect()
+}
+
+/// Returns the fixture facility with the given ID.
+pub fn sandbox_facility(facility_id: &str) -> Option<Facility> {
+    SANDBOX_FACILITIES
+        .iter()
+        .find(|(id, ..)| *id == facility_id)
+        .map(fixture_facility)
+}
+
+/// Returns a handful of fixture campsites in two loops for a fixture facility; the last
+/// one is a cabin.
+pub fn sandbox_campsites(facility_id: &str) -> Vec<Campsite> {
+    if sandbox_facility(facility_id).is_none() {
+        return Vec::new();
+    }
+
+    (1..=6)
+        .map(|number| Campsite {
+            id: format!("{}-{:03}", facility_id, number),
+            name: format!("{:03}", number),
+            site_type: Some(
+                if number == 6 {
+                    "CABIN NONELECTRIC"
+                } else if number % 3 == 0 {
+                    "TENT ONLY NONELECTRIC"
+                } else {
+                    "STANDARD NONELECTRIC"
+                }
+                .to_string(),
+            ),
+            type_of_use: Some("Overnight".to_string()),
+            loop_name: Some(if number <= 3 { "A" } else { "B" }.to_string()),
+            accessible: number == 1,
+        })
+        .collect()
+}
+
+/// Returns fixture RIDB attributes for a fixture campsite: sites 1 and 4 have 50 amp
+/// electric and water hookups, tent sites fit no vehicle, and pets stay out of the cabin.
+pub fn sandbox_campsite_attributes(campsite_id: &str) -> Vec<CampsiteAttribute> {
+    let Some(number) = campsite_id
+        .rsplit_once('-')
+        .and_then(|(facility_id, number)| {
+            sandbox_facility(facility_id).and(number.parse::<u32>().ok())
+        })
+        .filter(|number| (1..=6).contains(number))
+    else {
+        return Vec::new();
+    };
+
+    let (electric, water) = if number % 3 == 1 {
+        ("50", "Yes")
+    } else {
+        ("N/A", "N/A")
+    };
+    let max_vehicle_length = match number {
+        3 | 6 => "0",
+        1 | 4 => "40",
+        _ => "24",
+    };
+    let pets_allowed = if number == 6 { "No" } else { "Yes" };
+
+    [
+        ("Electricity Hookup", electric),
+        ("Water Hookup", water),
+        ("Sewer Hookup", "N/A"),
+        ("Max Vehicle Length", max_vehicle_length),
+        ("Pets Allowed", pets_allowed),
+    ]
+    .into_iter()
+    .map(|(name, value)| CampsiteAttribute {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+    .collect()
+}
+
+/// Returns fixture availability for a month: each fixture campsite is available on a
+/// different, fixed set of nights so previews look realistic, with a nightly price.
+pub fn sandbox_availability(facility_id: &str, month: NaiveDate) -> MonthAvailability {
+    let next_month = month + Months::new(1);
+
+    let campsites: HashMap<String, CampsiteAvailability> = sandbox_campsites(facility_id)
+        .into_iter()
+        .enumerate()
+        .map(|(index, site)| {
+            let availabilities: BTreeMap<String, String> = month
+                .iter_days()
+                .take_while(|date| *date < next_month)
+                .map(|date| {
+                    let status = if (date.day() as usize + index).is_multiple_of(4) {
+                        AVAILABLE
+                    } else {
+                        "Reserved"
+                    };
+                    (
+                        format!("{}T00:00:00Z", date.format("%Y-%m-%d")),
+                        status.to_string(),
+                    )
+                })
+                .collect();
+
+            // Cabins cost more than campsites
+            let price = if site.site_type.as_deref() == Some("CABIN NONELECTRIC") {
+                120.0
+            } else {
+                36.0
+            };
+            let prices = availabilities
+                .keys()
+                .map(|night| (night.clone(), price))
+                .collect();
+
+            let availability = CampsiteAvailability {
+                campsite_id: site.id.clone(),
+                site: site.name,
+                loop_name: site.loop_name,
+                campsite_type: site.site_type,
+                availabilities,
+                quantities: BTreeMap::new(),
+                type_of_use: site.type_of_use,
+                prices,
+            };
+            (site.id, availability)
+        })
+        .collect();
+
+    MonthAvailability {
+        count: Some(campsites.len()),
+        campsites,
+    }
+}
+
+/// Fixture permit quotas for a month: two divisions of 30 permits a day, with a few
+/// left on some days
+pub fn sandbox_permit_availability(permit_id: &str, month: NaiveDate) -> PermitMonthAvailability {
+    let next_month = month + Months::new(1);
+
+    let availability = (1..=2)
+        .map(|index| {
+            let division_id = format!("{}-{}", permit_id, index);
+            let date_availability = month
+                .iter_days()
+                .take_while(|date| *date < next_month)
+                .map(|date| {
+                    let remaining = if (date.day() as usize + index).is_multiple_of(5) {
+                        4
+                    } else {
+                        0
+                    };
+                    (
+                        format!("{}T00:00:00Z", date.format("%Y-%m-%d")),
+                        PermitDateAvailability {
+                            total: 30,
+                            remaining,
+                            show_walkup: false,
+                            is_secret_quota: false,
+                        },
+                    )
+                })
+                .collect();
+
+            (
+                division_id.clone(),
+                PermitDivisionAvailability {
+                    division_id,
+                    date_availability,
+                },
+            )
+        })
+        .collect();
+
+    PermitMonthAvailability { availability }
+}
+
+/// Fixture ReserveCalifornia availability: four units, the last a cabin, each free on a
+/// different, fixed set of nights.
+pub fn sandbox_reserve_california_availability(
+    facility_id: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<CampsiteAvailability> {
+    (1..=4)
+        .map(|number| {
+            let availabilities = start
+                .iter_days()
+                .take_while(|date| *date < end)
+                .map(|date| {
+                    let status = if (date.day() as usize + number).is_multiple_of(3) {
+                        AVAILABLE
+                    } else {
+                        RESERVED
+                    };
+                    (
+                        format!("{}T00:00:00Z", date.format("%Y-%m-%d")),
+                        status.to_string(),
+                    )
+                })
+                .collect();
+
+            CampsiteAvailability {
+                campsite_id: format!("{}-{}", facility_id, number),
+                site: format!("{}", number),
+                loop_name: None,
+                campsite_type: (number == 4).then(|| "CABIN".to_string()),
+                availabilities,
+                quantities: BTreeMap::new(),
+                type_of_use: Some("Overnight".to_string()),
+                prices: BTreeMap::new(),
+            }
+        })
+        .collect()
+}
+
+fn fixture_facility(&(id, name, recarea, state): &(&str, &str, &str, &str)) -> Facility {
+    Facility {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: format!("Sandbox fixture campground in {}", recarea),
+        type_description: Some("Campground".to_string()),
+        latitude: None,
+        longitude: None,
+        state: Some(state.to_string()),
+        reservable: Some(true),
+        stay_limit: Some("14 days".to_string()),
+        location_description: None,
+        organizations: vec![Organization {
+            id: "128".to_string(),
+            name: "National Park Service".to_string(),
+            abbreviation: "NPS".to_string(),
+        }],
+        addresses: Vec::new(),
+    }
+}
@@ -0,0 +1,249 @@
+use chrono::{DateTime, Utc};
+use rand::seq::IndexedRandom;
+use reqwest::RequestBuilder;
+use reqwest::header::{ACCEPT, ACCEPT_LANGUAGE, COOKIE, HeaderMap, SET_COOKIE, USER_AGENT};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Browser user agents sessions take turns presenting
+const USER_AGENTS: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/141.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/18.6 Safari/605.1.15",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:143.0) Gecko/20100101 Firefox/143.0",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/141.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/141.0.0.0 Safari/537.36",
+];
+
+/// `Accept-Language` values a session picks from, as US visitors' browsers send them
+const ACCEPT_LANGUAGES: &[&str] = &[
+    "en-US,en;q=0.9",
+    "en-US,en;q=0.8",
+    "en-US,en;q=0.9,es;q=0.8",
+    "en-US,en;q=0.5",
+    "en-US",
+];
+
+/// `Accept` values a session picks from
+const ACCEPTS: &[&str] = &["application/json, text/plain, */*", "*/*"];
+
+/// Next user agent handed to a new session, shared so sessions on different proxies
+/// don't all look like the same browser
+static NEXT_USER_AGENT: AtomicUsize = AtomicUsize::new(0);
+
+/// A cookie set by recreation.gov
+#[derive(Debug, Clone)]
+struct StoredCookie {
+    value: String,
+    expires: Option<DateTime<Utc>>,
+}
+
+/// Browser-like session with recreation.gov: one user agent and the cookies recreation.gov
+/// set, sent back on every call so consecutive calls look like one visitor. Each route
+/// (the direct connection and every proxy) has its own session, so a user agent and its
+/// cookies always come from the same IP.
+#[derive(Debug)]
+pub(crate) struct RecGovSession {
+    user_agent: &'static str,
+    accept_language: &'static str,
+    accept: &'static str,
+    /// Cookies by the domain they were set for
+    cookies: HashMap<String, BTreeMap<String, StoredCookie>>,
+}
+
+impl RecGovSession {
+    /// Starts a session with the next user agent, randomly picked `Accept` headers, and
+    /// no cookies
+    pub(crate) fn new() -> Self {
+        let index = NEXT_USER_AGENT.fetch_add(1, Ordering::Relaxed);
+        let mut rng = rand::rng();
+
+        Self {
+            user_agent: USER_AGENTS[index % USER_AGENTS.len()],
+            accept_language: ACCEPT_LANGUAGES
+                .choose(&mut rng)
+                .copied()
+                .unwrap_or("en-US"),
+            accept: ACCEPTS.choose(&mut rng).copied().unwrap_or("*/*"),
+            cookies: HashMap::new(),
+        }
+    }
+
+    /// Starts over as a different visitor, after recreation.gov blocked this one: the
+    /// cookies are dropped, the user agent after this one is presented, and the `Accept`
+    /// headers are picked again
+    pub(crate) fn renew(&mut self) {
+        let index = USER_AGENTS
+            .iter()
+            .position(|user_agent| *user_agent == self.user_agent)
+            .unwrap_or_default();
+
+        *self = Self {
+            user_agent: USER_AGENTS[(index + 1) % USER_AGENTS.len()],
+            ..Self::new()
+        };
+    }
+
+    /// Adds the session's browser headers and the cookies for `host` to a request
+    pub(crate) fn apply(&self, host: &str, request: RequestBuilder) -> RequestBuilder {
+        let request = request
+            .header(USER_AGENT, self.user_agent)
+            .header(ACCEPT, self.accept)
+            .header(ACCEPT_LANGUAGE, self.accept_language);
+
+        match self.cookie_header(host) {
+            Some(cookies) => request.header(COOKIE, cookies),
+            None => request,
+        }
+    }
+
+    /// Keeps the cookies a response from `host` set, dropping those it expired
+    pub(crate) fn store(&mut self, host: &str, headers: &HeaderMap) {
+        for header in headers.get_all(SET_COOKIE) {
+            let Some((name, cookie, domain)) = header
+                .to_str()
+                .ok()
+                .and_then(|header| parse_set_cookie(host, header))
+            else {
+                continue;
+            };
+
+            let jar = self.cookies.entry(domain).or_default();
+            if cookie.expires.is_some_and(|expires| expires <= Utc::now()) {
+                jar.remove(&name);
+            } else {
+                jar.insert(name, cookie);
+            }
+        }
+    }
+
+    /// `Cookie` header value for a request to `host`
+    fn cookie_header(&self, host: &str) -> Option<String> {
+        let now = Utc::now();
+        let cookies: Vec<String> = self
+            .cookies
+            .iter()
+            .filter(|(domain, _)| {
+                host == domain.as_str() || host.ends_with(&format!(".{}", domain))
+            })
+            .flat_map(|(_, jar)| jar.iter())
+            .filter(|(_, cookie)| cookie.expires.is_none_or(|expires| expires > now))
+            .map(|(name, cookie)| format!("{}={}", name, cookie.value))
+            .collect();
+
+        (!cookies.is_empty()).then(|| cookies.join("; "))
+    }
+}
+
+/// Parses a `Set-Cookie` header into the cookie's name, value and expiry, and the domain
+/// it's sent to (`Domain` when set, otherwise the host that set it)
+fn parse_set_cookie(host: &str, header: &str) -> Option<(String, StoredCookie, String)> {
+    let mut parts = header.split(';').map(str::trim);
+    let (name, value) = parts.next()?.split_once('=')?;
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut domain = host.to_string();
+    let mut expires = None;
+    let mut max_age = None;
+
+    for attribute in parts {
+        let (key, attribute_value) = attribute.split_once('=').unwrap_or((attribute, ""));
+        match key.to_ascii_lowercase().as_str() {
+            "domain" => {
+                let attribute_value = attribute_value.trim_start_matches('.').to_lowercase();
+                // A response can only set cookies for its own domain
+                if host != attribute_value && !host.ends_with(&format!(".{}", attribute_value)) {
+                    return None;
+                }
+                domain = attribute_value;
+            }
+            "max-age" => max_age = attribute_value.parse::<i64>().ok(),
+            "expires" => {
+                expires = DateTime::parse_from_rfc2822(&attribute_value.replace(" GMT", " +0000"))
+                    .ok()
+                    .map(|expires| expires.with_timezone(&Utc))
+            }
+            _ => {}
+        }
+    }
+
+    // Max-Age wins over Expires
+    if let Some(max_age) = max_age {
+        expires = Some(Utc::now() + chrono::Duration::seconds(max_age));
+    }
+
+    Some((
+        name.to_string(),
+        StoredCookie {
+            value: value.to_string(),
+            expires,
+        },
+        domain,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn set_cookies(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(SET_COOKIE, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn sends_back_cookies_for_the_domain() {
+        let mut session = RecGovSession::new();
+        session.store(
+            "www.recreation.gov",
+            &set_cookies(&[
+                "visitor=abc; Path=/; HttpOnly",
+                "shared=1; Domain=.recreation.gov; Secure",
+                "other=x; Domain=example.com",
+            ]),
+        );
+
+        let header = session.cookie_header("www.recreation.gov").unwrap();
+        let mut cookies: Vec<&str> = header.split("; ").collect();
+        cookies.sort();
+        assert_eq!(cookies, ["shared=1", "visitor=abc"]);
+
+        assert_eq!(
+            session.cookie_header("ridb.recreation.gov").as_deref(),
+            Some("shared=1")
+        );
+        assert_eq!(session.cookie_header("example.com"), None);
+    }
+
+    #[test]
+    fn expired_cookies_are_dropped() {
+        let mut session = RecGovSession::new();
+        session.store("www.recreation.gov", &set_cookies(&["a=1", "b=2"]));
+        session.store(
+            "www.recreation.gov",
+            &set_cookies(&[
+                "a=; Max-Age=0",
+                "b=2; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
+            ]),
+        );
+
+        assert_eq!(session.cookie_header("www.recreation.gov"), None);
+    }
+
+    #[test]
+    fn renewing_changes_visitor() {
+        let mut session = RecGovSession::new();
+        let user_agent = session.user_agent;
+        session.store("www.recreation.gov", &set_cookies(&["visitor=abc"]));
+
+        session.renew();
+
+        assert_ne!(session.user_agent, user_agent);
+        assert_eq!(session.cookie_header("www.recreation.gov"), None);
+    }
+}
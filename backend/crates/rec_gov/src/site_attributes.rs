@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ridb::CampsiteAttribute;
+
+/// What a campsite offers, read from its RIDB attributes. `None` means RIDB doesn't say.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SiteAttributes {
+    /// Whether the site has an electric hookup
+    pub electric_hookup: Option<bool>,
+    /// Whether the site has a water hookup
+    pub water_hookup: Option<bool>,
+    /// Whether the site has a sewer hookup
+    pub sewer_hookup: Option<bool>,
+    /// Longest vehicle or trailer the site fits, in feet
+    pub max_vehicle_length: Option<i32>,
+    /// Whether pets are allowed on the site
+    pub pets_allowed: Option<bool>,
+}
+
+impl SiteAttributes {
+    /// Reads the attributes filters use from a campsite's RIDB attributes. Hookups are
+    /// listed with free-text values such as `50` (amps), `Yes` or `N/A`; a full hookup
+    /// covers all three.
+    pub fn from_ridb(attributes: &[CampsiteAttribute]) -> Self {
+        let mut site = SiteAttributes::default();
+
+        for attribute in attributes {
+            let name = attribute.name.to_lowercase();
+            let value = attribute.value.as_str();
+
+            if name.contains("full hookup") {
+                if parse_flag(value) == Some(true) {
+                    site.electric_hookup = Some(true);
+                    site.water_hookup = Some(true);
+                    site.sewer_hookup = Some(true);
+                }
+            } else if name.contains("electric") {
+                site.electric_hookup = merge_flag(site.electric_hookup, parse_flag(value));
+            } else if name.contains("water hookup") {
+                site.water_hookup = merge_flag(site.water_hookup, parse_flag(value));
+            } else if name.contains("sewer hookup") {
+                site.sewer_hookup = merge_flag(site.sewer_hookup, parse_flag(value));
+            } else if name.contains("max vehicle length") {
+                // RIDB reports unknown lengths as 0
+                site.max_vehicle_length = parse_feet(value).filter(|feet| *feet > 0);
+            } else if name.contains("pets allowed") {
+                site.pets_allowed = parse_flag(value);
+            }
+        }
+
+        site
+    }
+}
+
+/// Site features a scan requires before it alerts about a site. Sites whose attributes
+/// RIDB doesn't report don't match a filter that asks for them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SiteFilters {
+    /// Only sites with an electric hookup
+    pub electric_hookup: bool,
+    /// Only sites with a water hookup
+    pub water_hookup: bool,
+    /// Only sites with a sewer hookup
+    pub sewer_hookup: bool,
+    /// Only sites fitting a vehicle or trailer this long, in feet
+    pub vehicle_length: Option<i32>,
+    /// Only sites allowing pets
+    pub pets_allowed: bool,
+}
+
+impl SiteFilters {
+    /// Returns true if the filters let every site through.
+    pub fn is_empty(&self) -> bool {
+        *self == SiteFilters::default()
+    }
+
+    /// Returns true if a site with `attributes` meets every filter.
+    pub fn matches(&self, attributes: &SiteAttributes) -> bool {
+        let required = |wanted: bool, offered: Option<bool>| !wanted || offered == Some(true);
+
+        required(self.electric_hookup, attributes.electric_hookup)
+            && required(self.water_hookup, attributes.water_hookup)
+            && required(self.sewer_hookup, attributes.sewer_hookup)
+            && required(self.pets_allowed, attributes.pets_allowed)
+            && self.vehicle_length.is_none_or(|length| {
+                attributes
+                    .max_vehicle_length
+                    .is_some_and(|max_length| max_length >= length)
+            })
+    }
+}
+
+/// Reads a yes/no attribute value; amperages and other positive numbers mean yes.
+fn parse_flag(value: &str) -> Option<bool> {
+    let value = value.trim().to_lowercase();
+
+    match value.as_str() {
+        "" => None,
+        "no" | "n" | "n/a" | "na" | "none" | "false" | "0" => Some(false),
+        "yes" | "y" | "true" | "domestic" => Some(true),
+        // e.g. "50", "30/50", "20 amp"
+        _ if value.starts_with(|c: char| c.is_ascii_digit()) => Some(
+            value
+                .split(|c: char| !c.is_ascii_digit())
+                .any(|part| part.parse::<u32>().is_ok_and(|number| number > 0)),
+        ),
+        _ => None,
+    }
+}
+
+/// A hookup listed under several attributes (e.g. `Electricity Hookup` and
+/// `Electric Hookup Amps`) is offered if any of them says so.
+fn merge_flag(current: Option<bool>, parsed: Option<bool>) -> Option<bool> {
+    match (current, parsed) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (None, None) => None,
+    }
+}
+
+/// Reads a length in feet such as `35`, `35.0` or `35 ft`.
+fn parse_feet(value: &str) -> Option<i32> {
+    let number: String = value
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+
+    number
+        .parse::<f64>()
+        .ok()
+        .map(|feet| feet.floor().min(f64::from(i32::MAX)) as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attribute(name: &str, value: &str) -> CampsiteAttribute {
+        CampsiteAttribute {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn reads_ridb_attributes_and_matches_filters() {
+        let site = SiteAttributes::from_ridb(&[
+            attribute("Electricity Hookup", "30/50"),
+            attribute("Water Hookup", "N/A"),
+            attribute("Max Vehicle Length", "35.0"),
+            attribute("Pets Allowed", "Domestic"),
+        ]);
+        assert_eq!(
+            site,
+            SiteAttributes {
+                electric_hookup: Some(true),
+                water_hookup: Some(false),
+                sewer_hookup: None,
+                max_vehicle_length: Some(35),
+                pets_allowed: Some(true),
+            }
+        );
+
+        let trailer = SiteFilters {
+            electric_hookup: true,
+            vehicle_length: Some(30),
+            ..SiteFilters::default()
+        };
+        assert!(trailer.matches(&site));
+        assert!(
+            !SiteFilters {
+                vehicle_length: Some(40),
+                ..trailer.clone()
+            }
+            .matches(&site)
+        );
+        assert!(
+            !SiteFilters {
+                sewer_hookup: true,
+                ..trailer
+            }
+            .matches(&site)
+        );
+
+        let full = SiteAttributes::from_ridb(&[
+            attribute("Full Hookup", "Yes"),
+            attribute("Max Vehicle Length", "0"),
+        ]);
+        assert_eq!(full.sewer_hookup, Some(true));
+        assert_eq!(full.max_vehicle_length, None);
+    }
+}
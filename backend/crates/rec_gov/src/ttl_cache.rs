@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// In-memory cache whose entries expire after a TTL. Once full, expired entries are
+/// dropped to make room, and then the least recently used one.
+#[derive(Debug)]
+pub(crate) struct TtlCache<K, V> {
+    capacity: usize,
+    ttl: Duration,
+    /// Value, when it was cached, and when it was last used
+    entries: HashMap<K, (V, Instant, Instant)>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Cached value of the key, if it hasn't expired
+    pub(crate) fn get(&mut self, key: &K) -> Option<V> {
+        let (value, cached_at, used_at) = self.entries.get_mut(key)?;
+
+        if cached_at.elapsed() >= self.ttl {
+            self.entries.remove(key);
+            return None;
+        }
+
+        *used_at = Instant::now();
+        Some(value.clone())
+    }
+
+    /// Caches a value, making room by dropping expired entries and then the least
+    /// recently used one
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let ttl = self.ttl;
+            self.entries
+                .retain(|_, (_, cached_at, _)| cached_at.elapsed() < ttl);
+        }
+
+        if self.entries.len() >= self.capacity
+            && !self.entries.contains_key(&key)
+            && let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, _, used_at))| *used_at)
+                .map(|(key, _)| key.clone())
+        {
+            self.entries.remove(&oldest);
+        }
+
+        let now = Instant::now();
+        self.entries.insert(key, (value, now, now));
+    }
+
+    /// Number of entries held, expired or not
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
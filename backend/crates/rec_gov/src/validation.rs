@@ -0,0 +1,410 @@
+use chrono::{Months, NaiveDate};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::availability::MonthAvailability;
+use crate::drift::parse_night_key;
+use crate::permits::PermitMonthAvailability;
+
+/// Most offending entries kept as a sample of a quarantined payload
+pub const QUARANTINE_SAMPLE_SIZE: usize = 5;
+
+/// Largest unparseable payload kept whole in quarantine, in bytes
+pub const RAW_PAYLOAD_LIMIT: usize = 512 * 1024;
+
+/// Impossible state found in an upstream availability payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PayloadIssue {
+    /// The same site (or permit division) reported the same date more than once
+    DuplicateDate {
+        /// Campsite or division ID
+        entry_id: String,
+        /// Date reported twice
+        date: NaiveDate,
+    },
+    /// A site is listed under a key other than its own ID, or under several keys
+    DuplicateEntry {
+        /// Key the entry is listed under
+        key: String,
+        /// ID the entry reports
+        entry_id: String,
+    },
+    /// A date outside the month that was requested
+    DateOutsideWindow {
+        /// Campsite or division ID
+        entry_id: String,
+        /// Date as reported
+        date: String,
+    },
+    /// A negative inventory count (units or permits left)
+    NegativeQuantity {
+        /// Campsite or division ID
+        entry_id: String,
+        /// Date of the count
+        date: NaiveDate,
+        /// Count as reported
+        quantity: i32,
+    },
+    /// The number of sites listed differs from the total the payload reports
+    SiteCountMismatch {
+        /// Total reported by recreation.gov
+        reported: usize,
+        /// Sites actually listed
+        listed: usize,
+    },
+    /// The payload couldn't be parsed at all; the sample holds it as received
+    DataFormat {
+        /// Parser error
+        message: String,
+    },
+}
+
+impl PayloadIssue {
+    /// Key of the payload entry the issue was found in, if it concerns a single entry
+    pub fn entry_key(&self) -> Option<&str> {
+        match self {
+            PayloadIssue::DuplicateEntry { key, .. } => Some(key),
+            PayloadIssue::DuplicateDate { entry_id, .. }
+            | PayloadIssue::DateOutsideWindow { entry_id, .. }
+            | PayloadIssue::NegativeQuantity { entry_id, .. } => Some(entry_id),
+            PayloadIssue::SiteCountMismatch { .. } | PayloadIssue::DataFormat { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for PayloadIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadIssue::DuplicateDate { entry_id, date } => {
+                write!(f, "{} reports {} more than once", entry_id, date)
+            }
+            PayloadIssue::DuplicateEntry { key, entry_id } => {
+                write!(f, "entry {} is listed under key {}", entry_id, key)
+            }
+            PayloadIssue::DateOutsideWindow { entry_id, date } => {
+                write!(
+                    f,
+                    "{} reports {} outside the requested month",
+                    entry_id, date
+                )
+            }
+            PayloadIssue::NegativeQuantity {
+                entry_id,
+                date,
+                quantity,
+            } => write!(f, "{} reports {} left on {}", entry_id, quantity, date),
+            PayloadIssue::SiteCountMismatch { reported, listed } => {
+                write!(f, "{} sites listed but {} reported", listed, reported)
+            }
+            PayloadIssue::DataFormat { message } => write!(f, "unreadable payload: {}", message),
+        }
+    }
+}
+
+/// Checks one month of campground availability for impossible states. Nights must fall
+/// in `month`, each site must be listed once under its own ID with each night once,
+/// inventory counts can't be negative, and the site count must match the reported total.
+pub fn validate_month_availability(
+    month: NaiveDate,
+    availability: &MonthAvailability,
+) -> Vec<PayloadIssue> {
+    let mut issues = Vec::new();
+    let mut seen_sites = HashSet::new();
+
+    for (key, site) in &availability.campsites {
+        if *key != site.campsite_id || !seen_sites.insert(site.campsite_id.as_str()) {
+            issues.push(PayloadIssue::DuplicateEntry {
+                key: key.clone(),
+                entry_id: site.campsite_id.clone(),
+            });
+        }
+
+        // A night may be reported as a status, a quantity, or both under the same key
+        let quantity_keys = site
+            .quantities
+            .keys()
+            .filter(|key| !site.availabilities.contains_key(*key));
+        check_dates(
+            month,
+            &site.campsite_id,
+            site.availabilities.keys().chain(quantity_keys),
+            &mut issues,
+        );
+
+        for (key, quantity) in &site.quantities {
+            if *quantity < 0
+                && let Some(date) = parse_night_key(key)
+            {
+                issues.push(PayloadIssue::NegativeQuantity {
+                    entry_id: site.campsite_id.clone(),
+                    date,
+                    quantity: *quantity,
+                });
+            }
+        }
+    }
+
+    if let Some(reported) = availability.count
+        && reported != availability.campsites.len()
+    {
+        issues.push(PayloadIssue::SiteCountMismatch {
+            reported,
+            listed: availability.campsites.len(),
+        });
+    }
+
+    issues
+}
+
+/// Checks one month of permit quotas for impossible states: dates outside `month`,
+/// dates reported twice for a division, and negative quotas.
+pub fn validate_permit_month(
+    month: NaiveDate,
+    availability: &PermitMonthAvailability,
+) -> Vec<PayloadIssue> {
+    let mut issues = Vec::new();
+
+    for (key, division) in &availability.availability {
+        if *key != division.division_id {
+            issues.push(PayloadIssue::DuplicateEntry {
+                key: key.clone(),
+                entry_id: division.division_id.clone(),
+            });
+        }
+
+        check_dates(
+            month,
+            &division.division_id,
+            division.date_availability.keys(),
+            &mut issues,
+        );
+
+        for (date, quota) in division.days() {
+            for quantity in [quota.total, quota.remaining] {
+                if quantity < 0 {
+                    issues.push(PayloadIssue::NegativeQuantity {
+                        entry_id: division.division_id.clone(),
+                        date,
+                        quantity,
+                    });
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// Sample of the entries behind the first few issues, for debugging a quarantined payload
+pub fn issue_sample<T: Serialize>(
+    issues: &[PayloadIssue],
+    entries: &HashMap<String, T>,
+) -> serde_json::Value {
+    let mut sample = serde_json::Map::new();
+
+    for key in issues.iter().filter_map(PayloadIssue::entry_key) {
+        if sample.len() >= QUARANTINE_SAMPLE_SIZE {
+            break;
+        }
+
+        if let Some(entry) = entries.get(key) {
+            sample.insert(
+                key.to_string(),
+                serde_json::to_value(entry).unwrap_or_default(),
+            );
+        }
+    }
+
+    serde_json::Value::Object(sample)
+}
+
+/// Raw payload kept when one can't be parsed: its JSON when it is JSON, otherwise its
+/// text, shortened to [`RAW_PAYLOAD_LIMIT`] bytes
+pub fn raw_payload_sample(body: &str) -> serde_json::Value {
+    if body.len() <= RAW_PAYLOAD_LIMIT
+        && let Ok(value) = serde_json::from_str(body)
+    {
+        return value;
+    }
+
+    let mut end = body.len().min(RAW_PAYLOAD_LIMIT);
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    serde_json::Value::String(body[..end].to_string())
+}
+
+/// Flags dates outside `month` and dates reported under more than one key.
+fn check_dates<'a>(
+    month: NaiveDate,
+    entry_id: &str,
+    keys: impl Iterator<Item = &'a String>,
+    issues: &mut Vec<PayloadIssue>,
+) {
+    let next_month = month + Months::new(1);
+    let mut seen = HashSet::new();
+
+    for key in keys {
+        match parse_night_key(key) {
+            Some(date) if date >= month && date < next_month => {
+                if !seen.insert(date) {
+                    issues.push(PayloadIssue::DuplicateDate {
+                        entry_id: entry_id.to_string(),
+                        date,
+                    });
+                }
+            }
+            _ => issues.push(PayloadIssue::DateOutsideWindow {
+                entry_id: entry_id.to_string(),
+                date: key.clone(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::availability::CampsiteAvailability;
+    use std::collections::BTreeMap;
+
+    fn august() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 8, 1).unwrap()
+    }
+
+    fn night(date: &str) -> String {
+        format!("{}T00:00:00Z", date)
+    }
+
+    fn site(id: &str, nights: &[&str], quantities: &[(&str, i32)]) -> CampsiteAvailability {
+        CampsiteAvailability {
+            campsite_id: id.to_string(),
+            site: id.to_string(),
+            loop_name: None,
+            campsite_type: None,
+            availabilities: nights
+                .iter()
+                .map(|date| (night(date), "Available".to_string()))
+                .collect(),
+            quantities: quantities
+                .iter()
+                .map(|(date, quantity)| (night(date), *quantity))
+                .collect(),
+            type_of_use: None,
+            prices: BTreeMap::new(),
+        }
+    }
+
+    fn month(sites: Vec<(&str, CampsiteAvailability)>, count: Option<usize>) -> MonthAvailability {
+        MonthAvailability {
+            campsites: sites
+                .into_iter()
+                .map(|(key, site)| (key.to_string(), site))
+                .collect(),
+            count,
+        }
+    }
+
+    #[test]
+    fn nights_must_fall_in_the_requested_month() {
+        for (date, allowed) in [
+            ("2025-07-31", false),
+            ("2025-08-01", true),
+            ("2025-08-31", true),
+            ("2025-09-01", false),
+        ] {
+            let issues = validate_month_availability(
+                august(),
+                &month(vec![("100", site("100", &[date], &[]))], Some(1)),
+            );
+
+            assert_eq!(issues.is_empty(), allowed, "{}: {:?}", date, issues);
+            if !allowed {
+                assert_eq!(
+                    issues,
+                    [PayloadIssue::DateOutsideWindow {
+                        entry_id: "100".to_string(),
+                        date: night(date),
+                    }]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn counts_may_be_zero_but_not_negative() {
+        for (quantity, allowed) in [(-1, false), (0, true), (1, true)] {
+            let issues = validate_month_availability(
+                august(),
+                &month(
+                    vec![("100", site("100", &[], &[("2025-08-14", quantity)]))],
+                    Some(1),
+                ),
+            );
+
+            assert_eq!(issues.is_empty(), allowed, "{} left", quantity);
+        }
+    }
+
+    #[test]
+    fn sites_and_nights_are_listed_once() {
+        let mut twice = site("100", &["2025-08-14"], &[]);
+        twice.availabilities.insert(
+            "2025-08-14T00:00:00.000Z".to_string(),
+            "Reserved".to_string(),
+        );
+
+        assert_eq!(
+            validate_month_availability(august(), &month(vec![("100", twice)], Some(1))),
+            [PayloadIssue::DuplicateDate {
+                entry_id: "100".to_string(),
+                date: NaiveDate::from_ymd_opt(2025, 8, 14).unwrap(),
+            }]
+        );
+
+        // A night reported both as a status and a quantity is one night
+        assert_eq!(
+            validate_month_availability(
+                august(),
+                &month(
+                    vec![("100", site("100", &["2025-08-14"], &[("2025-08-14", 1)]))],
+                    Some(1)
+                ),
+            ),
+            []
+        );
+
+        assert_eq!(
+            validate_month_availability(
+                august(),
+                &month(vec![("101", site("100", &["2025-08-14"], &[]))], Some(1)),
+            ),
+            [PayloadIssue::DuplicateEntry {
+                key: "101".to_string(),
+                entry_id: "100".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn listed_sites_must_match_the_reported_count() {
+        let sites = || {
+            vec![
+                ("100", site("100", &["2025-08-14"], &[])),
+                ("101", site("101", &["2025-08-14"], &[])),
+            ]
+        };
+
+        for (count, allowed) in [
+            (None, true),
+            (Some(1), false),
+            (Some(2), true),
+            (Some(3), false),
+        ] {
+            let issues = validate_month_availability(august(), &month(sites(), count));
+            assert_eq!(issues.is_empty(), allowed, "{:?} reported", count);
+        }
+    }
+}
@@ -1,8 +1,25 @@
 use actix_web::{HttpResponse, Result, web};
-use notification_services::types::DeleteUserQuery;
-use sqlx::{PgPool, Row};
+use app_config::AppConfig;
+use campground_scan::{
+    AlertConversionQuery, AvailabilityExportQuery, BookingAnalytics, DisablePollingJobRequest,
+    PollingJobAdmin, PollingJobReportQuery, ReleaseScheduleRequest, ScanConfigUpdate, ScanError,
+    ScanManager, ScanWatchdog, SharedScanConfig, TimeToBookQuery, UpdatePlanRequest,
+    UpdatePollingJobRequest, WatchdogSettings, availability_export,
+};
+use i18n::user_timezone;
+use notification_services::notification_log::NOTIFICATION_STATUSES;
+use notification_services::types::{DeleteUserQuery, TestNotificationRequest};
+use notification_services::{NotificationListQuery, NotificationLog, SmsBudget};
+use postgres::backup::{BackupArchive, BackupError, BackupQuery, export_archive, restore_archive};
+use rec_gov::{ApiCallLedger, ApiUsageQuery, RecGovClient, field_error_counts};
+use sqlx::PgPool;
+use std::time::Duration;
+use validator::Validate;
 
-use auth_services::types::AuthError;
+use auth_services::middleware::AdminUser;
+use auth_services::types::{AuthError, UserListQuery};
+
+use crate::AppState;
 
 /// Health check endpoint for auth service
 pub async fn auth_health() -> Result<HttpResponse> {
@@ -13,33 +30,114 @@ pub async fn auth_health() -> Result<HttpResponse> {
     })))
 }
 
-/// Development endpoint to list users (remove in production!)
-pub async fn list_users(pool: web::Data<PgPool>) -> Result<HttpResponse, AuthError> {
-    let rows = sqlx::query(
-        "SELECT id, name, email, phone, email_verified, phone_verified, created_at FROM users ORDER BY created_at DESC"
-    )
-    .fetch_all(pool.get_ref())
-    .await?;
+/// Lists users for admins a page at a time, newest first, filtered by email, status,
+/// and signup date
+pub async fn list_users(
+    state: web::Data<AppState>,
+    _admin: AdminUser,
+    query: web::Query<UserListQuery>,
+) -> Result<HttpResponse, AuthError> {
+    let users = state.auth.list_users(&query).await?;
 
-    let users: Vec<serde_json::Value> = rows
-        .iter()
-        .map(|row| {
-            serde_json::json!({
-                "id": row.get::<uuid::Uuid, _>("id"),
-                "name": row.get::<String, _>("name"),
-                "email": row.get::<String, _>("email"),
-                "phone": row.get::<Option<String>, _>("phone"),
-                "email_verified": row.get::<bool, _>("email_verified"),
-                "phone_verified": row.get::<bool, _>("phone_verified"),
-                "created_at": row.get::<chrono::DateTime<chrono::Utc>, _>("created_at")
-            })
-        })
-        .collect();
+    Ok(HttpResponse::Ok().json(users))
+}
 
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "users": users,
-        "count": users.len()
-    })))
+/// Lists sent and queued notifications for admins a page at a time, newest first,
+/// filtered by email, status, channel, and date
+pub async fn list_notifications(
+    pool: web::Data<PgPool>,
+    _admin: AdminUser,
+    query: web::Query<NotificationListQuery>,
+) -> Result<HttpResponse, AuthError> {
+    if let Some(status) = query.status.as_deref()
+        && !NOTIFICATION_STATUSES.contains(&status)
+    {
+        return Err(AuthError::Validation(format!(
+            "Unknown status '{}'; use one of {}",
+            status,
+            NOTIFICATION_STATUSES.join(", ")
+        )));
+    }
+
+    let notifications = NotificationLog::new(pool.get_ref().clone())
+        .list(&query)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(notifications))
+}
+
+/// Sends a sample availability alert to the calling admin's own email or phone through
+/// the real templates, SES/SNS clients and notifications ledger, so delivery problems
+/// show up before a real alert is lost. Send failures come back as `502` with the
+/// provider's error.
+pub async fn send_test_notification(
+    state: web::Data<AppState>,
+    sms_budget: web::Data<SmsBudget>,
+    admin: AdminUser,
+    request: web::Json<TestNotificationRequest>,
+) -> Result<HttpResponse, AuthError> {
+    let user = state
+        .auth
+        .get_user_by_id(&admin.0)
+        .await?
+        .ok_or(AuthError::UserNotFound)?;
+    let timezone = user_timezone(Some(&user.timezone));
+
+    let (recipient, sent) = match request.channel.as_str() {
+        "email" => (
+            user.email.clone(),
+            state
+                .notifications
+                .send_test_alert_email(&admin.0, &user.email, user.locale, timezone)
+                .await
+                .map(|()| None),
+        ),
+        "sms" => {
+            let Some(phone) = user.phone.filter(|phone| !phone.is_empty()) else {
+                return Err(AuthError::Validation(
+                    "Add a phone number to your profile to test SMS alerts".to_string(),
+                ));
+            };
+            let sent = state
+                .notifications
+                .send_test_alert_sms(&sms_budget, &admin.0, &phone, user.locale, timezone)
+                .await
+                .map(Some);
+            (phone, sent)
+        }
+        other => {
+            return Err(AuthError::Validation(format!(
+                "Unknown channel '{}'; use email or sms",
+                other
+            )));
+        }
+    };
+
+    match sent {
+        Ok(allowance) => {
+            log::info!("🧪 Admin {} sent a test {} alert", admin.0, request.channel);
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "channel": request.channel,
+                "recipient": recipient,
+                "sent": allowance.is_none_or(|allowance| allowance.is_allowed()),
+                "sms_allowance": allowance
+            })))
+        }
+        Err(e) => {
+            log::error!(
+                "❌ Test {} alert for admin {} failed: {}",
+                request.channel,
+                admin.0,
+                e
+            );
+            Ok(HttpResponse::BadGateway().json(serde_json::json!({
+                "error": "delivery_failed",
+                "channel": request.channel,
+                "recipient": recipient,
+                "message": e.to_string()
+            })))
+        }
+    }
 }
 
 /// Handles user deletion by email, deleting user sessions first to avoid foreign key constraints.
@@ -78,3 +176,511 @@ pub async fn delete_user_by_email(
         })))
     }
 }
+
+/// Reports upstream API usage per day, endpoint, and campground, availability fields
+/// recreation.gov sent in an unexpected shape since startup, and this month's SMS budget
+/// (admin only)
+pub async fn get_api_usage(
+    ledger: web::Data<ApiCallLedger>,
+    sms_budget: web::Data<SmsBudget>,
+    _admin: AdminUser,
+    query: web::Query<ApiUsageQuery>,
+) -> Result<HttpResponse, AuthError> {
+    let days = query.days.unwrap_or(7).clamp(1, 90);
+    let usage = ledger.daily_usage(days).await?;
+    let total_calls: i64 = usage.iter().map(|u| u.calls).sum();
+    let sms = sms_budget.global_usage().await?;
+    let hourly_budget = ledger.hourly_budget().await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "days": days,
+        "total_calls": total_calls,
+        "usage": usage,
+        "parse_errors": field_error_counts(),
+        "hourly_budget": hourly_budget,
+        "sms_budget": sms
+    })))
+}
+
+/// Admin endpoint reporting how quickly sites get booked at each campground
+pub async fn get_time_to_book(
+    pool: web::Data<PgPool>,
+    _admin: AdminUser,
+    query: web::Query<TimeToBookQuery>,
+) -> Result<HttpResponse, AuthError> {
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+    let analytics = BookingAnalytics::new(pool.get_ref().clone());
+    let campgrounds = analytics.time_to_book(days).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "days": days,
+        "campgrounds": campgrounds
+    })))
+}
+
+/// Admin endpoint reporting how often alerts turn into bookings at each campground,
+/// from the outcomes users record on their scans
+pub async fn get_alert_conversion(
+    pool: web::Data<PgPool>,
+    _admin: AdminUser,
+    query: web::Query<AlertConversionQuery>,
+) -> Result<HttpResponse, AuthError> {
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+    let analytics = BookingAnalytics::new(pool.get_ref().clone());
+    let campgrounds = analytics.alert_conversion(days).await?;
+    let alerted_scans: i64 = campgrounds.iter().map(|c| c.alerted_scans).sum();
+    let booked: i64 = campgrounds.iter().map(|c| c.booked).sum();
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "days": days,
+        "alerted_scans": alerted_scans,
+        "booked": booked,
+        "conversion_rate": booked as f64 / alerted_scans.max(1) as f64,
+        "campgrounds": campgrounds
+    })))
+}
+
+/// Admin endpoint downloading cached campground availability over a date range as CSV
+/// (default) or JSON, streamed a page of rows at a time
+pub async fn export_availability(
+    pool: web::Data<PgPool>,
+    admin: AdminUser,
+    query: web::Query<AvailabilityExportQuery>,
+) -> Result<HttpResponse, ScanError> {
+    let query = query.into_inner();
+    query.validate()?;
+    let format = query.format.unwrap_or_default();
+
+    log::info!(
+        "📤 Admin {} exporting availability from {} to {}",
+        admin.0,
+        query.start_date,
+        query.end_date
+    );
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            "Content-Disposition",
+            format!(
+                "attachment; filename=\"availability-{}-{}.{}\"",
+                query.start_date,
+                query.end_date,
+                format.extension()
+            ),
+        ))
+        .streaming(availability_export(pool.get_ref().clone(), query)))
+}
+
+/// Admin endpoint reporting the polling queue: due jobs and how long they've waited in
+/// each priority bucket, and the jobs being polled right now
+pub async fn get_polling_queue(
+    pool: web::Data<PgPool>,
+    _admin: AdminUser,
+) -> Result<HttpResponse, ScanError> {
+    let stats = PollingJobAdmin::new(pool.get_ref().clone())
+        .queue_stats()
+        .await?;
+
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+/// Admin endpoint drilling into one campground's polling: the job's schedule and error
+/// streak, its latest upstream calls, and the dates whose availability check failed
+pub async fn get_polling_job(
+    pool: web::Data<PgPool>,
+    _admin: AdminUser,
+    path: web::Path<String>,
+    query: web::Query<PollingJobReportQuery>,
+) -> Result<HttpResponse, ScanError> {
+    let campground_id = path.into_inner();
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+
+    match PollingJobAdmin::new(pool.get_ref().clone())
+        .report(&campground_id, limit)
+        .await?
+    {
+        Some(report) => Ok(HttpResponse::Ok().json(report)),
+        None => Ok(polling_job_not_found(&campground_id)),
+    }
+}
+
+/// Admin endpoint changing a campground's poll frequency and priority, pausing or
+/// resuming it, and pinning its schedule against automatic retuning
+pub async fn update_polling_job(
+    pool: web::Data<PgPool>,
+    admin: AdminUser,
+    path: web::Path<String>,
+    request: web::Json<UpdatePollingJobRequest>,
+) -> Result<HttpResponse, ScanError> {
+    request
+        .validate()
+        .map_err(|e| ScanError::Validation(format!("Validation error: {}", e)))?;
+    if request.is_empty() {
+        return Err(ScanError::Validation(
+            "Nothing to update: set poll_frequency_minutes, priority, paused, or pinned"
+                .to_string(),
+        ));
+    }
+    let campground_id = path.into_inner();
+
+    let Some(job) = PollingJobAdmin::new(pool.get_ref().clone())
+        .update(&campground_id, &request)
+        .await?
+    else {
+        return Ok(polling_job_not_found(&campground_id));
+    };
+
+    log::info!(
+        "🛠️ Admin {} updated polling of campground {}: every {} minutes, priority {}, paused {}, pinned {}",
+        admin.0,
+        campground_id,
+        job.poll_frequency_minutes.unwrap_or_default(),
+        job.priority.unwrap_or_default(),
+        job.paused,
+        job.pinned
+    );
+    Ok(HttpResponse::Ok().json(job))
+}
+
+/// Admin endpoint clearing a campground's consecutive error count
+pub async fn reset_polling_job_errors(
+    pool: web::Data<PgPool>,
+    admin: AdminUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ScanError> {
+    let campground_id = path.into_inner();
+
+    if !PollingJobAdmin::new(pool.get_ref().clone())
+        .reset_errors(&campground_id)
+        .await?
+    {
+        return Ok(polling_job_not_found(&campground_id));
+    }
+
+    log::info!(
+        "🔄 Admin {} reset the error count of campground {}",
+        admin.0,
+        campground_id
+    );
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "campground_id": campground_id,
+        "consecutive_errors": 0
+    })))
+}
+
+/// Admin endpoint skipping a campground's polling for a number of minutes
+pub async fn disable_polling_job(
+    pool: web::Data<PgPool>,
+    admin: AdminUser,
+    path: web::Path<String>,
+    request: web::Json<DisablePollingJobRequest>,
+) -> Result<HttpResponse, ScanError> {
+    request
+        .validate()
+        .map_err(|e| ScanError::Validation(format!("Validation error: {}", e)))?;
+    let campground_id = path.into_inner();
+
+    let Some(disabled_until) = PollingJobAdmin::new(pool.get_ref().clone())
+        .disable(&campground_id, request.minutes)
+        .await?
+    else {
+        return Ok(polling_job_not_found(&campground_id));
+    };
+
+    log::warn!(
+        "⏸️ Admin {} disabled polling of campground {} until {}",
+        admin.0,
+        campground_id,
+        disabled_until
+    );
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "campground_id": campground_id,
+        "disabled_until": disabled_until
+    })))
+}
+
+/// Admin endpoint resuming a disabled campground's polling right away
+pub async fn enable_polling_job(
+    pool: web::Data<PgPool>,
+    admin: AdminUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ScanError> {
+    let campground_id = path.into_inner();
+
+    if !PollingJobAdmin::new(pool.get_ref().clone())
+        .enable(&campground_id)
+        .await?
+    {
+        return Ok(polling_job_not_found(&campground_id));
+    }
+
+    log::info!(
+        "▶️ Admin {} re-enabled polling of campground {}",
+        admin.0,
+        campground_id
+    );
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "campground_id": campground_id,
+        "disabled_until": null
+    })))
+}
+
+/// Admin endpoint polling a campground's active scans right away, due or not, and
+/// alerting about any open sites as a scheduled poll would
+pub async fn force_scan(
+    scan_manager: web::Data<ScanManager>,
+    admin: AdminUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ScanError> {
+    let campground_id = path.into_inner();
+
+    let Some(round) = scan_manager.force_scan(&campground_id).await? else {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "scan_not_started",
+            "message": format!(
+                "Campground {} has no active scans or is being polled right now",
+                campground_id
+            )
+        })));
+    };
+
+    log::info!(
+        "⚡ Admin {} forced a poll of campground {}: {} scans checked, {} alerts sent",
+        admin.0,
+        campground_id,
+        round.scans_checked,
+        round.alerts_sent
+    );
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "campground_id": campground_id,
+        "round": round
+    })))
+}
+
+/// Admin endpoint showing what this instance's scan manager has been polling
+pub async fn get_scan_manager_status(
+    scan_manager: web::Data<ScanManager>,
+    _admin: AdminUser,
+) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(scan_manager.status()))
+}
+
+/// Admin endpoint setting when a campground releases inventory, so it's polled every few
+/// seconds around that time
+pub async fn set_release_schedule(
+    pool: web::Data<PgPool>,
+    admin: AdminUser,
+    path: web::Path<String>,
+    request: web::Json<ReleaseScheduleRequest>,
+) -> Result<HttpResponse, ScanError> {
+    request
+        .validate()
+        .map_err(|e| ScanError::Validation(format!("Validation error: {}", e)))?;
+    let Some(schedule) = request.schedule() else {
+        return Err(ScanError::Validation("Unknown time zone".to_string()));
+    };
+    let campground_id = path.into_inner();
+
+    let Some(job) = PollingJobAdmin::new(pool.get_ref().clone())
+        .set_release_schedule(&campground_id, Some(&schedule))
+        .await?
+    else {
+        return Ok(polling_job_not_found(&campground_id));
+    };
+
+    log::info!(
+        "⏰ Admin {} set campground {} to release at {} {}",
+        admin.0,
+        campground_id,
+        schedule.release_time.format("%H:%M"),
+        schedule.time_zone
+    );
+    Ok(HttpResponse::Ok().json(job))
+}
+
+/// Admin endpoint clearing a campground's release schedule, ending its release bursts
+pub async fn clear_release_schedule(
+    pool: web::Data<PgPool>,
+    admin: AdminUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ScanError> {
+    let campground_id = path.into_inner();
+
+    let Some(job) = PollingJobAdmin::new(pool.get_ref().clone())
+        .set_release_schedule(&campground_id, None)
+        .await?
+    else {
+        return Ok(polling_job_not_found(&campground_id));
+    };
+
+    log::info!(
+        "⏰ Admin {} cleared the release schedule of campground {}",
+        admin.0,
+        campground_id
+    );
+    Ok(HttpResponse::Ok().json(job))
+}
+
+/// Admin endpoint reporting whether the scan system is up, as of the watchdog's last
+/// check, with the polling and upstream API figures behind it
+pub async fn get_scan_system_stats(
+    pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
+    _admin: AdminUser,
+) -> Result<HttpResponse, ScanError> {
+    let stats = ScanWatchdog::new(
+        pool.get_ref().clone(),
+        WatchdogSettings::from_config(&config),
+    )
+    .stats()
+    .await?;
+
+    Ok(HttpResponse::Ok().json(stats))
+}
+
+/// Admin endpoint listing the recreation.gov proxies with their call and failure counts,
+/// including those taken out of rotation
+pub async fn get_proxy_health(
+    client: web::Data<RecGovClient>,
+    _admin: AdminUser,
+) -> Result<HttpResponse> {
+    let proxies = client.proxy_health();
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "in_rotation": proxies.iter().filter(|proxy| proxy.in_rotation).count(),
+        "proxies": proxies
+    })))
+}
+
+/// Admin endpoint showing the polling settings in effect on this instance
+pub async fn get_scan_config(
+    scan_config: web::Data<SharedScanConfig>,
+    _admin: AdminUser,
+) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(scan_config.get()))
+}
+
+/// Admin endpoint changing the minimum interval between upstream calls, the hourly call
+/// budget, and the error backoff on this instance without a restart, e.g. during a
+/// recreation.gov incident
+pub async fn update_scan_config(
+    scan_config: web::Data<SharedScanConfig>,
+    client: web::Data<RecGovClient>,
+    admin: AdminUser,
+    request: web::Json<ScanConfigUpdate>,
+) -> Result<HttpResponse, ScanError> {
+    request
+        .validate()
+        .map_err(|e| ScanError::Validation(format!("Validation error: {}", e)))?;
+    if request.is_empty() {
+        return Err(ScanError::Validation(
+            "Nothing to update: set min_api_interval_ms, max_calls_per_hour, or error_backoff_max_minutes"
+                .to_string(),
+        ));
+    }
+
+    let updated = scan_config.update(&request);
+    client.set_call_limits(
+        Duration::from_millis(updated.min_api_interval_ms),
+        updated.max_calls_per_hour,
+    );
+
+    log::warn!(
+        "🎛️ Admin {} changed the scan config: calls at least {} ms apart, {} calls per hour, error backoff up to {} minutes",
+        admin.0,
+        updated.min_api_interval_ms,
+        updated.max_calls_per_hour,
+        updated.error_backoff_max_minutes
+    );
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+fn polling_job_not_found(campground_id: &str) -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "error": "polling_job_not_found",
+        "message": format!("Campground {} has no polling job", campground_id)
+    }))
+}
+
+/// Maps archive errors to a response; database failures surface as internal errors
+fn backup_error_response(error: BackupError) -> Result<HttpResponse, AuthError> {
+    match error {
+        BackupError::Database(e) => Err(AuthError::Database(e)),
+        e => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "invalid_archive",
+            "message": e.to_string()
+        }))),
+    }
+}
+
+/// Admin endpoint exporting campgrounds, users, organizations, and scans as a versioned
+/// archive, optionally with user PII stripped
+pub async fn export_backup(
+    pool: web::Data<PgPool>,
+    admin: AdminUser,
+    query: web::Query<BackupQuery>,
+) -> Result<HttpResponse, AuthError> {
+    let strip_pii = query.strip_pii.unwrap_or(false);
+
+    log::warn!(
+        "🗄️ Admin {} exporting backup archive (PII stripped: {})",
+        admin.0,
+        strip_pii
+    );
+
+    match export_archive(pool.get_ref(), strip_pii).await {
+        Ok(archive) => Ok(HttpResponse::Ok().json(archive)),
+        Err(e) => backup_error_response(e),
+    }
+}
+
+/// Admin endpoint restoring an archive; existing rows are kept
+pub async fn restore_backup(
+    pool: web::Data<PgPool>,
+    admin: AdminUser,
+    archive: web::Json<BackupArchive>,
+) -> Result<HttpResponse, AuthError> {
+    log::warn!(
+        "🗄️ Admin {} restoring backup archive from {}",
+        admin.0,
+        archive.created_at
+    );
+
+    match restore_archive(pool.get_ref(), &archive).await {
+        Ok(summary) => Ok(HttpResponse::Ok().json(summary)),
+        Err(e) => backup_error_response(e),
+    }
+}
+
+/// Admin endpoint moving a user to another plan tier
+pub async fn update_user_plan(
+    state: web::Data<AppState>,
+    admin: AdminUser,
+    path: web::Path<uuid::Uuid>,
+    request: web::Json<UpdatePlanRequest>,
+) -> Result<HttpResponse, ScanError> {
+    let user_id = path.into_inner();
+    let scan_service = &state.scans;
+
+    match scan_service.set_user_plan(&user_id, &request.plan).await? {
+        Some(plan) => {
+            log::info!(
+                "💳 Admin {} moved user {} to the {} plan",
+                admin.0,
+                user_id,
+                plan.id
+            );
+            let quota = scan_service.scan_quota(&user_id).await?;
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "user_id": user_id,
+                "plan": plan,
+                "quota": quota
+            })))
+        }
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "user_not_found",
+            "message": format!("No user found with ID {}", user_id)
+        }))),
+    }
+}
@@ -0,0 +1,74 @@
+use app_config::AppConfig;
+use auth_services::auth_api::{AuthApi, TwoFactorApi};
+use auth_services::jwt::JwtService;
+use auth_services::service::AuthService;
+use auth_services::two_factor::{TwoFactorCipher, TwoFactorService};
+use campground_scan::{PermitScanService, ScanApi, ScanService};
+use notification_services::service::NotificationService;
+use sqlx::PgPool;
+
+/// Services shared by the handlers. Created once at startup and registered as
+/// `web::Data<AppState>`, so handlers don't rebuild them on every request. Handlers
+/// generic over [`AuthApi`], [`ScanApi`], and [`TwoFactorApi`] can be tested against
+/// mock services.
+pub struct AppState<
+    A: AuthApi = AuthService,
+    S: ScanApi = ScanService,
+    T: TwoFactorApi = TwoFactorService,
+> {
+    /// User accounts, sessions, and profiles
+    pub auth: A,
+    /// Users' authenticator enrollments and their codes
+    pub two_factor: T,
+    /// Signs and verifies access, refresh, and two-factor challenge tokens
+    pub jwt: JwtService,
+    /// Users' campground scans, expiring them as configured
+    pub scans: S,
+    /// Users' permit scans
+    pub permit_scans: PermitScanService,
+    /// Email and SMS delivery
+    pub notifications: NotificationService,
+}
+
+impl<A: AuthApi, S: ScanApi, T: TwoFactorApi> AppState<A, S, T> {
+    /// Creates the state from already-built services
+    pub fn new(
+        auth: A,
+        two_factor: T,
+        jwt: JwtService,
+        scans: S,
+        permit_scans: PermitScanService,
+        notifications: NotificationService,
+    ) -> Self {
+        Self {
+            auth,
+            two_factor,
+            jwt,
+            scans,
+            permit_scans,
+            notifications,
+        }
+    }
+}
+
+impl AppState {
+    /// Creates the services backed by the given pool and configuration
+    pub fn from_config(
+        pool: PgPool,
+        config: &AppConfig,
+        jwt: JwtService,
+        notifications: NotificationService,
+    ) -> Self {
+        Self::new(
+            AuthService::new(pool.clone()),
+            TwoFactorService::new(
+                pool.clone(),
+                TwoFactorCipher::new(config.two_factor_key_material()),
+            ),
+            jwt,
+            ScanService::from_config(pool.clone(), config),
+            PermitScanService::new(pool),
+            notifications,
+        )
+    }
+}
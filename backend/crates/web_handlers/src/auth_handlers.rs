@@ -1,20 +1,22 @@
 use actix_web::{HttpResponse, Result, web};
 use bcrypt::hash;
-use sqlx::PgPool;
 use validator::Validate;
 
-use auth_services::jwt::JwtService;
-use auth_services::service::AuthService;
+use auth_services::auth_api::{AuthApi, TwoFactorApi};
+use auth_services::jwt::{JwtService, TWO_FACTOR_CHALLENGE_SECS};
 use auth_services::types::*;
+use campground_scan::ScanApi;
 use notification_services::service::*;
 use notification_services::types::*;
+use notification_services::verification::VerificationStore;
+
+use crate::AppState;
 
 /// Handles user signup by validating the request, creating a new user,
 /// generating access and refresh tokens, and returning the user info.
 /// Returns a 201 Created response with the user info and tokens.
-pub async fn signup(
-    pool: web::Data<PgPool>,
-    notification_service: web::Data<NotificationService>,
+pub async fn signup<A: AuthApi, S: ScanApi, T: TwoFactorApi>(
+    state: web::Data<AppState<A, S, T>>,
     verification_store: web::Data<VerificationStore>,
     request: web::Json<SignUpRequest>,
 ) -> Result<HttpResponse, AuthError> {
@@ -23,15 +25,14 @@ pub async fn signup(
         .validate()
         .map_err(|e| AuthError::Validation(format!("Validation error: {}", e)))?;
 
-    let auth_service = AuthService::new(pool.get_ref().clone());
-    let jwt_service = JwtService::new();
+    let auth_service = &state.auth;
 
     // Create the user
     let user = auth_service.create_user(&request).await?;
 
     // Generate tokens
-    let access_token = jwt_service.generate_access_token(&user)?;
-    let refresh_token = jwt_service.generate_refresh_token(&user.id)?;
+    let access_token = state.jwt.generate_access_token(&user)?;
+    let refresh_token = state.jwt.generate_refresh_token(&user.id)?;
 
     // Hash and store the refresh token
     let refresh_token_hash = hash(&refresh_token, bcrypt::DEFAULT_COST)?;
@@ -41,15 +42,35 @@ pub async fn signup(
 
     // Send verification email with LINK (not code)
     let verification_token = NotificationService::generate_verification_token(); // 32-char token
-    let email_key = format!("email_token_{}_{}", user.id, user.email); // Different key format
 
-    store_verification_code(&verification_store, &email_key, &verification_token, 1440); // 24 hours
+    let stored = verification_store
+        .store_token(
+            &user.id,
+            VerificationChannel::Email,
+            &user.email,
+            &verification_token,
+            1440, // 24 hours
+        )
+        .await;
 
     // Try to send verification email link (don't fail signup if this fails)
-    if let Err(e) = notification_service
-        .send_email_verification_link(&user.id, &user.email, &user.name, &verification_token)
-        .await
-    {
+    let sent = match stored {
+        Ok(()) => {
+            state
+                .notifications
+                .send_email_verification_link(
+                    &user.id,
+                    &user.email,
+                    &user.name,
+                    &verification_token,
+                    user.locale,
+                )
+                .await
+        }
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = sent {
         log::warn!("Failed to send verification email during signup: {}", e);
         // Continue with signup - user can verify later
     }
@@ -68,6 +89,8 @@ pub async fn signup(
             email_verified: user.email_verified, // Will be false for new users
             phone_verified: user.phone_verified, // Will be false for new users
             notification_preferences: notification_prefs,
+            locale: user.locale,
+            timezone: user.timezone,
         },
     };
 
@@ -75,9 +98,11 @@ pub async fn signup(
 }
 
 /// Handles user login by validating the request, verifying credentials,
-/// generating access and refresh tokens, and returning the user info.
-pub async fn login(
-    pool: web::Data<PgPool>,
+/// generating access and refresh tokens, and returning the user info. Users with
+/// two-factor authentication get a short-lived challenge token instead, exchanged for
+/// the tokens at `/api/auth/2fa` with a code from their authenticator.
+pub async fn login<A: AuthApi, S: ScanApi, T: TwoFactorApi>(
+    state: web::Data<AppState<A, S, T>>,
     request: web::Json<LoginRequest>,
 ) -> Result<HttpResponse, AuthError> {
     // Validate the request
@@ -85,14 +110,67 @@ pub async fn login(
         .validate()
         .map_err(|e| AuthError::Validation(format!("Validation error: {}", e)))?;
 
-    let auth_service = AuthService::new(pool.get_ref().clone());
-    let jwt_service = JwtService::new();
+    let auth_service = &state.auth;
 
     // Verify credentials
     let user = auth_service
         .verify_password(&request.email, &request.password)
         .await?;
 
+    // Ask for the second factor before issuing tokens
+    if state.two_factor.is_enabled(&user.id).await? {
+        return Ok(HttpResponse::Ok().json(TwoFactorChallenge {
+            two_factor_required: true,
+            challenge_token: state.jwt.generate_two_factor_challenge(&user.id)?,
+            expires_in: TWO_FACTOR_CHALLENGE_SECS,
+        }));
+    }
+
+    let response = start_session(auth_service, &state.jwt, user).await?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Completes the login of a user with two-factor authentication: checks the challenge
+/// token from `login` and a code from their authenticator (or a recovery code), then
+/// issues the access and refresh tokens.
+pub async fn login_two_factor(
+    state: web::Data<AppState>,
+    request: web::Json<TwoFactorLoginRequest>,
+) -> Result<HttpResponse, AuthError> {
+    request
+        .validate()
+        .map_err(|e| AuthError::Validation(format!("Validation error: {}", e)))?;
+
+    let user_id = state
+        .jwt
+        .verify_two_factor_challenge(&request.challenge_token)?;
+
+    state.two_factor.verify(&user_id, &request.code).await?;
+
+    let auth_service = &state.auth;
+    let user = auth_service
+        .get_user_by_id(&user_id)
+        .await?
+        .ok_or(AuthError::InvalidChallenge)?;
+
+    let response = start_session(auth_service, &state.jwt, user).await?;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Publishes the public keys tokens are signed with (JWKS), so other services can verify
+/// access tokens without sharing a secret. Empty when tokens are signed with HS256.
+pub async fn jwks(state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "public, max-age=300"))
+        .json(state.jwt.jwks())
+}
+
+/// Generates and stores the access and refresh tokens of a signed-in user.
+async fn start_session<A: AuthApi>(
+    auth_service: &A,
+    jwt_service: &JwtService,
+    user: User,
+) -> Result<AuthResponse, AuthError> {
     // Generate tokens
     let access_token = jwt_service.generate_access_token(&user)?;
     let refresh_token = jwt_service.generate_refresh_token(&user.id)?;
@@ -103,10 +181,9 @@ pub async fn login(
         .create_session(&user.id, &refresh_token_hash)
         .await?;
 
-    // Prepare response
     let notification_prefs = user.to_notification_preferences()?;
 
-    let response = AuthResponse {
+    Ok(AuthResponse {
         access_token,
         refresh_token,
         user: UserInfo {
@@ -117,8 +194,252 @@ pub async fn login(
             email_verified: user.email_verified,
             phone_verified: user.phone_verified,
             notification_preferences: notification_prefs,
+            locale: user.locale,
+            timezone: user.timezone,
         },
-    };
+    })
+}
 
-    Ok(HttpResponse::Ok().json(response))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_state, test_user, unused_pool};
+    use actix_web::http::StatusCode;
+    use actix_web::{App, test};
+    use app_config::AppConfig;
+    use campground_scan::ScanService;
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    /// Accounts held in memory: a single registered user, and the sessions started
+    struct MockAuth {
+        email: String,
+        password: String,
+        sessions: Mutex<Vec<Uuid>>,
+    }
+
+    impl MockAuth {
+        fn new() -> Self {
+            Self {
+                email: "camper@example.com".to_string(),
+                password: "correct horse".to_string(),
+                sessions: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl AuthApi for MockAuth {
+        async fn create_user(&self, request: &SignUpRequest) -> Result<User, AuthError> {
+            if request.email.eq_ignore_ascii_case(&self.email) {
+                return Err(AuthError::EmailExists);
+            }
+            Ok(test_user(&request.email))
+        }
+
+        async fn verify_password(&self, email: &str, password: &str) -> Result<User, AuthError> {
+            if email == self.email && password == self.password {
+                Ok(test_user(email))
+            } else {
+                Err(AuthError::InvalidCredentials)
+            }
+        }
+
+        async fn create_session(
+            &self,
+            user_id: &Uuid,
+            _refresh_token_hash: &str,
+        ) -> Result<Uuid, AuthError> {
+            self.sessions.lock().unwrap().push(*user_id);
+            Ok(Uuid::new_v4())
+        }
+    }
+
+    /// Two-factor enrollments held in memory: whether every user has it enabled
+    struct MockTwoFactor {
+        enabled: bool,
+    }
+
+    impl TwoFactorApi for MockTwoFactor {
+        async fn is_enabled(&self, _user_id: &Uuid) -> Result<bool, AuthError> {
+            Ok(self.enabled)
+        }
+    }
+
+    type TestState = AppState<MockAuth, ScanService, MockTwoFactor>;
+
+    /// Posts `body` to the signup or login route and returns the status and JSON body
+    async fn post(
+        state: web::Data<TestState>,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let app = test::init_service(
+            App::new()
+                .app_data(state)
+                .app_data(web::Data::new(
+                    VerificationStore::from_config(unused_pool(), &AppConfig::default()).await,
+                ))
+                .route(
+                    "/api/auth/signup",
+                    web::post().to(signup::<MockAuth, ScanService, MockTwoFactor>),
+                )
+                .route(
+                    "/api/auth/login",
+                    web::post().to(login::<MockAuth, ScanService, MockTwoFactor>),
+                ),
+        )
+        .await;
+
+        let request = test::TestRequest::post()
+            .uri(uri)
+            .set_json(body)
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        let status = response.status();
+        let body = test::read_body(response).await;
+
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    fn state() -> web::Data<TestState> {
+        state_with_two_factor(false)
+    }
+
+    fn state_with_two_factor(enabled: bool) -> web::Data<TestState> {
+        test_state(
+            MockAuth::new(),
+            ScanService::new(unused_pool()),
+            MockTwoFactor { enabled },
+        )
+    }
+
+    fn signup_body(email: &str) -> serde_json::Value {
+        serde_json::json!({
+            "name": "New Camper",
+            "email": email,
+            "phone": "5555550199",
+            "password": "long enough password",
+            "notification_preferences": { "email": true, "sms": false }
+        })
+    }
+
+    #[actix_web::test]
+    async fn signup_rejects_invalid_requests() {
+        let (status, body) = post(state(), "/api/auth/signup", signup_body("not an email")).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "validation_error");
+    }
+
+    #[actix_web::test]
+    async fn signup_creates_the_user_and_starts_a_session() {
+        let state = state();
+        let (status, body) = post(
+            state.clone(),
+            "/api/auth/signup",
+            signup_body("new@example.com"),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["user"]["email"], "new@example.com");
+        assert_eq!(body["user"]["email_verified"], false);
+        let access_token = body["access_token"].as_str().unwrap();
+        assert!(state.jwt.verify_token(access_token).is_ok());
+        assert!(
+            body["refresh_token"]
+                .as_str()
+                .is_some_and(|t| !t.is_empty())
+        );
+        assert_eq!(state.auth.sessions.lock().unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn signup_conflicts_with_a_registered_email() {
+        let state = state();
+        let (status, body) = post(
+            state.clone(),
+            "/api/auth/signup",
+            signup_body("Camper@Example.com"),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "email_exists");
+        assert!(state.auth.sessions.lock().unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn login_rejects_invalid_requests() {
+        let (status, body) = post(
+            state(),
+            "/api/auth/login",
+            serde_json::json!({ "email": "camper@example.com", "password": "" }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "validation_error");
+    }
+
+    #[actix_web::test]
+    async fn login_rejects_wrong_credentials_without_a_session() {
+        let state = state();
+
+        for (email, password) in [
+            ("camper@example.com", "wrong password"),
+            ("stranger@example.com", "correct horse"),
+        ] {
+            let (status, body) = post(
+                state.clone(),
+                "/api/auth/login",
+                serde_json::json!({ "email": email, "password": password }),
+            )
+            .await;
+
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(body["error"], "invalid_credentials");
+        }
+        assert!(state.auth.sessions.lock().unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn login_issues_tokens_for_valid_credentials() {
+        let state = state();
+        let (status, body) = post(
+            state.clone(),
+            "/api/auth/login",
+            serde_json::json!({ "email": "camper@example.com", "password": "correct horse" }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["user"]["email"], "camper@example.com");
+        let access_token = body["access_token"].as_str().unwrap();
+        assert!(state.jwt.verify_token(access_token).is_ok());
+        assert!(
+            body["refresh_token"]
+                .as_str()
+                .is_some_and(|t| !t.is_empty())
+        );
+        assert_eq!(state.auth.sessions.lock().unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn login_challenges_users_with_two_factor_before_a_session() {
+        let state = state_with_two_factor(true);
+        let (status, body) = post(
+            state.clone(),
+            "/api/auth/login",
+            serde_json::json!({ "email": "camper@example.com", "password": "correct horse" }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["two_factor_required"], true);
+        let challenge = body["challenge_token"].as_str().unwrap();
+        assert!(state.jwt.verify_two_factor_challenge(challenge).is_ok());
+        assert!(body.get("access_token").is_none());
+        assert!(state.auth.sessions.lock().unwrap().is_empty());
+    }
 }
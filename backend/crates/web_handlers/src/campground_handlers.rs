@@ -0,0 +1,20 @@
+use actix_web::{HttpResponse, Result, web};
+use sqlx::PgPool;
+
+use campground_scan::{BookingAnalytics, CampgroundStatsQuery, ScanError};
+
+/// Gets a campground's availability trends: openings by weekday, time to rebook, and the
+/// best hours to scan
+pub async fn get_campground_stats(
+    pool: web::Data<PgPool>,
+    path: web::Path<String>,
+    query: web::Query<CampgroundStatsQuery>,
+) -> Result<HttpResponse, ScanError> {
+    let campground_id = path.into_inner();
+    let days = query.days.unwrap_or(90).clamp(1, 365);
+
+    let analytics = BookingAnalytics::new(pool.get_ref().clone());
+    let stats = analytics.campground_stats(&campground_id, days).await?;
+
+    Ok(HttpResponse::Ok().json(stats))
+}
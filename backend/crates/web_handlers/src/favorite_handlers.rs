@@ -0,0 +1,81 @@
+use actix_web::{HttpResponse, Result, web};
+use validator::Validate;
+
+use auth_services::middleware::AuthenticatedUser;
+use campground_scan::{
+    AddFavoriteRequest, FavoriteScanRequest, FavoriteService, FavoritesResponse, ScanError,
+};
+use rec_gov::RecGovClient;
+
+use crate::AppState;
+use crate::scan_handlers::create_checked_scan;
+
+/// Lists the user's favorite campgrounds with their cached availability
+pub async fn list_favorites(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ScanError> {
+    let favorites = FavoriteService::new(pool.get_ref().clone())
+        .list(&user.0)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(FavoritesResponse { favorites }))
+}
+
+/// Bookmarks a campground, or updates the note of one already bookmarked
+pub async fn add_favorite(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    request: web::Json<AddFavoriteRequest>,
+) -> Result<HttpResponse, ScanError> {
+    request
+        .validate()
+        .map_err(|e| ScanError::Validation(format!("Validation error: {}", e)))?;
+
+    let favorite = FavoriteService::new(pool.get_ref().clone())
+        .add(&user.0, &request)
+        .await?;
+
+    Ok(HttpResponse::Created().json(favorite))
+}
+
+/// Removes a campground from the user's favorites
+pub async fn remove_favorite(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ScanError> {
+    let campground_id = path.into_inner();
+    let removed = FavoriteService::new(pool.get_ref().clone())
+        .remove(&user.0, &campground_id)
+        .await?;
+
+    if !removed {
+        return Err(ScanError::CampgroundNotFound);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Creates a scan of a favorite campground for the given stay, with the same checks as
+/// `POST /api/scans`
+pub async fn scan_favorite(
+    state: web::Data<AppState>,
+    pool: web::Data<sqlx::PgPool>,
+    client: web::Data<RecGovClient>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    stay: web::Json<FavoriteScanRequest>,
+) -> Result<HttpResponse, ScanError> {
+    let campground_id = path.into_inner();
+    let favorite = FavoriteService::new(pool.get_ref().clone())
+        .get(&user.0, &campground_id)
+        .await?;
+
+    let request = favorite.scan_request(&stay);
+    request
+        .validate()
+        .map_err(|e| ScanError::Validation(format!("Validation error: {}", e)))?;
+
+    create_checked_scan(&pool, &state.scans, &client, &user.0, &request).await
+}
@@ -0,0 +1,110 @@
+use actix_web::{HttpResponse, web};
+use campground_scan::{WorkerHeartbeats, WorkerStatus};
+use rec_gov::RecGovClient;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::time::{Duration, Instant};
+
+/// How long the readiness check waits for the database
+const DATABASE_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Result of the database connectivity check
+#[derive(Debug, Serialize)]
+pub struct DatabaseCheck {
+    /// Whether the database answered a query
+    pub healthy: bool,
+    /// Round-trip time of the query in milliseconds
+    pub latency_ms: u64,
+    /// Why the check failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response of the health endpoints
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    /// `ok` or `unavailable`
+    pub status: &'static str,
+    /// Database connectivity (readiness only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<DatabaseCheck>,
+    /// Heartbeats of the background workers
+    pub workers: Vec<WorkerStatus>,
+    /// Whether recreation.gov answers (readiness only; informational, `null` in sandbox
+    /// mode)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recreation_gov: Option<rec_gov::Reachability>,
+    /// When the report was made
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+impl HealthReport {
+    /// 200 when healthy, 503 otherwise, so load balancers and orchestrators can act on it
+    fn into_response(self) -> HttpResponse {
+        if self.status == "ok" {
+            HttpResponse::Ok().json(self)
+        } else {
+            HttpResponse::ServiceUnavailable().json(self)
+        }
+    }
+}
+
+/// Liveness probe: fails when a background worker has stopped beating, which only a
+/// restart fixes. Dependencies aren't checked, so an outage doesn't cause restarts.
+pub async fn health_live(heartbeats: web::Data<WorkerHeartbeats>) -> HttpResponse {
+    let workers = heartbeats.statuses();
+    let healthy = workers.iter().all(|worker| worker.healthy);
+
+    HealthReport {
+        status: if healthy { "ok" } else { "unavailable" },
+        database: None,
+        workers,
+        recreation_gov: None,
+        timestamp: chrono::Utc::now(),
+    }
+    .into_response()
+}
+
+/// Readiness probe: fails when the database doesn't answer or a background worker has
+/// stalled. Recreation.gov reachability is reported but doesn't fail the check, since an
+/// upstream outage would otherwise take every instance out of rotation.
+pub async fn health_ready(
+    pool: web::Data<PgPool>,
+    heartbeats: web::Data<WorkerHeartbeats>,
+    client: web::Data<RecGovClient>,
+) -> HttpResponse {
+    let (database, recreation_gov) = tokio::join!(check_database(&pool), client.reachability());
+    let workers = heartbeats.statuses();
+    let healthy = database.healthy && workers.iter().all(|worker| worker.healthy);
+
+    HealthReport {
+        status: if healthy { "ok" } else { "unavailable" },
+        database: Some(database),
+        workers,
+        recreation_gov,
+        timestamp: chrono::Utc::now(),
+    }
+    .into_response()
+}
+
+/// Runs a trivial query, giving up after [`DATABASE_CHECK_TIMEOUT`].
+async fn check_database(pool: &PgPool) -> DatabaseCheck {
+    let started = Instant::now();
+    let result = actix_web::rt::time::timeout(
+        DATABASE_CHECK_TIMEOUT,
+        sqlx::query("SELECT 1").execute(pool),
+    )
+    .await;
+
+    let error = match result {
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some("timed out".to_string()),
+    };
+
+    DatabaseCheck {
+        healthy: error.is_none(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error,
+    }
+}
@@ -2,6 +2,10 @@
 //!
 //! This crate provides the web handlers for the Campsite Tracker application.
 
+/// Services shared by the handlers
+mod app_state;
+pub use app_state::AppState;
+
 /// Authentication handlers (signup, login)
 mod auth_handlers;
 pub use auth_handlers::*;
@@ -10,10 +14,26 @@ pub use auth_handlers::*;
 mod profile_handlers;
 pub use profile_handlers::*;
 
+/// Two-factor authentication enrollment handlers
+mod two_factor_handlers;
+pub use two_factor_handlers::*;
+
 /// Email and SMS verification handlers
 mod verification_handlers;
 pub use verification_handlers::*;
 
+/// One-click unsubscribe links from notification emails
+mod unsubscribe_handlers;
+pub use unsubscribe_handlers::*;
+
+/// SES and SNS delivery status webhooks
+mod webhook_handlers;
+pub use webhook_handlers::*;
+
+/// Liveness and readiness probes
+mod health_handlers;
+pub use health_handlers::*;
+
 /// Admin and development handlers
 mod admin_handlers;
 pub use admin_handlers::*;
@@ -21,3 +41,31 @@ pub use admin_handlers::*;
 /// Handlers for campground scan API endpoints
 mod scan_handlers;
 pub use scan_handlers::*;
+
+/// Handlers for favorite campgrounds
+mod favorite_handlers;
+pub use favorite_handlers::*;
+
+/// Handlers for trips grouping scans
+mod trip_handlers;
+pub use trip_handlers::*;
+
+/// Handlers for organizations and their members
+mod organization_handlers;
+pub use organization_handlers::*;
+
+/// Handlers for campground statistics
+mod campground_handlers;
+pub use campground_handlers::*;
+
+/// Handlers for permit scans
+mod permit_handlers;
+pub use permit_handlers::*;
+
+/// WebSocket streaming live scan updates
+mod live_handlers;
+pub use live_handlers::*;
+
+/// Fixtures shared by the handler tests
+#[cfg(test)]
+mod test_support;
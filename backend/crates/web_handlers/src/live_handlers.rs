@@ -0,0 +1,154 @@
+use actix_web::{HttpRequest, HttpResponse, Result, web};
+use actix_ws::{CloseCode, CloseReason, Message, MessageStream, Session};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use auth_services::jwt::JwtService;
+use campground_scan::ScanUpdateHub;
+use i18n::{current_locale, message};
+
+use crate::AppState;
+
+/// How long a client connecting without an Authorization header has to send its token
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often connected clients are pinged to keep proxies from dropping idle connections
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// First message of a client that couldn't send an Authorization header (browsers can't
+/// set headers on WebSocket requests)
+#[derive(Debug, Deserialize)]
+struct AuthMessage {
+    token: String,
+}
+
+/// Opens a WebSocket streaming live updates (availability hits and poll heartbeats) of
+/// the authenticated user's scans. The access token goes in the Authorization header or,
+/// from browsers, in a `{"token": "..."}` first message; it never appears in the URL, so
+/// it stays out of access logs.
+pub async fn scan_updates_ws(
+    req: HttpRequest,
+    body: web::Payload,
+    state: web::Data<AppState>,
+    hub: web::Data<ScanUpdateHub>,
+) -> Result<HttpResponse> {
+    let header_token = req
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+
+    let header_user = match header_token {
+        Some(token) => match state.jwt.extract_user_id_from_token(token) {
+            Ok(user_id) => Some(user_id),
+            Err(_) => {
+                return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+                    "error": "invalid_token",
+                    "message": message(current_locale(), "invalid-token")
+                })));
+            }
+        },
+        None => None,
+    };
+
+    let (response, session, mut stream) = actix_ws::handle(&req, body)?;
+    let jwt_service = state.jwt.clone();
+    let hub = hub.get_ref().clone();
+
+    actix_web::rt::spawn(async move {
+        let user_id = match header_user {
+            Some(user_id) => Some(user_id),
+            None => authenticate(&mut stream, &jwt_service).await,
+        };
+
+        match user_id {
+            Some(user_id) => stream_updates(user_id, session, stream, &hub).await,
+            None => {
+                let _ = session
+                    .close(Some(CloseReason {
+                        code: CloseCode::Policy,
+                        description: Some("Invalid or missing token".to_string()),
+                    }))
+                    .await;
+            }
+        }
+    });
+
+    Ok(response)
+}
+
+/// Waits for the client's token message and returns the user it belongs to
+async fn authenticate(stream: &mut MessageStream, jwt_service: &JwtService) -> Option<Uuid> {
+    let message = tokio::time::timeout(AUTH_TIMEOUT, stream.recv())
+        .await
+        .ok()??;
+
+    let Ok(Message::Text(text)) = message else {
+        return None;
+    };
+
+    let auth: AuthMessage = serde_json::from_str(&text).ok()?;
+    jwt_service.extract_user_id_from_token(&auth.token).ok()
+}
+
+/// Forwards the user's scan updates to the socket until either side goes away
+async fn stream_updates(
+    user_id: Uuid,
+    mut session: Session,
+    mut stream: MessageStream,
+    hub: &ScanUpdateHub,
+) {
+    let mut updates = hub.subscribe();
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+
+    if session
+        .text(serde_json::json!({ "type": "connected", "user_id": user_id }).to_string())
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    let close_reason = loop {
+        tokio::select! {
+            message = stream.recv() => match message {
+                Some(Ok(Message::Ping(bytes))) => {
+                    if session.pong(&bytes).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Ok(Message::Close(reason))) => break reason,
+                Some(Ok(_)) => {}
+                Some(Err(_)) | None => break None,
+            },
+            update = updates.recv() => match update {
+                Ok((recipient, update)) if recipient == user_id => {
+                    let Ok(text) = serde_json::to_string(&update) else {
+                        continue;
+                    };
+                    if session.text(text).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    log::warn!(
+                        "⚠️ Live updates for user {} fell behind; {} updates skipped",
+                        user_id,
+                        skipped
+                    );
+                }
+                Err(RecvError::Closed) => break None,
+            },
+            _ = ping.tick() => {
+                if session.ping(b"").await.is_err() {
+                    return;
+                }
+            }
+        }
+    };
+
+    let _ = session.close(close_reason).await;
+}
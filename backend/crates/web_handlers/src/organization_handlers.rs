@@ -0,0 +1,148 @@
+use actix_web::{HttpResponse, Result, web};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use auth_services::middleware::AuthenticatedUser;
+use auth_services::organizations::*;
+use campground_scan::{ListScansResponse, ScanError};
+use i18n::current_locale;
+use notification_services::NotificationService;
+
+use crate::AppState;
+
+/// Creates an organization owned by the authenticated user
+pub async fn create_organization(
+    pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
+    request: web::Json<CreateOrganizationRequest>,
+) -> Result<HttpResponse, OrganizationError> {
+    request
+        .validate()
+        .map_err(|e| OrganizationError::Validation(format!("Validation error: {}", e)))?;
+
+    let organization_service = OrganizationService::new(pool.get_ref().clone());
+    let organization = organization_service
+        .create_organization(&user.0, &request.name)
+        .await?;
+
+    Ok(HttpResponse::Created().json(organization))
+}
+
+/// Lists the organizations the authenticated user belongs to
+pub async fn list_organizations(
+    pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, OrganizationError> {
+    let organization_service = OrganizationService::new(pool.get_ref().clone());
+    let organizations = organization_service.list_organizations(&user.0).await?;
+
+    Ok(HttpResponse::Ok().json(organizations))
+}
+
+/// Gets an organization with its members and pending invitations
+pub async fn get_organization(
+    pool: web::Data<PgPool>,
+    member: OrganizationMember,
+) -> Result<HttpResponse, OrganizationError> {
+    let organization_service = OrganizationService::new(pool.get_ref().clone());
+    let organization = organization_service
+        .get_organization(&member.user_id, &member.organization_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(organization))
+}
+
+/// Invites someone to the organization by email (owner only)
+pub async fn invite_organization_member(
+    state: web::Data<AppState>,
+    pool: web::Data<PgPool>,
+    member: OrganizationMember,
+    request: web::Json<InviteMemberRequest>,
+) -> Result<HttpResponse, OrganizationError> {
+    request
+        .validate()
+        .map_err(|e| OrganizationError::Validation(format!("Validation error: {}", e)))?;
+
+    let organization_service = OrganizationService::new(pool.get_ref().clone());
+    let token = NotificationService::generate_verification_token();
+    let invitation = organization_service
+        .create_invitation(&member, &request.email, &token)
+        .await?;
+
+    let inviter_name = state
+        .auth
+        .get_user_by_id(&member.user_id)
+        .await
+        .ok()
+        .flatten()
+        .map(|user| user.name)
+        .unwrap_or_else(|| "A CampTracker user".to_string());
+
+    state
+        .notifications
+        .send_organization_invitation(
+            &invitation.email,
+            &inviter_name,
+            &invitation.organization_name,
+            &token,
+            // The invitee may not have an account yet; write in the inviter's language
+            current_locale(),
+        )
+        .await
+        .map_err(|e| OrganizationError::Validation(format!("Failed to send invitation: {}", e)))?;
+
+    Ok(HttpResponse::Created().json(serde_json::json!({
+        "email": invitation.email,
+        "expires_at": invitation.expires_at,
+    })))
+}
+
+/// Accepts an invitation sent to the authenticated user's email address
+pub async fn accept_organization_invitation(
+    pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
+    request: web::Json<AcceptInvitationRequest>,
+) -> Result<HttpResponse, OrganizationError> {
+    let organization_service = OrganizationService::new(pool.get_ref().clone());
+    let organization = organization_service
+        .accept_invitation(&user.0, &request.token)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(organization))
+}
+
+/// Removes a member from the organization; the owner can remove anyone, members can leave
+pub async fn remove_organization_member(
+    pool: web::Data<PgPool>,
+    member: OrganizationMember,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, OrganizationError> {
+    let (_, user_id) = path.into_inner();
+
+    let organization_service = OrganizationService::new(pool.get_ref().clone());
+    organization_service
+        .remove_member(&member, &user_id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Gets the scans shared with an organization
+pub async fn get_organization_scans(
+    state: web::Data<AppState>,
+    member: OrganizationMember,
+) -> Result<HttpResponse, ScanError> {
+    let scan_service = &state.scans;
+    let scans = scan_service
+        .get_organization_scans(&member.organization_id)
+        .await?;
+
+    let response = ListScansResponse {
+        total: scans.len() as i64,
+        scans,
+        quota: None,
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
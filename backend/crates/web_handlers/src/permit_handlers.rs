@@ -0,0 +1,55 @@
+use actix_web::{HttpResponse, Result, web};
+use validator::Validate;
+
+use auth_services::middleware::AuthenticatedUser;
+use campground_scan::{CreatePermitScanRequest, ListPermitScansResponse, ScanError};
+
+use crate::AppState;
+
+/// Creates a permit scan for the authenticated user
+pub async fn create_permit_scan(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    request: web::Json<CreatePermitScanRequest>,
+) -> Result<HttpResponse, ScanError> {
+    request
+        .validate()
+        .map_err(|e| ScanError::Validation(format!("Validation error: {}", e)))?;
+
+    let scan = state
+        .permit_scans
+        .create_permit_scan(&user.0, &request)
+        .await?;
+
+    Ok(HttpResponse::Created().json(scan))
+}
+
+/// Gets all permit scans for the authenticated user
+pub async fn get_permit_scans(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ScanError> {
+    let scans = state.permit_scans.get_user_permit_scans(&user.0).await?;
+
+    let response = ListPermitScansResponse {
+        total: scans.len() as i64,
+        scans,
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Deletes a permit scan
+pub async fn delete_permit_scan(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, ScanError> {
+    let scan_id = path.into_inner();
+    state
+        .permit_scans
+        .delete_permit_scan(&user.0, &scan_id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
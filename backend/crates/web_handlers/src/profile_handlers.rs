@@ -1,41 +1,83 @@
 use actix_web::{HttpResponse, Result, web};
-use sqlx::PgPool;
+use chrono::{Duration, Utc};
+use serde::Serialize;
 use validator::Validate;
 
 use auth_services::middleware::AuthenticatedUser;
 use auth_services::service::AuthService;
 use auth_services::types::*;
+use i18n::{format_phone_number, validate_phone_number};
+use notification_services::{DeliverabilityStatus, NotificationService};
+
+use crate::AppState;
+
+/// How long deliverability check results are reused before the channels are checked again
+const DELIVERABILITY_CHECK_TTL_MINUTES: i64 = 60;
+
+/// How long the link confirming a new email address stays valid
+const EMAIL_CHANGE_EXPIRY_HOURS: i64 = 24;
+
+/// User profile along with the deliverability of each notification channel
+#[derive(Debug, Serialize)]
+pub struct ProfileResponse {
+    /// Profile information
+    #[serde(flatten)]
+    pub user: UserInfo,
+    /// Result of the latest pre-flight check for each notification channel
+    pub deliverability: DeliverabilityStatus,
+    /// New email address waiting to be confirmed through the link sent to it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_email: Option<String>,
+}
 
 /// Handles user profile retrieval by fetching user info based on the authenticated user.
 pub async fn get_profile(
-    pool: web::Data<PgPool>,
+    state: web::Data<AppState>,
     user: AuthenticatedUser,
 ) -> Result<HttpResponse, AuthError> {
-    let auth_service = AuthService::new(pool.get_ref().clone());
+    let auth_service = &state.auth;
 
     let user = auth_service
         .get_user_by_id(&user.0)
         .await?
         .ok_or(AuthError::UserNotFound)?;
 
-    let notification_prefs = user.to_notification_preferences()?;
+    let deliverability =
+        channel_deliverability(auth_service, &state.notifications, &user, false).await?;
+    let pending_email = auth_service.pending_email(&user.id).await?;
 
-    let user_info = UserInfo {
-        id: user.id,
-        name: user.name,
-        email: user.email,
-        phone: user.phone.unwrap_or_default(),
-        email_verified: user.email_verified,
-        phone_verified: user.phone_verified,
-        notification_preferences: notification_prefs,
-    };
+    Ok(HttpResponse::Ok().json(ProfileResponse {
+        user: user_info(user)?,
+        deliverability,
+        pending_email,
+    }))
+}
+
+/// Discards a pending email change, so its confirmation link no longer works.
+pub async fn cancel_email_change(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, AuthError> {
+    state.auth.cancel_email_change(&user.0).await?;
 
-    Ok(HttpResponse::Ok().json(user_info))
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Reports which account setup steps the user has completed, so the frontend can guide
+/// them through the rest.
+pub async fn get_onboarding(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, AuthError> {
+    let auth_service = &state.auth;
+    let checklist = auth_service.onboarding_checklist(&user.0).await?;
+
+    Ok(HttpResponse::Ok().json(checklist))
 }
 
 /// Handles user profile update by validating the request, updating user info,
 pub async fn update_profile(
-    pool: web::Data<PgPool>,
+    state: web::Data<AppState>,
     user: AuthenticatedUser,
     request: web::Json<UpdateProfileRequest>,
 ) -> Result<HttpResponse, AuthError> {
@@ -44,22 +86,199 @@ pub async fn update_profile(
         .validate()
         .map_err(|e| AuthError::Validation(format!("Validation error: {}", e)))?;
 
-    let auth_service = AuthService::new(pool.get_ref().clone());
+    let auth_service = &state.auth;
 
-    // Update user profile
-    let updated_user = auth_service.update_user_profile(&user.0, &request).await?;
+    // Update user profile (all fields are provided, so every value is replaced)
+    let patch = PatchProfileRequest::from(request.into_inner());
+    let response = apply_profile_patch(auth_service, &state.notifications, &user, &patch).await?;
 
-    let notification_prefs = updated_user.to_notification_preferences()?;
+    Ok(HttpResponse::Ok().json(response))
+}
 
-    let user_info = UserInfo {
-        id: updated_user.id,
-        name: updated_user.name,
-        email: updated_user.email,
-        phone: updated_user.phone.unwrap_or_default(),
-        email_verified: updated_user.email_verified,
-        phone_verified: updated_user.phone_verified,
-        notification_preferences: notification_prefs,
-    };
+/// Handles partial profile updates: only the fields present in the request are validated
+/// and changed, the rest keep their current values.
+pub async fn patch_profile(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    request: web::Json<PatchProfileRequest>,
+) -> Result<HttpResponse, AuthError> {
+    // Validate the provided fields
+    request
+        .validate()
+        .map_err(|e| AuthError::Validation(format!("Validation error: {}", e)))?;
+
+    let auth_service = &state.auth;
 
-    Ok(HttpResponse::Ok().json(user_info))
+    let response = apply_profile_patch(auth_service, &state.notifications, &user, &request).await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Applies a profile patch after making sure every channel it enables or re-targets can
+/// actually deliver, then re-checks deliverability for the updated profile. A new email
+/// isn't applied right away: a confirmation link is sent to it and the current address
+/// stays in use until the link is followed.
+async fn apply_profile_patch(
+    auth_service: &AuthService,
+    notification_service: &NotificationService,
+    user: &AuthenticatedUser,
+    patch: &PatchProfileRequest,
+) -> Result<ProfileResponse, AuthError> {
+    let current_user = auth_service
+        .get_user_by_id(&user.0)
+        .await?
+        .ok_or(AuthError::UserNotFound)?;
+
+    ensure_new_channels_deliverable(notification_service, &current_user, patch).await?;
+
+    let updated_user = auth_service.update_user_profile(&user.0, patch).await?;
+
+    let new_email = patch
+        .email
+        .as_deref()
+        .map(|email| email.trim().to_lowercase())
+        .filter(|email| *email != current_user.email);
+
+    if let Some(new_email) = &new_email {
+        let token = NotificationService::generate_verification_token();
+        auth_service
+            .request_email_change(
+                &user.0,
+                new_email,
+                &token,
+                Utc::now() + Duration::hours(EMAIL_CHANGE_EXPIRY_HOURS),
+            )
+            .await?;
+
+        notification_service
+            .send_email_change_confirmation(
+                new_email,
+                &updated_user.name,
+                &token,
+                updated_user.locale,
+            )
+            .await
+            .map_err(|e| AuthError::Validation(format!("Failed to send email: {}", e)))?;
+    }
+
+    let deliverability =
+        channel_deliverability(auth_service, notification_service, &updated_user, true).await?;
+    let pending_email = auth_service.pending_email(&updated_user.id).await?;
+
+    Ok(ProfileResponse {
+        user: user_info(updated_user)?,
+        deliverability,
+        pending_email,
+    })
+}
+
+/// Rejects a patch that would rely on a channel that fails its pre-flight check: either
+/// a channel being enabled, or an enabled channel whose email/phone is changing.
+/// Checks that can't be run don't block the update.
+async fn ensure_new_channels_deliverable(
+    notification_service: &NotificationService,
+    current_user: &User,
+    patch: &PatchProfileRequest,
+) -> Result<(), AuthError> {
+    let current_prefs = current_user.to_notification_preferences()?;
+    let new_prefs = patch
+        .notification_preferences
+        .as_ref()
+        .unwrap_or(&current_prefs);
+
+    let new_email = patch.email.as_deref().map(|e| e.trim().to_lowercase());
+    let email_changed = new_email.as_ref().is_some_and(|e| *e != current_user.email);
+
+    if new_prefs.email && (!current_prefs.email || email_changed) {
+        let email = new_email.unwrap_or_else(|| current_user.email.clone());
+
+        match notification_service
+            .check_email_deliverability(&email)
+            .await
+        {
+            Ok(result) if result.is_undeliverable() => {
+                return Err(AuthError::Validation(format!(
+                    "Email notifications can't be delivered to {}: {}",
+                    email,
+                    result.reason.unwrap_or_default()
+                )));
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("⚠️ Skipping email deliverability pre-flight check: {}", e),
+        }
+    }
+
+    // Invalid numbers are rejected by the profile update itself
+    let new_phone = patch
+        .phone
+        .as_deref()
+        .filter(|phone| validate_phone_number(phone))
+        .map(format_phone_number);
+    let phone_changed = new_phone.is_some() && new_phone != current_user.phone;
+
+    if new_prefs.sms
+        && (!current_prefs.sms || phone_changed)
+        && let Some(phone) = new_phone.or_else(|| current_user.phone.clone())
+    {
+        match notification_service.check_sms_deliverability(&phone).await {
+            Ok(result) if result.is_undeliverable() => {
+                return Err(AuthError::Validation(format!(
+                    "SMS notifications can't be delivered to {}: {}",
+                    phone,
+                    result.reason.unwrap_or_default()
+                )));
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("⚠️ Skipping SMS deliverability pre-flight check: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the user's channel deliverability, re-running the pre-flight checks if the
+/// cached result is missing, stale, or `force_check` is set.
+async fn channel_deliverability(
+    auth_service: &AuthService,
+    notification_service: &NotificationService,
+    user: &User,
+    force_check: bool,
+) -> Result<DeliverabilityStatus, AuthError> {
+    if !force_check
+        && let Some((cached, checked_at)) =
+            auth_service.get_channel_deliverability(&user.id).await?
+        && checked_at > Utc::now() - Duration::minutes(DELIVERABILITY_CHECK_TTL_MINUTES)
+        && let Ok(status) = serde_json::from_value(cached)
+    {
+        return Ok(status);
+    }
+
+    let status = notification_service
+        .check_deliverability(&user.email, user.phone.as_deref())
+        .await;
+
+    let value = serde_json::to_value(&status)
+        .map_err(|e| AuthError::Internal(format!("Failed to serialize deliverability: {}", e)))?;
+    auth_service
+        .update_channel_deliverability(&user.id, &value)
+        .await?;
+
+    Ok(status)
+}
+
+/// Builds the profile information returned to the client.
+fn user_info(user: User) -> Result<UserInfo, AuthError> {
+    let notification_prefs = user.to_notification_preferences()?;
+
+    Ok(UserInfo {
+        id: user.id,
+        name: user.name,
+        email: user.email,
+        phone: user.phone.unwrap_or_default(),
+        email_verified: user.email_verified,
+        phone_verified: user.phone_verified,
+        notification_preferences: notification_prefs,
+        locale: user.locale,
+        timezone: user.timezone,
+    })
 }
@@ -1,15 +1,30 @@
 use actix_web::{HttpResponse, Result, web};
 use validator::Validate;
 
+use app_config::AppConfig;
+use auth_services::auth_api::AuthApi;
 use auth_services::middleware::AuthenticatedUser;
 use campground_scan::{
-    CreateScanRequest, CreateScanResponse, ListScansResponse, ScanError, ScanService,
-    UpdateScanRequest, UserScanWithCampground,
+    AddScanRecipientRequest, CalendarFeedQuery, CreateScanRequest, CreateScanResponse, CreatedScan,
+    ListScansResponse, PatchScanRequest, RemapScanRequest, ScanApi, ScanError, ScanEventLog,
+    ScanEventsQuery, ScanExportQuery, ScanOutcomeRequest, ScanRecipientLinkQuery,
+    ScanRecipientService, ScanSharing, UpdateScanRequest, UserScanWithCampground, scan_calendar,
+    scan_export, shared_scan_page,
 };
+use i18n::{format_phone_number, validate_phone_number};
+use notification_services::types::{NotificationError, VerificationChannel};
+use notification_services::{SmsBudget, VerificationThrottle};
+use rec_gov::{Facility, Provider, RecGovClient, campground_details};
 
-/// Creates a new campground scan for the authenticated user
-pub async fn create_scan(
+use crate::AppState;
+
+/// Creates a new campground scan for the authenticated user. Recreation.gov campgrounds
+/// are checked against their RIDB details first: unknown facilities, first-come,
+/// first-served campgrounds, and stays over the campground's limit are refused.
+pub async fn create_scan<A: AuthApi, S: ScanApi>(
+    state: web::Data<AppState<A, S>>,
     pool: web::Data<sqlx::PgPool>,
+    client: web::Data<RecGovClient>,
     user: AuthenticatedUser,
     request: web::Json<CreateScanRequest>,
 ) -> Result<HttpResponse, ScanError> {
@@ -18,57 +33,149 @@ pub async fn create_scan(
         .validate()
         .map_err(|e| ScanError::Validation(format!("Validation error: {}", e)))?;
 
-    let scan_service = ScanService::new(pool.get_ref().clone());
-    let scan = scan_service.create_scan(&user.0, &request).await?;
+    create_checked_scan(&pool, &state.scans, &client, &user.0, &request).await
+}
+
+/// Creates a validated scan request once it passes the campground's rules
+pub(crate) async fn create_checked_scan<S: ScanApi>(
+    pool: &sqlx::PgPool,
+    scan_service: &S,
+    client: &RecGovClient,
+    user_id: &uuid::Uuid,
+    request: &CreateScanRequest,
+) -> Result<HttpResponse, ScanError> {
+    if request.provider == Provider::RecreationGov {
+        check_campground_rules(pool, client, scan_service, request).await?;
+    }
+    let created = scan_service.create_scan(user_id, request).await?;
+
+    Ok(HttpResponse::Created().json(created_scan_response(created, &request.campground_name)))
+}
+
+/// Checks a new scan against the campground's (cached) RIDB details.
+async fn check_campground_rules<S: ScanApi>(
+    pool: &sqlx::PgPool,
+    client: &RecGovClient,
+    scan_service: &S,
+    request: &CreateScanRequest,
+) -> Result<(), ScanError> {
+    let facility = match campground_details(pool, client, &request.campground_id).await {
+        Ok(Some(details)) => details.facility,
+        Ok(None) => return Err(ScanError::CampgroundNotFound),
+        // Don't block scans on RIDB outages
+        Err(e) => {
+            log::warn!(
+                "⚠️ Couldn't check the rules of campground {}: {}",
+                request.campground_id,
+                e
+            );
+            return Ok(());
+        }
+    };
+
+    if facility.is_first_come_first_served() {
+        scan_service
+            .mark_not_reservable(&request.campground_id, &request.campground_name)
+            .await?;
+        return Err(ScanError::NotReservable(request.campground_name.clone()));
+    }
+
+    check_stay_limit(&facility, request)
+}
+
+/// Checks the stay against the longest one the campground allows, if RIDB says.
+fn check_stay_limit(facility: &Facility, request: &CreateScanRequest) -> Result<(), ScanError> {
+    let nights = (request.check_out_date - request.check_in_date).num_days();
+    if let Some(max_nights) = facility.max_stay_nights()
+        && nights > max_nights
+    {
+        return Err(ScanError::StayTooLong {
+            campground: request.campground_name.clone(),
+            nights,
+            max_nights,
+        });
+    }
+
+    Ok(())
+}
+
+/// Response to a newly created scan
+fn created_scan_response(created: CreatedScan, campground_name: &str) -> CreateScanResponse {
+    let CreatedScan { scan, warnings } = created;
 
-    // Convert to response format
-    let response = CreateScanResponse {
+    CreateScanResponse {
         id: scan.id,
         campground_id: scan.campground_id,
-        campground_name: request.campground_name.clone(),
+        campground_name: campground_name.to_string(),
         check_in_date: scan.check_in_date,
         check_out_date: scan.check_out_date,
         nights: scan.nights,
         status: scan.status,
         notification_sent: scan.notification_sent,
         created_at: scan.created_at,
-    };
-
-    Ok(HttpResponse::Created().json(response))
+        organization_id: scan.organization_id,
+        facility_type: scan.facility_type,
+        provider: scan.provider,
+        flexibility_days: scan.flexibility_days,
+        max_price: scan.max_price,
+        site_filters: scan.site_filters,
+        quick_book: scan.quick_book,
+        warnings,
+    }
 }
 
 /// Gets all scans for the authenticated user
 pub async fn get_user_scans(
-    pool: web::Data<sqlx::PgPool>,
+    state: web::Data<AppState>,
     user: AuthenticatedUser,
 ) -> Result<HttpResponse, ScanError> {
-    let scan_service = ScanService::new(pool.get_ref().clone());
+    let scan_service = &state.scans;
     let scans = scan_service.get_user_scans(&user.0).await?;
+    let quota = scan_service.scan_quota(&user.0).await?;
 
     let response = ListScansResponse {
         total: scans.len() as i64,
         scans,
+        quota: Some(quota),
     };
 
     Ok(HttpResponse::Ok().json(response))
 }
 
+/// Downloads the authenticated user's scans, including shared ones, as CSV (default) or
+/// JSON. The file is streamed a page of scans at a time.
+pub async fn export_scans(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    query: web::Query<ScanExportQuery>,
+) -> Result<HttpResponse, ScanError> {
+    let format = query.format.unwrap_or_default();
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"scans.{}\"", format.extension()),
+        ))
+        .streaming(scan_export(pool.get_ref().clone(), user.0, format)))
+}
+
 /// Gets a specific scan by ID for the authenticated user
 pub async fn get_scan(
-    pool: web::Data<sqlx::PgPool>,
+    state: web::Data<AppState>,
     user: AuthenticatedUser,
     path: web::Path<uuid::Uuid>,
 ) -> Result<HttpResponse, ScanError> {
     let scan_id = path.into_inner();
-    let scan_service = ScanService::new(pool.get_ref().clone());
+    let scan_service = &state.scans;
     let scan = scan_service.get_user_scan(&user.0, &scan_id).await?;
 
     Ok(HttpResponse::Ok().json(scan))
 }
 
 /// Updates a scan's status
-pub async fn update_scan(
-    pool: web::Data<sqlx::PgPool>,
+pub async fn update_scan<A: AuthApi, S: ScanApi>(
+    state: web::Data<AppState<A, S>>,
     user: AuthenticatedUser,
     path: web::Path<uuid::Uuid>,
     request: web::Json<UpdateScanRequest>,
@@ -79,7 +186,7 @@ pub async fn update_scan(
         .map_err(|e| ScanError::Validation(format!("Validation error: {}", e)))?;
 
     let scan_id = path.into_inner();
-    let scan_service = ScanService::new(pool.get_ref().clone());
+    let scan_service = &state.scans;
     let updated_scan = scan_service
         .update_scan_status(&user.0, &scan_id, &request.status)
         .await?;
@@ -87,25 +194,506 @@ pub async fn update_scan(
     Ok(HttpResponse::Ok().json(updated_scan))
 }
 
-/// Deletes a scan
-pub async fn delete_scan(
+/// Changes a scan's dates, facility type, or flexibility. Alerts start over for the new
+/// stay and the campground is checked on the next polling cycle.
+pub async fn patch_scan(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+    request: web::Json<PatchScanRequest>,
+) -> Result<HttpResponse, ScanError> {
+    let scan_id = path.into_inner();
+    let scan_service = &state.scans;
+    let scan = scan_service
+        .modify_scan(&user.0, &scan_id, &request)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(scan))
+}
+
+/// Moves a scan that points at the wrong facility (e.g. a day-use area) to the intended
+/// campground. Without a `campground_id`, returns likely intended campgrounds instead.
+pub async fn remap_scan(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+    request: web::Json<RemapScanRequest>,
+) -> Result<HttpResponse, ScanError> {
+    let scan_id = path.into_inner();
+    let scan_service = &state.scans;
+
+    match &request.campground_id {
+        Some(campground_id) => {
+            let scan = scan_service
+                .remap_scan(
+                    &user.0,
+                    &scan_id,
+                    campground_id,
+                    request.campground_name.as_deref(),
+                )
+                .await?;
+
+            Ok(HttpResponse::Ok().json(scan))
+        }
+        None => {
+            let suggestions = scan_service
+                .suggest_remap_targets(&user.0, &scan_id)
+                .await?;
+
+            Ok(HttpResponse::Ok().json(suggestions))
+        }
+    }
+}
+
+/// Extends a scan that is about to expire (or has expired) by the configured maximum
+/// scan age, still ending once check-in passes
+pub async fn extend_scan(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, ScanError> {
+    let scan_id = path.into_inner();
+    let scan_service = &state.scans;
+    let scan = scan_service.extend_scan(&user.0, &scan_id).await?;
+
+    Ok(HttpResponse::Ok().json(scan))
+}
+
+/// Records what came of a scan: `booked` completes it, `gave_up` cancels it, and
+/// `missed` keeps it running
+pub async fn set_scan_outcome(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+    request: web::Json<ScanOutcomeRequest>,
+) -> Result<HttpResponse, ScanError> {
+    let scan_id = path.into_inner();
+    let scan_service = &state.scans;
+    let scan = scan_service
+        .set_outcome(&user.0, &scan_id, request.outcome)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(scan))
+}
+
+/// Gets a scan's activity timeline, oldest first: creation, each check and the open sites
+/// it saw, alerts, errors, and status changes
+pub async fn get_scan_events(
+    state: web::Data<AppState>,
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+    query: web::Query<ScanEventsQuery>,
+) -> Result<HttpResponse, ScanError> {
+    let scan_id = path.into_inner();
+    let scan_service = &state.scans;
+
+    // Only the owner and the organization the scan is shared with may see it
+    scan_service.get_user_scan(&user.0, &scan_id).await?;
+
+    let events = ScanEventLog::new(pool.get_ref().clone())
+        .recent(&scan_id, query.limit.unwrap_or(100))
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "scan_id": scan_id,
+        "events": events
+    })))
+}
+
+/// Creates a public read-only link to one of the user's own scans, or returns the
+/// existing one
+pub async fn share_scan(
     pool: web::Data<sqlx::PgPool>,
+    config: web::Data<AppConfig>,
     user: AuthenticatedUser,
     path: web::Path<uuid::Uuid>,
 ) -> Result<HttpResponse, ScanError> {
     let scan_id = path.into_inner();
-    let scan_service = ScanService::new(pool.get_ref().clone());
+    let link = ScanSharing::new(pool.get_ref().clone())
+        .share(&user.0, &scan_id, &config.base_url)
+        .await?;
+
+    log::info!(
+        "🔗 User {} shared scan {} as {}",
+        user.0,
+        scan_id,
+        link.slug
+    );
+    Ok(HttpResponse::Ok().json(link))
+}
+
+/// Removes a scan's public link
+pub async fn unshare_scan(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, ScanError> {
+    let scan_id = path.into_inner();
+
+    if !ScanSharing::new(pool.get_ref().clone())
+        .unshare(&user.0, &scan_id)
+        .await?
+    {
+        return Err(ScanError::NotFound);
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Shows a shared scan to anyone with its link: campground, stay, and status
+pub async fn get_shared_scan(
+    pool: web::Data<sqlx::PgPool>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ScanError> {
+    let scan = ScanSharing::new(pool.get_ref().clone())
+        .shared_scan(&path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(scan))
+}
+
+/// Public page of a share link (`/s/{slug}`)
+pub async fn shared_scan_link(
+    pool: web::Data<sqlx::PgPool>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ScanError> {
+    match ScanSharing::new(pool.get_ref().clone())
+        .shared_scan(&path.into_inner())
+        .await
+    {
+        Ok(scan) => Ok(HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .body(shared_scan_page(&scan))),
+        Err(ScanError::NotFound) => Ok(HttpResponse::NotFound()
+            .content_type("text/html; charset=utf-8")
+            .body(SHARED_SCAN_NOT_FOUND_HTML)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Copies a shared scan into the authenticated user's account
+pub async fn clone_shared_scan(
+    state: web::Data<AppState>,
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ScanError> {
+    let slug = path.into_inner();
+    let scan_service = &state.scans;
+    let sharing = ScanSharing::new(pool.get_ref().clone());
+
+    let shared = sharing.shared_scan(&slug).await?;
+    let created = sharing.clone_scan(scan_service, &user.0, &shared).await?;
+
+    log::info!(
+        "📋 User {} cloned shared scan {} as {}",
+        user.0,
+        slug,
+        created.scan.id
+    );
+    Ok(HttpResponse::Created().json(created_scan_response(created, &shared.campground_name)))
+}
+
+/// Page shown for a share link that doesn't exist or was removed
+const SHARED_SCAN_NOT_FOUND_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Scan not found - CampTracker</title>
+    <style>
+        body { font-family: Arial, sans-serif; text-align: center; padding: 50px; background: #f0f9ff; }
+        .container { max-width: 500px; margin: 0 auto; background: white; padding: 30px; border-radius: 10px; box-shadow: 0 4px 6px rgba(0,0,0,0.1); }
+        h1 { color: #2c3e50; }
+        .button { background: #4a6741; color: white; padding: 12px 24px; text-decoration: none; border-radius: 8px; display: inline-block; margin-top: 20px; }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>This scan isn't shared</h1>
+        <p>The link was removed by its owner, or the scan was deleted.</p>
+        <a href="/" class="button">Go to CampTracker</a>
+    </div>
+</body>
+</html>
+"#;
+
+/// Adds a friend's email address or phone number to one of the user's scans. They get a
+/// confirmation link and aren't alerted until they use it.
+pub async fn add_scan_recipient(
+    state: web::Data<AppState>,
+    pool: web::Data<sqlx::PgPool>,
+    sms_budget: web::Data<SmsBudget>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+    request: web::Json<AddScanRecipientRequest>,
+) -> Result<HttpResponse, ScanError> {
+    request
+        .validate()
+        .map_err(|e| ScanError::Validation(format!("Validation error: {}", e)))?;
+
+    let (channel, address) = match (&request.email, &request.phone) {
+        (Some(email), None) => ("email", email.trim().to_lowercase()),
+        (None, Some(phone)) if validate_phone_number(phone) => ("sms", format_phone_number(phone)),
+        (None, Some(_)) => {
+            return Err(ScanError::Validation("Invalid US phone number".to_string()));
+        }
+        _ => {
+            return Err(ScanError::Validation(
+                "Give either an email or a phone number".to_string(),
+            ));
+        }
+    };
+
+    let scan_id = path.into_inner();
+    let recipients = ScanRecipientService::new(pool.get_ref().clone());
+    let invitation = recipients.add(&user.0, &scan_id, channel, &address).await?;
+    let recipient = &invitation.recipient;
+
+    // Removing and re-adding an address mustn't resend its invitation without limit
+    let verification_channel = match channel {
+        "sms" => VerificationChannel::Sms,
+        _ => VerificationChannel::Email,
+    };
+    if let Err(e) = VerificationThrottle::new(pool.get_ref().clone())
+        .acquire_invitation(&scan_id, verification_channel, &address)
+        .await
+    {
+        recipients.remove(&user.0, &scan_id, &recipient.id).await?;
+        return Err(match e {
+            NotificationError::TooManySends { retry_after_secs } => {
+                ScanError::RateLimited(retry_after_secs)
+            }
+            NotificationError::Database(e) => ScanError::Database(e),
+            other => ScanError::Validation(other.to_string()),
+        });
+    }
+
+    let sent = match channel {
+        "sms" => state
+            .notifications
+            .send_scan_recipient_invitation_sms(
+                &sms_budget,
+                &user.0,
+                &address,
+                &invitation.owner_name,
+                &invitation.campground_name,
+                &invitation.dates(),
+                &recipient.token,
+                invitation.locale,
+            )
+            .await
+            .map(|allowance| allowance.is_allowed()),
+        _ => state
+            .notifications
+            .send_scan_recipient_invitation(
+                &address,
+                &invitation.owner_name,
+                &invitation.campground_name,
+                &invitation.dates(),
+                &recipient.token,
+                invitation.locale,
+            )
+            .await
+            .map(|()| true),
+    };
+
+    // Without the invitation they could never confirm; take them off so the owner can
+    // try again
+    if !matches!(sent, Ok(true)) {
+        if let Err(e) = &sent {
+            log::error!("❌ Failed to invite a recipient to scan {}: {}", scan_id, e);
+        }
+        recipients.remove(&user.0, &scan_id, &recipient.id).await?;
+        return Err(ScanError::Validation(match channel {
+            "sms" => "Couldn't text the invitation; your SMS budget may be used up".to_string(),
+            _ => "Couldn't email the invitation, try again later".to_string(),
+        }));
+    }
+
+    log::info!(
+        "👥 User {} invited a {} recipient to scan {}",
+        user.0,
+        channel,
+        scan_id
+    );
+    Ok(HttpResponse::Created().json(recipient))
+}
+
+/// Lists the extra recipients of one of the user's scans and whether they confirmed
+pub async fn list_scan_recipients(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, ScanError> {
+    let scan_id = path.into_inner();
+    let recipients = ScanRecipientService::new(pool.get_ref().clone())
+        .list(&user.0, &scan_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "scan_id": scan_id,
+        "recipients": recipients
+    })))
+}
+
+/// Stops alerting an extra recipient of one of the user's scans
+pub async fn remove_scan_recipient(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<HttpResponse, ScanError> {
+    let (scan_id, recipient_id) = path.into_inner();
+    ScanRecipientService::new(pool.get_ref().clone())
+        .remove(&user.0, &scan_id, &recipient_id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Confirmation link sent to a new scan recipient: starts their alerts
+pub async fn confirm_scan_recipient(
+    pool: web::Data<sqlx::PgPool>,
+    query: web::Query<ScanRecipientLinkQuery>,
+) -> Result<HttpResponse, ScanError> {
+    respond_to_scan_invitation(&pool, &query.token, true).await
+}
+
+/// Decline link sent to a new scan recipient, also the (one-click) unsubscribe link of
+/// their alerts: stops their alerts for the scan
+pub async fn decline_scan_recipient(
+    pool: web::Data<sqlx::PgPool>,
+    query: web::Query<ScanRecipientLinkQuery>,
+) -> Result<HttpResponse, ScanError> {
+    respond_to_scan_invitation(&pool, &query.token, false).await
+}
+
+/// Records a recipient's answer and shows them a page saying what happens next
+async fn respond_to_scan_invitation(
+    pool: &sqlx::PgPool,
+    token: &str,
+    confirmed: bool,
+) -> Result<HttpResponse, ScanError> {
+    let Some(recipient) = ScanRecipientService::new(pool.clone())
+        .respond(token, confirmed)
+        .await?
+    else {
+        return Ok(HttpResponse::NotFound()
+            .content_type("text/html; charset=utf-8")
+            .body(SCAN_RECIPIENT_LINK_INVALID_HTML));
+    };
+
+    log::info!(
+        "👥 Recipient {} of scan {} {}",
+        recipient.id,
+        recipient.scan_id,
+        recipient.status
+    );
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(if confirmed {
+            SCAN_RECIPIENT_CONFIRMED_HTML
+        } else {
+            SCAN_RECIPIENT_DECLINED_HTML
+        }))
+}
+
+/// Page shown once a scan recipient confirms
+const SCAN_RECIPIENT_CONFIRMED_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Alerts on - CampTracker</title>
+    <style>
+        body { font-family: Arial, sans-serif; text-align: center; padding: 50px; background: #f0f9ff; }
+        .container { max-width: 500px; margin: 0 auto; background: white; padding: 30px; border-radius: 10px; box-shadow: 0 4px 6px rgba(0,0,0,0.1); }
+        h1 { color: #2c3e50; }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>You're in</h1>
+        <p>You'll get an alert when sites open up. Every alert has a link to stop them.</p>
+    </div>
+</body>
+</html>
+"#;
+
+/// Page shown once a scan recipient declines or stops their alerts
+const SCAN_RECIPIENT_DECLINED_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>No more alerts - CampTracker</title>
+    <style>
+        body { font-family: Arial, sans-serif; text-align: center; padding: 50px; background: #f0f9ff; }
+        .container { max-width: 500px; margin: 0 auto; background: white; padding: 30px; border-radius: 10px; box-shadow: 0 4px 6px rgba(0,0,0,0.1); }
+        h1 { color: #2c3e50; }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>No more alerts</h1>
+        <p>You won't hear from this scan again.</p>
+    </div>
+</body>
+</html>
+"#;
+
+/// Page shown for a recipient link that doesn't exist, e.g. after the owner removed them
+const SCAN_RECIPIENT_LINK_INVALID_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <title>Link expired - CampTracker</title>
+    <style>
+        body { font-family: Arial, sans-serif; text-align: center; padding: 50px; background: #f0f9ff; }
+        .container { max-width: 500px; margin: 0 auto; background: white; padding: 30px; border-radius: 10px; box-shadow: 0 4px 6px rgba(0,0,0,0.1); }
+        h1 { color: #2c3e50; }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>This link no longer works</h1>
+        <p>The scan was deleted or you were removed from its alerts.</p>
+    </div>
+</body>
+</html>
+"#;
+
+/// Deletes a scan; it can be restored for 30 days
+pub async fn delete_scan(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, ScanError> {
+    let scan_id = path.into_inner();
+    let scan_service = &state.scans;
     scan_service.delete_scan(&user.0, &scan_id).await?;
 
     Ok(HttpResponse::NoContent().finish())
 }
 
+/// Restores a scan deleted within the last 30 days
+pub async fn restore_scan(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, ScanError> {
+    let scan_id = path.into_inner();
+    let scan_service = &state.scans;
+    let scan = scan_service.restore_scan(&user.0, &scan_id).await?;
+
+    Ok(HttpResponse::Ok().json(scan))
+}
+
 /// Gets active scans for the authenticated user (for display on profile page)
 pub async fn get_active_scans(
-    pool: web::Data<sqlx::PgPool>,
+    state: web::Data<AppState>,
     user: AuthenticatedUser,
 ) -> Result<HttpResponse, ScanError> {
-    let scan_service = ScanService::new(pool.get_ref().clone());
+    let scan_service = &state.scans;
     let all_scans = scan_service.get_user_scans(&user.0).await?;
 
     // Filter only active scans
@@ -114,10 +702,425 @@ pub async fn get_active_scans(
         .filter(|scan| scan.status == "active")
         .collect();
 
+    let quota = scan_service.scan_quota(&user.0).await?;
+
     let response = ListScansResponse {
         total: active_scans.len() as i64,
         scans: active_scans,
+        quota: Some(quota),
     };
 
     Ok(HttpResponse::Ok().json(response))
 }
+
+/// Gets the link of the authenticated user's scan calendar feed, for subscribing from a
+/// calendar app
+pub async fn get_calendar_feed_url(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ScanError> {
+    let version = state
+        .scans
+        .calendar_feed_version(&user.0)
+        .await?
+        .ok_or(ScanError::NotFound)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "url": state.notifications.calendar_feed().url(&user.0, version)
+    })))
+}
+
+/// Replaces the authenticated user's calendar feed link with a new one, so calendars
+/// subscribed to the old link (e.g. one shared by mistake) stop receiving the feed
+pub async fn reset_calendar_feed_url(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ScanError> {
+    let version = state.scans.reset_calendar_feed(&user.0).await?;
+    log::info!("🔁 Reset the calendar feed link of user {}", user.0);
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "url": state.notifications.calendar_feed().url(&user.0, version)
+    })))
+}
+
+/// Serves a user's scans as an iCalendar feed. Calendar apps can't log in, so the signed
+/// token in the link identifies the user; tokens of links the user has since reset are
+/// rejected.
+pub async fn get_scan_calendar(
+    state: web::Data<AppState>,
+    config: web::Data<AppConfig>,
+    query: web::Query<CalendarFeedQuery>,
+) -> Result<HttpResponse, ScanError> {
+    let Some((user_id, version)) = state.notifications.calendar_feed().verify(&query.token) else {
+        log::warn!("⚠️ Rejected an invalid calendar feed token");
+        return Err(ScanError::Unauthorized);
+    };
+    if state.scans.calendar_feed_version(&user_id).await? != Some(version) {
+        log::warn!(
+            "⚠️ Rejected a revoked calendar feed token of user {}",
+            user_id
+        );
+        return Err(ScanError::Unauthorized);
+    }
+
+    let scans = state.scans.get_user_scans(&user_id).await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .insert_header(("Cache-Control", "private, max-age=900"))
+        .body(scan_calendar(&scans, &config.base_url)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_state, test_user, unused_pool, unused_two_factor};
+    use actix_web::http::StatusCode;
+    use actix_web::{App, test};
+    use auth_services::middleware::AuthMiddleware;
+    use auth_services::service::AuthService;
+    use campground_scan::{ScanHealth, UserScan};
+    use chrono::{Duration, NaiveDate, Utc};
+    use rec_gov::{ApiCallLedger, SiteFilters};
+    use std::sync::Mutex;
+    use uuid::Uuid;
+
+    /// Scans held in memory: one existing scan, and the users scans were created for
+    struct MockScans {
+        owner: Uuid,
+        scan_id: Uuid,
+        quota_reached: bool,
+        created_for: Mutex<Vec<Uuid>>,
+    }
+
+    impl MockScans {
+        fn new() -> Self {
+            Self {
+                owner: Uuid::new_v4(),
+                scan_id: Uuid::new_v4(),
+                quota_reached: false,
+                created_for: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl ScanApi for MockScans {
+        async fn create_scan(
+            &self,
+            user_id: &Uuid,
+            request: &CreateScanRequest,
+        ) -> Result<CreatedScan, ScanError> {
+            if self.quota_reached {
+                return Err(ScanError::QuotaExceeded {
+                    plan: "free".to_string(),
+                    limit: 2,
+                });
+            }
+            self.created_for.lock().unwrap().push(*user_id);
+
+            Ok(CreatedScan {
+                scan: UserScan {
+                    id: Uuid::new_v4(),
+                    user_id: *user_id,
+                    campground_id: request.campground_id.clone(),
+                    check_in_date: request.check_in_date,
+                    check_out_date: request.check_out_date,
+                    nights: (request.check_out_date - request.check_in_date).num_days() as i32,
+                    status: "active".to_string(),
+                    notification_sent: false,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                    expires_at: None,
+                    organization_id: None,
+                    facility_type: "campsite".to_string(),
+                    provider: "reserve_california".to_string(),
+                    flexibility_days: 0,
+                    max_price: None,
+                    site_filters: SiteFilters::default(),
+                    quick_book: false,
+                },
+                warnings: Vec::new(),
+            })
+        }
+
+        async fn update_scan_status(
+            &self,
+            user_id: &Uuid,
+            scan_id: &Uuid,
+            new_status: &str,
+        ) -> Result<UserScanWithCampground, ScanError> {
+            if *scan_id != self.scan_id {
+                return Err(ScanError::NotFound);
+            }
+            if *user_id != self.owner {
+                return Err(ScanError::Unauthorized);
+            }
+
+            let check_in_date = check_in();
+            Ok(UserScanWithCampground {
+                id: self.scan_id,
+                campground_id: "232447".to_string(),
+                campground_name: "Upper Pines".to_string(),
+                check_in_date,
+                check_out_date: check_in_date + Duration::days(2),
+                nights: 2,
+                status: new_status.to_string(),
+                notification_sent: false,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                expires_at: None,
+                organization_id: None,
+                facility_type: "campsite".to_string(),
+                provider: "recreation_gov".to_string(),
+                flexibility_days: 0,
+                max_price: None,
+                site_filters: SiteFilters::default(),
+                quick_book: false,
+                outcome: None,
+                outcome_at: None,
+                trip_id: None,
+                last_polled: None,
+                next_poll_at: None,
+                last_error: None,
+                health: ScanHealth::Healthy,
+                seasonal_closure: None,
+            })
+        }
+
+        async fn mark_not_reservable(
+            &self,
+            _campground_id: &str,
+            _campground_name: &str,
+        ) -> Result<(), ScanError> {
+            Ok(())
+        }
+    }
+
+    fn check_in() -> NaiveDate {
+        Utc::now().date_naive() + Duration::days(30)
+    }
+
+    fn state(scans: MockScans) -> web::Data<AppState<AuthService, MockScans>> {
+        test_state(AuthService::new(unused_pool()), scans, unused_two_factor())
+    }
+
+    /// Access token of a user with the given ID
+    fn token(state: &AppState<AuthService, MockScans>, user_id: Uuid) -> String {
+        let mut user = test_user("camper@example.com");
+        user.id = user_id;
+        state.jwt.generate_access_token(&user).unwrap()
+    }
+
+    /// Sends `request` to the scan routes and returns the status and JSON body
+    async fn call(
+        state: web::Data<AppState<AuthService, MockScans>>,
+        request: test::TestRequest,
+    ) -> (StatusCode, serde_json::Value) {
+        let pool = unused_pool();
+        let config = AppConfig::default();
+        let app = test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .app_data(web::Data::new(pool.clone()))
+                .app_data(web::Data::new(RecGovClient::new(
+                    &config,
+                    ApiCallLedger::new(pool),
+                )))
+                .service(
+                    web::scope("/api/scans")
+                        .wrap(AuthMiddleware::new(state.jwt.clone()))
+                        .route("", web::post().to(create_scan::<AuthService, MockScans>))
+                        .route(
+                            "/{scan_id}",
+                            web::put().to(update_scan::<AuthService, MockScans>),
+                        ),
+                ),
+        )
+        .await;
+
+        let response = test::call_service(&app, request.to_request()).await;
+        let status = response.status();
+        let body = test::read_body(response).await;
+
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    /// A ReserveCalifornia scan, so no RIDB rules are checked
+    fn create_body(campground_id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "campground_id": campground_id,
+            "campground_name": "Doheny State Beach",
+            "check_in_date": check_in(),
+            "check_out_date": check_in() + Duration::days(2),
+            "provider": "reserve_california"
+        })
+    }
+
+    #[actix_web::test]
+    async fn create_scan_requires_a_valid_token() {
+        let state = state(MockScans::new());
+
+        let (status, body) = call(
+            state.clone(),
+            test::TestRequest::post()
+                .uri("/api/scans")
+                .set_json(create_body("rc-718")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "missing_token");
+
+        let (status, body) = call(
+            state.clone(),
+            test::TestRequest::post()
+                .uri("/api/scans")
+                .insert_header(("Authorization", "Bearer not-a-token"))
+                .set_json(create_body("rc-718")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "invalid_token");
+
+        assert!(state.scans.created_for.lock().unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn create_scan_rejects_invalid_requests() {
+        let state = state(MockScans::new());
+        let token = token(&state, Uuid::new_v4());
+
+        let (status, body) = call(
+            state.clone(),
+            test::TestRequest::post()
+                .uri("/api/scans")
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .set_json(create_body("")),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "validation_error");
+        assert!(state.scans.created_for.lock().unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn create_scan_refuses_scans_over_the_plan_quota() {
+        let state = state(MockScans {
+            quota_reached: true,
+            ..MockScans::new()
+        });
+        let token = token(&state, Uuid::new_v4());
+
+        let (status, body) = call(
+            state,
+            test::TestRequest::post()
+                .uri("/api/scans")
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .set_json(create_body("rc-718")),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "quota_exceeded");
+        assert_eq!(body["limit"], 2);
+    }
+
+    #[actix_web::test]
+    async fn create_scan_creates_it_for_the_token_user() {
+        let state = state(MockScans::new());
+        let user_id = Uuid::new_v4();
+        let token = token(&state, user_id);
+
+        let (status, body) = call(
+            state.clone(),
+            test::TestRequest::post()
+                .uri("/api/scans")
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .set_json(create_body("rc-718")),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["campground_name"], "Doheny State Beach");
+        assert_eq!(body["nights"], 2);
+        assert_eq!(*state.scans.created_for.lock().unwrap(), vec![user_id]);
+    }
+
+    #[actix_web::test]
+    async fn update_scan_rejects_unknown_statuses() {
+        let scans = MockScans::new();
+        let (owner, scan_id) = (scans.owner, scans.scan_id);
+        let state = state(scans);
+        let token = token(&state, owner);
+
+        let (status, body) = call(
+            state,
+            test::TestRequest::put()
+                .uri(&format!("/api/scans/{}", scan_id))
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .set_json(serde_json::json!({ "status": "deleted" })),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "validation_error");
+    }
+
+    #[actix_web::test]
+    async fn update_scan_only_changes_the_owners_scan() {
+        let scans = MockScans::new();
+        let (owner, scan_id) = (scans.owner, scans.scan_id);
+        let state = state(scans);
+
+        let update = |user_id: Uuid, scan_id: Uuid| {
+            test::TestRequest::put()
+                .uri(&format!("/api/scans/{}", scan_id))
+                .insert_header((
+                    "Authorization",
+                    format!("Bearer {}", token(&state, user_id)),
+                ))
+                .set_json(serde_json::json!({ "status": "paused" }))
+        };
+
+        let (status, body) = call(state.clone(), update(Uuid::new_v4(), scan_id)).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["error"], "unauthorized");
+
+        let (status, body) = call(state.clone(), update(owner, Uuid::new_v4())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "scan_not_found");
+
+        let (status, body) = call(state.clone(), update(owner, scan_id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "paused");
+    }
+
+    #[actix_web::test]
+    async fn stays_may_last_up_to_the_campground_limit() {
+        let facility = Facility {
+            stay_limit: Some("14 days".to_string()),
+            ..Default::default()
+        };
+
+        for (nights, allowed) in [(1, true), (13, true), (14, true), (15, false), (30, false)] {
+            let mut body = create_body("232447");
+            body["check_out_date"] = serde_json::json!(check_in() + Duration::days(nights));
+            let request: CreateScanRequest = serde_json::from_value(body).unwrap();
+
+            let result = check_stay_limit(&facility, &request);
+            assert_eq!(result.is_ok(), allowed, "{} nights", nights);
+            if !allowed {
+                assert!(matches!(
+                    result,
+                    Err(ScanError::StayTooLong { max_nights: 14, .. })
+                ));
+            }
+        }
+
+        let mut body = create_body("232447");
+        body["check_out_date"] = serde_json::json!(check_in() + Duration::days(60));
+        let request: CreateScanRequest = serde_json::from_value(body).unwrap();
+        assert!(check_stay_limit(&Facility::default(), &request).is_ok());
+    }
+}
@@ -0,0 +1,75 @@
+use actix_web::web;
+use app_config::AppConfig;
+use auth_services::auth_api::{AuthApi, TwoFactorApi};
+use auth_services::jwt::JwtService;
+use auth_services::two_factor::{TwoFactorCipher, TwoFactorService};
+use auth_services::types::User;
+use campground_scan::{PermitScanService, ScanApi};
+use chrono::Utc;
+use i18n::Locale;
+use notification_services::{NotificationService, NotificationTemplates};
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::AppState;
+
+/// Pool of a database that never answers, for handler data the tests don't exercise
+pub(crate) fn unused_pool() -> PgPool {
+    PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(50))
+        .connect_lazy("postgres://localhost:1/unused")
+        .unwrap()
+}
+
+/// Two-factor service backed by [`unused_pool`], for handlers that never reach it
+pub(crate) fn unused_two_factor() -> TwoFactorService {
+    TwoFactorService::new(
+        unused_pool(),
+        TwoFactorCipher::new(AppConfig::default().two_factor_key_material()),
+    )
+}
+
+/// State with the given account, scan, and two-factor services, HS256 tokens, and
+/// sandbox notifications
+pub(crate) fn test_state<A: AuthApi, S: ScanApi, T: TwoFactorApi>(
+    auth: A,
+    scans: S,
+    two_factor: T,
+) -> web::Data<AppState<A, S, T>> {
+    let notifications = NotificationService::sandbox(
+        &AppConfig::default(),
+        NotificationTemplates::load(None).unwrap(),
+        unused_pool(),
+    );
+
+    web::Data::new(AppState::new(
+        auth,
+        two_factor,
+        JwtService::new("handler-test-secret"),
+        scans,
+        PermitScanService::new(unused_pool()),
+        notifications,
+    ))
+}
+
+/// An active, unverified user with the given email
+pub(crate) fn test_user(email: &str) -> User {
+    User {
+        id: Uuid::new_v4(),
+        email: email.to_string(),
+        name: "Test Camper".to_string(),
+        phone: Some("5555550123".to_string()),
+        password_hash: String::new(),
+        role: "user".to_string(),
+        email_verified: false,
+        phone_verified: false,
+        notification_preferences: serde_json::json!({ "email": true, "sms": false }),
+        timezone: "America/Los_Angeles".to_string(),
+        locale: Locale::default(),
+        is_active: true,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
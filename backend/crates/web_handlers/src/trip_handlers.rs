@@ -0,0 +1,88 @@
+use actix_web::{HttpResponse, Result, web};
+use validator::Validate;
+
+use auth_services::middleware::AuthenticatedUser;
+use campground_scan::{CreateTripRequest, ListTripsResponse, ScanError, TripService};
+
+/// Creates a trip for the authenticated user
+pub async fn create_trip(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    request: web::Json<CreateTripRequest>,
+) -> Result<HttpResponse, ScanError> {
+    request
+        .validate()
+        .map_err(|e| ScanError::Validation(format!("Validation error: {}", e)))?;
+
+    let trip = TripService::new(pool.get_ref().clone())
+        .create(&user.0, &request)
+        .await?;
+
+    Ok(HttpResponse::Created().json(trip))
+}
+
+/// Lists the user's trips with the aggregate status of their scans
+pub async fn list_trips(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, ScanError> {
+    let trips = TripService::new(pool.get_ref().clone())
+        .list(&user.0)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(ListTripsResponse { trips }))
+}
+
+/// Gets a trip, the aggregate status of its scans, and the scans
+pub async fn get_trip(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, ScanError> {
+    let trip = TripService::new(pool.get_ref().clone())
+        .get(&user.0, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(trip))
+}
+
+/// Deletes a trip; its scans keep running on their own
+pub async fn delete_trip(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    path: web::Path<uuid::Uuid>,
+) -> Result<HttpResponse, ScanError> {
+    TripService::new(pool.get_ref().clone())
+        .delete(&user.0, &path.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Adds one of the user's scans to a trip
+pub async fn add_trip_scan(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<HttpResponse, ScanError> {
+    let (trip_id, scan_id) = path.into_inner();
+    let trip = TripService::new(pool.get_ref().clone())
+        .add_scan(&user.0, &trip_id, &scan_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(trip))
+}
+
+/// Takes a scan out of a trip
+pub async fn remove_trip_scan(
+    pool: web::Data<sqlx::PgPool>,
+    user: AuthenticatedUser,
+    path: web::Path<(uuid::Uuid, uuid::Uuid)>,
+) -> Result<HttpResponse, ScanError> {
+    let (trip_id, scan_id) = path.into_inner();
+    let trip = TripService::new(pool.get_ref().clone())
+        .remove_scan(&user.0, &trip_id, &scan_id)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(trip))
+}
@@ -0,0 +1,61 @@
+use actix_web::{HttpResponse, Result, web};
+use validator::Validate;
+
+use auth_services::middleware::AuthenticatedUser;
+use auth_services::types::*;
+
+use crate::AppState;
+
+/// Starts enrolling an authenticator app: returns a new secret, its `otpauth://` URL, and
+/// a QR code of it. Two-factor authentication isn't on until a code is confirmed.
+pub async fn setup_two_factor(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, AuthError> {
+    let profile = state
+        .auth
+        .get_user_by_id(&user.0)
+        .await?
+        .ok_or(AuthError::UserNotFound)?;
+
+    let setup = state
+        .two_factor
+        .begin_setup(&user.0, &profile.email)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(setup))
+}
+
+/// Confirms the authenticator with one of its codes and turns two-factor authentication
+/// on, returning the recovery codes (shown only this once)
+pub async fn verify_two_factor(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    request: web::Json<TwoFactorCodeRequest>,
+) -> Result<HttpResponse, AuthError> {
+    request
+        .validate()
+        .map_err(|e| AuthError::Validation(format!("Validation error: {}", e)))?;
+
+    let recovery_codes = state.two_factor.enable(&user.0, &request.code).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "enabled": true,
+        "recovery_codes": recovery_codes
+    })))
+}
+
+/// Turns two-factor authentication off after checking an authenticator or recovery code
+pub async fn disable_two_factor(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    request: web::Json<TwoFactorCodeRequest>,
+) -> Result<HttpResponse, AuthError> {
+    request
+        .validate()
+        .map_err(|e| AuthError::Validation(format!("Validation error: {}", e)))?;
+
+    state.two_factor.disable(&user.0, &request.code).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "enabled": false })))
+}
@@ -0,0 +1,72 @@
+use actix_web::{HttpResponse, web};
+use notification_services::service::NotificationService;
+use notification_services::types::*;
+use sqlx::PgPool;
+
+use crate::AppState;
+
+/// Unsubscribe link from a notification email: turns the notifications off and shows a
+/// confirmation page
+pub async fn unsubscribe(
+    state: web::Data<AppState>,
+    pool: web::Data<PgPool>,
+    query: web::Query<UnsubscribeQuery>,
+) -> HttpResponse {
+    match apply_unsubscribe(&pool, &state.notifications, &query.token).await {
+        Some(true) => HttpResponse::Ok()
+            .content_type("text/html")
+            .body(UNSUBSCRIBE_SUCCESS_HTML),
+        Some(false) => HttpResponse::BadRequest()
+            .content_type("text/html")
+            .body(UNSUBSCRIBE_ERROR_HTML),
+        None => HttpResponse::InternalServerError()
+            .content_type("text/html")
+            .body(UNSUBSCRIBE_ERROR_HTML),
+    }
+}
+
+/// One-click unsubscribe (RFC 8058): mail clients POST `List-Unsubscribe=One-Click` to
+/// the `List-Unsubscribe` URL without showing it to the user
+pub async fn unsubscribe_one_click(
+    state: web::Data<AppState>,
+    pool: web::Data<PgPool>,
+    query: web::Query<UnsubscribeQuery>,
+) -> HttpResponse {
+    match apply_unsubscribe(&pool, &state.notifications, &query.token).await {
+        Some(true) => HttpResponse::Ok().finish(),
+        Some(false) => HttpResponse::BadRequest().finish(),
+        None => HttpResponse::InternalServerError().finish(),
+    }
+}
+
+/// Verifies the token and applies the unsubscribe. Returns whether the token was valid,
+/// or `None` when it couldn't be applied.
+async fn apply_unsubscribe(
+    pool: &PgPool,
+    notification_service: &NotificationService,
+    token: &str,
+) -> Option<bool> {
+    let Some(unsubscribe) = notification_service.unsubscribe_links().verify(token) else {
+        log::warn!("⚠️ Rejected an invalid unsubscribe token");
+        return Some(false);
+    };
+
+    match unsubscribe.apply(pool).await {
+        Ok(()) => {
+            log::info!(
+                "🔕 User {} unsubscribed from {:?}",
+                unsubscribe.user_id,
+                unsubscribe.target
+            );
+            Some(true)
+        }
+        Err(e) => {
+            log::error!(
+                "❌ Failed to unsubscribe user {}: {}",
+                unsubscribe.user_id,
+                e
+            );
+            None
+        }
+    }
+}
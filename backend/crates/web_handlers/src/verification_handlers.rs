@@ -1,37 +1,55 @@
 use actix_web::{HttpResponse, Result, web};
 
 use auth_services::middleware::AuthenticatedUser;
-use auth_services::service::AuthService;
 use auth_services::types::*;
 use notification_services::service::*;
 use notification_services::types::*;
+use notification_services::verification::VerificationStore;
+use notification_services::{SmsBudget, VerificationThrottle};
 use sqlx::PgPool;
 
+use crate::AppState;
+
 /// Send email verification link
 pub async fn send_email_verification_link(
+    state: web::Data<AppState>,
     pool: web::Data<PgPool>,
-    notification_service: web::Data<NotificationService>,
     verification_store: web::Data<VerificationStore>,
     user: AuthenticatedUser,
 ) -> Result<HttpResponse, AuthError> {
-    let auth_service = AuthService::new(pool.get_ref().clone());
+    let auth_service = &state.auth;
     let user_data = auth_service
         .get_user_by_id(&user.0)
         .await?
         .ok_or(AuthError::UserNotFound)?;
 
+    VerificationThrottle::new(pool.get_ref().clone())
+        .acquire(&user.0, VerificationChannel::Email)
+        .await
+        .map_err(verification_error)?;
+
     let verification_token = NotificationService::generate_verification_token(); // 32-char token
-    let key = format!("email_token_{}_{}", user.0, user_data.email); // Updated key format
 
-    store_verification_code(&verification_store, &key, &verification_token, 1440); // 24 hours
+    verification_store
+        .store_token(
+            &user.0,
+            VerificationChannel::Email,
+            &user_data.email,
+            &verification_token,
+            1440, // 24 hours
+        )
+        .await
+        .map_err(verification_error)?;
 
-    notification_service
+    state
+        .notifications
         .send_email_verification_link(
             // Updated function name
             &user.0,
             &user_data.email,
             &user_data.name,
             &verification_token, // Token instead of code
+            user_data.locale,
         )
         .await
         .map_err(|e| AuthError::Validation(format!("Failed to send email: {}", e)))?;
@@ -43,40 +61,18 @@ pub async fn send_email_verification_link(
 
 /// Verify email with token (from email link)
 pub async fn verify_email_with_token(
-    pool: web::Data<PgPool>,
+    state: web::Data<AppState>,
     verification_store: web::Data<VerificationStore>,
     query: web::Query<EmailVerificationQuery>,
 ) -> Result<HttpResponse, AuthError> {
-    let token = &query.token;
-
-    // Look for verification token in store
-    let store = verification_store.lock().unwrap();
-    let mut found_key = None;
-    let mut user_id = None;
-
-    for (key, verification) in store.iter() {
-        if key.starts_with("email_token_") && verification.code == *token {
-            if verification.expires_at > chrono::Utc::now() {
-                found_key = Some(key.clone());
-                // Extract user_id from key format: "email_token_{user_id}_{email}"
-                if let Some(id_part) = key.split('_').nth(2) {
-                    if let Ok(parsed_id) = uuid::Uuid::parse_str(id_part) {
-                        user_id = Some(parsed_id);
-                    }
-                }
-            }
-            break;
-        }
-    }
-
-    drop(store);
-
-    if let (Some(key), Some(uid)) = (found_key, user_id) {
-        // Remove the token
-        verification_store.lock().unwrap().remove(&key);
+    let user_id = verification_store
+        .consume_email_token(&query.token)
+        .await
+        .map_err(verification_error)?;
 
+    if let Some(uid) = user_id {
         // Update user verification status
-        let auth_service = AuthService::new(pool.get_ref().clone());
+        let auth_service = &state.auth;
         auth_service
             .update_user_verification(&uid, Some(true), None)
             .await?;
@@ -92,14 +88,58 @@ pub async fn verify_email_with_token(
     }
 }
 
+/// Confirm an email change with the token from the link sent to the new address, and
+/// let the previous address know about it
+pub async fn confirm_email_change(
+    state: web::Data<AppState>,
+    query: web::Query<EmailVerificationQuery>,
+) -> Result<HttpResponse, AuthError> {
+    let auth_service = &state.auth;
+
+    let change = match auth_service.confirm_email_change(&query.token).await {
+        Ok(Some(change)) => change,
+        Ok(None) | Err(AuthError::EmailExists) => {
+            return Ok(HttpResponse::BadRequest()
+                .content_type("text/html")
+                .body(EMAIL_CHANGE_ERROR_HTML));
+        }
+        Err(e) => return Err(e),
+    };
+
+    log::info!("✅ User {} changed their email", change.user_id);
+
+    // The change is done; a failed notice is only logged
+    if let Err(e) = state
+        .notifications
+        .send_email_changed_notice(
+            &change.old_email,
+            &change.name,
+            &change.new_email,
+            change.locale,
+        )
+        .await
+    {
+        log::error!(
+            "❌ Failed to send email change notice for user {}: {}",
+            change.user_id,
+            e
+        );
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/html")
+        .body(EMAIL_CHANGE_SUCCESS_HTML))
+}
+
 /// Send SMS verification code
 pub async fn send_sms_verification(
+    state: web::Data<AppState>,
     pool: web::Data<PgPool>,
-    notification_service: web::Data<NotificationService>,
     verification_store: web::Data<VerificationStore>,
+    sms_budget: web::Data<SmsBudget>,
     user: AuthenticatedUser,
 ) -> Result<HttpResponse, AuthError> {
-    let auth_service = AuthService::new(pool.get_ref().clone());
+    let auth_service = &state.auth;
     let user_data = auth_service
         .get_user_by_id(&user.0)
         .await?
@@ -108,13 +148,33 @@ pub async fn send_sms_verification(
     let phone = user_data
         .phone
         .ok_or(AuthError::Validation("No phone number on file".to_string()))?;
+    VerificationThrottle::new(pool.get_ref().clone())
+        .acquire(&user.0, VerificationChannel::Sms)
+        .await
+        .map_err(verification_error)?;
+
     let verification_code = NotificationService::generate_verification_code();
-    let key = format!("sms_{}_{}", user.0, phone);
 
-    store_verification_code(&verification_store, &key, &verification_code, 10); // 10 minutes
+    verification_store
+        .store_token(
+            &user.0,
+            VerificationChannel::Sms,
+            &phone,
+            &verification_code,
+            10, // 10 minutes
+        )
+        .await
+        .map_err(verification_error)?;
 
-    notification_service
-        .send_sms_verification(&user.0, &phone, &verification_code)
+    state
+        .notifications
+        .send_sms_verification(
+            &sms_budget,
+            &user.0,
+            &phone,
+            &verification_code,
+            user_data.locale,
+        )
         .await
         .map_err(|e| AuthError::Validation(format!("Failed to send SMS: {}", e)))?;
 
@@ -125,12 +185,12 @@ pub async fn send_sms_verification(
 
 /// Verify phone with code
 pub async fn verify_phone(
-    pool: web::Data<PgPool>,
+    state: web::Data<AppState>,
     verification_store: web::Data<VerificationStore>,
     user: AuthenticatedUser,
     request: web::Json<VerifyPhoneRequest>,
 ) -> Result<HttpResponse, AuthError> {
-    let auth_service = AuthService::new(pool.get_ref().clone());
+    let auth_service = &state.auth;
     let user_data = auth_service
         .get_user_by_id(&user.0)
         .await?
@@ -139,9 +199,11 @@ pub async fn verify_phone(
     let phone = user_data
         .phone
         .ok_or(AuthError::Validation("No phone number on file".to_string()))?;
-    let key = format!("sms_{}_{}", user.0, phone);
 
-    match verify_code(&verification_store, &key, &request.code) {
+    match verification_store
+        .verify_code(&user.0, VerificationChannel::Sms, &phone, &request.code)
+        .await
+    {
         Ok(true) => {
             // Update user phone verification status
             auth_service
@@ -155,6 +217,21 @@ pub async fn verify_phone(
         Ok(false) => Err(AuthError::Validation(
             "Invalid verification code".to_string(),
         )),
-        Err(err) => Err(AuthError::Validation(err)),
+        Err(err) => Err(verification_error(err)),
+    }
+}
+
+/// Maps verification storage errors onto the auth error surface, keeping storage
+/// failures as internal errors rather than validation messages.
+fn verification_error(err: NotificationError) -> AuthError {
+    match err {
+        NotificationError::VerificationNotFound
+        | NotificationError::VerificationExpired
+        | NotificationError::TooManyAttempts => AuthError::Validation(err.to_string()),
+        NotificationError::TooManySends { retry_after_secs } => {
+            AuthError::RateLimited(retry_after_secs)
+        }
+        NotificationError::Database(e) => AuthError::Database(e),
+        other => AuthError::Internal(other.to_string()),
     }
 }
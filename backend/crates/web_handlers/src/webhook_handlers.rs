@@ -0,0 +1,127 @@
+use actix_web::{HttpResponse, web};
+use app_config::AppConfig;
+use notification_services::types::WebhookQuery;
+use notification_services::{
+    DeliveryEvent, DeliveryTracker, NotificationError, SnsEnvelope, SnsSignatureVerifier,
+};
+use sqlx::PgPool;
+
+/// SES bounce, complaint, and delivery notifications, delivered through an SNS topic
+/// subscribed to `/api/webhooks/ses?token=...`
+pub async fn ses_webhook(
+    pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
+    verifier: web::Data<SnsSignatureVerifier>,
+    query: web::Query<WebhookQuery>,
+    body: String,
+) -> HttpResponse {
+    handle_delivery_webhook(&pool, &config, &verifier, &query, &body, |message| {
+        DeliveryEvent::from_ses(message)
+    })
+    .await
+}
+
+/// SNS SMS delivery status records, published to an SNS topic subscribed to
+/// `/api/webhooks/sns?token=...`
+pub async fn sns_webhook(
+    pool: web::Data<PgPool>,
+    config: web::Data<AppConfig>,
+    verifier: web::Data<SnsSignatureVerifier>,
+    query: web::Query<WebhookQuery>,
+    body: String,
+) -> HttpResponse {
+    handle_delivery_webhook(&pool, &config, &verifier, &query, &body, |message| {
+        DeliveryEvent::from_sms(message).map(Some)
+    })
+    .await
+}
+
+/// Checks the webhook token, the message's topic and its SNS signature, confirms new
+/// subscriptions, and applies the delivery event of a notification. The token alone
+/// isn't trusted, since it travels in the URL and so ends up in request logs, and
+/// subscriptions are only confirmed for the configured topics. Errors
+/// storing the event return `500` so SNS retries it.
+async fn handle_delivery_webhook(
+    pool: &PgPool,
+    config: &AppConfig,
+    verifier: &SnsSignatureVerifier,
+    query: &WebhookQuery,
+    body: &str,
+    parse: impl Fn(&str) -> Result<Option<DeliveryEvent>, NotificationError>,
+) -> HttpResponse {
+    let Some(expected) = config.delivery_webhook_token.as_deref() else {
+        return HttpResponse::NotFound().finish();
+    };
+    if !query
+        .token
+        .as_deref()
+        .is_some_and(|token| tokens_match(token, expected))
+    {
+        log::warn!("⚠️ Rejected a delivery webhook call with a wrong token");
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    let envelope: SnsEnvelope = match serde_json::from_str(body) {
+        Ok(envelope) => envelope,
+        Err(e) => {
+            log::warn!("⚠️ Invalid SNS message: {}", e);
+            return HttpResponse::BadRequest().finish();
+        }
+    };
+    if let Err(e) = verifier.verify(&envelope).await {
+        log::warn!("⚠️ Rejected a delivery webhook call: {}", e);
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    match envelope.kind.as_str() {
+        "SubscriptionConfirmation" => match envelope.confirm_subscription().await {
+            Ok(()) => HttpResponse::Ok().finish(),
+            Err(e) => {
+                log::error!("❌ {}", e);
+                HttpResponse::BadRequest().finish()
+            }
+        },
+        "Notification" => {
+            let event = match parse(&envelope.message) {
+                Ok(Some(event)) => event,
+                Ok(None) => return HttpResponse::Ok().finish(),
+                Err(e) => {
+                    log::warn!("⚠️ {}", e);
+                    return HttpResponse::BadRequest().finish();
+                }
+            };
+
+            match DeliveryTracker::new(pool.clone()).apply(&event).await {
+                Ok(update) => {
+                    if update.users_disabled > 0 {
+                        log::warn!(
+                            "📪 Turned off email alerts for {} users after {:?}",
+                            update.users_disabled,
+                            event
+                        );
+                    }
+                    HttpResponse::Ok().finish()
+                }
+                Err(e) => {
+                    log::error!(
+                        "❌ Failed to apply delivery event for {}: {}",
+                        event.message_id(),
+                        e
+                    );
+                    HttpResponse::InternalServerError().finish()
+                }
+            }
+        }
+        _ => HttpResponse::Ok().finish(),
+    }
+}
+
+/// Compares tokens in constant time
+fn tokens_match(token: &str, expected: &str) -> bool {
+    token.len() == expected.len()
+        && token
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
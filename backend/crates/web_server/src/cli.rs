@@ -0,0 +1,170 @@
+//! Admin subcommands run instead of the server, e.g. `web_server backup export prod.json`.
+
+use app_config::AppConfig;
+use campground_scan::{MockChannel, ReplayScenario, ScanExecutorConfig, load_history, replay};
+use postgres::backup::{BackupArchive, export_archive, restore_archive};
+use sqlx::PgPool;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+const USAGE: &str = "usage:
+  web_server backup export <file> [--strip-pii]
+  web_server backup import <file>
+  web_server replay <scenario.json> [--sms-failing] [--update]";
+
+/// Runs the admin subcommand in `args` (everything after the binary name)
+pub async fn run(args: &[String], pool: &PgPool, config: &AppConfig) -> Result<(), String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        ["backup", "export", path, flags @ ..] => {
+            let strip_pii = match flags {
+                [] => false,
+                ["--strip-pii"] => true,
+                _ => return Err(USAGE.to_string()),
+            };
+            export(pool, path, strip_pii).await
+        }
+        ["backup", "import", path] => import(pool, path).await,
+        ["replay", path, flags @ ..] => {
+            let mut sms_failing = false;
+            let mut update = false;
+            for flag in flags {
+                match *flag {
+                    "--sms-failing" => sms_failing = true,
+                    "--update" => update = true,
+                    _ => return Err(USAGE.to_string()),
+                }
+            }
+            replay_scenario(pool, config, path, sms_failing, update).await
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+async fn export(pool: &PgPool, path: &str, strip_pii: bool) -> Result<(), String> {
+    let archive = export_archive(pool, strip_pii)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    serde_json::to_writer(BufWriter::new(file), &archive)
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    for (table, rows) in &archive.tables {
+        log::info!("🗄️ Exported {} rows from {}", rows.len(), table);
+    }
+    log::info!(
+        "✅ Backup written to {} (format v{}, PII stripped: {})",
+        path,
+        archive.format_version,
+        strip_pii
+    );
+
+    Ok(())
+}
+
+async fn import(pool: &PgPool, path: &str) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let archive: BackupArchive = serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+    let summary = restore_archive(pool, &archive)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for (table, inserted) in &summary.inserted {
+        log::info!("🗄️ Restored {} rows into {}", inserted, table);
+    }
+    log::info!("✅ Backup {} restored", path);
+
+    Ok(())
+}
+
+/// Replays a scenario's campground history with mock email and SMS channels and checks
+/// the notifications against those it expects. With `update`, the notifications produced
+/// become the expected ones.
+async fn replay_scenario(
+    pool: &PgPool,
+    config: &AppConfig,
+    path: &str,
+    sms_failing: bool,
+    update: bool,
+) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let mut scenario: ReplayScenario = serde_json::from_reader(BufReader::new(file))
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+    let history = load_history(pool, &scenario.campground_id, scenario.from, scenario.to)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut email = MockChannel::new("email");
+    let mut sms = if sms_failing {
+        MockChannel::failing("sms")
+    } else {
+        MockChannel::new("sms")
+    };
+
+    let report = replay(
+        &scenario,
+        &history,
+        &ScanExecutorConfig::from_config(config),
+        &mut [&mut email, &mut sms],
+    );
+
+    log::info!(
+        "🔁 Replayed {} polls of campground {} from {} to {}",
+        report.snapshots,
+        scenario.campground_id,
+        scenario.from,
+        scenario.to
+    );
+    for notification in &report.notifications {
+        log::info!(
+            "🔔 {} {} via {}: sites {}",
+            notification.day,
+            notification.scan,
+            notification.channel,
+            notification.site_ids.join(", ")
+        );
+    }
+
+    if update {
+        scenario.expected = report.notifications;
+        let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &scenario)
+            .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        log::info!("✅ Expected notifications of {} updated", path);
+        return Ok(());
+    }
+
+    for notification in &report.missing {
+        log::error!(
+            "❌ Missing: {} {} via {}",
+            notification.day,
+            notification.scan,
+            notification.channel
+        );
+    }
+    for notification in &report.unexpected {
+        log::error!(
+            "❌ Unexpected: {} {} via {}",
+            notification.day,
+            notification.scan,
+            notification.channel
+        );
+    }
+
+    if !report.passed() {
+        return Err(format!(
+            "Replay of {} differs from the expected notifications ({} missing, {} unexpected)",
+            path,
+            report.missing.len(),
+            report.unexpected.len()
+        ));
+    }
+
+    log::info!("✅ Replay of {} matches the expected notifications", path);
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use actix_web::Error;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::ACCEPT_LANGUAGE;
+use actix_web::middleware::Next;
+use i18n::{Locale, with_locale};
+
+/// Handles the request in the language its `Accept-Language` header prefers, English when
+/// it names none we support. Signed-in users get their profile's language instead, set by
+/// the auth middleware from their token.
+pub async fn negotiate_locale(
+    request: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let locale = request
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(Locale::from_accept_language)
+        .unwrap_or_default();
+
+    with_locale(locale, next.call(request)).await
+}
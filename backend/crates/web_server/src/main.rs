@@ -1,19 +1,47 @@
 //! Main entry point for the Campsite Tracker backend server.
 //! This crate provides REST API endpoints and serves the frontend application.
 
+mod cli;
+mod locale;
+mod sandbox;
+mod telemetry;
+
 use actix_files::Files;
-use actix_web::{App, HttpResponse, HttpServer, Result, middleware::Logger, web};
+use actix_web::{
+    App, HttpResponse, HttpServer, Result,
+    middleware::{Condition, DefaultHeaders, from_fn},
+    web,
+};
+use app_config::AppConfig;
+use auth_services::jwt::JwtService;
 use auth_services::middleware::AuthMiddleware;
-use notification_services::{NotificationService, create_verification_store};
+use auth_services::service::AuthService;
+use auth_services::two_factor::TwoFactorService;
+use campground_scan::{
+    AvailabilityNotifier, AvailabilitySnapshots, BookingAnalytics, CampgroundSync,
+    CampsiteAttributeStore, POLL_CLAIM_LEASE_MINUTES, PermitPoller, PermitScanService,
+    PollingJobQueue, PriorityScoring, ReleaseBurstScheduler, RetentionService, ScanEventLog,
+    ScanExecutor, ScanExecutorConfig, ScanExpiryPolicy, ScanExpiryService, ScanHealthService,
+    ScanManager, ScanService, ScanUpdateHub, ScanWatchdog, SharedScanConfig, SniperScheduler,
+    StatusChange, WatchdogSettings, WorkerHeartbeats, WorkerRounds, WorkerShutdown,
+};
+use notification_services::{
+    AlertClaims, NotificationService, NotificationTemplates, OpsAlerts, SmsBudget,
+    SnsSignatureVerifier, VerificationChannel, VerificationReminders, VerificationStore,
+};
 use postgres::database::*;
+use postgres::migrations::run_migrations;
 use rec_gov::*;
 use std::path::Path;
+use std::time::Duration;
+use tracing_actix_web::TracingLogger;
 use web_handlers::*;
 
-async fn api_hello() -> Result<HttpResponse> {
+async fn api_hello(sandbox: web::Data<SandboxMode>) -> Result<HttpResponse> {
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "message": "Hello from Rust backend on AWS!",
-        "status": "running"
+        "status": "running",
+        "sandbox": sandbox.is_enabled()
     })))
 }
 
@@ -31,18 +59,629 @@ fn get_frontend_path() -> &'static str {
     }
 }
 
+/// How long in-flight requests get to finish after a shutdown signal
+const HTTP_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long background workers get to finish their current round at shutdown
+const WORKER_DRAIN_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Largest backup archive accepted by the restore endpoint
+const BACKUP_UPLOAD_LIMIT: usize = 512 * 1024 * 1024;
+
+/// Runs `round` every `interval` until shutdown, on one instance at a time: each tick
+/// beats the worker's heartbeat, then claims the round under `name` and skips it while
+/// another instance holds it.
+fn spawn_round_worker(
+    name: &'static str,
+    interval: Duration,
+    rounds: WorkerRounds,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+    round: impl AsyncFn() + 'static,
+) {
+    let heartbeat = heartbeats.register(name, interval);
+
+    let mut shutdown_signal = shutdown.signal();
+
+    shutdown.track(actix_web::rt::spawn(async move {
+        let mut ticks = actix_web::rt::time::interval(interval);
+
+        while shutdown_signal.tick(&mut ticks).await {
+            heartbeat.beat();
+
+            match rounds.claim(name, interval).await {
+                Ok(true) => {}
+                // Another instance is running this round
+                Ok(false) => continue,
+                Err(e) => {
+                    log::error!("❌ Failed to claim a {} round: {}", name, e);
+                    continue;
+                }
+            }
+
+            round().await;
+        }
+    }));
+}
+
+/// Days of booked windows used to tune poll frequency
+const POLL_TUNING_WINDOW_DAYS: i32 = 30;
+
+/// How often polling job frequency is retuned
+const POLL_TUNING_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Retunes polling job frequency from time-to-book statistics once an hour.
+fn spawn_poll_frequency_tuning(
+    pool: sqlx::PgPool,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+) {
+    let analytics = BookingAnalytics::new(pool.clone());
+
+    spawn_round_worker(
+        "poll_frequency_tuning",
+        POLL_TUNING_INTERVAL,
+        WorkerRounds::new(pool),
+        heartbeats,
+        shutdown,
+        async move || match analytics
+            .apply_poll_frequencies(POLL_TUNING_WINDOW_DAYS)
+            .await
+        {
+            Ok(0) => {}
+            Ok(updated) => log::info!("⏱️ Retuned poll frequency for {} campgrounds", updated),
+            Err(e) => log::error!("❌ Failed to retune poll frequency: {}", e),
+        },
+    );
+}
+
+/// Days of sell-outs polling priority is scored from
+const PRIORITY_SCORING_WINDOW_DAYS: i32 = 14;
+
+/// How often polling priority is rescored
+const PRIORITY_SCORING_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Rescores polling job priority from sell-outs, active scans and upcoming check-ins once
+/// an hour.
+fn spawn_priority_scoring(
+    pool: sqlx::PgPool,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+) {
+    let scoring = PriorityScoring::new(pool.clone());
+
+    spawn_round_worker(
+        "priority_scoring",
+        PRIORITY_SCORING_INTERVAL,
+        WorkerRounds::new(pool),
+        heartbeats,
+        shutdown,
+        async move || match scoring.apply(PRIORITY_SCORING_WINDOW_DAYS).await {
+            Ok(scored) => log::info!("🎯 Rescored polling priority of {} campgrounds", scored),
+            Err(e) => log::error!("❌ Failed to rescore polling priority: {}", e),
+        },
+    );
+}
+
+/// How often expired scans are ended and expiry warnings sent
+const SCAN_EXPIRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Ends scans past their expiry and warns owners of scans about to expire.
+fn spawn_scan_expiry(
+    pool: sqlx::PgPool,
+    notification_service: NotificationService,
+    policy: ScanExpiryPolicy,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+) {
+    let expiry = ScanExpiryService::new(pool.clone(), policy);
+
+    spawn_round_worker(
+        "scan_expiry",
+        SCAN_EXPIRY_INTERVAL,
+        WorkerRounds::new(pool),
+        heartbeats,
+        shutdown,
+        async move || {
+            match expiry.expire_due().await {
+                Ok(ended) if ended.completed + ended.expired > 0 => log::info!(
+                    "⌛ Ended {} scans past check-in and {} at their maximum age",
+                    ended.completed,
+                    ended.expired
+                ),
+                Ok(_) => {}
+                Err(e) => log::error!("❌ Failed to expire scans: {}", e),
+            }
+
+            let expiring = match expiry.expiring_soon().await {
+                Ok(expiring) => expiring,
+                Err(e) => {
+                    log::error!("❌ Failed to load expiring scans: {}", e);
+                    return;
+                }
+            };
+
+            for scan in expiring {
+                let sent = notification_service
+                    .send_scan_expiring(
+                        &scan.user_id,
+                        &scan.email,
+                        &scan.campground_name,
+                        scan.check_in_date,
+                        scan.check_out_date,
+                        scan.expires_at,
+                        &scan.scan_id,
+                        scan.locale,
+                        scan.timezone,
+                    )
+                    .await;
+
+                match sent {
+                    Ok(()) => {
+                        if let Err(e) = expiry.mark_warned(&scan.scan_id).await {
+                            log::error!(
+                                "❌ Failed to record expiry warning for scan {}: {}",
+                                scan.scan_id,
+                                e
+                            );
+                        }
+                    }
+                    Err(e) => log::error!(
+                        "❌ Failed to send expiry warning for scan {}: {}",
+                        scan.scan_id,
+                        e
+                    ),
+                }
+            }
+        },
+    );
+}
+
+/// How often scans whose campground can't be checked are looked for
+const SCAN_HEALTH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Emails owners of active scans whose campground has been failing to be checked for a
+/// day (e.g. closed for the season), once per failing streak.
+fn spawn_scan_health(
+    pool: sqlx::PgPool,
+    notification_service: NotificationService,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+) {
+    let health = ScanHealthService::new(pool.clone());
+
+    spawn_round_worker(
+        "scan_health",
+        SCAN_HEALTH_INTERVAL,
+        WorkerRounds::new(pool),
+        heartbeats,
+        shutdown,
+        async move || {
+            let blocked = match health.newly_blocked().await {
+                Ok(blocked) => blocked,
+                Err(e) => {
+                    log::error!("❌ Failed to load blocked scans: {}", e);
+                    return;
+                }
+            };
+
+            for scan in blocked {
+                let sent = notification_service
+                    .send_scan_blocked(
+                        &scan.user_id,
+                        &scan.email,
+                        &scan.campground_name,
+                        scan.check_in_date,
+                        scan.check_out_date,
+                        scan.failing_since,
+                        &scan.scan_id,
+                        scan.locale,
+                        scan.timezone,
+                    )
+                    .await;
+
+                match sent {
+                    Ok(()) => {
+                        if let Err(e) = health.mark_notified(&scan.scan_id).await {
+                            log::error!(
+                                "❌ Failed to record blocked notice for scan {}: {}",
+                                scan.scan_id,
+                                e
+                            );
+                        }
+                    }
+                    Err(e) => log::error!(
+                        "❌ Failed to send blocked notice for scan {}: {}",
+                        scan.scan_id,
+                        e
+                    ),
+                }
+            }
+        },
+    );
+}
+
+/// How often users who haven't verified are checked for reminders
+const VERIFICATION_REMINDER_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Reminds users who signed up but never verified their email (or their phone, with SMS
+/// alerts on) that alerts can't reach them, with a fresh email verification link.
+fn spawn_verification_reminders(
+    pool: sqlx::PgPool,
+    notification_service: NotificationService,
+    verification_store: VerificationStore,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+) {
+    let reminders = VerificationReminders::new(pool.clone());
+
+    spawn_round_worker(
+        "verification_reminders",
+        VERIFICATION_REMINDER_INTERVAL,
+        WorkerRounds::new(pool),
+        heartbeats,
+        shutdown,
+        async move || {
+            let due = match reminders.due().await {
+                Ok(due) => due,
+                Err(e) => {
+                    log::error!("❌ Failed to load verification reminders: {}", e);
+                    return;
+                }
+            };
+
+            for reminder in due {
+                let token = if reminder.email_unverified {
+                    let token = NotificationService::generate_verification_token();
+                    let stored = verification_store
+                        .store_token(
+                            &reminder.user_id,
+                            VerificationChannel::Email,
+                            &reminder.email,
+                            &token,
+                            1440, // 24 hours
+                        )
+                        .await;
+
+                    if let Err(e) = stored {
+                        log::error!(
+                            "❌ Failed to store verification token for user {}: {}",
+                            reminder.user_id,
+                            e
+                        );
+                        continue;
+                    }
+                    Some(token)
+                } else {
+                    None
+                };
+
+                let sent = notification_service
+                    .send_verification_reminder(
+                        &reminder.email,
+                        &reminder.name,
+                        token.as_deref(),
+                        reminder.phone_unverified,
+                        reminder.locale,
+                    )
+                    .await;
+
+                match sent {
+                    Ok(()) => {
+                        if let Err(e) = reminders
+                            .mark_sent(&reminder.user_id, reminder.number)
+                            .await
+                        {
+                            log::error!(
+                                "❌ Failed to record verification reminder for user {}: {}",
+                                reminder.user_id,
+                                e
+                            );
+                        }
+                    }
+                    Err(e) => log::error!(
+                        "❌ Failed to send verification reminder to user {}: {}",
+                        reminder.user_id,
+                        e
+                    ),
+                }
+            }
+        },
+    );
+}
+
+/// How often rows past their retention window are deleted
+const RETENTION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Deletes availability, notification, and scan event rows past their retention windows
+/// once a day.
+fn spawn_retention_pruning(
+    pool: sqlx::PgPool,
+    config: ScanExecutorConfig,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+) {
+    let retention = RetentionService::new(pool.clone(), config);
+
+    spawn_round_worker(
+        "retention_pruning",
+        RETENTION_INTERVAL,
+        WorkerRounds::new(pool),
+        heartbeats,
+        shutdown,
+        async move || match retention.prune().await {
+            Ok(pruned) if pruned.total() > 0 => log::info!(
+                "🧹 Pruned {} availability, {} availability history, {} notification and {} scan event rows",
+                pruned.campground_availability,
+                pruned.availability_history,
+                pruned.notifications,
+                pruned.scan_events
+            ),
+            Ok(_) => {}
+            Err(e) => log::error!("❌ Failed to prune old rows: {}", e),
+        },
+    );
+}
+
+/// How often scanned campgrounds' metadata is refreshed from RIDB
+const CAMPGROUND_SYNC_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Refreshes the names, coordinates, states, site counts and reservable flags of
+/// campgrounds with active scans from RIDB once a day.
+fn spawn_campground_sync(
+    pool: sqlx::PgPool,
+    client: RecGovClient,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+) {
+    let campground_sync = CampgroundSync::new(pool.clone());
+
+    spawn_round_worker(
+        "campground_sync",
+        CAMPGROUND_SYNC_INTERVAL,
+        WorkerRounds::new(pool),
+        heartbeats,
+        shutdown,
+        async move || match campground_sync.sync(&client).await {
+            Ok(summary) => log::info!(
+                "🏕️ Synced metadata of {} of {} scanned campgrounds ({} unknown to RIDB, {} failed)",
+                summary.updated,
+                summary.checked,
+                summary.missing,
+                summary.failed
+            ),
+            Err(e) => log::error!("❌ Failed to sync campground metadata: {}", e),
+        },
+    );
+}
+
+/// How often the watchdog checks the scan system
+const SCAN_WATCHDOG_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Checks polling and upstream API health, alerting ops when the scan system goes down
+/// and again when it recovers.
+fn spawn_scan_watchdog(
+    pool: sqlx::PgPool,
+    settings: WatchdogSettings,
+    ops_alerts: OpsAlerts,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+) {
+    let watchdog = ScanWatchdog::new(pool.clone(), settings);
+
+    spawn_round_worker(
+        "scan_watchdog",
+        SCAN_WATCHDOG_INTERVAL,
+        WorkerRounds::new(pool),
+        heartbeats,
+        shutdown,
+        async move || {
+            let change = match watchdog.check().await {
+                Ok(problems) => watchdog.record(&problems).await,
+                Err(e) => Err(e),
+            };
+
+            let (subject, body) = match change {
+                Ok(Some(StatusChange::Down(problems))) => (
+                    "🚨 Campsite Tracker scan system is down",
+                    problems
+                        .iter()
+                        .map(|problem| format!("- {}", problem.describe()))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ),
+                Ok(Some(StatusChange::Recovered)) => (
+                    "✅ Campsite Tracker scan system recovered",
+                    "Polling and upstream API calls are healthy again.".to_string(),
+                ),
+                Ok(None) => return,
+                Err(e) => {
+                    log::error!("❌ Scan watchdog check failed: {}", e);
+                    return;
+                }
+            };
+
+            // Failures are logged by the alerts themselves
+            let _ = ops_alerts.send(subject, &body).await;
+        },
+    );
+}
+
+/// How often campgrounds are moved in and out of sniper mode
+const SNIPER_SCHEDULE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Keeps campgrounds of same-week scans in sniper mode while their trips are upcoming.
+fn spawn_sniper_mode(
+    pool: sqlx::PgPool,
+    config: ScanExecutorConfig,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+) {
+    let scheduler = SniperScheduler::new(pool.clone(), config);
+
+    spawn_round_worker(
+        "sniper_mode",
+        SNIPER_SCHEDULE_INTERVAL,
+        WorkerRounds::new(pool),
+        heartbeats,
+        shutdown,
+        async move || match scheduler.apply().await {
+            Ok(update) if update.sniping > 0 || update.released > 0 => log::info!(
+                "🎯 Sniper mode: {} campgrounds active, {} released, {} scans set to expire",
+                update.sniping,
+                update.released,
+                update.expiring_scans
+            ),
+            Ok(_) => {}
+            Err(e) => log::error!("❌ Failed to apply sniper mode: {}", e),
+        },
+    );
+}
+
+/// How often release bursts are started and ended; well under a burst's lead time
+const RELEASE_BURST_SCHEDULE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Polls campgrounds with a release schedule every few seconds around their release time.
+fn spawn_release_bursts(
+    pool: sqlx::PgPool,
+    config: ScanExecutorConfig,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+) {
+    let scheduler = ReleaseBurstScheduler::new(pool.clone(), config);
+
+    spawn_round_worker(
+        "release_bursts",
+        RELEASE_BURST_SCHEDULE_INTERVAL,
+        WorkerRounds::new(pool),
+        heartbeats,
+        shutdown,
+        async move || match scheduler.apply().await {
+            Ok(update) if update.bursting > 0 || update.ended > 0 => log::info!(
+                "⏱️ Release bursts: {} campgrounds started, {} ended",
+                update.bursting,
+                update.ended
+            ),
+            Ok(_) => {}
+            Err(e) => log::error!("❌ Failed to apply release bursts: {}", e),
+        },
+    );
+}
+
+/// How often due campgrounds are claimed and polled; well under the shortest release
+/// burst interval
+const SCAN_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Polls the campgrounds this instance claims. Rounds can run long when recreation.gov
+/// is slow, so the worker only counts as stalled once a round has outlasted the claim
+/// lease, when other instances take its campgrounds over anyway. Each round runs as a
+/// task of its own, so a panic loses that round (whose claims lapse) rather than
+/// stopping polling; panics polling a campground are contained by the [`ScanManager`].
+fn spawn_scan_polling(
+    scan_manager: ScanManager,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+) {
+    let heartbeat = heartbeats.register(
+        "scan_polling",
+        Duration::from_secs(POLL_CLAIM_LEASE_MINUTES as u64 * 60 / 2),
+    );
+
+    let mut shutdown_signal = shutdown.signal();
+
+    shutdown.track(actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(SCAN_POLL_INTERVAL);
+
+        while shutdown_signal.tick(&mut interval).await {
+            heartbeat.beat();
+
+            let manager = scan_manager.clone();
+            match actix_web::rt::spawn(async move { manager.run_round().await }).await {
+                Ok(Ok(round)) if round.campgrounds > 0 || round.deferred > 0 || round.panicked > 0 => {
+                    log::info!(
+                        "🏕️ Polled {} campgrounds ({} scans checked, {} failed, {} deferred, {} panicked), {} alerts sent",
+                        round.campgrounds,
+                        round.scans_checked,
+                        round.failed,
+                        round.deferred,
+                        round.panicked,
+                        round.alerts_sent
+                    )
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => log::error!("❌ Failed to run a scan polling round: {}", e),
+                Err(e) => log::error!("💥 Scan polling round panicked: {}", e),
+            }
+        }
+    }));
+}
+
+/// How often due permits are claimed and polled; each permit is polled every 15 minutes
+const PERMIT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Polls the permits this instance claims. Like scan polling, each round runs as a task
+/// of its own, and panics polling a permit are contained by the [`PermitPoller`].
+fn spawn_permit_polling(
+    permit_poller: PermitPoller,
+    heartbeats: &WorkerHeartbeats,
+    shutdown: &WorkerShutdown,
+) {
+    let heartbeat = heartbeats.register(
+        "permit_polling",
+        Duration::from_secs(POLL_CLAIM_LEASE_MINUTES as u64 * 60 / 2),
+    );
+
+    let mut shutdown_signal = shutdown.signal();
+
+    shutdown.track(actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(PERMIT_POLL_INTERVAL);
+
+        while shutdown_signal.tick(&mut interval).await {
+            heartbeat.beat();
+
+            let poller = permit_poller.clone();
+            match actix_web::rt::spawn(async move { poller.run_round().await }).await {
+                Ok(Ok(round)) if round.permits > 0 || round.deferred > 0 || round.panicked > 0 => {
+                    log::info!(
+                        "🎟️ Polled {} permits ({} scans checked, {} failed, {} deferred, {} panicked), {} alerts sent",
+                        round.permits,
+                        round.scans_checked,
+                        round.failed,
+                        round.deferred,
+                        round.panicked,
+                        round.alerts_sent
+                    )
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => log::error!("❌ Failed to run a permit polling round: {}", e),
+                Err(e) => log::error!("💥 Permit polling round panicked: {}", e),
+            }
+        }
+    }));
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     // Load environment variables
     dotenvy::dotenv().ok();
 
-    // Initialize logger
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    // Initialize logging (LOG_FORMAT=json for structured logs)
+    telemetry::init_logging();
 
     log::info!("🚀 Starting campsite tracker server...");
 
-    // Create database connection pool
-    let pool = match create_connection_pool().await {
+    // Load and validate settings before starting anything else
+    let config = match AppConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            log::error!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Create database connection pool (settings were validated by AppConfig::load)
+    let database_config = config.database().expect("validated database settings");
+    let pool = match create_connection_pool_with_config(&database_config).await {
         Ok(pool) => {
             log::info!("🗃️ Database pool created successfully");
 
@@ -58,53 +697,316 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
+    // Bring the schema up to date before anything touches the database
+    if let Err(e) = run_migrations(&pool).await {
+        log::error!("❌ Failed to run database migrations: {}", e);
+        std::process::exit(1);
+    }
+
+    // Admin subcommands (e.g. `backup export`) run against the database and exit
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = cli::run(&args, &pool, &config).await {
+            log::error!("❌ {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let sandbox_mode = SandboxMode(config.sandbox_mode);
+    if sandbox_mode.is_enabled() {
+        log::warn!("🧪 SANDBOX_MODE enabled: using fixture campgrounds and mock email/SMS");
+
+        if let Err(e) = sandbox::seed_sandbox_data(&pool).await {
+            log::error!("❌ Failed to seed sandbox data: {}", e);
+        }
+    }
+
+    // Load email/SMS templates, with the deployment's overrides
+    let templates = match NotificationTemplates::load(
+        config.notification_templates_dir.as_deref().map(Path::new),
+    ) {
+        Ok(templates) => templates,
+        Err(e) => {
+            log::error!("❌ Failed to load notification templates: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     // Create notification service
-    let notification_service = match NotificationService::new().await {
-        Ok(service) => {
-            log::info!("📧 Notification service initialized successfully");
-            service
+    let notification_service = if sandbox_mode.is_enabled() {
+        NotificationService::sandbox(&config, templates, pool.clone())
+    } else {
+        match NotificationService::new(&config, templates.clone(), pool.clone()).await {
+            Ok(service) => {
+                log::info!("📧 Notification service initialized successfully");
+                service
+            }
+            Err(e) => {
+                log::error!("❌ Failed to initialize notification service: {}", e);
+                log::warn!("🔧 Check AWS credentials and SES setup");
+                // For now, let's not exit - you can still test other features
+                // std::process::exit(1);
+                NotificationService::new(&config, templates, pool.clone())
+                    .await
+                    .unwrap() // This will fail gracefully in handlers
+            }
         }
+    };
+
+    // Create verification store (Redis when REDIS_URL is set, Postgres otherwise)
+    let verification_store = VerificationStore::from_config(pool.clone(), &config).await;
+
+    // Create the monthly SMS budget, tracked against the notifications ledger
+    let sms_budget = SmsBudget::from_config(pool.clone(), &config);
+
+    // Create the upstream API call ledger, which also keeps the hourly request budget
+    let api_ledger = ApiCallLedger::from_config(pool.clone(), &config);
+
+    // Create the recreation.gov client (serves fixtures in sandbox mode)
+    let rec_gov_client = RecGovClient::new(&config, api_ledger.clone());
+    let reserve_california_client = ReserveCaliforniaClient::new(&config, api_ledger.clone());
+
+    // Create the hub fanning live scan updates out to connected dashboards
+    let scan_updates = ScanUpdateHub::new();
+
+    // Create the reverse geocoder (sandbox mode never calls external providers)
+    let geocoder = if sandbox_mode.is_enabled() {
+        Geocoder::disabled()
+    } else {
+        Geocoder::from_config(&config)
+    };
+
+    // Polling settings ops can change at runtime through the admin API
+    let scan_config = SharedScanConfig::new(ScanExecutorConfig::from_config(&config));
+
+    // Polling jobs are claimed under this instance's ID, so replicas can run side by side
+    let polling_jobs = PollingJobQueue::new(pool.clone(), scan_config.clone());
+    log::info!("🪪 Polling as instance {}", polling_jobs.instance_id());
+
+    // Polls the campgrounds this instance claims and alerts about open sites
+    let scan_executor_config = ScanExecutorConfig::from_config(&config);
+    let scan_manager = ScanManager::new(
+        pool.clone(),
+        polling_jobs.clone(),
+        ScanExecutor::new(
+            rec_gov_client.clone(),
+            reserve_california_client.clone(),
+            ScanEventLog::new(pool.clone()),
+            AvailabilitySnapshots::new(pool.clone()),
+            CampsiteAttributeStore::new(pool.clone()),
+            scan_updates.clone(),
+            scan_executor_config,
+        ),
+        ScanService::from_config(pool.clone(), &config),
+        AvailabilityNotifier::new(
+            notification_service.clone(),
+            sms_budget.clone(),
+            AlertClaims::new(pool.clone()),
+            scan_config.clone(),
+        ),
+        geocoder.clone(),
+    );
+
+    let heartbeats = WorkerHeartbeats::new();
+    let worker_shutdown = WorkerShutdown::new();
+
+    spawn_scan_polling(scan_manager.clone(), &heartbeats, &worker_shutdown);
+
+    spawn_poll_frequency_tuning(pool.clone(), &heartbeats, &worker_shutdown);
+    spawn_priority_scoring(pool.clone(), &heartbeats, &worker_shutdown);
+    spawn_sniper_mode(
+        pool.clone(),
+        ScanExecutorConfig::from_config(&config),
+        &heartbeats,
+        &worker_shutdown,
+    );
+    spawn_release_bursts(
+        pool.clone(),
+        ScanExecutorConfig::from_config(&config),
+        &heartbeats,
+        &worker_shutdown,
+    );
+    spawn_retention_pruning(
+        pool.clone(),
+        ScanExecutorConfig::from_config(&config),
+        &heartbeats,
+        &worker_shutdown,
+    );
+    spawn_campground_sync(
+        pool.clone(),
+        rec_gov_client.clone(),
+        &heartbeats,
+        &worker_shutdown,
+    );
+    spawn_scan_expiry(
+        pool.clone(),
+        notification_service.clone(),
+        ScanExpiryPolicy::from_config(&config),
+        &heartbeats,
+        &worker_shutdown,
+    );
+    spawn_scan_health(
+        pool.clone(),
+        notification_service.clone(),
+        &heartbeats,
+        &worker_shutdown,
+    );
+    spawn_verification_reminders(
+        pool.clone(),
+        notification_service.clone(),
+        verification_store.clone(),
+        &heartbeats,
+        &worker_shutdown,
+    );
+    spawn_permit_polling(
+        PermitPoller::new(
+            polling_jobs,
+            PermitScanService::new(pool.clone()),
+            rec_gov_client.clone(),
+            notification_service.clone(),
+            sms_budget.clone(),
+        ),
+        &heartbeats,
+        &worker_shutdown,
+    );
+
+    spawn_scan_watchdog(
+        pool.clone(),
+        WatchdogSettings::from_config(&config),
+        OpsAlerts::new(&config, notification_service.clone()),
+        &heartbeats,
+        &worker_shutdown,
+    );
+
+    let jwt_service = match JwtService::from_config(&config) {
+        Ok(service) => service,
         Err(e) => {
-            log::error!("❌ Failed to initialize notification service: {}", e);
-            log::warn!("🔧 Check AWS credentials and SES setup");
-            // For now, let's not exit - you can still test other features
-            // std::process::exit(1);
-            NotificationService::new().await.unwrap() // This will fail gracefully in handlers
+            log::error!("❌ Failed to load JWT signing keys: {}", e);
+            std::process::exit(1);
         }
     };
+    // Services the handlers share, created once instead of on every request
+    let app_state = web::Data::new(AppState::from_config(
+        pool.clone(),
+        &config,
+        jwt_service.clone(),
+        notification_service,
+    ));
+    // Shared so each SNS signing certificate is fetched once
+    let sns_verifier = web::Data::new(SnsSignatureVerifier::new(
+        config.delivery_webhook_topic_arn_list(),
+    ));
+    let bind_address = config.bind_address.clone();
 
-    // Create verification store
-    let verification_store = create_verification_store();
+    let shutdown_pool = pool.clone();
+    let shutdown_scan_manager = scan_manager.clone();
 
     let frontend_path = get_frontend_path();
     log::info!("📁 Frontend files location: {}", frontend_path);
-    log::info!("🌐 Server will be available at: http://0.0.0.0:8080");
+    log::info!("🌐 Server will be available at: http://{}", bind_address);
 
     HttpServer::new(move || {
         App::new()
+            .app_data(web::Data::new(config.clone()))
             .app_data(web::Data::new(pool.clone()))
-            .app_data(web::Data::new(notification_service.clone()))
+            .app_data(app_state.clone())
             .app_data(web::Data::new(verification_store.clone()))
-            .wrap(Logger::default())
+            .app_data(web::Data::new(sms_budget.clone()))
+            .app_data(web::Data::new(api_ledger.clone()))
+            .app_data(web::Data::new(rec_gov_client.clone()))
+            .app_data(web::Data::new(scan_config.clone()))
+            .app_data(web::Data::new(scan_manager.clone()))
+            .app_data(web::Data::new(reserve_california_client.clone()))
+            .app_data(web::Data::new(geocoder.clone()))
+            .app_data(web::Data::new(scan_updates.clone()))
+            .app_data(web::Data::new(heartbeats.clone()))
+            .app_data(web::Data::new(sandbox_mode))
+            .app_data(sns_verifier.clone())
+            .wrap(Condition::new(
+                sandbox_mode.is_enabled(),
+                DefaultHeaders::new().add(("X-Sandbox-Mode", "true")),
+            ))
+            .wrap(from_fn(locale::negotiate_locale))
+            .wrap(from_fn(telemetry::propagate_request_id))
+            .wrap(TracingLogger::<telemetry::RequestSpan>::new())
             .service(
                 web::scope("/api")
                     // Public routes
                     .route("/hello", web::get().to(api_hello))
                     .route("/facilities/search", web::get().to(facilities_search))
+                    .route("/campgrounds/search", web::get().to(campground_search))
+                    .route(
+                        "/campgrounds/{campground_id}",
+                        web::get().to(get_campground_details),
+                    )
+                    .route(
+                        "/campgrounds/{campground_id}/availability",
+                        web::get().to(get_campground_availability),
+                    )
+                    .route(
+                        "/campgrounds/{campground_id}/availability/live",
+                        web::get().to(get_live_campground_availability),
+                    )
+                    .route(
+                        "/campgrounds/{campground_id}/stats",
+                        web::get().to(get_campground_stats),
+                    )
+                    .route(
+                        "/permits/{permit_id}/availability",
+                        web::get().to(get_permit_availability),
+                    )
                     .route("/dev/delete-user", web::delete().to(delete_user_by_email))
+                    // Live scan updates (authenticates itself, since browsers can't send
+                    // headers on WebSocket requests)
+                    .route("/ws", web::get().to(scan_updates_ws))
+                    // SES/SNS delivery feedback (authenticated by DELIVERY_WEBHOOK_TOKEN and
+                    // the SNS message signature)
+                    .service(
+                        web::scope("/webhooks")
+                            .route("/ses", web::post().to(ses_webhook))
+                            .route("/sns", web::post().to(sns_webhook)),
+                    )
+                    // Calendar feed for calendar apps (authenticated by its signed token)
+                    .route("/user/scans.ics", web::get().to(get_scan_calendar))
                     .service(
                         web::scope("/auth")
                             .route("/health", web::get().to(auth_health))
-                            .route("/signup", web::post().to(signup))
-                            .route("/login", web::post().to(login))
-                            .route("/users", web::get().to(list_users)),
+                            .route(
+                                "/signup",
+                                web::post()
+                                    .to(signup::<AuthService, ScanService, TwoFactorService>),
+                            )
+                            .route(
+                                "/login",
+                                web::post().to(login::<AuthService, ScanService, TwoFactorService>),
+                            )
+                            .route("/2fa", web::post().to(login_two_factor)),
                     )
                     // Protected routes (require authentication)
                     .service(
                         web::scope("/user")
-                            .wrap(AuthMiddleware)
+                            .wrap(AuthMiddleware::new(jwt_service.clone()))
                             .route("/profile", web::get().to(get_profile))
+                            .route("/profile", web::patch().to(patch_profile))
+                            .route("/calendar", web::get().to(get_calendar_feed_url))
+                            .route("/calendar/reset", web::post().to(reset_calendar_feed_url))
+                            .route("/onboarding", web::get().to(get_onboarding))
+                            .route("/favorites", web::get().to(list_favorites))
+                            .route("/favorites", web::post().to(add_favorite))
+                            .route(
+                                "/favorites/{campground_id}",
+                                web::delete().to(remove_favorite),
+                            )
+                            .route(
+                                "/favorites/{campground_id}/scan",
+                                web::post().to(scan_favorite),
+                            )
+                            .route("/2fa/setup", web::post().to(setup_two_factor))
+                            .route("/2fa/verify", web::post().to(verify_two_factor))
+                            .route("/2fa/disable", web::post().to(disable_two_factor))
                             .route("/profile/update", web::put().to(update_profile))
+                            .route("/email-change", web::delete().to(cancel_email_change))
                             // Add verification routes
                             .route(
                                 "/verify/email/send",
@@ -116,23 +1018,187 @@ async fn main() -> std::io::Result<()> {
                     // Scan routes (require authentication)
                     .service(
                         web::scope("/scans")
-                            .wrap(AuthMiddleware)
-                            .route("", web::post().to(create_scan))
+                            .wrap(AuthMiddleware::new(jwt_service.clone()))
+                            .route("", web::post().to(create_scan::<AuthService, ScanService>))
                             .route("", web::get().to(get_user_scans))
                             .route("/active", web::get().to(get_active_scans))
+                            .route("/export", web::get().to(export_scans))
                             .route("/{scan_id}", web::get().to(get_scan))
-                            .route("/{scan_id}", web::put().to(update_scan))
-                            .route("/{scan_id}", web::delete().to(delete_scan)),
+                            .route(
+                                "/{scan_id}",
+                                web::put().to(update_scan::<AuthService, ScanService>),
+                            )
+                            .route("/{scan_id}", web::patch().to(patch_scan))
+                            .route("/{scan_id}", web::delete().to(delete_scan))
+                            .route("/{scan_id}/remap", web::post().to(remap_scan))
+                            .route("/{scan_id}/extend", web::post().to(extend_scan))
+                            .route("/{scan_id}/restore", web::post().to(restore_scan))
+                            .route("/{scan_id}/outcome", web::put().to(set_scan_outcome))
+                            .route("/{scan_id}/share", web::post().to(share_scan))
+                            .route("/{scan_id}/share", web::delete().to(unshare_scan))
+                            .route("/{scan_id}/recipients", web::get().to(list_scan_recipients))
+                            .route("/{scan_id}/recipients", web::post().to(add_scan_recipient))
+                            .route(
+                                "/{scan_id}/recipients/{recipient_id}",
+                                web::delete().to(remove_scan_recipient),
+                            )
+                            .route("/{scan_id}/events", web::get().to(get_scan_events)),
+                    )
+                    // Trip routes (require authentication)
+                    .service(
+                        web::scope("/trips")
+                            .wrap(AuthMiddleware::new(jwt_service.clone()))
+                            .route("", web::post().to(create_trip))
+                            .route("", web::get().to(list_trips))
+                            .route("/{trip_id}", web::get().to(get_trip))
+                            .route("/{trip_id}", web::delete().to(delete_trip))
+                            .route("/{trip_id}/scans/{scan_id}", web::put().to(add_trip_scan))
+                            .route(
+                                "/{trip_id}/scans/{scan_id}",
+                                web::delete().to(remove_trip_scan),
+                            ),
+                    )
+                    // Shared scans: public read-only view, cloning requires authentication
+                    .service(
+                        web::scope("/shared")
+                            .route("/{slug}", web::get().to(get_shared_scan))
+                            .service(
+                                web::resource("/{slug}/clone")
+                                    .wrap(AuthMiddleware::new(jwt_service.clone()))
+                                    .route(web::post().to(clone_shared_scan)),
+                            ),
+                    )
+                    // Permit scan routes (require authentication)
+                    .service(
+                        web::scope("/permit-scans")
+                            .wrap(AuthMiddleware::new(jwt_service.clone()))
+                            .route("", web::post().to(create_permit_scan))
+                            .route("", web::get().to(get_permit_scans))
+                            .route("/{scan_id}", web::delete().to(delete_permit_scan)),
+                    )
+                    // Organization routes (require authentication)
+                    .service(
+                        web::scope("/orgs")
+                            .wrap(AuthMiddleware::new(jwt_service.clone()))
+                            .route("", web::post().to(create_organization))
+                            .route("", web::get().to(list_organizations))
+                            .route(
+                                "/invitations/accept",
+                                web::post().to(accept_organization_invitation),
+                            )
+                            .route("/{org_id}", web::get().to(get_organization))
+                            .route(
+                                "/{org_id}/invitations",
+                                web::post().to(invite_organization_member),
+                            )
+                            .route(
+                                "/{org_id}/members/{user_id}",
+                                web::delete().to(remove_organization_member),
+                            )
+                            .route("/{org_id}/scans", web::get().to(get_organization_scans)),
+                    )
+                    // Admin routes (require an admin account)
+                    .service(
+                        web::scope("/admin")
+                            .wrap(AuthMiddleware::new(jwt_service.clone()))
+                            .route("/api-usage", web::get().to(get_api_usage))
+                            .route("/time-to-book", web::get().to(get_time_to_book))
+                            .route("/alert-conversion", web::get().to(get_alert_conversion))
+                            .route("/scan-system", web::get().to(get_scan_system_stats))
+                            .route("/proxies", web::get().to(get_proxy_health))
+                            .route("/scan-config", web::get().to(get_scan_config))
+                            .route("/scan-config", web::put().to(update_scan_config))
+                            .route("/scan-manager", web::get().to(get_scan_manager_status))
+                            .route("/polling-jobs/queue", web::get().to(get_polling_queue))
+                            .route(
+                                "/polling-jobs/{campground_id}",
+                                web::get().to(get_polling_job),
+                            )
+                            .route(
+                                "/polling-jobs/{campground_id}",
+                                web::put().to(update_polling_job),
+                            )
+                            .route(
+                                "/polling-jobs/{campground_id}/release-schedule",
+                                web::put().to(set_release_schedule),
+                            )
+                            .route(
+                                "/polling-jobs/{campground_id}/release-schedule",
+                                web::delete().to(clear_release_schedule),
+                            )
+                            .route(
+                                "/polling-jobs/{campground_id}/scan",
+                                web::post().to(force_scan),
+                            )
+                            .route(
+                                "/polling-jobs/{campground_id}/reset-errors",
+                                web::post().to(reset_polling_job_errors),
+                            )
+                            .route(
+                                "/polling-jobs/{campground_id}/disable",
+                                web::post().to(disable_polling_job),
+                            )
+                            .route(
+                                "/polling-jobs/{campground_id}/enable",
+                                web::post().to(enable_polling_job),
+                            )
+                            .route("/backup", web::get().to(export_backup))
+                            .route("/availability/export", web::get().to(export_availability))
+                            .route("/users", web::get().to(list_users))
+                            .route("/users/{user_id}/plan", web::put().to(update_user_plan))
+                            .route("/notifications", web::get().to(list_notifications))
+                            .route(
+                                "/notifications/test",
+                                web::post().to(send_test_notification),
+                            )
+                            .service(
+                                web::resource("/restore")
+                                    .app_data(web::JsonConfig::default().limit(BACKUP_UPLOAD_LIMIT))
+                                    .route(web::post().to(restore_backup)),
+                            ),
                     ),
             )
             .route(
                 "/health",
                 web::get().to(|| async { HttpResponse::Ok().body("OK") }),
             )
+            .route("/health/live", web::get().to(health_live))
+            .route("/health/ready", web::get().to(health_ready))
             .route("/verify-email", web::get().to(verify_email_with_token))
+            .route("/confirm-email-change", web::get().to(confirm_email_change))
+            .route("/unsubscribe", web::get().to(unsubscribe))
+            .route("/s/{slug}", web::get().to(shared_scan_link))
+            .route("/recipients/confirm", web::get().to(confirm_scan_recipient))
+            .route("/recipients/decline", web::get().to(decline_scan_recipient))
+            .route(
+                "/recipients/decline",
+                web::post().to(decline_scan_recipient),
+            )
+            .route("/unsubscribe", web::post().to(unsubscribe_one_click))
+            .route("/.well-known/jwks.json", web::get().to(jwks))
             .service(Files::new("/", frontend_path).index_file("index.html"))
     })
-    .bind("0.0.0.0:8080")?
+    .shutdown_timeout(HTTP_SHUTDOWN_TIMEOUT.as_secs())
+    .bind(bind_address)?
     .run()
-    .await
+    .await?;
+
+    // SIGTERM/SIGINT: new connections were refused and in-flight requests have finished
+    log::info!("🛑 HTTP server stopped, draining background workers");
+
+    let aborted = worker_shutdown.stop(WORKER_DRAIN_TIMEOUT).await;
+    if aborted > 0 {
+        log::warn!("⚠️ Aborted {} background workers still running", aborted);
+    }
+
+    match shutdown_scan_manager.stop().await {
+        Ok(0) => {}
+        Ok(released) => log::info!("🔓 Released {} polling job claims", released),
+        Err(e) => log::error!("❌ Failed to release polling job claims: {}", e),
+    }
+
+    shutdown_pool.close().await;
+    log::info!("👋 Shutdown complete");
+
+    Ok(())
 }
@@ -0,0 +1,81 @@
+//! Demo data for sandbox mode.
+
+use auth_services::service::AuthService;
+use auth_services::types::{NotificationPreferences, SignUpRequest};
+use campground_scan::{CreateScanRequest, ScanService};
+use chrono::{Duration, Utc};
+use rec_gov::{FacilityType, Provider, SANDBOX_FACILITIES, SiteFilters};
+use sqlx::PgPool;
+
+/// Email of the demo account seeded in sandbox mode.
+pub const SANDBOX_DEMO_EMAIL: &str = "demo@sandbox.camptracker.test";
+/// Password of the demo account seeded in sandbox mode.
+pub const SANDBOX_DEMO_PASSWORD: &str = "sandbox-demo";
+
+/// Seeds a verified demo user with a few scans on fixture campgrounds.
+/// Does nothing if the demo user already exists.
+pub async fn seed_sandbox_data(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    let auth_service = AuthService::new(pool.clone());
+
+    if auth_service
+        .get_user_by_email(SANDBOX_DEMO_EMAIL)
+        .await?
+        .is_some()
+    {
+        log::info!("🧪 Sandbox demo data already present");
+        return Ok(());
+    }
+
+    let user = auth_service
+        .create_user(&SignUpRequest {
+            name: "Sandbox Camper".to_string(),
+            email: SANDBOX_DEMO_EMAIL.to_string(),
+            phone: "5555550100".to_string(),
+            password: SANDBOX_DEMO_PASSWORD.to_string(),
+            notification_preferences: NotificationPreferences {
+                email: true,
+                sms: true,
+            },
+            locale: None,
+            timezone: None,
+        })
+        .await?;
+
+    auth_service
+        .update_user_verification(&user.id, Some(true), Some(true))
+        .await?;
+
+    let scan_service = ScanService::new(pool.clone());
+    let today = Utc::now().date_naive();
+
+    for (offset, (id, name, _, _)) in SANDBOX_FACILITIES.iter().take(2).enumerate() {
+        let check_in_date = today + Duration::days(30 + 7 * offset as i64);
+
+        scan_service
+            .create_scan(
+                &user.id,
+                &CreateScanRequest {
+                    campground_id: id.to_string(),
+                    campground_name: name.to_string(),
+                    check_in_date,
+                    organization_id: None,
+                    facility_type: FacilityType::Campsite,
+                    provider: Provider::RecreationGov,
+                    check_out_date: check_in_date + Duration::days(2),
+                    flexibility_days: 0,
+                    max_price: None,
+                    site_filters: SiteFilters::default(),
+                    quick_book: false,
+                },
+            )
+            .await?;
+    }
+
+    log::info!(
+        "🧪 Seeded sandbox demo user {} (password: {})",
+        SANDBOX_DEMO_EMAIL,
+        SANDBOX_DEMO_PASSWORD
+    );
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpMessage};
+use rec_gov::{REQUEST_ID_HEADER, with_request_id};
+use tracing::Span;
+use tracing_actix_web::{DefaultRootSpanBuilder, RequestId, RootSpanBuilder, root_span};
+use tracing_subscriber::EnvFilter;
+
+/// Longest `X-Request-Id` sent by a client that is recorded
+const MAX_CLIENT_REQUEST_ID_LEN: usize = 128;
+
+/// Sets up logging: human-readable lines by default, or one JSON object per event with
+/// the enclosing spans (and so the request ID) when `LOG_FORMAT=json`. `RUST_LOG` picks
+/// the levels, and records from the `log` crate are included.
+///
+/// Read from the environment rather than `AppConfig`, since logging starts before the
+/// configuration is loaded.
+pub fn init_logging() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let json = std::env::var("LOG_FORMAT").is_ok_and(|format| format.eq_ignore_ascii_case("json"));
+
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    if json {
+        subscriber
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .init();
+    } else {
+        subscriber.init();
+    }
+}
+
+/// Root span of each request: the default HTTP fields and generated `request_id`, plus
+/// the `X-Request-Id` the client sent, if any, so reports quoting either can be found.
+/// Logs one line per request when it completes, in place of the access log.
+pub struct RequestSpan;
+
+impl RootSpanBuilder for RequestSpan {
+    fn on_request_start(request: &ServiceRequest) -> Span {
+        let client_request_id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| id.len() <= MAX_CLIENT_REQUEST_ID_LEN)
+            .unwrap_or_default()
+            .to_string();
+
+        root_span!(request, client_request_id = %client_request_id)
+    }
+
+    fn on_request_end<B: MessageBody>(span: Span, outcome: &Result<ServiceResponse<B>, Error>) {
+        let status = match outcome {
+            Ok(response) => response.status(),
+            Err(error) => error.as_response_error().status_code(),
+        };
+        tracing::info!(parent: &span, status = status.as_u16(), "📨 Request completed");
+
+        DefaultRootSpanBuilder::on_request_end(span, outcome);
+    }
+}
+
+/// Makes the request ID current while the request is handled, so upstream calls carry
+/// it, and returns it in the `X-Request-Id` response header for users to quote.
+pub async fn propagate_request_id(
+    request: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let Some(request_id) = request
+        .extensions()
+        .get::<RequestId>()
+        .map(ToString::to_string)
+    else {
+        return next.call(request).await;
+    };
+
+    let mut response = with_request_id(request_id.clone(), next.call(request)).await?;
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static("x-request-id"), value);
+    }
+
+    Ok(response)
+}
@@ -1,11 +1,14 @@
 -- Campsite Tracker Database Schema
 -- Migration 001: Initial tables
+--
+-- Statements are idempotent so databases provisioned before migrations were
+-- embedded in the server can be adopted without errors.
 
 -- Enable UUID extension
 CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
 
 -- Users table
-CREATE TABLE users (
+CREATE TABLE IF NOT EXISTS users (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
     email VARCHAR(255) UNIQUE NOT NULL,
     name VARCHAR(255) NOT NULL,
@@ -22,7 +25,7 @@ CREATE TABLE users (
 );
 
 -- Campgrounds table (RIDB facilities)
-CREATE TABLE campgrounds (
+CREATE TABLE IF NOT EXISTS campgrounds (
     id VARCHAR(50) PRIMARY KEY, -- RIDB facility ID
     name VARCHAR(255) NOT NULL,
     parent_recarea_id VARCHAR(50),
@@ -38,7 +41,7 @@ CREATE TABLE campgrounds (
 );
 
 -- Campsites table (individual sites within campgrounds)
-CREATE TABLE campsites (
+CREATE TABLE IF NOT EXISTS campsites (
     id VARCHAR(100) PRIMARY KEY, -- RIDB campsite ID
     campground_id VARCHAR(50) NOT NULL REFERENCES campgrounds(id),
     site_number VARCHAR(20),
@@ -52,7 +55,7 @@ CREATE TABLE campsites (
 );
 
 -- User scans table (what users want to monitor)
-CREATE TABLE user_scans (
+CREATE TABLE IF NOT EXISTS user_scans (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
     user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
     campground_id VARCHAR(50) NOT NULL REFERENCES campgrounds(id),
@@ -70,7 +73,7 @@ CREATE TABLE user_scans (
 );
 
 -- Campground availability cache
-CREATE TABLE campground_availability (
+CREATE TABLE IF NOT EXISTS campground_availability (
     campground_id VARCHAR(50) NOT NULL REFERENCES campgrounds(id),
     date DATE NOT NULL,
     available_sites INTEGER DEFAULT 0,
@@ -84,7 +87,7 @@ CREATE TABLE campground_availability (
 );
 
 -- Polling jobs for efficient monitoring
-CREATE TABLE polling_jobs (
+CREATE TABLE IF NOT EXISTS polling_jobs (
     campground_id VARCHAR(50) PRIMARY KEY REFERENCES campgrounds(id),
     active_scan_count INTEGER DEFAULT 0,
     last_polled TIMESTAMP WITH TIME ZONE,
@@ -97,7 +100,7 @@ CREATE TABLE polling_jobs (
 );
 
 -- Notifications audit trail
-CREATE TABLE notifications (
+CREATE TABLE IF NOT EXISTS notifications (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
     user_id UUID NOT NULL REFERENCES users(id),
     user_scan_id UUID REFERENCES user_scans(id) ON DELETE SET NULL,
@@ -113,7 +116,7 @@ CREATE TABLE notifications (
 );
 
 -- User sessions for JWT refresh tokens
-CREATE TABLE user_sessions (
+CREATE TABLE IF NOT EXISTS user_sessions (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
     user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
     refresh_token_hash VARCHAR(255) NOT NULL,
@@ -126,16 +129,16 @@ CREATE TABLE user_sessions (
 );
 
 -- Indexes for performance
-CREATE INDEX idx_users_email ON users(email);
-CREATE INDEX idx_users_role ON users(role);
-CREATE INDEX idx_user_scans_user_id ON user_scans(user_id);
-CREATE INDEX idx_user_scans_campground_dates ON user_scans(campground_id, check_in_date, check_out_date);
-CREATE INDEX idx_user_scans_active ON user_scans(status) WHERE status = 'active';
-CREATE INDEX idx_polling_jobs_next_poll ON polling_jobs(next_poll_at);
-CREATE INDEX idx_campground_availability_campground_date ON campground_availability(campground_id, date);
-CREATE INDEX idx_notifications_user_scan ON notifications(user_scan_id);
-CREATE INDEX idx_notifications_status ON notifications(status);
-CREATE INDEX idx_campsites_campground ON campsites(campground_id);
+CREATE INDEX IF NOT EXISTS idx_users_email ON users(email);
+CREATE INDEX IF NOT EXISTS idx_users_role ON users(role);
+CREATE INDEX IF NOT EXISTS idx_user_scans_user_id ON user_scans(user_id);
+CREATE INDEX IF NOT EXISTS idx_user_scans_campground_dates ON user_scans(campground_id, check_in_date, check_out_date);
+CREATE INDEX IF NOT EXISTS idx_user_scans_active ON user_scans(status) WHERE status = 'active';
+CREATE INDEX IF NOT EXISTS idx_polling_jobs_next_poll ON polling_jobs(next_poll_at);
+CREATE INDEX IF NOT EXISTS idx_campground_availability_campground_date ON campground_availability(campground_id, date);
+CREATE INDEX IF NOT EXISTS idx_notifications_user_scan ON notifications(user_scan_id);
+CREATE INDEX IF NOT EXISTS idx_notifications_status ON notifications(status);
+CREATE INDEX IF NOT EXISTS idx_campsites_campground ON campsites(campground_id);
 
 -- Trigger function to update polling job counts
 CREATE OR REPLACE FUNCTION update_polling_job_count()
@@ -160,6 +163,7 @@ END;
 $$ LANGUAGE plpgsql;
 
 -- Trigger to automatically manage polling jobs
+DROP TRIGGER IF EXISTS trigger_update_polling_count ON user_scans;
 CREATE TRIGGER trigger_update_polling_count
     AFTER INSERT OR DELETE ON user_scans
     FOR EACH ROW EXECUTE FUNCTION update_polling_job_count();
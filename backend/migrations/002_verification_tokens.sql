@@ -0,0 +1,19 @@
+-- Campsite Tracker Database Schema
+-- Migration 002: Persistent verification tokens
+
+-- Pending email/SMS verifications (one outstanding token per user and channel)
+CREATE TABLE IF NOT EXISTS verification_tokens (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel VARCHAR(20) NOT NULL, -- email, sms
+    target VARCHAR(255) NOT NULL, -- email address or E.164 phone being verified
+    token VARCHAR(255) NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+
+    CONSTRAINT unique_user_channel UNIQUE (user_id, channel)
+);
+
+CREATE INDEX IF NOT EXISTS idx_verification_tokens_token ON verification_tokens(channel, token);
+CREATE INDEX IF NOT EXISTS idx_verification_tokens_expires ON verification_tokens(expires_at);
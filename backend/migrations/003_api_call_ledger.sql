@@ -0,0 +1,15 @@
+-- Campsite Tracker Database Schema
+-- Migration 003: Upstream API call ledger
+
+-- One row per call made to recreation.gov / RIDB
+CREATE TABLE IF NOT EXISTS api_call_ledger (
+    id BIGSERIAL PRIMARY KEY,
+    endpoint VARCHAR(100) NOT NULL, -- ridb_recareas, ridb_recarea_facilities, ...
+    campground_id VARCHAR(50), -- facility the call was made on behalf of, if any
+    status_code INTEGER, -- HTTP status, NULL when the request never got a response
+    duration_ms INTEGER NOT NULL,
+    called_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_call_ledger_called_at ON api_call_ledger(called_at);
+CREATE INDEX IF NOT EXISTS idx_api_call_ledger_campground ON api_call_ledger(campground_id, called_at);
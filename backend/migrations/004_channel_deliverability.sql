@@ -0,0 +1,7 @@
+-- Campsite Tracker Database Schema
+-- Migration 004: Notification channel deliverability
+
+-- Latest pre-flight check results per channel, e.g.
+-- {"email": {"deliverable": true, "reason": null}, "sms": {"deliverable": false, "reason": "..."}}
+ALTER TABLE users ADD COLUMN IF NOT EXISTS channel_deliverability JSONB;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS deliverability_checked_at TIMESTAMP WITH TIME ZONE;
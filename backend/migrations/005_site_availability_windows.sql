@@ -0,0 +1,20 @@
+-- Campsite Tracker Database Schema
+-- Migration 005: Site availability windows
+
+-- One row per stretch of time a campsite stayed available for a night: opened by the
+-- poll that first saw it available, closed by the first poll that no longer did
+CREATE TABLE IF NOT EXISTS site_availability_windows (
+    id BIGSERIAL PRIMARY KEY,
+    campground_id VARCHAR(50) NOT NULL REFERENCES campgrounds(id),
+    campsite_id VARCHAR(100) NOT NULL,
+    date DATE NOT NULL,
+    opened_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    closed_at TIMESTAMP WITH TIME ZONE -- NULL while the site is still available
+);
+
+-- At most one open window per site and night
+CREATE UNIQUE INDEX IF NOT EXISTS idx_site_availability_windows_open
+    ON site_availability_windows(campground_id, campsite_id, date)
+    WHERE closed_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_site_availability_windows_closed
+    ON site_availability_windows(campground_id, closed_at);
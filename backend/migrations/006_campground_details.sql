@@ -0,0 +1,6 @@
+-- Campsite Tracker Database Schema
+-- Migration 006: Cached campground details
+
+-- Facility, campsites and photos fetched from RIDB for the campground detail page
+ALTER TABLE campgrounds ADD COLUMN IF NOT EXISTS details JSONB;
+ALTER TABLE campgrounds ADD COLUMN IF NOT EXISTS details_updated_at TIMESTAMP WITH TIME ZONE;
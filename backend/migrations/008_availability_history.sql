@@ -0,0 +1,19 @@
+-- Campsite Tracker Database Schema
+-- Migration 008: Availability history
+
+-- One row per poll of a campground night: every site available at that moment, and
+-- which sites opened up or were taken since the previous poll
+CREATE TABLE IF NOT EXISTS availability_history (
+    id BIGSERIAL PRIMARY KEY,
+    campground_id VARCHAR(50) NOT NULL REFERENCES campgrounds(id),
+    date DATE NOT NULL,
+    observed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    available_site_ids TEXT[] NOT NULL DEFAULT '{}',
+    opened_site_ids TEXT[] NOT NULL DEFAULT '{}',
+    closed_site_ids TEXT[] NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX IF NOT EXISTS idx_availability_history_campground
+    ON availability_history(campground_id, observed_at);
+CREATE INDEX IF NOT EXISTS idx_availability_history_night
+    ON availability_history(campground_id, date, observed_at);
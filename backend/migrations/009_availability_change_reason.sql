@@ -0,0 +1,6 @@
+-- Campsite Tracker Database Schema
+-- Migration 009: Availability change reasons
+
+-- Likely cause of the sites opening up in a snapshot: 'cancellation' or
+-- 'inventory_release'; NULL when nothing opened up or the cause can't be told
+ALTER TABLE availability_history ADD COLUMN IF NOT EXISTS change_reason VARCHAR(20);
@@ -0,0 +1,25 @@
+-- Campsite Tracker Database Schema
+-- Migration 010: Permit scans (e.g. Half Dome, Mt. Whitney)
+
+-- Scans watching for released permit slots rather than campsites
+CREATE TABLE IF NOT EXISTS permit_scans (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    permit_id VARCHAR(50) NOT NULL, -- recreation.gov permit ID
+    permit_name VARCHAR(255) NOT NULL,
+    division_id VARCHAR(50), -- trailhead or zone; NULL watches every division
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    group_size INTEGER NOT NULL DEFAULT 1,
+    status VARCHAR(20) DEFAULT 'active', -- active, paused, completed, cancelled
+    notification_sent BOOLEAN DEFAULT FALSE,
+    last_checked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+
+    CONSTRAINT valid_permit_date_range CHECK (end_date >= start_date),
+    CONSTRAINT valid_group_size CHECK (group_size > 0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_permit_scans_user ON permit_scans(user_id);
+CREATE INDEX IF NOT EXISTS idx_permit_scans_active ON permit_scans(permit_id) WHERE status = 'active';
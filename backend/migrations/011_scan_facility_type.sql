@@ -0,0 +1,6 @@
+-- Campsite Tracker Database Schema
+-- Migration 011: Non-campsite inventory (cabins, fire lookouts, group sites)
+
+-- Kind of inventory a scan watches: campsite, cabin, lookout, group_site
+ALTER TABLE user_scans
+    ADD COLUMN IF NOT EXISTS facility_type VARCHAR(20) NOT NULL DEFAULT 'campsite';
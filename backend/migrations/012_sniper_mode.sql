@@ -0,0 +1,6 @@
+-- Campsite Tracker Database Schema
+-- Migration 012: Sniper mode for same-week trips
+
+-- Campgrounds polled at sniper frequency until this time (the check-in date has passed);
+-- frequency tuning leaves them alone meanwhile
+ALTER TABLE polling_jobs ADD COLUMN IF NOT EXISTS sniper_until TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,17 @@
+-- Campsite Tracker Database Schema
+-- Migration 013: Quarantine of invalid upstream availability payloads
+
+-- Payloads that failed validation are kept here instead of the availability cache, with
+-- the issues found and a sample of the offending entries for debugging
+CREATE TABLE IF NOT EXISTS quarantined_payloads (
+    id BIGSERIAL PRIMARY KEY,
+    endpoint VARCHAR(100) NOT NULL, -- recgov_availability_month, recgov_permit_month
+    entity_id VARCHAR(50) NOT NULL, -- campground or permit ID
+    month DATE NOT NULL,
+    issue_count INTEGER NOT NULL,
+    issues JSONB NOT NULL, -- first issues found, see PayloadIssue
+    sample JSONB NOT NULL, -- offending entries as received
+    quarantined_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_quarantined_payloads_entity ON quarantined_payloads(entity_id, quarantined_at);
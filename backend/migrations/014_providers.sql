@@ -0,0 +1,13 @@
+-- Campsite Tracker Database Schema
+-- Migration 014: Reservation providers beyond recreation.gov
+
+-- Reservation system a campground is booked through: recreation_gov, reserve_california.
+-- ReserveCalifornia campground IDs are prefixed with "rc-" so they can't collide with
+-- RIDB facility IDs.
+ALTER TABLE campgrounds
+    ADD COLUMN IF NOT EXISTS provider VARCHAR(30) NOT NULL DEFAULT 'recreation_gov';
+
+ALTER TABLE user_scans
+    ADD COLUMN IF NOT EXISTS provider VARCHAR(30) NOT NULL DEFAULT 'recreation_gov';
+
+CREATE INDEX IF NOT EXISTS idx_user_scans_provider ON user_scans(provider, status);
@@ -0,0 +1,22 @@
+-- Campsite Tracker Database Schema
+-- Migration 015: Scan expiry and expiring-soon warnings
+
+-- Whether the owner was emailed that the scan is about to expire
+ALTER TABLE user_scans
+    ADD COLUMN IF NOT EXISTS expiry_warning_sent BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Existing scans end once check-in passes or 180 days after creation, but no sooner than
+-- a week from now so their owners can be warned and extend them. Scans whose check-in
+-- already passed expire now.
+UPDATE user_scans
+SET expires_at = GREATEST(
+    LEAST(
+        (check_in_date + 1)::TIMESTAMP AT TIME ZONE 'UTC',
+        GREATEST(created_at + INTERVAL '180 days', NOW() + INTERVAL '7 days')
+    ),
+    NOW()
+)
+WHERE expires_at IS NULL AND status IN ('active', 'paused');
+
+CREATE INDEX IF NOT EXISTS idx_user_scans_expires_at ON user_scans(expires_at)
+    WHERE status IN ('active', 'paused');
@@ -0,0 +1,20 @@
+-- Campsite Tracker Database Schema
+-- Migration 016: Plan tiers and scan quotas
+
+CREATE TABLE IF NOT EXISTS plans (
+    id VARCHAR(30) PRIMARY KEY, -- free, plus, pro
+    name VARCHAR(100) NOT NULL,
+    max_active_scans INTEGER, -- NULL means unlimited
+    sniper_mode BOOLEAN NOT NULL DEFAULT FALSE, -- same-week trips polled at sniper frequency
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CONSTRAINT valid_max_active_scans CHECK (max_active_scans IS NULL OR max_active_scans >= 0)
+);
+
+INSERT INTO plans (id, name, max_active_scans, sniper_mode) VALUES
+    ('free', 'Free', 3, FALSE),
+    ('plus', 'Plus', 15, TRUE),
+    ('pro', 'Pro', NULL, TRUE)
+ON CONFLICT (id) DO NOTHING;
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS plan VARCHAR(30) NOT NULL DEFAULT 'free' REFERENCES plans(id);
@@ -0,0 +1,7 @@
+-- Campsite Tracker Database Schema
+-- Migration 017: Flexible scan dates
+
+-- Days the stay may shift earlier or later while keeping its length
+ALTER TABLE user_scans
+    ADD COLUMN IF NOT EXISTS flexibility_days INTEGER NOT NULL DEFAULT 0
+        CONSTRAINT valid_flexibility_days CHECK (flexibility_days BETWEEN 0 AND 7);
@@ -0,0 +1,14 @@
+-- Campsite Tracker Database Schema
+-- Migration 018: Scan activity timeline
+
+-- What happened to each scan (created, polled, sites seen, alerted, errors, status
+-- changes), so users can see the scanner working
+CREATE TABLE IF NOT EXISTS scan_events (
+    id BIGSERIAL PRIMARY KEY,
+    user_scan_id UUID NOT NULL REFERENCES user_scans(id) ON DELETE CASCADE,
+    event_type VARCHAR(30) NOT NULL, -- see ScanEventKind
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_scan_events_scan ON scan_events(user_scan_id, created_at);
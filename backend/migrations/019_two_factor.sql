@@ -0,0 +1,18 @@
+-- Campsite Tracker Database Schema
+-- Migration 019: Two-factor authentication
+
+-- Authenticator (TOTP) enrollment of each user. The secret is encrypted with
+-- AES-256-GCM; recovery codes are stored as SHA-256 hashes and removed once used.
+CREATE TABLE IF NOT EXISTS user_two_factor (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    secret_ciphertext BYTEA NOT NULL,
+    secret_nonce BYTEA NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    recovery_code_hashes TEXT[] NOT NULL DEFAULT '{}',
+    -- Last 30-second time step a code was accepted for, so codes can't be replayed
+    last_used_step BIGINT,
+    failed_attempts INTEGER NOT NULL DEFAULT 0,
+    locked_until TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    enabled_at TIMESTAMP WITH TIME ZONE
+);
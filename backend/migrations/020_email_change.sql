@@ -0,0 +1,10 @@
+-- Campsite Tracker Database Schema
+-- Migration 020: Email change confirmation
+
+-- A new email address only replaces the current one once it's confirmed through the
+-- link sent to it; until then the current address stays active.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS pending_email VARCHAR(255);
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_change_token VARCHAR(64);
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_change_expires_at TIMESTAMP WITH TIME ZONE;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email_change_token ON users(email_change_token);
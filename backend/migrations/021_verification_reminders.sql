@@ -0,0 +1,16 @@
+-- Campsite Tracker Database Schema
+-- Migration 021: Verification send throttling and reminders
+
+-- Verification emails/SMS sent at a user's request, to cap how often they can be resent
+CREATE TABLE IF NOT EXISTS verification_sends (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    channel VARCHAR(20) NOT NULL, -- email, sms
+    sent_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_verification_sends_user ON verification_sends(user_id, channel, sent_at);
+
+-- Reminders sent to users who haven't finished verifying their contact details
+ALTER TABLE users ADD COLUMN IF NOT EXISTS verification_reminders_sent INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS last_verification_reminder_at TIMESTAMP WITH TIME ZONE;
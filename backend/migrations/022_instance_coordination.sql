@@ -0,0 +1,14 @@
+-- Campsite Tracker Database Schema
+-- Migration 022: Coordination between several server instances
+
+-- Instance polling a campground and when its claim lapses, so replicas never poll the
+-- same campground twice and a crashed instance's claims are picked up again
+ALTER TABLE polling_jobs ADD COLUMN IF NOT EXISTS claimed_by VARCHAR(100);
+ALTER TABLE polling_jobs ADD COLUMN IF NOT EXISTS claimed_until TIMESTAMP WITH TIME ZONE;
+
+-- When each background worker last started a round on any instance, so a round runs on
+-- only one of them
+CREATE TABLE IF NOT EXISTS worker_rounds (
+    worker VARCHAR(50) PRIMARY KEY,
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
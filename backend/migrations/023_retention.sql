@@ -0,0 +1,8 @@
+-- Campsite Tracker Database Schema
+-- Migration 023: Indexes for the retention worker
+
+-- The retention worker deletes rows older than a window from these tables
+CREATE INDEX IF NOT EXISTS idx_campground_availability_date ON campground_availability(date);
+CREATE INDEX IF NOT EXISTS idx_availability_history_observed_at ON availability_history(observed_at);
+CREATE INDEX IF NOT EXISTS idx_notifications_created_at ON notifications(created_at);
+CREATE INDEX IF NOT EXISTS idx_scan_events_created_at ON scan_events(created_at);
@@ -0,0 +1,11 @@
+-- Campsite Tracker Database Schema
+-- Migration 024: Per-scan unsubscribes
+
+-- Recipients who unsubscribed from a scan's alerts through the link in an email. The scan
+-- keeps running for its other recipients (organization members).
+CREATE TABLE IF NOT EXISTS scan_notification_opt_outs (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    scan_id UUID NOT NULL REFERENCES user_scans(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (user_id, scan_id)
+);
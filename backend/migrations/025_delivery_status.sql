@@ -0,0 +1,7 @@
+-- Campsite Tracker Database Schema
+-- Migration 025: Delivery status from SES/SNS feedback
+
+-- Delivery webhooks look notifications up by the SES/SNS message ID. Statuses now also
+-- include bounced and complained (email) besides pending, sent, failed, and delivered.
+CREATE INDEX IF NOT EXISTS idx_notifications_external_id ON notifications(external_id)
+    WHERE external_id IS NOT NULL;
@@ -0,0 +1,6 @@
+-- Campsite Tracker Database Schema
+-- Migration 026: Temporarily disabled polling jobs
+
+-- Admins can stop polling a campground until a given time, e.g. while its upstream data
+-- is broken; the job is skipped until then and resumes on its own
+ALTER TABLE polling_jobs ADD COLUMN IF NOT EXISTS disabled_until TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,11 @@
+-- Campsite Tracker Database Schema
+-- Migration 027: Scan system status kept by the watchdog
+
+-- Single row holding the scan watchdog's latest verdict, shared by every server instance
+CREATE TABLE IF NOT EXISTS scan_system_status (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    status VARCHAR(10) NOT NULL DEFAULT 'ok', -- ok, down
+    problems JSONB NOT NULL DEFAULT '[]'::JSONB,
+    checked_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    changed_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
@@ -0,0 +1,13 @@
+-- Campsite Tracker Database Schema
+-- Migration 028: Nightly prices and max-price filters
+
+-- Price of each campsite that night in dollars, keyed by campsite ID, for sites whose
+-- price the provider reported
+ALTER TABLE campground_availability
+    ADD COLUMN IF NOT EXISTS site_prices JSONB;
+
+-- Highest nightly price in whole dollars a scan alerts about; sites without a reported
+-- price still match
+ALTER TABLE user_scans
+    ADD COLUMN IF NOT EXISTS max_price INTEGER
+        CONSTRAINT valid_max_price CHECK (max_price IS NULL OR max_price > 0);
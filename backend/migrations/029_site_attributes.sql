@@ -0,0 +1,16 @@
+-- Campsite Tracker Database Schema
+-- Migration 029: Campsite attributes and site filters
+
+-- Attributes read from RIDB's /campsites/{id}/attributes; NULL when RIDB doesn't say.
+-- The raw attributes go in `amenities`.
+ALTER TABLE campsites ADD COLUMN IF NOT EXISTS loop_name VARCHAR(100);
+ALTER TABLE campsites ADD COLUMN IF NOT EXISTS electric_hookup BOOLEAN;
+ALTER TABLE campsites ADD COLUMN IF NOT EXISTS water_hookup BOOLEAN;
+ALTER TABLE campsites ADD COLUMN IF NOT EXISTS sewer_hookup BOOLEAN;
+ALTER TABLE campsites ADD COLUMN IF NOT EXISTS max_vehicle_length INTEGER;
+ALTER TABLE campsites ADD COLUMN IF NOT EXISTS pets_allowed BOOLEAN;
+ALTER TABLE campsites ADD COLUMN IF NOT EXISTS attributes_updated_at TIMESTAMP WITH TIME ZONE;
+
+-- Site features a scan requires (e.g. {"electric_hookup": true, "vehicle_length": 30});
+-- empty when any site will do
+ALTER TABLE user_scans ADD COLUMN IF NOT EXISTS site_filters JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,6 @@
+-- Campsite Tracker Database Schema
+-- Migration 030: Quick-book links
+
+-- Whether alerts for the scan link straight to each open site's booking page with the
+-- stay's dates filled in, instead of only to the campground
+ALTER TABLE user_scans ADD COLUMN IF NOT EXISTS quick_book BOOLEAN NOT NULL DEFAULT FALSE;
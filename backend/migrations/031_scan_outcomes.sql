@@ -0,0 +1,11 @@
+-- Campsite Tracker Database Schema
+-- Migration 031: Scan outcomes
+
+-- What came of a scan, as reported by its owner: booked, missed (sites were gone by the
+-- time they tried), or gave_up
+ALTER TABLE user_scans ADD COLUMN IF NOT EXISTS outcome VARCHAR(20)
+    CONSTRAINT valid_outcome CHECK (outcome IN ('booked', 'missed', 'gave_up'));
+ALTER TABLE user_scans ADD COLUMN IF NOT EXISTS outcome_at TIMESTAMP WITH TIME ZONE;
+
+-- Alert-to-booking reports look alerts up by time
+CREATE INDEX IF NOT EXISTS idx_scan_events_type ON scan_events(event_type, created_at);
@@ -0,0 +1,11 @@
+-- Campsite Tracker Database Schema
+-- Migration 032: Scarcity-based polling priority
+
+-- Hourly score of how urgently a campground should be polled, from its sell-outs,
+-- active scans and soonest check-in. Due jobs are claimed highest score first;
+-- `priority` now only lifts campgrounds in sniper mode above everything else.
+ALTER TABLE polling_jobs ADD COLUMN IF NOT EXISTS priority_score DOUBLE PRECISION NOT NULL DEFAULT 0;
+ALTER TABLE polling_jobs ADD COLUMN IF NOT EXISTS priority_scored_at TIMESTAMP WITH TIME ZONE;
+
+-- Tuning no longer sets `priority`; jobs outside sniper mode go back to the base priority
+UPDATE polling_jobs SET priority = 1 WHERE sniper_until IS NULL AND priority IS DISTINCT FROM 1;
@@ -0,0 +1,16 @@
+-- Campsite Tracker Database Schema
+-- Migration 033: Shared scans
+
+-- Public read-only links to a scan (/s/{slug}), created by the scan's owner. Deleting
+-- the scan or unsharing it removes the link.
+CREATE TABLE IF NOT EXISTS scan_shares (
+    slug VARCHAR(16) PRIMARY KEY,
+    scan_id UUID NOT NULL UNIQUE REFERENCES user_scans(id) ON DELETE CASCADE,
+    created_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    clone_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Scan a clone was made from through a share link, for referral counts
+ALTER TABLE user_scans ADD COLUMN IF NOT EXISTS cloned_from UUID
+    REFERENCES user_scans(id) ON DELETE SET NULL;
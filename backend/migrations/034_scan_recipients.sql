@@ -0,0 +1,21 @@
+-- Campsite Tracker Database Schema
+-- Migration 034: Extra scan recipients
+
+-- Friends a scan's owner adds to its alerts by email or phone. Nobody is alerted until
+-- they confirm through the link sent to them; declining (or the unsubscribe link in an
+-- alert) keeps the row so the owner can't invite them to that scan again.
+CREATE TABLE IF NOT EXISTS scan_recipients (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    scan_id UUID NOT NULL REFERENCES user_scans(id) ON DELETE CASCADE,
+    channel VARCHAR(10) NOT NULL CONSTRAINT valid_recipient_channel CHECK (channel IN ('email', 'sms')),
+    address VARCHAR(255) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CONSTRAINT valid_recipient_status CHECK (status IN ('pending', 'confirmed', 'declined')),
+    token VARCHAR(64) UNIQUE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    responded_at TIMESTAMP WITH TIME ZONE,
+    UNIQUE (scan_id, channel, address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_scan_recipients_confirmed
+    ON scan_recipients(scan_id) WHERE status = 'confirmed';
@@ -0,0 +1,7 @@
+-- Campsite Tracker Database Schema
+-- Migration 035: Language preference of users
+
+-- Language of the user's emails, texts and API error messages (`en` or `es`)
+ALTER TABLE users ADD COLUMN IF NOT EXISTS locale VARCHAR(10) NOT NULL DEFAULT 'en';
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_locale_check;
+ALTER TABLE users ADD CONSTRAINT users_locale_check CHECK (locale IN ('en', 'es'));
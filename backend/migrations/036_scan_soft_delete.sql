@@ -0,0 +1,9 @@
+-- Campsite Tracker Database Schema
+-- Migration 036: Soft-deleted scans
+
+-- When the owner deleted the scan. Deleted scans keep their history, are hidden from
+-- listings and polling, and can be restored for 30 days.
+ALTER TABLE user_scans ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_user_scans_deleted_at ON user_scans(deleted_at)
+    WHERE deleted_at IS NOT NULL;
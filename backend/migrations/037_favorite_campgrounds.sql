@@ -0,0 +1,12 @@
+-- Campsite Tracker Database Schema
+-- Migration 037: Favorite campgrounds
+
+-- Campgrounds users bookmarked from search, to keep an eye on and scan later
+CREATE TABLE IF NOT EXISTS favorite_campgrounds (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    campground_id VARCHAR(50) NOT NULL REFERENCES campgrounds(id),
+    note TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+
+    PRIMARY KEY (user_id, campground_id)
+);
@@ -0,0 +1,24 @@
+-- Campsite Tracker Database Schema
+-- Migration 038: Trips grouping scans
+
+-- A trip a user plans, whose scans watch alternative campgrounds for the same getaway
+CREATE TABLE IF NOT EXISTS trips (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    party_size INTEGER NOT NULL DEFAULT 1,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    -- Pause the trip's other active scans once one of them is booked
+    auto_pause_on_booking BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+
+    CONSTRAINT valid_trip_dates CHECK (end_date > start_date),
+    CONSTRAINT valid_party_size CHECK (party_size > 0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_trips_user ON trips(user_id);
+
+ALTER TABLE user_scans ADD COLUMN IF NOT EXISTS trip_id UUID REFERENCES trips(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_user_scans_trip ON user_scans(trip_id) WHERE trip_id IS NOT NULL;
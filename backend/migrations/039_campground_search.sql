@@ -0,0 +1,24 @@
+-- Campsite Tracker Database Schema
+-- Migration 039: Full-text campground search
+
+-- Trigram matching, for searches with typos ("uper pines")
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Facility description from RIDB (HTML; the parser skips the tags), searched with the name
+ALTER TABLE campgrounds ADD COLUMN IF NOT EXISTS description TEXT;
+
+UPDATE campgrounds
+SET description = details->'facility'->>'FacilityDescription'
+WHERE description IS NULL AND details IS NOT NULL;
+
+-- Names weigh most, then the recreation area and state, then the description
+ALTER TABLE campgrounds ADD COLUMN IF NOT EXISTS search_vector tsvector
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', coalesce(name, '')), 'A') ||
+        setweight(to_tsvector('english', coalesce(parent_recarea_name, '')), 'B') ||
+        setweight(to_tsvector('simple', coalesce(state, '')), 'B') ||
+        setweight(to_tsvector('english', coalesce(description, '')), 'C')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_campgrounds_search_vector ON campgrounds USING GIN(search_vector);
+CREATE INDEX IF NOT EXISTS idx_campgrounds_name_trgm ON campgrounds USING GIN(name gin_trgm_ops);
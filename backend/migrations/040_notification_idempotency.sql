@@ -0,0 +1,13 @@
+-- Campsite Tracker Database Schema
+-- Migration 040: Idempotent availability alerts
+
+-- Availability alerts are claimed in the ledger before they're sent, keyed by a hash of
+-- the scan, recipient, and the sites and nights the alert lists. A retry or a second job
+-- polling the same scan finds the key taken and doesn't send the alert again. Claims of
+-- alerts that failed to send, or that are old enough for the same sites to be news
+-- again, give up their key.
+ALTER TABLE notifications ADD COLUMN IF NOT EXISTS idempotency_key VARCHAR(64);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_notifications_idempotency_key
+    ON notifications(idempotency_key)
+    WHERE idempotency_key IS NOT NULL;
@@ -0,0 +1,10 @@
+-- Campsite Tracker Database Schema
+-- Migration 041: Hourly upstream API budget
+
+-- Upstream calls counted per minute. The last 60 rows make the rolling hourly budget
+-- every instance checks before calling recreation.gov, so restarts and replicas can't
+-- push past it. Rows older than a day are pruned as calls are counted.
+CREATE TABLE IF NOT EXISTS api_budget_windows (
+    window_start TIMESTAMP WITH TIME ZONE PRIMARY KEY,
+    calls INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,12 @@
+-- Campsite Tracker Database Schema
+-- Migration 042: Hourly API budgets per endpoint class
+
+-- Availability polls, detail lookups, and searches each get their own hourly budget on
+-- top of the overall one, so a burst of searches can't starve polling. Calls counted so
+-- far were mostly polls.
+ALTER TABLE api_budget_windows
+    ADD COLUMN IF NOT EXISTS endpoint_class VARCHAR(20) NOT NULL DEFAULT 'availability';
+
+ALTER TABLE api_budget_windows DROP CONSTRAINT IF EXISTS api_budget_windows_pkey;
+ALTER TABLE api_budget_windows ADD CONSTRAINT api_budget_windows_pkey
+    PRIMARY KEY (endpoint_class, window_start);
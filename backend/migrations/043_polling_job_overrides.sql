@@ -0,0 +1,7 @@
+-- Campsite Tracker Database Schema
+-- Migration 043: Admin overrides of polling jobs
+
+-- Admins can pause a polling job until they resume it, and pin its frequency and priority
+-- so the hourly retune and sniper mode leave them alone.
+ALTER TABLE polling_jobs ADD COLUMN IF NOT EXISTS paused BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE polling_jobs ADD COLUMN IF NOT EXISTS pinned BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,10 @@
+-- Campsite Tracker Database Schema
+-- Migration 044: Scan health
+
+-- When a campground's polls started failing, cleared by the next successful poll; a
+-- streak of a day or more marks its scans as blocked (e.g. closed for the season).
+ALTER TABLE polling_jobs ADD COLUMN IF NOT EXISTS failing_since TIMESTAMP WITH TIME ZONE;
+
+-- When the scan's owner was last told its campground can't be checked, so each failing
+-- streak is emailed about once.
+ALTER TABLE user_scans ADD COLUMN IF NOT EXISTS blocked_notice_sent_at TIMESTAMP WITH TIME ZONE;
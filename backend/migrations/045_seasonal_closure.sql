@@ -0,0 +1,10 @@
+-- Campsite Tracker Database Schema
+-- Migration 045: Seasonal closure
+
+-- Polls in a row that found no reservable site for the night (every site closed or not
+-- reservable), and since when. A scan whose nights have all stayed closed for a few
+-- polls shows its campground as likely closed for the season, and a campground closed
+-- on every night polled is polled about daily until it reopens.
+ALTER TABLE campground_availability
+    ADD COLUMN IF NOT EXISTS closed_polls INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS closed_since TIMESTAMP WITH TIME ZONE;
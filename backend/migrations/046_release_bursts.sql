@@ -0,0 +1,14 @@
+-- Campsite Tracker Database Schema
+-- Migration 046: Release bursts
+
+-- When a campground releases inventory each day (e.g. 07:00 America/Los_Angeles) and
+-- how many months ahead the night released then is; campgrounds without a release time
+-- aren't burst-polled.
+ALTER TABLE polling_jobs
+    ADD COLUMN IF NOT EXISTS release_time TIME,
+    ADD COLUMN IF NOT EXISTS release_time_zone TEXT,
+    ADD COLUMN IF NOT EXISTS release_window_months INTEGER;
+
+-- End of the release burst the campground is in, during which it's polled every few
+-- seconds instead of at its poll frequency.
+ALTER TABLE polling_jobs ADD COLUMN IF NOT EXISTS burst_until TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,9 @@
+-- Campsite Tracker Database Schema
+-- Migration 047: Poll failure kinds
+
+-- Kind of the latest failed poll (timeout, http, decode, blocked, config) and the HTTP
+-- status recreation.gov answered with, if any; cleared by the next successful poll.
+-- The kind decides how soon the campground is polled again.
+ALTER TABLE polling_jobs
+    ADD COLUMN IF NOT EXISTS last_failure_kind TEXT,
+    ADD COLUMN IF NOT EXISTS last_failure_status INTEGER;
@@ -0,0 +1,7 @@
+-- Campsite Tracker Database Schema
+-- Migration 048: Calendar feed versions
+
+-- Version of each user's calendar feed link. Feed tokens carry the version they were
+-- issued for, so resetting the link (bumping the version) revokes every older one.
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS calendar_feed_version INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,15 @@
+-- Campsite Tracker Database Schema
+-- Migration 049: Scan invitation throttling
+
+-- Invitations sent to a scan's extra recipients, to cap how often the owner can resend
+-- one to the same address by removing and re-adding it
+CREATE TABLE IF NOT EXISTS scan_invitation_sends (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    scan_id UUID NOT NULL REFERENCES user_scans(id) ON DELETE CASCADE,
+    channel VARCHAR(10) NOT NULL, -- email, sms
+    address VARCHAR(255) NOT NULL,
+    sent_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_scan_invitation_sends_address
+    ON scan_invitation_sends(scan_id, channel, address, sent_at);
@@ -0,0 +1,43 @@
+-- Campsite Tracker Database Schema
+-- Migration 050: Polling jobs of permits
+
+-- One polling job per permit watched by a permit scan, claimed by one server instance
+-- at a time like campground polling jobs, so a permit is checked once per poll however
+-- many scans watch it
+CREATE TABLE IF NOT EXISTS permit_polling_jobs (
+    permit_id VARCHAR(50) PRIMARY KEY,
+    last_polled TIMESTAMP WITH TIME ZONE,
+    next_poll_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    poll_frequency_minutes INTEGER NOT NULL DEFAULT 15,
+    consecutive_errors INTEGER NOT NULL DEFAULT 0,
+    claimed_by VARCHAR(100),
+    claimed_until TIMESTAMP WITH TIME ZONE,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_permit_polling_jobs_next_poll
+    ON permit_polling_jobs(next_poll_at);
+
+-- Jobs of the permits already watched
+INSERT INTO permit_polling_jobs (permit_id)
+SELECT DISTINCT permit_id FROM permit_scans WHERE status = 'active'
+ON CONFLICT (permit_id) DO NOTHING;
+
+-- New permit scans get their permit polled right away
+CREATE OR REPLACE FUNCTION ensure_permit_polling_job()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO permit_polling_jobs (permit_id, next_poll_at)
+    VALUES (NEW.permit_id, NOW())
+    ON CONFLICT (permit_id)
+    DO UPDATE SET
+        next_poll_at = LEAST(permit_polling_jobs.next_poll_at, NOW()),
+        updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_ensure_permit_polling_job ON permit_scans;
+CREATE TRIGGER trigger_ensure_permit_polling_job
+    AFTER INSERT ON permit_scans
+    FOR EACH ROW EXECUTE FUNCTION ensure_permit_polling_job();
@@ -4,6 +4,13 @@ set -e
 # Load environment
 source .env
 
+# Infrastructure set up before links were signed with their own key: generate it once
+# and keep it, so links already sent survive later deploys
+if [ -z "$LINK_SIGNING_KEY" ]; then
+    LINK_SIGNING_KEY=$(openssl rand -base64 32)
+    echo "LINK_SIGNING_KEY=$LINK_SIGNING_KEY" >> .env
+fi
+
 echo "🔨 Building Docker image..."
 
 # Go to project root (where Dockerfile is located)
@@ -49,9 +56,18 @@ ssh -i campsite-key.pem -o StrictHostKeyChecking=no ec2-user@$INSTANCE_IP << EOF
         --name campsite-tracker \
         -p 8080:8080 \
         --restart unless-stopped \
+        --stop-timeout 60 \
         -e DATABASE_URL="postgres://postgres:$DB_PASSWORD@$DB_ENDPOINT/campsite_tracker" \
         -e JWT_SECRET="$(openssl rand -base64 32)" \
+        -e TWO_FACTOR_ENCRYPTION_KEY="$TWO_FACTOR_ENCRYPTION_KEY" \
+        -e LINK_SIGNING_KEY="$LINK_SIGNING_KEY" \
+        -e RECREATION_GOV_API_KEY="$RECREATION_GOV_API_KEY" \
+        -e BASE_URL="${BASE_URL:-http://$INSTANCE_IP:8080}" \
+        -e EMAIL_SENDING_DOMAIN="$EMAIL_SENDING_DOMAIN" \
+        -e FROM_EMAIL="$FROM_EMAIL" \
+        -e REPLY_TO_EMAIL="$REPLY_TO_EMAIL" \
         -e RUST_LOG=info \
+        -e LOG_FORMAT=json \
         campsite-tracker
         
     echo "Container started!"
@@ -61,6 +77,12 @@ ssh -i campsite-key.pem -o StrictHostKeyChecking=no ec2-user@$INSTANCE_IP << EOF
     sleep 5
     if sudo docker ps | grep -q campsite-tracker; then
         echo "✅ Container is running!"
+        if curl -fsS http://localhost:8080/health/ready > /dev/null; then
+            echo "✅ Readiness check passed"
+        else
+            echo "⚠️ Readiness check failed:"
+            curl -sS http://localhost:8080/health/ready || true
+        fi
         echo "📋 Checking logs..."
         sudo docker logs campsite-tracker --tail 10
     else
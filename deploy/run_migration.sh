@@ -1,4 +1,6 @@
 #!/bin/bash
+# Manual schema bootstrap. The server applies backend/migrations on startup,
+# so this is only needed to provision a database before the first deploy.
 set -e
 
 echo "🗄️ Running database migrations..."
@@ -61,6 +61,15 @@ echo "Security Group ID: $SG_ID"
 # Save for later scripts
 echo "INSTANCE_ID=$INSTANCE_ID" > .env
 echo "SG_ID=$SG_ID" >> .env
+# Must stay the same across deploys, or enrolled two-factor authenticators stop working
+echo "TWO_FACTOR_ENCRYPTION_KEY=$(openssl rand -base64 32)" >> .env
+# Must stay the same across deploys, or unsubscribe and calendar feed links already sent
+# stop working
+echo "LINK_SIGNING_KEY=$(openssl rand -base64 32)" >> .env
+# Public URL (defaults to the instance IP) and SES-verified domain of outgoing email;
+# fill these in before deploying
+echo "BASE_URL=" >> .env
+echo "EMAIL_SENDING_DOMAIN=" >> .env
 
 echo ""
 echo "⏳ Wait about 3 minutes for instance to boot and install Docker..."
@@ -140,14 +140,67 @@ export const deleteScan = async (scanId: string): Promise<void> => {
   }
 };
 
+// Deleted scans can be restored for 30 days
+export const restoreScan = async (scanId: string): Promise<ScanData> => {
+  const response = await fetch(`/api/scans/${scanId}/restore`, {
+    method: "POST",
+    headers: getAuthHeaders(),
+  });
+
+  if (!response.ok) {
+    const errorData = await response.json();
+    throw new Error(errorData.message || "Failed to restore scan");
+  }
+
+  return response.json();
+};
+
+// Bookmarks a campground in the user's favorites
+export const addFavorite = async (
+  campgroundId: string,
+  campgroundName: string,
+): Promise<void> => {
+  const response = await fetch("/api/user/favorites", {
+    method: "POST",
+    headers: getAuthHeaders(),
+    body: JSON.stringify({
+      campground_id: campgroundId,
+      campground_name: campgroundName,
+    }),
+  });
+
+  if (!response.ok) {
+    const errorData = await response.json();
+    throw new Error(errorData.message || "Failed to save favorite");
+  }
+};
+
+// Date-only strings ("YYYY-MM-DD") are calendar days, not instants. Parsing
+// them with `new Date()` treats them as UTC midnight, which shows the previous
+// day west of Greenwich, so build them from local date components instead.
+const parseCalendarDate = (dateString: string): Date | null => {
+  const match = /^(\d{4})-(\d{2})-(\d{2})$/.exec(dateString);
+  if (!match) return null;
+  return new Date(Number(match[1]), Number(match[2]) - 1, Number(match[3]));
+};
+
+// Helper function to get a local date in YYYY-MM-DD format
+export const toLocalDateString = (date: Date): string => {
+  const month = String(date.getMonth() + 1).padStart(2, "0");
+  const day = String(date.getDate()).padStart(2, "0");
+  return `${date.getFullYear()}-${month}-${day}`;
+};
+
 // Helper function to format dates for API
 export const formatDateForApi = (dateString: string): string => {
-  return new Date(dateString).toISOString().split("T")[0];
+  const date = parseCalendarDate(dateString) ?? new Date(dateString);
+  return toLocalDateString(date);
 };
 
 // Helper function to format dates for display
 export const formatDateForDisplay = (dateString: string): string => {
-  return new Date(dateString).toLocaleDateString();
+  const date = parseCalendarDate(dateString) ?? new Date(dateString);
+  return date.toLocaleDateString();
 };
 
 // Helper function to get status badge color
@@ -1,5 +1,6 @@
 import React, { useRef } from "react";
 import { Calendar } from "lucide-react";
+import { toLocalDateString } from "../CreateScan/ScanUtils";
 import "./DatePicker.css";
 
 interface DatePickerProps {
@@ -18,8 +19,8 @@ const DatePicker: React.FC<DatePickerProps> = ({
   const checkInRef = useRef<HTMLInputElement>(null);
   const checkOutRef = useRef<HTMLInputElement>(null);
 
-  // Get today's date in YYYY-MM-DD format for min attribute
-  const today = new Date().toISOString().split("T")[0];
+  // Get today's local date in YYYY-MM-DD format for min attribute
+  const today = toLocalDateString(new Date());
 
   const handleCheckInClick = () => {
     checkInRef.current?.showPicker?.();
@@ -1,5 +1,6 @@
 import React, { useState, useEffect, useCallback } from "react";
-import { Search, MapPin } from "lucide-react";
+import { Search, MapPin, Star } from "lucide-react";
+import { addFavorite } from "../CreateScan/ScanUtils";
 import "./FacilitySearch.css";
 
 export interface Facility {
@@ -28,7 +29,7 @@ const searchFacilities = async (query: string): Promise<Facility[]> => {
 
   try {
     const response = await fetch(
-      `/api/facilities/search?q=${encodeURIComponent(query)}`,
+      `/api/campgrounds/search?q=${encodeURIComponent(query)}`,
     );
 
     if (!response.ok) {
@@ -39,11 +40,11 @@ const searchFacilities = async (query: string): Promise<Facility[]> => {
     console.log("API Response for query:", query, data); // Debug log
 
     const facilities: Facility[] =
-      data.RECDATA?.map((facility: any) => ({
-        id: facility.FacilityID,
-        name: facility.FacilityName,
-        description: facility.FacilityDescription,
-        state: facility.AddressStateCode,
+      data.campgrounds?.map((campground: any) => ({
+        id: campground.id,
+        name: campground.name,
+        description: campground.recarea_name ?? undefined,
+        state: campground.state ?? undefined,
       })) || [];
 
     console.log("Parsed facilities:", facilities); // Debug log
@@ -79,6 +80,7 @@ const FacilitySearch: React.FC<FacilitySearchProps> = ({
   const [isLoading, setIsLoading] = useState(false);
   const [suggestions, setSuggestions] = useState<Facility[]>([]);
   const [showSuggestions, setShowSuggestions] = useState(false);
+  const [favorites, setFavorites] = useState<Set<number>>(new Set());
 
   // Debounced search function
   const debouncedSearch = useCallback(
@@ -112,6 +114,16 @@ const FacilitySearch: React.FC<FacilitySearchProps> = ({
     setShowSuggestions(false);
   };
 
+  const handleFavoriteClick = async (facility: Facility) => {
+    try {
+      await addFavorite(String(facility.id), facility.name);
+      setFavorites((prev) => new Set(prev).add(facility.id));
+    } catch (error) {
+      console.error("Error saving favorite:", error);
+      alert(error instanceof Error ? error.message : "Failed to save favorite");
+    }
+  };
+
   const handleInputChange = (newValue: string) => {
     onChange(newValue);
     setShowSuggestions(true);
@@ -151,6 +163,18 @@ const FacilitySearch: React.FC<FacilitySearchProps> = ({
                   <span className="suggestion-state">{suggestion.state}</span>
                 )}
               </div>
+              <button
+                type="button"
+                className={`favorite-btn ${favorites.has(suggestion.id) ? "saved" : ""}`}
+                title="Save to favorites"
+                onMouseDown={(e) => {
+                  e.preventDefault();
+                  e.stopPropagation();
+                  handleFavoriteClick(suggestion);
+                }}
+              >
+                <Star size={16} />
+              </button>
             </div>
           ))}
         </div>
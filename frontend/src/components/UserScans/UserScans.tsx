@@ -16,6 +16,7 @@ import {
   getUserScans,
   updateScan,
   deleteScan,
+  restoreScan,
   formatDateForDisplay,
   getStatusColor,
 } from "../CreateScan/ScanUtils";
@@ -30,6 +31,7 @@ const UserScans: React.FC<UserScansProps> = ({ refreshTrigger }) => {
   const [isLoading, setIsLoading] = useState<boolean>(true);
   const [error, setError] = useState<string | null>(null);
   const [updatingScans, setUpdatingScans] = useState<Set<string>>(new Set());
+  const [deletedScan, setDeletedScan] = useState<ScanData | null>(null);
 
   const loadScans = async () => {
     try {
@@ -88,7 +90,8 @@ const UserScans: React.FC<UserScansProps> = ({ refreshTrigger }) => {
       setUpdatingScans((prev) => new Set(prev).add(scanId));
       await deleteScan(scanId);
 
-      // Remove the scan from our local state
+      // Remove the scan from our local state, keeping it around for undo
+      setDeletedScan(scans.find((scan) => scan.id === scanId) ?? null);
       setScans((prevScans) => prevScans.filter((scan) => scan.id !== scanId));
     } catch (err) {
       console.error("Error deleting scan:", err);
@@ -102,6 +105,28 @@ const UserScans: React.FC<UserScansProps> = ({ refreshTrigger }) => {
     }
   };
 
+  const handleUndoDelete = async () => {
+    if (!deletedScan) return;
+
+    try {
+      await restoreScan(deletedScan.id);
+      setDeletedScan(null);
+      await loadScans();
+    } catch (err) {
+      console.error("Error restoring scan:", err);
+      alert(err instanceof Error ? err.message : "Failed to restore scan");
+    }
+  };
+
+  const undoBanner = deletedScan && (
+    <div className="undo-banner">
+      <span>Scan for {deletedScan.campground_name} deleted.</span>
+      <button onClick={handleUndoDelete} className="undo-btn">
+        Undo
+      </button>
+    </div>
+  );
+
   const getStatusIcon = (status: string) => {
     switch (status) {
       case "active":
@@ -195,6 +220,7 @@ const UserScans: React.FC<UserScansProps> = ({ refreshTrigger }) => {
   if (scans.length === 0) {
     return (
       <div className="user-scans empty">
+        {undoBanner}
         <div className="empty-state">
           <Tent size={48} />
           <h3>No scans yet</h3>
@@ -213,6 +239,8 @@ const UserScans: React.FC<UserScansProps> = ({ refreshTrigger }) => {
         <span className="scan-count">{scans.length} total</span>
       </div>
 
+      {undoBanner}
+
       <div className="scans-list">
         {scans.map((scan) => (
           <div key={scan.id} className={`scan-card ${scan.status}`}>
@@ -403,8 +403,11 @@ class YosemiteAvailabilityMonitor:
 
 def main():
     """Main function"""
-    # Your RIDB API key
-    RIDB_API_KEY = "da7bd758-b219-4a80-b885-556101d03afb"
+    # Your RIDB API key, the same one the backend reads
+    RIDB_API_KEY = os.environ.get("RECREATION_GOV_API_KEY")
+    if not RIDB_API_KEY:
+        print("❌ Set RECREATION_GOV_API_KEY to your RIDB API key")
+        return
 
     print("🏕️ YOSEMITE AVAILABILITY CHECK")
     print("Using the recgov library approach")